@@ -82,6 +82,19 @@ struct ChatMessageResponse {
     content: String,
 }
 
+/// Ollama embed request
+#[derive(Debug, Serialize)]
+struct EmbedRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+/// Ollama embed response (one vector per input, in order)
+#[derive(Debug, Deserialize)]
+struct EmbedResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
 /// Ollama model list response
 #[derive(Debug, Deserialize)]
 struct ModelsResponse {
@@ -250,6 +263,45 @@ impl OllamaClient {
         Ok(response.message.content)
     }
 
+    /// Embed texts with the client's model (which must be an embedding model,
+    /// e.g. nomic-embed-text). Returns one vector per input, in order.
+    pub async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let url = format!("{}/api/embed", self.base_url);
+
+        let request = EmbedRequest {
+            model: &self.model,
+            input: texts,
+        };
+
+        let res = self
+            .client
+            .post(&url)
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| self.connection_error(e))?;
+
+        if !res.status().is_success() {
+            return Err(self.api_error("Embedding failed", res).await);
+        }
+
+        let response: EmbedResponse = res.json().await.map_err(|e| Error::DaemonError {
+            message: format!("Failed to parse embed response: {}", e),
+        })?;
+
+        if response.embeddings.len() != texts.len() {
+            return Err(Error::DaemonError {
+                message: format!(
+                    "Ollama returned {} embeddings for {} inputs",
+                    response.embeddings.len(),
+                    texts.len()
+                ),
+            });
+        }
+
+        Ok(response.embeddings)
+    }
+
     /// Rerank search results by relevance to query
     /// Returns indices in order of relevance: [2, 0, 5, 1, ...]
     ///
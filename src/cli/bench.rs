@@ -0,0 +1,212 @@
+//! Bench command implementation
+//!
+//! Measures search latency against the running daemon: every query in a file
+//! is sent `--iterations` times over one connection and the round trips are
+//! summarized as percentiles.
+//!
+//! @module cli/bench
+
+use clap::Args;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use crate::core::config::Config;
+use crate::core::error::{Error, Result};
+use crate::core::project::resolve_project;
+use crate::daemon::client;
+
+/// Arguments for the bench command
+#[derive(Args, Debug)]
+#[command(after_help = "EXAMPLES:
+    greppy bench --queries queries.txt                 10 runs of each query
+    greppy bench --queries queries.txt -i 100          More runs for a stabler p99
+    greppy bench --queries queries.txt --json          Machine-readable summary
+
+Queries are read one per line (blank lines skipped) and sent to the daemon,
+which is started if needed. Latency is the client-side round trip; repeated
+queries may be answered from the daemon's query cache.")]
+pub struct BenchArgs {
+    /// File with one search query per line
+    #[arg(long, value_name = "FILE")]
+    pub queries: PathBuf,
+
+    /// Times each query is run
+    #[arg(long, short = 'i', default_value = "10", value_parser = clap::value_parser!(u32).range(1..))]
+    pub iterations: u32,
+
+    /// Max results per search
+    #[arg(short = 'n', long, default_value = "20")]
+    pub limit: usize,
+
+    /// JSON output
+    #[arg(long)]
+    pub json: bool,
+
+    /// Project path (default: current directory)
+    #[arg(short, long)]
+    pub project: Option<PathBuf>,
+}
+
+/// Latency summary in milliseconds
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct LatencySummary {
+    pub samples: usize,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub mean_ms: f64,
+    pub max_ms: f64,
+}
+
+impl LatencySummary {
+    /// Summarize latency samples (milliseconds, any order; must not be empty)
+    pub fn from_samples(samples: &[f64]) -> Self {
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        Self {
+            samples: sorted.len(),
+            p50_ms: percentile(&sorted, 50.0),
+            p95_ms: percentile(&sorted, 95.0),
+            p99_ms: percentile(&sorted, 99.0),
+            mean_ms: sorted.iter().sum::<f64>() / sorted.len() as f64,
+            max_ms: sorted.last().copied().unwrap_or_default(),
+        }
+    }
+}
+
+/// Latency of one query across all iterations
+#[derive(Debug, Serialize)]
+pub struct QueryBench {
+    pub query: String,
+    #[serde(flatten)]
+    pub latency: LatencySummary,
+}
+
+/// Result of a bench run
+#[derive(Debug, Serialize)]
+pub struct BenchReport {
+    pub iterations: u32,
+    pub queries: Vec<QueryBench>,
+    /// Every sample of every query together
+    pub overall: LatencySummary,
+}
+
+/// Nearest-rank percentile of ascending `sorted` samples
+///
+/// The smallest sample with at least `p` percent of samples at or below it,
+/// so every reported value is a latency that was actually observed.
+pub fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Run the bench command
+pub fn run(args: BenchArgs) -> Result<()> {
+    let project = resolve_project(args.project.clone())?;
+    let queries: Vec<String> = std::fs::read_to_string(&args.queries)?
+        .lines()
+        .map(str::trim)
+        .filter(|q| !q.is_empty())
+        .map(String::from)
+        .collect();
+    if queries.is_empty() {
+        return Err(Error::SearchError {
+            message: format!("No queries in {}", args.queries.display()),
+        });
+    }
+
+    if !client::ensure_running(&Config::load()?.daemon) {
+        return Err(Error::DaemonNotRunning);
+    }
+    let mut session = client::SearchSession::connect()?;
+
+    let mut all = Vec::new();
+    let mut results = Vec::with_capacity(queries.len());
+    for query in queries {
+        let mut samples = Vec::with_capacity(args.iterations as usize);
+        for _ in 0..args.iterations {
+            let start = Instant::now();
+            session.search(&query, &project.root, args.limit, None)?;
+            samples.push(start.elapsed().as_secs_f64() * 1000.0);
+        }
+        all.extend_from_slice(&samples);
+        results.push(QueryBench {
+            query,
+            latency: LatencySummary::from_samples(&samples),
+        });
+    }
+
+    let report = BenchReport {
+        iterations: args.iterations,
+        queries: results,
+        overall: LatencySummary::from_samples(&all),
+    };
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print!("{}", format_table(&report));
+    }
+    Ok(())
+}
+
+/// Summary table: one row per query, then the overall row
+fn format_table(report: &BenchReport) -> String {
+    let width = report
+        .queries
+        .iter()
+        .map(|q| q.query.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("QUERY".len());
+    let row = |name: &str, l: &LatencySummary| {
+        format!(
+            "{:<width$}  {:>9.3}  {:>9.3}  {:>9.3}  {:>9.3}  {:>9.3}\n",
+            name, l.p50_ms, l.p95_ms, l.p99_ms, l.mean_ms, l.max_ms
+        )
+    };
+
+    let mut out = format!(
+        "{:<width$}  {:>9}  {:>9}  {:>9}  {:>9}  {:>9}\n",
+        "QUERY", "p50 ms", "p95 ms", "p99 ms", "mean ms", "max ms"
+    );
+    for q in &report.queries {
+        out.push_str(&row(&q.query, &q.latency));
+    }
+    out.push_str(&row("(all)", &report.overall));
+    out.push_str(&format!(
+        "\n{} queries x {} iterations = {} searches\n",
+        report.queries.len(),
+        report.iterations,
+        report.overall.samples
+    ));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles_of_fixed_sample() {
+        // 1..=100 ms, shuffled: the nearest rank is the value itself
+        let samples: Vec<f64> = (1..=100).map(|i| ((i * 37) % 100 + 1) as f64).collect();
+        let summary = LatencySummary::from_samples(&samples);
+        assert_eq!(summary.samples, 100);
+        assert_eq!(summary.p50_ms, 50.0);
+        assert_eq!(summary.p95_ms, 95.0);
+        assert_eq!(summary.p99_ms, 99.0);
+        assert_eq!(summary.mean_ms, 50.5);
+        assert_eq!(summary.max_ms, 100.0);
+
+        // Small samples round the rank up
+        let small = LatencySummary::from_samples(&[4.0, 1.0, 3.0, 2.0]);
+        assert_eq!(small.p50_ms, 2.0);
+        assert_eq!(small.p95_ms, 4.0);
+        assert_eq!(small.p99_ms, 4.0);
+        assert_eq!(LatencySummary::from_samples(&[7.5]).p50_ms, 7.5);
+    }
+}
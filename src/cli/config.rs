@@ -0,0 +1,101 @@
+//! Config command implementation
+//!
+//! Reads and writes `config.toml` through `core/config.rs`, so values are
+//! validated against the real config schema before they are saved.
+//!
+//! @module cli/config
+
+use clap::{Args, Subcommand};
+
+use crate::core::config::{Config, ENV_OVERRIDES};
+use crate::core::error::Result;
+
+/// Arguments for the config command
+#[derive(Args, Debug)]
+#[command(after_help = "EXAMPLES:
+    greppy config list                          Show the effective configuration
+    greppy config get search.rerank             Print one value
+    greppy config set general.default_limit 50  Change a value
+    greppy config set aliases.auth auth,login   Lists take comma-separated items
+
+Keys are dotted paths into config.toml. Values overridden by an environment
+variable (e.g. GREPPY_LOG_FILE for log.file) are shown with their source.")]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    pub command: ConfigCommand,
+}
+
+/// Config subcommands
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Print the effective value of a key
+    Get {
+        /// Dotted key, e.g. search.rerank
+        key: String,
+    },
+    /// Validate and save a value
+    Set {
+        /// Dotted key, e.g. general.default_limit
+        key: String,
+        /// New value
+        value: String,
+    },
+    /// Print every key with its effective value
+    List,
+}
+
+/// Run the config command
+pub fn run(args: ConfigArgs) -> Result<()> {
+    match args.command {
+        ConfigCommand::Get { key } => {
+            let config = Config::load()?;
+            let value = config.get_value(&key);
+            match Config::env_override(&key) {
+                Some((_, env_value)) => println!("{}", env_value),
+                None => println!("{}", display_value(&value?)),
+            }
+        }
+        ConfigCommand::Set { key, value } => {
+            let mut config = Config::load()?;
+            config.set_value(&key, &value)?;
+            config.save()?;
+            println!("{} = {}", key, config.get_value(&key)?);
+            if let Some((var, _)) = Config::env_override(&key) {
+                println!("note: {} is set and overrides this value", var);
+            }
+        }
+        ConfigCommand::List => {
+            let entries = Config::load()?.entries()?;
+            for (key, value) in &entries {
+                match Config::env_override(key) {
+                    Some((var, env_value)) => println!("{} = {:?}  # from {}", key, env_value, var),
+                    None if is_secret(key) => println!("{} = \"<redacted>\"", key),
+                    None => println!("{} = {}", key, value),
+                }
+            }
+            // Overrides of keys that are unset in the file
+            for (key, _) in ENV_OVERRIDES {
+                if entries.iter().any(|(k, _)| k == key) {
+                    continue;
+                }
+                if let Some((var, env_value)) = Config::env_override(key) {
+                    println!("{} = {:?}  # from {}", key, env_value, var);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Credentials are never echoed by `list` (use `get` to print one explicitly)
+fn is_secret(key: &str) -> bool {
+    key.ends_with("_token")
+}
+
+/// Strings print bare for `get`; everything else in TOML syntax
+fn display_value(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
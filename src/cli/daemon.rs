@@ -1,7 +1,9 @@
-//! Daemon command implementations (start, stop, status)
+//! Daemon command implementations (start, stop, status, events)
 
+use crate::cli::EventsArgs;
 use crate::core::error::Result;
-use crate::daemon::process;
+use crate::daemon::{client, process};
+use std::io::Write;
 
 /// Start the daemon
 pub fn start() -> Result<()> {
@@ -61,3 +63,27 @@ pub fn status() -> Result<()> {
     }
     Ok(())
 }
+
+/// Stream daemon events to stdout until the daemon goes away
+pub fn events(args: EventsArgs) -> Result<()> {
+    let ndjson = args.format == "ndjson";
+    let stdout = std::io::stdout();
+    client::subscribe(|event| {
+        let now = chrono::Utc::now();
+        let mut out = stdout.lock();
+        if ndjson {
+            writeln!(out, "{}", event.to_ndjson(now))?;
+        } else {
+            writeln!(
+                out,
+                "{} {} {}",
+                now.format("%H:%M:%S"),
+                event.event_type(),
+                event.payload()
+            )?;
+        }
+        // Flush per event so pipes see lines as they happen
+        out.flush()?;
+        Ok(())
+    })
+}
@@ -0,0 +1,147 @@
+//! Export/import command implementations
+//!
+//! Moves a trace index between machines as a JSON Lines document so it can
+//! be analyzed where the source is not available.
+//!
+//! @module cli/export
+
+use clap::Args;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::core::config::Config;
+use crate::core::error::{Error, Result};
+use crate::core::project::{resolve_project, resolve_target_project};
+use crate::index::TantivyIndex;
+use crate::trace::{
+    check_index_size, export_index, import_index, index_file_size, load_index, save_index,
+    trace_index_path,
+};
+
+/// Arguments for the export command
+#[derive(Args, Debug)]
+#[command(after_help = "EXAMPLES:
+    greppy export                     Write trace index JSON Lines to stdout
+    greppy export -o index.jsonl      Write to a file
+    greppy export -p ~/code           Export a specific project")]
+pub struct ExportArgs {
+    /// Output file (default: stdout)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Project path (default: current directory)
+    #[arg(short, long)]
+    pub project: Option<PathBuf>,
+}
+
+/// Arguments for the import command
+#[derive(Args, Debug)]
+#[command(after_help = "EXAMPLES:
+    greppy import index.jsonl             Import into the current project (or directory)
+    greppy import index.jsonl -p ~/audit  Import into a specific directory")]
+pub struct ImportArgs {
+    /// JSON Lines document produced by 'greppy export'
+    pub input: PathBuf,
+
+    /// Project path (default: the project around the current directory, or
+    /// the directory itself outside any project)
+    #[arg(short, long)]
+    pub project: Option<PathBuf>,
+}
+
+/// Export the trace index as JSON Lines
+pub fn export(args: ExportArgs) -> Result<()> {
+    let project = resolve_project(args.project)?;
+
+    let index_path = trace_index_path(&project.root);
+    if !index_path.exists() {
+        return Err(Error::IndexNotFound {
+            path: project.root.clone(),
+        });
+    }
+    let index = load_index(&index_path)?;
+
+    match args.output {
+        Some(path) => {
+            let mut writer = BufWriter::new(File::create(&path)?);
+            let records = export_index(&index, &mut writer)?;
+            writer.flush()?;
+            eprintln!("Exported {} records to {}", records, path.display());
+        }
+        None => {
+            let stdout = std::io::stdout();
+            let mut writer = BufWriter::new(stdout.lock());
+            export_index(&index, &mut writer)?;
+            writer.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Import a JSON Lines document and save it as the project's trace index
+pub fn import(args: ImportArgs) -> Result<()> {
+    let cwd = std::env::current_dir()?;
+    import_from(args, &cwd)
+}
+
+/// [`import`] with an explicit working directory
+fn import_from(args: ImportArgs, cwd: &Path) -> Result<()> {
+    let root = resolve_target_project(args.project, cwd)?.root;
+
+    let reader = BufReader::new(File::open(&args.input)?);
+    let index = import_index(reader)?;
+    check_index_size(
+        TantivyIndex::disk_size(&root)? + index_file_size(&index),
+        Config::load()?.index.max_index_bytes,
+    )?;
+
+    let index_path = trace_index_path(&root);
+    if let Some(parent) = index_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    save_index(&index, &index_path)?;
+
+    let stats = index.stats();
+    println!(
+        "Imported trace index: {} files ({} symbols, {} edges) into {}",
+        stats.files,
+        stats.symbols,
+        stats.edges,
+        index_path.display()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace::SemanticIndexBuilder;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_import_into_markerless_directory_without_project() {
+        let source = TempDir::new().unwrap();
+        let path = source.path().join("lib.rs");
+        let content = "fn used() {}\nfn main() { used(); }\n";
+        std::fs::write(&path, content).unwrap();
+        let mut builder = SemanticIndexBuilder::new(source.path());
+        builder.add_file(&path, content);
+        let document = source.path().join("index.jsonl");
+        export_index(&builder.build(), &mut File::create(&document).unwrap()).unwrap();
+
+        // A plain directory: no .git, no package manifest
+        let target = TempDir::new().unwrap();
+        let args = ImportArgs {
+            input: document,
+            project: None,
+        };
+        import_from(args, target.path()).unwrap();
+
+        let root = target.path().canonicalize().unwrap();
+        let index = load_index(trace_index_path(&root)).unwrap();
+        assert_eq!(index.stats().symbols, 2);
+    }
+}
@@ -2,107 +2,166 @@
 //!
 //! Memory-safe parallel indexing:
 //! - Phase 1: Collect file paths (small memory footprint)
-//! - Phase 2: Parallel read + chunk with rayon (bounded by thread pool)
-//! - Phase 3: Sequential write to Tantivy with periodic commits
-//! - Phase 4: Build semantic trace index (symbols, calls, references)
+//! - Phase 2: Build semantic trace index in memory and check the size cap
+//! - Phase 3: Parallel read + chunk with rayon, sequential write to Tantivy
+//!   with periodic commits
+//! - Phase 4: Save the semantic trace index (symbols, calls, references)
 //!
 //! This avoids holding all file contents or chunks in memory at once.
 
 use crate::cli::IndexArgs;
 use crate::core::config::Config;
-use crate::core::error::Result;
-use crate::core::project::Project;
-use crate::index::{IndexWriter, TantivyIndex};
-use crate::parse::{chunk_file, Chunk};
+use crate::core::error::{Error, Result};
+use crate::core::project::{resolve_project, Project};
+use crate::index::hashes::content_hash;
+use crate::index::{FileHashes, IndexWriter, TantivyIndex};
+use crate::output::color;
+use crate::parse::{git_tracked_files, Chunk, Chunker};
+use crate::trace::operations::{compute_stats, TraceFilter};
+use crate::trace::output::StatsResult;
 use crate::trace::{
-    build_and_save_index, detect_language, find_dead_symbols, is_treesitter_supported, load_index,
-    snapshots::create_snapshot, trace_index_path, SemanticIndex,
+    build_project_index, check_index_size, create_formatter, detect_language, find_dead_symbols,
+    index_file_size, is_treesitter_supported, load_index, save_project_index,
+    snapshots::create_snapshot, trace_index_path, AsciiConfig, BuildStats, CsvConfig,
+    MarkdownConfig, OutputFormat, SemanticIndex,
 };
 use ignore::WalkBuilder;
 use rayon::prelude::*;
+use serde::Serialize;
 use std::collections::{HashMap, HashSet};
-use std::env;
-use std::path::PathBuf;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Instant;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 /// Batch size for commits - prevents unbounded memory growth in Tantivy
 const COMMIT_BATCH_SIZE: usize = 5000;
 
+/// Bytes the indexes will take once an index run has written them
+///
+/// Tantivy stores the text of every chunk, so the indexed sources are added
+/// to what the text index directory already holds (nothing under `--force`,
+/// which deletes it first). The trace index size is exact.
+fn projected_index_bytes(
+    root: &Path,
+    file_paths: &[PathBuf],
+    force: bool,
+    trace: Option<&(SemanticIndex, BuildStats)>,
+) -> Result<u64> {
+    let existing = if force {
+        0
+    } else {
+        TantivyIndex::disk_size(root)?
+    };
+    let sources: u64 = file_paths
+        .iter()
+        .filter_map(|path| std::fs::metadata(path).ok())
+        .map(|meta| meta.len())
+        .sum();
+    let trace = trace.map_or(0, |(index, _)| index_file_size(index));
+    Ok(existing + sources + trace)
+}
+
 /// Run the index command
 pub fn run(args: IndexArgs) -> Result<()> {
-    // Determine project path
-    let project_path = args
-        .project
-        .unwrap_or_else(|| env::current_dir().expect("Failed to get current directory"));
-
-    // Detect project
-    let project = Project::detect(&project_path)?;
+    let project = resolve_project(args.project.clone())?;
+    if args.verify {
+        return verify(&project.root, args.max_violations);
+    }
     info!(project = %project.name, root = %project.root.display(), "Indexing project");
 
-    let start = Instant::now();
+    if let Some(lang) = project.primary_language() {
+        if !is_treesitter_supported(lang) {
+            progress(
+                args.json,
+                &format!(
+                    "Warning: primary language '{}' has no tree-sitter support; trace results will be limited",
+                    lang
+                ),
+            );
+        }
+    }
 
     // Load config for ignore patterns
     let config = Config::load()?;
+    index_project(&project, &config, &args)
+}
 
-    // Create or open index
-    let index = if args.force {
-        TantivyIndex::delete(&project.root)?;
-        TantivyIndex::open_or_create(&project.root)?
-    } else {
-        TantivyIndex::open_or_create(&project.root)?
-    };
+/// Build the text and trace indexes for a project
+fn index_project(project: &Project, config: &Config, args: &IndexArgs) -> Result<()> {
+    let start = Instant::now();
 
     // =========================================================================
     // PHASE 1: Collect file paths (memory-efficient - just PathBufs)
     // =========================================================================
-    let walker = WalkBuilder::new(&project.root)
-        .hidden(true)
-        .git_ignore(true)
-        .git_global(true)
-        .git_exclude(true)
-        .max_filesize(Some(config.index.max_file_size))
-        .build();
-
-    let ignore_patterns = config.ignore.patterns.clone();
-
-    let file_paths: Vec<PathBuf> = walker
-        .flatten()
-        .filter_map(|entry| {
-            let path = entry.path();
-            if path.is_dir() {
-                return None;
-            }
-            if !is_code_file(path) {
+    let git_tracked = args.git_tracked || config.index.git_tracked_only;
+    let file_paths = collect_file_paths(&project.root, config, git_tracked)?;
+
+    let total_files = file_paths.len();
+    info!(files = total_files, "Found files to index");
+
+    // =========================================================================
+    // PHASE 2: Build semantic trace index in memory, then check the size cap
+    // before anything is written
+    // =========================================================================
+    let trace_start = Instant::now();
+    info!("Building semantic trace index...");
+
+    // Collect files that support tree-sitter for semantic indexing
+    // We need to re-read files for semantic extraction (different from chunking)
+    let semantic_files: Vec<(PathBuf, String)> = file_paths
+        .par_iter()
+        .filter_map(|path| {
+            let lang = detect_language(path);
+            if !is_treesitter_supported(lang) {
                 return None;
             }
-            if should_ignore(path, &ignore_patterns) {
-                debug!(path = %path.display(), "Skipping ignored file");
-                return None;
+            match std::fs::read_to_string(path) {
+                Ok(content) => Some((path.clone(), content)),
+                Err(_) => None,
             }
-            Some(path.to_path_buf())
         })
         .collect();
 
-    let total_files = file_paths.len();
-    info!(files = total_files, "Found files to index");
+    let semantic_file_count = semantic_files.len();
+    let trace =
+        (semantic_file_count > 0).then(|| build_project_index(&project.root, &semantic_files));
+    // Sources are re-read per batch below; only the built index is kept
+    drop(semantic_files);
+    let trace_build_elapsed = trace_start.elapsed();
+
+    check_index_size(
+        projected_index_bytes(&project.root, &file_paths, args.force, trace.as_ref())?,
+        config.index.max_index_bytes,
+    )?;
+
+    // Create or open index
+    let index = if args.force {
+        TantivyIndex::delete(&project.root)?;
+        TantivyIndex::open_or_create(&project.root)?
+    } else {
+        TantivyIndex::open_or_create(&project.root)?
+    };
 
     // =========================================================================
-    // PHASE 2: Parallel read + chunk (rayon handles thread pool bounds)
+    // PHASE 3: Parallel read + chunk (rayon handles thread pool bounds)
     // Memory safety: Each file is read, chunked, and dropped before next batch
     // =========================================================================
     let file_count = AtomicUsize::new(0);
     let chunk_count = AtomicUsize::new(0);
+    let chunker = Chunker::from_config(&config.index);
 
     // Process in batches to control memory - don't load all files at once
     let batch_size = 500; // Process 500 files at a time
     let mut writer = IndexWriter::new(&index)?;
     let mut total_chunks_written = 0usize;
+    // A full index replaces every recorded hash
+    let mut hashes = FileHashes::empty(&project.root)?;
 
     for batch in file_paths.chunks(batch_size) {
         // Parallel: read and chunk files in this batch
-        let batch_chunks: Vec<Chunk> = batch
+        let batch_chunks: Vec<(&PathBuf, u64, Vec<Chunk>)> = batch
             .par_iter()
             .filter_map(|path| {
                 let content = match std::fs::read_to_string(path) {
@@ -114,17 +173,21 @@ pub fn run(args: IndexArgs) -> Result<()> {
                 };
 
                 file_count.fetch_add(1, Ordering::Relaxed);
-                let chunks = chunk_file(path, &content);
+                let chunks = chunker.chunk_file(path, &content);
                 chunk_count.fetch_add(chunks.len(), Ordering::Relaxed);
 
                 // Return chunks, content is dropped here (memory freed)
-                Some(chunks)
+                Some((path, content_hash(&content), chunks))
             })
-            .flatten()
             .collect();
 
+        // Record content hashes so incremental updates can skip unchanged files
+        for (path, hash, _) in &batch_chunks {
+            hashes.insert(path, *hash);
+        }
+
         // Sequential: write to Tantivy (thread-safe requirement)
-        for chunk in &batch_chunks {
+        for chunk in batch_chunks.iter().flat_map(|(_, _, chunks)| chunks) {
             writer.add_chunk(chunk)?;
             total_chunks_written += 1;
 
@@ -139,6 +202,9 @@ pub fn run(args: IndexArgs) -> Result<()> {
 
     // Final commit
     writer.commit()?;
+    if let Err(e) = hashes.save() {
+        warn!(error = %e, "Failed to save file hashes");
+    }
 
     let tantivy_elapsed = start.elapsed();
     let final_file_count = file_count.load(Ordering::Relaxed);
@@ -158,41 +224,25 @@ pub fn run(args: IndexArgs) -> Result<()> {
         "Text index complete"
     );
 
-    println!(
-        "Text index: {} files ({} chunks) in {:.2}s",
-        final_file_count,
-        final_chunk_count,
-        tantivy_elapsed.as_secs_f64(),
+    progress(
+        args.json,
+        &format!(
+            "Text index: {} files ({} chunks) in {:.2}s",
+            final_file_count,
+            final_chunk_count,
+            tantivy_elapsed.as_secs_f64(),
+        ),
     );
 
     // =========================================================================
-    // PHASE 4: Build semantic trace index
+    // PHASE 4: Save semantic trace index
     // =========================================================================
-    let trace_start = Instant::now();
-    info!("Building semantic trace index...");
-
-    // Collect files that support tree-sitter for semantic indexing
-    // We need to re-read files for semantic extraction (different from chunking)
-    let semantic_files: Vec<(PathBuf, String)> = file_paths
-        .par_iter()
-        .filter_map(|path| {
-            let lang = detect_language(path);
-            if !is_treesitter_supported(lang) {
-                return None;
-            }
-            match std::fs::read_to_string(path) {
-                Ok(content) => Some((path.clone(), content)),
-                Err(_) => None,
-            }
-        })
-        .collect();
-
-    let semantic_file_count = semantic_files.len();
-
-    if semantic_file_count > 0 {
-        match build_and_save_index(&project.root, &semantic_files) {
-            Ok(stats) => {
-                let trace_elapsed = trace_start.elapsed();
+    let mut report = IndexReport::default();
+    if let Some((trace_index, stats)) = trace {
+        let save_start = Instant::now();
+        match save_project_index(&project.root, &trace_index) {
+            Ok(()) => {
+                let trace_elapsed = trace_build_elapsed + save_start.elapsed();
                 info!(
                     files = stats.files,
                     symbols = stats.symbols,
@@ -201,28 +251,43 @@ pub fn run(args: IndexArgs) -> Result<()> {
                     elapsed_ms = trace_elapsed.as_millis(),
                     "Trace index complete"
                 );
-                println!(
-                    "Trace index: {} files ({} symbols, {} edges) in {:.2}s",
-                    stats.files,
-                    stats.symbols,
-                    stats.edges,
-                    trace_elapsed.as_secs_f64(),
+                progress(
+                    args.json,
+                    &format!(
+                        "Trace index: {} files ({} symbols, {} edges) in {:.2}s",
+                        stats.files,
+                        stats.symbols,
+                        stats.edges,
+                        trace_elapsed.as_secs_f64(),
+                    ),
                 );
+                if !args.json {
+                    if let Some(summary) = parse_fallback_report(&stats.parse_fallbacks) {
+                        println!("{}", summary);
+                    }
+                }
+                report.parse_fallbacks = stats.parse_fallbacks;
             }
             Err(e) => {
-                tracing::warn!("Failed to build trace index: {}", e);
-                println!("Warning: Trace index build failed: {}", e);
+                tracing::warn!("Failed to save trace index: {}", e);
+                progress(
+                    args.json,
+                    &format!("Warning: Trace index save failed: {}", e),
+                );
             }
         }
     } else {
-        println!("Trace index: skipped (no supported languages)");
+        progress(args.json, "Trace index: skipped (no supported languages)");
     }
 
     let total_elapsed = start.elapsed();
-    println!(
-        "\nTotal: {:.2}s ({:.0} chunks/sec)",
-        total_elapsed.as_secs_f64(),
-        chunks_per_sec
+    progress(
+        args.json,
+        &format!(
+            "\nTotal: {:.2}s ({:.0} chunks/sec)",
+            total_elapsed.as_secs_f64(),
+            chunks_per_sec
+        ),
     );
 
     // =========================================================================
@@ -260,9 +325,112 @@ pub fn run(args: IndexArgs) -> Result<()> {
         }
     }
 
+    // =========================================================================
+    // PHASE 6: Optional statistics for the fresh index
+    // =========================================================================
+    if args.stats_after {
+        report.stats = stats_after(&project.root);
+        match &report.stats {
+            Some(_) if args.json => {}
+            Some(stats) => println!("\n{}", format_stats_text(stats)),
+            None => progress(args.json, "\nTrace stats: no trace index was built"),
+        }
+    }
+
+    // --json: the report is the only thing on stdout
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).unwrap_or_else(|_| "{}".to_string())
+        );
+    }
+
     Ok(())
 }
 
+/// The single document `greppy index --json` prints on stdout
+#[derive(Debug, Default, Serialize)]
+struct IndexReport {
+    /// Files extracted by regex after a parse error (relative to the root)
+    parse_fallbacks: Vec<PathBuf>,
+    /// Trace statistics, with --stats-after
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stats: Option<StatsResult>,
+}
+
+/// Print a progress line, on stderr under --json so stdout holds only the report
+fn progress(json: bool, line: &str) {
+    if json {
+        eprintln!("{}", line);
+    } else {
+        println!("{}", line);
+    }
+}
+
+/// Summarize files whose trace data came from the regex fallback after a
+/// parse error, or nothing when every file parsed
+fn parse_fallback_report(fallbacks: &[PathBuf]) -> Option<String> {
+    if fallbacks.is_empty() {
+        return None;
+    }
+    let files: Vec<_> = fallbacks.iter().map(|p| p.display().to_string()).collect();
+    Some(format!(
+        "{} file{} fell back to regex due to parse errors: {}",
+        files.len(),
+        if files.len() == 1 { "" } else { "s" },
+        files.join(", ")
+    ))
+}
+
+/// Statistics for the trace index just saved under `root`
+fn stats_after(root: &Path) -> Option<StatsResult> {
+    let index = load_index(trace_index_path(root)).ok()?;
+    Some(compute_stats(&index, &TraceFilter::default()))
+}
+
+/// Format --stats-after output for the terminal
+fn format_stats_text(stats: &StatsResult) -> String {
+    create_formatter(
+        OutputFormat::Ascii,
+        AsciiConfig {
+            no_color: !color::should_color(std::io::stdout().is_terminal()),
+            ..AsciiConfig::default()
+        },
+        MarkdownConfig::default(),
+        CsvConfig::default(),
+    )
+    .format_stats(stats)
+}
+
+/// Check the saved trace index under `root` and report its violations
+fn verify(root: &Path, max_violations: usize) -> Result<()> {
+    let index_path = trace_index_path(root);
+    if !index_path.exists() {
+        return Err(Error::IndexNotFound {
+            path: root.to_path_buf(),
+        });
+    }
+    let index = load_index(&index_path)?;
+    let violations = index.verify();
+    if violations.is_empty() {
+        println!("OK");
+        return Ok(());
+    }
+
+    for violation in violations.iter().take(max_violations) {
+        println!("{}", violation);
+    }
+    if violations.len() > max_violations {
+        println!("... and {} more", violations.len() - max_violations);
+    }
+    Err(Error::IndexError {
+        message: format!(
+            "trace index is inconsistent ({} violations); run 'greppy index --force'",
+            violations.len()
+        ),
+    })
+}
+
 /// Count cycles using DFS (simplified version)
 fn count_cycles(index: &SemanticIndex) -> usize {
     let mut graph: HashMap<u16, HashSet<u16>> = HashMap::new();
@@ -365,9 +533,55 @@ fn is_code_file(path: &std::path::Path) -> bool {
             | "sql"
             | "vue"
             | "svelte"
+            | "ipynb"
     )
 }
 
+/// Collect the code files to index, from git or from a filesystem walk
+///
+/// With `git_tracked`, the list comes from `git ls-files` (and errors outside
+/// a git repository); otherwise from a walk that respects `.gitignore`. Either
+/// way only code files within the size limit that match no ignore pattern
+/// are kept.
+fn collect_file_paths(root: &Path, config: &Config, git_tracked: bool) -> Result<Vec<PathBuf>> {
+    let candidates: Vec<PathBuf> = if git_tracked {
+        git_tracked_files(root)?
+            .into_iter()
+            .filter(|path| {
+                std::fs::metadata(path)
+                    .map(|m| m.is_file() && m.len() <= config.index.max_file_size)
+                    .unwrap_or(false)
+            })
+            .collect()
+    } else {
+        WalkBuilder::new(root)
+            .hidden(true)
+            .git_ignore(true)
+            .git_global(true)
+            .git_exclude(true)
+            .max_filesize(Some(config.index.max_file_size))
+            .build()
+            .flatten()
+            .map(|entry| entry.into_path())
+            .filter(|path| !path.is_dir())
+            .collect()
+    };
+
+    Ok(candidates
+        .into_iter()
+        .filter(|path| {
+            if !is_code_file(path) {
+                return false;
+            }
+            if should_ignore(path, &config.ignore.patterns) {
+                debug!(path = %path.display(), "Skipping ignored file");
+                return false;
+            }
+            true
+        })
+        .collect())
+}
+
 /// Check if a path matches any ignore pattern
 fn should_ignore(path: &std::path::Path, patterns: &[String]) -> bool {
     let path_str = path.to_string_lossy();
@@ -382,3 +596,143 @@ fn should_ignore(path: &std::path::Path, patterns: &[String]) -> bool {
 
     false
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace::build_and_save_index;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn git(root: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(root)
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn test_git_tracked_excludes_untracked_files() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        git(root, &["init", "-q"]);
+        std::fs::write(root.join("tracked.rs"), "fn tracked() {}\n").unwrap();
+        std::fs::write(root.join("notes.txt"), "not code\n").unwrap();
+        git(root, &["add", "tracked.rs", "notes.txt"]);
+        std::fs::write(root.join("generated.rs"), "fn generated() {}\n").unwrap();
+
+        let config = Config::default();
+        let files = collect_file_paths(root, &config, true).unwrap();
+        assert_eq!(files, vec![root.join("tracked.rs")]);
+
+        // The filesystem walk still sees the untracked file
+        let walked = collect_file_paths(root, &config, false).unwrap();
+        assert!(walked.contains(&root.join("generated.rs")));
+    }
+
+    #[test]
+    fn test_index_over_max_index_bytes_aborts_before_writing() {
+        use crate::core::error::exit_code;
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::write(
+            root.join("lib.rs"),
+            "fn helper() {}\nfn main() {\n    helper();\n}\n",
+        )
+        .unwrap();
+        let project = Project::from_path(root).unwrap();
+        let mut config = Config::default();
+        config.index.max_index_bytes = Some(64);
+        let args = IndexArgs {
+            project: None,
+            force: false,
+            git_tracked: false,
+            stats_after: false,
+            json: false,
+            verify: false,
+            max_violations: 20,
+        };
+
+        let err = index_project(&project, &config, &args).unwrap_err();
+        assert!(err.to_string().contains("max_index_bytes"), "{}", err);
+        assert_eq!(err.exit_code(), exit_code::FAILURE);
+        // Neither index was touched
+        assert!(!TantivyIndex::exists(&project.root).unwrap());
+        assert!(!trace_index_path(&project.root).exists());
+    }
+
+    #[test]
+    fn test_stats_after_matches_built_index() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let files = vec![
+            (
+                root.join("src/main.rs"),
+                "fn main() {\n    helper();\n}\n".to_string(),
+            ),
+            (root.join("src/lib.rs"), "fn helper() {}\n".to_string()),
+        ];
+
+        let built = build_and_save_index(root, &files, None).unwrap();
+
+        let stats = stats_after(root).unwrap();
+        assert_eq!(stats.total_files, built.files);
+        assert_eq!(stats.total_symbols, built.symbols);
+        assert_eq!(stats.total_edges, built.edges);
+
+        // --json --stats-after: one document holding the stats
+        let report = IndexReport {
+            parse_fallbacks: built.parse_fallbacks,
+            stats: Some(stats),
+        };
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["stats"]["total_files"], built.files);
+        assert_eq!(json["parse_fallbacks"], serde_json::json!([]));
+
+        // Nothing to report before an index exists
+        assert!(stats_after(&root.join("elsewhere")).is_none());
+    }
+
+    #[test]
+    fn test_unparseable_file_is_reported_as_regex_fallback() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let files = vec![
+            (root.join("src/good.rs"), "fn ok() {}\n".to_string()),
+            (
+                root.join("src/broken.rs"),
+                "}}} ))) fn ((( @@@ ### ]]] let = = = ;;; {{{\n".to_string(),
+            ),
+        ];
+
+        let built = build_and_save_index(root, &files, None).unwrap();
+        assert_eq!(built.parse_fallbacks, vec![PathBuf::from("src/broken.rs")]);
+
+        let summary = parse_fallback_report(&built.parse_fallbacks).unwrap();
+        assert_eq!(
+            summary,
+            "1 file fell back to regex due to parse errors: src/broken.rs"
+        );
+        let report = IndexReport {
+            parse_fallbacks: built.parse_fallbacks,
+            stats: None,
+        };
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["parse_fallbacks"][0], "src/broken.rs");
+        assert!(json.get("stats").is_none());
+
+        // Nothing to say when every file parsed
+        assert!(parse_fallback_report(&[]).is_none());
+    }
+
+    #[test]
+    fn test_git_tracked_outside_repo_errors() {
+        let temp = TempDir::new().unwrap();
+        let err = collect_file_paths(temp.path(), &Config::default(), true).unwrap_err();
+        assert!(err.to_string().contains("git repository"));
+    }
+}
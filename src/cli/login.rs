@@ -228,6 +228,29 @@ pub fn logout() -> Result<()> {
     Ok(())
 }
 
+/// Run `auth status` - show the active provider and authenticated providers
+pub fn status(json: bool) -> Result<()> {
+    let config = Config::load()?;
+    let status = crate::cli::model::ai_status(&config, &auth::get_authenticated_providers());
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&status)?);
+        return Ok(());
+    }
+
+    println!("Active: {}", crate::cli::model::format_current(&config));
+    if status.authenticated.is_empty() {
+        println!("Authenticated: none (run 'greppy login')");
+    } else {
+        println!("Authenticated: {}", status.authenticated.join(", "));
+    }
+    if !status.profiles.is_empty() {
+        println!("Profiles: {}", status.profiles.join(", "));
+    }
+
+    Ok(())
+}
+
 /// Check if user is authenticated with any provider (including Ollama)
 pub fn is_authenticated() -> bool {
     if !auth::get_authenticated_providers().is_empty() {
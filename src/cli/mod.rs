@@ -1,6 +1,9 @@
 //! CLI command definitions and handlers
 
+pub mod bench;
+pub mod config;
 pub mod daemon;
+pub mod export;
 pub mod index;
 pub mod login;
 pub mod model;
@@ -8,8 +11,8 @@ pub mod search;
 pub mod trace;
 pub mod web;
 
-use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use clap::{Parser, Subcommand, ValueEnum};
+use std::path::{Component, Path, PathBuf};
 
 const LONG_ABOUT: &str = r#"
  ██████╗ ██████╗ ███████╗██████╗ ██████╗ ██╗   ██╗
@@ -34,10 +37,13 @@ DAEMON (optional, for faster searches):
     greppy start              Start background daemon with file watcher
     greppy stop               Stop the daemon
     greppy status             Check if daemon is running
+    greppy events             Stream daemon events (--format ndjson)
+    greppy bench --queries f  Measure search latency percentiles
 
 AI PROVIDERS:
     greppy login              Configure AI provider for semantic search
     greppy logout             Remove stored credentials
+    greppy auth status        Show authenticated providers (--json)
 
     Supports Ollama (local, free), Claude, or Gemini. Without login,
     searches fall back to direct BM25 mode automatically.
@@ -49,6 +55,14 @@ TRACE (symbol analysis):
     greppy trace --dead               Find unused code
     greppy trace --stats              Codebase statistics
 
+CONFIGURATION:
+    greppy config list                Show the effective configuration
+    greppy config set <key> <value>   Change a value (validated)
+
+OFFLINE ANALYSIS:
+    greppy export -o index.jsonl      Export trace index as JSON Lines
+    greppy import index.jsonl         Rebuild trace index from an export
+
 EXAMPLES:
     greppy index                      Index current directory
     greppy search "error handling"    Find error handling code
@@ -56,6 +70,15 @@ EXAMPLES:
     greppy search "auth" --json       JSON output for scripting
     greppy trace --refs createUser    Find all references to createUser
     greppy trace --impact auth        Analyze impact of changing auth
+
+EXIT CODES:
+    0  Success
+    1  Unclassified failure
+    2  Usage error (invalid arguments or flags)
+    3  Index missing (run `greppy index` first)
+    4  Daemon unavailable
+    5  Authentication error
+    6  Check mode found a match (`trace --pattern --first-match-only`)
 "#;
 
 /// Sub-millisecond semantic code search
@@ -66,6 +89,10 @@ EXAMPLES:
 #[command(long_about = LONG_ABOUT)]
 #[command(propagate_version = true)]
 pub struct Cli {
+    /// Color output: auto (terminals, unless NO_COLOR is set), always, or never
+    #[arg(long, global = true, value_enum, value_name = "WHEN", default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -89,6 +116,9 @@ pub enum Commands {
     /// Check if the daemon is running
     Status,
 
+    /// Stream daemon events (file changes, reindexing) as they happen
+    Events(EventsArgs),
+
     /// Configure AI provider for semantic search (Ollama, Claude, or Gemini)
     #[command(after_help = "AI PROVIDERS:
     1. Run 'greppy login'
@@ -128,9 +158,16 @@ Shows current AI configuration and lets you:
 
 EXAMPLES:
     greppy model              Open interactive switcher
+    greppy model --json       Print current provider, model and profiles
     
 Profiles are saved in ~/.config/greppy/config.toml")]
-    Model,
+    Model(ModelArgs),
+
+    /// Inspect AI provider authentication
+    Auth(AuthArgs),
+
+    /// View and change configuration values
+    Config(config::ConfigArgs),
 
     /// Trace symbol invocations across codebase
     #[command(visible_alias = "t")]
@@ -139,6 +176,15 @@ Profiles are saved in ~/.config/greppy/config.toml")]
     /// Launch web UI for visual codebase exploration
     #[command(visible_alias = "w")]
     Web(web::WebArgs),
+
+    /// Export the trace index as JSON Lines
+    Export(export::ExportArgs),
+
+    /// Import a JSON Lines trace index for offline analysis
+    Import(export::ImportArgs),
+
+    /// Measure daemon search latency (p50/p95/p99) over a query file
+    Bench(bench::BenchArgs),
 }
 
 /// Arguments for the search command
@@ -147,10 +193,14 @@ Profiles are saved in ~/.config/greppy/config.toml")]
     greppy search \"authentication\"       Semantic search (AI)
     greppy search -d \"authentication\"    Direct BM25 search
     greppy search \"error\" -n 10          Limit results
-    greppy search \"query\" --json         JSON output")]
+    greppy search \"query\" --json         JSON output
+    greppy search --batch < queries.txt  One query per line, NDJSON out
+    greppy search --explain \"auth\"       Show why each result scored as it did
+    greppy search \"token\" --in src/auth  Only results under src/auth")]
 pub struct SearchArgs {
     /// Search query
-    pub query: String,
+    #[arg(required_unless_present = "batch")]
+    pub query: Option<String>,
 
     /// Direct mode (BM25 only, no AI)
     #[arg(short = 'd', long)]
@@ -164,17 +214,82 @@ pub struct SearchArgs {
     #[arg(long)]
     pub json: bool,
 
+    /// Print only unique matching file paths, in rank order
+    #[arg(short = 'l', long)]
+    pub path_only: bool,
+
+    /// Read one query per line from stdin and emit NDJSON (BM25, no AI)
+    #[arg(long, conflicts_with = "query")]
+    pub batch: bool,
+
+    /// Show matched terms and per-field score contributions (implies --direct)
+    #[arg(long, conflicts_with_all = ["batch", "path_only"])]
+    pub explain: bool,
+
+    /// Only return results under this path (relative to the project root)
+    #[arg(long = "in", value_name = "PATH")]
+    pub r#in: Option<PathBuf>,
+
     /// Project path (default: current directory)
     #[arg(short, long)]
     pub project: Option<PathBuf>,
 }
 
+impl SearchArgs {
+    /// The query given on the command line (empty in --batch mode)
+    pub fn query(&self) -> &str {
+        self.query.as_deref().unwrap_or_default()
+    }
+
+    /// The `--in` path resolved against the project root
+    pub fn scope(&self, root: &Path) -> Option<PathBuf> {
+        // Indexed paths are joined onto the root, so drop `./` segments
+        self.r#in.as_ref().map(|dir| {
+            root.join(
+                dir.components()
+                    .filter(|c| *c != Component::CurDir)
+                    .collect::<PathBuf>(),
+            )
+        })
+    }
+}
+
+/// Arguments for the model command
+#[derive(Parser, Debug)]
+pub struct ModelArgs {
+    /// Print the current AI configuration as JSON instead of switching
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for the auth command
+#[derive(Parser, Debug)]
+pub struct AuthArgs {
+    #[command(subcommand)]
+    pub command: AuthCommand,
+}
+
+/// Auth subcommands
+#[derive(Subcommand, Debug)]
+pub enum AuthCommand {
+    /// Show the active provider and which providers are authenticated
+    Status {
+        /// JSON output
+        #[arg(long)]
+        json: bool,
+    },
+}
+
 /// Arguments for the index command
 #[derive(Parser, Debug)]
 #[command(after_help = "EXAMPLES:
     greppy index              Index current directory
     greppy index -p ~/code    Index specific directory
-    greppy index --force      Force full re-index")]
+    greppy index --force      Force full re-index
+    greppy index --git-tracked  Only index files tracked by git
+    greppy index --stats-after  Print trace statistics once indexing finishes
+    greppy index --json       Report files that fell back to regex as JSON
+    greppy index --verify     Check the saved trace index for corruption (no reindex)")]
 pub struct IndexArgs {
     /// Project path (default: current directory)
     #[arg(short, long)]
@@ -183,6 +298,37 @@ pub struct IndexArgs {
     /// Force full re-index
     #[arg(short, long)]
     pub force: bool,
+
+    /// Only index files tracked by git (skips untracked build artifacts)
+    #[arg(long)]
+    pub git_tracked: bool,
+
+    /// Print trace index statistics after indexing completes
+    #[arg(long)]
+    pub stats_after: bool,
+
+    /// Print one JSON report (parse fallbacks, --stats-after) on stdout; progress goes to stderr
+    #[arg(long)]
+    pub json: bool,
+
+    /// Check the saved trace index's invariants instead of indexing
+    #[arg(long, conflicts_with_all = ["force", "git_tracked", "stats_after"])]
+    pub verify: bool,
+
+    /// Maximum violations printed by --verify
+    #[arg(long, default_value = "20", requires = "verify")]
+    pub max_violations: usize,
+}
+
+/// Arguments for the events command
+#[derive(Parser, Debug)]
+#[command(after_help = "EXAMPLES:
+    greppy events                   Follow daemon events
+    greppy events --format ndjson   One JSON object per line (timestamp, type, payload)")]
+pub struct EventsArgs {
+    /// Output format: text or ndjson
+    #[arg(long, default_value = "text", value_parser = ["text", "ndjson"])]
+    pub format: String,
 }
 
 /// Output format options
@@ -191,3 +337,11 @@ pub enum OutputFormat {
     Human,
     Json,
 }
+
+/// When to emit ANSI colors (`--color`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
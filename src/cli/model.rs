@@ -1,14 +1,52 @@
 //! Interactive AI model switcher
 
 use crate::ai::ollama::OllamaClient;
+use crate::auth::{self, Provider};
+use crate::cli::ModelArgs;
 use crate::core::config::{AiProfile, AiProvider, Config};
 use crate::core::error::{Error, Result};
 use dialoguer::{theme::ColorfulTheme, Input, Select};
+use serde::Serialize;
+
+/// Current AI configuration, as printed by `--json`
+#[derive(Debug, Serialize)]
+pub struct AiStatus {
+    pub provider: AiProvider,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub profiles: Vec<String>,
+    pub authenticated: Vec<&'static str>,
+}
+
+/// Summarize the active provider, saved profiles and authenticated providers
+pub fn ai_status(config: &Config, authenticated: &[Provider]) -> AiStatus {
+    let mut profiles: Vec<String> = config.ai.profiles.keys().cloned().collect();
+    profiles.sort();
+
+    AiStatus {
+        provider: config.ai.provider.clone(),
+        model: (config.ai.provider == AiProvider::Ollama).then(|| config.ai.ollama_model.clone()),
+        profiles,
+        authenticated: authenticated
+            .iter()
+            .map(|p| match p {
+                Provider::Anthropic => "anthropic",
+                Provider::Google => "google",
+            })
+            .collect(),
+    }
+}
 
 /// Run the interactive model switcher
-pub async fn run() -> Result<()> {
+pub async fn run(args: ModelArgs) -> Result<()> {
     let mut config = Config::load()?;
 
+    if args.json {
+        let status = ai_status(&config, &auth::get_authenticated_providers());
+        println!("{}", serde_json::to_string_pretty(&status)?);
+        return Ok(());
+    }
+
     // Show current model
     println!();
     println!(" Current: {}", format_current(&config));
@@ -149,7 +187,7 @@ enum Action {
     DeleteProfile,
 }
 
-fn format_current(config: &Config) -> String {
+pub(crate) fn format_current(config: &Config) -> String {
     match config.ai.provider {
         AiProvider::Claude => "Claude (Anthropic)".to_string(),
         AiProvider::Gemini => "Gemini (Google)".to_string(),
@@ -275,3 +313,29 @@ async fn delete_profile(config: &mut Config) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_json_lists_authenticated_providers() {
+        let mut config = Config::default();
+        config.ai.provider = AiProvider::Ollama;
+        config.ai.ollama_model = "llama3".to_string();
+
+        let status = ai_status(&config, &[Provider::Google, Provider::Anthropic]);
+        let json = serde_json::to_value(&status).unwrap();
+
+        assert_eq!(
+            json["authenticated"],
+            serde_json::json!(["google", "anthropic"])
+        );
+        assert_eq!(json["provider"], "ollama");
+        assert_eq!(json["model"], "llama3");
+
+        let json = serde_json::to_value(ai_status(&Config::default(), &[])).unwrap();
+        assert_eq!(json["authenticated"], serde_json::json!([]));
+        assert!(json.get("model").is_none());
+    }
+}
@@ -1,34 +1,44 @@
 //! Search command implementation
 
-use crate::ai::{claude::ClaudeClient, gemini::GeminiClient};
+use crate::ai::{claude::ClaudeClient, gemini::GeminiClient, ollama::OllamaClient};
 use crate::auth::{self, Provider};
 use crate::cli::login::{get_ollama_client, is_ollama_configured};
 use crate::cli::{OutputFormat, SearchArgs};
+use crate::core::config::{AiConfig, Config, RerankMode};
 use crate::core::error::Result;
-use crate::core::project::Project;
+use crate::core::project::{resolve_project, Project};
 use crate::daemon::client;
 use crate::index::TantivyIndex;
-use crate::output::format_results;
-use crate::search::SearchQuery;
-use std::env;
+use crate::output::{color, format_results, paths};
+use crate::search::aliases::expand_query_text;
+use crate::search::rerank::{rerank_by_embeddings, EmbeddingCache};
+use crate::search::{SearchQuery, SearchResponse};
+use std::collections::HashMap;
+use std::io::{BufRead, IsTerminal, Write};
 use tracing::debug;
 
 /// Run the search command
-pub async fn run(args: SearchArgs) -> Result<()> {
-    let project_path = args
-        .project
-        .clone()
-        .unwrap_or_else(|| env::current_dir().expect("Failed to get current directory"));
-
-    let project = Project::detect(&project_path)?;
+pub async fn run(mut args: SearchArgs) -> Result<()> {
+    let project = resolve_project(args.project.clone())?;
     let format = if args.json {
         OutputFormat::Json
     } else {
         OutputFormat::Human
     };
 
-    // Direct mode: BM25 only
-    if args.direct {
+    // Batch mode: many queries from stdin, BM25 only
+    let aliases = Config::load()?.aliases;
+    if args.batch {
+        return run_batch_search(&args, &project, &aliases);
+    }
+
+    // Configured aliases add their expansions as extra terms
+    if let Some(query) = args.query.take() {
+        args.query = Some(expand_query_text(&query, &aliases));
+    }
+
+    // Direct mode: BM25 only (explanations describe BM25 scores)
+    if args.direct || args.explain {
         return run_direct_search(&args, &project, format).await;
     }
 
@@ -42,11 +52,14 @@ async fn run_direct_search(
     project: &Project,
     format: OutputFormat,
 ) -> Result<()> {
-    // Try daemon first
-    if let Ok(true) = client::is_running() {
+    // Try daemon first (auto-starting it if configured); it cannot explain
+    if !args.explain && client::ensure_running(&Config::load()?.daemon) {
         debug!("Using daemon for direct search");
-        if let Ok(results) = client::search(&args.query, &project.root, args.limit).await {
-            print!("{}", format_results(&results, format));
+        let scope = args.scope(&project.root);
+        if let Ok(results) =
+            client::search(args.query(), &project.root, args.limit, scope.as_deref()).await
+        {
+            print_results(args, &results, format);
             return Ok(());
         }
         debug!("Daemon search failed, falling back to direct");
@@ -54,9 +67,164 @@ async fn run_direct_search(
 
     // Direct mode (blocking, but fine for CLI)
     let index = TantivyIndex::open(&project.root)?;
-    let query = SearchQuery::new(&args.query).with_limit(args.limit);
+    let query = SearchQuery::new(args.query())
+        .with_limit(args.limit)
+        .with_path_filters(args.scope(&project.root).into_iter().collect())
+        .with_explain(args.explain);
     let results = query.execute(&index)?;
-    print!("{}", format_results(&results, format));
+    print_results(args, &results, format);
+
+    Ok(())
+}
+
+/// Batch search: one query per line on stdin, one NDJSON response per query
+fn run_batch_search(
+    args: &SearchArgs,
+    project: &Project,
+    aliases: &HashMap<String, Vec<String>>,
+) -> Result<()> {
+    let stdin = std::io::stdin();
+    let stdout = std::io::stdout();
+    let mut out = std::io::BufWriter::new(stdout.lock());
+
+    // One daemon connection (or one opened index) serves every query
+    let mut session = if client::ensure_running(&Config::load()?.daemon) {
+        client::SearchSession::connect().ok()
+    } else {
+        None
+    };
+    let mut index = None;
+    let scope = args.scope(&project.root);
+
+    let count = run_batch(stdin.lock(), &mut out, |query| {
+        let query = &expand_query_text(query, aliases);
+        if let Some(session) = session.as_mut() {
+            return session.search(query, &project.root, args.limit, scope.as_deref());
+        }
+        if index.is_none() {
+            index = Some(TantivyIndex::open(&project.root)?);
+        }
+        let index = index.as_ref().expect("index opened above");
+        SearchQuery::new(query)
+            .with_limit(args.limit)
+            .with_path_filters(scope.iter().cloned().collect())
+            .execute(index)
+    })?;
+    out.flush()?;
+
+    debug!(queries = count, "Batch search complete");
+    Ok(())
+}
+
+/// Run each non-empty input line as a query, writing one tagged JSON line each
+fn run_batch<R: BufRead, W: Write>(
+    input: R,
+    out: &mut W,
+    mut search: impl FnMut(&str) -> Result<SearchResponse>,
+) -> Result<usize> {
+    let mut count = 0;
+
+    for line in input.lines() {
+        let line = line?;
+        let query = line.trim();
+        if query.is_empty() {
+            continue;
+        }
+
+        let mut response = search(query)?;
+        response.query = query.to_string();
+        serde_json::to_writer(&mut *out, &response)?;
+        out.write_all(b"\n")?;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// Print results, honoring --path-only
+fn print_results(args: &SearchArgs, results: &SearchResponse, format: OutputFormat) {
+    if args.path_only {
+        print!("{}", paths::format(results));
+    } else {
+        let color = color::should_color(std::io::stdout().is_terminal());
+        print!("{}", format_results(results, format, color));
+    }
+}
+
+/// Build the snippets sent to the AI reranker, capped by the `[ai]` budget
+fn rerank_chunks(results: &SearchResponse, config: &AiConfig) -> Vec<String> {
+    let max_items = config.rerank_max_items.unwrap_or(usize::MAX);
+    let max_lines = config.rerank_snippet_lines.unwrap_or(usize::MAX);
+
+    results
+        .results
+        .iter()
+        .take(max_items)
+        .map(|r| {
+            let snippet: Vec<&str> = r.content.lines().take(max_lines).collect();
+            format!(
+                "// {}\n{}",
+                r.location(),
+                snippet.join("\n").chars().take(1500).collect::<String>()
+            )
+        })
+        .collect()
+}
+
+/// BM25 results to rerank (fetch more than needed so reranking has a choice)
+async fn fetch_rerank_candidates(
+    args: &SearchArgs,
+    project: &Project,
+    config: &Config,
+) -> Result<SearchResponse> {
+    let fetch_limit = (args.limit * 2).min(20); // Fetch 2x for better reranking, max 20
+    let scope = args.scope(&project.root);
+    if client::ensure_running(&config.daemon) {
+        debug!("Using daemon for search");
+        client::search(args.query(), &project.root, fetch_limit, scope.as_deref()).await
+    } else {
+        let index = TantivyIndex::open(&project.root)?;
+        let query = SearchQuery::new(args.query())
+            .with_limit(fetch_limit)
+            .with_path_filters(scope.into_iter().collect());
+        query.execute(&index)
+    }
+}
+
+/// Semantic search (BM25 + local embedding similarity)
+async fn run_embedding_search(
+    args: &SearchArgs,
+    project: &Project,
+    format: OutputFormat,
+    config: &Config,
+) -> Result<()> {
+    let mut results = fetch_rerank_candidates(args, project, config).await?;
+
+    let model = &config.search.embedding_model;
+    let cache_path = Config::index_dir(&project.root)?.join("embeddings.json");
+    let mut cache = EmbeddingCache::load(&cache_path);
+    let client = OllamaClient::with_config(&config.ai.ollama_url, model);
+
+    let reranked = rerank_by_embeddings(
+        args.query(),
+        &mut results.results,
+        model,
+        &mut cache,
+        |texts| async move { client.embed(&texts).await },
+    )
+    .await;
+    match reranked {
+        Ok(()) => cache.save(&cache_path)?,
+        // Keep BM25 order rather than failing the search
+        Err(e) => eprintln!("Embedding rerank unavailable ({}), using BM25 order", e),
+    }
+
+    results.results.truncate(args.limit);
+    if results.results.is_empty() && !args.path_only {
+        println!("No results found for: {}", args.query());
+        return Ok(());
+    }
+    print_results(args, &results, format);
 
     Ok(())
 }
@@ -67,6 +235,11 @@ async fn run_semantic_search(
     project: &Project,
     format: OutputFormat,
 ) -> Result<()> {
+    let config = Config::load()?;
+    if config.search.rerank == RerankMode::Embeddings {
+        return run_embedding_search(args, project, format, &config).await;
+    }
+
     // Check which provider is authenticated (OAuth or Ollama)
     let providers = auth::get_authenticated_providers();
     let ollama_configured = is_ollama_configured();
@@ -77,41 +250,24 @@ async fn run_semantic_search(
         return run_direct_search(args, project, format).await;
     }
 
-    // Get BM25 results first (fetch more than needed for reranking)
-    let fetch_limit = (args.limit * 2).min(20); // Fetch 2x for better reranking, max 20
-    let mut results = if let Ok(true) = client::is_running() {
-        debug!("Using daemon for search");
-        client::search(&args.query, &project.root, fetch_limit).await?
-    } else {
-        let index = TantivyIndex::open(&project.root)?;
-        let query = SearchQuery::new(&args.query).with_limit(fetch_limit);
-        query.execute(&index)?
-    };
+    let mut results = fetch_rerank_candidates(args, project, &config).await?;
 
     // If no results, nothing to rerank
     if results.results.is_empty() {
-        println!("No results found for: {}", args.query);
+        if !args.path_only {
+            println!("No results found for: {}", args.query());
+        }
         return Ok(());
     }
 
     // Build chunks for reranking
-    let chunks: Vec<String> = results
-        .results
-        .iter()
-        .map(|r| {
-            format!(
-                "// {}\n{}",
-                r.path,
-                r.content.chars().take(1500).collect::<String>()
-            )
-        })
-        .collect();
+    let chunks = rerank_chunks(&results, &config.ai);
 
     // Call AI to rerank - check Ollama first (local), then OAuth providers
     let indices = if ollama_configured {
         if let Some(client) = get_ollama_client() {
             debug!("Using Ollama for reranking");
-            client.rerank(&args.query, &chunks).await?
+            client.rerank(args.query(), &chunks).await?
         } else {
             // Fallback to BM25 order
             (0..chunks.len()).collect()
@@ -119,11 +275,11 @@ async fn run_semantic_search(
     } else if providers.contains(&Provider::Anthropic) {
         let token = auth::get_anthropic_token()?;
         let client = ClaudeClient::new(token);
-        client.rerank(&args.query, &chunks).await?
+        client.rerank(args.query(), &chunks).await?
     } else {
         let token = auth::get_google_token()?;
         let client = GeminiClient::new(token);
-        client.rerank(&args.query, &chunks).await?
+        client.rerank(args.query(), &chunks).await?
     };
 
     // Reorder results based on AI ranking
@@ -144,7 +300,48 @@ async fn run_semantic_search(
     }
 
     // Output same format as direct search
-    print!("{}", format_results(&results, format));
+    print_results(args, &results, format);
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_tags_results_with_query() {
+        let input = "parse_config\n\nload user\n  retry  \n";
+        let mut out = Vec::new();
+
+        let count = run_batch(input.as_bytes(), &mut out, |query| {
+            Ok(SearchResponse {
+                results: Vec::new(),
+                query: String::new(),
+                elapsed_ms: query.len() as f64,
+                project: "/tmp/project".to_string(),
+            })
+        })
+        .unwrap();
+        assert_eq!(count, 3);
+
+        let lines: Vec<serde_json::Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        let queries: Vec<_> = lines.iter().map(|v| v["query"].as_str().unwrap()).collect();
+        assert_eq!(queries, vec!["parse_config", "load user", "retry"]);
+        assert_eq!(lines[1]["elapsed_ms"], 9.0);
+    }
+
+    #[test]
+    fn test_direct_search_without_index_exits_index_missing() {
+        use crate::core::error::{exit_code, Error};
+        let dir = tempfile::tempdir().unwrap();
+
+        let err = TantivyIndex::open(dir.path()).err().unwrap();
+        assert!(matches!(err, Error::IndexNotFound { .. }));
+        assert_eq!(err.exit_code(), exit_code::INDEX_MISSING);
+    }
+}
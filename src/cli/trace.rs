@@ -5,29 +5,32 @@
 //!
 //! @module cli/trace
 
-use crate::ai::trace_prompts::is_natural_language_query;
-use crate::ai::{claude::ClaudeClient, gemini::GeminiClient};
-use crate::auth::{self, Provider};
+use crate::core::config::Config;
 use crate::core::error::{Error, Result};
-use crate::core::project::Project;
+use crate::core::project::{resolve_project, Project};
+use crate::output::color;
 use crate::trace::context::FileCache;
+use crate::trace::operations::{
+    pattern_regex, run_operation, stream_pattern, stream_refs, DefinitionChoice, OperationOptions,
+    OperationResult, TraceFilter, TraceOperation, DEFAULT_MAX_DEPTH, TRANSITIVE_DEFAULT_DEPTH,
+};
 use crate::trace::output::{
-    create_formatter, ChainStep, DeadCodeResult, DeadSymbol, FlowAction, FlowResult, FlowStep,
-    ImpactResult, InvocationPath, ModuleResult, OutputFormat, PatternMatch, PatternResult,
-    PotentialCaller, ReferenceInfo, ReferenceKind, RefsResult, RiskLevel, ScopeResult,
-    ScopeVariable, StatsResult, TraceResult,
+    create_formatter, format_lsp_locations, AsciiConfig, CallHierarchyResult, CsvConfig,
+    CsvFormatter, DeadCodeResult, DeprecatedResult, FlowResult, HotspotResult, ImpactResult,
+    MarkdownConfig, ModuleResult, NeighborhoodResult, OutputFormat, PatternResult, ReferenceKind,
+    RefsResult, ScopeResult, ShadowsResult, StatsResult, StubsResult, TraceFormatter, TraceResult,
+    UnusedParamsResult,
 };
 use crate::trace::{
-    find_dead_symbols, find_refs, load_index, trace_index_exists, trace_index_path,
-    trace_symbol_by_name, RefKind, SemanticIndex, SymbolKind,
+    load_index, trace_index_exists, trace_index_path, FilterExpr, SemanticIndex, Visibility,
 };
-use clap::Args;
-use regex::Regex;
+use clap::{Args, FromArgMatches};
 use serde::Serialize;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 use std::env;
+use std::io::{self, IsTerminal, Write};
 use std::path::{Path, PathBuf};
-use tracing::{debug, info, warn};
+use tracing::debug;
 
 /// Combined results for multi-operation JSON output
 #[derive(Debug, Default, Serialize)]
@@ -41,6 +44,12 @@ pub struct CombinedResults {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub callees: Option<TraceResult>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub call_hierarchy: Option<CallHierarchyResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub neighborhood: Option<NeighborhoodResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub call_path: Option<TraceResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub type_usage: Option<RefsResult>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub module: Option<ModuleResult>,
@@ -55,41 +64,182 @@ pub struct CombinedResults {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub dead_code: Option<DeadCodeResult>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<DeprecatedResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unused_params: Option<UnusedParamsResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shadows: Option<ShadowsResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stubs: Option<StubsResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hotspots: Option<HotspotResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub stats: Option<StatsResult>,
+    /// Raw call edges from `--output-edges`, across all operations
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edges: Option<Vec<EdgeRecord>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cycles: Option<ModuleResult>,
 }
 
-// =============================================================================
-// HELPERS
-// =============================================================================
-
-/// Convert SymbolKind to string
-fn symbol_kind_str(kind: SymbolKind) -> &'static str {
-    match kind {
-        SymbolKind::Function => "function",
-        SymbolKind::Method => "method",
-        SymbolKind::Class => "class",
-        SymbolKind::Struct => "struct",
-        SymbolKind::Enum => "enum",
-        SymbolKind::Interface => "interface",
-        SymbolKind::TypeAlias => "type_alias",
-        SymbolKind::Constant => "constant",
-        SymbolKind::Variable => "variable",
-        SymbolKind::Module => "module",
-        SymbolKind::Unknown => "unknown",
-    }
-}
-
-/// Convert output ReferenceKind to string
-fn reference_kind_str(kind: ReferenceKind) -> &'static str {
-    match kind {
-        ReferenceKind::Read => "read",
-        ReferenceKind::Write => "write",
-        ReferenceKind::Call => "call",
-        ReferenceKind::TypeAnnotation => "type",
-        ReferenceKind::Import => "import",
-        ReferenceKind::Export => "export",
+impl CombinedResults {
+    /// Store an operation's result in its slot
+    pub fn insert(&mut self, result: OperationResult) {
+        match result {
+            OperationResult::Trace(r) => self.trace = Some(r),
+            OperationResult::Refs(r) => self.refs = Some(r),
+            OperationResult::Callers(r) => self.callers = Some(r),
+            OperationResult::Callees(r) => self.callees = Some(r),
+            OperationResult::CallHierarchy(r) => self.call_hierarchy = Some(r),
+            OperationResult::Neighborhood(r) => self.neighborhood = Some(r),
+            OperationResult::CallPath(r) => self.call_path = Some(r),
+            OperationResult::TypeUsage(r) => self.type_usage = Some(r),
+            OperationResult::Module(r) => self.module = Some(r),
+            OperationResult::Pattern(r) => self.pattern = Some(r),
+            OperationResult::Flow(r) => self.flow = Some(r),
+            OperationResult::Impact(r) => self.impact = Some(r),
+            OperationResult::Scope(r) => self.scope = Some(r),
+            OperationResult::DeadCode(r) => self.dead_code = Some(r),
+            OperationResult::Deprecated(r) => self.deprecated = Some(r),
+            OperationResult::UnusedParams(r) => self.unused_params = Some(r),
+            OperationResult::Shadows(r) => self.shadows = Some(r),
+            OperationResult::Stubs(r) => self.stubs = Some(r),
+            OperationResult::Hotspots(r) => self.hotspots = Some(r),
+            OperationResult::Stats(r) => self.stats = Some(r),
+            OperationResult::Cycles(r) => self.cycles = Some(r),
+        }
+    }
+
+    /// Per-operation scalar summaries for `--summary --json`
+    pub fn summary(&self) -> serde_json::Value {
+        use serde_json::json;
+
+        let mut out = serde_json::Map::new();
+        if let Some(r) = &self.trace {
+            out.insert(
+                "trace".into(),
+                json!({ "paths": r.total_paths, "entry_points": r.entry_points }),
+            );
+        }
+        if let Some(r) = &self.refs {
+            out.insert(
+                "refs".into(),
+                json!({ "total": r.total_refs, "files": r.by_file.len() }),
+            );
+        }
+        if let Some(r) = &self.callers {
+            out.insert(
+                "callers".into(),
+                json!({ "callers": r.entry_points, "paths": r.total_paths }),
+            );
+        }
+        if let Some(r) = &self.callees {
+            out.insert("callees".into(), json!({ "total": r.total_paths }));
+        }
+        if let Some(r) = &self.call_hierarchy {
+            out.insert("call_hierarchy".into(), json!({ "nodes": r.total_nodes }));
+        }
+        if let Some(r) = &self.neighborhood {
+            out.insert(
+                "neighborhood".into(),
+                json!({ "callers": r.callers.len(), "callees": r.callees.len() }),
+            );
+        }
+        if let Some(r) = &self.call_path {
+            let length = r.invocation_paths.first().map_or(0, |p| p.chain.len());
+            out.insert(
+                "call_path".into(),
+                json!({ "found": r.total_paths > 0, "length": length }),
+            );
+        }
+        if let Some(r) = &self.type_usage {
+            out.insert("type_usage".into(), json!({ "total": r.total_refs }));
+        }
+        if let Some(r) = &self.module {
+            out.insert(
+                "module".into(),
+                json!({
+                    "exports": r.exports.len(),
+                    "imported_by": r.imported_by.len(),
+                    "dependencies": r.dependencies.len(),
+                }),
+            );
+        }
+        if let Some(r) = &self.pattern {
+            out.insert(
+                "pattern".into(),
+                json!({ "total": r.total_matches, "files": r.by_file.len() }),
+            );
+        }
+        if let Some(r) = &self.flow {
+            let steps: usize = r.flow_paths.iter().map(|p| p.len()).sum();
+            out.insert(
+                "flow".into(),
+                json!({ "paths": r.flow_paths.len(), "steps": steps }),
+            );
+        }
+        if let Some(r) = &self.impact {
+            out.insert(
+                "impact".into(),
+                json!({
+                    "direct_callers": r.direct_callers.len(),
+                    "transitive_callers": r.transitive_callers.len(),
+                    "entry_points": r.affected_entry_points.len(),
+                    "risk": r.risk_level.to_string(),
+                }),
+            );
+        }
+        if let Some(r) = &self.scope {
+            out.insert(
+                "scope".into(),
+                json!({
+                    "variables": r.local_variables.len(),
+                    "imports": r.imports.len(),
+                }),
+            );
+        }
+        if let Some(r) = &self.dead_code {
+            out.insert("dead".into(), json!({ "total": r.total_dead }));
+        }
+        if let Some(r) = &self.deprecated {
+            out.insert(
+                "deprecated".into(),
+                json!({ "total": r.total_deprecated, "usages": r.total_usages }),
+            );
+        }
+        if let Some(r) = &self.unused_params {
+            out.insert("unused_params".into(), json!({ "total": r.total }));
+        }
+        if let Some(r) = &self.shadows {
+            out.insert("shadows".into(), json!({ "total": r.total }));
+        }
+        if let Some(r) = &self.stubs {
+            out.insert("stubs".into(), json!({ "total": r.total }));
+        }
+        if let Some(r) = &self.hotspots {
+            out.insert(
+                "hotspots".into(),
+                json!({ "files": r.files.len(), "churn": r.churn_available }),
+            );
+        }
+        if let Some(r) = &self.stats {
+            out.insert(
+                "stats".into(),
+                json!({
+                    "files": r.total_files,
+                    "symbols": r.total_symbols,
+                    "references": r.total_references,
+                    "edges": r.total_edges,
+                }),
+            );
+        }
+        if let Some(r) = &self.cycles {
+            out.insert("cycles".into(), json!({ "total": r.circular_deps.len() }));
+        }
+        if let Some(edges) = &self.edges {
+            out.insert("edges".into(), json!({ "total": edges.len() }));
+        }
+        serde_json::Value::Object(out)
     }
 }
 
@@ -102,13 +252,21 @@ fn reference_kind_str(kind: ReferenceKind) -> &'static str {
 #[command(after_help = "EXAMPLES:
     greppy trace validateUser              Trace invocation paths
     greppy trace -d validateUser           Direct mode (no AI reranking)
+    greppy trace Server::handle            Only the handle method of Server
+    greppy trace \"handle*\"                 Every symbol whose name starts with handle
+    greppy trace handle --interactive      Pick one definition when several share the name
+    greppy trace handle --no-merge-definitions  One section per definition
     greppy trace --refs userId             Find all references
     greppy trace --refs userId -c 2        Find refs with 2 lines context
     greppy trace --refs userId --in src/   Limit to src/ directory
+    greppy trace --dead --in src --in lib  Limit to several directories
     greppy trace --reads userId            Find reads only
     greppy trace --writes userId           Find writes only
     greppy trace --callers fetchData       Show what calls this
+    greppy trace --call-hierarchy fetchData  Callers as a tree (shared subtrees shown once)
     greppy trace --callees fetchData       Show what this calls
+    greppy trace --neighborhood fetchData  Direct callers + callees together
+    greppy trace --path main saveUser      Shortest call path between two symbols
     greppy trace --type UserProfile        Trace type usage
     greppy trace --module @/lib/auth       Trace module imports/exports
     greppy trace --pattern \"TODO:.*\"       Find pattern occurrences
@@ -117,13 +275,23 @@ fn reference_kind_str(kind: ReferenceKind) -> &'static str {
     greppy trace --scope src/api.ts:42     Show scope at location
     greppy trace --dead                    Find unused code
     greppy trace --dead --xref             Dead code with potential callers
+    greppy trace --deprecated              Deprecated symbols and their usages
+    greppy trace --refs oldApi --deprecated-only  Only references to deprecated symbols
+    greppy trace --unused-params           Function parameters never read
+    greppy trace --shadows                 Variables shadowing an outer binding
+    greppy trace --stubs                   Functions with empty or todo!()-style bodies
+    greppy trace --hotspots                Files both heavily referenced and often changed
     greppy trace --stats                   Show codebase statistics
     greppy trace --cycles                  Find circular dependencies
+    greppy trace --cycles --algorithm dfs  Individual cycle paths instead of clusters
+    greppy trace --cycles --imports        Import cycles (module A imports B imports A)
 
 COMPOSABLE FLAGS (run multiple operations at once):
     greppy trace --dead --stats            Dead code + statistics
+    greppy trace --stats --with-dates      Statistics + least recently modified symbols
     greppy trace --dead --stats --in src/  Filtered to src/ directory
     greppy trace --dead --stats --summary  Condensed one-line summaries
+    greppy trace --dead --stats --summary --json  Headline numbers as one JSON object
     greppy trace --refs foo --impact foo   References + impact analysis
     greppy trace --dead --cycles           Dead code + circular deps
 
@@ -131,16 +299,32 @@ FILTERING:
     greppy trace --dead --in src/auth      Filter to path
     greppy trace --dead --symbol-type fn   Filter by type (fn, struct, etc)
     greppy trace --dead --name \"test.*\"    Filter by name pattern
+    greppy trace --dead --exclude-kind constant,type_alias  Skip kinds
+    greppy trace --dead --visibility public  Unused public API
+    greppy trace --refs log --limit-per-file 3  At most 3 refs per file
+    greppy trace --refs id --min-confidence 1   Only AST-resolved references
+    greppy trace --refs id --in app.ts --after-line 200  Refs from line 200 on
+    greppy trace --refs fetchUser --follow-rename v1.0..HEAD  Include pre-rename names
+    greppy trace --refs oldApi --transitive --max-depth 2  Also refs to its wrappers
+    greppy trace --refs charge --exclude-comments     Skip mentions in comments/strings
+    greppy trace --refs login --filter-expr \"kind=call and not name~test\"  Boolean filter over kind, file, name
 
 OUTPUT FORMATS:
     greppy trace --refs userId --json      JSON output for tooling
+    greppy trace --refs userId --format lsp  LSP Location[] for editor extensions
     greppy trace --dead --stats --json     Combined JSON for multi-op
+    greppy trace --pattern TODO --json --stream  Write matches as they are found
+    greppy trace --pattern \"dbg!\" -q        CI check: stop at the first match, exit 1 if none
     greppy trace --refs userId --plain     Plain text (no colors)
     greppy trace --refs userId --csv       CSV output
+    greppy trace --refs userId --csv --no-header  CSV rows only (for appending)
     greppy trace --refs userId --dot       DOT graph format
-    greppy trace --refs userId --markdown  Markdown output")]
+    greppy trace --refs userId --markdown  Markdown output
+    greppy trace --refs userId --output-edges  Raw caller/callee edge list (CSV)
+    greppy trace --dead --ascii-safe       No Unicode box-drawing (or NO_UNICODE=1)")]
 pub struct TraceArgs {
-    /// Symbol to trace (function, class, method, variable)
+    /// Symbol to trace (function, class, method, variable); `*` and `?`
+    /// match any symbols with a fitting name
     pub symbol: Option<String>,
 
     /// Direct mode (no AI reranking)
@@ -167,6 +351,22 @@ pub struct TraceArgs {
     #[arg(long, value_name = "SYMBOL")]
     pub callees: Option<String>,
 
+    /// With --callees, show a call tail shared by several paths only once
+    #[arg(long, requires = "callees")]
+    pub dedupe: bool,
+
+    /// Show callers of this symbol as a tree, up to --max-depth levels
+    #[arg(long, value_name = "SYMBOL")]
+    pub call_hierarchy: Option<String>,
+
+    /// Show direct callers and callees of a symbol together
+    #[arg(long, value_name = "SYMBOL")]
+    pub neighborhood: Option<String>,
+
+    /// Find the shortest call path from one symbol to another
+    #[arg(long = "path", num_args = 2, value_names = ["FROM", "TO"])]
+    pub call_path: Option<Vec<String>>,
+
     /// Trace type usage
     #[arg(long = "type", value_name = "TYPE")]
     pub type_name: Option<String>,
@@ -199,21 +399,54 @@ pub struct TraceArgs {
     #[arg(long)]
     pub xref: bool,
 
+    /// List deprecated symbols with their usage counts
+    #[arg(long)]
+    pub deprecated: bool,
+
+    /// List function parameters that are never read in the function body
+    #[arg(long)]
+    pub unused_params: bool,
+
+    /// List variable declarations that shadow a binding of an enclosing scope
+    #[arg(long)]
+    pub shadows: bool,
+
+    /// List functions whose body is empty or only a placeholder (`todo!()`, `pass`, ...)
+    #[arg(long)]
+    pub stubs: bool,
+
+    /// Rank files by references x git churn
+    #[arg(long)]
+    pub hotspots: bool,
+
     /// Show codebase statistics
     #[arg(long)]
     pub stats: bool,
 
+    /// Add each symbol's last-modified date (git blame of its definition
+    /// line) to --stats; slow on large repos
+    #[arg(long, requires = "stats")]
+    pub with_dates: bool,
+
     /// Find circular dependencies
     #[arg(long)]
     pub cycles: bool,
 
+    /// With --cycles: build the file graph from imports instead of calls
+    #[arg(long, requires = "cycles")]
+    pub imports: bool,
+
+    /// Cycle detection algorithm: tarjan (each cycle cluster once) or dfs (individual paths)
+    #[arg(long, value_name = "ALGO", default_value = "tarjan", value_parser = ["tarjan", "dfs"])]
+    pub algorithm: String,
+
     /// Filter by reference kind (read, write, call, type, import, export)
     #[arg(long, value_name = "KIND")]
     pub kind: Option<String>,
 
-    /// Limit search to path/directory
+    /// Limit search to path/directory (repeatable: --in src --in lib)
     #[arg(long, value_name = "PATH")]
-    pub r#in: Option<PathBuf>,
+    pub r#in: Vec<PathBuf>,
 
     /// Filter by symbol type (function, method, class, variable, type, interface)
     #[arg(long, value_name = "TYPE")]
@@ -223,10 +456,31 @@ pub struct TraceArgs {
     #[arg(long, value_name = "PATTERN")]
     pub name: Option<String>,
 
-    /// Group results by (file, kind, scope)
+    /// With --refs: keep references matching an expression over kind, file
+    /// and name (e.g. "kind=call and file~src/auth and not name~test")
+    #[arg(long, alias = "filter", value_name = "EXPR", requires = "refs")]
+    pub filter_expr: Option<FilterExpr>,
+
+    /// Exclude symbol kinds (comma-separated, e.g. constant,type_alias)
+    #[arg(long, value_name = "KINDS", value_delimiter = ',')]
+    pub exclude_kind: Vec<String>,
+
+    /// Only symbols with this visibility (public, internal, protected, private)
+    #[arg(long, value_parser = ["public", "internal", "protected", "private"])]
+    pub visibility: Option<String>,
+
+    /// Count vendored dependencies (node_modules, vendor, ...) in --dead and --stats
+    #[arg(long)]
+    pub include_vendor: bool,
+
+    /// Group results by (file, kind, scope, directory)
     #[arg(long, value_name = "GROUP")]
     pub group_by: Option<String>,
 
+    /// Number of leading path components kept when grouping by directory
+    #[arg(long, value_name = "N", default_value = "2")]
+    pub group_depth: usize,
+
     /// Output as JSON
     #[arg(long)]
     pub json: bool,
@@ -247,26 +501,156 @@ pub struct TraceArgs {
     #[arg(long)]
     pub markdown: bool,
 
+    /// With --markdown, emit only the references table (no title or summary)
+    #[arg(long, alias = "markdown-table-only", requires = "markdown")]
+    pub table_only: bool,
+
+    /// Output in an editor protocol's shape: `lsp` prints --refs as an LSP
+    /// `Location[]` (file:// URIs, zero-based lines)
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        value_parser = ["lsp"],
+        requires = "refs",
+        conflicts_with_all = ["json", "csv", "dot", "markdown", "count", "summary", "stream"]
+    )]
+    pub format: Option<String>,
+
+    /// With --csv or --output-edges, omit the header row (for appending runs to one file)
+    #[arg(long)]
+    pub no_header: bool,
+
+    /// Use pure-ASCII connectors instead of Unicode box-drawing (also: NO_UNICODE=1)
+    #[arg(long)]
+    pub ascii_safe: bool,
+
     /// Interactive TUI mode
     #[arg(long)]
     pub tui: bool,
 
-    /// Maximum trace depth
-    #[arg(long, default_value = "10")]
-    pub max_depth: usize,
+    /// Maximum trace depth (default 10; the hop limit for --refs
+    /// --transitive, default 2)
+    #[arg(long)]
+    pub max_depth: Option<usize>,
+
+    /// When the symbol has several definitions, pick one from a list (TTY only)
+    #[arg(long)]
+    pub interactive: bool,
+
+    /// When the symbol has several definitions, trace only the first (by file and line)
+    #[arg(long, conflicts_with = "all")]
+    pub first: bool,
+
+    /// When the symbol has several definitions, trace them all together (default)
+    #[arg(long)]
+    pub all: bool,
+
+    /// Combine results for every definition of the symbol (default)
+    #[arg(long, overrides_with = "no_merge_definitions")]
+    pub merge_definitions: bool,
+
+    /// Report each definition of the symbol in its own section
+    #[arg(long, overrides_with = "merge_definitions")]
+    pub no_merge_definitions: bool,
 
     /// Lines of code context to show (before and after)
     #[arg(long, short = 'c', default_value = "0")]
     pub context: u32,
 
+    /// For refs, show the whole enclosing statement instead of fixed lines
+    /// (tree-sitter languages; others fall back to --context)
+    #[arg(long)]
+    pub context_lang_aware: bool,
+
+    /// For refs, include a content hash of each file so cached results can
+    /// be checked for staleness
+    #[arg(long, requires = "refs")]
+    pub with_hashes: bool,
+
     /// Maximum number of results to show
     #[arg(long, short = 'n')]
     pub limit: Option<usize>,
 
+    /// Safety cap on pattern matches; scanning stops here even without --limit
+    #[arg(long, default_value = "10000")]
+    pub max_results: usize,
+
+    /// Worker threads for pattern scanning (default: all cores, 1 = sequential)
+    #[arg(long, value_name = "N")]
+    pub workers: Option<usize>,
+
+    /// Check mode: stop --pattern at the first match and exit 6 if found, 0 if not
+    #[arg(short = 'q', long, requires = "pattern", conflicts_with = "stream")]
+    pub first_match_only: bool,
+
+    /// Only show references at or after this line (combine with --in to pick the file)
+    #[arg(long, alias = "since-line", value_name = "LINE")]
+    pub after_line: Option<u32>,
+
+    /// Maximum references to show per file, applied before --limit
+    #[arg(long)]
+    pub limit_per_file: Option<usize>,
+
+    /// Write --refs/--pattern JSON as results are found instead of building
+    /// it in memory (references are not sorted)
+    #[arg(long, requires = "json", conflicts_with = "follow_rename")]
+    pub stream: bool,
+
+    /// Emit the raw call edges around the symbol (from_id,to_id,from_name,to_name)
+    /// instead of formatted results; CSV unless --json (with several operations,
+    /// under `edges` in the combined document). Respects --in and vendor filtering
+    #[arg(long)]
+    pub output_edges: bool,
+
+    /// For refs passed to a call, note the callee and argument position
+    /// (tree-sitter languages)
+    #[arg(long)]
+    pub arg_positions: bool,
+
+    /// Include each reference's byte offset in its file (JSON output)
+    #[arg(long)]
+    pub byte_offsets: bool,
+
+    /// Flag references whose target symbol is deprecated
+    #[arg(long)]
+    pub annotate_deprecated: bool,
+
+    /// Keep only references to deprecated symbols (implies --annotate-deprecated)
+    #[arg(long)]
+    pub deprecated_only: bool,
+
+    /// Drop references below this confidence (1.0 = AST-resolved only)
+    #[arg(long, default_value = "0.0")]
+    pub min_confidence: f32,
+
+    /// Also report references under names the symbol had before being
+    /// renamed in this git range (e.g. v1.0..HEAD)
+    #[arg(long, value_name = "RANGE", requires = "refs")]
+    pub follow_rename: Option<String>,
+
+    /// Also report references to the definitions that reference it, out to
+    /// --max-depth hops, 2 by default (each reference is labeled with its depth)
+    #[arg(long, requires = "refs", conflicts_with_all = ["follow_rename", "stream"])]
+    pub transitive: bool,
+
+    /// Drop references that fall inside a comment or string literal
+    #[arg(long, requires = "refs")]
+    pub exclude_comments: bool,
+
     /// Show only counts, not full results
     #[arg(long)]
     pub count: bool,
 
+    /// Breakdowns to print with --count (comma-separated: kind, file)
+    #[arg(
+        long,
+        value_name = "GROUPS",
+        value_delimiter = ',',
+        value_parser = ["kind", "file"],
+        requires = "count"
+    )]
+    pub by: Vec<String>,
+
     /// Summary mode: condensed output for multi-op commands
     #[arg(long)]
     pub summary: bool,
@@ -294,6 +678,36 @@ impl TraceArgs {
         }
     }
 
+    /// Determine CSV formatter settings from args
+    fn csv_config(&self) -> CsvConfig {
+        CsvConfig {
+            no_header: self.no_header,
+        }
+    }
+
+    /// How to narrow a symbol name with several definitions
+    ///
+    /// `--interactive` only prompts when stdin and stderr are terminals;
+    /// otherwise `--first` / `--all` decide.
+    fn definition_choice(&self) -> DefinitionChoice {
+        if self.interactive && io::stdin().is_terminal() && io::stderr().is_terminal() {
+            DefinitionChoice::Interactive
+        } else if self.first {
+            DefinitionChoice::First
+        } else {
+            DefinitionChoice::All
+        }
+    }
+
+    /// Determine ASCII formatter settings from args and the NO_UNICODE env var
+    fn ascii_config(&self) -> AsciiConfig {
+        let no_unicode = env::var("NO_UNICODE").is_ok_and(|v| !v.is_empty() && v != "0");
+        AsciiConfig {
+            ascii_safe: self.ascii_safe || no_unicode,
+            no_color: !color::should_color(io::stdout().is_terminal()),
+        }
+    }
+
     /// Get all operations to perform (supports composable flags)
     fn operations(&self) -> Vec<TraceOperation> {
         let mut ops = Vec::new();
@@ -302,6 +716,21 @@ impl TraceArgs {
         if self.dead {
             ops.push(TraceOperation::DeadCode);
         }
+        if self.deprecated {
+            ops.push(TraceOperation::Deprecated);
+        }
+        if self.unused_params {
+            ops.push(TraceOperation::UnusedParams);
+        }
+        if self.shadows {
+            ops.push(TraceOperation::Shadows);
+        }
+        if self.stubs {
+            ops.push(TraceOperation::Stubs);
+        }
+        if self.hotspots {
+            ops.push(TraceOperation::Hotspots);
+        }
         if self.stats {
             ops.push(TraceOperation::Stats);
         }
@@ -334,6 +763,18 @@ impl TraceArgs {
         if let Some(ref sym) = self.callees {
             ops.push(TraceOperation::Callees(sym.clone()));
         }
+        if let Some(ref sym) = self.call_hierarchy {
+            ops.push(TraceOperation::CallHierarchy(sym.clone()));
+        }
+        if let Some(ref sym) = self.neighborhood {
+            ops.push(TraceOperation::Neighborhood(sym.clone()));
+        }
+        if let Some([from, to]) = self.call_path.as_deref() {
+            ops.push(TraceOperation::Path {
+                from: from.clone(),
+                to: to.clone(),
+            });
+        }
         if let Some(ref sym) = self.reads {
             ops.push(TraceOperation::Refs {
                 symbol: sym.clone(),
@@ -375,78 +816,78 @@ impl TraceArgs {
     }
 }
 
-/// The trace operation to perform
-#[derive(Debug)]
-enum TraceOperation {
-    Trace(String),
-    Refs {
-        symbol: String,
-        kind: Option<ReferenceKind>,
-    },
-    Callers(String),
-    Callees(String),
-    Type(String),
-    Module(String),
-    Pattern(String),
-    Flow(String),
-    Impact(String),
-    Scope(String),
-    DeadCode,
-    Stats,
-    Cycles,
-}
-
-/// Universal filter for trace operations
-#[derive(Debug, Clone, Default)]
-pub struct TraceFilter {
-    /// Filter by file/folder path (contains match)
-    pub path: Option<String>,
-    /// Filter by symbol type (function, method, class, etc.)
-    pub symbol_type: Option<String>,
-    /// Filter by name pattern (regex)
-    pub name_pattern: Option<regex::Regex>,
-}
-
-impl TraceFilter {
-    /// Check if a symbol passes the filter
-    pub fn matches_symbol(&self, name: &str, kind: &str, file_path: &str) -> bool {
-        // Path filter
-        if let Some(ref path) = self.path {
-            if !file_path.contains(path) {
-                return false;
-            }
-        }
-        // Symbol type filter
-        if let Some(ref stype) = self.symbol_type {
-            if !kind.to_lowercase().contains(&stype.to_lowercase()) {
-                return false;
-            }
-        }
-        // Name pattern filter
-        if let Some(ref pattern) = self.name_pattern {
-            if !pattern.is_match(name) {
-                return false;
-            }
-        }
-        true
+impl Default for TraceArgs {
+    /// The flags of a bare `greppy trace`
+    fn default() -> Self {
+        let command = TraceArgs::augment_args(clap::Command::new("trace"));
+        TraceArgs::from_arg_matches(&command.get_matches_from(["trace"]))
+            .expect("trace arguments have defaults")
     }
+}
 
-    /// Check if a file path passes the filter
-    pub fn matches_path(&self, file_path: &str) -> bool {
-        if let Some(ref path) = self.path {
-            return file_path.contains(path);
+impl TraceArgs {
+    /// Depth limit for the selected operation
+    pub fn max_depth(&self) -> usize {
+        match self.max_depth {
+            Some(depth) => depth,
+            None if self.transitive => TRANSITIVE_DEFAULT_DEPTH,
+            None => DEFAULT_MAX_DEPTH,
+        }
+    }
+
+    /// Settings for running the selected operations
+    pub fn operation_options(&self) -> OperationOptions {
+        OperationOptions {
+            max_depth: self.max_depth(),
+            direct: self.direct,
+            definition: self.definition_choice(),
+            merge_definitions: !self.no_merge_definitions,
+            dedupe: self.dedupe,
+            limit: self.limit,
+            xref: self.xref,
+            with_dates: self.with_dates,
+            algorithm: self.algorithm.clone(),
+            imports: self.imports,
+            context: self.context,
+            context_lang_aware: self.context_lang_aware,
+            with_hashes: self.with_hashes,
+            max_results: self.max_results,
+            workers: self.workers,
+            first_match_only: self.first_match_only,
+            after_line: self.after_line,
+            limit_per_file: self.limit_per_file,
+            group_by: self.group_by.clone(),
+            group_depth: self.group_depth,
+            arg_positions: self.arg_positions,
+            byte_offsets: self.byte_offsets,
+            annotate_deprecated: self.annotate_deprecated,
+            deprecated_only: self.deprecated_only,
+            min_confidence: self.min_confidence,
+            follow_rename: self.follow_rename.clone(),
+            transitive: self.transitive,
+            exclude_comments: self.exclude_comments,
         }
-        true
     }
-}
 
-impl TraceArgs {
     /// Build a universal filter from args
     pub fn build_filter(&self) -> TraceFilter {
         TraceFilter {
-            path: self.r#in.as_ref().map(|p| p.to_string_lossy().to_string()),
+            paths: self
+                .r#in
+                .iter()
+                .map(|p| p.to_string_lossy().to_string())
+                .collect(),
             symbol_type: self.symbol_type.clone(),
             name_pattern: self.name.as_ref().and_then(|p| regex::Regex::new(p).ok()),
+            exclude_kinds: self
+                .exclude_kind
+                .iter()
+                .map(|k| k.trim().to_lowercase())
+                .filter(|k| !k.is_empty())
+                .collect(),
+            exclude_vendor: !self.include_vendor,
+            visibility: self.visibility.as_deref().and_then(Visibility::parse),
+            expr: self.filter_expr.clone(),
         }
     }
 }
@@ -457,14 +898,13 @@ impl TraceArgs {
 
 /// Run the trace command
 pub async fn run(args: TraceArgs) -> Result<()> {
-    let project_path = args
-        .project
-        .clone()
-        .unwrap_or_else(|| env::current_dir().expect("Failed to get current directory"));
-
-    let project = Project::detect(&project_path)?;
+    let project = resolve_project(args.project.clone())?;
     let format = args.output_format();
-    let formatter = create_formatter(format);
+    let ascii_config = args.ascii_config();
+    let markdown_config = MarkdownConfig {
+        table_only: args.table_only,
+    };
+    let formatter = create_formatter(format, ascii_config, markdown_config, args.csv_config());
 
     // Check for TUI mode
     if args.tui {
@@ -475,8 +915,9 @@ pub async fn run(args: TraceArgs) -> Result<()> {
     let operations = args.operations();
     debug!(?operations, "Trace operations");
 
-    // Build universal filter from args
+    // Build universal filter and operation settings from args
     let filter = args.build_filter();
+    let options = args.operation_options();
 
     // No operations specified
     if operations.is_empty() {
@@ -491,1989 +932,617 @@ pub async fn run(args: TraceArgs) -> Result<()> {
         });
     }
 
+    if args.output_edges {
+        if let Some(operation) = operations.iter().find(|op| edge_selection(op).is_none()) {
+            return Err(Error::Usage {
+                message: format!(
+                    "--output-edges needs a symbol operation (trace, --refs, --callers, --callees, --hierarchy or --neighborhood), not {}",
+                    operation_header(operation)
+                ),
+            });
+        }
+    }
+
+    // Every operation works on the same index: load it once
+    let index = load_semantic_index(&project)?;
+
     let multi_op = operations.len() > 1;
     let summary_mode = args.summary;
-    let json_multi_op = args.json && multi_op;
+    // Multi-op JSON and --summary --json collect results into one document
+    let json_combined = args.json && (multi_op || summary_mode);
 
-    // For JSON multi-op mode, collect results into combined struct
+    // For combined JSON mode, collect results into combined struct
     let mut combined = CombinedResults::default();
+    // Pattern that matched in check mode (--first-match-only), reported as exit 6
+    let mut check_found = None;
 
     // Execute each operation
     for (i, operation) in operations.iter().enumerate() {
         // Print section header for multi-operation mode or summary mode (not for JSON)
-        if (multi_op || summary_mode) && !json_multi_op {
+        if (multi_op || summary_mode) && !json_combined {
             if i > 0 {
                 println!();
             }
             let header = operation_header(operation);
-            println!("{}", "═".repeat(79));
+            let rule = if ascii_config.ascii_safe { "=" } else { "═" };
+            println!("{}", rule.repeat(79));
             println!("{}", header);
-            println!("{}", "═".repeat(79));
+            println!("{}", rule.repeat(79));
         }
 
-        match operation {
-            TraceOperation::Trace(symbol) => {
-                info!(symbol = %symbol, "Tracing symbol invocations");
-                let result =
-                    trace_symbol_cmd(&project, symbol, args.max_depth, args.direct, &filter)
-                        .await?;
-                if json_multi_op {
-                    combined.trace = Some(result);
-                } else if summary_mode {
-                    println!(
-                        "  Paths: {}  Entry points: {}",
-                        result.invocation_paths.len(),
-                        result.entry_points
-                    );
-                } else {
-                    println!("{}", formatter.format_trace(&result));
-                }
-            }
-            TraceOperation::Refs { symbol, kind } => {
-                info!(symbol = %symbol, ?kind, "Finding references");
-                let result = find_refs_cmd(&project, symbol, *kind, &args, &filter).await?;
-                if json_multi_op {
-                    combined.refs = Some(result);
-                } else if args.count || summary_mode {
-                    println!(
-                        "  References: {}  Files: {}",
-                        result.total_refs,
-                        result.by_file.len()
-                    );
-                } else {
-                    println!("{}", formatter.format_refs(&result));
-                }
-            }
-            TraceOperation::Callers(symbol) => {
-                info!(symbol = %symbol, "Finding callers");
-                let result = find_callers_cmd(&project, symbol, args.max_depth, &filter).await?;
-                if json_multi_op {
-                    combined.callers = Some(result);
-                } else if summary_mode {
-                    println!(
-                        "  Callers: {}  Paths: {}",
-                        result.entry_points,
-                        result.invocation_paths.len()
-                    );
-                } else {
-                    println!("{}", formatter.format_trace(&result));
-                }
-            }
-            TraceOperation::Callees(symbol) => {
-                info!(symbol = %symbol, "Finding callees");
-                let result = find_callees_cmd(&project, symbol, args.max_depth, &filter).await?;
-                if json_multi_op {
-                    combined.callees = Some(result);
-                } else if summary_mode {
-                    println!("  Callees: {}", result.invocation_paths.len());
-                } else {
-                    println!("{}", formatter.format_trace(&result));
-                }
-            }
-            TraceOperation::Type(type_name) => {
-                info!(type_name = %type_name, "Tracing type usage");
-                let result = find_refs_cmd(
-                    &project,
-                    type_name,
-                    Some(ReferenceKind::TypeAnnotation),
-                    &args,
-                    &filter,
-                )
-                .await?;
-                if json_multi_op {
-                    combined.type_usage = Some(result);
-                } else if summary_mode {
-                    println!("  Type usages: {}", result.total_refs);
-                } else {
-                    println!("{}", formatter.format_refs(&result));
-                }
-            }
-            TraceOperation::Module(module) => {
-                info!(module = %module, "Tracing module");
-                let result = trace_module_cmd(&project, module, &filter).await?;
-                if json_multi_op {
-                    combined.module = Some(result);
-                } else if summary_mode {
-                    println!(
-                        "  Exports: {}  Imported by: {}  Deps: {}",
-                        result.exports.len(),
-                        result.imported_by.len(),
-                        result.dependencies.len()
-                    );
-                } else {
-                    println!("{}", formatter.format_module(&result));
-                }
-            }
-            TraceOperation::Pattern(pattern) => {
-                info!(pattern = %pattern, "Tracing pattern");
-                let result = trace_pattern_cmd(&project, pattern, &args, &filter).await?;
-                if json_multi_op {
-                    combined.pattern = Some(result);
-                } else if summary_mode {
-                    println!(
-                        "  Matches: {}  Files: {}",
-                        result.total_matches,
-                        result.by_file.len()
-                    );
-                } else {
-                    println!("{}", formatter.format_pattern(&result));
-                }
-            }
-            TraceOperation::Flow(symbol) => {
-                info!(symbol = %symbol, "Tracing data flow");
-                let result = trace_flow_cmd(&project, symbol, &args, &filter).await?;
-                if json_multi_op {
-                    combined.flow = Some(result);
-                } else if summary_mode {
-                    let total_steps: usize = result.flow_paths.iter().map(|p| p.len()).sum();
-                    println!(
-                        "  Flow paths: {}  Steps: {}",
-                        result.flow_paths.len(),
-                        total_steps
-                    );
-                } else {
-                    println!("{}", formatter.format_flow(&result));
-                }
-            }
-            TraceOperation::Impact(symbol) => {
-                info!(symbol = %symbol, "Analyzing impact");
-                let result = analyze_impact_cmd(&project, symbol, args.max_depth, &filter).await?;
-                if json_multi_op {
-                    combined.impact = Some(result);
-                } else if summary_mode {
-                    println!(
-                        "  Direct callers: {}  Transitive: {}  Entry points: {}  Risk: {:?}",
-                        result.direct_callers.len(),
-                        result.transitive_callers.len(),
-                        result.affected_entry_points.len(),
-                        result.risk_level
-                    );
-                } else {
-                    println!("{}", formatter.format_impact(&result));
-                }
-            }
-            TraceOperation::Scope(location) => {
-                info!(location = %location, "Analyzing scope");
-                let result = analyze_scope_cmd(&project, location, &filter).await?;
-                if json_multi_op {
-                    combined.scope = Some(result);
-                } else if summary_mode {
-                    println!(
-                        "  Scope: {}  Variables: {}  Imports: {}",
-                        result.enclosing_scope.as_deref().unwrap_or("global"),
-                        result.local_variables.len(),
-                        result.imports.len()
-                    );
-                } else {
-                    println!("{}", formatter.format_scope(&result));
+        if let Some((symbol, incoming, outgoing)) =
+            edge_selection(operation).filter(|_| args.output_edges)
+        {
+            let edges = collect_symbol_edges(&index, symbol, incoming, outgoing, &filter);
+            if json_combined {
+                let all = combined.edges.get_or_insert_with(Vec::new);
+                for edge in edges {
+                    if !all.contains(&edge) {
+                        all.push(edge);
+                    }
                 }
+            } else {
+                println!("{}", format_edge_list(&edges, args.json, args.no_header));
             }
-            TraceOperation::DeadCode => {
-                info!("Finding dead code");
-                let result = find_dead_code_cmd(&project, args.limit, &filter, args.xref).await?;
-                if json_multi_op {
-                    combined.dead_code = Some(result);
-                } else if args.count || summary_mode {
-                    let kinds: Vec<_> = result
-                        .by_kind
-                        .iter()
-                        .map(|(k, v)| format!("{}={}", k, v))
-                        .collect();
-                    println!(
-                        "  Dead symbols: {}  ({})",
-                        result.total_dead,
-                        kinds.join(", ")
-                    );
-                } else {
-                    println!("{}", formatter.format_dead_code(&result));
-                }
+            continue;
+        }
+
+        // Streaming writes results as they are found instead of returning them
+        match operation {
+            TraceOperation::Refs { symbol, kind }
+                if args.stream && !json_combined && !args.count =>
+            {
+                stream_refs_cmd(&project, &index, symbol, *kind, &options, &filter).await?;
+                continue;
             }
-            TraceOperation::Stats => {
-                info!("Computing statistics");
-                let result = compute_stats_cmd(&project, &filter).await?;
-                if json_multi_op {
-                    combined.stats = Some(result);
-                } else if summary_mode {
-                    println!(
-                        "  Files: {}  Symbols: {}  Refs: {}  Edges: {}",
-                        result.total_files,
-                        result.total_symbols,
-                        result.total_references,
-                        result.total_edges
-                    );
-                } else {
-                    println!("{}", formatter.format_stats(&result));
-                }
+            TraceOperation::Pattern(pattern) if args.stream && !json_combined => {
+                stream_pattern_cmd(&project, &index, pattern, &options, &filter).await?;
+                continue;
             }
-            TraceOperation::Cycles => {
-                info!("Finding circular dependencies");
-                let result = find_cycles_cmd(&project, &filter).await?;
-                if json_multi_op {
-                    combined.cycles = Some(result);
-                } else if summary_mode {
-                    println!("  Circular deps: {}", result.circular_deps.len());
-                } else {
-                    println!("{}", formatter.format_module(&result));
-                }
+            _ => {}
+        }
+
+        let result = match run_operation(&project, &index, operation, &options, &filter).await {
+            // A clean check is a success, not a failure
+            Err(Error::NoMatch { .. }) if args.first_match_only => continue,
+            result => result?,
+        };
+        if let OperationResult::Pattern(found) = &result {
+            if args.first_match_only {
+                check_found = Some(found.pattern.clone());
             }
         }
+        if json_combined {
+            combined.insert(result);
+        } else {
+            print!(
+                "{}",
+                render_operation(&result, &args, formatter.as_ref(), format, &project.root)
+            );
+        }
     }
 
-    // Output combined JSON for multi-op JSON mode
-    if json_multi_op {
+    // Output combined JSON: compact headline numbers in summary mode, full results otherwise
+    if json_combined {
+        let output = if summary_mode {
+            serde_json::to_string(&combined.summary())
+        } else {
+            serde_json::to_string_pretty(&combined)
+        };
         println!(
             "{}",
-            serde_json::to_string_pretty(&combined)
-                .unwrap_or_else(|e| format!("{{\"error\": \"{}\"}}", e))
+            output.unwrap_or_else(|e| format!("{{\"error\": \"{}\"}}", e))
         );
     }
 
-    Ok(())
+    match check_found {
+        Some(pattern) => Err(Error::CheckFound { pattern }),
+        None => Ok(()),
+    }
 }
 
-/// Generate header text for an operation in multi-op mode
-fn operation_header(op: &TraceOperation) -> String {
-    match op {
-        TraceOperation::Trace(s) => format!("TRACE: {}", s),
-        TraceOperation::Refs { symbol, kind } => {
-            if let Some(k) = kind {
-                format!("REFERENCES: {} ({:?})", symbol, k)
+/// Text printed for one operation: its headline numbers with --summary or
+/// --count, the formatted result otherwise
+fn render_operation(
+    result: &OperationResult,
+    args: &TraceArgs,
+    formatter: &dyn TraceFormatter,
+    format: OutputFormat,
+    root: &Path,
+) -> String {
+    let summary_mode = args.summary;
+    let output = match result {
+        OperationResult::Trace(result) => {
+            if summary_mode {
+                format!(
+                    "  Paths: {}  Entry points: {}",
+                    result.total_paths, result.entry_points
+                )
             } else {
-                format!("REFERENCES: {}", symbol)
+                format_trace_sections(formatter, format, result)
             }
         }
-        TraceOperation::Callers(s) => format!("CALLERS: {}", s),
-        TraceOperation::Callees(s) => format!("CALLEES: {}", s),
-        TraceOperation::Type(s) => format!("TYPE USAGE: {}", s),
-        TraceOperation::Module(s) => format!("MODULE: {}", s),
-        TraceOperation::Pattern(s) => format!("PATTERN: {}", s),
-        TraceOperation::Flow(s) => format!("DATA FLOW: {}", s),
-        TraceOperation::Impact(s) => format!("IMPACT ANALYSIS: {}", s),
-        TraceOperation::Scope(s) => format!("SCOPE: {}", s),
-        TraceOperation::DeadCode => "DEAD CODE ANALYSIS".to_string(),
-        TraceOperation::Stats => "CODEBASE STATISTICS".to_string(),
-        TraceOperation::Cycles => "CIRCULAR DEPENDENCIES".to_string(),
-    }
-}
-
-// =============================================================================
-// INDEX LOADING
-// =============================================================================
-
-/// Load the semantic index for a project
-fn load_semantic_index(project: &Project) -> Result<SemanticIndex> {
-    let index_path = trace_index_path(&project.root);
-
-    if !trace_index_exists(&project.root) {
-        return Err(Error::IndexError {
-            message: format!(
-                "Trace index not found. Run 'greppy index' first.\nExpected at: {}",
-                index_path.display()
-            ),
-        });
-    }
-
-    load_index(&index_path).map_err(|e| Error::IndexError {
-        message: format!("Failed to load trace index: {}", e),
-    })
-}
-
-// =============================================================================
-// PHASE 1: CODE CONTEXT ENGINE
-// =============================================================================
-
-/// Get code context for a reference
-fn get_code_context(cache: &mut FileCache, file: &Path, line: u32, context_lines: u32) -> String {
-    if context_lines == 0 {
-        // Just get the single line
-        cache
-            .get_line(file, line)
-            .map(|l| l.trim().to_string())
-            .unwrap_or_else(|| format!("// line {}", line))
-    } else {
-        // Get context with surrounding lines
-        cache
-            .get_context(file, line, context_lines, context_lines)
-            .map(|ctx| ctx.format(false))
-            .unwrap_or_else(|| format!("// line {}", line))
-    }
-}
-
-// =============================================================================
-// PHASE 2: COMPLETE REFERENCE SYSTEM
-// =============================================================================
-
-/// Find references to a symbol with full context
-async fn find_refs_cmd(
-    project: &Project,
-    symbol: &str,
-    kind_filter: Option<ReferenceKind>,
-    args: &TraceArgs,
-    filter: &TraceFilter,
-) -> Result<RefsResult> {
-    debug!(symbol = %symbol, ?kind_filter, ?filter, "find_refs");
-
-    let index = load_semantic_index(project)?;
-    let mut cache = FileCache::new(&project.root);
-
-    let mut references = Vec::new();
-    let mut by_kind: HashMap<String, usize> = HashMap::new();
-    let mut by_file: HashMap<String, usize> = HashMap::new();
-
-    // Find symbol IDs matching the name
-    let symbol_ids = index.symbols_by_name(symbol).cloned().unwrap_or_default();
-
-    // Get definition location from first matching symbol
-    let defined_at = symbol_ids.first().and_then(|&id| {
-        let sym = index.symbol(id)?;
-        let file = index.file_path(sym.file_id)?;
-        Some(format!("{}:{}", file.display(), sym.start_line))
-    });
-
-    // Get symbol kind
-    let symbol_kind = symbol_ids
-        .first()
-        .and_then(|&id| index.symbol(id))
-        .map(|s| symbol_kind_str(s.symbol_kind()).to_string());
-
-    // Find all references to all matching symbols (via Reference table)
-    for &sym_id in &symbol_ids {
-        let refs = find_refs(&index, sym_id);
-
-        for ref_ctx in refs {
-            // Convert RefKind to ReferenceKind
-            let kind = match ref_ctx.reference.ref_kind() {
-                RefKind::Read => ReferenceKind::Read,
-                RefKind::Write => ReferenceKind::Write,
-                RefKind::Call => ReferenceKind::Call,
-                RefKind::TypeAnnotation => ReferenceKind::TypeAnnotation,
-                RefKind::Import => ReferenceKind::Import,
-                RefKind::Export => ReferenceKind::Export,
-                RefKind::Construction => ReferenceKind::Call, // Treat construction as call-like
-                RefKind::Inheritance | RefKind::Decorator | RefKind::Unknown => ReferenceKind::Read,
-            };
-
-            // Apply kind filter
-            if let Some(filter_kind) = kind_filter {
-                if kind != filter_kind {
-                    continue;
-                }
+        OperationResult::Refs(result) => {
+            if args.format.as_deref() == Some("lsp") {
+                format_lsp_locations(result, root)
+            } else if args.count && !args.by.is_empty() {
+                // Already ends in a newline
+                return format_ref_counts(result, &args.by);
+            } else if args.count || summary_mode {
+                format!(
+                    "  References: {}  Files: {}",
+                    result.total_refs,
+                    result.by_file.len()
+                )
+            } else if !result.by_directory.is_empty() {
+                formatter.format_refs_by_directory(result)
+            } else {
+                formatter.format_refs(result)
             }
-
-            // Get file path
-            let file_path = index
-                .file_path(ref_ctx.file_id)
-                .map(|p| p.to_path_buf())
-                .unwrap_or_default();
-            let file = file_path.to_string_lossy().to_string();
-
-            // Apply universal filter (path, type, name)
-            if !filter.matches_path(&file) {
-                continue;
+        }
+        OperationResult::Callers(result) => {
+            if summary_mode {
+                format!(
+                    "  Callers: {}  Paths: {}",
+                    result.entry_points, result.total_paths
+                )
+            } else {
+                format_trace_sections(formatter, format, result)
             }
-
-            // Find enclosing symbol
-            let enclosing_symbol = find_enclosing_symbol(&index, ref_ctx.file_id, ref_ctx.line);
-
-            // Get code context
-            let context = get_code_context(&mut cache, &file_path, ref_ctx.line, args.context);
-
-            // Count by kind and file
-            *by_kind
-                .entry(reference_kind_str(kind).to_string())
-                .or_insert(0) += 1;
-            *by_file.entry(file.clone()).or_insert(0) += 1;
-
-            references.push(ReferenceInfo {
-                file,
-                line: ref_ctx.line,
-                column: ref_ctx.column,
-                kind,
-                context,
-                enclosing_symbol,
-            });
         }
-    }
-
-    // ALWAYS search tokens by name (catches variables, params, field names)
-    if let Some(token_ids) = index.tokens_by_name(symbol) {
-        for &token_id in token_ids {
-            if let Some(token) = index.token(token_id) {
-                let file_path = index
-                    .file_path(token.file_id)
-                    .map(|p| p.to_path_buf())
-                    .unwrap_or_default();
-                let file = file_path.to_string_lossy().to_string();
-
-                // Skip if we already have this location
-                let already_have = references
-                    .iter()
-                    .any(|r| r.file == file && r.line == token.line && r.column == token.column);
-
-                if already_have {
-                    continue;
-                }
-
-                // Apply path filter
-                if let Some(ref in_path) = args.r#in {
-                    if !file.contains(&in_path.to_string_lossy().to_string()) {
-                        continue;
-                    }
-                }
-
-                let kind = match token.token_kind() {
-                    crate::trace::TokenKind::Call => ReferenceKind::Call,
-                    _ => ReferenceKind::Read,
-                };
-
-                // Apply kind filter
-                if let Some(filter_kind) = kind_filter {
-                    if kind != filter_kind {
-                        continue;
-                    }
-                }
-
-                let enclosing_symbol = find_enclosing_symbol(&index, token.file_id, token.line);
-                let context = get_code_context(&mut cache, &file_path, token.line, args.context);
-
-                *by_kind
-                    .entry(reference_kind_str(kind).to_string())
-                    .or_insert(0) += 1;
-                *by_file.entry(file.clone()).or_insert(0) += 1;
-
-                references.push(ReferenceInfo {
-                    file,
-                    line: token.line,
-                    column: token.column,
-                    kind,
-                    context,
-                    enclosing_symbol,
-                });
+        OperationResult::Callees(result) => {
+            if summary_mode {
+                format!("  Callees: {}", result.total_paths)
+            } else {
+                format_trace_sections(formatter, format, result)
             }
         }
-    }
-
-    // Sort by file and line
-    references.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
-
-    // Apply limit
-    if let Some(limit) = args.limit {
-        references.truncate(limit);
-    }
-
-    Ok(RefsResult {
-        symbol: symbol.to_string(),
-        defined_at,
-        symbol_kind,
-        total_refs: references.len(),
-        references,
-        by_kind,
-        by_file,
-    })
-}
-
-/// Find the enclosing symbol for a given location
-fn find_enclosing_symbol(index: &SemanticIndex, file_id: u16, line: u32) -> Option<String> {
-    let mut best: Option<(&crate::trace::Symbol, u32)> = None;
-
-    for symbol in &index.symbols {
-        if symbol.file_id == file_id && symbol.start_line <= line && symbol.end_line >= line {
-            let size = symbol.end_line - symbol.start_line;
-            match best {
-                None => best = Some((symbol, size)),
-                Some((_, best_size)) if size < best_size => best = Some((symbol, size)),
-                _ => {}
+        OperationResult::CallHierarchy(result) => {
+            if summary_mode {
+                format!("  Call hierarchy nodes: {}", result.total_nodes)
+            } else {
+                formatter.format_call_hierarchy(result)
             }
         }
-    }
-
-    best.and_then(|(sym, _)| index.symbol_name(sym).map(|s| s.to_string()))
-}
-
-// =============================================================================
-// PHASE 3: CALL GRAPH WITH FULL PRECISION
-// =============================================================================
-
-/// Find what calls a symbol (callers/incoming)
-async fn find_callers_cmd(
-    project: &Project,
-    symbol: &str,
-    max_depth: usize,
-    filter: &TraceFilter,
-) -> Result<TraceResult> {
-    let _ = filter; // TODO: apply filter to results
-    debug!(symbol = %symbol, "find_callers");
-
-    let index = load_semantic_index(project)?;
-
-    let symbol_ids = index.symbols_by_name(symbol).cloned().unwrap_or_default();
-    if symbol_ids.is_empty() {
-        return Ok(TraceResult {
-            symbol: symbol.to_string(),
-            defined_at: None,
-            kind: "unknown".to_string(),
-            invocation_paths: Vec::new(),
-            total_paths: 0,
-            entry_points: 0,
-        });
-    }
-
-    let mut paths = Vec::new();
-    let mut visited = HashSet::new();
-
-    for &sym_id in &symbol_ids {
-        collect_callers_recursive(
-            &index,
-            sym_id,
-            &mut paths,
-            &mut visited,
-            Vec::new(),
-            max_depth,
-        );
-    }
-
-    let defined_at = symbol_ids.first().and_then(|&id| {
-        let sym = index.symbol(id)?;
-        let file = index.file_path(sym.file_id)?;
-        Some(format!("{}:{}", file.display(), sym.start_line))
-    });
-
-    let kind = symbol_ids
-        .first()
-        .and_then(|&id| index.symbol(id))
-        .map(|s| symbol_kind_str(s.symbol_kind()).to_string())
-        .unwrap_or_else(|| "function".to_string());
-
-    let entry_points = paths
-        .iter()
-        .map(|p| &p.entry_point)
-        .collect::<HashSet<_>>()
-        .len();
-
-    Ok(TraceResult {
-        symbol: symbol.to_string(),
-        defined_at,
-        kind,
-        invocation_paths: paths.clone(),
-        total_paths: paths.len(),
-        entry_points,
-    })
-}
-
-fn collect_callers_recursive(
-    index: &SemanticIndex,
-    sym_id: u32,
-    paths: &mut Vec<InvocationPath>,
-    visited: &mut HashSet<u32>,
-    current_chain: Vec<ChainStep>,
-    max_depth: usize,
-) {
-    if current_chain.len() >= max_depth {
-        return;
-    }
-
-    let callers = index.callers(sym_id);
-    if callers.is_empty() && !current_chain.is_empty() {
-        // End of chain - record path
-        if let Some(sym) = index.symbol(sym_id) {
-            let name = index.symbol_name(sym).unwrap_or("<unknown>");
-            let file = index
-                .file_path(sym.file_id)
-                .map(|p| p.to_string_lossy().to_string())
-                .unwrap_or_default();
-
-            let mut chain = current_chain.clone();
-            chain.push(ChainStep {
-                symbol: name.to_string(),
-                file: file.clone(),
-                line: sym.start_line,
-                column: None,
-                context: None,
-            });
-
-            paths.push(InvocationPath {
-                entry_point: format!("{} ({})", name, file),
-                entry_kind: symbol_kind_str(sym.symbol_kind()).to_string(),
-                chain,
-            });
-        }
-        return;
-    }
-
-    for &caller_id in callers {
-        if visited.contains(&caller_id) {
-            continue;
-        }
-        visited.insert(caller_id);
-
-        if let Some(caller) = index.symbol(caller_id) {
-            let name = index.symbol_name(caller).unwrap_or("<unknown>");
-            let file = index
-                .file_path(caller.file_id)
-                .map(|p| p.to_string_lossy().to_string())
-                .unwrap_or_default();
-
-            // Find call line from edge
-            let call_line = index
-                .edges
-                .iter()
-                .find(|e| e.from_symbol == caller_id && e.to_symbol == sym_id)
-                .map(|e| e.line)
-                .unwrap_or(caller.start_line);
-
-            let mut new_chain = current_chain.clone();
-            new_chain.push(ChainStep {
-                symbol: name.to_string(),
-                file,
-                line: call_line,
-                column: None,
-                context: None,
-            });
-
-            collect_callers_recursive(index, caller_id, paths, visited, new_chain, max_depth);
-        }
-    }
-}
-
-/// Find what a symbol calls (callees/outgoing)
-async fn find_callees_cmd(
-    project: &Project,
-    symbol: &str,
-    max_depth: usize,
-    filter: &TraceFilter,
-) -> Result<TraceResult> {
-    let _ = filter; // TODO: apply filter to results
-    debug!(symbol = %symbol, "find_callees");
-
-    let index = load_semantic_index(project)?;
-
-    let symbol_ids = index.symbols_by_name(symbol).cloned().unwrap_or_default();
-    if symbol_ids.is_empty() {
-        return Ok(TraceResult {
-            symbol: symbol.to_string(),
-            defined_at: None,
-            kind: "unknown".to_string(),
-            invocation_paths: Vec::new(),
-            total_paths: 0,
-            entry_points: 0,
-        });
-    }
-
-    let mut paths = Vec::new();
-
-    for &sym_id in &symbol_ids {
-        let mut visited = HashSet::new();
-        collect_callees_recursive(
-            &index,
-            sym_id,
-            &mut paths,
-            &mut visited,
-            Vec::new(),
-            max_depth,
-        );
-    }
-
-    let defined_at = symbol_ids.first().and_then(|&id| {
-        let sym = index.symbol(id)?;
-        let file = index.file_path(sym.file_id)?;
-        Some(format!("{}:{}", file.display(), sym.start_line))
-    });
-
-    let kind = symbol_ids
-        .first()
-        .and_then(|&id| index.symbol(id))
-        .map(|s| symbol_kind_str(s.symbol_kind()).to_string())
-        .unwrap_or_else(|| "function".to_string());
-
-    Ok(TraceResult {
-        symbol: symbol.to_string(),
-        defined_at,
-        kind,
-        invocation_paths: paths.clone(),
-        total_paths: paths.len(),
-        entry_points: 1,
-    })
-}
-
-fn collect_callees_recursive(
-    index: &SemanticIndex,
-    sym_id: u32,
-    paths: &mut Vec<InvocationPath>,
-    visited: &mut HashSet<u32>,
-    current_chain: Vec<ChainStep>,
-    max_depth: usize,
-) {
-    if current_chain.len() >= max_depth {
-        return;
-    }
-
-    visited.insert(sym_id);
-
-    let callees = index.callees(sym_id);
-
-    // Add current symbol to chain
-    let mut chain = current_chain.clone();
-    if let Some(sym) = index.symbol(sym_id) {
-        let name = index.symbol_name(sym).unwrap_or("<unknown>");
-        let file = index
-            .file_path(sym.file_id)
-            .map(|p| p.to_string_lossy().to_string())
-            .unwrap_or_default();
-
-        chain.push(ChainStep {
-            symbol: name.to_string(),
-            file: file.clone(),
-            line: sym.start_line,
-            column: None,
-            context: None,
-        });
-
-        if callees.is_empty() && !chain.is_empty() {
-            // End of chain
-            paths.push(InvocationPath {
-                entry_point: chain.first().map(|c| c.symbol.clone()).unwrap_or_default(),
-                entry_kind: symbol_kind_str(sym.symbol_kind()).to_string(),
-                chain: chain.clone(),
-            });
-            return;
-        }
-    }
-
-    for &callee_id in callees {
-        if visited.contains(&callee_id) {
-            continue;
-        }
-        collect_callees_recursive(index, callee_id, paths, visited, chain.clone(), max_depth);
-    }
-}
-
-// =============================================================================
-// PHASE 4: IMPACT ANALYSIS (REAL DATA)
-// =============================================================================
-
-/// Analyze impact of changing a symbol
-async fn analyze_impact_cmd(
-    project: &Project,
-    symbol: &str,
-    max_depth: usize,
-    filter: &TraceFilter,
-) -> Result<ImpactResult> {
-    let _ = filter; // TODO: apply filter to results
-    debug!(symbol = %symbol, "analyze_impact");
-
-    let index = load_semantic_index(project)?;
-
-    // Parse file:symbol format if present
-    let sym_name = if symbol.contains(':') {
-        symbol.splitn(2, ':').nth(1).unwrap_or(symbol)
-    } else {
-        symbol
-    };
-
-    let symbol_ids = index.symbols_by_name(sym_name).cloned().unwrap_or_default();
-
-    if symbol_ids.is_empty() {
-        return Ok(ImpactResult {
-            symbol: symbol.to_string(),
-            file: String::new(),
-            defined_at: None,
-            direct_callers: Vec::new(),
-            direct_caller_count: 0,
-            transitive_callers: Vec::new(),
-            transitive_caller_count: 0,
-            affected_entry_points: Vec::new(),
-            files_affected: Vec::new(),
-            risk_level: RiskLevel::Low,
-        });
-    }
-
-    let first_id = symbol_ids[0];
-    let defined_at = index.symbol(first_id).and_then(|sym| {
-        let file = index.file_path(sym.file_id)?;
-        Some(format!("{}:{}", file.display(), sym.start_line))
-    });
-
-    let file = index
-        .symbol(first_id)
-        .and_then(|s| index.file_path(s.file_id))
-        .map(|p| p.to_string_lossy().to_string())
-        .unwrap_or_default();
-
-    // Collect direct callers (deduplicated)
-    let mut direct_callers_set = HashSet::new();
-    let mut direct_caller_files = HashSet::new();
-
-    for &sym_id in &symbol_ids {
-        for &caller_id in index.callers(sym_id) {
-            if let Some(caller) = index.symbol(caller_id) {
-                let name = index.symbol_name(caller).unwrap_or("<unknown>");
-                let caller_file = index
-                    .file_path(caller.file_id)
-                    .map(|p| p.to_string_lossy().to_string())
-                    .unwrap_or_default();
-
-                let caller_str = format!("{} ({}:{})", name, caller_file, caller.start_line);
-                direct_callers_set.insert(caller_str);
-                direct_caller_files.insert(caller_file);
+        OperationResult::Neighborhood(result) => {
+            if summary_mode {
+                format!(
+                    "  Callers: {}  Callees: {}",
+                    result.callers.len(),
+                    result.callees.len()
+                )
+            } else {
+                formatter.format_neighborhood(result)
             }
         }
-    }
-    let direct_callers: Vec<_> = direct_callers_set.into_iter().collect();
-
-    // Collect transitive callers via BFS (deduplicated)
-    let mut transitive_callers_set = HashSet::new();
-    let mut visited = HashSet::new();
-    let mut queue: Vec<(u32, usize)> = symbol_ids.iter().map(|&id| (id, 0)).collect();
-    let mut affected_entry_points_set = HashSet::new();
-    let mut all_files = HashSet::new();
-
-    while let Some((current, depth)) = queue.pop() {
-        if depth > max_depth || visited.contains(&current) {
-            continue;
-        }
-        visited.insert(current);
-
-        if let Some(sym) = index.symbol(current) {
-            let sym_file = index
-                .file_path(sym.file_id)
-                .map(|p| p.to_string_lossy().to_string())
-                .unwrap_or_default();
-            all_files.insert(sym_file.clone());
-
-            if sym.is_entry_point() {
-                let name = index.symbol_name(sym).unwrap_or("<unknown>");
-                affected_entry_points_set.insert(format!("{} ({})", name, sym_file));
-            }
-
-            if depth > 1 {
-                let name = index.symbol_name(sym).unwrap_or("<unknown>");
-                transitive_callers_set.insert(format!("{} (depth {})", name, depth));
+        OperationResult::CallPath(result) => {
+            if summary_mode {
+                match result.invocation_paths.first() {
+                    Some(path) => format!("  Path length: {}", path.chain.len()),
+                    None => "  No path".to_string(),
+                }
+            } else {
+                formatter.format_trace(result)
             }
         }
-
-        for &caller_id in index.callers(current) {
-            if !visited.contains(&caller_id) {
-                queue.push((caller_id, depth + 1));
+        OperationResult::TypeUsage(result) => {
+            if summary_mode {
+                format!("  Type usages: {}", result.total_refs)
+            } else if !result.by_directory.is_empty() {
+                formatter.format_refs_by_directory(result)
+            } else {
+                formatter.format_refs(result)
             }
         }
-    }
-    let transitive_callers: Vec<_> = transitive_callers_set.into_iter().collect();
-    let affected_entry_points: Vec<_> = affected_entry_points_set.into_iter().collect();
-
-    // Determine risk level
-    let risk_level = if affected_entry_points.len() > 10 || all_files.len() > 50 {
-        RiskLevel::Critical
-    } else if affected_entry_points.len() > 5 || all_files.len() > 20 {
-        RiskLevel::High
-    } else if direct_callers.len() > 5 || all_files.len() > 5 {
-        RiskLevel::Medium
-    } else {
-        RiskLevel::Low
-    };
-
-    Ok(ImpactResult {
-        symbol: symbol.to_string(),
-        file,
-        defined_at,
-        direct_callers: direct_callers.clone(),
-        direct_caller_count: direct_callers.len(),
-        transitive_callers: transitive_callers.clone(),
-        transitive_caller_count: transitive_callers.len(),
-        affected_entry_points,
-        files_affected: all_files.into_iter().collect(),
-        risk_level,
-    })
-}
-
-// =============================================================================
-// PHASE 5: TYPE TRACING (handled by refs with TypeAnnotation filter)
-// =============================================================================
-
-// Type tracing is implemented via find_refs_cmd with ReferenceKind::TypeAnnotation
-
-// =============================================================================
-// PHASE 6: MODULE TRACING
-// =============================================================================
-
-/// Trace module imports/exports
-async fn trace_module_cmd(
-    project: &Project,
-    module: &str,
-    filter: &TraceFilter,
-) -> Result<ModuleResult> {
-    let _ = filter; // TODO: apply filter to results
-    debug!(module = %module, "trace_module");
-
-    let index = load_semantic_index(project)?;
-
-    // Find files that match the module pattern
-    let module_files: Vec<_> = index
-        .files
-        .iter()
-        .enumerate()
-        .filter(|(_, path)| path.to_string_lossy().contains(module))
-        .collect();
-
-    let mut exports = Vec::new();
-    let mut imported_by = Vec::new();
-    let mut dependencies = Vec::new();
-
-    for (file_id, _file_path) in &module_files {
-        // Find exported symbols from this file
-        for symbol in index.symbols_in_file(*file_id as u16) {
-            if symbol.is_exported() {
-                let name = index.symbol_name(symbol).unwrap_or("<unknown>");
-                exports.push(format!(
-                    "{} ({})",
-                    name,
-                    symbol_kind_str(symbol.symbol_kind())
-                ));
+        OperationResult::Module(result) => {
+            if summary_mode {
+                format!(
+                    "  Exports: {}  Imported by: {}  Deps: {}",
+                    result.exports.len(),
+                    result.imported_by.len(),
+                    result.dependencies.len()
+                )
+            } else {
+                formatter.format_module(result)
             }
         }
-
-        // Find imports of this module (tokens with Import kind referencing this file)
-        // This is approximate - we look for symbols from this file being referenced elsewhere
-        for symbol in index.symbols_in_file(*file_id as u16) {
-            for reference in index.references_to(symbol.id) {
-                if reference.ref_kind() == RefKind::Import {
-                    if let Some(token) = index.token(reference.token_id) {
-                        if token.file_id != *file_id as u16 {
-                            let importer_file = index
-                                .file_path(token.file_id)
-                                .map(|p| p.to_string_lossy().to_string())
-                                .unwrap_or_default();
-                            let name = index.symbol_name(symbol).unwrap_or("<unknown>");
-                            imported_by.push(format!("{} imports {}", importer_file, name));
-                        }
-                    }
-                }
+        OperationResult::Pattern(result) => {
+            if summary_mode {
+                format!(
+                    "  Matches: {}  Files: {}",
+                    result.total_matches,
+                    result.by_file.len()
+                )
+            } else {
+                formatter.format_pattern(result)
             }
         }
-    }
-
-    // Find what this module depends on (imports from other modules)
-    for (file_id, _) in &module_files {
-        for token in index.tokens_in_file(*file_id as u16) {
-            if token.token_kind() == crate::trace::TokenKind::Import {
-                let name = index.token_name(token).unwrap_or("<unknown>");
-                if !dependencies.contains(&name.to_string()) {
-                    dependencies.push(name.to_string());
-                }
+        OperationResult::Flow(result) => {
+            if summary_mode {
+                let total_steps: usize = result.flow_paths.iter().map(|p| p.len()).sum();
+                format!(
+                    "  Flow paths: {}  Steps: {}",
+                    result.flow_paths.len(),
+                    total_steps
+                )
+            } else {
+                formatter.format_flow(result)
             }
         }
-    }
-
-    let file_path = module_files
-        .first()
-        .map(|(_, p)| p.to_string_lossy().to_string())
-        .unwrap_or_else(|| module.to_string());
-
-    Ok(ModuleResult {
-        module: module.to_string(),
-        file_path,
-        exports,
-        imported_by,
-        dependencies,
-        circular_deps: Vec::new(), // Populated by cycles command
-    })
-}
-
-/// Find circular dependencies
-async fn find_cycles_cmd(project: &Project, filter: &TraceFilter) -> Result<ModuleResult> {
-    debug!("find_cycles filter={:?}", filter);
-
-    let index = load_semantic_index(project)?;
-
-    // Helper to check if file passes filter
-    let file_passes = |file_id: u16| -> bool {
-        if let Some(path) = index.file_path(file_id) {
-            filter.matches_path(&path.to_string_lossy())
-        } else {
-            false
+        OperationResult::Impact(result) => {
+            if summary_mode {
+                format!(
+                    "  Direct callers: {}  Transitive: {}  Entry points: {}  Risk: {:?}",
+                    result.direct_callers.len(),
+                    result.transitive_callers.len(),
+                    result.affected_entry_points.len(),
+                    result.risk_level
+                )
+            } else {
+                formatter.format_impact(result)
+            }
         }
-    };
-
-    // Build file dependency graph (filtered)
-    let mut file_deps: HashMap<u16, HashSet<u16>> = HashMap::new();
-
-    for edge in &index.edges {
-        if let (Some(from_sym), Some(to_sym)) =
-            (index.symbol(edge.from_symbol), index.symbol(edge.to_symbol))
-        {
-            if from_sym.file_id != to_sym.file_id {
-                // Only include if at least one file passes the filter (or no filter)
-                let from_passes = file_passes(from_sym.file_id);
-                let to_passes = file_passes(to_sym.file_id);
-
-                if from_passes || to_passes || filter.path.is_none() {
-                    file_deps
-                        .entry(from_sym.file_id)
-                        .or_default()
-                        .insert(to_sym.file_id);
-                }
+        OperationResult::Scope(result) => {
+            if summary_mode {
+                format!(
+                    "  Scope: {}  Variables: {}  Imports: {}",
+                    result.enclosing_scope.as_deref().unwrap_or("global"),
+                    result.local_variables.len(),
+                    result.imports.len()
+                )
+            } else {
+                formatter.format_scope(result)
             }
         }
-    }
-
-    // Find cycles using DFS
-    let mut cycles = Vec::new();
-    let mut visited = HashSet::new();
-    let mut rec_stack = HashSet::new();
-    let mut path = Vec::new();
-
-    for &file_id in file_deps.keys() {
-        find_cycles_dfs(
-            file_id,
-            &file_deps,
-            &mut visited,
-            &mut rec_stack,
-            &mut path,
-            &mut cycles,
-            &index,
-            filter,
-        );
-    }
-
-    Ok(ModuleResult {
-        module: "Circular Dependencies".to_string(),
-        file_path: String::new(),
-        exports: Vec::new(),
-        imported_by: Vec::new(),
-        dependencies: Vec::new(),
-        circular_deps: cycles,
-    })
-}
-
-fn find_cycles_dfs(
-    node: u16,
-    graph: &HashMap<u16, HashSet<u16>>,
-    visited: &mut HashSet<u16>,
-    rec_stack: &mut HashSet<u16>,
-    path: &mut Vec<u16>,
-    cycles: &mut Vec<String>,
-    index: &SemanticIndex,
-    filter: &TraceFilter,
-) {
-    visited.insert(node);
-    rec_stack.insert(node);
-    path.push(node);
-
-    if let Some(neighbors) = graph.get(&node) {
-        for &neighbor in neighbors {
-            if !visited.contains(&neighbor) {
-                find_cycles_dfs(
-                    neighbor, graph, visited, rec_stack, path, cycles, index, filter,
-                );
-            } else if rec_stack.contains(&neighbor) {
-                // Found a cycle
-                let cycle_start = path.iter().position(|&n| n == neighbor).unwrap_or(0);
-                let cycle_path: Vec<_> = path[cycle_start..]
+        OperationResult::DeadCode(result) => {
+            if args.count || summary_mode {
+                let kinds: Vec<_> = result
+                    .by_kind
                     .iter()
-                    .filter_map(|&fid| {
-                        index
-                            .file_path(fid)
-                            .map(|p| p.to_string_lossy().to_string())
-                    })
+                    .map(|(k, v)| format!("{}={}", k, v))
                     .collect();
-
-                if !cycle_path.is_empty() {
-                    // Only include cycle if at least one file in the cycle passes the filter
-                    let cycle_passes =
-                        filter.path.is_none() || cycle_path.iter().any(|p| filter.matches_path(p));
-
-                    if cycle_passes {
-                        cycles.push(cycle_path.join(" -> ") + " -> " + &cycle_path[0]);
-                    }
-                }
-            }
-        }
-    }
-
-    path.pop();
-    rec_stack.remove(&node);
-}
-
-// =============================================================================
-// PHASE 7: DATA FLOW TRACING
-// =============================================================================
-
-/// Trace data flow for a variable
-async fn trace_flow_cmd(
-    project: &Project,
-    symbol: &str,
-    args: &TraceArgs,
-    filter: &TraceFilter,
-) -> Result<FlowResult> {
-    debug!(symbol = %symbol, "trace_flow filter={:?}", filter);
-
-    let index = load_semantic_index(project)?;
-    let mut cache = FileCache::new(&project.root);
-
-    let mut flow_paths = Vec::new();
-    let mut current_path = Vec::new();
-
-    // Find all tokens with this name
-    if let Some(token_ids) = index.tokens_by_name(symbol) {
-        // Group by file and sort by line
-        let mut by_file: HashMap<u16, Vec<&crate::trace::Token>> = HashMap::new();
-        for &token_id in token_ids {
-            if let Some(token) = index.token(token_id) {
-                // Apply path filter
-                if let Some(path) = index.file_path(token.file_id) {
-                    if !filter.matches_path(&path.to_string_lossy()) {
-                        continue;
-                    }
-                }
-                by_file.entry(token.file_id).or_default().push(token);
-            }
-        }
-
-        for (file_id, mut tokens) in by_file {
-            tokens.sort_by_key(|t| (t.line, t.column));
-
-            let file_path = index
-                .file_path(file_id)
-                .map(|p| p.to_path_buf())
-                .unwrap_or_default();
-            let file = file_path.to_string_lossy().to_string();
-
-            for token in tokens {
-                // Map token kinds to flow actions
-                // Note: TokenKind doesn't have Assignment/Return, so we infer from context
-                let action = match token.token_kind() {
-                    crate::trace::TokenKind::Call => FlowAction::PassToFunction,
-                    crate::trace::TokenKind::Property => FlowAction::Read,
-                    crate::trace::TokenKind::Identifier => FlowAction::Read, // Could be assign or read
-                    _ => FlowAction::Read,
-                };
-
-                // First occurrence is considered a definition
-                let actual_action = if current_path.is_empty() {
-                    FlowAction::Define
-                } else {
-                    action
-                };
-
-                let expression = get_code_context(&mut cache, &file_path, token.line, 0);
-
-                current_path.push(FlowStep {
-                    variable: symbol.to_string(),
-                    action: actual_action,
-                    file: file.clone(),
-                    line: token.line,
-                    expression,
-                });
-            }
-        }
-    }
-
-    if !current_path.is_empty() {
-        flow_paths.push(current_path);
-    }
-
-    // Apply limit
-    if let Some(limit) = args.limit {
-        for path in &mut flow_paths {
-            path.truncate(limit);
-        }
-    }
-
-    Ok(FlowResult {
-        symbol: symbol.to_string(),
-        flow_paths,
-    })
-}
-
-// =============================================================================
-// PHASE 8: PATTERN SEARCH
-// =============================================================================
-
-/// Search for regex pattern in codebase
-async fn trace_pattern_cmd(
-    project: &Project,
-    pattern: &str,
-    args: &TraceArgs,
-    filter: &TraceFilter,
-) -> Result<PatternResult> {
-    debug!(pattern = %pattern, "trace_pattern filter={:?}", filter);
-
-    let regex = Regex::new(pattern).map_err(|e| Error::SearchError {
-        message: format!("Invalid regex pattern: {}", e),
-    })?;
-
-    let index = load_semantic_index(project)?;
-    let mut cache = FileCache::new(&project.root);
-
-    let mut matches = Vec::new();
-
-    for (file_id, file_path) in index.files.iter().enumerate() {
-        // Apply universal path filter
-        let file_str = file_path.to_string_lossy();
-        if !filter.matches_path(&file_str) {
-            continue;
-        }
-
-        // Read file and search
-        if let Some(line_count) = cache.line_count(file_path) {
-            for line_num in 1..=line_count as u32 {
-                if let Some(line_content) = cache.get_line(file_path, line_num) {
-                    if let Some(mat) = regex.find(&line_content) {
-                        let context = if args.context > 0 {
-                            cache
-                                .get_context(file_path, line_num, args.context, args.context)
-                                .map(|ctx| ctx.format(false))
-                                .unwrap_or_else(|| line_content.clone())
-                        } else {
-                            line_content.trim().to_string()
-                        };
-
-                        let enclosing = find_enclosing_symbol(&index, file_id as u16, line_num);
-
-                        matches.push(PatternMatch {
-                            file: file_path.to_string_lossy().to_string(),
-                            line: line_num,
-                            column: mat.start() as u16,
-                            matched_text: mat.as_str().to_string(),
-                            context,
-                            enclosing_symbol: enclosing,
-                        });
-
-                        // Apply limit
-                        if let Some(limit) = args.limit {
-                            if matches.len() >= limit {
-                                break;
-                            }
-                        }
-                    }
-                }
+                format!(
+                    "  Dead symbols: {}  ({})",
+                    result.total_dead,
+                    kinds.join(", ")
+                )
+            } else {
+                formatter.format_dead_code(result)
             }
         }
-
-        // Check limit
-        if let Some(limit) = args.limit {
-            if matches.len() >= limit {
-                break;
+        OperationResult::Deprecated(result) => {
+            if args.count || summary_mode {
+                format!(
+                    "  Deprecated symbols: {}  Usages: {}",
+                    result.total_deprecated, result.total_usages
+                )
+            } else {
+                formatter.format_deprecated(result)
             }
         }
-    }
-
-    // Count by file
-    let mut by_file: HashMap<String, usize> = HashMap::new();
-    for m in &matches {
-        *by_file.entry(m.file.clone()).or_insert(0) += 1;
-    }
-
-    Ok(PatternResult {
-        pattern: pattern.to_string(),
-        total_matches: matches.len(),
-        matches,
-        by_file,
-    })
-}
-
-// =============================================================================
-// PHASE 9: SCOPE ANALYSIS
-// =============================================================================
-
-/// Analyze scope at a specific location
-async fn analyze_scope_cmd(
-    project: &Project,
-    location: &str,
-    filter: &TraceFilter,
-) -> Result<ScopeResult> {
-    debug!(location = %location, "analyze_scope");
-    let _ = filter; // TODO: Apply filter to scope analysis
-
-    // Parse file:line format
-    let parts: Vec<&str> = location.rsplitn(2, ':').collect();
-    if parts.len() != 2 {
-        return Err(Error::SearchError {
-            message: "Location must be in format file:line".to_string(),
-        });
-    }
-
-    let line: u32 = parts[0].parse().map_err(|_| Error::SearchError {
-        message: "Invalid line number".to_string(),
-    })?;
-    let file_pattern = parts[1];
-
-    let index = load_semantic_index(project)?;
-
-    // Find the file
-    let file_id = index
-        .files
-        .iter()
-        .enumerate()
-        .find(|(_, p)| p.to_string_lossy().contains(file_pattern))
-        .map(|(id, _)| id as u16);
-
-    let file_id = match file_id {
-        Some(id) => id,
-        None => {
-            return Err(Error::SearchError {
-                message: format!("File not found: {}", file_pattern),
-            });
-        }
-    };
-
-    let file_path = index
-        .file_path(file_id)
-        .map(|p| p.to_string_lossy().to_string())
-        .unwrap_or_default();
-
-    // Find enclosing scope
-    let enclosing_scope = find_enclosing_symbol(&index, file_id, line);
-
-    // Find local variables (symbols in the same scope that are defined before this line)
-    let mut local_variables = Vec::new();
-    let mut parameters = Vec::new();
-    let mut imports = Vec::new();
-
-    for symbol in index.symbols_in_file(file_id) {
-        let name = index.symbol_name(symbol).unwrap_or("<unknown>");
-        let kind = symbol_kind_str(symbol.symbol_kind());
-
-        // Check if this symbol is in scope at the given line
-        if symbol.start_line <= line && symbol.end_line >= line {
-            // This is the enclosing function/class
-            continue;
-        }
-
-        if symbol.start_line < line && symbol.end_line < line {
-            // Symbol defined before the line
-            match symbol.symbol_kind() {
-                SymbolKind::Variable | SymbolKind::Constant => {
-                    local_variables.push(ScopeVariable {
-                        name: name.to_string(),
-                        kind: kind.to_string(),
-                        defined_at: symbol.start_line,
-                    });
-                }
-                SymbolKind::Function | SymbolKind::Method => {
-                    // Could be a parameter if inside a function
-                    if let Some(ref scope) = enclosing_scope {
-                        if name != scope {
-                            parameters.push(ScopeVariable {
-                                name: name.to_string(),
-                                kind: kind.to_string(),
-                                defined_at: symbol.start_line,
-                            });
-                        }
-                    }
-                }
-                _ => {}
+        OperationResult::UnusedParams(result) => {
+            if args.count || summary_mode {
+                format!("  Unused parameters: {}", result.total)
+            } else {
+                formatter.format_unused_params(result)
             }
         }
-    }
-
-    // Find imports (tokens with Import kind in this file)
-    for token in index.tokens_in_file(file_id) {
-        if token.token_kind() == crate::trace::TokenKind::Import {
-            let name = index.token_name(token).unwrap_or("<unknown>");
-            imports.push(name.to_string());
-        }
-    }
-
-    Ok(ScopeResult {
-        file: file_path,
-        line,
-        enclosing_scope,
-        local_variables,
-        parameters,
-        imports,
-    })
-}
-
-// =============================================================================
-// PHASE 10: STATISTICS
-// =============================================================================
-
-/// Compute codebase statistics
-async fn compute_stats_cmd(project: &Project, filter: &TraceFilter) -> Result<StatsResult> {
-    debug!("compute_stats");
-
-    let index = load_semantic_index(project)?;
-    let stats = index.stats();
-
-    // Helper to check if a file passes the filter
-    let file_passes = |file_id: u16| -> bool {
-        if let Some(path) = index.file_path(file_id) {
-            filter.matches_path(&path.to_string_lossy())
-        } else {
-            false
+        OperationResult::Shadows(result) => {
+            if args.count || summary_mode {
+                format!("  Shadowed variables: {}", result.total)
+            } else {
+                formatter.format_shadows(result)
+            }
         }
-    };
-
-    // Helper to check if a symbol passes the filter
-    let symbol_passes = |symbol: &crate::trace::Symbol| -> bool {
-        if let Some(path) = index.file_path(symbol.file_id) {
-            let name = index.symbol_name(symbol).unwrap_or("");
-            let kind = symbol_kind_str(symbol.symbol_kind());
-            filter.matches_symbol(name, kind, &path.to_string_lossy())
-        } else {
-            false
+        OperationResult::Stubs(result) => {
+            if args.count || summary_mode {
+                format!("  Stub functions: {}", result.total)
+            } else {
+                formatter.format_stubs(result)
+            }
         }
-    };
-
-    // Count symbols by kind (filtered)
-    let mut symbols_by_kind: HashMap<String, usize> = HashMap::new();
-    let mut filtered_symbol_count = 0;
-    for symbol in &index.symbols {
-        if symbol_passes(symbol) {
-            filtered_symbol_count += 1;
-            *symbols_by_kind
-                .entry(symbol_kind_str(symbol.symbol_kind()).to_string())
-                .or_insert(0) += 1;
+        OperationResult::Hotspots(result) => {
+            if args.count || summary_mode {
+                format!(
+                    "  Hotspot files: {}  Churn: {}",
+                    result.files.len(),
+                    if result.churn_available {
+                        "git"
+                    } else {
+                        "unavailable"
+                    }
+                )
+            } else {
+                formatter.format_hotspots(result)
+            }
         }
-    }
-
-    // Count files by extension (filtered)
-    let mut files_by_extension: HashMap<String, usize> = HashMap::new();
-    let mut filtered_file_count = 0;
-    for (idx, file) in index.files.iter().enumerate() {
-        if file_passes(idx as u16) {
-            filtered_file_count += 1;
-            let ext = file
-                .extension()
-                .map(|e| e.to_string_lossy().to_string())
-                .unwrap_or_else(|| "unknown".to_string());
-            *files_by_extension.entry(ext).or_insert(0) += 1;
+        OperationResult::Stats(result) => {
+            if summary_mode {
+                format!(
+                    "  Files: {}  Symbols: {}  Refs: {}  Edges: {}",
+                    result.total_files,
+                    result.total_symbols,
+                    result.total_references,
+                    result.total_edges
+                )
+            } else {
+                formatter.format_stats(result)
+            }
         }
-    }
-
-    // Find most referenced symbols (aggregated by name, filtered)
-    let mut symbol_ref_counts: HashMap<String, usize> = HashMap::new();
-    for s in &index.symbols {
-        if symbol_passes(s) {
-            if let Some(name) = index.symbol_name(s) {
-                let ref_count = index.references_to(s.id).count();
-                if ref_count > 0 {
-                    *symbol_ref_counts.entry(name.to_string()).or_insert(0) += ref_count;
-                }
+        OperationResult::Cycles(result) => {
+            if summary_mode {
+                format!("  Circular deps: {}", result.circular_deps.len())
+            } else {
+                formatter.format_module(result)
             }
         }
-    }
-    let mut sorted_refs: Vec<_> = symbol_ref_counts.into_iter().collect();
-    sorted_refs.sort_by(|a, b| b.1.cmp(&a.1));
-    let most_referenced: Vec<_> = sorted_refs.into_iter().take(10).collect();
+    };
+    output + "\n"
+}
 
-    // Find largest files (by symbol count, filtered)
-    let mut file_symbol_counts: HashMap<u16, usize> = HashMap::new();
-    for symbol in &index.symbols {
-        if file_passes(symbol.file_id) {
-            *file_symbol_counts.entry(symbol.file_id).or_insert(0) += 1;
+/// Generate header text for an operation in multi-op mode
+fn operation_header(op: &TraceOperation) -> String {
+    match op {
+        TraceOperation::Trace(s) => format!("TRACE: {}", s),
+        TraceOperation::Refs { symbol, kind } => {
+            if let Some(k) = kind {
+                format!("REFERENCES: {} ({:?})", symbol, k)
+            } else {
+                format!("REFERENCES: {}", symbol)
+            }
         }
+        TraceOperation::Callers(s) => format!("CALLERS: {}", s),
+        TraceOperation::CallHierarchy(s) => format!("CALL HIERARCHY: {}", s),
+        TraceOperation::Callees(s) => format!("CALLEES: {}", s),
+        TraceOperation::Neighborhood(s) => format!("NEIGHBORHOOD: {}", s),
+        TraceOperation::Path { from, to } => format!("CALL PATH: {} -> {}", from, to),
+        TraceOperation::Type(s) => format!("TYPE USAGE: {}", s),
+        TraceOperation::Module(s) => format!("MODULE: {}", s),
+        TraceOperation::Pattern(s) => format!("PATTERN: {}", s),
+        TraceOperation::Flow(s) => format!("DATA FLOW: {}", s),
+        TraceOperation::Impact(s) => format!("IMPACT ANALYSIS: {}", s),
+        TraceOperation::Scope(s) => format!("SCOPE: {}", s),
+        TraceOperation::DeadCode => "DEAD CODE ANALYSIS".to_string(),
+        TraceOperation::Deprecated => "DEPRECATED SYMBOLS".to_string(),
+        TraceOperation::UnusedParams => "UNUSED PARAMETERS".to_string(),
+        TraceOperation::Shadows => "SHADOWED VARIABLES".to_string(),
+        TraceOperation::Stubs => "STUB FUNCTIONS".to_string(),
+        TraceOperation::Hotspots => "HOTSPOTS".to_string(),
+        TraceOperation::Stats => "CODEBASE STATISTICS".to_string(),
+        TraceOperation::Cycles => "CIRCULAR DEPENDENCIES".to_string(),
     }
-    let mut largest_files: Vec<_> = file_symbol_counts
-        .into_iter()
-        .filter_map(|(file_id, count)| {
-            let path = index.file_path(file_id)?;
-            Some((path.to_string_lossy().to_string(), count))
-        })
-        .collect();
-    largest_files.sort_by(|a, b| b.1.cmp(&a.1));
-    largest_files.truncate(10);
-
-    // Use filtered counts if filter is active, otherwise use global stats
-    let (total_files, total_symbols) =
-        if filter.path.is_some() || filter.symbol_type.is_some() || filter.name_pattern.is_some() {
-            (filtered_file_count, filtered_symbol_count)
-        } else {
-            (stats.files, stats.symbols)
-        };
-
-    // Calculate call graph stats
-    let max_call_depth = calculate_max_call_depth(&index);
-    let avg_call_depth = calculate_avg_call_depth(&index);
-
-    Ok(StatsResult {
-        total_files,
-        total_symbols,
-        total_tokens: stats.tokens, // Not filtered (token-level filtering is expensive)
-        total_references: stats.references, // Not filtered
-        total_edges: stats.edges,   // Not filtered
-        total_entry_points: stats.entry_points,
-        symbols_by_kind,
-        files_by_extension,
-        most_referenced,
-        largest_files,
-        max_call_depth,
-        avg_call_depth,
-    })
 }
 
-fn calculate_max_call_depth(index: &SemanticIndex) -> usize {
-    let mut max_depth = 0;
-
-    for &entry_id in &index.entry_points {
-        let depth = calculate_depth_from(index, entry_id, &mut HashSet::new());
-        max_depth = max_depth.max(depth);
-    }
+// =============================================================================
+// INDEX LOADING
+// =============================================================================
 
-    max_depth
+#[cfg(test)]
+thread_local! {
+    /// Number of trace indexes this test thread has loaded from disk
+    static INDEX_LOADS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
 }
 
-fn calculate_depth_from(index: &SemanticIndex, sym_id: u32, visited: &mut HashSet<u32>) -> usize {
-    if visited.contains(&sym_id) {
-        return 0;
-    }
-    visited.insert(sym_id);
-
-    let callees = index.callees(sym_id);
-    if callees.is_empty() {
-        return 0;
-    }
-
-    let max_child_depth = callees
-        .iter()
-        .map(|&callee| calculate_depth_from(index, callee, visited))
-        .max()
-        .unwrap_or(0);
-
-    max_child_depth + 1
-}
+/// Load the semantic index for a project
+fn load_semantic_index(project: &Project) -> Result<SemanticIndex> {
+    #[cfg(test)]
+    INDEX_LOADS.with(|loads| loads.set(loads.get() + 1));
+    let index_path = trace_index_path(&project.root);
 
-fn calculate_avg_call_depth(index: &SemanticIndex) -> f32 {
-    if index.entry_points.is_empty() {
-        return 0.0;
+    if !trace_index_exists(&project.root) {
+        return Err(Error::IndexNotFound {
+            path: project.root.clone(),
+        });
     }
 
-    let total_depth: usize = index
-        .entry_points
-        .iter()
-        .map(|&id| calculate_depth_from(index, id, &mut HashSet::new()))
-        .sum();
-
-    total_depth as f32 / index.entry_points.len() as f32
+    let mut index = load_index(&index_path).map_err(|e| Error::IndexError {
+        message: format!("Failed to load trace index: {}", e),
+    })?;
+    index.normalize_names = Config::load()
+        .map(|c| c.search.normalize_names)
+        .unwrap_or_default();
+    Ok(index)
 }
 
 // =============================================================================
-// TRACE OPERATIONS
+// STREAMING
 // =============================================================================
 
-/// Trace symbol invocation paths
-async fn trace_symbol_cmd(
+/// Find references to a symbol, writing JSON to stdout as they are found
+async fn stream_refs_cmd(
     project: &Project,
+    index: &SemanticIndex,
     symbol: &str,
-    max_depth: usize,
-    direct: bool,
+    kind_filter: Option<ReferenceKind>,
+    options: &OperationOptions,
     filter: &TraceFilter,
-) -> Result<TraceResult> {
-    debug!(symbol = %symbol, max_depth, direct, ?filter, "trace_symbol");
-
-    let index = load_semantic_index(project)?;
-
-    // Determine symbols to search for
-    let symbols_to_search = if direct {
-        vec![symbol.to_string()]
-    } else {
-        expand_query_with_ai(symbol).await
-    };
-
-    debug!(symbols = ?symbols_to_search, "Searching for symbols");
-
-    // Find and trace all matching symbols
-    let mut all_trace_results = Vec::new();
-    for sym_name in &symbols_to_search {
-        let trace_results = trace_symbol_by_name(&index, sym_name, Some(max_depth));
-        all_trace_results.extend(trace_results);
-    }
-
-    if all_trace_results.is_empty() {
-        return Ok(TraceResult {
-            symbol: symbol.to_string(),
-            defined_at: None,
-            kind: "unknown".to_string(),
-            invocation_paths: Vec::new(),
-            total_paths: 0,
-            entry_points: 0,
-        });
-    }
+) -> Result<()> {
+    debug!(symbol = %symbol, ?kind_filter, ?filter, "stream_refs");
 
-    // Convert traverse results to output format
-    let mut invocation_paths = Vec::new();
-    let mut entry_points_set = std::collections::HashSet::new();
-
-    for trace_result in &all_trace_results {
-        for path in &trace_result.paths {
-            let entry_symbol = index.symbol(path.entry_point);
-            let entry_name = entry_symbol
-                .and_then(|s| index.symbol_name(s))
-                .unwrap_or("<unknown>");
-            let entry_file = entry_symbol
-                .and_then(|s| index.file_path(s.file_id))
-                .map(|p| p.to_string_lossy().to_string())
-                .unwrap_or_default();
-            let entry_kind = entry_symbol
-                .map(|s| symbol_kind_str(s.symbol_kind()))
-                .unwrap_or("function");
-
-            entry_points_set.insert(path.entry_point);
+    let mut cache = FileCache::new(&project.root);
+    let stdout = io::BufWriter::new(io::stdout().lock());
+
+    let mut out = stream_refs(
+        index,
+        &mut cache,
+        symbol,
+        kind_filter,
+        options,
+        filter,
+        stdout,
+    )?;
+    writeln!(out)?;
+    Ok(())
+}
 
-            let chain: Vec<ChainStep> = path
-                .chain
-                .iter()
-                .enumerate()
-                .filter_map(|(i, &sym_id)| {
-                    let sym = index.symbol(sym_id)?;
-                    let name = index.symbol_name(sym)?;
-                    let file = index
-                        .file_path(sym.file_id)
-                        .map(|p| p.to_string_lossy().to_string())
-                        .unwrap_or_default();
-                    let line = if i > 0 {
-                        path.call_lines
-                            .get(i - 1)
-                            .copied()
-                            .unwrap_or(sym.start_line)
-                    } else {
-                        sym.start_line
-                    };
-
-                    Some(ChainStep {
-                        symbol: name.to_string(),
-                        file,
-                        line,
-                        column: None,
-                        context: None,
-                    })
-                })
-                .collect();
-
-            if !chain.is_empty() {
-                invocation_paths.push(InvocationPath {
-                    entry_point: format!("{} ({})", entry_name, entry_file),
-                    entry_kind: entry_kind.to_string(),
-                    chain,
-                });
-            }
-        }
-    }
+/// Search for regex pattern, writing JSON to stdout as matches are found
+async fn stream_pattern_cmd(
+    project: &Project,
+    index: &SemanticIndex,
+    pattern: &str,
+    options: &OperationOptions,
+    filter: &TraceFilter,
+) -> Result<()> {
+    debug!(pattern = %pattern, "stream_pattern filter={:?}", filter);
 
-    // AI reranking when not in direct mode
-    if !direct && invocation_paths.len() > 1 {
-        invocation_paths = rerank_paths_with_ai(symbol, invocation_paths).await;
-    }
+    let regex = pattern_regex(pattern)?;
+    let mut cache = FileCache::new(&project.root);
+    let stdout = io::BufWriter::new(io::stdout().lock());
 
-    let first_target = all_trace_results.first().map(|r| r.target);
-    let defined_at = first_target.and_then(|id| {
-        let sym = index.symbol(id)?;
-        let file = index.file_path(sym.file_id)?;
-        Some(format!("{}:{}", file.display(), sym.start_line))
-    });
-    let kind = first_target
-        .and_then(|id| index.symbol(id))
-        .map(|s| symbol_kind_str(s.symbol_kind()).to_string())
-        .unwrap_or_else(|| "function".to_string());
-
-    Ok(TraceResult {
-        symbol: symbol.to_string(),
-        defined_at,
-        kind,
-        invocation_paths: invocation_paths.clone(),
-        total_paths: invocation_paths.len(),
-        entry_points: entry_points_set.len(),
-    })
+    let mut out = stream_pattern(index, &mut cache, &regex, pattern, options, filter, stdout)?;
+    writeln!(out)?;
+    Ok(())
 }
 
 // =============================================================================
-// AI ENHANCEMENT
+// OUTPUT HELPERS
 // =============================================================================
 
-/// Expand a query into related symbol names using AI
-async fn expand_query_with_ai(query: &str) -> Vec<String> {
-    let providers = auth::get_authenticated_providers();
-
-    if providers.is_empty() {
-        debug!("No AI provider authenticated, skipping query expansion");
-        return vec![query.to_string()];
-    }
-
-    if !is_natural_language_query(query) && query.len() > 15 {
-        return vec![query.to_string()];
-    }
+/// Compact `--count --by` printout: the total plus each requested breakdown
+fn format_ref_counts(result: &RefsResult, by: &[String]) -> String {
+    let mut output = format!(
+        "  References: {}  Files: {}\n",
+        result.total_refs,
+        result.by_file.len()
+    );
+    for group in by {
+        let (label, counts) = match group.as_str() {
+            "kind" => ("kind", &result.by_kind),
+            "file" => ("file", &result.by_file),
+            _ => continue,
+        };
+        let mut counts: Vec<_> = counts.iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        let width = counts.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
 
-    let expanded = if providers.contains(&Provider::Anthropic) {
-        match auth::get_anthropic_token() {
-            Ok(token) => {
-                let client = ClaudeClient::new(token);
-                match client.expand_query(query).await {
-                    Ok(symbols) => {
-                        debug!(count = symbols.len(), "AI expanded query to symbols");
-                        symbols
-                    }
-                    Err(e) => {
-                        warn!("AI query expansion failed: {}", e);
-                        vec![query.to_string()]
-                    }
-                }
-            }
-            Err(e) => {
-                warn!("Failed to get Anthropic token: {}", e);
-                vec![query.to_string()]
-            }
-        }
-    } else if providers.contains(&Provider::Google) {
-        match auth::get_google_token() {
-            Ok(token) => {
-                let client = GeminiClient::new(token);
-                match client.expand_query(query).await {
-                    Ok(symbols) => {
-                        debug!(count = symbols.len(), "AI expanded query to symbols");
-                        symbols
-                    }
-                    Err(e) => {
-                        warn!("AI query expansion failed: {}", e);
-                        vec![query.to_string()]
-                    }
-                }
-            }
-            Err(e) => {
-                warn!("Failed to get Google token: {}", e);
-                vec![query.to_string()]
-            }
+        output.push_str(&format!("  By {}:\n", label));
+        for (name, count) in counts {
+            output.push_str(&format!("    {:<width$}  {}\n", name, count, width = width));
         }
-    } else {
-        vec![query.to_string()]
-    };
-
-    let mut result = expanded;
-    if !result.iter().any(|s| s.eq_ignore_ascii_case(query)) {
-        result.insert(0, query.to_string());
     }
-    result.truncate(10);
-    result
+    output
 }
 
-/// Rerank invocation paths by relevance using AI
-async fn rerank_paths_with_ai(query: &str, mut paths: Vec<InvocationPath>) -> Vec<InvocationPath> {
-    let providers = auth::get_authenticated_providers();
-
-    if providers.is_empty() {
-        debug!("No AI provider authenticated, skipping reranking");
-        return paths;
-    }
-
-    if paths.len() <= 3 {
-        return paths;
-    }
-
-    let path_descriptions: Vec<String> = paths
-        .iter()
-        .map(|p| {
-            let chain_str: Vec<String> = p.chain.iter().map(|c| c.symbol.clone()).collect();
-            format!(
-                "Entry: {} ({})\nChain: {}",
-                p.entry_point,
-                p.entry_kind,
-                chain_str.join(" -> ")
-            )
-        })
-        .collect();
-
-    let indices = if providers.contains(&Provider::Anthropic) {
-        match auth::get_anthropic_token() {
-            Ok(token) => {
-                let client = ClaudeClient::new(token);
-                match client.rerank_trace(query, &path_descriptions).await {
-                    Ok(idx) => {
-                        debug!(order = ?idx, "AI reranked trace paths");
-                        idx
-                    }
-                    Err(e) => {
-                        warn!("AI reranking failed: {}", e);
-                        (0..paths.len()).collect()
-                    }
-                }
-            }
-            Err(e) => {
-                warn!("Failed to get Anthropic token: {}", e);
-                (0..paths.len()).collect()
-            }
-        }
-    } else if providers.contains(&Provider::Google) {
-        match auth::get_google_token() {
-            Ok(token) => {
-                let client = GeminiClient::new(token);
-                match client.rerank_trace(query, &path_descriptions).await {
-                    Ok(idx) => {
-                        debug!(order = ?idx, "AI reranked trace paths");
-                        idx
-                    }
-                    Err(e) => {
-                        warn!("AI reranking failed: {}", e);
-                        (0..paths.len()).collect()
-                    }
-                }
-            }
-            Err(e) => {
-                warn!("Failed to get Google token: {}", e);
-                (0..paths.len()).collect()
-            }
-        }
-    } else {
-        (0..paths.len()).collect()
-    };
-
-    let original_paths = std::mem::take(&mut paths);
-    let mut reranked = Vec::with_capacity(original_paths.len());
-
-    for &idx in &indices {
-        if idx < original_paths.len() {
-            reranked.push(original_paths[idx].clone());
-        }
-    }
-
-    for (i, path) in original_paths.into_iter().enumerate() {
-        if !indices.contains(&i) {
-            reranked.push(path);
-        }
-    }
-
-    reranked
+/// A raw caller -> callee edge for `--output-edges`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EdgeRecord {
+    pub from_id: u32,
+    pub to_id: u32,
+    pub from_name: String,
+    pub to_name: String,
 }
 
-// =============================================================================
-// DEAD CODE
-// =============================================================================
-
-/// Find dead/unused code
-async fn find_dead_code_cmd(
-    project: &Project,
-    limit: Option<usize>,
-    filter: &TraceFilter,
-    xref: bool,
-) -> Result<DeadCodeResult> {
-    debug!("find_dead_code filter={:?} xref={}", filter, xref);
-
-    let index = load_semantic_index(project)?;
-
-    let dead_symbols = find_dead_symbols(&index);
-
-    let mut symbols = Vec::new();
-    let mut by_kind: HashMap<String, usize> = HashMap::new();
-    let mut by_file: HashMap<String, usize> = HashMap::new();
-
-    for sym in dead_symbols {
-        let file = index
-            .file_path(sym.file_id)
-            .map(|p| p.to_string_lossy().to_string())
-            .unwrap_or_else(|| "<unknown>".to_string());
-
-        let name = index.symbol_name(sym).unwrap_or("<unknown>").to_string();
-        let kind = symbol_kind_str(sym.symbol_kind()).to_string();
-
-        // Apply universal filter
-        if !filter.matches_symbol(&name, &kind, &file) {
-            continue;
+/// Which edges `--output-edges` exports for an operation: (symbol, incoming, outgoing)
+fn edge_selection(operation: &TraceOperation) -> Option<(&str, bool, bool)> {
+    match operation {
+        TraceOperation::Callers(symbol) | TraceOperation::CallHierarchy(symbol) => {
+            Some((symbol, true, false))
         }
-
-        *by_kind.entry(kind.clone()).or_insert(0) += 1;
-        *by_file.entry(file.clone()).or_insert(0) += 1;
-
-        // Cross-reference: find potential callers if enabled
-        let potential_callers = if xref {
-            find_potential_callers(&index, sym, &name)
-        } else {
-            Vec::new()
-        };
-
-        symbols.push(DeadSymbol {
-            name,
-            kind,
-            file,
-            line: sym.start_line,
-            reason: "No references or calls found".to_string(),
-            potential_callers,
-        });
-    }
-
-    // Sort by file and line
-    symbols.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
-
-    // Apply limit
-    if let Some(limit) = limit {
-        symbols.truncate(limit);
+        TraceOperation::Callees(symbol) => Some((symbol, false, true)),
+        TraceOperation::Refs { symbol, .. }
+        | TraceOperation::Neighborhood(symbol)
+        | TraceOperation::Trace(symbol) => Some((symbol, true, true)),
+        _ => None,
     }
-
-    Ok(DeadCodeResult {
-        total_dead: symbols.len(),
-        symbols,
-        by_kind,
-        by_file,
-    })
 }
 
-/// Find potential callers for a dead symbol (for cross-referencing)
-fn find_potential_callers(
+/// Collect the edges incident to every symbol with the given name, in index order
+///
+/// Both endpoints must lie in files the filter keeps (`--in`, vendored paths).
+fn collect_symbol_edges(
     index: &SemanticIndex,
-    dead_sym: &crate::trace::Symbol,
-    dead_name: &str,
-) -> Vec<PotentialCaller> {
-    let mut callers = Vec::new();
-
-    // Strategy 1: Find functions in the same file that could call this
-    let same_file_symbols: Vec<_> = index
-        .symbols
+    symbol: &str,
+    incoming: bool,
+    outgoing: bool,
+    filter: &TraceFilter,
+) -> Vec<EdgeRecord> {
+    let targets: HashSet<u32> = index.resolve_symbols(symbol).into_iter().collect();
+    let kept = |id: u32| {
+        index
+            .symbol(id)
+            .and_then(|s| index.file_path(s.file_id))
+            .map(|p| p.to_string_lossy())
+            .is_some_and(|file| filter.matches_path(&file) && !filter.excludes_vendored(&file))
+    };
+    let name = |id: u32| {
+        index
+            .symbol(id)
+            .and_then(|s| index.symbol_name(s))
+            .unwrap_or("<unknown>")
+            .to_string()
+    };
+
+    index
+        .edges
         .iter()
-        .filter(|s| {
-            s.file_id == dead_sym.file_id
-                && s.id != dead_sym.id
-                && matches!(s.symbol_kind(), SymbolKind::Function | SymbolKind::Method)
+        .filter(|e| {
+            (incoming && targets.contains(&e.to_symbol))
+                || (outgoing && targets.contains(&e.from_symbol))
         })
-        .collect();
-
-    for caller in same_file_symbols.iter().take(3) {
-        if let Some(caller_name) = index.symbol_name(caller) {
-            if let Some(path) = index.file_path(caller.file_id) {
-                callers.push(PotentialCaller {
-                    name: caller_name.to_string(),
-                    file: path.to_string_lossy().to_string(),
-                    line: caller.start_line,
-                    reason: "Same file - could call this".to_string(),
-                });
-            }
-        }
-    }
-
-    // Strategy 2: Find entry points that could use this
-    for &entry_id in index.entry_points.iter().take(2) {
-        if let Some(entry_sym) = index.symbols.iter().find(|s| s.id == entry_id) {
-            if entry_sym.id != dead_sym.id {
-                if let Some(entry_name) = index.symbol_name(entry_sym) {
-                    if let Some(path) = index.file_path(entry_sym.file_id) {
-                        callers.push(PotentialCaller {
-                            name: entry_name.to_string(),
-                            file: path.to_string_lossy().to_string(),
-                            line: entry_sym.start_line,
-                            reason: "Entry point - could reach this".to_string(),
-                        });
-                    }
-                }
-            }
-        }
-    }
+        .filter(|e| kept(e.from_symbol) && kept(e.to_symbol))
+        .map(|e| EdgeRecord {
+            from_id: e.from_symbol,
+            to_id: e.to_symbol,
+            from_name: name(e.from_symbol),
+            to_name: name(e.to_symbol),
+        })
+        .collect()
+}
 
-    // Strategy 3: Find token matches (name appears but not as actual reference)
-    if let Some(token_ids) = index.tokens_by_name(dead_name) {
-        for &token_id in token_ids.iter().take(2) {
-            if let Some(token) = index.token(token_id) {
-                // Skip if it's at the definition location
-                if token.file_id != dead_sym.file_id || token.line != dead_sym.start_line {
-                    if let Some(path) = index.file_path(token.file_id) {
-                        callers.push(PotentialCaller {
-                            name: dead_name.to_string(),
-                            file: path.to_string_lossy().to_string(),
-                            line: token.line,
-                            reason: "Token match - name appears here".to_string(),
-                        });
-                    }
+/// Format a trace result, one section per definition when they were not merged
+///
+/// JSON keeps the sections nested under `definitions` in a single document;
+/// CSV concatenates the sections' rows under one header.
+fn format_trace_sections(
+    formatter: &dyn TraceFormatter,
+    format: OutputFormat,
+    result: &TraceResult,
+) -> String {
+    if result.definitions.is_empty() || format == OutputFormat::Json {
+        return formatter.format_trace(result);
+    }
+    if format == OutputFormat::Csv {
+        let rows = CsvFormatter::with_config(CsvConfig { no_header: true });
+        return result
+            .definitions
+            .iter()
+            .enumerate()
+            .map(|(i, section)| {
+                if i == 0 {
+                    formatter.format_trace(section)
+                } else {
+                    rows.format_trace(section)
                 }
-            }
-        }
+            })
+            .collect();
     }
+    result
+        .definitions
+        .iter()
+        .map(|section| formatter.format_trace(section))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
 
-    callers
+/// Render an edge list as CSV (header unless `no_header`) or a JSON array
+fn format_edge_list(edges: &[EdgeRecord], json: bool, no_header: bool) -> String {
+    if json {
+        return serde_json::to_string_pretty(edges).unwrap_or_else(|_| "[]".to_string());
+    }
+    let mut rows = Vec::with_capacity(edges.len() + 1);
+    if !no_header {
+        rows.push("from_id,to_id,from_name,to_name".to_string());
+    }
+    for e in edges {
+        rows.push(format!(
+            "{},{},{},{}",
+            e.from_id,
+            e.to_id,
+            CsvFormatter::escape_csv(&e.from_name),
+            CsvFormatter::escape_csv(&e.to_name)
+        ));
+    }
+    rows.join("\n")
 }
 
 /// Run interactive TUI mode
@@ -2488,44 +1557,27 @@ async fn run_tui(_args: &TraceArgs, _project: &Project) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::trace::output::ReferenceInfo;
+    use std::collections::HashMap;
+    /// Parse a `trace ...` command line into its arguments
+    fn parse_trace(argv: &[&str]) -> TraceArgs {
+        use clap::Parser;
+
+        #[derive(Parser)]
+        struct Cli {
+            #[command(flatten)]
+            trace: TraceArgs,
+        }
+
+        Cli::try_parse_from(argv).unwrap().trace
+    }
 
     #[test]
     fn test_args_output_format() {
         let args = TraceArgs {
             symbol: Some("test".to_string()),
-            direct: false,
-            refs: None,
-            reads: None,
-            writes: None,
-            callers: None,
-            callees: None,
-            type_name: None,
-            module: None,
-            pattern: None,
-            flow: None,
-            impact: None,
-            scope: None,
-            dead: false,
-            stats: false,
-            cycles: false,
-            kind: None,
-            r#in: None,
-            symbol_type: None,
-            name: None,
-            group_by: None,
             json: true,
-            plain: false,
-            csv: false,
-            dot: false,
-            markdown: false,
-            tui: false,
-            max_depth: 10,
-            context: 0,
-            limit: None,
-            count: false,
-            summary: false,
-            xref: false,
-            project: None,
+            ..Default::default()
         };
         assert_eq!(args.output_format(), OutputFormat::Json);
     }
@@ -2534,39 +1586,7 @@ mod tests {
     fn test_args_operations() {
         let args = TraceArgs {
             symbol: Some("test".to_string()),
-            direct: false,
-            refs: None,
-            reads: None,
-            writes: None,
-            callers: None,
-            callees: None,
-            type_name: None,
-            module: None,
-            pattern: None,
-            flow: None,
-            impact: None,
-            scope: None,
-            dead: false,
-            stats: false,
-            cycles: false,
-            kind: None,
-            r#in: None,
-            symbol_type: None,
-            name: None,
-            group_by: None,
-            json: false,
-            plain: false,
-            csv: false,
-            dot: false,
-            markdown: false,
-            tui: false,
-            max_depth: 10,
-            context: 0,
-            limit: None,
-            count: false,
-            summary: false,
-            xref: false,
-            project: None,
+            ..Default::default()
         };
 
         let ops = args.operations();
@@ -2580,40 +1600,8 @@ mod tests {
     #[test]
     fn test_args_refs_operations() {
         let args = TraceArgs {
-            symbol: None,
-            direct: false,
             refs: Some("userId".to_string()),
-            reads: None,
-            writes: None,
-            callers: None,
-            callees: None,
-            type_name: None,
-            module: None,
-            pattern: None,
-            flow: None,
-            impact: None,
-            scope: None,
-            dead: false,
-            stats: false,
-            cycles: false,
-            kind: None,
-            r#in: None,
-            symbol_type: None,
-            name: None,
-            group_by: None,
-            json: false,
-            plain: false,
-            csv: false,
-            dot: false,
-            markdown: false,
-            tui: false,
-            max_depth: 10,
-            context: 0,
-            limit: None,
-            count: false,
-            summary: false,
-            xref: false,
-            project: None,
+            ..Default::default()
         };
 
         let ops = args.operations();
@@ -2632,39 +1620,10 @@ mod tests {
         // Test that multiple flags result in multiple operations
         let args = TraceArgs {
             symbol: Some("test".to_string()),
-            direct: false,
             refs: Some("other".to_string()),
-            reads: None,
-            writes: None,
-            callers: None,
-            callees: None,
-            type_name: None,
-            module: None,
-            pattern: None,
-            flow: None,
-            impact: None,
-            scope: None,
             dead: true,
             stats: true,
-            cycles: false,
-            kind: None,
-            r#in: None,
-            symbol_type: None,
-            name: None,
-            group_by: None,
-            json: false,
-            plain: false,
-            csv: false,
-            dot: false,
-            markdown: false,
-            tui: false,
-            max_depth: 10,
-            context: 0,
-            limit: None,
-            count: false,
-            summary: false,
-            xref: false,
-            project: None,
+            ..Default::default()
         };
 
         let ops = args.operations();
@@ -2684,6 +1643,375 @@ mod tests {
         assert!(has_refs, "Missing Refs operation");
         assert!(has_trace, "Missing Trace operation");
     }
+
+    #[test]
+    fn test_operation_options_from_flags() {
+        // A bare `greppy trace` runs with the library defaults
+        assert_eq!(
+            TraceArgs::default().operation_options(),
+            OperationOptions::default()
+        );
+
+        let options = parse_trace(&["trace", "--refs", "x", "--transitive"]).operation_options();
+        assert_eq!(options.max_depth, TRANSITIVE_DEFAULT_DEPTH);
+        let options = parse_trace(&["trace", "x", "--max-depth", "3", "--no-merge-definitions"])
+            .operation_options();
+        assert_eq!(options.max_depth, 3);
+        assert!(!options.merge_definitions);
+
+        let filter = parse_trace(&["trace", "--dead", "--in", "src", "--in", "lib"]).build_filter();
+        assert_eq!(filter.paths, ["src", "lib"]);
+        assert!(filter.exclude_vendor);
+        let filter = parse_trace(&["trace", "--dead", "--include-vendor"]).build_filter();
+        assert!(!filter.exclude_vendor);
+    }
+
+    #[tokio::test]
+    async fn test_summary_json_is_compact() {
+        use crate::trace::SemanticIndexBuilder;
+        let trace = parse_trace(&["trace", "--dead", "--stats", "--summary", "--json"]);
+        assert!(trace.summary && trace.json);
+        assert_eq!(trace.operations().len(), 2);
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut builder = SemanticIndexBuilder::new(dir.path());
+        builder.add_file(&dir.path().join("src/app.rs"), "fn unused_fn() {}\n");
+        builder.add_file(&dir.path().join("lib/util.rs"), "fn lib_helper() {}\n");
+        let index = builder.build();
+        let project = Project::from_path(dir.path()).unwrap();
+
+        let (options, filter) = (trace.operation_options(), trace.build_filter());
+        let mut combined = CombinedResults::default();
+        for operation in trace.operations() {
+            let result = run_operation(&project, &index, &operation, &options, &filter).await;
+            combined.insert(result.unwrap());
+        }
+
+        let summary = combined.summary();
+        let keys: Vec<_> = summary.as_object().unwrap().keys().cloned().collect();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(summary["dead"]["total"], 2);
+        assert_eq!(summary["stats"]["files"], 2);
+        assert_eq!(summary["stats"]["symbols"], 2);
+
+        // Single line, no full result lists
+        let line = serde_json::to_string(&summary).unwrap();
+        assert!(!line.contains('\n'));
+        assert!(!line.contains("symbols_by_kind"));
+    }
+
+    #[test]
+    fn test_output_edges_incident_to_symbol() {
+        use crate::trace::{Edge, Symbol, SymbolFlags, SymbolKind};
+
+        let mut index = SemanticIndex::new();
+        let file = index.add_file("src/lib.rs".into());
+        for (id, name) in ["main", "target", "helper", "leaf"].iter().enumerate() {
+            let offset = index.strings.intern(name);
+            index.add_symbol(
+                Symbol::new(
+                    id as u32,
+                    offset,
+                    file,
+                    SymbolKind::Function,
+                    SymbolFlags::empty(),
+                    id as u32 * 10 + 1,
+                    id as u32 * 10 + 5,
+                ),
+                name,
+            );
+        }
+        index.add_edge(Edge::new(0, 1, 2));
+        index.add_edge(Edge::new(1, 2, 12));
+        index.add_edge(Edge::new(2, 3, 22));
+        index.add_edge(Edge::new(0, 3, 3));
+
+        let all = TraceFilter::default();
+        let edges = collect_symbol_edges(&index, "target", true, true, &all);
+        let pairs: Vec<_> = edges.iter().map(|e| (e.from_id, e.to_id)).collect();
+        assert_eq!(pairs, vec![(0, 1), (1, 2)]);
+        assert_eq!(edges[0].from_name, "main");
+        assert_eq!(edges[1].to_name, "helper");
+
+        let callers = collect_symbol_edges(&index, "target", true, false, &all);
+        assert_eq!(callers.len(), 1);
+        assert_eq!(
+            format_edge_list(&callers, false, false),
+            "from_id,to_id,from_name,to_name\n0,1,main,target"
+        );
+
+        // --in keeps only edges whose endpoints both lie in matching files
+        let elsewhere = TraceFilter {
+            paths: vec!["src/other".to_string()],
+            ..TraceFilter::default()
+        };
+        assert!(collect_symbol_edges(&index, "target", true, true, &elsewhere).is_empty());
+
+        let odd = EdgeRecord {
+            from_id: 0,
+            to_id: 1,
+            from_name: "a,b".to_string(),
+            to_name: "say \"hi\"".to_string(),
+        };
+        assert_eq!(
+            format_edge_list(&[odd], false, true),
+            "0,1,\"a,b\",\"say \"\"hi\"\"\""
+        );
+    }
+
+    #[tokio::test]
+    async fn test_output_edges_rejects_operations_without_edges() {
+        use crate::core::error::exit_code;
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join(".git")).unwrap();
+        let project = dir.path().to_str().unwrap();
+
+        let trace = parse_trace(&[
+            "trace",
+            "--callers",
+            "target",
+            "--dead",
+            "--output-edges",
+            "--project",
+            project,
+        ]);
+        let err = run(trace).await.unwrap_err();
+        assert!(matches!(err, Error::Usage { .. }), "{}", err);
+        assert_eq!(err.exit_code(), exit_code::USAGE);
+    }
+
+    #[test]
+    fn test_count_by_kind_prints_breakdown_only() {
+        let trace = parse_trace(&["trace", "--refs", "target", "--count", "--by", "kind"]);
+        assert_eq!(trace.by, ["kind"]);
+
+        let result = RefsResult {
+            symbol: "target".to_string(),
+            defined_at: None,
+            symbol_kind: None,
+            references: vec![ReferenceInfo {
+                file: "src/lib.rs".to_string(),
+                line: 7,
+                column: 4,
+                kind: ReferenceKind::Call,
+                context: "target();".to_string(),
+                enclosing_symbol: None,
+                confidence: 1.0,
+                byte_offset: None,
+                callee: None,
+                arg_index: None,
+                target_deprecated: false,
+                referenced_as: None,
+                depth: None,
+            }],
+            total_refs: 3,
+            by_kind: HashMap::from([("call".to_string(), 2), ("read".to_string(), 1)]),
+            by_confidence: HashMap::new(),
+            by_file: HashMap::from([("src/lib.rs".to_string(), 3)]),
+            by_directory: Vec::new(),
+            source_hashes: HashMap::new(),
+            warnings: Vec::new(),
+            symbol_id: None,
+            stable_id: None,
+        };
+
+        let output = format_ref_counts(&result, &trace.by);
+        assert!(output.contains("By kind:"));
+        assert!(output.contains("call  2"));
+        assert!(output.contains("read  1"));
+        assert!(!output.contains("By file:"));
+        assert!(!output.contains("target();"));
+        assert!(!output.contains(":7"));
+    }
+
+    #[tokio::test]
+    async fn test_csv_no_header_starts_with_data_row() {
+        use crate::trace::SemanticIndexBuilder;
+        let code = "fn target() {}\n\nfn caller() {\n    target();\n}\n";
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        std::fs::write(&path, code).unwrap();
+        let mut builder = SemanticIndexBuilder::new(dir.path());
+        builder.add_file(&path, code);
+        let index = builder.build();
+        let project = Project::from_path(dir.path()).unwrap();
+        let operation = TraceOperation::Refs {
+            symbol: "target".to_string(),
+            kind: None,
+        };
+        let result = run_operation(
+            &project,
+            &index,
+            &operation,
+            &OperationOptions::default(),
+            &TraceFilter::default(),
+        )
+        .await
+        .unwrap();
+        let OperationResult::Refs(result) = result else {
+            panic!("expected refs result, got {:?}", result);
+        };
+
+        let render = |argv: &[&str]| {
+            let trace = parse_trace(argv);
+            create_formatter(
+                trace.output_format(),
+                trace.ascii_config(),
+                MarkdownConfig::default(),
+                trace.csv_config(),
+            )
+            .format_refs(&result)
+        };
+
+        let with_header = render(&["trace", "--refs", "target", "--csv"]);
+        assert!(with_header.starts_with("file,line,column,kind"));
+
+        let headerless = render(&["trace", "--refs", "target", "--csv", "--no-header"]);
+        let first = headerless.lines().next().unwrap();
+        assert!(first.starts_with("lib.rs,"), "{}", first);
+        assert_eq!(headerless.lines().count(), with_header.lines().count() - 1);
+    }
+
+    #[tokio::test]
+    async fn test_unmerged_definitions_summary_and_csv() {
+        use crate::trace::SemanticIndexBuilder;
+        let dir = tempfile::tempdir().unwrap();
+        let mut builder = SemanticIndexBuilder::new(dir.path());
+        for (file, caller) in [("a.rs", "run_a"), ("b.rs", "run_b")] {
+            let content = format!(
+                "fn {}() {{\n    helper();\n}}\n\nfn helper() {{}}\n",
+                caller
+            );
+            builder.add_file(&dir.path().join(file), &content);
+        }
+        let index = builder.build();
+        let project = Project::from_path(dir.path()).unwrap();
+
+        async fn render(project: &Project, index: &SemanticIndex, argv: &[&str]) -> String {
+            let trace = parse_trace(argv);
+            let operation = TraceOperation::Callers("helper".to_string());
+            let result = run_operation(
+                project,
+                index,
+                &operation,
+                &trace.operation_options(),
+                &TraceFilter::default(),
+            )
+            .await
+            .unwrap();
+            let formatter = create_formatter(
+                trace.output_format(),
+                trace.ascii_config(),
+                MarkdownConfig::default(),
+                trace.csv_config(),
+            );
+            render_operation(
+                &result,
+                &trace,
+                formatter.as_ref(),
+                trace.output_format(),
+                &project.root,
+            )
+        }
+
+        let summary = render(
+            &project,
+            &index,
+            &["trace", "--callers", "helper", "--summary"],
+        )
+        .await;
+        let split = render(
+            &project,
+            &index,
+            &[
+                "trace",
+                "--callers",
+                "helper",
+                "--no-merge-definitions",
+                "--summary",
+            ],
+        )
+        .await;
+        // Calls resolve by name, so each definition's section has both callers
+        assert_eq!(summary.trim(), "Callers: 2  Paths: 2");
+        assert_eq!(split.trim(), "Callers: 4  Paths: 4");
+
+        let csv = render(
+            &project,
+            &index,
+            &[
+                "trace",
+                "--callers",
+                "helper",
+                "--no-merge-definitions",
+                "--csv",
+            ],
+        )
+        .await;
+        let headers = csv.lines().filter(|l| l.starts_with("path_num,")).count();
+        assert_eq!(headers, 1, "{}", csv);
+        let rows = csv.lines().filter(|l| !l.is_empty()).count();
+        assert_eq!(rows, 1 + 2 * 4, "{}", csv);
+    }
+
+    #[tokio::test]
+    async fn test_run_loads_index_once_for_composed_operations() {
+        use crate::trace::SemanticIndexBuilder;
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join(".git")).unwrap();
+        let path = dir.path().join("lib.rs");
+        let content = "fn used() {}\nfn unused() {}\nfn main() { used(); }\n";
+        std::fs::write(&path, content).unwrap();
+
+        let mut builder = SemanticIndexBuilder::new(dir.path());
+        builder.add_file(&path, content);
+        let index = builder.build();
+        std::fs::create_dir(dir.path().join(".greppy")).unwrap();
+        crate::trace::save_index(&index, trace_index_path(dir.path())).unwrap();
+
+        let project = dir.path().to_str().unwrap();
+        let trace = parse_trace(&["trace", "--dead", "--stats", "--project", project]);
+        let before = INDEX_LOADS.with(|loads| loads.get());
+        run(trace).await.unwrap();
+        assert_eq!(INDEX_LOADS.with(|loads| loads.get()) - before, 1);
+    }
+
+    #[tokio::test]
+    async fn test_first_match_only_exits_check_found() {
+        use crate::core::error::exit_code;
+        use crate::trace::SemanticIndexBuilder;
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join(".git")).unwrap();
+        let path = dir.path().join("lib.rs");
+        let content = "fn main() { dbg!(1); }\n";
+        std::fs::write(&path, content).unwrap();
+
+        let mut builder = SemanticIndexBuilder::new(dir.path());
+        builder.add_file(&path, content);
+        let index = builder.build();
+        std::fs::create_dir(dir.path().join(".greppy")).unwrap();
+        crate::trace::save_index(&index, trace_index_path(dir.path())).unwrap();
+
+        let project = dir.path().to_str().unwrap();
+        let found = parse_trace(&["trace", "--pattern", "dbg!", "-q", "--project", project]);
+        let err = run(found).await.unwrap_err();
+        assert!(matches!(err, Error::CheckFound { .. }), "{}", err);
+        assert_eq!(err.exit_code(), exit_code::CHECK_FOUND);
+
+        let clean = parse_trace(&["trace", "--pattern", "todo!", "-q", "--project", project]);
+        run(clean).await.unwrap();
+    }
+
+    #[test]
+    fn test_trace_without_index_exits_index_missing() {
+        use crate::core::error::exit_code;
+        let dir = tempfile::tempdir().unwrap();
+        let project = Project::from_path(dir.path()).unwrap();
+
+        let err = load_semantic_index(&project).unwrap_err();
+        assert!(matches!(err, Error::IndexNotFound { .. }));
+        assert_eq!(err.exit_code(), exit_code::INDEX_MISSING);
+    }
 }
 
 #[allow(dead_code)]
@@ -5,10 +5,10 @@
 //! @module cli/web
 
 use clap::Args;
-use std::env;
 use std::path::PathBuf;
 
 use crate::core::error::Result;
+use crate::core::project::resolve_project;
 
 /// Arguments for the web command
 #[derive(Args, Debug)]
@@ -33,9 +33,6 @@ pub struct WebArgs {
 
 /// Run the web command
 pub async fn run(args: WebArgs) -> Result<()> {
-    let project_path = args
-        .project
-        .unwrap_or_else(|| env::current_dir().expect("Failed to get current directory"));
-
-    crate::web::server::run(project_path, args.port, args.open).await
+    let project = resolve_project(args.project)?;
+    crate::web::server::run(project, args.port, args.open).await
 }
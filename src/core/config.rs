@@ -4,7 +4,7 @@ use crate::core::error::{Error, Result};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Global configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,8 +16,23 @@ pub struct Config {
     pub index: IndexConfig,
     pub cache: CacheConfig,
     #[serde(default)]
+    pub search: SearchConfig,
+    #[serde(default)]
+    pub daemon: DaemonConfig,
+    #[serde(default)]
+    pub dead_code: DeadCodeConfig,
+    #[serde(default)]
+    pub impact: ImpactConfig,
+    #[serde(default, alias = "llm")]
     pub ai: AiConfig,
     #[serde(default)]
+    pub log: LogConfig,
+    #[serde(default)]
+    pub web: WebConfig,
+    /// Query term expansions, e.g. `auth = ["authentication", "login"]`
+    #[serde(default)]
+    pub aliases: HashMap<String, Vec<String>>,
+    #[serde(default)]
     pub projects: HashMap<String, ProjectConfig>,
 }
 
@@ -26,8 +41,6 @@ pub struct Config {
 pub struct GeneralConfig {
     /// Default result limit
     pub default_limit: usize,
-    /// Auto-start daemon
-    pub daemon_autostart: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,6 +68,17 @@ pub struct IndexConfig {
     pub max_file_size: u64,
     /// Maximum files per project
     pub max_files: usize,
+    /// Only index files tracked by git (`git ls-files`), in `greppy index` and the daemon
+    pub git_tracked_only: bool,
+    /// Lines per search chunk
+    pub chunk_lines: usize,
+    /// Lines repeated at the start of the next chunk, so matches spanning a
+    /// chunk boundary are found whole
+    pub chunk_overlap: usize,
+    /// Soft cap on the text and trace indexes' combined size (bytes); an
+    /// index or import that would exceed it fails before writing, the daemon
+    /// skips saving, and one close to it warns
+    pub max_index_bytes: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -66,6 +90,106 @@ pub struct CacheConfig {
     pub max_queries: usize,
 }
 
+/// Search configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchConfig {
+    /// Match symbol names modulo case and `_`/`-` separators when there is
+    /// no exact match (`UserService` finds `user_service`)
+    pub normalize_names: bool,
+    /// How semantic search reorders BM25 results: "llm" or "embeddings"
+    pub rerank: RerankMode,
+    /// Ollama embedding model used when `rerank = "embeddings"`
+    pub embedding_model: String,
+}
+
+/// Reranking stage for semantic search
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RerankMode {
+    /// Ask the configured AI provider to order the results
+    #[default]
+    Llm,
+    /// Order by cosine similarity of local Ollama embeddings
+    Embeddings,
+}
+
+/// Log file configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct LogConfig {
+    /// Also write logs to this file, rotated daily (`GREPPY_LOG_FILE` overrides)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<PathBuf>,
+}
+
+/// Web UI configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct WebConfig {
+    /// Token every request must present (`Authorization: Bearer` or `?token=`);
+    /// unset serves without authentication
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth_token: Option<String>,
+    /// Read the most-referenced source files into the context cache at
+    /// startup, up to its size limit, so the first detail views are instant
+    pub warm_context: bool,
+}
+
+/// Daemon lifecycle configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DaemonConfig {
+    /// Start the daemon on demand when a command wants it
+    pub autostart: bool,
+    /// How long to wait for an auto-started daemon to answer (milliseconds)
+    pub ready_timeout_ms: u64,
+    /// Project paths whose indexes are loaded into memory at startup
+    pub preload: Vec<PathBuf>,
+    /// How many recently searched projects to remember and preload next boot (0 disables)
+    pub remember_recent: usize,
+}
+
+/// Dead code analysis configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeadCodeConfig {
+    /// Symbol name patterns (regex) that are never reported dead,
+    /// e.g. handlers registered via reflection or DI containers
+    pub keep_alive_patterns: Vec<String>,
+    /// Never report symbols that carry a decorator/attribute
+    pub keep_decorated: bool,
+    /// Symbol name patterns (regex) that are unused on purpose, e.g.
+    /// `^_` placeholders; a `// greppy:ignore-dead` comment on the line
+    /// above a definition does the same for a single symbol
+    pub ignore_names: Vec<String>,
+}
+
+/// Impact analysis risk scoring
+///
+/// Each contributor adds `count * weight` to the risk score; the score is
+/// then bucketed into a risk level by the thresholds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImpactConfig {
+    /// Weight per affected entry point
+    pub entry_point_weight: u32,
+    /// Weight per affected file
+    pub file_weight: u32,
+    /// Weight per direct caller
+    pub direct_caller_weight: u32,
+    /// Weight per transitive caller
+    pub transitive_caller_weight: u32,
+    /// Flat weight when the symbol is part of a call cycle
+    pub cycle_weight: u32,
+    /// Minimum score for medium risk
+    pub medium_threshold: u32,
+    /// Minimum score for high risk
+    pub high_threshold: u32,
+    /// Minimum score for critical risk
+    pub critical_threshold: u32,
+}
+
 /// AI provider configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
@@ -85,6 +209,12 @@ pub struct AiConfig {
     /// Saved AI profiles for quick switching
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub profiles: HashMap<String, AiProfile>,
+    /// Maximum results/paths sent to the model for reranking (default: all)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rerank_max_items: Option<usize>,
+    /// Maximum snippet lines per result sent for reranking (default: no line cap)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rerank_snippet_lines: Option<usize>,
 }
 
 /// A saved AI profile (model configuration with optional tokens)
@@ -125,6 +255,8 @@ impl Default for AiConfig {
             google_token: None,
             anthropic_token: None,
             profiles: HashMap::new(),
+            rerank_max_items: None,
+            rerank_snippet_lines: None,
         }
     }
 }
@@ -143,17 +275,47 @@ impl Default for Config {
             ignore: IgnoreConfig::default(),
             index: IndexConfig::default(),
             cache: CacheConfig::default(),
+            search: SearchConfig::default(),
+            daemon: DaemonConfig::default(),
+            dead_code: DeadCodeConfig::default(),
+            impact: ImpactConfig::default(),
             ai: AiConfig::default(),
+            log: LogConfig::default(),
+            web: WebConfig::default(),
+            aliases: HashMap::new(),
             projects: HashMap::new(),
         }
     }
 }
 
 impl Default for GeneralConfig {
+    fn default() -> Self {
+        Self { default_limit: 20 }
+    }
+}
+
+impl Default for DaemonConfig {
     fn default() -> Self {
         Self {
-            default_limit: 20,
-            daemon_autostart: false,
+            autostart: true,
+            ready_timeout_ms: 3000,
+            preload: Vec::new(),
+            remember_recent: 5,
+        }
+    }
+}
+
+impl Default for ImpactConfig {
+    fn default() -> Self {
+        Self {
+            entry_point_weight: 10,
+            file_weight: 2,
+            direct_caller_weight: 3,
+            transitive_caller_weight: 1,
+            cycle_weight: 15,
+            medium_threshold: 15,
+            high_threshold: 40,
+            critical_threshold: 100,
         }
     }
 }
@@ -189,6 +351,10 @@ impl Default for IndexConfig {
         Self {
             max_file_size: 1_048_576, // 1MB
             max_files: 100_000,
+            git_tracked_only: false,
+            chunk_lines: CHUNK_MAX_LINES,
+            chunk_overlap: CHUNK_OVERLAP,
+            max_index_bytes: None,
         }
     }
 }
@@ -202,13 +368,39 @@ impl Default for CacheConfig {
     }
 }
 
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            normalize_names: false,
+            rerank: RerankMode::Llm,
+            embedding_model: "nomic-embed-text".to_string(),
+        }
+    }
+}
+
+impl Default for DeadCodeConfig {
+    fn default() -> Self {
+        Self {
+            keep_alive_patterns: vec![],
+            keep_decorated: true,
+            ignore_names: vec![],
+        }
+    }
+}
+
+/// Config keys that an environment variable overrides at runtime
+pub const ENV_OVERRIDES: &[(&str, &str)] = &[("log.file", "GREPPY_LOG_FILE")];
+
 impl Config {
     /// Load configuration from default location
     pub fn load() -> Result<Self> {
-        let config_path = Self::config_path()?;
+        Self::load_from(&Self::config_path()?)
+    }
 
-        if config_path.exists() {
-            let content = std::fs::read_to_string(&config_path)?;
+    /// Load configuration from `path`, using defaults if it does not exist
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if path.exists() {
+            let content = std::fs::read_to_string(path)?;
             let config: Config = toml::from_str(&content)?;
             Ok(config)
         } else {
@@ -219,14 +411,82 @@ impl Config {
     /// Save configuration to default location
     pub fn save(&self) -> Result<()> {
         Self::ensure_home()?;
-        let config_path = Self::config_path()?;
+        self.save_to(&Self::config_path()?)
+    }
+
+    /// Save configuration to `path`
+    pub fn save_to(&self, path: &Path) -> Result<()> {
         let content = toml::to_string_pretty(self).map_err(|e| Error::ConfigError {
             message: format!("Failed to serialize config: {}", e),
         })?;
-        std::fs::write(&config_path, content)?;
+        std::fs::write(path, content)?;
         Ok(())
     }
 
+    /// Read a dotted key such as `search.rerank`
+    pub fn get_value(&self, key: &str) -> Result<toml::Value> {
+        let root = self.to_toml()?;
+        lookup(&root, key)
+            .cloned()
+            .ok_or_else(|| unknown_key(&root, key))
+    }
+
+    /// Set a dotted key from its command-line form
+    ///
+    /// The value is parsed as the type the key already holds, and the result
+    /// must deserialize back into a valid config with the key present.
+    pub fn set_value(&mut self, key: &str, raw: &str) -> Result<()> {
+        let mut root = self.to_toml()?;
+        let (parent_key, leaf) = key.rsplit_once('.').unwrap_or(("", key));
+        let parent = if parent_key.is_empty() {
+            Some(&root)
+        } else {
+            lookup(&root, parent_key)
+        };
+        let Some(toml::Value::Table(parent)) = parent else {
+            return Err(unknown_key(&root, key));
+        };
+        let value = parse_value(raw, parent.get(leaf)).map_err(|message| Error::ConfigError {
+            message: format!("Invalid value for `{}`: {}", key, message),
+        })?;
+
+        if let Some(toml::Value::Table(parent)) = lookup_mut(&mut root, parent_key) {
+            parent.insert(leaf.to_string(), value.clone());
+        }
+        let updated: Config = root.clone().try_into().map_err(|e| Error::ConfigError {
+            message: format!("Invalid value for `{}`: {}", key, e),
+        })?;
+
+        // Keys the config does not know are silently dropped by serde
+        if lookup(&updated.to_toml()?, key) != Some(&value) {
+            return Err(unknown_key(&self.to_toml()?, key));
+        }
+        *self = updated;
+        Ok(())
+    }
+
+    /// Every set key with its value, sorted by key
+    pub fn entries(&self) -> Result<Vec<(String, toml::Value)>> {
+        let mut entries = Vec::new();
+        flatten("", &self.to_toml()?, &mut entries);
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(entries)
+    }
+
+    /// Environment variable overriding `key` and its value, if one is set
+    pub fn env_override(key: &str) -> Option<(&'static str, String)> {
+        ENV_OVERRIDES
+            .iter()
+            .filter(|(k, _)| *k == key)
+            .find_map(|(_, var)| std::env::var(var).ok().map(|value| (*var, value)))
+    }
+
+    fn to_toml(&self) -> Result<toml::Value> {
+        toml::Value::try_from(self).map_err(|e| Error::ConfigError {
+            message: format!("Failed to serialize config: {}", e),
+        })
+    }
+
     /// Get the configuration file path
     pub fn config_path() -> Result<PathBuf> {
         let home = Self::greppy_home()?;
@@ -255,6 +515,11 @@ impl Config {
         Ok(home.join("indexes").join(format!("{:016x}", hash)))
     }
 
+    /// Get the file listing projects the daemon served most recently
+    pub fn recent_projects_path() -> Result<PathBuf> {
+        Ok(Self::greppy_home()?.join("recent.json"))
+    }
+
     /// Get registry file path (tracks indexed projects)
     pub fn registry_path() -> Result<PathBuf> {
         Ok(Self::greppy_home()?.join("registry.json"))
@@ -299,6 +564,93 @@ impl Config {
     }
 }
 
+/// Follow a dotted key through nested tables
+fn lookup<'a>(root: &'a toml::Value, key: &str) -> Option<&'a toml::Value> {
+    key.split('.').try_fold(root, |value, part| value.get(part))
+}
+
+fn lookup_mut<'a>(root: &'a mut toml::Value, key: &str) -> Option<&'a mut toml::Value> {
+    if key.is_empty() {
+        return Some(root);
+    }
+    key.split('.')
+        .try_fold(root, |value, part| value.get_mut(part))
+}
+
+/// Error for a key the config does not have, suggesting same-named keys
+fn unknown_key(root: &toml::Value, key: &str) -> Error {
+    let leaf = key.rsplit('.').next().unwrap_or(key);
+    let mut entries = Vec::new();
+    flatten("", root, &mut entries);
+    let suggestions: Vec<String> = entries
+        .into_iter()
+        .map(|(k, _)| k)
+        .filter(|k| k.rsplit('.').next() == Some(leaf))
+        .map(|k| format!("`{}`", k))
+        .collect();
+
+    let hint = if suggestions.is_empty() {
+        " (run `greppy config list` to see all keys)".to_string()
+    } else {
+        format!(" (did you mean {}?)", suggestions.join(" or "))
+    };
+    Error::ConfigError {
+        message: format!("Unknown config key `{}`{}", key, hint),
+    }
+}
+
+/// Parse a command-line value as the type of the value it replaces
+fn parse_value(
+    raw: &str,
+    current: Option<&toml::Value>,
+) -> std::result::Result<toml::Value, String> {
+    use toml::Value;
+
+    match current {
+        Some(Value::String(_)) => Ok(Value::String(raw.to_string())),
+        Some(Value::Integer(_)) => raw
+            .parse()
+            .map(Value::Integer)
+            .map_err(|_| format!("expected an integer, got `{}`", raw)),
+        Some(Value::Float(_)) => raw
+            .parse()
+            .map(Value::Float)
+            .map_err(|_| format!("expected a number, got `{}`", raw)),
+        Some(Value::Boolean(_)) => raw
+            .parse()
+            .map(Value::Boolean)
+            .map_err(|_| format!("expected true or false, got `{}`", raw)),
+        Some(Value::Array(_)) if !raw.trim_start().starts_with('[') => Ok(Value::Array(
+            raw.split(',')
+                .map(|item| Value::String(item.trim().to_string()))
+                .filter(|item| item.as_str() != Some(""))
+                .collect(),
+        )),
+        // New keys and inline arrays/tables: TOML syntax, else a bare string
+        _ => Ok(toml::from_str::<toml::Table>(&format!("v = {}", raw))
+            .ok()
+            .and_then(|mut table| table.remove("v"))
+            .unwrap_or_else(|| Value::String(raw.to_string()))),
+    }
+}
+
+/// Collect `(dotted key, value)` for every non-table value
+fn flatten(prefix: &str, value: &toml::Value, out: &mut Vec<(String, toml::Value)>) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table {
+                let key = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten(&key, value, out);
+            }
+        }
+        other => out.push((prefix.to_string(), other.clone())),
+    }
+}
+
 /// Default daemon port for Windows TCP connection
 #[cfg(windows)]
 const DEFAULT_DAEMON_PORT: u16 = 19532;
@@ -306,3 +658,56 @@ const DEFAULT_DAEMON_PORT: u16 = 19532;
 pub const MAX_FILE_SIZE: u64 = 1_048_576; // 1MB
 pub const CHUNK_MAX_LINES: usize = 50;
 pub const CHUNK_OVERLAP: usize = 5;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_then_get_round_trips_through_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+
+        let mut config = Config::load_from(&path).unwrap();
+        config.set_value("general.default_limit", "50").unwrap();
+        config.set_value("search.rerank", "embeddings").unwrap();
+        config.save_to(&path).unwrap();
+
+        let loaded = Config::load_from(&path).unwrap();
+        assert_eq!(
+            loaded.get_value("general.default_limit").unwrap(),
+            toml::Value::Integer(50)
+        );
+        assert_eq!(
+            loaded.get_value("search.rerank").unwrap(),
+            toml::Value::String("embeddings".to_string())
+        );
+    }
+
+    #[test]
+    fn test_set_rejects_unknown_keys_and_bad_values() {
+        let mut config = Config::default();
+
+        let err = config.set_value("search.default_limit", "50").unwrap_err();
+        assert!(err.to_string().contains("general.default_limit"));
+        assert!(config.set_value("general.nope", "1").is_err());
+        assert!(config.set_value("general.default_limit", "many").is_err());
+        assert!(config.set_value("search.rerank", "magic").is_err());
+        assert_eq!(
+            config.get_value("general.default_limit").unwrap(),
+            toml::Value::Integer(20)
+        );
+    }
+
+    #[test]
+    fn test_old_general_daemon_autostart_is_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "[general]\ndaemon_autostart = false\n").unwrap();
+
+        // Superseded by [daemon] autostart, which keeps its own default
+        let config = Config::load_from(&path).unwrap();
+        assert!(config.daemon.autostart);
+        assert!(config.get_value("general.daemon_autostart").is_err());
+    }
+}
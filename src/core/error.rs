@@ -6,16 +6,36 @@ use thiserror::Error;
 /// Result type alias using Greppy's Error
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Process exit codes, stable across releases so scripts can branch on them
+pub mod exit_code {
+    /// Command completed successfully
+    pub const SUCCESS: u8 = 0;
+    /// Unclassified failure
+    pub const FAILURE: u8 = 1;
+    /// Invalid arguments or flags (from the argument parser or a command's own checks)
+    pub const USAGE: u8 = 2;
+    /// The project has not been indexed yet
+    pub const INDEX_MISSING: u8 = 3;
+    /// The daemon could not be reached
+    pub const DAEMON_UNAVAILABLE: u8 = 4;
+    /// Authentication with an AI provider failed
+    pub const AUTH: u8 = 5;
+    /// `trace --pattern --first-match-only` found a match (check mode)
+    pub const CHECK_FOUND: u8 = 6;
+}
+
 /// Greppy error types
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("Project not found: {path}")]
     ProjectNotFound { path: PathBuf },
 
-    #[error("No project root found (looked for .git, package.json, Cargo.toml, etc.)")]
+    #[error(
+        "No project root found (looked for .git, package.json, Cargo.toml, etc.); run inside a project or pass --project"
+    )]
     NoProjectRoot,
 
-    #[error("Index not found for project: {path}")]
+    #[error("Index not found for project: {path}. Run 'greppy index' first.")]
     IndexNotFound { path: PathBuf },
 
     #[error("Index error: {message}")]
@@ -33,6 +53,15 @@ pub enum Error {
     #[error("Daemon not running")]
     DaemonNotRunning,
 
+    #[error("Invalid usage: {message}")]
+    Usage { message: String },
+
+    #[error("No match for pattern: {pattern}")]
+    NoMatch { pattern: String },
+
+    #[error("Found a match for pattern: {pattern}")]
+    CheckFound { pattern: String },
+
     #[error("Watch error: {message}")]
     WatchError { message: String },
 
@@ -51,3 +80,50 @@ pub enum Error {
     #[error("Auth error: {0}")]
     Auth(#[from] anyhow::Error),
 }
+
+impl Error {
+    /// Exit code reported to the shell when this error ends the process
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::Usage { .. } => exit_code::USAGE,
+            Error::IndexNotFound { .. } => exit_code::INDEX_MISSING,
+            Error::DaemonNotRunning => exit_code::DAEMON_UNAVAILABLE,
+            Error::Auth(_) => exit_code::AUTH,
+            Error::CheckFound { .. } => exit_code::CHECK_FOUND,
+            _ => exit_code::FAILURE,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes_are_distinct_per_kind() {
+        let index = Error::IndexNotFound {
+            path: PathBuf::from("/tmp/project"),
+        };
+        assert_eq!(index.exit_code(), exit_code::INDEX_MISSING);
+        let usage = Error::Usage {
+            message: "--output-edges needs a symbol operation".to_string(),
+        };
+        assert_eq!(usage.exit_code(), exit_code::USAGE);
+        assert_eq!(
+            Error::DaemonNotRunning.exit_code(),
+            exit_code::DAEMON_UNAVAILABLE
+        );
+        assert_eq!(
+            Error::Auth(anyhow::anyhow!("expired token")).exit_code(),
+            exit_code::AUTH
+        );
+        let other = Error::SearchError {
+            message: "bad query".to_string(),
+        };
+        assert_eq!(other.exit_code(), exit_code::FAILURE);
+        let found = Error::CheckFound {
+            pattern: "dbg!".to_string(),
+        };
+        assert_eq!(found.exit_code(), exit_code::CHECK_FOUND);
+    }
+}
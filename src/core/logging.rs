@@ -0,0 +1,93 @@
+//! Logging setup
+//!
+//! Logs always go to stderr. When `GREPPY_LOG_FILE` or `[log] file` is set,
+//! they are also written to that file with daily rotation, which keeps a
+//! record of what the long-lived daemon did.
+
+use std::path::{Path, PathBuf};
+
+use tracing::Subscriber;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{fmt, prelude::*, EnvFilter, Layer};
+
+use crate::core::config::LogConfig;
+use crate::core::error::{Error, Result};
+
+/// Resolve the log file path: `GREPPY_LOG_FILE` first, then `[log] file`
+pub fn log_file_path(config: &LogConfig) -> Option<PathBuf> {
+    match std::env::var("GREPPY_LOG_FILE") {
+        Ok(path) if !path.is_empty() => Some(PathBuf::from(path)),
+        _ => config.file.clone(),
+    }
+}
+
+/// Build a plain-text fmt layer writing to a daily-rotated file
+///
+/// Rotated files are named `<file>.YYYY-MM-DD` next to the configured path.
+pub fn file_layer<S>(path: &Path) -> Result<impl Layer<S>>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| Error::ConfigError {
+            message: format!("Invalid log file path: {}", path.display()),
+        })?;
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    std::fs::create_dir_all(dir)?;
+
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(file_name)
+        .build(dir)
+        .map_err(|e| Error::ConfigError {
+            message: format!("Cannot open log file {}: {}", path.display(), e),
+        })?;
+    Ok(fmt::layer().with_writer(appender).with_ansi(false))
+}
+
+/// Install the global subscriber: stderr plus the optional log file
+pub fn init(filter: EnvFilter, config: &LogConfig) {
+    let file = log_file_path(config).and_then(|path| match file_layer(&path) {
+        Ok(layer) => Some(layer),
+        Err(e) => {
+            eprintln!("Warning: not logging to {}: {}", path.display(), e);
+            None
+        }
+    });
+
+    tracing_subscriber::registry()
+        .with(fmt::layer())
+        .with(file)
+        .with(filter)
+        .init();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_file_layer_writes_events() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("logs").join("greppy.log");
+
+        let subscriber = tracing_subscriber::registry().with(file_layer(&path).unwrap());
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(files = 3, "daemon reindexed project");
+        });
+
+        let written: String = std::fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .map(|entry| std::fs::read_to_string(entry.unwrap().path()).unwrap())
+            .collect();
+        assert!(written.contains("daemon reindexed project"));
+        assert!(written.contains("files=3"));
+    }
+}
@@ -2,6 +2,8 @@
 
 use crate::core::config::Config;
 use crate::core::error::{Error, Result};
+use crate::trace::detect_language;
+use ignore::WalkBuilder;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -25,6 +27,28 @@ const PROJECT_MARKERS: &[&str] = &[
     "bun.lockb",      // Bun
 ];
 
+/// File that authoritatively marks a project root, overriding nearer markers
+const ROOT_MARKER_FILE: &str = ".greppy.toml";
+
+/// Contents of a `.greppy.toml` root marker
+#[derive(Debug, Default, Deserialize)]
+struct RootMarker {
+    #[serde(default)]
+    project: RootMarkerProject,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RootMarkerProject {
+    /// Root relative to the marker's directory (default: the directory itself)
+    root: Option<PathBuf>,
+}
+
+/// Maximum files inspected when detecting a project's languages
+const LANGUAGE_SCAN_LIMIT: usize = 2000;
+
+/// Detected languages that are data or markup rather than source code
+const NON_SOURCE_LANGUAGES: &[&str] = &["unknown", "json", "yaml", "toml", "markdown"];
+
 /// Represents a detected project
 #[derive(Debug, Clone)]
 pub struct Project {
@@ -34,6 +58,8 @@ pub struct Project {
     pub project_type: ProjectType,
     /// Name of the project (directory name)
     pub name: String,
+    /// Source languages by file count, most common first
+    pub languages: Vec<(String, usize)>,
 }
 
 /// Type of project based on detected marker
@@ -64,10 +90,13 @@ impl Project {
             .map(|n| n.to_string_lossy().to_string())
             .unwrap_or_else(|| "unknown".to_string());
 
+        let languages = scan_languages(&root);
+
         Ok(Self {
             root,
             project_type,
             name,
+            languages,
         })
     }
 
@@ -87,12 +116,59 @@ impl Project {
             .map(|n| n.to_string_lossy().to_string())
             .unwrap_or_else(|| "unknown".to_string());
 
+        let languages = scan_languages(&root);
+
         Ok(Self {
             root,
             project_type,
             name,
+            languages,
         })
     }
+
+    /// The most common source language, if any source files were found
+    pub fn primary_language(&self) -> Option<&str> {
+        self.languages.first().map(|(lang, _)| lang.as_str())
+    }
+}
+
+/// Resolve the project a command operates on
+///
+/// An explicit `--project` path must exist; the project is detected upward
+/// from it, or rooted at the path itself when no marker is found. Without
+/// a path, the project is detected upward from the current directory.
+pub fn resolve_project(path: Option<PathBuf>) -> Result<Project> {
+    let cwd = std::env::current_dir()?;
+    resolve_project_from(path, &cwd)
+}
+
+/// Resolve the project a command creates data for, like `import`
+///
+/// As [`resolve_project`] from `cwd`, except that without a path and with
+/// no marker above `cwd`, the project is rooted at `cwd` itself: the target
+/// may be a plain directory on a machine that never had the source.
+pub fn resolve_target_project(path: Option<PathBuf>, cwd: &Path) -> Result<Project> {
+    match resolve_project_from(path, cwd) {
+        Err(Error::NoProjectRoot) => Project::from_path(cwd),
+        result => result,
+    }
+}
+
+/// [`resolve_project`] with an explicit working directory
+fn resolve_project_from(path: Option<PathBuf>, cwd: &Path) -> Result<Project> {
+    match path {
+        Some(path) => {
+            let path = cwd.join(path);
+            if !path.exists() {
+                return Err(Error::ProjectNotFound { path });
+            }
+            match Project::detect(&path) {
+                Err(Error::NoProjectRoot) => Project::from_path(&path),
+                result => result,
+            }
+        }
+        None => Project::detect(cwd),
+    }
 }
 
 /// Find project root by searching upward for markers
@@ -105,6 +181,11 @@ fn find_project_root(start: &Path) -> Result<PathBuf> {
 
     let mut current = start.canonicalize().map_err(|_| Error::NoProjectRoot)?;
 
+    // An explicit marker anywhere above wins over nearer package markers
+    if let Some(root) = find_marked_root(&current)? {
+        return Ok(root);
+    }
+
     loop {
         // Check for any project marker
         for marker in PROJECT_MARKERS {
@@ -123,9 +204,67 @@ fn find_project_root(start: &Path) -> Result<PathBuf> {
     Err(Error::NoProjectRoot)
 }
 
+/// Find the nearest `.greppy.toml` above `start` and resolve its root
+///
+/// The marker may pin a different directory with `[project] root = "..."`,
+/// resolved relative to the marker file.
+fn find_marked_root(start: &Path) -> Result<Option<PathBuf>> {
+    let Some(dir) = start
+        .ancestors()
+        .find(|dir| dir.join(ROOT_MARKER_FILE).is_file())
+    else {
+        return Ok(None);
+    };
+
+    let content = std::fs::read_to_string(dir.join(ROOT_MARKER_FILE))?;
+    let marker: RootMarker = toml::from_str(&content)?;
+    let root = match marker.project.root {
+        Some(root) => {
+            let pinned = dir.join(root);
+            pinned.canonicalize().map_err(|_| Error::ConfigError {
+                message: format!(
+                    "{} points at a missing root: {}",
+                    dir.join(ROOT_MARKER_FILE).display(),
+                    pinned.display()
+                ),
+            })?
+        }
+        None => dir.to_path_buf(),
+    };
+    Ok(Some(root))
+}
+
+/// Count source files per language with a bounded, gitignore-aware walk
+fn scan_languages(root: &Path) -> Vec<(String, usize)> {
+    let mut counts: HashMap<&'static str, usize> = HashMap::new();
+
+    let files = WalkBuilder::new(root)
+        .hidden(true)
+        .git_ignore(true)
+        .build()
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .take(LANGUAGE_SCAN_LIMIT);
+
+    for entry in files {
+        let lang = detect_language(entry.path());
+        if !NON_SOURCE_LANGUAGES.contains(&lang) {
+            *counts.entry(lang).or_insert(0) += 1;
+        }
+    }
+
+    let mut languages: Vec<(String, usize)> = counts
+        .into_iter()
+        .map(|(lang, count)| (lang.to_string(), count))
+        .collect();
+    languages.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    languages
+}
+
 /// Detect project type from root directory
 fn detect_project_type(root: &Path) -> ProjectType {
     for (marker, project_type) in [
+        (ROOT_MARKER_FILE, ProjectType::Greppy),
         (".greppy", ProjectType::Greppy),
         (".git", ProjectType::Git),
         ("package.json", ProjectType::NodeJs),
@@ -177,6 +316,100 @@ mod tests {
         assert_eq!(project.project_type, ProjectType::Rust);
     }
 
+    #[test]
+    fn test_detect_primary_language() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(temp.path().join("pyproject.toml"), "[project]").unwrap();
+        std::fs::create_dir(temp.path().join("pkg")).unwrap();
+        for name in ["a.py", "b.py", "c.py", "pkg/d.py"] {
+            std::fs::write(temp.path().join(name), "def f():\n    pass\n").unwrap();
+        }
+        std::fs::write(temp.path().join("build.sh"), "echo hi\n").unwrap();
+        std::fs::write(temp.path().join("README.md"), "# Docs\n").unwrap();
+
+        let project = Project::detect(temp.path()).unwrap();
+        assert_eq!(project.primary_language(), Some("python"));
+        assert_eq!(
+            project.languages,
+            vec![("python".to_string(), 4), ("bash".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn test_greppy_toml_overrides_git_and_nested_markers() {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir(temp.path().join(".git")).unwrap();
+
+        let workspace = temp.path().join("services");
+        let package = workspace.join("api");
+        std::fs::create_dir_all(package.join("src")).unwrap();
+        std::fs::write(workspace.join(".greppy.toml"), "").unwrap();
+        std::fs::write(package.join("package.json"), "{}").unwrap();
+
+        // Nearer package.json and higher .git both lose to the explicit marker
+        let project = Project::detect(&package.join("src")).unwrap();
+        assert_eq!(project.root, workspace.canonicalize().unwrap());
+        assert_eq!(project.project_type, ProjectType::Greppy);
+
+        // [project] root pins a directory relative to the marker
+        std::fs::write(
+            workspace.join(".greppy.toml"),
+            "[project]\nroot = \"api\"\n",
+        )
+        .unwrap();
+        let project = Project::detect(&package.join("src")).unwrap();
+        assert_eq!(project.root, package.canonicalize().unwrap());
+    }
+
+    #[test]
+    fn test_resolve_project_explicit_path() {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir(temp.path().join(".git")).unwrap();
+        let nested = temp.path().join("src");
+        std::fs::create_dir(&nested).unwrap();
+        let elsewhere = TempDir::new().unwrap();
+
+        // An explicit path wins over the working directory and searches upward
+        let project = resolve_project_from(Some(nested), elsewhere.path()).unwrap();
+        assert_eq!(project.root, temp.path().canonicalize().unwrap());
+
+        // Relative paths are taken from the working directory
+        let project = resolve_project_from(Some("src".into()), temp.path()).unwrap();
+        assert_eq!(project.root, temp.path().canonicalize().unwrap());
+
+        // A marker-less directory is used as given
+        let project = resolve_project_from(Some(elsewhere.path().into()), temp.path()).unwrap();
+        assert_eq!(project.root, elsewhere.path().canonicalize().unwrap());
+    }
+
+    #[test]
+    fn test_resolve_project_missing_explicit_path() {
+        let temp = TempDir::new().unwrap();
+        let missing = temp.path().join("missing");
+        let err = resolve_project_from(Some(missing.clone()), temp.path()).unwrap_err();
+        assert!(matches!(err, Error::ProjectNotFound { path } if path == missing));
+    }
+
+    #[test]
+    fn test_resolve_project_detects_upward_from_cwd() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(temp.path().join("go.mod"), "module x").unwrap();
+        let nested = temp.path().join("cmd").join("app");
+        std::fs::create_dir_all(&nested).unwrap();
+
+        let project = resolve_project_from(None, &nested).unwrap();
+        assert_eq!(project.root, temp.path().canonicalize().unwrap());
+        assert_eq!(project.project_type, ProjectType::Go);
+    }
+
+    #[test]
+    fn test_resolve_project_not_found_from_cwd() {
+        let temp = TempDir::new().unwrap();
+        let err = resolve_project_from(None, temp.path()).unwrap_err();
+        assert!(matches!(err, Error::NoProjectRoot));
+        assert!(err.to_string().contains("--project"));
+    }
+
     #[test]
     fn test_no_project_root() {
         let temp = TempDir::new().unwrap();
@@ -1,11 +1,14 @@
 //! Client for communicating with daemon
 
-use crate::core::config::Config;
+use crate::core::config::{Config, DaemonConfig};
 use crate::core::error::{Error, Result};
+use crate::daemon::events::DaemonEvent;
+use crate::daemon::process;
 use crate::daemon::protocol::{Method, Request, Response, ResponseResult};
 use std::io::{BufRead, BufReader, Write};
 use std::path::Path;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use tracing::debug;
 
 #[cfg(unix)]
 use std::os::unix::net::UnixStream;
@@ -19,6 +22,12 @@ const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
 /// Extended timeout for indexing operations (10 minutes)
 const INDEX_TIMEOUT: Duration = Duration::from_secs(600);
 
+/// Timeout for liveness checks
+const PING_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Interval between readiness checks after an auto-start
+const READY_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
 /// Check if daemon is running (Unix: check socket file exists)
 #[cfg(unix)]
 pub fn is_running() -> Result<bool> {
@@ -36,9 +45,19 @@ pub fn is_running() -> Result<bool> {
 /// Connect to the daemon (Unix: Unix socket)
 #[cfg(unix)]
 fn connect_with_timeout(read_timeout: Duration) -> Result<UnixStream> {
-    let socket_path = Config::socket_path()?;
-    let stream = UnixStream::connect(&socket_path).map_err(|e| Error::DaemonError {
-        message: format!("Failed to connect to daemon: {}", e),
+    connect_socket(&Config::socket_path()?, read_timeout)
+}
+
+/// Connect to the daemon listening on `socket_path`
+#[cfg(unix)]
+fn connect_socket(socket_path: &Path, read_timeout: Duration) -> Result<UnixStream> {
+    let stream = UnixStream::connect(socket_path).map_err(|e| {
+        debug!(
+            "Failed to connect to daemon at {}: {}",
+            socket_path.display(),
+            e
+        );
+        Error::DaemonNotRunning
     })?;
     stream
         .set_read_timeout(Some(read_timeout))
@@ -57,8 +76,9 @@ fn connect_with_timeout(read_timeout: Duration) -> Result<UnixStream> {
 #[cfg(windows)]
 fn connect_with_timeout(read_timeout: Duration) -> Result<TcpStream> {
     let port_path = Config::port_path()?;
-    let port_str = std::fs::read_to_string(&port_path).map_err(|e| Error::DaemonError {
-        message: format!("Failed to read daemon port file: {}", e),
+    let port_str = std::fs::read_to_string(&port_path).map_err(|e| {
+        debug!("Failed to read daemon port file: {}", e);
+        Error::DaemonNotRunning
     })?;
     let port: u16 = port_str.trim().parse().map_err(|e| Error::DaemonError {
         message: format!("Invalid port in daemon port file: {}", e),
@@ -66,8 +86,9 @@ fn connect_with_timeout(read_timeout: Duration) -> Result<TcpStream> {
 
     let addr = format!("127.0.0.1:{}", port);
     let stream = TcpStream::connect_timeout(&addr.parse().unwrap(), Duration::from_secs(5))
-        .map_err(|e| Error::DaemonError {
-            message: format!("Failed to connect to daemon at {}: {}", addr, e),
+        .map_err(|e| {
+            debug!("Failed to connect to daemon at {}: {}", addr, e);
+            Error::DaemonNotRunning
         })?;
     stream
         .set_read_timeout(Some(read_timeout))
@@ -114,30 +135,60 @@ fn send_request<S: Write + std::io::Read>(stream: &mut S, request: &Request) ->
 }
 
 /// Send a search request to the daemon
+///
+/// `within` restricts results to chunks under that path.
 pub async fn search(
     query: &str,
     project: &Path,
     limit: usize,
+    within: Option<&Path>,
 ) -> Result<crate::search::SearchResponse> {
-    let mut stream = connect_with_timeout(REQUEST_TIMEOUT)?;
+    SearchSession::connect()?.search(query, project, limit, within)
+}
 
-    let request = Request {
-        id: uuid::Uuid::new_v4().to_string(),
-        method: Method::Search {
-            query: query.to_string(),
-            project: project.to_string_lossy().to_string(),
-            limit,
-        },
-    };
+/// A daemon connection reused across many search requests
+pub struct SearchSession {
+    #[cfg(unix)]
+    stream: UnixStream,
+    #[cfg(windows)]
+    stream: TcpStream,
+}
 
-    let response = send_request(&mut stream, &request)?;
+impl SearchSession {
+    /// Open a connection to the daemon
+    pub fn connect() -> Result<Self> {
+        Ok(Self {
+            stream: connect_with_timeout(REQUEST_TIMEOUT)?,
+        })
+    }
 
-    match response.result {
-        ResponseResult::Search(search_response) => Ok(search_response),
-        ResponseResult::Error { message } => Err(Error::DaemonError { message }),
-        _ => Err(Error::DaemonError {
-            message: "Unexpected response type".to_string(),
-        }),
+    /// Run one search over the open connection
+    pub fn search(
+        &mut self,
+        query: &str,
+        project: &Path,
+        limit: usize,
+        within: Option<&Path>,
+    ) -> Result<crate::search::SearchResponse> {
+        let request = Request {
+            id: uuid::Uuid::new_v4().to_string(),
+            method: Method::Search {
+                query: query.to_string(),
+                project: project.to_string_lossy().to_string(),
+                limit,
+                within: within.map(|p| p.to_string_lossy().to_string()),
+            },
+        };
+
+        let response = send_request(&mut self.stream, &request)?;
+
+        match response.result {
+            ResponseResult::Search(search_response) => Ok(search_response),
+            ResponseResult::Error { message } => Err(Error::DaemonError { message }),
+            _ => Err(Error::DaemonError {
+                message: "Unexpected response type".to_string(),
+            }),
+        }
     }
 }
 
@@ -212,3 +263,178 @@ pub async fn status() -> Result<(bool, u32, Vec<crate::daemon::protocol::Project
         }),
     }
 }
+
+/// Check that the daemon answers requests
+pub fn ping() -> Result<()> {
+    let mut stream = connect_with_timeout(PING_TIMEOUT)?;
+
+    let request = Request {
+        id: uuid::Uuid::new_v4().to_string(),
+        method: Method::Ping,
+    };
+
+    let response = send_request(&mut stream, &request)?;
+
+    match response.result {
+        ResponseResult::Pong => Ok(()),
+        ResponseResult::Error { message } => Err(Error::DaemonError { message }),
+        _ => Err(Error::DaemonError {
+            message: "Unexpected response type".to_string(),
+        }),
+    }
+}
+
+/// Stream daemon events until the connection closes
+///
+/// `on_event` is called for every event; returning an error stops the stream.
+pub fn subscribe(mut on_event: impl FnMut(DaemonEvent) -> Result<()>) -> Result<()> {
+    let stream = connect_with_timeout(REQUEST_TIMEOUT)?;
+    // Events can be hours apart; only the subscribe handshake is time-bound
+    let mut writer = stream.try_clone()?;
+
+    let request = Request {
+        id: uuid::Uuid::new_v4().to_string(),
+        method: Method::Subscribe,
+    };
+    writer.write_all((serde_json::to_string(&request)? + "\n").as_bytes())?;
+    writer.flush()?;
+
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    let mut subscribed = false;
+    while reader.read_line(&mut line)? > 0 {
+        let response: Response = serde_json::from_str(&line).map_err(|e| Error::DaemonError {
+            message: format!("Invalid response from daemon: {}", e),
+        })?;
+        line.clear();
+
+        match response.result {
+            ResponseResult::Subscribed if !subscribed => {
+                subscribed = true;
+                reader.get_ref().set_read_timeout(None)?;
+            }
+            ResponseResult::Event(event) => on_event(event)?,
+            ResponseResult::Error { message } => return Err(Error::DaemonError { message }),
+            _ => {
+                return Err(Error::DaemonError {
+                    message: "Unexpected response type".to_string(),
+                })
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check that the daemon is running and answering requests
+fn is_ready() -> bool {
+    matches!(is_running(), Ok(true)) && ping().is_ok()
+}
+
+/// Make sure a daemon is available, starting one if autostart is enabled
+///
+/// Returns true when the daemon is ready to serve requests. Callers fall
+/// back to their non-daemon path when this returns false.
+pub fn ensure_running(config: &DaemonConfig) -> bool {
+    ensure_running_with(config, is_ready, process::start_daemon)
+}
+
+fn ensure_running_with(
+    config: &DaemonConfig,
+    mut is_ready: impl FnMut() -> bool,
+    start: impl FnOnce() -> Result<u32>,
+) -> bool {
+    if is_ready() {
+        return true;
+    }
+    if !config.autostart {
+        return false;
+    }
+
+    match start() {
+        Ok(pid) => debug!("Auto-started daemon (PID: {})", pid),
+        Err(e) => {
+            debug!("Daemon auto-start failed: {}", e);
+            return false;
+        }
+    }
+
+    // Bounded readiness poll
+    let deadline = Instant::now() + Duration::from_millis(config.ready_timeout_ms);
+    loop {
+        if is_ready() {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            debug!(
+                "Daemon not ready after {}ms, continuing without it",
+                config.ready_timeout_ms
+            );
+            return false;
+        }
+        std::thread::sleep(READY_POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn config(autostart: bool) -> DaemonConfig {
+        DaemonConfig {
+            autostart,
+            ready_timeout_ms: 200,
+            ..DaemonConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_autostart_starts_missing_daemon() {
+        let started = Cell::new(false);
+        let ready = ensure_running_with(
+            &config(true),
+            || started.get(),
+            || {
+                started.set(true);
+                Ok(42)
+            },
+        );
+        assert!(started.get());
+        assert!(ready);
+    }
+
+    #[test]
+    fn test_autostart_disabled_does_not_start() {
+        let started = Cell::new(false);
+        let ready = ensure_running_with(
+            &config(false),
+            || false,
+            || {
+                started.set(true);
+                Ok(42)
+            },
+        );
+        assert!(!started.get());
+        assert!(!ready);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_search_without_daemon_reports_unavailable() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = connect_socket(&dir.path().join("daemon.sock"), REQUEST_TIMEOUT).unwrap_err();
+        assert!(matches!(err, Error::DaemonNotRunning));
+        assert_eq!(
+            err.exit_code(),
+            crate::core::error::exit_code::DAEMON_UNAVAILABLE
+        );
+    }
+
+    #[test]
+    fn test_autostart_readiness_is_bounded() {
+        let start = Instant::now();
+        let ready = ensure_running_with(&config(true), || false, || Ok(42));
+        assert!(!ready);
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+}
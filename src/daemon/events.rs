@@ -3,6 +3,7 @@
 //! Provides a broadcast channel for daemon events that can be subscribed to
 //! by clients (like the web server) for real-time updates.
 
+use chrono::{DateTime, SecondsFormat, Utc};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use tokio::sync::broadcast;
@@ -45,6 +46,40 @@ pub enum DaemonEvent {
     StatusUpdate { projects: usize, watching: usize },
 }
 
+impl DaemonEvent {
+    /// Snake-case event name, used as `type` in NDJSON output
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            DaemonEvent::FileChanged { .. } => "file_changed",
+            DaemonEvent::ReindexStart { .. } => "reindex_start",
+            DaemonEvent::ReindexProgress { .. } => "reindex_progress",
+            DaemonEvent::ReindexComplete { .. } => "reindex_complete",
+            DaemonEvent::StatusUpdate { .. } => "status_update",
+        }
+    }
+
+    /// Event fields without the enum tag
+    pub fn payload(&self) -> serde_json::Value {
+        match serde_json::to_value(self) {
+            Ok(mut value) => value
+                .get_mut("data")
+                .map(serde_json::Value::take)
+                .unwrap_or_default(),
+            Err(_) => serde_json::Value::Null,
+        }
+    }
+
+    /// Single-line JSON record: `{"timestamp", "type", "payload"}`
+    pub fn to_ndjson(&self, timestamp: DateTime<Utc>) -> String {
+        serde_json::json!({
+            "timestamp": timestamp.to_rfc3339_opts(SecondsFormat::Millis, true),
+            "type": self.event_type(),
+            "payload": self.payload(),
+        })
+        .to_string()
+    }
+}
+
 /// File change action type
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -168,6 +203,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_reindex_event_is_one_ndjson_line() {
+        let event = DaemonEvent::ReindexComplete {
+            project: "/tmp/app".to_string(),
+            files: 3,
+            symbols: 42,
+            dead: 1,
+            duration_ms: 12.5,
+        };
+        let timestamp = DateTime::parse_from_rfc3339("2026-01-02T03:04:05Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let line = event.to_ndjson(timestamp);
+        assert!(!line.contains('\n'));
+
+        let record: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(record["type"], "reindex_complete");
+        assert_eq!(record["timestamp"], "2026-01-02T03:04:05.000Z");
+        assert_eq!(record["payload"]["symbols"], 42);
+        assert_eq!(record["payload"]["project"], "/tmp/app");
+    }
+
     #[test]
     fn test_no_subscribers() {
         let broadcaster = EventBroadcaster::new(16);
@@ -11,5 +11,6 @@ pub mod client;
 pub mod events;
 pub mod process;
 pub mod protocol;
+pub mod recent;
 pub mod server;
 pub mod watcher;
@@ -15,6 +15,9 @@ pub enum Method {
         query: String,
         project: String,
         limit: usize,
+        /// Only return chunks under this path
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        within: Option<String>,
     },
     Index {
         project: String,
@@ -29,6 +32,8 @@ pub enum Method {
         project: String,
     },
     Stop,
+    /// Liveness check
+    Ping,
     /// Subscribe to daemon events (returns a stream)
     Subscribe,
 }
@@ -64,6 +69,8 @@ pub enum ResponseResult {
     Stop {
         success: bool,
     },
+    /// Answer to Ping
+    Pong,
     /// Subscribed to events successfully
     Subscribed,
     /// An event from the daemon (streamed after Subscribe)
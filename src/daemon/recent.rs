@@ -0,0 +1,69 @@
+//! Projects the daemon served most recently
+//!
+//! Persisted across restarts so the next boot can preload their indexes.
+
+use crate::core::error::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Most recently searched projects, newest first
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RecentProjects {
+    projects: Vec<PathBuf>,
+}
+
+impl RecentProjects {
+    /// Load the state file, starting empty if it is missing or unreadable
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the state file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Move `project` to the front, keeping at most `limit` entries
+    ///
+    /// Returns whether the list changed.
+    pub fn touch(&mut self, project: &Path, limit: usize) -> bool {
+        if self.projects.first().map(PathBuf::as_path) == Some(project) {
+            return false;
+        }
+        self.projects.retain(|p| p != project);
+        self.projects.insert(0, project.to_path_buf());
+        self.projects.truncate(limit);
+        true
+    }
+
+    /// Remembered projects, newest first
+    pub fn projects(&self) -> &[PathBuf] {
+        &self.projects
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_touch_keeps_newest_first_within_limit() {
+        let mut recent = RecentProjects::default();
+        assert!(recent.touch(Path::new("/a"), 2));
+        assert!(recent.touch(Path::new("/b"), 2));
+        assert!(!recent.touch(Path::new("/b"), 2));
+        assert!(recent.touch(Path::new("/a"), 2));
+        assert!(recent.touch(Path::new("/c"), 2));
+        assert_eq!(
+            recent.projects(),
+            [PathBuf::from("/c"), PathBuf::from("/a")]
+        );
+    }
+}
@@ -1,16 +1,22 @@
-use crate::core::config::Config;
+use crate::core::config::{Config, DaemonConfig};
 use crate::core::error::Result;
 use crate::core::project::{Project, ProjectEntry, Registry};
 use crate::daemon::cache::QueryCache;
 use crate::daemon::events::{DaemonEvent, EventBroadcaster, FileAction};
 use crate::daemon::protocol::{Method, ProjectInfo, Request, Response, ResponseResult};
+use crate::daemon::recent::RecentProjects;
 use crate::daemon::watcher::WatcherManager;
-use crate::index::{IndexSearcher, IndexWriter, TantivyIndex};
-use crate::parse::{chunk_file, walk_project};
+use crate::index::hashes::content_hash;
+use crate::index::{FileHashes, IndexSearcher, IndexWriter, TantivyIndex};
+use crate::parse::{walk_git_tracked, walk_project, Chunk, Chunker, FileInfo};
 use crate::search::SearchResponse;
+use crate::trace::{
+    build_project_index, check_index_size, detect_language, find_dead_symbols, index_file_size,
+    is_treesitter_supported, save_project_index,
+};
 use parking_lot::{Mutex, RwLock};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{Duration, Instant, SystemTime};
 use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
@@ -20,6 +26,9 @@ use tracing::{debug, info, warn};
 #[cfg(unix)]
 use tokio::net::UnixListener;
 
+/// Emit an index progress event every this many files
+const PROGRESS_INTERVAL: usize = 25;
+
 #[cfg(windows)]
 use tokio::net::TcpListener;
 
@@ -30,6 +39,8 @@ pub struct DaemonState {
     pub watcher: Mutex<WatcherManager>,
     pub shutdown: broadcast::Sender<()>,
     pub events: EventBroadcaster,
+    pub config: DaemonConfig,
+    pub recent: Mutex<RecentProjects>,
 }
 
 impl Default for DaemonState {
@@ -48,6 +59,63 @@ impl DaemonState {
             watcher: Mutex::new(WatcherManager::new()),
             shutdown,
             events: EventBroadcaster::default(),
+            config: Config::load().map(|c| c.daemon).unwrap_or_default(),
+            recent: Mutex::new(
+                Config::recent_projects_path()
+                    .map(|path| RecentProjects::load(&path))
+                    .unwrap_or_default(),
+            ),
+        }
+    }
+
+    /// Projects to load at startup: configured preloads, then recent ones
+    pub fn preload_targets(&self) -> Vec<PathBuf> {
+        let mut targets: Vec<PathBuf> = Vec::new();
+        let recent = self.recent.lock();
+        for path in self.config.preload.iter().chain(recent.projects()) {
+            let path = path.canonicalize().unwrap_or_else(|_| path.clone());
+            if !targets.contains(&path) {
+                targets.push(path);
+            }
+        }
+        targets
+    }
+
+    /// Open the indexes of `projects` into the searcher map
+    ///
+    /// Projects without an index are skipped. Returns how many were loaded.
+    pub fn preload<F>(&self, projects: &[PathBuf], index_dir: F) -> usize
+    where
+        F: Fn(&Path) -> Result<PathBuf>,
+    {
+        let mut loaded = 0;
+        for project in projects {
+            let opened = index_dir(project).and_then(|dir| IndexSearcher::open_dir(&dir));
+            match opened {
+                Ok(searcher) => {
+                    let key = project.to_string_lossy().to_string();
+                    self.searchers.write().insert(key, searcher);
+                    loaded += 1;
+                }
+                Err(e) => {
+                    debug!(project = %project.display(), error = %e, "Skipping preload");
+                }
+            }
+        }
+        loaded
+    }
+
+    /// Record a served project so the next boot preloads it
+    fn remember_project(&self, project: &Path) {
+        if self.config.remember_recent == 0 {
+            return;
+        }
+        let mut recent = self.recent.lock();
+        if !recent.touch(project, self.config.remember_recent) {
+            return;
+        }
+        if let Err(e) = Config::recent_projects_path().and_then(|path| recent.save(&path)) {
+            warn!(error = %e, "Failed to save recent projects");
         }
     }
 
@@ -76,6 +144,20 @@ impl DaemonState {
     }
 }
 
+/// Warm the searcher map so the first search after a restart is fast
+fn preload_projects(state: &DaemonState) {
+    let targets = state.preload_targets();
+    if targets.is_empty() {
+        return;
+    }
+    let loaded = state.preload(&targets, Config::index_dir);
+    info!(
+        loaded,
+        requested = targets.len(),
+        "Preloaded project indexes"
+    );
+}
+
 /// Background watcher loop - runs independently, doesn't block requests
 async fn run_watcher_loop(state: Arc<DaemonState>) {
     info!("Starting file watcher for incremental indexing");
@@ -160,6 +242,7 @@ pub async fn run_server() -> Result<()> {
     let state = Arc::new(DaemonState::new());
 
     info!("Daemon starting...");
+    preload_projects(&state);
 
     // Start watcher background task - processes file changes without blocking requests
     let watcher_state = Arc::clone(&state);
@@ -222,6 +305,7 @@ pub async fn run_server() -> Result<()> {
     let state = Arc::new(DaemonState::new());
 
     info!("Daemon starting...");
+    preload_projects(&state);
 
     // Start watcher background task - processes file changes without blocking requests
     let watcher_state = Arc::clone(&state);
@@ -349,7 +433,8 @@ async fn handle_request(request: Request, state: &DaemonState) -> Response {
             query,
             project,
             limit,
-        } => handle_search(&query, &project, limit, state).await,
+            within,
+        } => handle_search(&query, &project, limit, within.as_deref(), state).await,
 
         Method::Index { project, force } => handle_index(&project, force, state).await,
 
@@ -363,6 +448,8 @@ async fn handle_request(request: Request, state: &DaemonState) -> Response {
 
         Method::Stop => ResponseResult::Stop { success: true },
 
+        Method::Ping => ResponseResult::Pong,
+
         // Subscribe is handled specially in handle_connection
         Method::Subscribe => ResponseResult::Subscribed,
     };
@@ -377,13 +464,20 @@ async fn handle_search(
     query: &str,
     project_path: &str,
     limit: usize,
+    within: Option<&str>,
     state: &DaemonState,
 ) -> ResponseResult {
     let start = Instant::now();
     let path = PathBuf::from(project_path);
 
     // Check cache
-    let cache_key = format!("{}:{}:{}", project_path, query, limit);
+    let cache_key = format!(
+        "{}:{}:{}:{}",
+        project_path,
+        query,
+        limit,
+        within.unwrap_or_default()
+    );
     {
         let mut cache = state.cache.write();
         if let Some(cached) = cache.get(&cache_key) {
@@ -433,8 +527,10 @@ async fn handle_search(
         }
     };
 
+    state.remember_project(&path);
+
     // Search
-    match searcher.search(query, limit) {
+    match searcher.search_in(query, limit, within.map(Path::new)) {
         Ok(results) => {
             let elapsed = start.elapsed();
             let response = SearchResponse {
@@ -479,26 +575,45 @@ async fn do_index(
     state: &DaemonState,
 ) -> Result<(usize, usize, f64)> {
     let start = Instant::now();
+    let config = Config::load().map(|c| c.index).unwrap_or_default();
 
     // Walk and chunk files
-    let files = walk_project(path)?;
+    let files = if config.git_tracked_only {
+        walk_git_tracked(path)?
+    } else {
+        walk_project(path)?
+    };
     let file_count = files.len();
 
     let index = TantivyIndex::open_or_create(path)?;
     let mut writer = IndexWriter::new(&index)?;
-    let mut chunk_count = 0;
 
-    for file in &files {
-        let chunks = chunk_file(&file.path, &file.content);
-        for chunk in chunks {
-            writer.add_chunk(&chunk)?;
-            chunk_count += 1;
-        }
-    }
+    state.events.reindex_start(path, file_count, "index");
+    let chunker = Chunker::from_config(&config);
+    let chunk_count = chunk_files_with_progress(&files, path, &chunker, &state.events, |chunk| {
+        writer.add_chunk(chunk)
+    })?;
 
     writer.commit()?;
+    let (symbols, dead) = rebuild_trace_index(path, &files, config.max_index_bytes);
+
+    // Record content hashes so the watcher can skip unchanged files
+    let mut hashes = FileHashes::empty(path)?;
+    for file in &files {
+        hashes.insert(&file.path, content_hash(&file.content));
+    }
+    if let Err(e) = hashes.save() {
+        warn!(project = %path.display(), error = %e, "Failed to save file hashes");
+    }
 
     let elapsed = start.elapsed();
+    state.events.reindex_complete(
+        path,
+        file_count,
+        symbols,
+        dead,
+        elapsed.as_secs_f64() * 1000.0,
+    );
 
     // Update registry
     {
@@ -532,6 +647,65 @@ async fn do_index(
     Ok((file_count, chunk_count, elapsed.as_secs_f64() * 1000.0))
 }
 
+/// Build and save the trace index from the walked sources
+///
+/// Returns the symbol and dead-symbol counts the completed reindex reports;
+/// both are 0 when no file has tree-sitter support. A save that fails (or
+/// would go over the size cap) is logged and leaves the old index in place.
+fn rebuild_trace_index(
+    project: &Path,
+    files: &[FileInfo],
+    max_index_bytes: Option<u64>,
+) -> (usize, usize) {
+    let sources: Vec<(PathBuf, String)> = files
+        .iter()
+        .filter(|file| is_treesitter_supported(detect_language(&file.path)))
+        .map(|file| (file.path.clone(), file.content.clone()))
+        .collect();
+    if sources.is_empty() {
+        return (0, 0);
+    }
+
+    let (index, stats) = build_project_index(project, &sources);
+    let dead = find_dead_symbols(&index).len();
+
+    let size = TantivyIndex::disk_size(project).unwrap_or(0) + index_file_size(&index);
+    let saved =
+        check_index_size(size, max_index_bytes).and_then(|()| save_project_index(project, &index));
+    if let Err(e) = saved {
+        warn!(project = %project.display(), error = %e, "Failed to save trace index");
+    }
+
+    (stats.symbols, dead)
+}
+
+/// Chunk files into `add_chunk`, publishing progress events as files complete
+fn chunk_files_with_progress(
+    files: &[FileInfo],
+    project: &Path,
+    chunker: &Chunker,
+    events: &EventBroadcaster,
+    mut add_chunk: impl FnMut(&Chunk) -> Result<()>,
+) -> Result<usize> {
+    let project = project.to_path_buf();
+    let total = files.len();
+    let mut chunk_count = 0;
+
+    for (i, file) in files.iter().enumerate() {
+        for chunk in chunker.chunk_file(&file.path, &file.content) {
+            add_chunk(&chunk)?;
+            chunk_count += 1;
+        }
+
+        let processed = i + 1;
+        if processed % PROGRESS_INTERVAL == 0 || processed == total {
+            events.reindex_progress(&project, processed, total);
+        }
+    }
+
+    Ok(chunk_count)
+}
+
 async fn handle_index_watch(project_path: &str, state: &DaemonState) -> ResponseResult {
     let path = PathBuf::from(project_path);
 
@@ -628,3 +802,103 @@ async fn handle_forget(project_path: &str, state: &DaemonState) -> ResponseResul
         success: true,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::IndexSchema;
+
+    #[test]
+    fn test_index_progress_events_published() {
+        let events = EventBroadcaster::new(64);
+        // The web forwarder receives events through a Subscribe on this bus
+        let mut rx = events.subscribe();
+
+        let files: Vec<FileInfo> = (0..60)
+            .map(|i| FileInfo {
+                path: PathBuf::from(format!("src/file{}.rs", i)),
+                content: format!("fn f{}() {{}}\n", i),
+            })
+            .collect();
+
+        let mut added = 0;
+        let chunker = Chunker::default();
+        let chunks =
+            chunk_files_with_progress(&files, Path::new("/tmp/proj"), &chunker, &events, |_| {
+                added += 1;
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(chunks, added);
+
+        let mut progress = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            match event {
+                DaemonEvent::ReindexProgress {
+                    project,
+                    processed,
+                    total,
+                } => {
+                    assert_eq!(project, "/tmp/proj");
+                    assert_eq!(total, 60);
+                    progress.push(processed);
+                }
+                other => panic!("Unexpected event: {:?}", other),
+            }
+        }
+        assert_eq!(progress, vec![25, 50, 60]);
+    }
+
+    #[test]
+    fn test_rebuild_trace_index_counts_symbols_and_dead() {
+        let project = tempfile::tempdir().unwrap();
+        let files = vec![
+            FileInfo {
+                path: project.path().join("main.rs"),
+                content: "fn main() {\n    used();\n}\n\nfn used() {}\n\nfn unused() {}\n"
+                    .to_string(),
+            },
+            FileInfo {
+                path: project.path().join("notes.md"),
+                content: "# not traced\n".to_string(),
+            },
+        ];
+
+        let (symbols, dead) = rebuild_trace_index(project.path(), &files, None);
+        assert_eq!(symbols, 3);
+        assert_eq!(dead, 1);
+        assert!(crate::trace::trace_index_path(project.path()).exists());
+
+        // Nothing to trace: nothing counted, nothing written
+        let empty = tempfile::tempdir().unwrap();
+        assert_eq!(rebuild_trace_index(empty.path(), &files[1..], None), (0, 0));
+        assert!(!crate::trace::trace_index_path(empty.path()).exists());
+    }
+
+    #[test]
+    fn test_preload_loads_configured_project() {
+        let project = tempfile::tempdir().unwrap();
+        let index_dir = tempfile::tempdir().unwrap();
+        tantivy::Index::create_in_dir(index_dir.path(), IndexSchema::new().schema).unwrap();
+        let project_path = project.path().canonicalize().unwrap();
+        let missing = project_path.join("not-indexed");
+
+        let mut state = DaemonState::new();
+        state.config.preload = vec![project_path.clone(), missing.clone()];
+        *state.recent.lock() = RecentProjects::default();
+        state.searchers.write().clear();
+
+        let loaded = state.preload(&state.preload_targets(), |p| {
+            if p == project_path {
+                Ok(index_dir.path().to_path_buf())
+            } else {
+                Ok(PathBuf::from("/nonexistent/index"))
+            }
+        });
+
+        assert_eq!(loaded, 1);
+        let searchers = state.searchers.read();
+        assert!(searchers.contains_key(&*project_path.to_string_lossy()));
+        assert!(!searchers.contains_key(&*missing.to_string_lossy()));
+    }
+}
@@ -7,12 +7,15 @@
 //!
 //! Design: Non-blocking, runs in background task, doesn't affect search performance.
 
+use crate::core::config::Config;
 use crate::core::error::{Error, Result};
-use crate::index::{IndexWriter, TantivyIndex};
-use crate::parse::chunk_file;
+use crate::index::{FileHashes, IndexWriter, TantivyIndex};
+use crate::parse::Chunker;
 use crate::trace::builder::{remove_file_from_index, update_file_incremental};
-use crate::trace::storage::{load_index, save_index, trace_index_path};
-use crate::trace::{find_dead_symbols, snapshots::create_snapshot, SemanticIndex};
+use crate::trace::storage::{index_file_size, load_index, save_index, trace_index_path};
+use crate::trace::{
+    check_index_size, find_dead_symbols, snapshots::create_snapshot, SemanticIndex,
+};
 use notify::{
     event::{CreateKind, ModifyKind, RemoveKind},
     Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher,
@@ -260,6 +263,7 @@ fn is_indexable_file(path: &Path) -> bool {
             | "sql"
             | "vue"
             | "svelte"
+            | "ipynb"
     )
 }
 
@@ -287,6 +291,13 @@ fn process_project_events_sync(
         }
     }
 
+    // Skip files whose content is unchanged (e.g. mtime bumped by a checkout)
+    let mut hashes = FileHashes::load(project_path)?;
+    let to_reindex = changed_files(&mut hashes, to_reindex);
+    for path in &to_delete {
+        hashes.remove(path);
+    }
+
     if to_reindex.is_empty() && to_delete.is_empty() {
         return Ok(UpdateResult::default());
     }
@@ -304,6 +315,11 @@ fn process_project_events_sync(
     // Update trace semantic index
     update_trace_index(project_path, &to_reindex, &to_delete);
 
+    // Only record hashes once the indexes hold the new content
+    if let Err(e) = hashes.save() {
+        warn!(project = %project_path.display(), error = %e, "Failed to save file hashes");
+    }
+
     let elapsed_ms = start.elapsed().as_secs_f64() * 1000.0;
 
     let result = UpdateResult {
@@ -325,6 +341,26 @@ fn process_project_events_sync(
     Ok(result)
 }
 
+/// Keep only the files whose content differs from the recorded hash
+///
+/// Records the new hashes; unreadable files are kept so the update can
+/// report them as it does today.
+fn changed_files(hashes: &mut FileHashes, to_reindex: HashSet<PathBuf>) -> HashSet<PathBuf> {
+    to_reindex
+        .into_iter()
+        .filter(|path| match std::fs::read_to_string(path) {
+            Ok(content) => {
+                let changed = hashes.update(path, &content);
+                if !changed {
+                    debug!(path = %path.display(), "Content unchanged, skipping reindex");
+                }
+                changed
+            }
+            Err(_) => true,
+        })
+        .collect()
+}
+
 /// Update the Tantivy text search index
 fn update_tantivy_index(
     project_path: &Path,
@@ -342,9 +378,10 @@ fn update_tantivy_index(
     }
 
     // Re-index changed files
+    let chunker = Chunker::from_config(&Config::load().map(|c| c.index).unwrap_or_default());
     for path in to_reindex {
         if let Ok(content) = std::fs::read_to_string(path) {
-            let chunks = chunk_file(path, &content);
+            let chunks = chunker.chunk_file(path, &content);
             for chunk in &chunks {
                 writer.add_chunk(chunk)?;
             }
@@ -414,9 +451,15 @@ fn update_trace_index(
         }
     }
 
-    // Save the updated index
+    // Save the updated index, unless it would take the indexes past the size cap
     if files_updated > 0 || files_deleted > 0 {
-        if let Err(e) = save_index(&index, &trace_path) {
+        let max_index_bytes = Config::load()
+            .map(|c| c.index.max_index_bytes)
+            .unwrap_or_default();
+        let size = TantivyIndex::disk_size(project_path).unwrap_or(0) + index_file_size(&index);
+        let saved =
+            check_index_size(size, max_index_bytes).and_then(|()| save_index(&index, &trace_path));
+        if let Err(e) = saved {
             warn!(
                 project = %project_path.display(),
                 error = %e,
@@ -517,6 +560,26 @@ fn count_cycles_dfs(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_unchanged_content_is_not_reindexed() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("lib.rs");
+        std::fs::write(&file, "fn a() {}\n").unwrap();
+        let mut hashes = FileHashes::load_from(&dir.path().join("hashes.json"));
+        let events = || HashSet::from([file.clone()]);
+
+        // First sighting is indexed
+        assert_eq!(changed_files(&mut hashes, events()).len(), 1);
+
+        // Same bytes again (e.g. touched by a checkout): nothing to rewrite
+        std::fs::write(&file, "fn a() {}\n").unwrap();
+        assert!(changed_files(&mut hashes, events()).is_empty());
+
+        // Real edit is picked up
+        std::fs::write(&file, "fn b() {}\n").unwrap();
+        assert_eq!(changed_files(&mut hashes, events()), events());
+    }
+
     #[test]
     fn test_is_indexable_file() {
         assert!(is_indexable_file(Path::new("src/main.rs")));
@@ -0,0 +1,113 @@
+//! Per-file content hashes
+//!
+//! Stored next to the Tantivy index so incremental updates can skip files
+//! whose content is unchanged (e.g. an mtime bump from `git checkout`).
+
+use crate::core::config::Config;
+use crate::core::error::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// File name of the hash table inside a project's index directory
+const HASHES_FILE: &str = "hashes.json";
+
+/// Hash of a file's content
+pub fn content_hash(content: &str) -> u64 {
+    xxhash_rust::xxh3::xxh3_64(content.as_bytes())
+}
+
+/// Content hash of every indexed file, keyed by path
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct FileHashes {
+    #[serde(skip)]
+    path: PathBuf,
+    hashes: HashMap<PathBuf, u64>,
+}
+
+impl FileHashes {
+    /// Load the hashes recorded for a project
+    pub fn load(project_path: &Path) -> Result<Self> {
+        Ok(Self::load_from(
+            &Config::index_dir(project_path)?.join(HASHES_FILE),
+        ))
+    }
+
+    /// Load a hash file, starting empty if it is missing or unreadable
+    pub fn load_from(path: &Path) -> Self {
+        let mut hashes: Self = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        hashes.path = path.to_path_buf();
+        hashes
+    }
+
+    /// An empty table that will be saved for `project_path`
+    pub fn empty(project_path: &Path) -> Result<Self> {
+        Ok(Self {
+            path: Config::index_dir(project_path)?.join(HASHES_FILE),
+            hashes: HashMap::new(),
+        })
+    }
+
+    /// Write the hash file
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Record the hash of `content` for `file`
+    ///
+    /// Returns false when the same hash was already recorded, i.e. the file
+    /// does not need reindexing.
+    pub fn update(&mut self, file: &Path, content: &str) -> bool {
+        let hash = content_hash(content);
+        self.hashes.insert(file.to_path_buf(), hash) != Some(hash)
+    }
+
+    /// Record a precomputed hash for `file`
+    pub fn insert(&mut self, file: &Path, hash: u64) {
+        self.hashes.insert(file.to_path_buf(), hash);
+    }
+
+    /// Forget a deleted file
+    pub fn remove(&mut self, file: &Path) {
+        self.hashes.remove(file);
+    }
+
+    /// Number of files with a recorded hash
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    /// Whether no hashes are recorded
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hashes_round_trip_and_detect_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(HASHES_FILE);
+        let file = Path::new("/proj/src/lib.rs");
+
+        let mut hashes = FileHashes::load_from(&path);
+        assert!(hashes.is_empty());
+        assert!(hashes.update(file, "fn a() {}"));
+        hashes.save().unwrap();
+
+        let mut loaded = FileHashes::load_from(&path);
+        assert_eq!(loaded.len(), 1);
+        assert!(!loaded.update(file, "fn a() {}"));
+        assert!(loaded.update(file, "fn b() {}"));
+    }
+}
@@ -1,9 +1,11 @@
+pub mod hashes;
 pub mod reader;
 pub mod schema;
 pub mod tantivy_index;
 pub mod writer;
 
-pub use reader::IndexSearcher;
+pub use hashes::FileHashes;
+pub use reader::{path_prefix_query, IndexSearcher};
 pub use schema::IndexSchema;
 pub use tantivy_index::TantivyIndex;
 pub use writer::IndexWriter;
@@ -1,13 +1,33 @@
 use crate::core::config::Config;
 use crate::core::error::{Error, Result};
 use crate::index::schema::IndexSchema;
+use crate::search::identifiers::identifier_variants;
+use crate::search::query::VARIANT_BOOST;
 use crate::search::SearchResult;
 use std::path::Path;
 use tantivy::collector::TopDocs;
-use tantivy::query::{BooleanQuery, BoostQuery, Occur, Query, TermQuery};
-use tantivy::schema::{IndexRecordOption, Value};
+use tantivy::query::{BooleanQuery, BoostQuery, Occur, Query, RegexQuery, TermQuery};
+use tantivy::schema::{Field, IndexRecordOption, Value};
 use tantivy::{Index, IndexReader, ReloadPolicy, Term};
 
+/// Query matching documents whose `path` is `dir` itself or lies below it
+///
+/// Paths are indexed untokenized, so this matches whole path terms:
+/// `src/auth` covers `src/auth/login.rs` but not `src/authz.rs`.
+pub fn path_prefix_query(path_field: Field, dir: &Path) -> Result<Box<dyn Query>> {
+    let prefix = dir.to_string_lossy();
+    let prefix = prefix.trim_end_matches(std::path::MAIN_SEPARATOR);
+    let pattern = format!(
+        "{}({}.*)?",
+        regex::escape(prefix),
+        regex::escape(std::path::MAIN_SEPARATOR_STR)
+    );
+    let query = RegexQuery::from_pattern(&pattern, path_field).map_err(|e| Error::SearchError {
+        message: format!("Invalid path filter '{}': {}", dir.display(), e),
+    })?;
+    Ok(Box::new(query))
+}
+
 #[derive(Clone)]
 pub struct IndexSearcher {
     reader: IndexReader,
@@ -26,8 +46,13 @@ impl IndexSearcher {
             });
         }
 
+        Self::open_dir(&index_dir)
+    }
+
+    /// Open the index stored in `index_dir`
+    pub fn open_dir(index_dir: &Path) -> Result<Self> {
         let schema = IndexSchema::new();
-        let index = Index::open_in_dir(&index_dir).map_err(|e| Error::IndexError {
+        let index = Index::open_in_dir(index_dir).map_err(|e| Error::IndexError {
             message: e.to_string(),
         })?;
 
@@ -54,6 +79,16 @@ impl IndexSearcher {
 
     /// Search the index
     pub fn search(&self, query_text: &str, limit: usize) -> Result<Vec<SearchResult>> {
+        self.search_in(query_text, limit, None)
+    }
+
+    /// Search the index, keeping only chunks under `within` when given
+    pub fn search_in(
+        &self,
+        query_text: &str,
+        limit: usize,
+        within: Option<&Path>,
+    ) -> Result<Vec<SearchResult>> {
         let searcher = self.reader.searcher();
 
         // Tokenize query
@@ -74,23 +109,40 @@ impl IndexSearcher {
             return Ok(Vec::new());
         }
 
+        // Identifier sub-terms, weighted like `SearchQuery` does
+        let variants: Vec<String> = identifier_variants(query_text)
+            .into_iter()
+            .filter(|v| !tokens.contains(v))
+            .collect();
+
         // Build query: content + boosted symbol_name
         let mut subqueries: Vec<(Occur, Box<dyn Query>)> = Vec::new();
 
-        for token in &tokens {
+        for (token, boost) in tokens
+            .iter()
+            .map(|t| (t, 1.0))
+            .chain(variants.iter().map(|v| (v, VARIANT_BOOST)))
+        {
             // Content query
             let content_term = Term::from_field_text(self.schema.content, token);
             let content_query = TermQuery::new(content_term, IndexRecordOption::WithFreqs);
+            let content_query = BoostQuery::new(Box::new(content_query), boost);
             subqueries.push((Occur::Should, Box::new(content_query)));
 
             // Symbol name query (boosted 3x)
             let symbol_term = Term::from_field_text(self.schema.symbol_name, token);
             let symbol_query = TermQuery::new(symbol_term, IndexRecordOption::WithFreqs);
-            let boosted = BoostQuery::new(Box::new(symbol_query), 3.0);
+            let boosted = BoostQuery::new(Box::new(symbol_query), 3.0 * boost);
             subqueries.push((Occur::Should, Box::new(boosted)));
         }
 
-        let query = BooleanQuery::new(subqueries);
+        let mut query: Box<dyn Query> = Box::new(BooleanQuery::new(subqueries));
+        if let Some(dir) = within {
+            query = Box::new(BooleanQuery::new(vec![
+                (Occur::Must, query),
+                (Occur::Must, path_prefix_query(self.schema.path, dir)?),
+            ]));
+        }
 
         // Execute search
         let top_docs = searcher
@@ -148,6 +200,11 @@ impl IndexSearcher {
                 .unwrap_or("unknown")
                 .to_string();
 
+            let cell = doc
+                .get_first(self.schema.cell)
+                .and_then(|v| v.as_u64())
+                .map(|c| c as usize);
+
             results.push(SearchResult {
                 path,
                 content,
@@ -156,10 +213,70 @@ impl IndexSearcher {
                 start_line,
                 end_line,
                 language,
+                cell,
                 score,
+                explanation: None,
             });
         }
 
         Ok(results)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::{IndexWriter, TantivyIndex};
+    use crate::parse::Chunk;
+
+    #[test]
+    fn test_search_in_excludes_results_outside_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let schema = IndexSchema::new();
+        let index = Index::create_in_dir(dir.path(), schema.schema.clone()).unwrap();
+        let reader = index.reader().unwrap();
+        let index = TantivyIndex {
+            index,
+            schema,
+            reader,
+        };
+
+        let mut writer = IndexWriter::new(&index).unwrap();
+        for path in [
+            "/proj/src/auth/token.rs",
+            "/proj/src/auth/jwt/token.rs",
+            "/proj/src/authz/token.rs",
+            "/proj/src/db/token.rs",
+        ] {
+            writer
+                .add_chunk(&Chunk {
+                    path: path.to_string(),
+                    content: "fn refresh_token(token: Token) {}".to_string(),
+                    symbol_name: Some("refresh_token".to_string()),
+                    symbol_type: Some("function".to_string()),
+                    start_line: 1,
+                    end_line: 1,
+                    language: "rust".to_string(),
+                    file_hash: String::new(),
+                    cell: None,
+                })
+                .unwrap();
+        }
+        writer.commit().unwrap();
+
+        let searcher = IndexSearcher::open_dir(dir.path()).unwrap();
+        assert_eq!(searcher.search("token", 10).unwrap().len(), 4);
+
+        let mut paths: Vec<String> = searcher
+            .search_in("token", 10, Some(Path::new("/proj/src/auth/")))
+            .unwrap()
+            .into_iter()
+            .map(|r| r.path)
+            .collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec!["/proj/src/auth/jwt/token.rs", "/proj/src/auth/token.rs"]
+        );
+    }
+}
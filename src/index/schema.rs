@@ -14,6 +14,7 @@ pub struct IndexSchema {
     pub end_line: Field,
     pub language: Field,
     pub file_hash: Field,
+    pub cell: Field,
 }
 
 impl IndexSchema {
@@ -23,7 +24,7 @@ impl IndexSchema {
         // Unique ID: "{path}:{start}:{end}"
         let id = builder.add_text_field("id", STRING | STORED);
 
-        // File path
+        // File path, indexed untokenized so `search --in` can filter by prefix
         let path = builder.add_text_field("path", STRING | STORED);
 
         // Main content - full text with positions
@@ -59,6 +60,9 @@ impl IndexSchema {
         // File hash for incremental indexing
         let file_hash = builder.add_text_field("file_hash", STRING | STORED);
 
+        // Notebook cell number (absent for ordinary files)
+        let cell = builder.add_u64_field("cell", STORED);
+
         Self {
             schema: builder.build(),
             id,
@@ -70,6 +74,7 @@ impl IndexSchema {
             end_line,
             language,
             file_hash,
+            cell,
         }
     }
 }
@@ -84,6 +84,19 @@ impl TantivyIndex {
         Ok(index_dir.join("meta.json").exists())
     }
 
+    /// Bytes the index for a project takes on disk (0 before the first index)
+    pub fn disk_size(project_path: &Path) -> Result<u64> {
+        let index_dir = Config::index_dir(project_path)?;
+        let Ok(entries) = std::fs::read_dir(&index_dir) else {
+            return Ok(0);
+        };
+        Ok(entries
+            .filter_map(|entry| entry.ok()?.metadata().ok())
+            .filter(|meta| meta.is_file())
+            .map(|meta| meta.len())
+            .sum())
+    }
+
     /// Delete an index for a project
     pub fn delete(project_path: &Path) -> Result<()> {
         let index_dir = Config::index_dir(project_path)?;
@@ -31,7 +31,7 @@ impl IndexWriter {
 
     /// Add a chunk to the index
     pub fn add_chunk(&mut self, chunk: &Chunk) -> Result<()> {
-        let doc = doc!(
+        let mut doc = doc!(
             self.schema.id => chunk.id(),
             self.schema.path => chunk.path.clone(),
             self.schema.content => chunk.content.clone(),
@@ -42,6 +42,9 @@ impl IndexWriter {
             self.schema.language => chunk.language.clone(),
             self.schema.file_hash => chunk.file_hash.clone()
         );
+        if let Some(cell) = chunk.cell {
+            doc.add_u64(self.schema.cell, cell as u64);
+        }
 
         self.writer.add_document(doc)?;
         Ok(())
@@ -1,25 +1,44 @@
 //! Greppy CLI entry point
 
 use clap::Parser;
-use greppy::cli::{Cli, Commands};
-use greppy::core::error::Result;
-use tracing_subscriber::{fmt, prelude::*, EnvFilter};
+use greppy::cli::{AuthCommand, Cli, Commands};
+use greppy::core::config::Config;
+use greppy::core::error::{exit_code, Error, Result};
+use greppy::core::logging;
+use std::process::ExitCode;
+use tracing_subscriber::EnvFilter;
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> ExitCode {
     // Check for hidden daemon mode (spawned by `greppy start`)
     let args: Vec<String> = std::env::args().collect();
-    if args.len() == 2 && args[1] == "__daemon" {
-        return run_daemon_server().await;
+    let result = if args.len() == 2 && args[1] == "__daemon" {
+        run_daemon_server().await
+    } else {
+        run_cli().await
+    };
+
+    // Every failure maps to a documented exit code (see `greppy --help`)
+    match result {
+        Ok(()) => ExitCode::from(exit_code::SUCCESS),
+        Err(e) => {
+            // A check-mode match was already printed; only the exit code reports it
+            if !matches!(e, Error::CheckFound { .. }) {
+                eprintln!("Error: {}", e);
+            }
+            ExitCode::from(e.exit_code())
+        }
     }
+}
 
+/// Parse arguments and dispatch to the selected command
+async fn run_cli() -> Result<()> {
     // Initialize logging
-    tracing_subscriber::registry()
-        .with(fmt::layer())
-        .with(EnvFilter::from_env("GREPPY_LOG"))
-        .init();
+    let log_config = Config::load().map(|c| c.log).unwrap_or_default();
+    logging::init(EnvFilter::from_env("GREPPY_LOG"), &log_config);
 
     let cli = Cli::parse();
+    greppy::output::color::set_choice(cli.color);
 
     match cli.command {
         Commands::Search(args) => greppy::cli::search::run(args).await,
@@ -27,21 +46,30 @@ async fn main() -> Result<()> {
         Commands::Start => greppy::cli::daemon::start(),
         Commands::Stop => greppy::cli::daemon::stop(),
         Commands::Status => greppy::cli::daemon::status(),
+        Commands::Events(args) => greppy::cli::daemon::events(args),
         Commands::Login => greppy::cli::login::run().await,
         Commands::Logout => greppy::cli::login::logout(),
-        Commands::Model => greppy::cli::model::run().await,
+        Commands::Model(args) => greppy::cli::model::run(args).await,
+        Commands::Auth(args) => match args.command {
+            AuthCommand::Status { json } => greppy::cli::login::status(json),
+        },
+        Commands::Config(args) => greppy::cli::config::run(args),
         Commands::Trace(args) => greppy::cli::trace::run(args).await,
         Commands::Web(args) => greppy::cli::web::run(args).await,
+        Commands::Export(args) => greppy::cli::export::export(args),
+        Commands::Import(args) => greppy::cli::export::import(args),
+        Commands::Bench(args) => greppy::cli::bench::run(args),
     }
 }
 
 /// Run the daemon server (called when spawned with __daemon arg)
 async fn run_daemon_server() -> Result<()> {
     // Initialize logging for daemon
-    tracing_subscriber::registry()
-        .with(fmt::layer())
-        .with(EnvFilter::from_env("GREPPY_LOG").add_directive("greppy=info".parse().unwrap()))
-        .init();
+    let log_config = Config::load().map(|c| c.log).unwrap_or_default();
+    logging::init(
+        EnvFilter::from_env("GREPPY_LOG").add_directive("greppy=info".parse().unwrap()),
+        &log_config,
+    );
 
     greppy::daemon::server::run_server().await
 }
@@ -0,0 +1,155 @@
+//! Terminal color policy
+//!
+//! `--color` is recorded once at startup; formatters and CLI prints ask
+//! [`should_color`] instead of deciding for themselves.
+
+use crate::cli::ColorChoice;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// `--color` choice for this process (a `ColorChoice` discriminant)
+static CHOICE: AtomicU8 = AtomicU8::new(ColorChoice::Auto as u8);
+
+/// Record the `--color` choice for this process
+pub fn set_choice(choice: ColorChoice) {
+    CHOICE.store(choice as u8, Ordering::Relaxed);
+}
+
+/// The recorded `--color` choice
+pub fn choice() -> ColorChoice {
+    match CHOICE.load(Ordering::Relaxed) {
+        x if x == ColorChoice::Always as u8 => ColorChoice::Always,
+        x if x == ColorChoice::Never as u8 => ColorChoice::Never,
+        _ => ColorChoice::Auto,
+    }
+}
+
+/// Whether output to a stream should carry ANSI colors
+///
+/// Pass whether that stream is a terminal, e.g. `io::stdout().is_terminal()`.
+pub fn should_color(is_terminal: bool) -> bool {
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    resolve(choice(), no_color, is_terminal)
+}
+
+/// `always` and `never` win; `auto` colors terminals unless `NO_COLOR` is set
+fn resolve(choice: ColorChoice, no_color: bool, is_terminal: bool) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => is_terminal && !no_color,
+    }
+}
+
+/// Wrap `text` in an SGR color code (e.g. `"36"` for cyan) when `enabled`
+pub fn paint(text: &str, code: &str, enabled: bool) -> String {
+    if enabled {
+        format!("\x1b[{}m{}\x1b[0m", code, text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Remove ANSI escape sequences from `text`
+pub fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // CSI sequences run until their final letter
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::OutputFormat;
+    use crate::output::format_results;
+    use crate::search::{SearchResponse, SearchResult};
+    use crate::trace::{
+        AsciiConfig, AsciiFormatter, ReferenceInfo, ReferenceKind, RefsResult, TraceFormatter,
+    };
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_never_keeps_trace_and_search_output_free_of_ansi() {
+        assert!(resolve(ColorChoice::Always, true, false));
+        assert!(!resolve(ColorChoice::Auto, true, true));
+        assert!(resolve(ColorChoice::Auto, false, true));
+
+        set_choice(ColorChoice::Never);
+        let color = should_color(true);
+        set_choice(ColorChoice::Auto);
+        assert!(!color);
+
+        let refs = RefsResult {
+            symbol: "load".to_string(),
+            defined_at: Some("src/lib.rs:1".to_string()),
+            symbol_kind: Some("function".to_string()),
+            references: vec![ReferenceInfo {
+                file: "src/main.rs".to_string(),
+                line: 4,
+                column: 5,
+                kind: ReferenceKind::Call,
+                context: "load();".to_string(),
+                enclosing_symbol: Some("main".to_string()),
+                confidence: 1.0,
+                byte_offset: None,
+                callee: None,
+                arg_index: None,
+                target_deprecated: true,
+                referenced_as: None,
+                depth: None,
+            }],
+            total_refs: 1,
+            by_kind: HashMap::new(),
+            by_confidence: HashMap::new(),
+            by_file: HashMap::new(),
+            by_directory: Vec::new(),
+            source_hashes: HashMap::new(),
+            warnings: Vec::new(),
+            symbol_id: None,
+            stable_id: None,
+        };
+        let trace = |no_color| {
+            AsciiFormatter::new(AsciiConfig {
+                ascii_safe: false,
+                no_color,
+            })
+            .format_refs(&refs)
+        };
+        assert!(trace(false).contains('\x1b'));
+        assert!(!trace(!color).contains('\x1b'));
+        assert_eq!(trace(!color), strip_ansi(&trace(false)));
+
+        let search = SearchResponse {
+            results: vec![SearchResult {
+                path: "src/main.rs".to_string(),
+                content: "fn main() {}".to_string(),
+                symbol_name: Some("main".to_string()),
+                symbol_type: Some("function".to_string()),
+                start_line: 1,
+                end_line: 1,
+                language: "rust".to_string(),
+                cell: None,
+                score: 1.5,
+                explanation: None,
+            }],
+            query: "main".to_string(),
+            elapsed_ms: 0.4,
+            project: "demo".to_string(),
+        };
+        assert!(format_results(&search, OutputFormat::Human, true).contains('\x1b'));
+        let plain = format_results(&search, OutputFormat::Human, color);
+        assert!(!plain.contains('\x1b'));
+        assert!(plain.contains("1. src/main.rs:1-1 (1.50)"));
+    }
+}
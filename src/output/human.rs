@@ -1,9 +1,10 @@
 //! Human-readable output formatting
 
+use super::color::paint;
 use crate::search::SearchResponse;
 
-/// Format results for human consumption
-pub fn format(results: &SearchResponse) -> String {
+/// Format results for human consumption, with ANSI colors when `color`
+pub fn format(results: &SearchResponse, color: bool) -> String {
     let mut output = String::new();
 
     if results.results.is_empty() {
@@ -24,12 +25,19 @@ pub fn format(results: &SearchResponse) -> String {
     for (i, result) in results.results.iter().enumerate() {
         // Header: path:lines (score)
         output.push_str(&format!(
-            "{}. {}:{}-{} ({:.2})\n",
+            "{}. {} {}\n",
             i + 1,
-            result.path,
-            result.start_line,
-            result.end_line,
-            result.score
+            paint(
+                &format!(
+                    "{}:{}-{}",
+                    result.location(),
+                    result.start_line,
+                    result.end_line
+                ),
+                "36",
+                color
+            ),
+            paint(&format!("({:.2})", result.score), "2", color)
         ));
 
         // Symbol info if available
@@ -37,6 +45,20 @@ pub fn format(results: &SearchResponse) -> String {
             output.push_str(&format!("   {} {}\n", stype, name));
         }
 
+        // Score breakdown (--explain)
+        if let Some(explanation) = &result.explanation {
+            let parts: Vec<String> = explanation
+                .terms
+                .iter()
+                .map(|t| format!("{}:{} {:.2}", t.field, t.term, t.score))
+                .collect();
+            output.push_str(&format!(
+                "   score {:.2} = {}\n",
+                explanation.score,
+                parts.join(" + ")
+            ));
+        }
+
         // Content preview (first 3 lines, truncated)
         let preview_lines: Vec<&str> = result.content.lines().take(3).collect();
         for line in preview_lines {
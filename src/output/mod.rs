@@ -1,14 +1,16 @@
 //! Output formatting
 
+pub mod color;
 pub mod human;
 pub mod json;
+pub mod paths;
 
 use crate::cli::OutputFormat;
 use crate::search::SearchResponse;
 
-pub fn format_results(results: &SearchResponse, format: OutputFormat) -> String {
+pub fn format_results(results: &SearchResponse, format: OutputFormat, color: bool) -> String {
     match format {
-        OutputFormat::Human => human::format(results),
+        OutputFormat::Human => human::format(results, color),
         OutputFormat::Json => json::format(results),
     }
 }
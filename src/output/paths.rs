@@ -0,0 +1,56 @@
+//! Path-only output formatting
+
+use crate::search::SearchResponse;
+use std::collections::HashSet;
+
+/// Format results as unique file paths in rank order, one per line
+pub fn format(results: &SearchResponse) -> String {
+    let mut seen = HashSet::new();
+    let mut output = String::new();
+
+    for result in &results.results {
+        if seen.insert(result.path.as_str()) {
+            output.push_str(&result.path);
+            output.push('\n');
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::SearchResult;
+
+    fn result(path: &str, start_line: usize, score: f32) -> SearchResult {
+        SearchResult {
+            path: path.to_string(),
+            content: String::new(),
+            symbol_name: None,
+            symbol_type: None,
+            start_line,
+            end_line: start_line + 5,
+            language: "rust".to_string(),
+            cell: None,
+            score,
+            explanation: None,
+        }
+    }
+
+    #[test]
+    fn test_same_file_yields_single_path() {
+        let response = SearchResponse {
+            results: vec![
+                result("src/auth.rs", 10, 3.0),
+                result("src/main.rs", 1, 2.5),
+                result("src/auth.rs", 80, 2.0),
+            ],
+            query: "auth".to_string(),
+            elapsed_ms: 1.0,
+            project: "/tmp/project".to_string(),
+        };
+
+        assert_eq!(format(&response), "src/auth.rs\nsrc/main.rs\n");
+    }
+}
@@ -1,4 +1,5 @@
-use crate::core::config::{CHUNK_MAX_LINES, CHUNK_OVERLAP};
+use crate::core::config::{IndexConfig, CHUNK_MAX_LINES, CHUNK_OVERLAP};
+use crate::parse::notebook::{code_cells, is_notebook};
 use crate::parse::walker::detect_language;
 use std::path::Path;
 
@@ -13,56 +14,126 @@ pub struct Chunk {
     pub end_line: usize,
     pub language: String,
     pub file_hash: String,
+    /// Notebook cell this chunk came from (lines are relative to the cell)
+    pub cell: Option<usize>,
 }
 
 impl Chunk {
     /// Generate unique ID for this chunk
     pub fn id(&self) -> String {
-        format!("{}:{}:{}", self.path, self.start_line, self.end_line)
+        format!("{}:{}:{}", self.location(), self.start_line, self.end_line)
+    }
+
+    /// Source location: the file path, or `file:cellN` for notebook cells
+    pub fn location(&self) -> String {
+        match self.cell {
+            Some(cell) => format!("{}:cell{}", self.path, cell),
+            None => self.path.clone(),
+        }
     }
 }
 
-/// Chunk a file into indexable pieces
-pub fn chunk_file(path: &Path, content: &str) -> Vec<Chunk> {
-    let language = detect_language(path);
-    let file_hash = compute_hash(content);
-    let path_str = path.to_string_lossy().to_string();
+/// Splits files into overlapping windows of lines
+#[derive(Debug, Clone, Copy)]
+pub struct Chunker {
+    max_lines: usize,
+    overlap: usize,
+}
 
-    let lines: Vec<&str> = content.lines().collect();
+impl Default for Chunker {
+    fn default() -> Self {
+        Self::new(CHUNK_MAX_LINES, CHUNK_OVERLAP)
+    }
+}
 
-    if lines.is_empty() {
-        return Vec::new();
+impl Chunker {
+    /// Chunks of at most `max_lines`, each repeating the previous one's last
+    /// `overlap` lines
+    ///
+    /// The overlap is capped below `max_lines` so every chunk makes progress.
+    pub fn new(max_lines: usize, overlap: usize) -> Self {
+        let max_lines = max_lines.max(1);
+        Self {
+            max_lines,
+            overlap: overlap.min(max_lines - 1),
+        }
     }
 
-    let mut chunks = Vec::new();
-    let mut start = 0;
+    /// Chunker for the `[index]` chunk settings
+    pub fn from_config(config: &IndexConfig) -> Self {
+        Self::new(config.chunk_lines, config.chunk_overlap)
+    }
 
-    while start < lines.len() {
-        let end = (start + CHUNK_MAX_LINES).min(lines.len());
-        let chunk_content = lines[start..end].join("\n");
+    /// Chunk a file into indexable pieces
+    pub fn chunk_file(&self, path: &Path, content: &str) -> Vec<Chunk> {
+        let file_hash = compute_hash(content);
+        let path_str = path.to_string_lossy().to_string();
+
+        if is_notebook(path) {
+            return code_cells(content)
+                .into_iter()
+                .flat_map(|cell| {
+                    let lines: Vec<&str> = cell.source.lines().collect();
+                    self.chunk_lines(
+                        &path_str,
+                        &lines,
+                        &cell.language,
+                        &file_hash,
+                        Some(cell.index),
+                    )
+                })
+                .collect();
+        }
 
-        // Try to extract symbol name from first non-empty line
-        let (symbol_name, symbol_type) = extract_symbol(&lines[start..end]);
+        let language = detect_language(path);
+        let lines: Vec<&str> = content.lines().collect();
+        self.chunk_lines(&path_str, &lines, &language, &file_hash, None)
+    }
 
-        chunks.push(Chunk {
-            path: path_str.clone(),
-            content: chunk_content,
-            symbol_name,
-            symbol_type,
-            start_line: start + 1, // 1-indexed
-            end_line: end,
-            language: language.clone(),
-            file_hash: file_hash.clone(),
-        });
+    /// Split lines into overlapping windows of at most `max_lines`
+    fn chunk_lines(
+        &self,
+        path: &str,
+        lines: &[&str],
+        language: &str,
+        file_hash: &str,
+        cell: Option<usize>,
+    ) -> Vec<Chunk> {
+        if lines.is_empty() {
+            return Vec::new();
+        }
 
-        if end >= lines.len() {
-            break;
+        let mut chunks = Vec::new();
+        let mut start = 0;
+
+        while start < lines.len() {
+            let end = (start + self.max_lines).min(lines.len());
+            let chunk_content = lines[start..end].join("\n");
+
+            // Try to extract symbol name from first non-empty line
+            let (symbol_name, symbol_type) = extract_symbol(&lines[start..end]);
+
+            chunks.push(Chunk {
+                path: path.to_string(),
+                content: chunk_content,
+                symbol_name,
+                symbol_type,
+                start_line: start + 1, // 1-indexed
+                end_line: end,
+                language: language.to_string(),
+                file_hash: file_hash.to_string(),
+                cell,
+            });
+
+            if end >= lines.len() {
+                break;
+            }
+
+            start = end - self.overlap;
         }
 
-        start = end.saturating_sub(CHUNK_OVERLAP);
+        chunks
     }
-
-    chunks
 }
 
 /// Compute hash of content
@@ -219,3 +290,50 @@ fn extract_method_name(line: &str) -> Option<String> {
 
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notebook_code_cells_become_chunks() {
+        let notebook = r##"{
+            "metadata": {"kernelspec": {"language": "python", "name": "python3"}},
+            "cells": [
+                {"cell_type": "markdown", "source": ["# Title"]},
+                {"cell_type": "code", "source": ["def load(path):\n", "    return open(path)\n"]},
+                {"cell_type": "code", "source": "class Model:\n    pass\n"}
+            ]
+        }"##;
+        let path = Path::new("/proj/analysis.ipynb");
+        let chunks = Chunker::default().chunk_file(path, notebook);
+
+        assert_eq!(chunks.len(), 2);
+        assert!(chunks.iter().all(|c| c.path == "/proj/analysis.ipynb"));
+        assert!(chunks.iter().all(|c| c.language == "python"));
+        assert_eq!(chunks[0].location(), "/proj/analysis.ipynb:cell2");
+        assert_eq!(chunks[0].symbol_name.as_deref(), Some("load"));
+        assert_eq!(chunks[1].location(), "/proj/analysis.ipynb:cell3");
+        assert_eq!(chunks[1].symbol_name.as_deref(), Some("Model"));
+        assert_ne!(chunks[0].id(), chunks[1].id());
+    }
+
+    #[test]
+    fn test_configured_overlap_shares_boundary_lines() {
+        let content: String = (1..=20).map(|i| format!("line {}\n", i)).collect();
+        let config = IndexConfig {
+            chunk_lines: 8,
+            chunk_overlap: 2,
+            ..IndexConfig::default()
+        };
+        let chunks = Chunker::from_config(&config).chunk_file(Path::new("notes.txt"), &content);
+
+        let ranges: Vec<_> = chunks.iter().map(|c| (c.start_line, c.end_line)).collect();
+        assert_eq!(ranges, vec![(1, 8), (7, 14), (13, 20)]);
+        for pair in chunks.windows(2) {
+            let tail: Vec<_> = pair[0].content.lines().rev().take(2).collect();
+            let head: Vec<_> = pair[1].content.lines().take(2).collect();
+            assert_eq!(tail.into_iter().rev().collect::<Vec<_>>(), head);
+        }
+    }
+}
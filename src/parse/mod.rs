@@ -1,5 +1,6 @@
 pub mod chunker;
+pub mod notebook;
 pub mod walker;
 
-pub use chunker::{chunk_file, Chunk};
-pub use walker::{walk_project, FileInfo};
+pub use chunker::{Chunk, Chunker};
+pub use walker::{git_tracked_files, walk_git_tracked, walk_project, FileInfo};
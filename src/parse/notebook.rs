@@ -0,0 +1,90 @@
+//! Jupyter notebook (`.ipynb`) support
+//!
+//! Notebooks are JSON documents, so their code cells are pulled out here and
+//! chunked individually with a synthetic `file:cell` location.
+
+use serde_json::Value;
+
+/// Language assumed when a notebook carries no kernel metadata
+const DEFAULT_NOTEBOOK_LANGUAGE: &str = "python";
+
+/// A code cell extracted from a notebook
+#[derive(Debug, Clone)]
+pub struct NotebookCell {
+    /// 1-indexed position of the cell among all cells in the notebook
+    pub index: usize,
+    pub language: String,
+    pub source: String,
+}
+
+/// Check if a path is a Jupyter notebook
+pub fn is_notebook(path: &std::path::Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("ipynb"))
+}
+
+/// Extract the non-empty code cells of a notebook
+///
+/// Returns an empty list when the content is not a valid notebook.
+pub fn code_cells(content: &str) -> Vec<NotebookCell> {
+    let Ok(notebook) = serde_json::from_str::<Value>(content) else {
+        return Vec::new();
+    };
+    let Some(cells) = notebook.get("cells").and_then(Value::as_array) else {
+        return Vec::new();
+    };
+    let default_language = notebook_language(&notebook);
+
+    cells
+        .iter()
+        .enumerate()
+        .filter(|(_, cell)| cell.get("cell_type").and_then(Value::as_str) == Some("code"))
+        .filter_map(|(i, cell)| {
+            let source = cell_source(cell.get("source")?);
+            if source.trim().is_empty() {
+                return None;
+            }
+            let language = cell
+                .pointer("/metadata/vscode/languageId")
+                .and_then(Value::as_str)
+                .map(str::to_lowercase)
+                .unwrap_or_else(|| default_language.clone());
+            Some(NotebookCell {
+                index: i + 1,
+                language,
+                source,
+            })
+        })
+        .collect()
+}
+
+/// The code cells joined in order into one source, a blank line apart
+///
+/// Trace extraction runs on this text, so its line numbers count lines of
+/// code cells rather than lines of the JSON file.
+pub fn code_source(cells: &[NotebookCell]) -> String {
+    let sources: Vec<&str> = cells.iter().map(|c| c.source.trim_end()).collect();
+    let mut source = sources.join("\n\n");
+    source.push('\n');
+    source
+}
+
+/// Kernel language declared in the notebook metadata
+fn notebook_language(notebook: &Value) -> String {
+    notebook
+        .pointer("/metadata/kernelspec/language")
+        .or_else(|| notebook.pointer("/metadata/language_info/name"))
+        .and_then(Value::as_str)
+        .unwrap_or(DEFAULT_NOTEBOOK_LANGUAGE)
+        .to_lowercase()
+}
+
+/// Cell source is either a single string or a list of lines
+fn cell_source(source: &Value) -> String {
+    match source {
+        Value::String(s) => s.clone(),
+        Value::Array(lines) => lines.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
@@ -1,7 +1,8 @@
 use crate::core::config::MAX_FILE_SIZE;
-use crate::core::error::Result;
+use crate::core::error::{Error, Result};
 use ignore::WalkBuilder;
 use std::path::{Path, PathBuf};
+use std::process::Command;
 
 /// File info for indexing
 pub struct FileInfo {
@@ -43,6 +44,53 @@ pub fn walk_project(root: &Path) -> Result<Vec<FileInfo>> {
     Ok(files)
 }
 
+/// Read the code files of a project that git tracks
+///
+/// Errors when `root` is not inside a git repository.
+pub fn walk_git_tracked(root: &Path) -> Result<Vec<FileInfo>> {
+    Ok(git_tracked_files(root)?
+        .into_iter()
+        .filter(|path| is_code_file(path))
+        .filter(|path| {
+            std::fs::metadata(path)
+                .map(|m| m.is_file() && m.len() <= MAX_FILE_SIZE)
+                .unwrap_or(false)
+        })
+        .filter_map(|path| {
+            let content = std::fs::read_to_string(&path).ok()?;
+            Some(FileInfo { path, content })
+        })
+        .collect())
+}
+
+/// List files tracked by git, as absolute paths under `root`
+pub fn git_tracked_files(root: &Path) -> Result<Vec<PathBuf>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(["ls-files", "-z"])
+        .output()
+        .map_err(|e| Error::IndexError {
+            message: format!("Indexing git-tracked files requires git: {}", e),
+        })?;
+
+    if !output.status.success() {
+        return Err(Error::IndexError {
+            message: format!(
+                "Indexing git-tracked files requires a git repository, but {} is not one",
+                root.display()
+            ),
+        });
+    }
+
+    Ok(output
+        .stdout
+        .split(|&b| b == 0)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| root.join(String::from_utf8_lossy(entry).as_ref()))
+        .collect())
+}
+
 /// Check if file is a code file worth indexing
 fn is_code_file(path: &Path) -> bool {
     let ext = path
@@ -95,6 +143,7 @@ fn is_code_file(path: &Path) -> bool {
             | "yml"
             | "toml"
             | "json"
+            | "ipynb"
     )
 }
 
@@ -134,6 +183,7 @@ pub fn detect_language(path: &Path) -> String {
         "yaml" | "yml" => "yaml",
         "toml" => "toml",
         "json" => "json",
+        "ipynb" => "notebook",
         _ => "unknown",
     }
     .to_string()
@@ -0,0 +1,63 @@
+//! Query alias expansion
+//!
+//! Expands team jargon from the `[aliases]` config section into extra query
+//! terms, deterministically and without any AI provider.
+
+use std::collections::HashMap;
+
+/// Terms for a query with configured aliases expanded
+///
+/// Returns the query first, then the expansions of the whole query and of
+/// each whitespace-separated word, without duplicates. Alias keys match
+/// case-insensitively.
+pub fn expand_aliases(query: &str, aliases: &HashMap<String, Vec<String>>) -> Vec<String> {
+    let mut terms = vec![query.to_string()];
+    if aliases.is_empty() {
+        return terms;
+    }
+
+    let keys = std::iter::once(query).chain(query.split_whitespace());
+    for key in keys {
+        let expansions = aliases
+            .iter()
+            .filter(|(alias, _)| alias.eq_ignore_ascii_case(key))
+            .flat_map(|(_, expansions)| expansions);
+        for expansion in expansions {
+            if !terms.iter().any(|t| t.eq_ignore_ascii_case(expansion)) {
+                terms.push(expansion.clone());
+            }
+        }
+    }
+
+    terms
+}
+
+/// A search query with alias expansions appended (terms are OR'd by BM25)
+pub fn expand_query_text(query: &str, aliases: &HashMap<String, Vec<String>>) -> String {
+    expand_aliases(query, aliases).join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alias_expands_search_terms() {
+        let aliases: HashMap<String, Vec<String>> = [(
+            "auth".to_string(),
+            vec!["authentication".to_string(), "login".to_string()],
+        )]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            expand_aliases("auth", &aliases),
+            ["auth", "authentication", "login"]
+        );
+        assert_eq!(
+            expand_query_text("Auth token", &aliases),
+            "Auth token authentication login"
+        );
+        assert_eq!(expand_aliases("session", &aliases), ["session"]);
+    }
+}
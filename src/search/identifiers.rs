@@ -0,0 +1,59 @@
+//! Identifier splitting for queries
+//!
+//! Tantivy's default tokenizer keeps `getUserById` as one token but splits
+//! `get_user_by_id` into four, so the two spellings never match each other.
+//! Splitting identifiers into their words lets a query reach both.
+
+/// Split an identifier into lowercase words at case changes and separators
+///
+/// `getUserById` and `get_user_by_id` both give `get`, `user`, `by`, `id`;
+/// acronyms stay together (`HTTPServer` gives `http`, `server`).
+pub fn split_identifier(identifier: &str) -> Vec<String> {
+    let chars: Vec<char> = identifier.chars().collect();
+    let mut words = Vec::new();
+    let mut current = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if c.is_uppercase() && !current.is_empty() {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            // `userId` -> user|Id, `HTTPServer` -> HTTP|Server
+            if !prev.is_uppercase() || next_is_lower {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// Alternative query terms for the multi-word identifiers in `text`
+///
+/// Each identifier contributes its words and their concatenation, so
+/// `getUserById` also looks for `get`, `user`, `by` and `id`, and
+/// `get_user_by_id` for `getuserbyid`. Terms are lowercase and unique.
+pub fn identifier_variants(text: &str) -> Vec<String> {
+    let mut variants: Vec<String> = Vec::new();
+    for identifier in text.split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-')) {
+        let words = split_identifier(identifier);
+        if words.len() < 2 {
+            continue;
+        }
+        let joined = words.concat();
+        for term in words.into_iter().chain([joined]) {
+            if !variants.contains(&term) {
+                variants.push(term);
+            }
+        }
+    }
+    variants
+}
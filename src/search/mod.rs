@@ -1,5 +1,8 @@
+pub mod aliases;
+pub mod identifiers;
 pub mod query;
+pub mod rerank;
 pub mod results;
 
 pub use query::SearchQuery;
-pub use results::{SearchResponse, SearchResult};
+pub use results::{ScoreExplanation, SearchResponse, SearchResult, TermContribution};
@@ -1,14 +1,15 @@
 //! Query parsing and execution
 
 use crate::core::error::{Error, Result};
-use crate::index::TantivyIndex;
-use crate::search::results::{SearchResponse, SearchResult};
+use crate::index::{path_prefix_query, TantivyIndex};
+use crate::search::identifiers::identifier_variants;
+use crate::search::results::{ScoreExplanation, SearchResponse, SearchResult, TermContribution};
 use std::path::PathBuf;
 use std::time::Instant;
 use tantivy::collector::TopDocs;
 use tantivy::query::{BooleanQuery, BoostQuery, Occur, Query, TermQuery};
 use tantivy::schema::{IndexRecordOption, Value};
-use tantivy::Term;
+use tantivy::{DocAddress, Searcher, Term};
 use tracing::debug;
 
 /// A search query with options
@@ -18,10 +19,22 @@ pub struct SearchQuery {
     pub text: String,
     /// Maximum results to return
     pub limit: usize,
-    /// Filter to specific paths
+    /// Only return chunks under one of these paths (none: everywhere)
     pub path_filters: Vec<PathBuf>,
     /// Include test files
     pub include_tests: bool,
+    /// Attach a score breakdown to each result
+    pub explain: bool,
+}
+
+/// Boost of identifier sub-term clauses relative to the query's own terms
+pub(crate) const VARIANT_BOOST: f32 = 0.5;
+
+/// One scored clause of the query: a term searched in a field
+struct TermClause {
+    term: String,
+    field: &'static str,
+    query: Box<dyn Query>,
 }
 
 impl SearchQuery {
@@ -32,6 +45,7 @@ impl SearchQuery {
             limit: 20,
             path_filters: Vec::new(),
             include_tests: false,
+            explain: false,
         }
     }
 
@@ -53,6 +67,12 @@ impl SearchQuery {
         self
     }
 
+    /// Explain each result's score
+    pub fn with_explain(mut self, explain: bool) -> Self {
+        self.explain = explain;
+        self
+    }
+
     /// Execute the search against an index
     pub fn execute(&self, index: &TantivyIndex) -> Result<SearchResponse> {
         let start = Instant::now();
@@ -61,7 +81,24 @@ impl SearchQuery {
         let schema = &index.schema;
 
         // Build the query
-        let query = self.build_query(index)?;
+        let clauses = self.term_clauses(index)?;
+        let mut query: Box<dyn Query> = Box::new(BooleanQuery::new(
+            clauses
+                .iter()
+                .map(|clause| (Occur::Should, clause.query.box_clone()))
+                .collect(),
+        ));
+        if !self.path_filters.is_empty() {
+            let paths = self
+                .path_filters
+                .iter()
+                .map(|dir| Ok((Occur::Should, path_prefix_query(schema.path, dir)?)))
+                .collect::<Result<Vec<_>>>()?;
+            query = Box::new(BooleanQuery::new(vec![
+                (Occur::Must, query),
+                (Occur::Must, Box::new(BooleanQuery::new(paths))),
+            ]));
+        }
 
         // Execute search
         let top_docs = searcher
@@ -118,6 +155,11 @@ impl SearchQuery {
                 .unwrap_or("unknown")
                 .to_string();
 
+            let cell = doc
+                .get_first(schema.cell)
+                .and_then(|v| v.as_u64())
+                .map(|c| c as usize);
+
             results.push(SearchResult {
                 path,
                 content,
@@ -126,7 +168,11 @@ impl SearchQuery {
                 start_line,
                 end_line,
                 language,
+                cell,
                 score,
+                explanation: self
+                    .explain
+                    .then(|| explain_score(&searcher, &clauses, doc_address, score)),
             });
         }
 
@@ -152,8 +198,8 @@ impl SearchQuery {
         Ok(response)
     }
 
-    /// Build a Tantivy query from the search text
-    fn build_query(&self, index: &TantivyIndex) -> Result<Box<dyn Query>> {
+    /// Build the query clauses for the search text
+    fn term_clauses(&self, index: &TantivyIndex) -> Result<Vec<TermClause>> {
         let schema = &index.schema;
 
         // Tokenize the query
@@ -176,24 +222,219 @@ impl SearchQuery {
             });
         }
 
+        // Identifier spellings the tokenizer can't bridge (`getUserById` vs
+        // `get_user_by_id`) are searched too, at a lower weight
+        let variants: Vec<String> = identifier_variants(&self.text)
+            .into_iter()
+            .filter(|v| !tokens.contains(v))
+            .collect();
+
         // Build query: search in content and symbol_name (boosted)
-        let mut subqueries: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+        let mut clauses = Vec::new();
 
-        for token in &tokens {
+        for (token, boost) in tokens
+            .into_iter()
+            .map(|t| (t, 1.0))
+            .chain(variants.into_iter().map(|v| (v, VARIANT_BOOST)))
+        {
             // Content query
-            let content_term = Term::from_field_text(schema.content, token);
+            let content_term = Term::from_field_text(schema.content, &token);
             let content_query = TermQuery::new(content_term, IndexRecordOption::WithFreqs);
+            let content_query = BoostQuery::new(Box::new(content_query), boost);
 
             // Symbol name query (boosted 3x)
-            let symbol_term = Term::from_field_text(schema.symbol_name, token);
+            let symbol_term = Term::from_field_text(schema.symbol_name, &token);
             let symbol_query = TermQuery::new(symbol_term, IndexRecordOption::WithFreqs);
-            let boosted_symbol = BoostQuery::new(Box::new(symbol_query), 3.0);
+            let boosted_symbol = BoostQuery::new(Box::new(symbol_query), 3.0 * boost);
 
-            // Combine with OR
-            subqueries.push((Occur::Should, Box::new(content_query)));
-            subqueries.push((Occur::Should, Box::new(boosted_symbol)));
+            // Combined with OR by the caller
+            clauses.push(TermClause {
+                term: token.clone(),
+                field: "content",
+                query: Box::new(content_query),
+            });
+            clauses.push(TermClause {
+                term: token,
+                field: "symbol_name",
+                query: Box::new(boosted_symbol),
+            });
         }
 
-        Ok(Box::new(BooleanQuery::new(subqueries)))
+        Ok(clauses)
+    }
+}
+
+/// Break a result's score down into the clauses that matched it
+///
+/// The clauses are OR-ed, so the contributions sum to the final score.
+fn explain_score(
+    searcher: &Searcher,
+    clauses: &[TermClause],
+    doc_address: DocAddress,
+    score: f32,
+) -> ScoreExplanation {
+    let terms = clauses
+        .iter()
+        .filter_map(|clause| {
+            // Clauses that do not match the document fail to explain
+            let explanation = clause.query.explain(searcher, doc_address).ok()?;
+            Some(TermContribution {
+                term: clause.term.clone(),
+                field: clause.field.to_string(),
+                score: explanation.value(),
+            })
+        })
+        .collect();
+    ScoreExplanation { score, terms }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::schema::IndexSchema;
+    use crate::index::IndexWriter;
+    use crate::parse::Chunk;
+    use tantivy::{Index, ReloadPolicy};
+
+    fn chunk(path: &str, symbol: &str, content: &str) -> Chunk {
+        Chunk {
+            path: path.to_string(),
+            content: content.to_string(),
+            symbol_name: Some(symbol.to_string()),
+            symbol_type: Some("function".to_string()),
+            start_line: 1,
+            end_line: 3,
+            language: "rust".to_string(),
+            file_hash: String::new(),
+            cell: None,
+        }
+    }
+
+    #[test]
+    fn test_explain_lists_matched_terms() {
+        let schema = IndexSchema::new();
+        let index = Index::create_in_ram(schema.schema.clone());
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()
+            .unwrap();
+        let index = TantivyIndex {
+            index,
+            schema,
+            reader,
+        };
+
+        let mut writer = IndexWriter::new(&index).unwrap();
+        writer
+            .add_chunk(&chunk(
+                "auth.rs",
+                "login",
+                "fn login(user: User) { check(user) }",
+            ))
+            .unwrap();
+        writer
+            .add_chunk(&chunk("db.rs", "connect", "fn connect() { pool() }"))
+            .unwrap();
+        writer.commit().unwrap();
+        index.reader.reload().unwrap();
+
+        let response = SearchQuery::new("login user session")
+            .with_explain(true)
+            .execute(&index)
+            .unwrap();
+        assert_eq!(response.results.len(), 1);
+        let result = &response.results[0];
+        let explanation = result.explanation.as_ref().unwrap();
+
+        assert_eq!(explanation.matched_terms(), vec!["login", "user"]);
+        assert!(explanation
+            .terms
+            .iter()
+            .any(|t| t.term == "login" && t.field == "symbol_name"));
+        let total: f32 = explanation.terms.iter().map(|t| t.score).sum();
+        assert!((total - result.score).abs() < 1e-3);
+
+        // Explanations are opt-in
+        let response = SearchQuery::new("login").execute(&index).unwrap();
+        assert!(response.results[0].explanation.is_none());
+    }
+
+    #[test]
+    fn test_notebook_cells_are_returned_with_their_cell() {
+        let schema = IndexSchema::new();
+        let index = Index::create_in_ram(schema.schema.clone());
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()
+            .unwrap();
+        let index = TantivyIndex {
+            index,
+            schema,
+            reader,
+        };
+
+        let notebook = r##"{"cells": [
+            {"cell_type": "code", "source": "def load_frame(path):\n    return read(path)\n"},
+            {"cell_type": "code", "source": "def save_frame(frame):\n    write(frame)\n"}
+        ]}"##;
+        let mut writer = IndexWriter::new(&index).unwrap();
+        for chunk in crate::parse::Chunker::default()
+            .chunk_file(std::path::Path::new("analysis.ipynb"), notebook)
+        {
+            writer.add_chunk(&chunk).unwrap();
+        }
+        writer.commit().unwrap();
+        index.reader.reload().unwrap();
+
+        // Both cells cover lines 1-2, but they are different code
+        let response = SearchQuery::new("frame").execute(&index).unwrap();
+        let mut cells: Vec<_> = response.results.iter().map(|r| r.location()).collect();
+        cells.sort();
+        assert_eq!(cells, vec!["analysis.ipynb:cell1", "analysis.ipynb:cell2"]);
+    }
+
+    #[test]
+    fn test_identifier_query_adds_split_sub_terms() {
+        let schema = IndexSchema::new();
+        let index = Index::create_in_ram(schema.schema.clone());
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()
+            .unwrap();
+        let index = TantivyIndex {
+            index,
+            schema,
+            reader,
+        };
+
+        let clauses = SearchQuery::new("getUserById")
+            .term_clauses(&index)
+            .unwrap();
+        let mut terms: Vec<&str> = clauses
+            .iter()
+            .filter(|c| c.field == "content")
+            .map(|c| c.term.as_str())
+            .collect();
+        terms.sort();
+        assert_eq!(terms, vec!["by", "get", "getuserbyid", "id", "user"]);
+
+        // The sub-terms reach snake_case content
+        let mut writer = IndexWriter::new(&index).unwrap();
+        writer
+            .add_chunk(&chunk(
+                "users.py",
+                "get_user_by_id",
+                "def get_user_by_id(user_id): return db.get(user_id)",
+            ))
+            .unwrap();
+        writer.commit().unwrap();
+        index.reader.reload().unwrap();
+
+        let response = SearchQuery::new("getUserById").execute(&index).unwrap();
+        assert_eq!(response.results.len(), 1);
+        assert_eq!(response.results[0].path, "users.py");
     }
 }
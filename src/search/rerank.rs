@@ -0,0 +1,206 @@
+//! Embedding-based reranking
+//!
+//! Reorders BM25 results by cosine similarity between the query embedding
+//! and each chunk's embedding. Embeddings are cached per (model, content)
+//! so repeated searches only embed new chunks.
+
+use crate::core::error::Result;
+use crate::search::results::SearchResult;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::Path;
+use xxhash_rust::xxh3::xxh3_64;
+
+/// Embedding vectors keyed by model and content hash
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct EmbeddingCache {
+    entries: HashMap<String, Vec<f32>>,
+}
+
+impl EmbeddingCache {
+    /// Load a cache file, starting empty if it is missing or unreadable
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the cache file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Number of cached vectors
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the cache holds no vectors
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn key(model: &str, text: &str) -> String {
+        format!("{}:{:016x}", model, xxh3_64(text.as_bytes()))
+    }
+}
+
+/// Cosine similarity of two vectors (0.0 when either is zero or lengths differ)
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+/// Reorder results by embedding similarity to the query
+///
+/// `embed` is called at most once, with every text not already in the cache
+/// (the query included), and must return one vector per text. Ties keep
+/// their BM25 order.
+pub async fn rerank_by_embeddings<F, Fut>(
+    query: &str,
+    results: &mut Vec<SearchResult>,
+    model: &str,
+    cache: &mut EmbeddingCache,
+    embed: F,
+) -> Result<()>
+where
+    F: FnOnce(Vec<String>) -> Fut,
+    Fut: Future<Output = Result<Vec<Vec<f32>>>>,
+{
+    if results.is_empty() {
+        return Ok(());
+    }
+
+    let texts: Vec<&str> = std::iter::once(query)
+        .chain(results.iter().map(|r| r.content.as_str()))
+        .collect();
+
+    let mut missing: Vec<String> = Vec::new();
+    for text in &texts {
+        let key = EmbeddingCache::key(model, text);
+        if !cache.entries.contains_key(&key) && !missing.iter().any(|m| m == text) {
+            missing.push(text.to_string());
+        }
+    }
+
+    if !missing.is_empty() {
+        let vectors = embed(missing.clone()).await?;
+        for (text, vector) in missing.iter().zip(vectors) {
+            cache
+                .entries
+                .insert(EmbeddingCache::key(model, text), vector);
+        }
+    }
+
+    let vector = |text: &str| {
+        cache
+            .entries
+            .get(&EmbeddingCache::key(model, text))
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    };
+    let query_vector = vector(query);
+    let mut scored: Vec<(f32, SearchResult)> = std::mem::take(results)
+        .into_iter()
+        .map(|r| (cosine_similarity(query_vector, vector(&r.content)), r))
+        .collect();
+
+    // Stable sort keeps BM25 order among equally similar chunks
+    scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+    results.extend(scored.into_iter().map(|(_, r)| r));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn result(path: &str, content: &str, score: f32) -> SearchResult {
+        SearchResult {
+            path: path.to_string(),
+            content: content.to_string(),
+            symbol_name: None,
+            symbol_type: None,
+            start_line: 1,
+            end_line: 5,
+            language: "rust".to_string(),
+            cell: None,
+            score,
+            explanation: None,
+        }
+    }
+
+    /// Bag-of-words embedder over a tiny fixed vocabulary
+    fn mock_embed(texts: &[String]) -> Vec<Vec<f32>> {
+        const VOCAB: [&str; 4] = ["login", "password", "render", "pixel"];
+        texts
+            .iter()
+            .map(|t| VOCAB.iter().map(|w| t.matches(w).count() as f32).collect())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_rerank_puts_similar_chunk_first() {
+        let mut results = vec![
+            result(
+                "src/draw.rs",
+                "fn render() { pixel(); render_pixel(); }",
+                9.0,
+            ),
+            result(
+                "src/auth.rs",
+                "fn login(password: &str) { check(password) }",
+                4.0,
+            ),
+        ];
+        let mut cache = EmbeddingCache::default();
+        let calls = Cell::new(0);
+
+        rerank_by_embeddings(
+            "login password",
+            &mut results,
+            "mock",
+            &mut cache,
+            |texts| {
+                calls.set(calls.get() + 1);
+                async move { Ok(mock_embed(&texts)) }
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(results[0].path, "src/auth.rs");
+        assert_eq!(results[1].path, "src/draw.rs");
+        assert_eq!(cache.len(), 3);
+
+        // Everything is cached now: a repeat search embeds nothing
+        rerank_by_embeddings(
+            "login password",
+            &mut results,
+            "mock",
+            &mut cache,
+            |_texts| async { panic!("cached embeddings should be reused") },
+        )
+        .await
+        .unwrap();
+        assert_eq!(calls.get(), 1);
+        assert_eq!(results[0].path, "src/auth.rs");
+    }
+}
@@ -11,13 +11,58 @@ pub struct SearchResult {
     pub start_line: usize,
     pub end_line: usize,
     pub language: String,
+    /// Notebook cell the lines belong to (lines are relative to the cell)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cell: Option<usize>,
     pub score: f32,
+    /// Why the result scored as it did (only with `--explain`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<ScoreExplanation>,
+}
+
+/// Breakdown of a result's BM25 score
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreExplanation {
+    /// Final score (the sum of the contributions)
+    pub score: f32,
+    /// Query terms that matched, per field
+    pub terms: Vec<TermContribution>,
+}
+
+/// Score contributed by one query term matching in one field
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TermContribution {
+    pub term: String,
+    pub field: String,
+    pub score: f32,
+}
+
+impl ScoreExplanation {
+    /// Distinct query terms that matched in any field, in query order
+    pub fn matched_terms(&self) -> Vec<&str> {
+        let mut terms: Vec<&str> = Vec::new();
+        for contribution in &self.terms {
+            if !terms.contains(&contribution.term.as_str()) {
+                terms.push(&contribution.term);
+            }
+        }
+        terms
+    }
 }
 
 impl SearchResult {
+    /// Source location: the file path, or `file:cellN` for notebook cells
+    pub fn location(&self) -> String {
+        match self.cell {
+            Some(cell) => format!("{}:cell{}", self.path, cell),
+            None => self.path.clone(),
+        }
+    }
+
     /// Check if this result overlaps with another (same file, overlapping lines)
     fn overlaps(&self, other: &SearchResult) -> bool {
         self.path == other.path
+            && self.cell == other.cell
             && self.start_line <= other.end_line
             && other.start_line <= self.end_line
     }
@@ -0,0 +1,144 @@
+//! Last-Modified Dates
+//!
+//! Dates symbols by the commit that last touched their definition line
+//! (`--with-dates`). Blame is expensive, so it runs once per file and every
+//! symbol of that file is answered from the same output.
+//!
+//! @module trace/blame
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use chrono::DateTime;
+
+use super::index::SemanticIndex;
+
+/// Commit date (`YYYY-MM-DD`, UTC) each line of a file was last modified
+///
+/// Returns None when git is unavailable or the file is not tracked.
+pub fn blame_dates(root: &Path, file: &Path) -> Option<HashMap<u32, String>> {
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(["blame", "--porcelain", "--"])
+        .arg(file)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(parse_porcelain(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Map final line numbers to commit dates in `git blame --porcelain` output
+///
+/// Commit headers (`committer-time`) are only printed the first time a
+/// commit appears, so times are remembered per commit.
+pub fn parse_porcelain(output: &str) -> HashMap<u32, String> {
+    let mut commit_times: HashMap<&str, i64> = HashMap::new();
+    let mut line_commits: Vec<(u32, &str)> = Vec::new();
+    let mut current: Option<&str> = None;
+
+    for line in output.lines() {
+        if line.starts_with('\t') {
+            // Line content ends the entry
+            current = None;
+            continue;
+        }
+        match current {
+            None => {
+                let mut fields = line.split(' ');
+                let (Some(commit), Some(_), Some(final_line)) =
+                    (fields.next(), fields.next(), fields.next())
+                else {
+                    continue;
+                };
+                if let Ok(final_line) = final_line.parse() {
+                    line_commits.push((final_line, commit));
+                    current = Some(commit);
+                }
+            }
+            Some(commit) => {
+                if let Some(time) = line.strip_prefix("committer-time ") {
+                    if let Ok(time) = time.trim().parse() {
+                        commit_times.insert(commit, time);
+                    }
+                }
+            }
+        }
+    }
+
+    line_commits
+        .into_iter()
+        .filter_map(|(line, commit)| {
+            let time = DateTime::from_timestamp(*commit_times.get(commit)?, 0)?;
+            Some((line, time.format("%Y-%m-%d").to_string()))
+        })
+        .collect()
+}
+
+/// Last-modified date of each symbol's definition line, keyed by symbol id
+///
+/// Symbols in files git cannot blame are left out.
+pub fn symbol_dates(
+    root: &Path,
+    index: &SemanticIndex,
+    symbol_ids: impl IntoIterator<Item = u32>,
+) -> HashMap<u32, String> {
+    let mut by_file: HashMap<u16, Vec<(u32, u32)>> = HashMap::new();
+    for id in symbol_ids {
+        if let Some(symbol) = index.symbol(id) {
+            by_file
+                .entry(symbol.file_id)
+                .or_default()
+                .push((id, symbol.start_line));
+        }
+    }
+
+    let mut dates = HashMap::new();
+    for (file_id, symbols) in by_file {
+        let Some(file) = index.file_path(file_id) else {
+            continue;
+        };
+        let Some(lines) = blame_dates(root, file) else {
+            continue;
+        };
+        for (id, line) in symbols {
+            if let Some(date) = lines.get(&line) {
+                dates.insert(id, date.clone());
+            }
+        }
+    }
+    dates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_porcelain_dates_lines_by_commit() {
+        // Line 3 reuses the first commit, whose headers are not repeated
+        let output = "\
+1111111111111111111111111111111111111111 1 1 1
+author Ada
+author-time 1600000000
+committer-time 1600000000
+filename src/lib.rs
+\tfn old() {}
+2222222222222222222222222222222222222222 1 2 1
+author Bob
+committer-time 1700000000
+summary Add new
+filename src/lib.rs
+\tfn new() {}
+1111111111111111111111111111111111111111 2 3
+\t}
+";
+        let dates = parse_porcelain(output);
+        assert_eq!(dates.len(), 3);
+        assert_eq!(dates[&1], "2020-09-13");
+        assert_eq!(dates[&2], "2023-11-14");
+        assert_eq!(dates[&3], "2020-09-13");
+    }
+}
@@ -9,15 +9,17 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 use rayon::prelude::*;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
-use super::extract::{extract_file, ExtractedCall, ExtractedData, ExtractedSymbol};
+use super::extract::{extract_file, ExtractedCall, ExtractedData, ExtractedRef, ExtractedSymbol};
+use super::imports::ImportResolver;
 use super::index::SemanticIndex;
-use super::storage::{save_index, trace_index_path};
+use super::storage::{index_file_size, save_index, trace_index_path};
 use super::types::{
     Edge, RefKind, Reference, Scope, ScopeKind, Symbol, SymbolFlags, SymbolKind, Token, TokenKind,
 };
-use crate::core::error::Result;
+use crate::core::error::{Error, Result};
+use crate::index::TantivyIndex;
 
 // =============================================================================
 // BUILDER
@@ -37,6 +39,8 @@ pub struct SemanticIndexBuilder {
     next_scope_id: u32,
     /// Project root for relative paths
     project_root: PathBuf,
+    /// Import statements, resolved once every file is known
+    pending_imports: Vec<(u16, ExtractedRef)>,
 }
 
 impl SemanticIndexBuilder {
@@ -49,6 +53,7 @@ impl SemanticIndexBuilder {
             next_token_id: 0,
             next_scope_id: 0,
             project_root: project_root.as_ref().to_path_buf(),
+            pending_imports: Vec::new(),
         }
     }
 
@@ -75,6 +80,7 @@ impl SemanticIndexBuilder {
             next_token_id: 0,
             next_scope_id: 0,
             project_root: project_root.as_ref().to_path_buf(),
+            pending_imports: Vec::new(),
         }
     }
 
@@ -129,9 +135,34 @@ impl SemanticIndexBuilder {
         // Add construction references
         for ref_item in &data.references {
             self.add_construction_reference(file_id, ref_item);
+            self.queue_import(file_id, ref_item);
         }
     }
 
+    /// Remember an import statement for resolution in `build`
+    fn queue_import(&mut self, file_id: u16, extracted: &ExtractedRef) {
+        if extracted.kind == super::extract::RefKind::Import {
+            self.pending_imports.push((file_id, extracted.clone()));
+        }
+    }
+
+    /// Resolve queued imports to Import references between files
+    pub fn resolve_imports(&mut self) {
+        let pending = std::mem::take(&mut self.pending_imports);
+        let resolver = ImportResolver::new(&self.index);
+        let linked: usize = pending
+            .iter()
+            .map(|(file_id, import)| resolver.link(&mut self.index, *file_id, import))
+            .sum();
+        self.next_token_id = self.index.next_token_id();
+
+        debug!(
+            imports = pending.len(),
+            references = linked,
+            "Resolved imports"
+        );
+    }
+
     /// Add a construction reference to the index
     fn add_construction_reference(
         &mut self,
@@ -209,10 +240,13 @@ impl SemanticIndexBuilder {
         if extracted.is_async {
             flags |= SymbolFlags::IS_ASYNC;
         }
+        if extracted.is_deprecated {
+            flags |= SymbolFlags::IS_DEPRECATED;
+        }
 
         // Detect entry points using multiple heuristics
-        let is_entry_point =
-            self.detect_entry_point(&extracted.name, kind, extracted.is_exported, file_path);
+        let is_entry_point = extracted.is_entry_point
+            || self.detect_entry_point(&extracted.name, kind, extracted.is_exported, file_path);
         if is_entry_point {
             flags |= SymbolFlags::IS_ENTRY_POINT;
         }
@@ -225,7 +259,12 @@ impl SemanticIndexBuilder {
             flags,
             extracted.start_line,
             extracted.end_line,
-        );
+        )
+        .with_visibility(extracted.visibility);
+        let symbol = match &extracted.parent_symbol {
+            Some(parent) => symbol.with_parent(self.index.strings.intern(parent)),
+            None => symbol,
+        };
 
         self.index.add_symbol(symbol, &extracted.name);
 
@@ -321,6 +360,8 @@ impl SemanticIndexBuilder {
 
         let kind = match extracted.kind {
             super::extract::TokenKind::Identifier => TokenKind::Identifier,
+            super::extract::TokenKind::Parameter => TokenKind::Parameter,
+            super::extract::TokenKind::Variable => TokenKind::Variable,
             // Keywords, Operators, Literals, Comments map to Unknown (no semantic index equivalent)
             super::extract::TokenKind::Keyword => TokenKind::Unknown,
             super::extract::TokenKind::Operator => TokenKind::Unknown,
@@ -363,7 +404,10 @@ impl SemanticIndexBuilder {
     }
 
     /// Add a scope to the index
-    fn add_scope(&mut self, file_id: u16, extracted: &super::extract::ExtractedScope, _idx: usize) {
+    ///
+    /// `idx` is the scope's position in its file's extracted scopes, which
+    /// `parent_index` refers to.
+    fn add_scope(&mut self, file_id: u16, extracted: &super::extract::ExtractedScope, idx: usize) {
         let id = self.next_scope_id;
         self.next_scope_id += 1;
 
@@ -378,7 +422,12 @@ impl SemanticIndexBuilder {
             super::extract::ScopeKind::Conditional => ScopeKind::Block,
         };
 
-        let parent_id = extracted.parent_index.map(|i| i as u32).unwrap_or(u32::MAX);
+        // Parent indexes are file-local; the file's scopes were assigned consecutive ids
+        let first_id = id - idx as u32;
+        let parent_id = extracted
+            .parent_index
+            .map(|i| first_id + i as u32)
+            .unwrap_or(u32::MAX);
         let name_offset = extracted
             .name
             .as_ref()
@@ -471,6 +520,7 @@ impl SemanticIndexBuilder {
     /// Build the final index
     pub fn build(mut self) -> SemanticIndex {
         self.resolve_edges();
+        self.resolve_imports();
         self.index
     }
 
@@ -483,12 +533,13 @@ impl SemanticIndexBuilder {
             scopes: self.index.scopes.len(),
             edges: self.index.edges.len(),
             references: self.index.references.len(),
+            parse_fallbacks: Vec::new(),
         }
     }
 }
 
 /// Statistics about the build process
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct BuildStats {
     pub files: usize,
     pub symbols: usize,
@@ -496,6 +547,9 @@ pub struct BuildStats {
     pub scopes: usize,
     pub edges: usize,
     pub references: usize,
+    /// Files in a tree-sitter language that failed to parse and were
+    /// extracted by regex instead (relative to the project root)
+    pub parse_fallbacks: Vec<PathBuf>,
 }
 
 impl std::fmt::Display for BuildStats {
@@ -514,21 +568,31 @@ impl std::fmt::Display for BuildStats {
 
 /// Build a SemanticIndex from a list of files in parallel
 pub fn build_index_parallel(project_root: &Path, files: &[(PathBuf, String)]) -> SemanticIndex {
+    build_from_extractions(project_root, &extract_files(files))
+}
+
+/// Extract all files in parallel
+fn extract_files(files: &[(PathBuf, String)]) -> Vec<(PathBuf, ExtractedData)> {
     info!(files = files.len(), "Building semantic index");
 
-    // Extract all files in parallel
-    let extractions: Vec<(PathBuf, ExtractedData)> = files
+    files
         .par_iter()
         .map(|(path, content)| {
             let data = extract_file(path, content, None);
             (path.clone(), data)
         })
-        .collect();
+        .collect()
+}
 
+/// Build an index from extracted files
+fn build_from_extractions(
+    project_root: &Path,
+    extractions: &[(PathBuf, ExtractedData)],
+) -> SemanticIndex {
     // Build index sequentially (index is not thread-safe)
-    let mut builder = SemanticIndexBuilder::with_capacity(project_root, files.len());
+    let mut builder = SemanticIndexBuilder::with_capacity(project_root, extractions.len());
 
-    for (path, data) in &extractions {
+    for (path, data) in extractions {
         if !data.is_empty() {
             // Re-add using the builder's method which handles all the details
             let rel_path = path
@@ -560,6 +624,7 @@ pub fn build_index_parallel(project_root: &Path, files: &[(PathBuf, String)]) ->
             // Add construction references
             for ref_item in &data.references {
                 builder.add_construction_reference(file_id, ref_item);
+                builder.queue_import(file_id, ref_item);
             }
         }
     }
@@ -570,14 +635,83 @@ pub fn build_index_parallel(project_root: &Path, files: &[(PathBuf, String)]) ->
     builder.build()
 }
 
+/// Share of `max_index_bytes` past which a build warns
+const INDEX_SIZE_WARN_RATIO: f64 = 0.8;
+
+/// Check the total size of a project's indexes against `[index] max_index_bytes`
+///
+/// `size` counts the text index directory and the trace index together.
+/// Callers check before writing, so an oversized index fails with a clear
+/// error and leaves the previous one intact instead of dying on a full disk.
+pub fn check_index_size(size: u64, max_bytes: Option<u64>) -> Result<()> {
+    let Some(max_bytes) = max_bytes else {
+        return Ok(());
+    };
+    if size > max_bytes {
+        return Err(Error::IndexError {
+            message: format!(
+                "indexes would take {} bytes, over the [index] max_index_bytes cap of {}; \
+                 raise the cap or index fewer files",
+                size, max_bytes
+            ),
+        });
+    }
+    if size as f64 >= max_bytes as f64 * INDEX_SIZE_WARN_RATIO {
+        warn!(
+            size,
+            max_bytes, "Trace index is approaching the max_index_bytes cap"
+        );
+    }
+    Ok(())
+}
+
 /// Build and save a semantic index for a project
+///
+/// Fails without writing when the text index directory plus the new trace
+/// index would exceed `max_index_bytes`.
 pub fn build_and_save_index(
     project_root: &Path,
     files: &[(PathBuf, String)],
+    max_index_bytes: Option<u64>,
 ) -> Result<BuildStats> {
-    let index = build_index_parallel(project_root, files);
+    let (index, stats) = build_project_index(project_root, files);
+    let text_bytes = TantivyIndex::disk_size(project_root)?;
+    check_index_size(text_bytes + index_file_size(&index), max_index_bytes)?;
+    save_project_index(project_root, &index)?;
+    Ok(stats)
+}
+
+/// Build a semantic index for a project in memory, without writing it
+pub fn build_project_index(
+    project_root: &Path,
+    files: &[(PathBuf, String)],
+) -> (SemanticIndex, BuildStats) {
+    let extractions = extract_files(files);
+    let parse_fallbacks: Vec<PathBuf> = extractions
+        .iter()
+        .filter(|(_, data)| data.fallback_reason.is_some())
+        .map(|(path, _)| {
+            path.strip_prefix(project_root)
+                .unwrap_or(path)
+                .to_path_buf()
+        })
+        .collect();
+    let index = build_from_extractions(project_root, &extractions);
     let stats = index.stats();
+    let stats = BuildStats {
+        files: stats.files,
+        symbols: stats.symbols,
+        tokens: stats.tokens,
+        scopes: stats.scopes,
+        edges: stats.edges,
+        references: stats.references,
+        parse_fallbacks,
+    };
+    (index, stats)
+}
 
+/// Save a project's semantic index to its trace index path
+pub fn save_project_index(project_root: &Path, index: &SemanticIndex) -> Result<()> {
     let path = trace_index_path(project_root);
 
     // Ensure directory exists
@@ -585,24 +719,16 @@ pub fn build_and_save_index(
         std::fs::create_dir_all(parent)?;
     }
 
-    save_index(&index, &path)?;
+    save_index(index, &path)?;
 
     info!(
         path = %path.display(),
-        symbols = stats.symbols,
-        tokens = stats.tokens,
-        edges = stats.edges,
+        symbols = index.symbols.len(),
+        tokens = index.tokens.len(),
+        edges = index.edges.len(),
         "Saved semantic index"
     );
-
-    Ok(BuildStats {
-        files: stats.files,
-        symbols: stats.symbols,
-        tokens: stats.tokens,
-        scopes: stats.scopes,
-        edges: stats.edges,
-        references: stats.references,
-    })
+    Ok(())
 }
 
 // =============================================================================
@@ -733,10 +859,13 @@ pub fn update_file_incremental(
         if sym.is_async {
             flags |= SymbolFlags::IS_ASYNC;
         }
+        if sym.is_deprecated {
+            flags |= SymbolFlags::IS_DEPRECATED;
+        }
 
         // Detect entry points using the same logic as the builder
-        let is_entry_point =
-            detect_entry_point_standalone(&sym.name, kind, sym.is_exported, Some(&rel_path));
+        let is_entry_point = sym.is_entry_point
+            || detect_entry_point_standalone(&sym.name, kind, sym.is_exported, Some(&rel_path));
         if is_entry_point {
             flags |= SymbolFlags::IS_ENTRY_POINT;
         }
@@ -749,7 +878,12 @@ pub fn update_file_incremental(
             flags,
             sym.start_line,
             sym.end_line,
-        );
+        )
+        .with_visibility(sym.visibility);
+        let symbol = match &sym.parent_symbol {
+            Some(parent) => symbol.with_parent(index.strings.intern(parent)),
+            None => symbol,
+        };
 
         index.add_symbol(symbol, &sym.name);
         new_symbol_ids.push(id);
@@ -763,6 +897,8 @@ pub fn update_file_incremental(
 
         let kind = match tok.kind {
             super::extract::TokenKind::Identifier => TokenKind::Identifier,
+            super::extract::TokenKind::Parameter => TokenKind::Parameter,
+            super::extract::TokenKind::Variable => TokenKind::Variable,
             // Keywords, Operators, Literals, Comments map to Unknown (no semantic index equivalent)
             super::extract::TokenKind::Keyword => TokenKind::Unknown,
             super::extract::TokenKind::Operator => TokenKind::Unknown,
@@ -775,7 +911,8 @@ pub fn update_file_incremental(
         index.add_token(token, &tok.name);
     }
 
-    // Add scopes
+    // Add scopes (parent indexes are relative to the file's first scope)
+    let first_scope_id = index.next_scope_id();
     for scope in data.scopes.iter() {
         let id = index.next_scope_id();
         let kind = match scope.kind {
@@ -789,7 +926,10 @@ pub fn update_file_incremental(
             super::extract::ScopeKind::Conditional => ScopeKind::Block,
         };
 
-        let parent_id = scope.parent_index.map(|i| i as u32).unwrap_or(u32::MAX);
+        let parent_id = scope
+            .parent_index
+            .map(|i| first_scope_id + i as u32)
+            .unwrap_or(u32::MAX);
         let name_offset = scope
             .name
             .as_ref()
@@ -885,6 +1025,18 @@ pub fn update_file_incremental(
         }
     }
 
+    // Add import references (every other file is already indexed)
+    if data
+        .references
+        .iter()
+        .any(|r| r.kind == super::extract::RefKind::Import)
+    {
+        let resolver = ImportResolver::new(index);
+        for ref_item in &data.references {
+            resolver.link(index, file_id, ref_item);
+        }
+    }
+
     let elapsed_ms = start.elapsed().as_secs_f64() * 1000.0;
 
     debug!(
@@ -1065,6 +1217,64 @@ function main() {
         assert!(index.symbols_by_name("main").is_some(), "Should find main");
     }
 
+    #[test]
+    fn test_build_and_save_aborts_over_max_index_bytes() {
+        let dir = tempdir().unwrap();
+        let files = vec![(
+            dir.path().join("lib.rs"),
+            "fn helper() {}\nfn main() {\n    helper();\n}\n".to_string(),
+        )];
+
+        let err = build_and_save_index(dir.path(), &files, Some(64)).unwrap_err();
+        assert!(err.to_string().contains("max_index_bytes"), "{}", err);
+        // Nothing was written
+        assert!(!trace_index_path(dir.path()).exists());
+
+        // The size estimate is exact, so the same cap at full size passes
+        let size = index_file_size(&build_index_parallel(dir.path(), &files));
+        build_and_save_index(dir.path(), &files, Some(size)).unwrap();
+        let written = std::fs::metadata(trace_index_path(dir.path()))
+            .unwrap()
+            .len();
+        assert_eq!(written, size);
+    }
+
+    #[test]
+    fn test_qualified_name_resolves_to_parent_method() {
+        let dir = tempdir().unwrap();
+        let mut builder = SemanticIndexBuilder::new(dir.path());
+
+        let code = r#"
+pub struct Server;
+pub struct Client;
+
+impl Server {
+    pub fn handle(&self) {}
+}
+
+impl Client {
+    pub fn handle(&self) {}
+}
+"#;
+
+        builder.add_file(&dir.path().join("net.rs"), code);
+        let index = builder.build();
+
+        assert_eq!(index.resolve_symbols("handle").len(), 2);
+
+        let ids = index.resolve_symbols("Server::handle");
+        assert_eq!(ids.len(), 1);
+        let sym = index.symbol(ids[0]).unwrap();
+        assert_eq!(index.symbol_parent(sym), Some("Server"));
+        assert_eq!(sym.start_line, 6);
+
+        assert_eq!(
+            index.resolve_symbols("net::Server.handle").as_slice(),
+            &ids[..]
+        );
+        assert!(index.resolve_symbols("Other::handle").is_empty());
+    }
+
     #[test]
     fn test_builder_call_resolution() {
         let dir = tempdir().unwrap();
@@ -1098,6 +1308,54 @@ function main() {
         assert!(callees.contains(&helper_ids[0]), "main should call helper");
     }
 
+    #[test]
+    fn test_language_entry_points() {
+        let dir = tempdir().unwrap();
+        let mut builder = SemanticIndexBuilder::new(dir.path());
+
+        let rust = r#"
+fn main() {
+    run();
+}
+
+fn run() {}
+
+#[test]
+fn parses_config() {
+    run();
+}
+"#;
+        let python = r#"
+@app.route("/health")
+def health():
+    return "ok"
+
+def cli():
+    pass
+
+def unused():
+    pass
+
+if __name__ == "__main__":
+    cli()
+"#;
+        builder.add_file(&dir.path().join("src/app.rs"), rust);
+        builder.add_file(&dir.path().join("src/server.py"), python);
+        let index = builder.build();
+
+        let is_entry = |name: &str| {
+            let ids = index.symbols_by_name(name).unwrap();
+            index.symbol(ids[0]).unwrap().is_entry_point()
+        };
+        assert!(is_entry("main"));
+        assert!(is_entry("parses_config"));
+        assert!(!is_entry("run"));
+
+        assert!(is_entry("health"));
+        assert!(is_entry("cli"));
+        assert!(!is_entry("unused"));
+    }
+
     #[test]
     fn test_incremental_update_add_file() {
         let dir = tempdir().unwrap();
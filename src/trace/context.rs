@@ -78,12 +78,16 @@ impl CodeContext {
 pub struct FileCache {
     /// Cached file contents (path -> lines)
     cache: HashMap<PathBuf, Vec<String>>,
+    /// Byte offset of each line start (path -> offsets), kept alongside `cache`
+    line_starts: HashMap<PathBuf, Vec<u32>>,
     /// Total bytes cached (approximate)
     bytes_cached: usize,
     /// Maximum bytes to cache
     max_bytes: usize,
     /// Project root for resolving relative paths
     project_root: PathBuf,
+    /// Files read from disk so far
+    disk_reads: usize,
 }
 
 impl FileCache {
@@ -96,9 +100,11 @@ impl FileCache {
     pub fn with_capacity(project_root: impl AsRef<Path>, max_bytes: usize) -> Self {
         Self {
             cache: HashMap::new(),
+            line_starts: HashMap::new(),
             bytes_cached: 0,
             max_bytes,
             project_root: project_root.as_ref().to_path_buf(),
+            disk_reads: 0,
         }
     }
 
@@ -118,6 +124,7 @@ impl FileCache {
         if !self.cache.contains_key(&resolved) {
             // Try to load the file
             let content = fs::read_to_string(&resolved).ok()?;
+            self.disk_reads += 1;
             let bytes = content.len();
 
             // Evict if needed
@@ -127,18 +134,37 @@ impl FileCache {
                     if let Some(lines) = self.cache.remove(&key) {
                         self.bytes_cached -= lines.iter().map(|l| l.len()).sum::<usize>();
                     }
+                    self.line_starts.remove(&key);
                 }
             }
 
             // Parse into lines
             let lines: Vec<String> = content.lines().map(|s| s.to_string()).collect();
+            // Offsets come from the raw content so CRLF endings are counted
+            let line_starts: Vec<u32> = std::iter::once(0)
+                .chain(content.match_indices('\n').map(|(i, _)| i as u32 + 1))
+                .collect();
             self.bytes_cached += bytes;
+            self.line_starts.insert(resolved.clone(), line_starts);
             self.cache.insert(resolved.clone(), lines);
         }
 
         self.cache.get(&resolved)
     }
 
+    /// Load a file ahead of use if it fits without evicting anything
+    ///
+    /// Returns whether the file is now cached.
+    pub fn preload(&mut self, path: &Path) -> bool {
+        let resolved = self.resolve_path(path);
+        if self.cache.contains_key(&resolved) {
+            return true;
+        }
+        let fits = fs::metadata(&resolved)
+            .is_ok_and(|m| self.bytes_cached as u64 + m.len() <= self.max_bytes as u64);
+        fits && self.ensure_loaded(path).is_some()
+    }
+
     /// Get a single line from a file (1-indexed)
     pub fn get_line(&mut self, path: &Path, line: u32) -> Option<String> {
         let lines = self.ensure_loaded(path)?;
@@ -146,6 +172,14 @@ impl FileCache {
         lines.get(idx).cloned()
     }
 
+    /// Byte offset of a 1-indexed line and 0-indexed byte column
+    pub fn byte_offset(&mut self, path: &Path, line: u32, column: u16) -> Option<u32> {
+        self.ensure_loaded(path)?;
+        let starts = self.line_starts.get(&self.resolve_path(path))?;
+        let start = *starts.get(line.checked_sub(1)? as usize)?;
+        Some(start + column as u32)
+    }
+
     /// Get multiple lines as a range (1-indexed, inclusive)
     pub fn get_range(&mut self, path: &Path, start: u32, end: u32) -> Option<Vec<String>> {
         let lines = self.ensure_loaded(path)?;
@@ -268,6 +302,11 @@ impl FileCache {
         Some(lines[start..=end.min(lines.len() - 1)].to_vec())
     }
 
+    /// Project root that relative paths are resolved against
+    pub fn project_root(&self) -> &Path {
+        &self.project_root
+    }
+
     /// Check if a file exists and is readable
     pub fn file_exists(&self, path: &Path) -> bool {
         let resolved = self.resolve_path(path);
@@ -282,6 +321,7 @@ impl FileCache {
     /// Clear the cache
     pub fn clear(&mut self) {
         self.cache.clear();
+        self.line_starts.clear();
         self.bytes_cached = 0;
     }
 
@@ -291,6 +331,7 @@ impl FileCache {
             files_cached: self.cache.len(),
             bytes_cached: self.bytes_cached,
             max_bytes: self.max_bytes,
+            disk_reads: self.disk_reads,
         }
     }
 }
@@ -301,6 +342,8 @@ pub struct CacheStats {
     pub files_cached: usize,
     pub bytes_cached: usize,
     pub max_bytes: usize,
+    /// Files read from disk, including reads of evicted files
+    pub disk_reads: usize,
 }
 
 // =============================================================================
@@ -468,6 +511,19 @@ mod tests {
         assert_eq!(range, vec!["b", "c", "d"]);
     }
 
+    #[test]
+    fn test_byte_offset_counts_line_endings() {
+        let dir = TempDir::new().unwrap();
+        let path = create_test_file(&dir, "test.rs", "ab\r\ncd\nef");
+
+        let mut cache = FileCache::new(dir.path());
+
+        assert_eq!(cache.byte_offset(&path, 1, 1), Some(1));
+        assert_eq!(cache.byte_offset(&path, 2, 0), Some(4));
+        assert_eq!(cache.byte_offset(&path, 3, 1), Some(8));
+        assert_eq!(cache.byte_offset(&path, 4, 0), None);
+    }
+
     #[test]
     fn test_context_format() {
         let ctx = CodeContext {
@@ -483,6 +539,22 @@ mod tests {
         assert!(formatted.contains("let x = 42;"));
     }
 
+    #[test]
+    fn test_context_line_number_gutter_marks_target() {
+        let ctx = CodeContext {
+            line: "    let x = 42;".to_string(),
+            before: vec!["".to_string(), "fn main() {".to_string()],
+            after: vec!["}".to_string()],
+            line_number: 11,
+            column: Some(8),
+        };
+
+        assert_eq!(
+            ctx.format(false),
+            "     9: \n    10: fn main() {\n>   11:     let x = 42;\n    12: }\n"
+        );
+    }
+
     #[test]
     fn test_cache_eviction() {
         let dir = TempDir::new().unwrap();
@@ -0,0 +1,453 @@
+//! JSON Lines Export / Import
+//!
+//! Serializes a SemanticIndex to a portable JSON Lines document and
+//! reconstructs it again, so CI-produced indexes can be analyzed on a
+//! machine that never had the source.
+//!
+//! Document layout (one record per line, tagged by `type`):
+//! - header: format version
+//! - string: string table entries in offset order
+//! - file: indexed file paths (index = file_id)
+//! - symbol, token, reference, scope, edge: primary data
+//!
+//! @module trace/export
+
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::index::SemanticIndex;
+use super::types::{
+    Edge, RefKind, Reference, Scope, ScopeKind, Symbol, SymbolFlags, SymbolKind, Token, TokenKind,
+    Visibility,
+};
+use crate::core::error::{Error, Result};
+
+// =============================================================================
+// CONSTANTS
+// =============================================================================
+
+/// Current export document version
+const EXPORT_VERSION: u32 = 1;
+
+// =============================================================================
+// RECORDS
+// =============================================================================
+
+/// A single line of the JSON Lines export document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExportRecord {
+    Header {
+        version: u32,
+    },
+    String {
+        value: String,
+    },
+    File {
+        id: u16,
+        path: String,
+    },
+    Symbol {
+        id: u32,
+        name: String,
+        file_id: u16,
+        kind: SymbolKind,
+        flags: SymbolFlags,
+        start_line: u32,
+        end_line: u32,
+        /// Enclosing type name (absent in documents written before it existed)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        parent: Option<String>,
+        /// Absent in older documents, where it follows the exported flag
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        visibility: Option<Visibility>,
+    },
+    Token {
+        id: u32,
+        name: String,
+        file_id: u16,
+        line: u32,
+        column: u16,
+        kind: TokenKind,
+        scope_id: u32,
+    },
+    Reference {
+        token_id: u32,
+        symbol_id: u32,
+        kind: RefKind,
+    },
+    Scope {
+        id: u32,
+        kind: ScopeKind,
+        file_id: u16,
+        parent_id: u32,
+        start_line: u32,
+        end_line: u32,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+    },
+    Edge {
+        from: u32,
+        to: u32,
+        line: u32,
+    },
+}
+
+// =============================================================================
+// EXPORT
+// =============================================================================
+
+/// Write a SemanticIndex as a JSON Lines document
+///
+/// Returns the number of records written.
+pub fn export_index<W: Write>(index: &SemanticIndex, writer: &mut W) -> Result<usize> {
+    export_index_with_paths(index, writer, |path| path.to_string_lossy().to_string())
+}
+
+/// Write a SemanticIndex as a JSON Lines document, writing each file path
+/// as `path_text` renders it (e.g. redacted for display)
+///
+/// Returns the number of records written.
+pub fn export_index_with_paths<W: Write>(
+    index: &SemanticIndex,
+    writer: &mut W,
+    path_text: impl Fn(&Path) -> String,
+) -> Result<usize> {
+    let mut count = 0;
+    let mut emit = |record: ExportRecord, writer: &mut W| -> Result<()> {
+        serde_json::to_writer(&mut *writer, &record)?;
+        writer.write_all(b"\n")?;
+        count += 1;
+        Ok(())
+    };
+
+    emit(
+        ExportRecord::Header {
+            version: EXPORT_VERSION,
+        },
+        writer,
+    )?;
+
+    // Strings first, in offset order, so re-interning reproduces the same offsets
+    for value in string_table_entries(index) {
+        emit(ExportRecord::String { value }, writer)?;
+    }
+
+    for (id, path) in index.files.iter().enumerate() {
+        emit(
+            ExportRecord::File {
+                id: id as u16,
+                path: path_text(path),
+            },
+            writer,
+        )?;
+    }
+
+    for symbol in &index.symbols {
+        emit(
+            ExportRecord::Symbol {
+                id: symbol.id,
+                name: index.symbol_name(symbol).unwrap_or_default().to_string(),
+                file_id: symbol.file_id,
+                kind: symbol.symbol_kind(),
+                flags: symbol.symbol_flags(),
+                start_line: symbol.start_line,
+                end_line: symbol.end_line,
+                parent: index.symbol_parent(symbol).map(str::to_string),
+                visibility: Some(symbol.visibility()),
+            },
+            writer,
+        )?;
+    }
+
+    for token in &index.tokens {
+        emit(
+            ExportRecord::Token {
+                id: token.id,
+                name: index.token_name(token).unwrap_or_default().to_string(),
+                file_id: token.file_id,
+                line: token.line,
+                column: token.column,
+                kind: token.token_kind(),
+                scope_id: token.scope_id,
+            },
+            writer,
+        )?;
+    }
+
+    for reference in &index.references {
+        emit(
+            ExportRecord::Reference {
+                token_id: reference.token_id,
+                symbol_id: reference.symbol_id,
+                kind: reference.ref_kind(),
+            },
+            writer,
+        )?;
+    }
+
+    for scope in &index.scopes {
+        emit(
+            ExportRecord::Scope {
+                id: scope.id,
+                kind: scope.scope_kind(),
+                file_id: scope.file_id,
+                parent_id: scope.parent_id,
+                start_line: scope.start_line,
+                end_line: scope.end_line,
+                name: index.strings.get(scope.name_offset).map(String::from),
+            },
+            writer,
+        )?;
+    }
+
+    for edge in &index.edges {
+        emit(
+            ExportRecord::Edge {
+                from: edge.from_symbol,
+                to: edge.to_symbol,
+                line: edge.line,
+            },
+            writer,
+        )?;
+    }
+
+    Ok(count)
+}
+
+/// Collect string table entries in offset order
+fn string_table_entries(index: &SemanticIndex) -> Vec<String> {
+    let mut entries: Vec<String> = index
+        .strings
+        .as_bytes()
+        .split(|&b| b == 0)
+        .map(|s| String::from_utf8_lossy(s).to_string())
+        .collect();
+    // Drop the empty segment after the final null terminator
+    entries.pop();
+    entries
+}
+
+// =============================================================================
+// IMPORT
+// =============================================================================
+
+/// Reconstruct a SemanticIndex from a JSON Lines document
+pub fn import_index<R: BufRead>(reader: R) -> Result<SemanticIndex> {
+    let mut index = SemanticIndex::new();
+    let mut seen_header = false;
+
+    for (line_no, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let record: ExportRecord = serde_json::from_str(&line).map_err(|e| Error::IndexError {
+            message: format!("Invalid export record on line {}: {}", line_no + 1, e),
+        })?;
+
+        match record {
+            ExportRecord::Header { version } => {
+                if version != EXPORT_VERSION {
+                    return Err(Error::IndexError {
+                        message: format!(
+                            "Unsupported export version {} (expected {})",
+                            version, EXPORT_VERSION
+                        ),
+                    });
+                }
+                seen_header = true;
+            }
+            _ if !seen_header => {
+                return Err(Error::IndexError {
+                    message: "Export document is missing its header record".into(),
+                });
+            }
+            ExportRecord::String { value } => {
+                index.strings.intern(&value);
+            }
+            ExportRecord::File { path, .. } => {
+                index.add_file(path.into());
+            }
+            ExportRecord::Symbol {
+                id,
+                name,
+                file_id,
+                kind,
+                flags,
+                start_line,
+                end_line,
+                parent,
+                visibility,
+            } => {
+                let name_offset = index.strings.intern(&name);
+                let mut symbol =
+                    Symbol::new(id, name_offset, file_id, kind, flags, start_line, end_line);
+                if let Some(visibility) = visibility {
+                    symbol = symbol.with_visibility(visibility);
+                }
+                let symbol = match parent {
+                    Some(parent) => symbol.with_parent(index.strings.intern(&parent)),
+                    None => symbol,
+                };
+                index.add_symbol(symbol, &name);
+            }
+            ExportRecord::Token {
+                id,
+                name,
+                file_id,
+                line,
+                column,
+                kind,
+                scope_id,
+            } => {
+                let name_offset = index.strings.intern(&name);
+                index.add_token(
+                    Token::new(id, name_offset, file_id, line, column, kind, scope_id),
+                    &name,
+                );
+            }
+            ExportRecord::Reference {
+                token_id,
+                symbol_id,
+                kind,
+            } => {
+                index.add_reference(Reference::new(token_id, symbol_id, kind));
+            }
+            ExportRecord::Scope {
+                id,
+                kind,
+                file_id,
+                parent_id,
+                start_line,
+                end_line,
+                name,
+            } => {
+                let name_offset = name.map(|n| index.strings.intern(&n)).unwrap_or(0);
+                index.add_scope(Scope::new(
+                    id,
+                    kind,
+                    file_id,
+                    parent_id,
+                    start_line,
+                    end_line,
+                    name_offset,
+                ));
+            }
+            ExportRecord::Edge { from, to, line } => {
+                index.add_edge(Edge::new(from, to, line));
+            }
+        }
+    }
+
+    if !seen_header {
+        return Err(Error::IndexError {
+            message: "Export document is empty".into(),
+        });
+    }
+
+    // Lookups were built incrementally, but rebuild to match a freshly loaded index
+    index.rebuild_lookups();
+
+    Ok(index)
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace::storage::{load_index, save_index};
+    use tempfile::tempdir;
+
+    fn create_test_index() -> SemanticIndex {
+        let mut index = SemanticIndex::new();
+        let file_id = index.add_file("src/main.rs".into());
+        let lib_id = index.add_file("src/lib.rs".into());
+
+        let main = index.strings.intern("main");
+        let helper = index.strings.intern("helper");
+
+        index.add_symbol(
+            Symbol::new(
+                0,
+                main,
+                file_id,
+                SymbolKind::Function,
+                SymbolFlags::IS_ENTRY_POINT | SymbolFlags::IS_EXPORTED,
+                1,
+                10,
+            ),
+            "main",
+        );
+        index.add_symbol(
+            Symbol::new(
+                1,
+                helper,
+                lib_id,
+                SymbolKind::Method,
+                SymbolFlags::IS_ASYNC,
+                3,
+                8,
+            ),
+            "helper",
+        );
+
+        index.add_token(
+            Token::new(0, helper, file_id, 5, 4, TokenKind::Call, 1),
+            "helper",
+        );
+        index.add_reference(Reference::new(0, 1, RefKind::Call));
+        index.add_scope(Scope::file_scope(0, file_id, 12));
+        index.add_scope(Scope::new(1, ScopeKind::Function, file_id, 0, 1, 10, main));
+        index.add_edge(Edge::new(0, 1, 5));
+
+        index
+    }
+
+    #[test]
+    fn test_export_import_roundtrip() {
+        let original = create_test_index();
+
+        let mut buf = Vec::new();
+        let written = export_index(&original, &mut buf).unwrap();
+        assert_eq!(buf.iter().filter(|&&b| b == b'\n').count(), written);
+
+        let imported = import_index(buf.as_slice()).unwrap();
+
+        let (a, b) = (original.stats(), imported.stats());
+        assert_eq!(a.symbols, b.symbols);
+        assert_eq!(a.tokens, b.tokens);
+        assert_eq!(a.references, b.references);
+        assert_eq!(a.scopes, b.scopes);
+        assert_eq!(a.edges, b.edges);
+        assert_eq!(a.files, b.files);
+        assert_eq!(a.entry_points, b.entry_points);
+        assert_eq!(a.string_bytes, b.string_bytes);
+        assert_eq!(a.unique_names, b.unique_names);
+
+        assert_eq!(imported.symbols, original.symbols);
+        assert_eq!(imported.callers(1), &[0]);
+        assert_eq!(imported.references_to(1).count(), 1);
+
+        // Survives a trip through the binary format as well
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("trace.idx");
+        save_index(&imported, &path).unwrap();
+        let loaded = load_index(&path).unwrap();
+        assert_eq!(loaded.stats().symbols, a.symbols);
+        assert!(loaded.symbols_by_name("helper").is_some());
+    }
+
+    #[test]
+    fn test_import_rejects_missing_header() {
+        let doc = r#"{"type":"file","id":0,"path":"a.rs"}"#;
+        assert!(import_index(doc.as_bytes()).is_err());
+        assert!(import_index("".as_bytes()).is_err());
+    }
+}
@@ -9,8 +9,15 @@
 pub mod regex;
 pub mod treesitter;
 
+use crate::parse::notebook;
+use std::collections::HashSet;
 use std::path::Path;
 
+use ::regex::Regex;
+use once_cell::sync::Lazy;
+
+pub use super::types::Visibility;
+
 // =============================================================================
 // EXTRACTED TYPES
 // =============================================================================
@@ -107,6 +114,10 @@ impl ScopeKind {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TokenKind {
     Identifier,
+    /// Identifier declaring a function parameter
+    Parameter,
+    /// Identifier declaring a block-scoped local variable
+    Variable,
     Keyword,
     Operator,
     Literal,
@@ -118,6 +129,8 @@ impl TokenKind {
     pub fn as_str(&self) -> &'static str {
         match self {
             Self::Identifier => "identifier",
+            Self::Parameter => "parameter",
+            Self::Variable => "variable",
             Self::Keyword => "keyword",
             Self::Operator => "operator",
             Self::Literal => "literal",
@@ -141,7 +154,14 @@ pub struct ExtractedSymbol {
     pub start_column: u16,
     pub end_column: u16,
     pub is_exported: bool,
+    /// Visibility from modifiers (`pub(crate)`, `protected`) or naming
+    /// convention (Go capitalization, Python `_` prefix)
+    pub visibility: Visibility,
     pub is_async: bool,
+    pub is_deprecated: bool,
+    /// Entry point by a language convention visible only in source
+    /// (test attributes, route decorators, `__main__` guards)
+    pub is_entry_point: bool,
     pub parent_symbol: Option<String>,
 }
 
@@ -199,6 +219,8 @@ pub struct ExtractedData {
     pub tokens: Vec<ExtractedToken>,
     pub language: String,
     pub extraction_method: ExtractionMethod,
+    /// Why tree-sitter failed, when a supported language fell back to regex
+    pub fallback_reason: Option<String>,
 }
 
 /// Method used for extraction
@@ -278,6 +300,45 @@ impl std::fmt::Display for ExtractError {
 
 impl std::error::Error for ExtractError {}
 
+// =============================================================================
+// COMMENT AND STRING SPANS
+// =============================================================================
+
+/// A source range: 1-based start line and byte column, end exclusive
+pub type Span = ((u32, u16), (u32, u16));
+
+/// Comment and string literal ranges of a file
+///
+/// Used to tell name matches in prose or quoted text apart from code. Spans
+/// are sorted and never overlap; interpolations (`${x}`, `{x}`) are left out
+/// of the strings that contain them.
+#[derive(Debug, Clone, Default)]
+pub struct LiteralSpans {
+    spans: Vec<Span>,
+}
+
+impl LiteralSpans {
+    /// Find the comments and strings of a file, by AST where tree-sitter
+    /// supports the language and lexically otherwise
+    pub fn parse(content: &str, language: &str) -> Self {
+        let spans = if is_treesitter_supported(language) {
+            treesitter::literal_spans(content, language)
+        } else {
+            None
+        };
+        Self {
+            spans: spans.unwrap_or_else(|| regex::literal_spans(content, language)),
+        }
+    }
+
+    /// Whether the position lies inside a comment or string literal
+    pub fn contains(&self, line: u32, column: u16) -> bool {
+        let pos = (line, column);
+        let after = self.spans.partition_point(|(start, _)| *start <= pos);
+        after > 0 && pos < self.spans[after - 1].1
+    }
+}
+
 // =============================================================================
 // LANGUAGE DETECTION
 // =============================================================================
@@ -342,6 +403,9 @@ pub fn is_treesitter_supported(language: &str) -> bool {
 /// Tree-sitter provides more accurate extraction with full AST access.
 /// Regex extraction is a best-effort fallback with lower confidence.
 ///
+/// Jupyter notebooks are reduced to their code cells first (see
+/// `notebook::code_source`), in the language of the first cell.
+///
 /// # Arguments
 /// * `path` - Path to the source file (used for language detection)
 /// * `content` - Source code content
@@ -350,19 +414,37 @@ pub fn is_treesitter_supported(language: &str) -> bool {
 /// # Returns
 /// ExtractedData containing all extracted information
 pub fn extract_file(path: &Path, content: &str, language: Option<&str>) -> ExtractedData {
-    let detected_lang = language.unwrap_or_else(|| detect_language(path));
+    if notebook::is_notebook(path) {
+        let cells = notebook::code_cells(content);
+        let source = notebook::code_source(&cells);
+        let cell_lang = cells.first().map_or("python", |c| c.language.as_str());
+        return extract_source(path, &source, language.unwrap_or(cell_lang));
+    }
 
+    extract_source(
+        path,
+        content,
+        language.unwrap_or_else(|| detect_language(path)),
+    )
+}
+
+/// Extract from source text already known to be in `detected_lang`
+fn extract_source(path: &Path, content: &str, detected_lang: &str) -> ExtractedData {
     // Skip empty files
     if content.trim().is_empty() {
         return ExtractedData::empty(detected_lang);
     }
 
     // Try tree-sitter first for supported languages
+    let mut fallback_reason = None;
     if is_treesitter_supported(detected_lang) {
         match treesitter::extract(content, detected_lang) {
             Ok(mut data) => {
                 data.language = detected_lang.to_string();
                 data.extraction_method = ExtractionMethod::TreeSitter;
+                regex::extract_imports_for_language(content, detected_lang, &mut data);
+                mark_deprecated_symbols(content, &mut data);
+                mark_entry_points(content, &mut data);
                 return data;
             }
             Err(e) => {
@@ -372,6 +454,7 @@ pub fn extract_file(path: &Path, content: &str, language: Option<&str>) -> Extra
                     path.display(),
                     e
                 );
+                fallback_reason = Some(e.to_string());
             }
         }
     }
@@ -380,9 +463,227 @@ pub fn extract_file(path: &Path, content: &str, language: Option<&str>) -> Extra
     let mut data = regex::extract(content, detected_lang);
     data.language = detected_lang.to_string();
     data.extraction_method = ExtractionMethod::Regex;
+    data.fallback_reason = fallback_reason;
+    mark_deprecated_symbols(content, &mut data);
+    mark_entry_points(content, &mut data);
     data
 }
 
+// =============================================================================
+// DEPRECATION DETECTION
+// =============================================================================
+
+/// Maximum number of attribute/comment lines scanned above a symbol
+const DEPRECATION_LOOKBACK: usize = 20;
+
+/// Flag symbols marked deprecated by an attribute, decorator, or doc comment.
+///
+/// Scans the symbol's first line and the contiguous block of attribute,
+/// decorator, and comment lines directly above it. Works the same for every
+/// language since the markers (`#[deprecated]`, `@deprecated`, `@Deprecated`,
+/// `[Obsolete]`, `// Deprecated:`) don't collide.
+fn mark_deprecated_symbols(content: &str, data: &mut ExtractedData) {
+    let lines: Vec<&str> = content.lines().collect();
+
+    for symbol in &mut data.symbols {
+        // start_line is 1-based
+        let first = symbol.start_line as usize;
+        if first == 0 || first > lines.len() {
+            continue;
+        }
+
+        if is_deprecation_marker(lines[first - 1]) {
+            symbol.is_deprecated = true;
+            continue;
+        }
+
+        symbol.is_deprecated = lines[..first - 1]
+            .iter()
+            .rev()
+            .take(DEPRECATION_LOOKBACK)
+            .take_while(|line| is_symbol_prelude(line))
+            .any(|line| is_deprecation_marker(line));
+    }
+}
+
+/// Check if a line can precede a symbol as part of its attributes or docs
+fn is_symbol_prelude(line: &str) -> bool {
+    let line = line.trim_start();
+    ["#", "@", "//", "/*", "*", "[", "--"]
+        .iter()
+        .any(|prefix| line.starts_with(prefix))
+}
+
+/// Check if a line carries a deprecation marker
+fn is_deprecation_marker(line: &str) -> bool {
+    let line = line.trim().to_ascii_lowercase();
+    if line.contains("#[deprecated") || line.contains("@deprecated") || line.contains("[obsolete") {
+        return true;
+    }
+    // Go-style doc comment: "// Deprecated: use X instead"
+    line.trim_start_matches(['/', '*', '#', '-', ' '])
+        .starts_with("deprecated:")
+}
+
+// =============================================================================
+// ENTRY POINT DETECTION
+// =============================================================================
+
+/// Rust attributes whose last path segment marks an entry point
+/// (`#[test]`, `#[tokio::main]`, rocket/actix route macros)
+const RUST_ENTRY_ATTRIBUTES: &[&str] = &[
+    "test", "main", "bench", "get", "post", "put", "delete", "patch", "head", "route",
+];
+
+/// Python decorators whose last dotted segment marks an entry point
+/// (`@app.route`, `@router.get`, `@click.command`, `@pytest.fixture`)
+const PYTHON_ENTRY_DECORATORS: &[&str] = &[
+    "route",
+    "get",
+    "post",
+    "put",
+    "delete",
+    "patch",
+    "websocket",
+    "command",
+    "group",
+    "task",
+    "fixture",
+];
+
+/// Express-style route registration: `app.get(`, `router.post(`, ...
+static JS_ROUTE_REGISTRATION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b(?:app|router|server|api)\.(?:get|post|put|delete|patch|all|use|route)\s*\(")
+        .unwrap()
+});
+
+static JS_STRING_LITERAL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"'[^']*'|"[^"]*"|`[^`]*`"#).unwrap());
+
+static JS_IDENTIFIER: Lazy<Regex> = Lazy::new(|| Regex::new(r"[A-Za-z_$][\w$]*").unwrap());
+
+/// Flag functions that are entry points by a language-specific convention.
+///
+/// Complements the name and path heuristics in the index builder with
+/// markers only visible in source: Rust test/main/route attributes, Python
+/// route decorators and `if __name__ == "__main__"` calls, JS functions
+/// registered as route handlers, and Go `main`/`init`/`TestXxx` functions.
+fn mark_entry_points(content: &str, data: &mut ExtractedData) {
+    let lines: Vec<&str> = content.lines().collect();
+    let handlers = match data.language.as_str() {
+        "python" => python_main_guard_calls(&lines, &data.calls),
+        "javascript" | "typescript" => js_route_handlers(&lines),
+        _ => HashSet::new(),
+    };
+
+    for symbol in &mut data.symbols {
+        if !matches!(symbol.kind, SymbolKind::Function | SymbolKind::Method) {
+            continue;
+        }
+        let first = symbol.start_line as usize;
+        if first == 0 || first > lines.len() {
+            continue;
+        }
+
+        // The symbol's own first line plus its attribute/decorator block
+        let mut prelude = std::iter::once(lines[first - 1]).chain(
+            lines[..first - 1]
+                .iter()
+                .rev()
+                .take(DEPRECATION_LOOKBACK)
+                .take_while(|line| is_symbol_prelude(line))
+                .copied(),
+        );
+
+        symbol.is_entry_point = match data.language.as_str() {
+            "rust" => prelude.any(|line| annotation_matches(line, "#[", RUST_ENTRY_ATTRIBUTES)),
+            "python" => {
+                handlers.contains(&symbol.name)
+                    || prelude.any(|line| annotation_matches(line, "@", PYTHON_ENTRY_DECORATORS))
+            }
+            "javascript" | "typescript" => handlers.contains(&symbol.name),
+            "go" => is_go_entry_name(&symbol.name),
+            _ => false,
+        };
+    }
+}
+
+/// Check if a line is an attribute/decorator whose last path segment is listed
+fn annotation_matches(line: &str, opener: &str, names: &[&str]) -> bool {
+    let Some(rest) = line.trim().strip_prefix(opener) else {
+        return false;
+    };
+    let path = rest
+        .split(|c: char| c == '(' || c == ']' || c.is_whitespace())
+        .next()
+        .unwrap_or("");
+    let last = path.rsplit([':', '.']).next().unwrap_or("");
+    names.contains(&last)
+}
+
+/// Names called inside a Python `if __name__ == "__main__":` block
+fn python_main_guard_calls(lines: &[&str], calls: &[ExtractedCall]) -> HashSet<String> {
+    let mut names = HashSet::new();
+
+    for (idx, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        if !(trimmed.starts_with("if __name__") && trimmed.contains("__main__")) {
+            continue;
+        }
+        let indent = line.len() - trimmed.len();
+
+        // The block runs until the next non-blank line at the guard's indentation
+        let end = lines[idx + 1..]
+            .iter()
+            .position(|l| !l.trim().is_empty() && l.len() - l.trim_start().len() <= indent)
+            .map_or(lines.len(), |offset| idx + 1 + offset);
+
+        // Call lines are 1-based; the guard is line idx + 1
+        let range = (idx as u32 + 2)..=(end as u32);
+        names.extend(
+            calls
+                .iter()
+                .filter(|call| range.contains(&call.line))
+                .map(|call| call.callee_name.clone()),
+        );
+    }
+
+    names
+}
+
+/// Identifiers passed to Express-style route registrations
+fn js_route_handlers(lines: &[&str]) -> HashSet<String> {
+    let mut names = HashSet::new();
+
+    for line in lines {
+        let Some(registration) = JS_ROUTE_REGISTRATION.find(line) else {
+            continue;
+        };
+        // Drop route paths so `'/users'` doesn't mark a `users` function
+        let args = JS_STRING_LITERAL.replace_all(&line[registration.end()..], "");
+        names.extend(
+            JS_IDENTIFIER
+                .find_iter(&args)
+                .map(|m| m.as_str().to_string()),
+        );
+    }
+
+    names
+}
+
+/// Go runs `main`, `init`, and `TestXxx`/`BenchmarkXxx`/`ExampleXxx`/`FuzzXxx`
+fn is_go_entry_name(name: &str) -> bool {
+    if name == "main" || name == "init" {
+        return true;
+    }
+    ["Test", "Benchmark", "Example", "Fuzz"]
+        .iter()
+        .any(|prefix| {
+            name.strip_prefix(prefix)
+                .is_some_and(|rest| rest.chars().next().map_or(true, |c| !c.is_lowercase()))
+        })
+}
+
 // =============================================================================
 // TESTS
 // =============================================================================
@@ -402,6 +703,28 @@ mod tests {
         assert_eq!(detect_language(Path::new("foo.xyz")), "unknown");
     }
 
+    #[test]
+    fn test_extract_notebook_code_cells() {
+        let notebook = r##"{
+            "metadata": {"kernelspec": {"language": "python"}},
+            "cells": [
+                {"cell_type": "code", "source": ["def load(path):\n", "    return open(path)\n"]},
+                {"cell_type": "markdown", "source": ["def not_code():"]},
+                {"cell_type": "code", "source": "class Model:\n    pass\n"}
+            ]
+        }"##;
+
+        let data = extract_file(Path::new("analysis.ipynb"), notebook, None);
+        assert_eq!(data.language, "python");
+        assert_eq!(data.extraction_method, ExtractionMethod::TreeSitter);
+        let symbols: Vec<_> = data
+            .symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.start_line))
+            .collect();
+        assert_eq!(symbols, vec![("load", 1), ("Model", 4)]);
+    }
+
     #[test]
     fn test_is_treesitter_supported() {
         assert!(is_treesitter_supported("typescript"));
@@ -413,6 +736,48 @@ mod tests {
         assert!(!is_treesitter_supported("unknown"));
     }
 
+    #[test]
+    fn test_mark_deprecated_symbols() {
+        let code = r#"
+#[deprecated(since = "1.2.0", note = "use new_api")]
+#[inline]
+pub fn old_api() {}
+
+/// Still supported
+pub fn new_api() {}
+
+// Deprecated: use NewClient.
+func OldClient() {}
+"#;
+        let mut data = ExtractedData::empty("rust");
+        for (name, line) in [("old_api", 4), ("new_api", 7), ("OldClient", 10)] {
+            data.symbols.push(ExtractedSymbol {
+                name: name.to_string(),
+                kind: SymbolKind::Function,
+                start_line: line,
+                end_line: line,
+                start_column: 0,
+                end_column: 0,
+                is_exported: true,
+                visibility: Visibility::Public,
+                is_async: false,
+                is_deprecated: false,
+                is_entry_point: false,
+                parent_symbol: None,
+            });
+        }
+
+        mark_deprecated_symbols(code, &mut data);
+
+        let deprecated: Vec<_> = data
+            .symbols
+            .iter()
+            .filter(|s| s.is_deprecated)
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(deprecated, vec!["old_api", "OldClient"]);
+    }
+
     #[test]
     fn test_extracted_data_empty() {
         let data = ExtractedData::empty("rust");
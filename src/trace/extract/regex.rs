@@ -14,7 +14,7 @@
 
 use super::{
     ExtractedCall, ExtractedData, ExtractedRef, ExtractedScope, ExtractedSymbol, ExtractedToken,
-    ExtractionMethod, RefKind, ScopeKind, SymbolKind, TokenKind,
+    ExtractionMethod, RefKind, ScopeKind, Span, SymbolKind, TokenKind, Visibility,
 };
 use once_cell::sync::Lazy;
 use regex::Regex;
@@ -295,7 +295,6 @@ struct ClassPattern {
 
 struct ImportPattern {
     regex: Regex,
-    #[allow(dead_code)]
     language: &'static str,
 }
 
@@ -384,7 +383,10 @@ fn extract_functions(content: &str, data: &mut ExtractedData) {
                     start_column: column,
                     end_column: 0,
                     is_exported,
+                    visibility: modifier_visibility(full_match, is_exported),
                     is_async: pattern.is_async || full_match.contains("async"),
+                    is_deprecated: false,
+                    is_entry_point: false,
                     parent_symbol: None,
                 });
             }
@@ -446,7 +448,10 @@ fn extract_classes(content: &str, data: &mut ExtractedData) {
                     start_column: column,
                     end_column: 0,
                     is_exported,
+                    visibility: modifier_visibility(full_match, is_exported),
                     is_async: false,
+                    is_deprecated: false,
+                    is_entry_point: false,
                     parent_symbol: None,
                 });
             }
@@ -534,7 +539,27 @@ fn extract_calls(content: &str, language: &str, data: &mut ExtractedData) {
 
 /// Extract import statements
 fn extract_imports(content: &str, data: &mut ExtractedData) {
-    for pattern in IMPORT_PATTERNS.iter() {
+    extract_imports_matching(content, data, |_| true);
+}
+
+/// Extract imports using only the patterns for `language`
+///
+/// Used alongside tree-sitter extraction, which does not record imports.
+pub fn extract_imports_for_language(content: &str, language: &str, data: &mut ExtractedData) {
+    let family = match language {
+        "typescript" => "javascript",
+        "cpp" => "c",
+        other => other,
+    };
+    extract_imports_matching(content, data, |pattern| pattern.language == family);
+}
+
+fn extract_imports_matching(
+    content: &str,
+    data: &mut ExtractedData,
+    include: impl Fn(&ImportPattern) -> bool,
+) {
+    for pattern in IMPORT_PATTERNS.iter().filter(|p| include(p)) {
         for caps in pattern.regex.captures_iter(content) {
             // Try to get the module name from capture group 1 or 2
             let module = caps.get(1).or(caps.get(2));
@@ -611,10 +636,153 @@ fn extract_tokens(content: &str, data: &mut ExtractedData) {
     }
 }
 
+// =============================================================================
+// COMMENT AND STRING SPANS
+// =============================================================================
+
+/// Line comment markers by language
+fn line_comment_markers(language: &str) -> &'static [&'static str] {
+    match language {
+        "python" | "ruby" | "bash" | "yaml" | "toml" | "elixir" => &["#"],
+        "php" => &["//", "#"],
+        "sql" | "lua" | "haskell" => &["--"],
+        "erlang" => &["%"],
+        "clojure" => &[";"],
+        "css" | "ocaml" | "html" | "markdown" | "json" => &[],
+        _ => &["//"],
+    }
+}
+
+/// Block comment delimiters by language
+fn block_comment_delimiters(language: &str) -> Option<(&'static str, &'static str)> {
+    match language {
+        "python" | "ruby" | "bash" | "yaml" | "toml" | "elixir" | "erlang" | "clojure" | "json"
+        | "markdown" => None,
+        "lua" => Some(("--[[", "]]")),
+        "haskell" => Some(("{-", "-}")),
+        "ocaml" => Some(("(*", "*)")),
+        "html" | "vue" | "svelte" => Some(("<!--", "-->")),
+        _ => Some(("/*", "*/")),
+    }
+}
+
+/// Comment and string spans found by scanning for each language's markers
+///
+/// Quotes are `"`, `'` and `` ` ``, except `'` where it is not a string
+/// delimiter (Haskell, OCaml, Clojure, Verilog). Backslash escapes are
+/// honored inside strings.
+pub fn literal_spans(content: &str, language: &str) -> Vec<Span> {
+    let line_markers = line_comment_markers(language);
+    let block = block_comment_delimiters(language);
+    let single_quote = !matches!(language, "haskell" | "ocaml" | "clojure" | "verilog");
+
+    let bytes = content.as_bytes();
+    let mut spans = Vec::new();
+    let (mut line, mut line_start) = (1u32, 0usize);
+    let mut i = 0;
+
+    // Position of byte `at`, given the start of the line it is on
+    let pos = |line: u32, line_start: usize, at: usize| (line, (at - line_start) as u16);
+
+    while i < bytes.len() {
+        let rest = &bytes[i..];
+        if bytes[i] == b'\n' {
+            line += 1;
+            line_start = i + 1;
+            i += 1;
+            continue;
+        }
+
+        // Block comments first: Lua's `--[[` also starts with the line marker
+        if let Some((open, close)) = block.filter(|(open, _)| rest.starts_with(open.as_bytes())) {
+            let start = pos(line, line_start, i);
+            let body = i + open.len();
+            let end = bytes[body..]
+                .windows(close.len())
+                .position(|w| w == close.as_bytes())
+                .map_or(bytes.len(), |at| body + at + close.len());
+            for (at, _) in bytes[i..end]
+                .iter()
+                .enumerate()
+                .filter(|(_, &b)| b == b'\n')
+            {
+                line += 1;
+                line_start = i + at + 1;
+            }
+            spans.push((start, pos(line, line_start, end)));
+            i = end;
+            continue;
+        }
+
+        if line_markers.iter().any(|m| rest.starts_with(m.as_bytes())) {
+            let end = rest
+                .iter()
+                .position(|&b| b == b'\n')
+                .map_or(bytes.len(), |at| i + at);
+            spans.push((pos(line, line_start, i), pos(line, line_start, end)));
+            i = end;
+            continue;
+        }
+
+        let quote = bytes[i];
+        if quote == b'"' || quote == b'`' || (quote == b'\'' && single_quote) {
+            let start = pos(line, line_start, i);
+            i += 1;
+            while i < bytes.len() && bytes[i] != quote {
+                if bytes[i] == b'\\' {
+                    i += 1;
+                } else if bytes[i] == b'\n' {
+                    // Only backtick strings span lines; an open quote ends here
+                    if quote != b'`' {
+                        break;
+                    }
+                }
+                if bytes.get(i) == Some(&b'\n') {
+                    line += 1;
+                    line_start = i + 1;
+                }
+                i += 1;
+            }
+            if i < bytes.len() && bytes[i] == quote {
+                i += 1;
+            }
+            spans.push((start, pos(line, line_start, i.min(bytes.len()))));
+            continue;
+        }
+
+        i += 1;
+    }
+
+    spans
+}
+
 // =============================================================================
 // HELPERS
 // =============================================================================
 
+/// Visibility from the modifiers in a matched declaration, falling back to
+/// public for exported names and private otherwise
+fn modifier_visibility(declaration: &str, is_exported: bool) -> Visibility {
+    if declaration.contains("pub(self)") {
+        return Visibility::Private;
+    }
+    if declaration.contains("pub(") {
+        return Visibility::Internal;
+    }
+    let words: Vec<&str> = declaration.split_whitespace().collect();
+    if words.contains(&"private") {
+        Visibility::Private
+    } else if words.contains(&"protected") {
+        Visibility::Protected
+    } else if words.contains(&"internal") {
+        Visibility::Internal
+    } else if words.contains(&"public") || is_exported {
+        Visibility::Public
+    } else {
+        Visibility::Private
+    }
+}
+
 /// Estimate the end line of a function/class definition
 fn estimate_end_line(lines: &[&str], start_line: usize) -> usize {
     if start_line == 0 || start_line > lines.len() {
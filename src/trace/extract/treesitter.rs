@@ -14,8 +14,10 @@
 
 use super::{
     ExtractError, ExtractedCall, ExtractedData, ExtractedRef, ExtractedScope, ExtractedSymbol,
-    ExtractedToken, ExtractionMethod, RefKind, ScopeKind, SymbolKind, TokenKind,
+    ExtractedToken, ExtractionMethod, RefKind, ScopeKind, Span, SymbolKind, TokenKind, Visibility,
 };
+use once_cell::sync::Lazy;
+use regex::Regex;
 use streaming_iterator::StreamingIterator;
 use tree_sitter::{Language, Parser, Query, QueryCursor, Tree};
 
@@ -161,6 +163,19 @@ pub fn extract(content: &str, language: &str) -> Result<ExtractedData, ExtractEr
             message: "Parser returned None".to_string(),
         })?;
 
+    // Error recovery always produces a tree; one that is mostly errors is
+    // worse than the regex fallback
+    let error_share = error_bytes(tree.root_node()) as f64 / content.len().max(1) as f64;
+    if error_share > MAX_ERROR_SHARE {
+        return Err(ExtractError::ParseFailed {
+            language: language.to_string(),
+            message: format!(
+                "syntax errors cover {:.0}% of the file",
+                error_share * 100.0
+            ),
+        });
+    }
+
     let mut data = ExtractedData {
         language: language.to_string(),
         extraction_method: ExtractionMethod::TreeSitter,
@@ -194,6 +209,22 @@ pub fn extract(content: &str, language: &str) -> Result<ExtractedData, ExtractEr
     Ok(data)
 }
 
+/// Share of a file's bytes inside syntax errors above which it counts as unparseable
+const MAX_ERROR_SHARE: f64 = 0.5;
+
+/// Bytes covered by the outermost ERROR nodes under `node`
+fn error_bytes(node: tree_sitter::Node) -> usize {
+    if node.is_error() {
+        return node.byte_range().len();
+    }
+    if !node.has_error() {
+        return 0;
+    }
+    let mut cursor = node.walk();
+    let total = node.children(&mut cursor).map(error_bytes).sum();
+    total
+}
+
 // =============================================================================
 // EXTRACTION HELPERS
 // =============================================================================
@@ -227,6 +258,7 @@ fn extract_functions(
         let mut end_line = 0u32;
         let mut start_col = 0u16;
         let mut end_col = 0u16;
+        let mut definition = None;
 
         for capture in m.captures {
             let node = capture.node;
@@ -239,16 +271,19 @@ fn extract_functions(
                     start_col = node.start_position().column as u16;
                 }
                 "function" => {
+                    definition = Some(node);
                     end_line = node.end_position().row as u32 + 1;
                     end_col = node.end_position().column as u16;
                 }
                 "method" => {
                     is_method = true;
+                    definition = Some(node);
                     end_line = node.end_position().row as u32 + 1;
                     end_col = node.end_position().column as u16;
                 }
                 "export_function" => {
                     is_exported = true;
+                    definition = Some(node);
                     end_line = node.end_position().row as u32 + 1;
                     end_col = node.end_position().column as u16;
                 }
@@ -259,6 +294,7 @@ fn extract_functions(
         if let Some(name) = name {
             // Skip test/private helpers (simple heuristic)
             if !name.starts_with('_') || language == "python" {
+                let visibility = definition_visibility(definition, &name, language, source);
                 data.symbols.push(ExtractedSymbol {
                     name,
                     kind: if is_method {
@@ -271,8 +307,11 @@ fn extract_functions(
                     start_column: start_col,
                     end_column: end_col,
                     is_exported,
+                    visibility,
                     is_async: false,
-                    parent_symbol: None,
+                    is_deprecated: false,
+                    is_entry_point: false,
+                    parent_symbol: definition.and_then(|n| enclosing_type_name(n, source)),
                 });
             }
         }
@@ -281,6 +320,137 @@ fn extract_functions(
     Ok(())
 }
 
+/// Visibility of a definition, from its modifiers or the language's
+/// naming convention
+fn definition_visibility(
+    definition: Option<tree_sitter::Node>,
+    name: &str,
+    language: &str,
+    source: &[u8],
+) -> Visibility {
+    match language {
+        // Exported names are capitalized; the rest are package-scoped
+        "go" => {
+            if name.starts_with(|c: char| c.is_uppercase()) {
+                Visibility::Public
+            } else {
+                Visibility::Internal
+            }
+        }
+        // `_name` is private by convention; dunders are protocol methods
+        "python" => {
+            if name.starts_with('_') && !(name.starts_with("__") && name.ends_with("__")) {
+                Visibility::Private
+            } else {
+                Visibility::Public
+            }
+        }
+        "rust" => {
+            let Some(node) = definition else {
+                return Visibility::Private;
+            };
+            let mut cursor = node.walk();
+            let modifier = node
+                .children(&mut cursor)
+                .find(|child| child.kind() == "visibility_modifier")
+                .and_then(|m| m.utf8_text(source).ok());
+            match modifier {
+                Some("pub") => Visibility::Public,
+                Some(m) if m.contains("self") => Visibility::Private,
+                Some(_) => Visibility::Internal,
+                // Trait items and trait impl methods are as visible as the trait
+                None if in_trait(node) => Visibility::Public,
+                None => Visibility::Private,
+            }
+        }
+        // JavaScript/TypeScript
+        _ => {
+            let Some(node) = definition else {
+                return Visibility::Private;
+            };
+            if node.kind() == "method_definition" {
+                let mut cursor = node.walk();
+                let modifier = node
+                    .children(&mut cursor)
+                    .find(|child| child.kind() == "accessibility_modifier")
+                    .and_then(|m| m.utf8_text(source).ok());
+                return match modifier {
+                    Some("private") => Visibility::Private,
+                    Some("protected") => Visibility::Protected,
+                    _ if name.starts_with('#') => Visibility::Private,
+                    _ => Visibility::Public,
+                };
+            }
+            let exported = node.kind() == "export_statement"
+                || node
+                    .parent()
+                    .is_some_and(|p| p.kind() == "export_statement");
+            if exported {
+                Visibility::Public
+            } else {
+                Visibility::Private
+            }
+        }
+    }
+}
+
+/// Whether a Rust item is declared in a trait or a trait impl
+fn in_trait(node: tree_sitter::Node) -> bool {
+    let Some(container) = node.parent().and_then(|list| list.parent()) else {
+        return false;
+    };
+    match container.kind() {
+        "trait_item" => true,
+        "impl_item" => container.child_by_field_name("trait").is_some(),
+        _ => false,
+    }
+}
+
+/// Name of the class, impl or trait a function definition is nested in
+fn enclosing_type_name(node: tree_sitter::Node, source: &[u8]) -> Option<String> {
+    // Go methods name their type in the receiver: `func (s *Server) handle()`
+    if node.kind() == "method_declaration" {
+        let receiver = node.child_by_field_name("receiver")?;
+        return first_of_kind(receiver, "type_identifier")
+            .and_then(|n| n.utf8_text(source).ok())
+            .map(str::to_string);
+    }
+
+    let mut current = node.parent();
+    while let Some(ancestor) = current {
+        let name_node = match ancestor.kind() {
+            // `impl Server`, `impl<T> Handler for Server<T>`: the implementing type
+            "impl_item" => ancestor
+                .child_by_field_name("type")
+                .map(|ty| ty.child_by_field_name("type").unwrap_or(ty)),
+            "class_declaration"
+            | "abstract_class_declaration"
+            | "class_definition"
+            | "class"
+            | "trait_item"
+            | "interface_declaration" => ancestor.child_by_field_name("name"),
+            _ => None,
+        };
+        if let Some(name_node) = name_node {
+            return name_node.utf8_text(source).ok().map(str::to_string);
+        }
+        current = ancestor.parent();
+    }
+    None
+}
+
+/// First descendant of `node` (depth-first) with the given kind
+fn first_of_kind<'a>(node: tree_sitter::Node<'a>, kind: &str) -> Option<tree_sitter::Node<'a>> {
+    if node.kind() == kind {
+        return Some(node);
+    }
+    let mut cursor = node.walk();
+    let children: Vec<_> = node.children(&mut cursor).collect();
+    children
+        .into_iter()
+        .find_map(|child| first_of_kind(child, kind))
+}
+
 /// Extract class, struct, enum, trait definitions
 fn extract_classes(
     tree: &Tree,
@@ -305,6 +475,7 @@ fn extract_classes(
     while let Some(m) = matches.next() {
         let mut name: Option<String> = None;
         let mut kind = SymbolKind::Class;
+        let mut definition = None;
         let mut start_line = 0u32;
         let mut end_line = 0u32;
         let mut start_col = 0u16;
@@ -348,9 +519,13 @@ fn extract_classes(
                 // Note: impl blocks are NOT extracted - see comment in class_query()
                 _ => {}
             }
+            if capture_name != "name" {
+                definition = Some(node);
+            }
         }
 
         if let Some(name) = name {
+            let visibility = definition_visibility(definition, &name, language, source);
             data.symbols.push(ExtractedSymbol {
                 name,
                 kind,
@@ -359,7 +534,10 @@ fn extract_classes(
                 start_column: start_col,
                 end_column: end_col,
                 is_exported: false,
+                visibility,
                 is_async: false,
+                is_deprecated: false,
+                is_entry_point: false,
                 parent_symbol: None,
             });
         }
@@ -646,12 +824,21 @@ fn extract_tokens(tree: &Tree, source: &[u8], data: &mut ExtractedData) {
             _ => None,
         };
 
+        // Parameter and variable names are declarations, not reads
+        let token_kind = match token_kind {
+            Some(TokenKind::Identifier) if is_parameter_name(node) => Some(TokenKind::Parameter),
+            Some(TokenKind::Identifier) if is_variable_name(node) => Some(TokenKind::Variable),
+            other => other,
+        };
+
         if let Some(kind) = token_kind {
             if let Ok(text) = node.utf8_text(source) {
                 // Skip very short identifiers and common noise
-                if (kind == TokenKind::Identifier && text.len() >= 2)
-                    || kind != TokenKind::Identifier
-                {
+                let is_name = matches!(
+                    kind,
+                    TokenKind::Identifier | TokenKind::Parameter | TokenKind::Variable
+                );
+                if !is_name || text.len() >= 2 {
                     tokens.push(ExtractedToken {
                         name: text.to_string(),
                         kind,
@@ -676,6 +863,51 @@ fn extract_tokens(tree: &Tree, source: &[u8], data: &mut ExtractedData) {
 // HELPERS
 // =============================================================================
 
+/// Check if an identifier node is the name of a function parameter
+fn is_parameter_name(node: tree_sitter::Node) -> bool {
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+    let is_field = |field: &str| parent.child_by_field_name(field) == Some(node);
+
+    match parent.kind() {
+        // JS `(a, b)` and Python `(a, b)` list bare identifiers
+        "formal_parameters" | "parameters" => true,
+        // TypeScript and Rust put the name in `pattern`
+        "required_parameter" | "optional_parameter" | "parameter" => is_field("pattern"),
+        // Python `a=1` / `a: int = 1`, Go `a int`
+        "default_parameter" | "typed_default_parameter" | "parameter_declaration" => {
+            is_field("name")
+        }
+        // Python `a: int` - the name is the unfielded identifier
+        "typed_parameter" => !is_field("type"),
+        _ => false,
+    }
+}
+
+/// Check if an identifier node is the name bound by a local variable declaration
+///
+/// Only block-scoped declarations count: Python assignments are skipped
+/// because they may rebind an existing name rather than declare one.
+fn is_variable_name(node: tree_sitter::Node) -> bool {
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+    let is_field = |field: &str| parent.child_by_field_name(field) == Some(node);
+
+    match parent.kind() {
+        // Rust `let x = ...`
+        "let_declaration" => is_field("pattern"),
+        // JS/TS `const x = ...`, Go `var x int`
+        "variable_declarator" | "var_spec" | "const_spec" => is_field("name"),
+        // Go `x, err := ...` puts the names in an expression list
+        "expression_list" => parent.parent().is_some_and(|p| {
+            p.kind() == "short_var_declaration" && p.child_by_field_name("left") == Some(parent)
+        }),
+        _ => false,
+    }
+}
+
 /// Check if a name is a common built-in that should be filtered
 fn is_common_builtin(name: &str, language: &str) -> bool {
     match language {
@@ -775,6 +1007,377 @@ fn is_common_builtin(name: &str, language: &str) -> bool {
     }
 }
 
+// =============================================================================
+// STATEMENT SPANS
+// =============================================================================
+
+/// Statements longer than this fall back to line-based context
+const MAX_STATEMENT_LINES: u32 = 25;
+
+/// Nodes whose children are statements
+const STATEMENT_CONTAINERS: &[&str] = &[
+    "program",
+    "source_file",
+    "module",
+    "block",
+    "statement_block",
+    "class_body",
+    "declaration_list",
+    "switch_case",
+    "switch_default",
+    "expression_case",
+    "default_case",
+];
+
+/// Argument list node kinds across the supported grammars
+const ARGUMENT_LISTS: &[&str] = &["arguments", "argument_list"];
+
+/// Call node kinds across the supported grammars
+const CALLS: &[&str] = &["call_expression", "call"];
+
+/// Variable declaration node kinds whose `type` field holds the declared type
+const VARIABLE_DECLARATIONS: &[&str] = &[
+    "let_declaration",
+    "variable_declarator",
+    "var_spec",
+    "const_spec",
+];
+
+/// A reference that is passed as an argument to a call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArgumentPosition {
+    /// Name of the called function or method
+    pub callee: String,
+    /// 0-based position among the call's arguments
+    pub index: usize,
+}
+
+/// Parsed file for snapping references to their enclosing statement
+pub struct StatementLocator {
+    tree: Tree,
+    source: String,
+}
+
+impl StatementLocator {
+    /// Parse a file, or `None` for languages without tree-sitter support
+    pub fn parse(content: &str, language: &str) -> Option<Self> {
+        let mut parser = get_parser(language).ok()?;
+        let tree = parser.parse(content, None)?;
+        Some(Self {
+            tree,
+            source: content.to_string(),
+        })
+    }
+
+    /// The call argument that contains the identifier at `line`/`column`
+    ///
+    /// `save(user, cache.get(key))` gives `save`/0 for `user` and `get`/0 for
+    /// `key`. Returns `None` when the position is the callee itself or is not
+    /// inside an argument list within its statement.
+    pub fn argument_at(&self, line: u32, column: u16) -> Option<ArgumentPosition> {
+        let point = tree_sitter::Point {
+            row: line.checked_sub(1)? as usize,
+            column: column as usize,
+        };
+        let mut node = self
+            .tree
+            .root_node()
+            .descendant_for_point_range(point, point)?;
+
+        loop {
+            let parent = node.parent()?;
+            if STATEMENT_CONTAINERS.contains(&parent.kind()) {
+                return None;
+            }
+            if CALLS.contains(&parent.kind()) {
+                // Reached a call from its callee rather than its arguments
+                return None;
+            }
+            if ARGUMENT_LISTS.contains(&parent.kind()) {
+                let call = parent.parent().filter(|c| CALLS.contains(&c.kind()))?;
+                let mut cursor = parent.walk();
+                let index = parent
+                    .named_children(&mut cursor)
+                    .filter(|arg| arg.kind() != "comment")
+                    .position(|arg| arg.id() == node.id())?;
+                let callee = self.callee_name(call.child_by_field_name("function")?)?;
+                return Some(ArgumentPosition { callee, index });
+            }
+            node = parent;
+        }
+    }
+
+    /// Bare name of a callee expression (`obj.save` and `db::save` give `save`)
+    fn callee_name(&self, node: tree_sitter::Node) -> Option<String> {
+        let name = match node.kind() {
+            "member_expression" => node.child_by_field_name("property")?,
+            "attribute" => node.child_by_field_name("attribute")?,
+            "field_expression" | "selector_expression" => node.child_by_field_name("field")?,
+            "scoped_identifier" => node.child_by_field_name("name")?,
+            "generic_function" => return self.callee_name(node.child_by_field_name("function")?),
+            _ => node,
+        };
+        name.utf8_text(self.source.as_bytes())
+            .ok()
+            .map(str::to_string)
+    }
+
+    /// Declared type of the variable whose name is at `line`/`column`
+    ///
+    /// `let user: User = ...` and `const user: User = ...` give `User`.
+    /// Returns `None` when the declaration has no type annotation.
+    pub fn declared_type(&self, line: u32, column: u16) -> Option<String> {
+        let point = tree_sitter::Point {
+            row: line.checked_sub(1)? as usize,
+            column: column as usize,
+        };
+        let name = self
+            .tree
+            .root_node()
+            .descendant_for_point_range(point, point)?;
+        let declaration = name
+            .parent()
+            .filter(|p| VARIABLE_DECLARATIONS.contains(&p.kind()))?;
+        let declared = declaration.child_by_field_name("type")?;
+        let text = declared.utf8_text(self.source.as_bytes()).ok()?;
+        // TypeScript annotations include their colon
+        Some(text.trim_start_matches(':').trim().to_string())
+    }
+
+    /// 1-based line span of the statement containing a position
+    ///
+    /// Climbs from the node at `line`/`column` to the child of the nearest
+    /// block-like container. Returns `None` when that statement is too long to
+    /// be a useful snippet (e.g. a whole function body).
+    pub fn span(&self, line: u32, column: u16) -> Option<(u32, u32)> {
+        let point = tree_sitter::Point {
+            row: line.checked_sub(1)? as usize,
+            column: column as usize,
+        };
+        let mut node = self
+            .tree
+            .root_node()
+            .descendant_for_point_range(point, point)?;
+
+        while let Some(parent) = node.parent() {
+            if STATEMENT_CONTAINERS.contains(&parent.kind()) {
+                break;
+            }
+            node = parent;
+        }
+        // A file-level node is not a statement
+        node.parent()?;
+
+        let start = node.start_position().row as u32 + 1;
+        let end = node.end_position().row as u32 + 1;
+        (end - start < MAX_STATEMENT_LINES).then_some((start, end))
+    }
+}
+
+// =============================================================================
+// COMMENT AND STRING SPANS
+// =============================================================================
+
+/// Comment node kinds across the supported grammars
+const COMMENTS: &[&str] = &["comment", "line_comment", "block_comment"];
+
+/// String literal node kinds across the supported grammars
+const STRINGS: &[&str] = &[
+    "string",
+    "string_literal",
+    "raw_string_literal",
+    "char_literal",
+    "template_string",
+    "interpreted_string_literal",
+];
+
+/// Code embedded in a string (`${x}` in JS/TS, `{x}` in Python f-strings)
+const INTERPOLATIONS: &[&str] = &["template_substitution", "interpolation"];
+
+/// Comment and string spans of a file, or `None` if it cannot be parsed
+pub fn literal_spans(content: &str, language: &str) -> Option<Vec<Span>> {
+    fn position(point: tree_sitter::Point) -> (u32, u16) {
+        (point.row as u32 + 1, point.column as u16)
+    }
+
+    fn walk(node: tree_sitter::Node, spans: &mut Vec<Span>) {
+        let kind = node.kind();
+        if COMMENTS.contains(&kind) {
+            spans.push((
+                position(node.start_position()),
+                position(node.end_position()),
+            ));
+            return;
+        }
+
+        let mut cursor = node.walk();
+        if STRINGS.contains(&kind) {
+            let holes: Vec<_> = node
+                .children(&mut cursor)
+                .filter(|child| INTERPOLATIONS.contains(&child.kind()))
+                .collect();
+            // The quoted text around each interpolation is still a string
+            let mut start = position(node.start_position());
+            for hole in holes {
+                spans.push((start, position(hole.start_position())));
+                start = position(hole.end_position());
+            }
+            spans.push((start, position(node.end_position())));
+            return;
+        }
+
+        for child in node.children(&mut cursor) {
+            walk(child, spans);
+        }
+    }
+
+    let mut parser = get_parser(language).ok()?;
+    let tree = parser.parse(content, None)?;
+    let mut spans = Vec::new();
+    walk(tree.root_node(), &mut spans);
+    Some(spans)
+}
+
+// =============================================================================
+// PLACEHOLDER BODIES
+// =============================================================================
+
+/// Statements that only mark a function as unfinished, per language
+///
+/// A function whose body is empty or holds nothing but one of these
+/// (comments and a leading docstring aside) is a stub.
+const STUB_PLACEHOLDERS: &[(&str, &str)] = &[
+    ("rust", r"^(todo|unimplemented)!\s*\(.*\)$"),
+    ("python", r"^(pass|\.\.\.|raise\s+NotImplementedError\b.*)$"),
+    (
+        "typescript",
+        r#"^throw\s+new\s+Error\s*\(\s*["'`](?i:not implemented)["'`]\s*\)$"#,
+    ),
+    (
+        "javascript",
+        r#"^throw\s+new\s+Error\s*\(\s*["'`](?i:not implemented)["'`]\s*\)$"#,
+    ),
+    ("go", r#"^panic\(\s*"(?i:not implemented)"\s*\)$"#),
+];
+
+static STUB_PATTERNS: Lazy<Vec<(&str, Regex)>> = Lazy::new(|| {
+    STUB_PLACEHOLDERS
+        .iter()
+        .map(|(language, pattern)| {
+            let pattern = format!("(?s){}", pattern);
+            (*language, Regex::new(&pattern).expect("valid stub pattern"))
+        })
+        .collect()
+});
+
+/// Function node kinds across the supported grammars
+const FUNCTIONS: &[&str] = &[
+    "function_item",
+    "function_definition",
+    "function_declaration",
+    "method_definition",
+    "method_declaration",
+    "arrow_function",
+];
+
+/// A function whose body does nothing but mark it unfinished
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StubFunction {
+    pub name: String,
+    /// 1-based line of the definition
+    pub line: u32,
+    /// The placeholder statement, or `None` for an empty body
+    pub placeholder: Option<String>,
+}
+
+/// Named functions of a file whose body is empty or a placeholder, or
+/// `None` if the file cannot be parsed
+pub fn stub_functions(content: &str, language: &str) -> Option<Vec<StubFunction>> {
+    fn walk(node: tree_sitter::Node, source: &[u8], language: &str, out: &mut Vec<StubFunction>) {
+        if FUNCTIONS.contains(&node.kind()) {
+            if let Some(stub) = stub_body(node, source, language) {
+                out.push(stub);
+            }
+        }
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            walk(child, source, language, out);
+        }
+    }
+
+    let mut parser = get_parser(language).ok()?;
+    let tree = parser.parse(content, None)?;
+    let mut stubs = Vec::new();
+    walk(tree.root_node(), content.as_bytes(), language, &mut stubs);
+    Some(stubs)
+}
+
+/// The stub a function node is, if its body is empty or a placeholder
+fn stub_body(function: tree_sitter::Node, source: &[u8], language: &str) -> Option<StubFunction> {
+    let name = match function.child_by_field_name("name") {
+        Some(name) => name,
+        // `const handler = () => {}`
+        None => function
+            .parent()
+            .filter(|p| p.kind() == "variable_declarator")?
+            .child_by_field_name("name")?,
+    };
+    let body = function.child_by_field_name("body")?;
+    // Expression-bodied arrow functions return something
+    if body.kind() != "block" && body.kind() != "statement_block" {
+        return None;
+    }
+
+    let mut cursor = body.walk();
+    let mut statements: Vec<_> = body
+        .named_children(&mut cursor)
+        .filter(|n| !COMMENTS.contains(&n.kind()))
+        .collect();
+    // Newer Go grammars wrap a block's statements in a list
+    if let [list] = statements[..] {
+        if list.kind() == "statement_list" {
+            let mut cursor = list.walk();
+            statements = list
+                .named_children(&mut cursor)
+                .filter(|n| !COMMENTS.contains(&n.kind()))
+                .collect();
+        }
+    }
+    // A docstring documents the stub rather than implementing it
+    if language == "python" && statements.len() > 1 && is_docstring(statements[0]) {
+        statements.remove(0);
+    }
+
+    let placeholder = match statements[..] {
+        [] => None,
+        [statement] => {
+            let text = statement.utf8_text(source).ok()?.trim();
+            let text = text.strip_suffix(';').unwrap_or(text).trim_end();
+            let (_, pattern) = STUB_PATTERNS.iter().find(|(l, _)| *l == language)?;
+            if !pattern.is_match(text) {
+                return None;
+            }
+            Some(text.to_string())
+        }
+        _ => return None,
+    };
+
+    Some(StubFunction {
+        name: name.utf8_text(source).ok()?.to_string(),
+        line: function.start_position().row as u32 + 1,
+        placeholder,
+    })
+}
+
+/// Whether a statement is a bare string (a Python docstring)
+fn is_docstring(statement: tree_sitter::Node) -> bool {
+    statement.kind() == "expression_statement"
+        && statement.named_child_count() == 1
+        && statement
+            .named_child(0)
+            .is_some_and(|child| child.kind() == "string")
+}
+
 // =============================================================================
 // TESTS
 // =============================================================================
@@ -841,6 +1444,47 @@ def standalone_function():
         assert!(class_names.contains(&"MyClass"), "Should find MyClass");
     }
 
+    #[test]
+    fn test_extract_rust_visibility() {
+        let code = r#"
+pub fn api() {}
+
+pub(crate) fn shared() {}
+
+pub(super) fn parent_only() {}
+
+fn helper() {}
+
+pub(crate) struct Config;
+
+struct Cache;
+
+impl Default for Cache {
+    fn default() -> Self {
+        Cache
+    }
+}
+"#;
+
+        let result = extract(code, "rust").unwrap();
+        let visibility = |name: &str| {
+            result
+                .symbols
+                .iter()
+                .find(|s| s.name == name)
+                .map(|s| s.visibility)
+                .unwrap_or_else(|| panic!("{} not extracted", name))
+        };
+        assert_eq!(visibility("api"), Visibility::Public);
+        assert_eq!(visibility("shared"), Visibility::Internal);
+        assert_eq!(visibility("parent_only"), Visibility::Internal);
+        assert_eq!(visibility("helper"), Visibility::Private);
+        assert_eq!(visibility("Config"), Visibility::Internal);
+        assert_eq!(visibility("Cache"), Visibility::Private);
+        // Trait impl methods are as visible as the trait
+        assert_eq!(visibility("default"), Visibility::Public);
+    }
+
     #[test]
     fn test_extract_rust_struct() {
         let code = r#"
@@ -0,0 +1,465 @@
+//! Reference Filter Expressions
+//!
+//! `--filter-expr` combines the reference filters into one boolean expression,
+//! e.g. `kind=call and file~src/auth and not name~test`.
+//!
+//! ```text
+//! expr       := and_expr ("or" and_expr)*
+//! and_expr   := unary ("and" unary)*
+//! unary      := "not" unary | "(" expr ")" | comparison
+//! comparison := field op value
+//! field      := "kind" | "file" | "name"
+//! op         := "=" | "!=" | "~"
+//! value      := word | "quoted string" | 'quoted string'
+//! ```
+//!
+//! `kind` is the reference kind (read, write, call, type, import, export),
+//! `file` the file path and `name` the symbol the reference sits in. `=` and
+//! `!=` compare whole values, `~` matches a substring. Keywords are
+//! case-insensitive; `and` binds tighter than `or`.
+//!
+//! @module trace/filter_expr
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Reference kinds accepted by `kind=`
+const REFERENCE_KINDS: &[&str] = &["read", "write", "call", "type", "import", "export"];
+
+/// Reference property compared by an expression
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterField {
+    /// Reference kind (`read`, `call`, ...)
+    Kind,
+    /// File containing the reference
+    File,
+    /// Symbol enclosing the reference
+    Name,
+}
+
+impl FilterField {
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "kind" => Some(Self::Kind),
+            "file" => Some(Self::File),
+            "name" => Some(Self::Name),
+            _ => None,
+        }
+    }
+}
+
+/// Comparison operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterOp {
+    /// `=`: whole value equals
+    Eq,
+    /// `!=`: whole value differs
+    Ne,
+    /// `~`: value contains
+    Contains,
+}
+
+/// Parsed filter expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterExpr {
+    Compare {
+        field: FilterField,
+        op: FilterOp,
+        value: String,
+    },
+    Not(Box<FilterExpr>),
+    And(Box<FilterExpr>, Box<FilterExpr>),
+    Or(Box<FilterExpr>, Box<FilterExpr>),
+}
+
+/// The properties of one reference an expression is evaluated against
+#[derive(Debug, Clone, Copy)]
+pub struct FilterSubject<'a> {
+    pub kind: &'a str,
+    pub file: &'a str,
+    /// None for references outside any symbol (top-level code)
+    pub name: Option<&'a str>,
+}
+
+/// Why an expression could not be parsed
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("{message} at position {position}")]
+pub struct FilterExprError {
+    pub message: String,
+    /// 1-based character position in the expression
+    pub position: usize,
+}
+
+impl FilterExpr {
+    /// Parse an expression (see the module docs for the grammar)
+    pub fn parse(input: &str) -> Result<Self, FilterExprError> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            pos: 0,
+            end: input.chars().count() + 1,
+        };
+        let expr = parser.expr()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some(token) => Err(token.error(format!("expected `and` or `or`, found {}", token))),
+        }
+    }
+
+    /// Whether a reference satisfies the expression
+    pub fn matches(&self, subject: &FilterSubject) -> bool {
+        match self {
+            Self::Compare { field, op, value } => {
+                let actual = match field {
+                    FilterField::Kind => Some(subject.kind),
+                    FilterField::File => Some(subject.file),
+                    FilterField::Name => subject.name,
+                };
+                let Some(actual) = actual else {
+                    // Nothing to compare: only "differs" holds
+                    return *op == FilterOp::Ne;
+                };
+                let equal = if *field == FilterField::Kind {
+                    actual.eq_ignore_ascii_case(value)
+                } else {
+                    actual == value
+                };
+                match op {
+                    FilterOp::Eq => equal,
+                    FilterOp::Ne => !equal,
+                    FilterOp::Contains => actual.contains(value.as_str()),
+                }
+            }
+            Self::Not(inner) => !inner.matches(subject),
+            Self::And(a, b) => a.matches(subject) && b.matches(subject),
+            Self::Or(a, b) => a.matches(subject) || b.matches(subject),
+        }
+    }
+}
+
+impl FromStr for FilterExpr {
+    type Err = FilterExprError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+// =============================================================================
+// TOKENIZER
+// =============================================================================
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TokenKind {
+    Word(String),
+    Quoted(String),
+    Op(FilterOp),
+    Open,
+    Close,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    /// 1-based character position
+    position: usize,
+}
+
+impl Token {
+    fn error(&self, message: String) -> FilterExprError {
+        FilterExprError {
+            message,
+            position: self.position,
+        }
+    }
+
+    /// Whether this is the keyword `keyword` (case-insensitive)
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(&self.kind, TokenKind::Word(w) if w.eq_ignore_ascii_case(keyword))
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            TokenKind::Word(w) => write!(f, "`{}`", w),
+            TokenKind::Quoted(s) => write!(f, "\"{}\"", s),
+            TokenKind::Op(FilterOp::Eq) => write!(f, "`=`"),
+            TokenKind::Op(FilterOp::Ne) => write!(f, "`!=`"),
+            TokenKind::Op(FilterOp::Contains) => write!(f, "`~`"),
+            TokenKind::Open => write!(f, "`(`"),
+            TokenKind::Close => write!(f, "`)`"),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, FilterExprError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let position = i + 1;
+        let kind = match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => TokenKind::Open,
+            ')' => TokenKind::Close,
+            '=' => TokenKind::Op(FilterOp::Eq),
+            '~' => TokenKind::Op(FilterOp::Contains),
+            '!' if chars.get(i + 1) == Some(&'=') => {
+                i += 1;
+                TokenKind::Op(FilterOp::Ne)
+            }
+            '!' => {
+                return Err(FilterExprError {
+                    message: "expected `!=`; use `not` to negate".to_string(),
+                    position,
+                })
+            }
+            '"' | '\'' => {
+                let Some(len) = chars[i + 1..].iter().position(|&q| q == c) else {
+                    return Err(FilterExprError {
+                        message: "unterminated quoted value".to_string(),
+                        position,
+                    });
+                };
+                let value = chars[i + 1..i + 1 + len].iter().collect();
+                i += len + 1;
+                TokenKind::Quoted(value)
+            }
+            _ => {
+                let len = chars[i..]
+                    .iter()
+                    .position(|&c| c.is_whitespace() || "()=!~\"'".contains(c))
+                    .unwrap_or(chars.len() - i);
+                let word = chars[i..i + len].iter().collect();
+                i += len;
+                tokens.push(Token {
+                    kind: TokenKind::Word(word),
+                    position,
+                });
+                continue;
+            }
+        };
+        tokens.push(Token { kind, position });
+        i += 1;
+    }
+    Ok(tokens)
+}
+
+// =============================================================================
+// PARSER
+// =============================================================================
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    /// Position reported for errors at the end of the input
+    end: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.pos);
+        self.pos += 1;
+        token
+    }
+
+    fn at_end(&self, expected: &str) -> FilterExprError {
+        FilterExprError {
+            message: format!("expected {}, found end of expression", expected),
+            position: self.end,
+        }
+    }
+
+    fn expr(&mut self) -> Result<FilterExpr, FilterExprError> {
+        let mut left = self.and_expr()?;
+        while self.peek().is_some_and(|t| t.is_keyword("or")) {
+            self.pos += 1;
+            let right = self.and_expr()?;
+            left = FilterExpr::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn and_expr(&mut self) -> Result<FilterExpr, FilterExprError> {
+        let mut left = self.unary()?;
+        while self.peek().is_some_and(|t| t.is_keyword("and")) {
+            self.pos += 1;
+            let right = self.unary()?;
+            left = FilterExpr::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<FilterExpr, FilterExprError> {
+        let Some(token) = self.peek() else {
+            return Err(self.at_end("a comparison"));
+        };
+        if token.is_keyword("not") {
+            self.pos += 1;
+            return Ok(FilterExpr::Not(Box::new(self.unary()?)));
+        }
+        if token.kind == TokenKind::Open {
+            let open = token.clone();
+            self.pos += 1;
+            let inner = self.expr()?;
+            return match self.next() {
+                Some(Token {
+                    kind: TokenKind::Close,
+                    ..
+                }) => Ok(inner),
+                Some(token) => Err(token.error(format!("expected `)`, found {}", token))),
+                None => Err(open.error("unclosed `(`".to_string())),
+            };
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<FilterExpr, FilterExprError> {
+        let token = self.next().cloned().ok_or_else(|| self.at_end("a field"))?;
+        let field = match &token.kind {
+            TokenKind::Word(name) => FilterField::parse(name).ok_or_else(|| {
+                token.error(format!(
+                    "unknown field `{}` (expected kind, file or name)",
+                    name
+                ))
+            })?,
+            _ => return Err(token.error(format!("expected a field, found {}", token))),
+        };
+
+        let op = match self.next() {
+            Some(Token {
+                kind: TokenKind::Op(op),
+                ..
+            }) => *op,
+            Some(token) => {
+                return Err(token.error(format!("expected `=`, `!=` or `~`, found {}", token)))
+            }
+            None => return Err(self.at_end("`=`, `!=` or `~`")),
+        };
+
+        let value_token = self.next().cloned().ok_or_else(|| self.at_end("a value"))?;
+        let value = match &value_token.kind {
+            TokenKind::Word(value) | TokenKind::Quoted(value) => value.clone(),
+            _ => return Err(value_token.error(format!("expected a value, found {}", value_token))),
+        };
+        if field == FilterField::Kind
+            && op != FilterOp::Contains
+            && !REFERENCE_KINDS.contains(&value.to_ascii_lowercase().as_str())
+        {
+            return Err(value_token.error(format!(
+                "unknown reference kind `{}` (expected {})",
+                value,
+                REFERENCE_KINDS.join(", ")
+            )));
+        }
+        Ok(FilterExpr::Compare { field, op, value })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_precedence_and_errors() {
+        let compare = |field, op, value: &str| FilterExpr::Compare {
+            field,
+            op,
+            value: value.to_string(),
+        };
+        let expr = FilterExpr::parse("kind=call or file~src AND NOT name~test").unwrap();
+        assert_eq!(
+            expr,
+            FilterExpr::Or(
+                Box::new(compare(FilterField::Kind, FilterOp::Eq, "call")),
+                Box::new(FilterExpr::And(
+                    Box::new(compare(FilterField::File, FilterOp::Contains, "src")),
+                    Box::new(FilterExpr::Not(Box::new(compare(
+                        FilterField::Name,
+                        FilterOp::Contains,
+                        "test"
+                    )))),
+                )),
+            )
+        );
+        assert_eq!(
+            FilterExpr::parse("file = 'my dir/a.rs'").unwrap(),
+            compare(FilterField::File, FilterOp::Eq, "my dir/a.rs")
+        );
+
+        let error = |input: &str| FilterExpr::parse(input).unwrap_err().to_string();
+        assert_eq!(
+            error("size=3"),
+            "unknown field `size` (expected kind, file or name) at position 1"
+        );
+        assert_eq!(
+            error("kind=cal"),
+            "unknown reference kind `cal` (expected read, write, call, type, import, export) at position 6"
+        );
+        assert_eq!(
+            error("kind=call and"),
+            "expected a comparison, found end of expression at position 14"
+        );
+        assert_eq!(error("(kind=call"), "unclosed `(` at position 1");
+        assert_eq!(
+            error("kind=call file~x"),
+            "expected `and` or `or`, found `file` at position 11"
+        );
+        assert_eq!(
+            error("name!test"),
+            "expected `!=`; use `not` to negate at position 5"
+        );
+    }
+
+    #[test]
+    fn test_compound_expressions_against_references() {
+        let refs = [
+            FilterSubject {
+                kind: "call",
+                file: "src/auth/login.rs",
+                name: Some("login"),
+            },
+            FilterSubject {
+                kind: "call",
+                file: "src/auth/tests.rs",
+                name: Some("test_login"),
+            },
+            FilterSubject {
+                kind: "read",
+                file: "src/auth/login.rs",
+                name: None,
+            },
+            FilterSubject {
+                kind: "call",
+                file: "src/api.rs",
+                name: Some("handler"),
+            },
+        ];
+        let matching = |input: &str| -> Vec<usize> {
+            let expr = FilterExpr::parse(input).unwrap();
+            (0..refs.len())
+                .filter(|&i| expr.matches(&refs[i]))
+                .collect()
+        };
+
+        assert_eq!(
+            matching("kind=call and file~src/auth and not name~test"),
+            [0]
+        );
+        assert_eq!(matching("kind=read or file=src/api.rs"), [2, 3]);
+        assert_eq!(matching("not (kind=CALL and file~auth)"), [2, 3]);
+        // References outside any symbol only pass `!=` on name
+        assert_eq!(matching("name!=handler"), [0, 1, 2]);
+        assert_eq!(matching("name~o"), [0, 1]);
+    }
+}
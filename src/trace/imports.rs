@@ -0,0 +1,260 @@
+//! Import Resolution
+//!
+//! Resolves import specifiers (`./b`, `pkg.module`, `crate::a::b`) to indexed
+//! files and records `RefKind::Import` references, so the file-level import
+//! graph can be walked independently of call edges.
+//!
+//! @module trace/imports
+
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+use super::extract::{self, ExtractedRef};
+use super::index::SemanticIndex;
+use super::types::{RefKind, Reference, Token, TokenKind};
+
+/// Extensions tried when a relative specifier omits one
+const RELATIVE_EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx", "mjs", "cjs", "rb", "py"];
+
+/// A symbol an import can bind to
+struct ImportTarget {
+    id: u32,
+    name_offset: u32,
+    exported: bool,
+}
+
+/// Lookups for linking imports, built once per pass over the index
+///
+/// Paths, per-file symbols, and per-line tokens are grouped up front so each
+/// import costs hash lookups instead of scans over every file, symbol, and
+/// token in the index.
+pub struct ImportResolver {
+    file_ids: HashMap<PathBuf, u16>,
+    symbols: HashMap<u16, Vec<ImportTarget>>,
+    /// (token id, name offset) keyed by (file, line)
+    tokens: HashMap<(u16, u32), Vec<(u32, u32)>>,
+}
+
+impl ImportResolver {
+    pub fn new(index: &SemanticIndex) -> Self {
+        let file_ids = index
+            .files
+            .iter()
+            .enumerate()
+            .map(|(id, path)| (path.clone(), id as u16))
+            .collect();
+
+        let mut symbols: HashMap<u16, Vec<ImportTarget>> = HashMap::new();
+        for symbol in &index.symbols {
+            symbols
+                .entry(symbol.file_id)
+                .or_default()
+                .push(ImportTarget {
+                    id: symbol.id,
+                    name_offset: symbol.name_offset,
+                    exported: symbol.is_exported(),
+                });
+        }
+
+        let mut tokens: HashMap<(u16, u32), Vec<(u32, u32)>> = HashMap::new();
+        for token in &index.tokens {
+            tokens
+                .entry((token.file_id, token.line))
+                .or_default()
+                .push((token.id, token.name_offset));
+        }
+
+        Self {
+            file_ids,
+            symbols,
+            tokens,
+        }
+    }
+
+    /// Resolve an import specifier written in `importer` to an indexed file
+    pub fn resolve(&self, importer: &Path, specifier: &str) -> Option<u16> {
+        candidate_paths(importer, specifier)
+            .iter()
+            .find_map(|path| self.file_ids.get(path).copied())
+    }
+
+    /// Record Import references for one import statement of `file_id`
+    ///
+    /// Identifiers on the import line that name symbols of the imported file
+    /// reference those symbols. Namespace and side-effect imports, which name
+    /// nothing, get a token at the specifier referencing the file's exported
+    /// symbols (all symbols if it exports none). Returns the references added.
+    pub fn link(&self, index: &mut SemanticIndex, file_id: u16, import: &ExtractedRef) -> usize {
+        if import.kind != extract::RefKind::Import {
+            return 0;
+        }
+        let Some(importer) = index.file_path(file_id) else {
+            return 0;
+        };
+        let Some(target) = self.resolve(importer, &import.name) else {
+            return 0;
+        };
+        if target == file_id {
+            return 0;
+        }
+        let targets = match self.symbols.get(&target) {
+            Some(targets) if !targets.is_empty() => targets,
+            _ => return 0,
+        };
+
+        // Named bindings: identifiers on the import line matching target symbols
+        let line_tokens = self
+            .tokens
+            .get(&(file_id, import.line))
+            .map(Vec::as_slice)
+            .unwrap_or(&[]);
+        let strings = &index.strings;
+        let named: Vec<(u32, u32)> = line_tokens
+            .iter()
+            .flat_map(|&(token_id, token_name)| {
+                let name = strings.get(token_name);
+                targets
+                    .iter()
+                    .filter(move |t| name.is_some() && name == strings.get(t.name_offset))
+                    .map(move |t| (token_id, t.id))
+            })
+            .collect();
+
+        if !named.is_empty() {
+            for &(token_id, sym_id) in &named {
+                index.add_reference(Reference::new(token_id, sym_id, RefKind::Import));
+            }
+            return named.len();
+        }
+
+        let exported: Vec<u32> = targets
+            .iter()
+            .filter(|t| t.exported)
+            .map(|t| t.id)
+            .collect();
+        let symbol_ids = if exported.is_empty() {
+            targets.iter().map(|t| t.id).collect()
+        } else {
+            exported
+        };
+
+        let token_id = index.next_token_id();
+        let name_offset = index.strings.intern(&import.name);
+        index.add_token(
+            Token::new(
+                token_id,
+                name_offset,
+                file_id,
+                import.line,
+                import.column,
+                TokenKind::Import,
+                0,
+            ),
+            &import.name,
+        );
+        for &sym_id in &symbol_ids {
+            index.add_reference(Reference::new(token_id, sym_id, RefKind::Import));
+        }
+        symbol_ids.len()
+    }
+}
+
+/// Project-relative paths an import specifier may refer to, most specific first
+fn candidate_paths(importer: &Path, specifier: &str) -> Vec<PathBuf> {
+    // Regex captures may carry trailing names (`os, sys`) or use-groups (`a::{b, c}`)
+    let specifier = specifier
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .next()
+        .unwrap_or("");
+    let specifier = specifier.split("::{").next().unwrap_or(specifier);
+    if specifier.is_empty() {
+        return Vec::new();
+    }
+    let dir = importer.parent().unwrap_or(Path::new(""));
+    let mut candidates = Vec::new();
+
+    if specifier.starts_with("./") || specifier.starts_with("../") {
+        let base = normalize(&dir.join(specifier));
+        candidates.push(base.clone());
+        for ext in RELATIVE_EXTENSIONS {
+            candidates.push(append_extension(&base, ext));
+        }
+        for ext in RELATIVE_EXTENSIONS {
+            candidates.push(base.join(format!("index.{}", ext)));
+        }
+    } else if let Some(rest) = specifier.strip_prefix("crate::") {
+        let src = rust_source_root(importer);
+        let segments: Vec<&str> = rest.split("::").filter(|s| !s.is_empty()).collect();
+        // Trailing segments may name items rather than modules
+        for len in (1..=segments.len()).rev() {
+            let module = src.join(segments[..len].join("/"));
+            candidates.push(append_extension(&module, "rs"));
+            candidates.push(module.join("mod.rs"));
+        }
+    } else if !specifier.contains('/') && !specifier.contains("::") {
+        let module = specifier.replace('.', "/");
+        for base in [dir.join(&module), PathBuf::from(&module)] {
+            candidates.push(append_extension(&base, "py"));
+            candidates.push(base.join("__init__.py"));
+        }
+    }
+
+    candidates
+}
+
+/// `src` directory enclosing a Rust file (the crate root for `crate::` paths)
+fn rust_source_root(importer: &Path) -> PathBuf {
+    importer
+        .ancestors()
+        .find(|p| p.file_name().is_some_and(|name| name == "src"))
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("src"))
+}
+
+/// `path` with `.ext` appended (keeping any dots already in the file name)
+fn append_extension(path: &Path, ext: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".");
+    name.push(ext);
+    PathBuf::from(name)
+}
+
+/// Resolve `.` and `..` components lexically
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candidate_paths_by_specifier_style() {
+        let relative = candidate_paths(Path::new("src/a/x.ts"), "../b/y");
+        assert!(relative.contains(&PathBuf::from("src/b/y.ts")));
+        assert!(relative.contains(&PathBuf::from("src/b/y/index.ts")));
+
+        let python = candidate_paths(Path::new("app/main.py"), "pkg.util\n");
+        assert!(python.contains(&PathBuf::from("pkg/util.py")));
+        assert!(python.contains(&PathBuf::from("app/pkg/util.py")));
+
+        let rust = candidate_paths(
+            Path::new("src/cli/run.rs"),
+            "crate::core::config::{Config, Load}",
+        );
+        assert!(rust.contains(&PathBuf::from("src/core/config.rs")));
+        assert!(rust.contains(&PathBuf::from("src/core/mod.rs")));
+
+        assert!(candidate_paths(Path::new("src/x.ts"), "@scope/pkg").is_empty());
+    }
+}
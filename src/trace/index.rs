@@ -164,6 +164,10 @@ pub struct SemanticIndex {
     /// SmallVec<[u32; 4]> because most names have 1-4 definitions
     pub symbol_by_name: HashMap<CompactString, SmallVec<[u32; 4]>>,
 
+    /// Symbol lookup by normalized name (see `normalize_name`), so
+    /// `UserService` and `user_service` share a key
+    pub symbol_by_normalized_name: HashMap<CompactString, SmallVec<[u32; 4]>>,
+
     /// Token lookup by name -> list of token IDs with that name
     pub token_by_name: HashMap<CompactString, Vec<u32>>,
 
@@ -189,6 +193,9 @@ pub struct SemanticIndex {
 
     /// Entry point symbol IDs (for fast traversal starting points)
     pub entry_points: Vec<u32>,
+
+    /// Fall back to normalized names in `symbols_by_name` (not persisted)
+    pub normalize_names: bool,
 }
 
 impl SemanticIndex {
@@ -201,6 +208,7 @@ impl SemanticIndex {
             scopes: Vec::new(),
             edges: Vec::new(),
             symbol_by_name: HashMap::new(),
+            symbol_by_normalized_name: HashMap::new(),
             token_by_name: HashMap::new(),
             incoming_edges: Vec::new(),
             outgoing_edges: Vec::new(),
@@ -208,6 +216,7 @@ impl SemanticIndex {
             files: Vec::new(),
             strings: StringTable::new(),
             entry_points: Vec::new(),
+            normalize_names: false,
         }
     }
 
@@ -227,6 +236,7 @@ impl SemanticIndex {
             scopes: Vec::with_capacity(scopes),
             edges: Vec::with_capacity(edges),
             symbol_by_name: HashMap::with_capacity(symbols),
+            symbol_by_normalized_name: HashMap::with_capacity(symbols),
             token_by_name: HashMap::with_capacity(tokens / 4), // Many tokens share names
             incoming_edges: Vec::with_capacity(symbols),
             outgoing_edges: Vec::with_capacity(symbols),
@@ -234,6 +244,7 @@ impl SemanticIndex {
             files: Vec::with_capacity(files),
             strings: StringTable::with_capacity(symbols * 20), // ~20 bytes per symbol name
             entry_points: Vec::with_capacity(files),           // ~1 entry point per file
+            normalize_names: false,
         }
     }
 
@@ -261,12 +272,16 @@ impl SemanticIndex {
         }
         self.symbols[id] = symbol;
 
-        // Update name lookup
+        // Update name lookups
         let compact_name = CompactString::new(name);
         self.symbol_by_name
             .entry(compact_name)
             .or_insert_with(SmallVec::new)
             .push(symbol.id);
+        self.symbol_by_normalized_name
+            .entry(normalize_name(name))
+            .or_default()
+            .push(symbol.id);
 
         // Track entry points
         if symbol.is_entry_point() {
@@ -348,6 +363,7 @@ impl SemanticIndex {
     pub fn rebuild_lookups(&mut self) {
         // Clear existing lookups
         self.symbol_by_name.clear();
+        self.symbol_by_normalized_name.clear();
         self.token_by_name.clear();
         self.incoming_edges.clear();
         self.outgoing_edges.clear();
@@ -368,6 +384,10 @@ impl SemanticIndex {
                     .entry(CompactString::new(name))
                     .or_insert_with(SmallVec::new)
                     .push(symbol.id);
+                self.symbol_by_normalized_name
+                    .entry(normalize_name(name))
+                    .or_default()
+                    .push(symbol.id);
             }
             if symbol.is_entry_point() {
                 self.entry_points.push(symbol.id);
@@ -410,8 +430,59 @@ impl SemanticIndex {
     // -------------------------------------------------------------------------
 
     /// Find symbols by exact name
+    ///
+    /// When `normalize_names` is set and there is no exact match, falls back
+    /// to symbols whose normalized name matches (e.g. `userService` finds
+    /// `user_service`).
     pub fn symbols_by_name(&self, name: &str) -> Option<&SmallVec<[u32; 4]>> {
-        self.symbol_by_name.get(&CompactString::new(name))
+        let exact = self.symbol_by_name.get(&CompactString::new(name));
+        if exact.is_some() || !self.normalize_names {
+            return exact;
+        }
+        self.symbols_by_normalized_name(name)
+    }
+
+    /// Resolve a possibly qualified name (`Parent::name` or `Parent.name`)
+    ///
+    /// Qualified names keep only definitions whose enclosing type is
+    /// `Parent`; plain names resolve like `symbols_by_name`. A name with `*`
+    /// or `?` wildcards resolves to every symbol it matches.
+    pub fn resolve_symbols(&self, name: &str) -> SmallVec<[u32; 4]> {
+        let Some((parent, member)) = split_qualified_name(name) else {
+            return self.symbols_named(name);
+        };
+        self.symbols_named(member)
+            .into_iter()
+            .filter(|&id| {
+                self.symbol(id)
+                    .and_then(|s| self.symbol_parent(s))
+                    .is_some_and(|p| p == parent)
+            })
+            .collect()
+    }
+
+    /// Symbols named `name`, or matching it when it is a glob pattern
+    fn symbols_named(&self, name: &str) -> SmallVec<[u32; 4]> {
+        if !is_glob_pattern(name) {
+            return self.symbols_by_name(name).cloned().unwrap_or_default();
+        }
+        let Ok(pattern) = glob::Pattern::new(name) else {
+            return SmallVec::new();
+        };
+        let mut ids: SmallVec<[u32; 4]> = self
+            .symbol_by_name
+            .iter()
+            .filter(|(symbol, _)| pattern.matches(symbol))
+            .flat_map(|(_, ids)| ids.iter().copied())
+            .collect();
+        // Map order is arbitrary; keep results stable between runs
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Find symbols whose name equals `name` modulo case and separators
+    pub fn symbols_by_normalized_name(&self, name: &str) -> Option<&SmallVec<[u32; 4]>> {
+        self.symbol_by_normalized_name.get(&normalize_name(name))
     }
 
     /// Find symbols matching a name pattern (substring match)
@@ -462,6 +533,31 @@ impl SemanticIndex {
         self.strings.get(symbol.name_offset)
     }
 
+    /// Get the name of the type enclosing a symbol (e.g. a method's class)
+    pub fn symbol_parent(&self, symbol: &Symbol) -> Option<&str> {
+        self.strings.get(symbol.parent_offset()?)
+    }
+
+    /// Content-derived id of a symbol that survives reindexing
+    ///
+    /// Unlike the positional symbol id, which shifts whenever files are added
+    /// or removed, this hashes the symbol's qualified name, file and kind, so
+    /// it stays the same across builds while the definition does.
+    pub fn stable_symbol_id(&self, id: u32) -> Option<String> {
+        let symbol = self.symbol(id)?;
+        let key = format!(
+            "{}\0{}\0{}\0{:?}",
+            self.symbol_parent(symbol).unwrap_or_default(),
+            self.symbol_name(symbol)?,
+            self.file_path(symbol.file_id)?.to_string_lossy(),
+            symbol.symbol_kind()
+        );
+        Some(format!(
+            "{:016x}",
+            xxhash_rust::xxh3::xxh3_64(key.as_bytes())
+        ))
+    }
+
     /// Get the name of a token
     pub fn token_name(&self, token: &Token) -> Option<&str> {
         self.strings.get(token.name_offset)
@@ -552,6 +648,94 @@ impl SemanticIndex {
         }
     }
 
+    /// Check the index's internal invariants without modifying it
+    ///
+    /// Returns a description of every violation: ids that do not match
+    /// their slot, dangling symbol or token ids in edges, references and
+    /// name maps, and file ids outside the file table. Empty means valid.
+    pub fn verify(&self) -> Vec<String> {
+        let mut violations = Vec::new();
+        let files = self.files.len();
+        let has_symbol = |id: u32| (id as usize) < self.symbols.len();
+        let has_token = |id: u32| (id as usize) < self.tokens.len();
+
+        for (slot, symbol) in self.symbols.iter().enumerate() {
+            if symbol.id as usize != slot {
+                violations.push(format!("symbol slot {} holds id {}", slot, symbol.id));
+            }
+            if symbol.file_id as usize >= files {
+                violations.push(format!(
+                    "symbol {} has file_id {} (only {} files)",
+                    symbol.id, symbol.file_id, files
+                ));
+            }
+        }
+        for (slot, token) in self.tokens.iter().enumerate() {
+            if token.id as usize != slot {
+                violations.push(format!("token slot {} holds id {}", slot, token.id));
+            }
+            if token.file_id as usize >= files {
+                violations.push(format!(
+                    "token {} has file_id {} (only {} files)",
+                    token.id, token.file_id, files
+                ));
+            }
+        }
+        for scope in &self.scopes {
+            if scope.file_id as usize >= files {
+                violations.push(format!(
+                    "scope {} has file_id {} (only {} files)",
+                    scope.id, scope.file_id, files
+                ));
+            }
+        }
+        for edge in &self.edges {
+            for (end, id) in [("from", edge.from_symbol), ("to", edge.to_symbol)] {
+                if !has_symbol(id) {
+                    violations.push(format!(
+                        "edge {} -> {} (line {}): {} symbol {} does not exist",
+                        edge.from_symbol, edge.to_symbol, edge.line, end, id
+                    ));
+                }
+            }
+        }
+        for reference in &self.references {
+            if !has_token(reference.token_id) {
+                violations.push(format!(
+                    "reference to symbol {}: token {} does not exist",
+                    reference.symbol_id, reference.token_id
+                ));
+            }
+            if !has_symbol(reference.symbol_id) {
+                violations.push(format!(
+                    "reference from token {}: symbol {} does not exist",
+                    reference.token_id, reference.symbol_id
+                ));
+            }
+        }
+        for (name, ids) in &self.symbol_by_name {
+            for &id in ids {
+                if !has_symbol(id) {
+                    violations.push(format!("name '{}' maps to missing symbol {}", name, id));
+                }
+            }
+        }
+        for (name, ids) in &self.token_by_name {
+            for &id in ids {
+                if !has_token(id) {
+                    violations.push(format!("name '{}' maps to missing token {}", name, id));
+                }
+            }
+        }
+        for &id in &self.entry_points {
+            if !has_symbol(id) {
+                violations.push(format!("entry point {} does not exist", id));
+            }
+        }
+
+        violations
+    }
+
     // -------------------------------------------------------------------------
     // Incremental Update Methods
     // -------------------------------------------------------------------------
@@ -597,6 +781,13 @@ impl SemanticIndex {
                             self.symbol_by_name.remove(&compact_name);
                         }
                     }
+                    let normalized = normalize_name(name);
+                    if let Some(ids) = self.symbol_by_normalized_name.get_mut(&normalized) {
+                        ids.retain(|id| *id != *symbol_id);
+                        if ids.is_empty() {
+                            self.symbol_by_normalized_name.remove(&normalized);
+                        }
+                    }
                 }
             }
         }
@@ -708,6 +899,38 @@ impl Default for SemanticIndex {
     }
 }
 
+/// Normalize a symbol name for cross-case lookup
+///
+/// Lowercases and strips `_`/`-` separators, so `UserService`, `userService`
+/// and `user_service` all map to `userservice`.
+pub fn normalize_name(name: &str) -> CompactString {
+    name.chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Whether a symbol argument is a glob pattern (`handle*`, `get?ser`)
+/// rather than a literal name
+pub fn is_glob_pattern(name: &str) -> bool {
+    name.contains(['*', '?'])
+}
+
+/// Split `Parent::name` / `Parent.name` into the innermost parent and member
+///
+/// Outer qualifiers are dropped, so `net::Server::handle` gives
+/// `("Server", "handle")`. Returns `None` for plain names.
+pub fn split_qualified_name(name: &str) -> Option<(&str, &str)> {
+    let (qualifier, member) = match (name.rfind("::"), name.rfind('.')) {
+        (Some(colons), Some(dot)) if dot > colons => (&name[..dot], &name[dot + 1..]),
+        (Some(colons), _) => (&name[..colons], &name[colons + 2..]),
+        (None, Some(dot)) => (&name[..dot], &name[dot + 1..]),
+        (None, None) => return None,
+    };
+    let parent = qualifier.rsplit([':', '.']).next().unwrap_or(qualifier);
+    (!parent.is_empty() && !member.is_empty()).then_some((parent, member))
+}
+
 // =============================================================================
 // INDEX STATISTICS
 // =============================================================================
@@ -794,6 +1017,71 @@ mod tests {
         assert_eq!(index.entry_points.len(), 1);
     }
 
+    #[test]
+    fn test_normalized_name_lookup() {
+        let mut index = SemanticIndex::new();
+        let file_id = index.add_file(PathBuf::from("svc.py"));
+        let name_offset = index.strings.intern("user_service");
+        index.add_symbol(
+            Symbol::new(
+                0,
+                name_offset,
+                file_id,
+                SymbolKind::Function,
+                SymbolFlags::empty(),
+                1,
+                5,
+            ),
+            "user_service",
+        );
+
+        // Off by default: exact names only
+        assert!(index.symbols_by_name("userService").is_none());
+
+        index.normalize_names = true;
+        assert_eq!(
+            index.symbols_by_name("userService").unwrap().as_slice(),
+            &[0]
+        );
+        assert_eq!(
+            index.symbols_by_name("UserService").unwrap().as_slice(),
+            &[0]
+        );
+        assert!(index.symbols_by_name("user_services").is_none());
+
+        // Survives a lookup rebuild (as after loading from disk)
+        index.rebuild_lookups();
+        assert!(index.symbols_by_name("USER-SERVICE").is_some());
+    }
+
+    #[test]
+    fn test_wildcard_name_resolves_matching_symbols() {
+        let mut index = SemanticIndex::new();
+        let file_id = index.add_file(PathBuf::from("api.ts"));
+        for (id, name) in ["getUser", "setUser", "getOrder"].into_iter().enumerate() {
+            let name_offset = index.strings.intern(name);
+            let line = id as u32 * 10 + 1;
+            index.add_symbol(
+                Symbol::new(
+                    id as u32,
+                    name_offset,
+                    file_id,
+                    SymbolKind::Function,
+                    SymbolFlags::empty(),
+                    line,
+                    line + 5,
+                ),
+                name,
+            );
+        }
+
+        assert_eq!(index.resolve_symbols("get*").as_slice(), &[0, 2]);
+        assert_eq!(index.resolve_symbols("?etUser").as_slice(), &[0, 1]);
+        assert!(index.resolve_symbols("fetch*").is_empty());
+        // Without wildcards the name is still matched exactly
+        assert!(index.resolve_symbols("get").is_empty());
+    }
+
     #[test]
     fn test_call_graph() {
         let mut index = SemanticIndex::new();
@@ -1002,4 +1290,30 @@ mod tests {
         assert!(index.has_file(std::path::Path::new("test.ts")));
         assert!(!index.has_file(std::path::Path::new("other.ts")));
     }
+
+    #[test]
+    fn test_verify_reports_dangling_edge() {
+        let mut index = SemanticIndex::new();
+        let file_id = index.add_file(PathBuf::from("main.rs"));
+        for (id, name) in [(0, "main"), (1, "helper")] {
+            let offset = index.strings.intern(name);
+            let symbol = Symbol::new(
+                id,
+                offset,
+                file_id,
+                SymbolKind::Function,
+                SymbolFlags::empty(),
+                1,
+                3,
+            );
+            index.add_symbol(symbol, name);
+        }
+        index.add_edge(Edge::new(0, 1, 2));
+        assert!(index.verify().is_empty());
+
+        index.edges.push(Edge::new(0, 7, 2));
+        let violations = index.verify();
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].contains("symbol 7 does not exist"));
+    }
 }
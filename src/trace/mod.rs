@@ -9,10 +9,15 @@
 //!
 //! @module trace
 
+pub mod blame;
 pub mod builder;
 pub mod context;
+pub mod export;
 pub mod extract;
+pub mod filter_expr;
+pub mod imports;
 pub mod index;
+pub mod operations;
 pub mod output;
 pub mod snapshots;
 pub mod storage;
@@ -25,30 +30,38 @@ pub mod types;
 
 pub use types::{
     Edge, RefKind, Reference, Scope, ScopeKind, Symbol, SymbolFlags, SymbolKind, Token, TokenKind,
-    NO_PARENT_SCOPE,
+    Visibility, NO_PARENT_SCOPE,
 };
 
 // =============================================================================
 // RE-EXPORTS: Index (index.rs)
 // =============================================================================
 
-pub use index::{IndexStats, SemanticIndex, StringTable};
+pub use index::{is_glob_pattern, normalize_name, IndexStats, SemanticIndex, StringTable};
 
 // =============================================================================
 // RE-EXPORTS: Storage (storage.rs)
 // =============================================================================
 
 pub use storage::{
-    load_index, load_index_streaming, save_index, trace_index_exists, trace_index_path,
+    index_file_size, load_index, load_index_streaming, save_index, trace_index_exists,
+    trace_index_path,
 };
 
+// =============================================================================
+// RE-EXPORTS: Export (export.rs)
+// =============================================================================
+
+pub use export::{export_index, export_index_with_paths, import_index, ExportRecord};
+
 // =============================================================================
 // RE-EXPORTS: Traversal (traverse.rs)
 // =============================================================================
 
 pub use traverse::{
-    find_call_refs, find_dead_symbols, find_read_refs, find_refs, find_refs_of_kind,
-    find_write_refs, format_call_chain, format_invocation_path, trace_symbol, trace_symbol_by_name,
+    find_call_refs, find_dead_symbols, find_dead_symbols_with, find_read_refs, find_refs,
+    find_refs_of_kind, find_write_refs, format_call_chain, format_invocation_path,
+    strongly_connected_components, trace_symbol, trace_symbol_by_name, DeadCodeOptions,
     InvocationPath as TraverseInvocationPath, ReferenceContext, TraceResult as TraverseTraceResult,
 };
 
@@ -66,17 +79,23 @@ pub use extract::{
 // =============================================================================
 
 pub use output::{
-    create_formatter, AsciiFormatter, ChainStep, DeadCodeResult, DeadSymbol, FlowAction,
-    FlowResult, FlowStep, ImpactResult, InvocationPath, JsonFormatter, OutputFormat,
-    PlainFormatter, ReferenceInfo, ReferenceKind, RefsResult, RiskLevel, TraceFormatter,
-    TraceResult,
+    create_formatter, AsciiConfig, AsciiFormatter, CallHierarchyNode, CallHierarchyResult,
+    ChainStep, CsvConfig, DeadCodeResult, DeadSymbol, DeprecatedResult, DeprecatedSymbol,
+    DirectoryGroup, FileCount, FlowAction, FlowResult, FlowStep, HotspotFile, HotspotResult,
+    ImpactResult, InvocationPath, JsonFormatter, MarkdownConfig, NeighborSymbol,
+    NeighborhoodResult, OutputFormat, PlainFormatter, ReferenceInfo, ReferenceKind, RefsResult,
+    RiskLevel, ShadowedVariable, ShadowsResult, StubSymbol, StubsResult, TraceFormatter,
+    TraceResult, UnusedParam, UnusedParamsResult,
 };
 
 // =============================================================================
 // RE-EXPORTS: Builder (builder.rs)
 // =============================================================================
 
-pub use builder::{build_and_save_index, build_index_parallel, BuildStats, SemanticIndexBuilder};
+pub use builder::{
+    build_and_save_index, build_index_parallel, build_project_index, check_index_size,
+    save_project_index, BuildStats, SemanticIndexBuilder,
+};
 
 // =============================================================================
 // RE-EXPORTS: Context (context.rs)
@@ -84,6 +103,12 @@ pub use builder::{build_and_save_index, build_index_parallel, BuildStats, Semant
 
 pub use context::{CacheStats, CodeContext, ContextBuilder, FileCache};
 
+// =============================================================================
+// RE-EXPORTS: Filter expressions (filter_expr.rs)
+// =============================================================================
+
+pub use filter_expr::{FilterExpr, FilterExprError, FilterSubject};
+
 // =============================================================================
 // RE-EXPORTS: Snapshots (snapshots.rs)
 // =============================================================================
@@ -93,3 +118,11 @@ pub use snapshots::{
     list_snapshots, load_snapshot, snapshots_dir, FileMetrics, Snapshot, SnapshotComparison,
     SnapshotDiff, SnapshotList, SnapshotMetrics, SnapshotSummary,
 };
+
+// =============================================================================
+// RE-EXPORTS: Library API (operations.rs)
+// =============================================================================
+
+pub use operations::{
+    run_operation, DefinitionChoice, OperationOptions, OperationResult, TraceFilter, TraceOperation,
+};
@@ -0,0 +1,6709 @@
+//! Trace operations
+//!
+//! Runs one trace operation (references, callers, dead code, statistics, ...)
+//! against a loaded semantic index and returns its result without printing.
+//! `greppy trace` renders these results; library users call [`run_operation`].
+//!
+//! @module trace/operations
+
+use crate::ai::trace_prompts::is_natural_language_query;
+use crate::ai::{claude::ClaudeClient, gemini::GeminiClient};
+use crate::auth::{self, Provider};
+use crate::core::config::{AiConfig, Config, ImpactConfig};
+use crate::core::error::{Error, Result};
+use crate::core::project::Project;
+use crate::index::hashes::content_hash;
+use crate::search::aliases::expand_aliases;
+use crate::trace::context::FileCache;
+use crate::trace::extract::treesitter::stub_functions;
+use crate::trace::extract::treesitter::StatementLocator;
+use crate::trace::extract::{detect_language, is_treesitter_supported, LiteralSpans};
+use crate::trace::output::{
+    CallHierarchyNode, CallHierarchyResult, ChainStep, DeadCodeResult, DeadSymbol,
+    DeprecatedResult, DeprecatedSymbol, DirectoryGroup, FileCount, FlowAction, FlowResult,
+    FlowStep, HotspotFile, HotspotResult, ImpactResult, InvocationPath, JsonArrayWriter,
+    ModuleResult, NeighborSymbol, NeighborhoodResult, PatternMatch, PatternResult, PotentialCaller,
+    ReferenceInfo, ReferenceKind, RefsResult, RiskLevel, ScopeResult, ScopeVariable,
+    ShadowedVariable, ShadowsResult, StatsResult, StubSymbol, StubsResult, SymbolDate, TraceResult,
+    UnusedParam, UnusedParamsResult,
+};
+use crate::trace::{
+    blame, find_dead_symbols_with, find_refs, is_glob_pattern, strongly_connected_components,
+    trace_symbol, trace_symbol_by_name, DeadCodeOptions, FilterExpr, FilterSubject, RefKind, Scope,
+    SemanticIndex, Symbol, SymbolKind, Token, TokenKind, TraverseTraceResult, Visibility,
+};
+use dialoguer::{theme::ColorfulTheme, Select};
+use once_cell::sync::Lazy;
+use rayon::prelude::*;
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, warn};
+
+// =============================================================================
+// HELPERS
+// =============================================================================
+
+/// Convert SymbolKind to string
+fn symbol_kind_str(kind: SymbolKind) -> &'static str {
+    match kind {
+        SymbolKind::Function => "function",
+        SymbolKind::Method => "method",
+        SymbolKind::Class => "class",
+        SymbolKind::Struct => "struct",
+        SymbolKind::Enum => "enum",
+        SymbolKind::Interface => "interface",
+        SymbolKind::TypeAlias => "type_alias",
+        SymbolKind::Constant => "constant",
+        SymbolKind::Variable => "variable",
+        SymbolKind::Module => "module",
+        SymbolKind::Unknown => "unknown",
+    }
+}
+
+/// Convert output ReferenceKind to string
+fn reference_kind_str(kind: ReferenceKind) -> &'static str {
+    match kind {
+        ReferenceKind::Read => "read",
+        ReferenceKind::Write => "write",
+        ReferenceKind::Call => "call",
+        ReferenceKind::TypeAnnotation => "type",
+        ReferenceKind::Import => "import",
+        ReferenceKind::Export => "export",
+    }
+}
+
+// =============================================================================
+// OPERATION API
+// =============================================================================
+
+/// The trace operation to perform
+#[derive(Debug)]
+pub enum TraceOperation {
+    Trace(String),
+    Refs {
+        symbol: String,
+        kind: Option<ReferenceKind>,
+    },
+    Callers(String),
+    CallHierarchy(String),
+    Callees(String),
+    Neighborhood(String),
+    Path {
+        from: String,
+        to: String,
+    },
+    Type(String),
+    Module(String),
+    Pattern(String),
+    Flow(String),
+    Impact(String),
+    Scope(String),
+    DeadCode,
+    Deprecated,
+    UnusedParams,
+    Shadows,
+    Stubs,
+    Hotspots,
+    Stats,
+    Cycles,
+}
+
+/// Result of one trace operation, as returned by [`run_operation`]
+#[derive(Debug, Clone)]
+pub enum OperationResult {
+    Trace(TraceResult),
+    Refs(RefsResult),
+    Callers(TraceResult),
+    Callees(TraceResult),
+    CallHierarchy(CallHierarchyResult),
+    Neighborhood(NeighborhoodResult),
+    CallPath(TraceResult),
+    TypeUsage(RefsResult),
+    Module(ModuleResult),
+    Pattern(PatternResult),
+    Flow(FlowResult),
+    Impact(ImpactResult),
+    Scope(ScopeResult),
+    DeadCode(DeadCodeResult),
+    Deprecated(DeprecatedResult),
+    UnusedParams(UnusedParamsResult),
+    Shadows(ShadowsResult),
+    Stubs(StubsResult),
+    Hotspots(HotspotResult),
+    Stats(StatsResult),
+    Cycles(ModuleResult),
+}
+
+/// Directories holding third-party code, skipped by --dead and --stats
+pub const VENDOR_DIRS: &[&str] = &[
+    "node_modules",
+    "vendor",
+    "third_party",
+    "bower_components",
+    "site-packages",
+    ".venv",
+];
+
+/// Check if a path lies inside a vendored dependency directory
+pub fn is_vendored_path(file_path: &str) -> bool {
+    file_path
+        .split(['/', '\\'])
+        .any(|component| VENDOR_DIRS.contains(&component))
+}
+
+/// Universal filter for trace operations
+#[derive(Debug, Clone, Default)]
+pub struct TraceFilter {
+    /// Filter by file/folder paths (contains match on any)
+    pub paths: Vec<String>,
+    /// Filter by symbol type (function, method, class, etc.)
+    pub symbol_type: Option<String>,
+    /// Filter by name pattern (regex)
+    pub name_pattern: Option<regex::Regex>,
+    /// Symbol kinds to exclude (exact match on kind name)
+    pub exclude_kinds: Vec<String>,
+    /// Skip vendored paths in dead code and stats (independent of `paths`)
+    pub exclude_vendor: bool,
+    /// Only symbols with this visibility
+    pub visibility: Option<Visibility>,
+    /// Reference filter expression (`--filter-expr`)
+    pub expr: Option<FilterExpr>,
+}
+
+impl TraceFilter {
+    /// Check if a symbol passes the filter
+    pub fn matches_symbol(&self, name: &str, kind: &str, file_path: &str) -> bool {
+        // Path filter
+        if !self.matches_path(file_path) {
+            return false;
+        }
+        // Symbol type filter
+        if let Some(ref stype) = self.symbol_type {
+            if !kind.to_lowercase().contains(&stype.to_lowercase()) {
+                return false;
+            }
+        }
+        // Name pattern filter
+        if let Some(ref pattern) = self.name_pattern {
+            if !pattern.is_match(name) {
+                return false;
+            }
+        }
+        // Excluded kinds filter
+        if self
+            .exclude_kinds
+            .iter()
+            .any(|k| k.eq_ignore_ascii_case(kind))
+        {
+            return false;
+        }
+        true
+    }
+
+    /// Check if a symbol has the visibility the filter asks for
+    pub fn matches_visibility(&self, symbol: &Symbol) -> bool {
+        self.visibility.map_or(true, |v| symbol.visibility() == v)
+    }
+
+    /// Check if an indexed symbol passes the filter
+    pub fn matches_symbol_id(&self, index: &SemanticIndex, symbol_id: u32) -> bool {
+        let Some(symbol) = index.symbol(symbol_id) else {
+            return false;
+        };
+        if !self.matches_visibility(symbol) {
+            return false;
+        }
+        let file = index
+            .file_path(symbol.file_id)
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default();
+        self.matches_symbol(
+            index.symbol_name(symbol).unwrap_or(""),
+            symbol_kind_str(symbol.symbol_kind()),
+            &file,
+        )
+    }
+
+    /// Check if a file path passes the filter
+    pub fn matches_path(&self, file_path: &str) -> bool {
+        self.paths.is_empty() || self.paths.iter().any(|p| file_path.contains(p.as_str()))
+    }
+
+    /// Check if a reference passes the filter expression, if any
+    pub fn matches_reference(&self, kind: ReferenceKind, file: &str, name: Option<&str>) -> bool {
+        self.expr.as_ref().map_or(true, |expr| {
+            expr.matches(&FilterSubject {
+                kind: reference_kind_str(kind),
+                file,
+                name,
+            })
+        })
+    }
+
+    /// Check if a file is excluded as a vendored dependency
+    pub fn excludes_vendored(&self, file_path: &str) -> bool {
+        self.exclude_vendor && is_vendored_path(file_path)
+    }
+}
+
+/// Depth limit for traces when no depth is given
+pub const DEFAULT_MAX_DEPTH: usize = 10;
+
+/// Hops followed by transitive references when no depth is given
+pub const TRANSITIVE_DEFAULT_DEPTH: usize = 2;
+
+/// How to narrow a symbol name that has several definitions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefinitionChoice {
+    /// Trace every definition together
+    All,
+    /// Trace the first definition by file and line
+    First,
+    /// Ask the user to pick one
+    Interactive,
+}
+
+/// Settings for [`run_operation`], one per `greppy trace` flag
+#[derive(Debug, Clone, PartialEq)]
+pub struct OperationOptions {
+    /// Maximum trace depth (hops for transitive references)
+    pub max_depth: usize,
+    /// Only direct callers in a trace
+    pub direct: bool,
+    /// Which definitions of an ambiguous symbol to trace
+    pub definition: DefinitionChoice,
+    /// Trace all definitions together instead of one section each
+    pub merge_definitions: bool,
+    /// Collapse callee paths that share a tail
+    pub dedupe: bool,
+    /// Maximum number of results
+    pub limit: Option<usize>,
+    /// Suggest likely replacements for dead symbols
+    pub xref: bool,
+    /// Attach git blame dates to statistics
+    pub with_dates: bool,
+    /// Cycle detection algorithm: tarjan or dfs
+    pub algorithm: String,
+    /// Build the cycle graph from imports instead of calls
+    pub imports: bool,
+    /// Lines of code context around each reference
+    pub context: u32,
+    /// Use the enclosing statement as context where tree-sitter can find it
+    pub context_lang_aware: bool,
+    /// Attach a content hash of each referenced file
+    pub with_hashes: bool,
+    /// Safety cap on pattern matches
+    pub max_results: usize,
+    /// Worker threads for pattern scanning (`None` = all cores)
+    pub workers: Option<usize>,
+    /// Stop a pattern scan at its first match
+    pub first_match_only: bool,
+    /// Only references after this line
+    pub after_line: Option<u32>,
+    /// Maximum references per file
+    pub limit_per_file: Option<usize>,
+    /// Group references (`directory`)
+    pub group_by: Option<String>,
+    /// Leading path components kept when grouping by directory
+    pub group_depth: usize,
+    /// Report the callee and argument index of references passed as arguments
+    pub arg_positions: bool,
+    /// Report the byte offset of each reference
+    pub byte_offsets: bool,
+    /// Flag references whose target is deprecated
+    pub annotate_deprecated: bool,
+    /// Keep only references to deprecated symbols
+    pub deprecated_only: bool,
+    /// Drop references below this confidence
+    pub min_confidence: f32,
+    /// Git range whose renames are followed back to earlier names
+    pub follow_rename: Option<String>,
+    /// Also report references from callers of callers
+    pub transitive: bool,
+    /// Drop references inside comments and string literals
+    pub exclude_comments: bool,
+}
+
+impl Default for OperationOptions {
+    /// The settings of a bare `greppy trace`
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_MAX_DEPTH,
+            direct: false,
+            definition: DefinitionChoice::All,
+            merge_definitions: true,
+            dedupe: false,
+            limit: None,
+            xref: false,
+            with_dates: false,
+            algorithm: "tarjan".to_string(),
+            imports: false,
+            context: 0,
+            context_lang_aware: false,
+            with_hashes: false,
+            max_results: 10_000,
+            workers: None,
+            first_match_only: false,
+            after_line: None,
+            limit_per_file: None,
+            group_by: None,
+            group_depth: 2,
+            arg_positions: false,
+            byte_offsets: false,
+            annotate_deprecated: false,
+            deprecated_only: false,
+            min_confidence: 0.0,
+            follow_rename: None,
+            transitive: false,
+            exclude_comments: false,
+        }
+    }
+}
+
+/// Run one operation against a loaded index, without printing
+///
+/// Source files (context lines, hashes, pattern scans) are read from
+/// `project.root`.
+pub async fn run_operation(
+    project: &Project,
+    index: &SemanticIndex,
+    operation: &TraceOperation,
+    options: &OperationOptions,
+    filter: &TraceFilter,
+) -> Result<OperationResult> {
+    let merge = options.merge_definitions;
+    let result = match operation {
+        TraceOperation::Trace(symbol) => {
+            info!(symbol = %symbol, "Tracing symbol invocations");
+            OperationResult::Trace(
+                trace_symbol_cmd(
+                    index,
+                    symbol,
+                    options.max_depth,
+                    options.direct,
+                    options.definition,
+                    merge,
+                    filter,
+                )
+                .await?,
+            )
+        }
+        TraceOperation::Refs { symbol, kind } => {
+            info!(symbol = %symbol, ?kind, "Finding references");
+            OperationResult::Refs(
+                find_refs_cmd(project, index, symbol, *kind, options, filter).await?,
+            )
+        }
+        TraceOperation::Callers(symbol) => {
+            info!(symbol = %symbol, "Finding callers");
+            OperationResult::Callers(
+                find_callers_cmd(index, symbol, options.max_depth, merge, filter).await?,
+            )
+        }
+        TraceOperation::Callees(symbol) => {
+            info!(symbol = %symbol, "Finding callees");
+            OperationResult::Callees(
+                find_callees_cmd(
+                    index,
+                    symbol,
+                    options.max_depth,
+                    options.dedupe,
+                    merge,
+                    filter,
+                )
+                .await?,
+            )
+        }
+        TraceOperation::CallHierarchy(symbol) => {
+            info!(symbol = %symbol, "Building call hierarchy");
+            OperationResult::CallHierarchy(
+                find_call_hierarchy_cmd(index, symbol, options.max_depth).await?,
+            )
+        }
+        TraceOperation::Neighborhood(symbol) => {
+            info!(symbol = %symbol, "Finding neighborhood");
+            OperationResult::Neighborhood(find_neighborhood_cmd(index, symbol, filter).await?)
+        }
+        TraceOperation::Path { from, to } => {
+            info!(from = %from, to = %to, "Finding call path");
+            OperationResult::CallPath(find_call_path_cmd(index, from, to).await?)
+        }
+        TraceOperation::Type(type_name) => {
+            info!(type_name = %type_name, "Tracing type usage");
+            OperationResult::TypeUsage(
+                find_refs_cmd(
+                    project,
+                    index,
+                    type_name,
+                    Some(ReferenceKind::TypeAnnotation),
+                    options,
+                    filter,
+                )
+                .await?,
+            )
+        }
+        TraceOperation::Module(module) => {
+            info!(module = %module, "Tracing module");
+            OperationResult::Module(trace_module_cmd(index, module, filter).await?)
+        }
+        TraceOperation::Pattern(pattern) => {
+            info!(pattern = %pattern, "Tracing pattern");
+            OperationResult::Pattern(
+                trace_pattern_cmd(project, index, pattern, options, filter).await?,
+            )
+        }
+        TraceOperation::Flow(symbol) => {
+            info!(symbol = %symbol, "Tracing data flow");
+            OperationResult::Flow(trace_flow_cmd(project, index, symbol, options, filter).await?)
+        }
+        TraceOperation::Impact(symbol) => {
+            info!(symbol = %symbol, "Analyzing impact");
+            OperationResult::Impact(
+                analyze_impact_cmd(index, symbol, options.max_depth, filter).await?,
+            )
+        }
+        TraceOperation::Scope(location) => {
+            info!(location = %location, "Analyzing scope");
+            OperationResult::Scope(analyze_scope_cmd(project, index, location, filter).await?)
+        }
+        TraceOperation::DeadCode => {
+            info!("Finding dead code");
+            OperationResult::DeadCode(
+                find_dead_code_cmd(&project.root, index, options.limit, filter, options.xref)
+                    .await?,
+            )
+        }
+        TraceOperation::Deprecated => {
+            info!("Finding deprecated symbols");
+            OperationResult::Deprecated(find_deprecated_cmd(index, options.limit, filter).await?)
+        }
+        TraceOperation::UnusedParams => {
+            info!("Finding unused parameters");
+            OperationResult::UnusedParams(
+                find_unused_params_cmd(index, options.limit, filter).await?,
+            )
+        }
+        TraceOperation::Shadows => {
+            info!("Finding shadowed variables");
+            OperationResult::Shadows(find_shadows_cmd(index, options.limit, filter).await?)
+        }
+        TraceOperation::Stubs => {
+            info!("Finding stub functions");
+            OperationResult::Stubs(find_stubs_cmd(project, index, options.limit, filter).await?)
+        }
+        TraceOperation::Hotspots => {
+            info!("Finding hotspots");
+            OperationResult::Hotspots(
+                find_hotspots_cmd(project, index, options.limit, filter).await?,
+            )
+        }
+        TraceOperation::Stats => {
+            info!("Computing statistics");
+            OperationResult::Stats(
+                compute_stats_cmd(project, index, filter, options.with_dates).await?,
+            )
+        }
+        TraceOperation::Cycles => {
+            info!("Finding circular dependencies");
+            OperationResult::Cycles(
+                find_cycles_cmd(index, &options.algorithm, options.imports, filter).await?,
+            )
+        }
+    };
+    Ok(result)
+}
+
+// =============================================================================
+// PHASE 1: CODE CONTEXT ENGINE
+// =============================================================================
+
+/// Get code context for a reference
+/// Parsed files for `--context-lang-aware`, `None` where tree-sitter can't help
+type StatementLocators = HashMap<PathBuf, Option<StatementLocator>>;
+
+/// Context for a reference: the enclosing statement when `--context-lang-aware`
+/// can find one, otherwise `--context` lines
+fn reference_context(
+    cache: &mut FileCache,
+    locators: &mut StatementLocators,
+    file: &Path,
+    line: u32,
+    column: u16,
+    options: &OperationOptions,
+) -> String {
+    if options.context_lang_aware {
+        let span = statement_locator(cache, locators, file).and_then(|l| l.span(line, column));
+        if let Some(ctx) = span.and_then(|(start, end)| {
+            cache.get_context(file, line, line - start.min(line), end.max(line) - line)
+        }) {
+            return ctx.format(false);
+        }
+    }
+    get_code_context(cache, file, line, options.context)
+}
+
+/// Parsed tree for `file`, parsed on first use
+fn statement_locator<'a>(
+    cache: &mut FileCache,
+    locators: &'a mut StatementLocators,
+    file: &Path,
+) -> Option<&'a StatementLocator> {
+    locators
+        .entry(file.to_path_buf())
+        .or_insert_with(|| {
+            let line_count = cache.line_count(file)? as u32;
+            let lines = cache.get_range(file, 1, line_count)?;
+            StatementLocator::parse(&lines.join("\n"), detect_language(file))
+        })
+        .as_ref()
+}
+
+/// Comment and string spans per file, found on first use
+type LiteralSpanCache = HashMap<PathBuf, LiteralSpans>;
+
+/// Whether the match at `line`/`column` of `file` is inside a comment or
+/// string literal (files that can't be read have none)
+fn in_comment_or_string(
+    cache: &mut FileCache,
+    spans: &mut LiteralSpanCache,
+    file: &Path,
+    line: u32,
+    column: u16,
+) -> bool {
+    spans
+        .entry(file.to_path_buf())
+        .or_insert_with(|| {
+            cache
+                .line_count(file)
+                .and_then(|count| cache.get_range(file, 1, count as u32))
+                .map(|lines| LiteralSpans::parse(&lines.join("\n"), detect_language(file)))
+                .unwrap_or_default()
+        })
+        .contains(line, column)
+}
+
+fn get_code_context(cache: &mut FileCache, file: &Path, line: u32, context_lines: u32) -> String {
+    if context_lines == 0 {
+        // Just get the single line
+        cache
+            .get_line(file, line)
+            .map(|l| l.trim().to_string())
+            .unwrap_or_else(|| format!("// line {}", line))
+    } else {
+        // Get context with surrounding lines
+        cache
+            .get_context(file, line, context_lines, context_lines)
+            .map(|ctx| ctx.format(false))
+            .unwrap_or_else(|| format!("// line {}", line))
+    }
+}
+
+// =============================================================================
+// PHASE 2: COMPLETE REFERENCE SYSTEM
+// =============================================================================
+
+/// Find references to a symbol with full context
+async fn find_refs_cmd(
+    project: &Project,
+    index: &SemanticIndex,
+    symbol: &str,
+    kind_filter: Option<ReferenceKind>,
+    options: &OperationOptions,
+    filter: &TraceFilter,
+) -> Result<RefsResult> {
+    debug!(symbol = %symbol, ?kind_filter, ?filter, "find_refs");
+
+    let mut cache = FileCache::new(&project.root);
+
+    if let Some(range) = &options.follow_rename {
+        let old_names = previous_names(&git_rename_pairs(&project.root, range)?, symbol);
+        debug!(?old_names, "follow_rename");
+        return Ok(collect_renamed_refs(
+            index,
+            &mut cache,
+            symbol,
+            &old_names,
+            kind_filter,
+            options,
+            filter,
+        ));
+    }
+
+    if options.transitive {
+        return Ok(collect_transitive_refs(
+            index,
+            &mut cache,
+            symbol,
+            kind_filter,
+            options,
+            filter,
+        ));
+    }
+
+    Ok(collect_refs(
+        index,
+        &mut cache,
+        symbol,
+        kind_filter,
+        options,
+        filter,
+    ))
+}
+
+/// Identifier renames made in a git range, as `(old, new)` pairs
+fn git_rename_pairs(root: &Path, range: &str) -> Result<Vec<(String, String)>> {
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(["log", "-M", "-p", "-U0", "--format="])
+        .arg(range)
+        .output()
+        .map_err(|e| Error::SearchError {
+            message: format!("Failed to run git: {}", e),
+        })?;
+    if !output.status.success() {
+        return Err(Error::SearchError {
+            message: format!(
+                "git log {} failed: {}",
+                range,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        });
+    }
+    Ok(rename_pairs(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Identifier renames in a unified diff, as `(old, new)` pairs
+///
+/// A removed line and the added line at the same position of its hunk are a
+/// rename when they differ only in one identifier, every occurrence of which
+/// was replaced.
+fn rename_pairs(diff: &str) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    let mut removed: Vec<&str> = Vec::new();
+    let mut added: Vec<&str> = Vec::new();
+    let mut in_hunk = false;
+
+    let mut flush = |removed: &mut Vec<&str>, added: &mut Vec<&str>| {
+        for (old_line, new_line) in removed.iter().zip(added.iter()) {
+            if let Some(pair) = renamed_identifier(old_line, new_line) {
+                if !pairs.contains(&pair) {
+                    pairs.push(pair);
+                }
+            }
+        }
+        removed.clear();
+        added.clear();
+    };
+
+    for line in diff.lines() {
+        if line.starts_with("diff ") {
+            in_hunk = false;
+        } else if line.starts_with("@@") {
+            in_hunk = true;
+        }
+        match line.as_bytes().first() {
+            Some(b'-') if in_hunk => {
+                if !added.is_empty() {
+                    flush(&mut removed, &mut added);
+                }
+                removed.push(&line[1..]);
+            }
+            Some(b'+') if in_hunk => added.push(&line[1..]),
+            _ => flush(&mut removed, &mut added),
+        }
+    }
+    flush(&mut removed, &mut added);
+
+    pairs
+}
+
+/// The single identifier renamed between two versions of a line
+fn renamed_identifier(old_line: &str, new_line: &str) -> Option<(String, String)> {
+    static SEGMENT: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"[A-Za-z_][A-Za-z0-9_]*|[^A-Za-z_]+").unwrap());
+    let is_identifier = |s: &str| s.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_');
+
+    let old: Vec<&str> = SEGMENT.find_iter(old_line).map(|m| m.as_str()).collect();
+    let new: Vec<&str> = SEGMENT.find_iter(new_line).map(|m| m.as_str()).collect();
+    if old.len() != new.len() {
+        return None;
+    }
+
+    let mut rename: Option<(&str, &str)> = None;
+    for (&o, &n) in old.iter().zip(&new) {
+        if o == n {
+            // An unchanged occurrence means the name was not replaced throughout
+            if rename.is_some_and(|(from, _)| from == o) {
+                return None;
+            }
+            continue;
+        }
+        if !is_identifier(o) || !is_identifier(n) {
+            return None;
+        }
+        match rename {
+            None => rename = Some((o, n)),
+            Some(pair) if pair == (o, n) => {}
+            Some(_) => return None,
+        }
+    }
+
+    rename.map(|(o, n)| (o.to_string(), n.to_string()))
+}
+
+/// Names `symbol` had before being renamed, following chains of renames
+fn previous_names(pairs: &[(String, String)], symbol: &str) -> Vec<String> {
+    let mut names = vec![symbol.to_string()];
+    let mut i = 0;
+    while i < names.len() {
+        for (old, new) in pairs {
+            if *new == names[i] && !names.contains(old) {
+                names.push(old.clone());
+            }
+        }
+        i += 1;
+    }
+    names.remove(0);
+    names
+}
+
+/// Collect references to a symbol from a loaded index
+fn collect_refs(
+    index: &SemanticIndex,
+    cache: &mut FileCache,
+    symbol: &str,
+    kind_filter: Option<ReferenceKind>,
+    options: &OperationOptions,
+    filter: &TraceFilter,
+) -> RefsResult {
+    let mut result = collect_all_refs(
+        index,
+        cache,
+        RefTarget::Name(symbol),
+        kind_filter,
+        options,
+        filter,
+    );
+    finish_refs(&mut result, options, cache.project_root());
+    result
+}
+
+/// Collect references to a symbol and to the names it had before a rename
+///
+/// Each reference records the name it used in `referenced_as`.
+fn collect_renamed_refs(
+    index: &SemanticIndex,
+    cache: &mut FileCache,
+    symbol: &str,
+    old_names: &[String],
+    kind_filter: Option<ReferenceKind>,
+    options: &OperationOptions,
+    filter: &TraceFilter,
+) -> RefsResult {
+    let mut result = collect_all_refs(
+        index,
+        cache,
+        RefTarget::Name(symbol),
+        kind_filter,
+        options,
+        filter,
+    );
+    for r in &mut result.references {
+        r.referenced_as = Some(symbol.to_string());
+    }
+
+    for old_name in old_names {
+        let older = collect_all_refs(
+            index,
+            cache,
+            RefTarget::Name(old_name),
+            kind_filter,
+            options,
+            filter,
+        );
+        if result.defined_at.is_none() {
+            result.defined_at = older.defined_at.clone();
+            result.symbol_kind = older.symbol_kind.clone();
+            result.symbol_id = older.symbol_id;
+            result.stable_id = older.stable_id.clone();
+        }
+        merge_refs(&mut result, older, old_name, None);
+    }
+
+    finish_refs(&mut result, options, cache.project_root());
+    result
+}
+
+/// Collect references to a symbol, then references to the definitions those
+/// references sit in, out to `--max-depth` hops
+///
+/// Later hops follow the enclosing definition by id, so a same-named symbol
+/// elsewhere is not expanded. Each reference records its hop count in
+/// `depth` and the symbol it referenced in `referenced_as`.
+fn collect_transitive_refs(
+    index: &SemanticIndex,
+    cache: &mut FileCache,
+    symbol: &str,
+    kind_filter: Option<ReferenceKind>,
+    options: &OperationOptions,
+    filter: &TraceFilter,
+) -> RefsResult {
+    let mut result = collect_all_refs(
+        index,
+        cache,
+        RefTarget::Name(symbol),
+        kind_filter,
+        options,
+        filter,
+    );
+    for r in &mut result.references {
+        r.referenced_as = Some(symbol.to_string());
+        r.depth = Some(1);
+    }
+
+    let mut visited: HashSet<u32> = index.resolve_symbols(symbol).into_iter().collect();
+    for depth in 2..=options.max_depth as u32 {
+        // Definitions containing the previous hop's references
+        let mut frontier: Vec<u32> = Vec::new();
+        for r in &result.references {
+            if r.depth != Some(depth - 1) {
+                continue;
+            }
+            let enclosing = index
+                .file_id_for_path(Path::new(&r.file))
+                .and_then(|file_id| find_enclosing_symbol_id(index, file_id, r.line));
+            if let Some(id) = enclosing {
+                if visited.insert(id) {
+                    frontier.push(id);
+                }
+            }
+        }
+        if frontier.is_empty() {
+            break;
+        }
+
+        for id in frontier {
+            let found = collect_all_refs(
+                index,
+                cache,
+                RefTarget::Symbol(id),
+                kind_filter,
+                options,
+                filter,
+            );
+            let name = found.symbol.clone();
+            merge_refs(&mut result, found, &name, Some(depth));
+        }
+    }
+
+    finish_refs(&mut result, options, cache.project_root());
+    result
+}
+
+/// Add references found under another name to `result`
+///
+/// Locations already in `result` are skipped; the rest are labeled with the
+/// name they used and counted.
+fn merge_refs(result: &mut RefsResult, found: RefsResult, name: &str, depth: Option<u32>) {
+    for mut r in found.references {
+        let duplicate = result
+            .references
+            .iter()
+            .any(|seen| seen.file == r.file && seen.line == r.line && seen.column == r.column);
+        if duplicate {
+            continue;
+        }
+        *result
+            .by_kind
+            .entry(reference_kind_str(r.kind).to_string())
+            .or_insert(0) += 1;
+        *result.by_file.entry(r.file.clone()).or_insert(0) += 1;
+        r.referenced_as = Some(name.to_string());
+        r.depth = depth;
+        result.references.push(r);
+    }
+    for warning in found.warnings {
+        if !result.warnings.contains(&warning) {
+            result.warnings.push(warning);
+        }
+    }
+}
+
+/// Every reference to a symbol, unsorted and before any limits
+fn collect_all_refs(
+    index: &SemanticIndex,
+    cache: &mut FileCache,
+    target: RefTarget<'_>,
+    kind_filter: Option<ReferenceKind>,
+    options: &OperationOptions,
+    filter: &TraceFilter,
+) -> RefsResult {
+    let mut references = Vec::new();
+    let mut result = visit_refs(
+        index,
+        cache,
+        target,
+        kind_filter,
+        options,
+        filter,
+        &mut |r| references.push(r),
+    );
+    result.total_refs = references.len();
+    result.references = references;
+    result
+}
+
+/// What a reference lookup is looking for
+#[derive(Debug, Clone, Copy)]
+enum RefTarget<'a> {
+    /// Every definition with this name, plus tokens spelled the same way
+    Name(&'a str),
+    /// One definition, by symbol id
+    Symbol(u32),
+}
+
+/// Hand each reference to a symbol to `emit` as it is found
+///
+/// The returned result carries the definition, counts and warnings but no
+/// references.
+fn visit_refs(
+    index: &SemanticIndex,
+    cache: &mut FileCache,
+    target: RefTarget<'_>,
+    kind_filter: Option<ReferenceKind>,
+    options: &OperationOptions,
+    filter: &TraceFilter,
+    emit: &mut dyn FnMut(ReferenceInfo),
+) -> RefsResult {
+    // Locations already reported, so name matches don't repeat AST references
+    let mut seen: HashSet<(u16, u32, u16)> = HashSet::new();
+    let mut by_kind: HashMap<String, usize> = HashMap::new();
+    let mut by_file: HashMap<String, usize> = HashMap::new();
+    let mut warnings = Vec::new();
+    let mut locators = StatementLocators::new();
+    let mut literal_spans = LiteralSpanCache::new();
+
+    // Find symbol IDs matching the name
+    let (symbol, symbol_ids) = match target {
+        RefTarget::Name(name) => (name, index.resolve_symbols(name)),
+        RefTarget::Symbol(id) => (
+            index
+                .symbol(id)
+                .and_then(|s| index.symbol_name(s))
+                .unwrap_or_default(),
+            std::iter::once(id).collect(),
+        ),
+    };
+
+    // Get definition location from first matching symbol
+    let defined_at = symbol_ids.first().and_then(|&id| {
+        let sym = index.symbol(id)?;
+        let file = index.file_path(sym.file_id)?;
+        Some(format!("{}:{}", file.display(), sym.start_line))
+    });
+
+    // Get symbol kind
+    let symbol_kind = symbol_ids
+        .first()
+        .and_then(|&id| index.symbol(id))
+        .map(|s| symbol_kind_str(s.symbol_kind()).to_string());
+
+    let annotate_deprecated = options.annotate_deprecated || options.deprecated_only;
+    let is_deprecated = |id: u32| index.symbol(id).is_some_and(|s| s.is_deprecated());
+    // Name-only matches cannot tell definitions apart: any deprecated one counts
+    let any_deprecated = symbol_ids.iter().any(|&id| is_deprecated(id));
+
+    // Find all references to all matching symbols (via Reference table)
+    for &sym_id in &symbol_ids {
+        let refs = find_refs(index, sym_id);
+
+        for ref_ctx in refs {
+            // Convert RefKind to ReferenceKind
+            let kind = match ref_ctx.reference.ref_kind() {
+                RefKind::Read => ReferenceKind::Read,
+                RefKind::Write => ReferenceKind::Write,
+                RefKind::Call => ReferenceKind::Call,
+                RefKind::TypeAnnotation => ReferenceKind::TypeAnnotation,
+                RefKind::Import => ReferenceKind::Import,
+                RefKind::Export => ReferenceKind::Export,
+                RefKind::Construction => ReferenceKind::Call, // Treat construction as call-like
+                RefKind::Inheritance | RefKind::Decorator | RefKind::Unknown => ReferenceKind::Read,
+            };
+
+            // Apply kind filter
+            if let Some(filter_kind) = kind_filter {
+                if kind != filter_kind {
+                    continue;
+                }
+            }
+
+            // Get file path
+            let file_path = index
+                .file_path(ref_ctx.file_id)
+                .map(|p| p.to_path_buf())
+                .unwrap_or_default();
+            let file = file_path.to_string_lossy().to_string();
+
+            // Apply universal filter (path, type, name)
+            let target_deprecated = annotate_deprecated && is_deprecated(sym_id);
+            if !filter.matches_path(&file)
+                || AST_REF_CONFIDENCE < options.min_confidence
+                || before_cutoff(options, ref_ctx.line)
+                || (options.deprecated_only && !target_deprecated)
+            {
+                continue;
+            }
+            if options.exclude_comments
+                && in_comment_or_string(
+                    cache,
+                    &mut literal_spans,
+                    &file_path,
+                    ref_ctx.line,
+                    ref_ctx.column,
+                )
+            {
+                continue;
+            }
+
+            // Find enclosing symbol
+            let enclosing_symbol = find_enclosing_symbol(index, ref_ctx.file_id, ref_ctx.line);
+            if !filter.matches_reference(kind, &file, enclosing_symbol.as_deref()) {
+                continue;
+            }
+
+            // Get code context
+            note_unreadable(cache, &file_path, &mut warnings);
+            let context = reference_context(
+                cache,
+                &mut locators,
+                &file_path,
+                ref_ctx.line,
+                ref_ctx.column,
+                options,
+            );
+
+            // Count by kind and file
+            *by_kind
+                .entry(reference_kind_str(kind).to_string())
+                .or_insert(0) += 1;
+            *by_file.entry(file.clone()).or_insert(0) += 1;
+
+            let byte_offset = if options.byte_offsets {
+                cache.byte_offset(&file_path, ref_ctx.line, ref_ctx.column)
+            } else {
+                None
+            };
+            let argument = if options.arg_positions {
+                statement_locator(cache, &mut locators, &file_path)
+                    .and_then(|l| l.argument_at(ref_ctx.line, ref_ctx.column))
+            } else {
+                None
+            };
+
+            seen.insert((ref_ctx.file_id, ref_ctx.line, ref_ctx.column));
+            emit(ReferenceInfo {
+                file,
+                line: ref_ctx.line,
+                column: ref_ctx.column,
+                kind,
+                context,
+                enclosing_symbol,
+                confidence: AST_REF_CONFIDENCE,
+                byte_offset,
+                arg_index: argument.as_ref().map(|a| a.index),
+                callee: argument.map(|a| a.callee),
+                target_deprecated,
+                referenced_as: None,
+                depth: None,
+            });
+        }
+    }
+
+    // Search tokens by name (catches variables, params, field names)
+    let token_ids = match target {
+        RefTarget::Name(name) => index.tokens_by_name(name),
+        RefTarget::Symbol(_) => None,
+    };
+    if let Some(token_ids) = token_ids {
+        for &token_id in token_ids {
+            if let Some(token) = index.token(token_id) {
+                let file_path = index
+                    .file_path(token.file_id)
+                    .map(|p| p.to_path_buf())
+                    .unwrap_or_default();
+                let file = file_path.to_string_lossy().to_string();
+
+                // Skip if we already have this location
+                if seen.contains(&(token.file_id, token.line, token.column)) {
+                    continue;
+                }
+
+                // Apply path filter; name-only matches may be unrelated locals
+                let target_deprecated = annotate_deprecated && any_deprecated;
+                if !filter.matches_path(&file)
+                    || TOKEN_REF_CONFIDENCE < options.min_confidence
+                    || before_cutoff(options, token.line)
+                    || (options.deprecated_only && !target_deprecated)
+                {
+                    continue;
+                }
+
+                let kind = match token.token_kind() {
+                    crate::trace::TokenKind::Call => ReferenceKind::Call,
+                    _ => ReferenceKind::Read,
+                };
+
+                // Apply kind filter
+                if let Some(filter_kind) = kind_filter {
+                    if kind != filter_kind {
+                        continue;
+                    }
+                }
+                if options.exclude_comments
+                    && in_comment_or_string(
+                        cache,
+                        &mut literal_spans,
+                        &file_path,
+                        token.line,
+                        token.column,
+                    )
+                {
+                    continue;
+                }
+
+                let enclosing_symbol = find_enclosing_symbol(index, token.file_id, token.line);
+                if !filter.matches_reference(kind, &file, enclosing_symbol.as_deref()) {
+                    continue;
+                }
+                note_unreadable(cache, &file_path, &mut warnings);
+                let context = reference_context(
+                    cache,
+                    &mut locators,
+                    &file_path,
+                    token.line,
+                    token.column,
+                    options,
+                );
+
+                *by_kind
+                    .entry(reference_kind_str(kind).to_string())
+                    .or_insert(0) += 1;
+                *by_file.entry(file.clone()).or_insert(0) += 1;
+
+                let byte_offset = if options.byte_offsets {
+                    cache.byte_offset(&file_path, token.line, token.column)
+                } else {
+                    None
+                };
+                let argument = if options.arg_positions {
+                    statement_locator(cache, &mut locators, &file_path)
+                        .and_then(|l| l.argument_at(token.line, token.column))
+                } else {
+                    None
+                };
+
+                seen.insert((token.file_id, token.line, token.column));
+                emit(ReferenceInfo {
+                    file,
+                    line: token.line,
+                    column: token.column,
+                    kind,
+                    context,
+                    enclosing_symbol,
+                    confidence: TOKEN_REF_CONFIDENCE,
+                    byte_offset,
+                    arg_index: argument.as_ref().map(|a| a.index),
+                    callee: argument.map(|a| a.callee),
+                    target_deprecated,
+                    referenced_as: None,
+                    depth: None,
+                });
+            }
+        }
+    }
+
+    RefsResult {
+        symbol: symbol.to_string(),
+        defined_at,
+        symbol_kind,
+        total_refs: 0,
+        references: Vec::new(),
+        by_kind,
+        by_confidence: HashMap::new(),
+        by_directory: Vec::new(),
+        by_file,
+        source_hashes: HashMap::new(),
+        warnings,
+        symbol_id: symbol_ids.first().copied(),
+        stable_id: symbol_ids
+            .first()
+            .and_then(|&id| index.stable_symbol_id(id)),
+    }
+}
+
+/// Sort collected references, apply the limits and fill in the rollups
+fn finish_refs(result: &mut RefsResult, options: &OperationOptions, root: &Path) {
+    let references = &mut result.references;
+
+    // Sort by file and line
+    references.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+
+    // Cap per file first (by_file keeps the true totals), then apply global limit
+    if let Some(per_file) = options.limit_per_file {
+        let mut shown: HashMap<String, usize> = HashMap::new();
+        references.retain(|r| {
+            let count = shown.entry(r.file.clone()).or_insert(0);
+            *count += 1;
+            *count <= per_file
+        });
+    }
+
+    if let Some(limit) = options.limit {
+        references.truncate(limit);
+    }
+
+    let mut by_confidence = confidence_buckets();
+    for r in references.iter() {
+        *by_confidence
+            .entry(confidence_label(r.confidence).to_string())
+            .or_insert(0) += 1;
+    }
+
+    result.total_refs = references.len();
+    result.by_confidence = by_confidence;
+    if options.group_by.as_deref() == Some("directory") {
+        result.by_directory = group_by_directory(&result.by_file, options.group_depth);
+    }
+    if options.with_hashes {
+        attach_source_hashes(result, root);
+    }
+}
+
+/// Record the content hash of every file with references (`--with-hashes`)
+///
+/// Lets callers that cache results notice when a file has changed since.
+/// Files that can't be read get no hash.
+fn attach_source_hashes(result: &mut RefsResult, root: &Path) {
+    for file in result.by_file.keys() {
+        if let Ok(content) = std::fs::read_to_string(root.join(file)) {
+            result
+                .source_hashes
+                .insert(file.clone(), format!("{:016x}", content_hash(&content)));
+        }
+    }
+    for group in &mut result.by_directory {
+        for file in &mut group.files {
+            file.source_hash = result.source_hashes.get(&file.file).cloned();
+        }
+    }
+}
+
+/// Write `--refs` JSON while references are found (`--stream`)
+///
+/// References come out in index order rather than sorted by file and line,
+/// and are dropped once written, so memory does not grow with their number.
+pub(crate) fn stream_refs<W: Write>(
+    index: &SemanticIndex,
+    cache: &mut FileCache,
+    symbol: &str,
+    kind_filter: Option<ReferenceKind>,
+    options: &OperationOptions,
+    filter: &TraceFilter,
+    out: W,
+) -> Result<W> {
+    let mut writer = JsonArrayWriter::begin(out, "references")?;
+    let mut shown: HashMap<String, usize> = HashMap::new();
+    let mut by_confidence = confidence_buckets();
+    let mut failed = None;
+
+    let mut result = visit_refs(
+        index,
+        cache,
+        RefTarget::Name(symbol),
+        kind_filter,
+        options,
+        filter,
+        &mut |r| {
+            if failed.is_some() || options.limit.is_some_and(|limit| writer.len() >= limit) {
+                return;
+            }
+            if let Some(per_file) = options.limit_per_file {
+                let count = shown.entry(r.file.clone()).or_insert(0);
+                *count += 1;
+                if *count > per_file {
+                    return;
+                }
+            }
+            *by_confidence
+                .entry(confidence_label(r.confidence).to_string())
+                .or_insert(0) += 1;
+            if let Err(e) = writer.push(&r) {
+                failed = Some(e);
+            }
+        },
+    );
+    if let Some(e) = failed {
+        return Err(e.into());
+    }
+
+    result.total_refs = writer.len();
+    result.by_confidence = by_confidence;
+    if options.group_by.as_deref() == Some("directory") {
+        result.by_directory = group_by_directory(&result.by_file, options.group_depth);
+    }
+    if options.with_hashes {
+        attach_source_hashes(&mut result, cache.project_root());
+    }
+    Ok(writer.finish(&result)?)
+}
+
+/// Roll per-file reference counts up to their leading `depth` directories
+///
+/// Directories are ordered by reference count, then name; files within a
+/// directory likewise. Files at the project root group under `.`.
+fn group_by_directory(by_file: &HashMap<String, usize>, depth: usize) -> Vec<DirectoryGroup> {
+    let mut groups: HashMap<String, Vec<FileCount>> = HashMap::new();
+    for (file, &count) in by_file {
+        let parent = Path::new(file).parent().unwrap_or(Path::new(""));
+        let directory: PathBuf = parent.components().take(depth.max(1)).collect();
+        let directory = if directory.as_os_str().is_empty() {
+            ".".to_string()
+        } else {
+            directory.display().to_string()
+        };
+        groups.entry(directory).or_default().push(FileCount {
+            file: file.clone(),
+            count,
+            source_hash: None,
+        });
+    }
+
+    let mut groups: Vec<DirectoryGroup> = groups
+        .into_iter()
+        .map(|(directory, mut files)| {
+            files.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.file.cmp(&b.file)));
+            DirectoryGroup {
+                directory,
+                count: files.iter().map(|f| f.count).sum(),
+                files,
+            }
+        })
+        .collect();
+    groups.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.directory.cmp(&b.directory))
+    });
+    groups
+}
+
+/// Whether a reference line falls before `--after-line`
+fn before_cutoff(options: &OperationOptions, line: u32) -> bool {
+    options.after_line.is_some_and(|cutoff| line < cutoff)
+}
+
+/// Record a warning (once per file) when an indexed file can't be read anymore
+fn note_unreadable(cache: &mut FileCache, file_path: &Path, warnings: &mut Vec<String>) {
+    if cache.line_count(file_path).is_some() {
+        return;
+    }
+    let warning = format!("could not read {}", file_path.display());
+    if !warnings.contains(&warning) {
+        warnings.push(warning);
+    }
+}
+
+/// Confidence for references resolved through the AST reference table
+const AST_REF_CONFIDENCE: f32 = 1.0;
+
+/// Confidence for references found only by matching the token name
+const TOKEN_REF_CONFIDENCE: f32 = 0.5;
+
+/// Empty `by_confidence` counts, with both buckets present
+fn confidence_buckets() -> HashMap<String, usize> {
+    ["exact", "heuristic"]
+        .iter()
+        .map(|label| (label.to_string(), 0))
+        .collect()
+}
+
+/// `by_confidence` bucket of a reference: `exact` when AST-resolved
+fn confidence_label(confidence: f32) -> &'static str {
+    if confidence >= AST_REF_CONFIDENCE {
+        "exact"
+    } else {
+        "heuristic"
+    }
+}
+
+/// Find the enclosing symbol for a given location
+fn find_enclosing_symbol(index: &SemanticIndex, file_id: u16, line: u32) -> Option<String> {
+    let id = find_enclosing_symbol_id(index, file_id, line)?;
+    index
+        .symbol(id)
+        .and_then(|sym| index.symbol_name(sym))
+        .map(|s| s.to_string())
+}
+
+/// Id of the innermost symbol whose span contains `line`
+fn find_enclosing_symbol_id(index: &SemanticIndex, file_id: u16, line: u32) -> Option<u32> {
+    let mut best: Option<(&crate::trace::Symbol, u32)> = None;
+
+    for symbol in &index.symbols {
+        if symbol.file_id == file_id && symbol.start_line <= line && symbol.end_line >= line {
+            let size = symbol.end_line - symbol.start_line;
+            match best {
+                None => best = Some((symbol, size)),
+                Some((_, best_size)) if size < best_size => best = Some((symbol, size)),
+                _ => {}
+            }
+        }
+    }
+
+    best.map(|(sym, _)| sym.id)
+}
+
+/// Find the closest symbol defined before a line (for matches outside any symbol)
+fn find_preceding_symbol(index: &SemanticIndex, file_id: u16, line: u32) -> Option<String> {
+    index
+        .symbols
+        .iter()
+        .filter(|s| s.file_id == file_id && s.start_line <= line)
+        .max_by_key(|s| s.start_line)
+        .and_then(|sym| index.symbol_name(sym).map(|s| s.to_string()))
+}
+
+// =============================================================================
+// PHASE 3: CALL GRAPH WITH FULL PRECISION
+// =============================================================================
+
+/// Find what calls a symbol (callers/incoming)
+async fn find_callers_cmd(
+    index: &SemanticIndex,
+    symbol: &str,
+    max_depth: usize,
+    merge: bool,
+    filter: &TraceFilter,
+) -> Result<TraceResult> {
+    debug!(symbol = %symbol, merge, "find_callers");
+
+    let symbol_ids = index.resolve_symbols(symbol);
+
+    Ok(if merge {
+        collect_callers(index, symbol, &symbol_ids, max_depth, filter)
+    } else {
+        definition_sections(index, symbol, &symbol_ids, |ids| {
+            collect_callers(index, symbol, ids, max_depth, filter)
+        })
+    })
+}
+
+/// Callers of the given definitions of `symbol`, merged into one result
+///
+/// Callers failing `filter` are neither reported nor traversed further.
+fn collect_callers(
+    index: &SemanticIndex,
+    symbol: &str,
+    symbol_ids: &[u32],
+    max_depth: usize,
+    filter: &TraceFilter,
+) -> TraceResult {
+    if symbol_ids.is_empty() {
+        return TraceResult {
+            symbol: symbol.to_string(),
+            defined_at: None,
+            kind: "unknown".to_string(),
+            invocation_paths: Vec::new(),
+            total_paths: 0,
+            entry_points: 0,
+            definitions: Vec::new(),
+            symbol_id: None,
+            stable_id: None,
+        };
+    }
+
+    let mut paths = Vec::new();
+    let mut visited = HashSet::new();
+
+    for &sym_id in symbol_ids {
+        collect_callers_recursive(
+            index,
+            sym_id,
+            &mut paths,
+            &mut visited,
+            Vec::new(),
+            max_depth,
+            filter,
+        );
+    }
+
+    let defined_at = symbol_ids.first().and_then(|&id| {
+        let sym = index.symbol(id)?;
+        let file = index.file_path(sym.file_id)?;
+        Some(format!("{}:{}", file.display(), sym.start_line))
+    });
+
+    let kind = symbol_ids
+        .first()
+        .and_then(|&id| index.symbol(id))
+        .map(|s| symbol_kind_str(s.symbol_kind()).to_string())
+        .unwrap_or_else(|| "function".to_string());
+
+    let entry_points = paths
+        .iter()
+        .map(|p| &p.entry_point)
+        .collect::<HashSet<_>>()
+        .len();
+
+    TraceResult {
+        symbol: symbol.to_string(),
+        defined_at,
+        kind,
+        invocation_paths: paths.clone(),
+        total_paths: paths.len(),
+        entry_points,
+        definitions: Vec::new(),
+        symbol_id: symbol_ids.first().copied(),
+        stable_id: symbol_ids
+            .first()
+            .and_then(|&id| index.stable_symbol_id(id)),
+    }
+}
+
+fn collect_callers_recursive(
+    index: &SemanticIndex,
+    sym_id: u32,
+    paths: &mut Vec<InvocationPath>,
+    visited: &mut HashSet<u32>,
+    current_chain: Vec<ChainStep>,
+    max_depth: usize,
+    filter: &TraceFilter,
+) {
+    if current_chain.len() >= max_depth {
+        return;
+    }
+
+    let callers: Vec<u32> = index
+        .callers(sym_id)
+        .iter()
+        .copied()
+        .filter(|&id| filter.matches_symbol_id(index, id))
+        .collect();
+    if callers.is_empty() && !current_chain.is_empty() {
+        // End of chain - record path
+        if let Some(sym) = index.symbol(sym_id) {
+            let name = index.symbol_name(sym).unwrap_or("<unknown>");
+            let file = index
+                .file_path(sym.file_id)
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_default();
+
+            let mut chain = current_chain.clone();
+            chain.push(ChainStep {
+                symbol: name.to_string(),
+                file: file.clone(),
+                line: sym.start_line,
+                column: None,
+                context: None,
+            });
+
+            paths.push(InvocationPath {
+                entry_point: format!("{} ({})", name, file),
+                entry_kind: symbol_kind_str(sym.symbol_kind()).to_string(),
+                chain,
+            });
+        }
+        return;
+    }
+
+    for caller_id in callers {
+        if visited.contains(&caller_id) {
+            continue;
+        }
+        visited.insert(caller_id);
+
+        if let Some(caller) = index.symbol(caller_id) {
+            let name = index.symbol_name(caller).unwrap_or("<unknown>");
+            let file = index
+                .file_path(caller.file_id)
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_default();
+
+            // Find call line from edge
+            let call_line = index
+                .edges
+                .iter()
+                .find(|e| e.from_symbol == caller_id && e.to_symbol == sym_id)
+                .map(|e| e.line)
+                .unwrap_or(caller.start_line);
+
+            let mut new_chain = current_chain.clone();
+            new_chain.push(ChainStep {
+                symbol: name.to_string(),
+                file,
+                line: call_line,
+                column: None,
+                context: None,
+            });
+
+            collect_callers_recursive(
+                index, caller_id, paths, visited, new_chain, max_depth, filter,
+            );
+        }
+    }
+}
+
+/// Build the incoming call tree for a symbol
+async fn find_call_hierarchy_cmd(
+    index: &SemanticIndex,
+    symbol: &str,
+    max_depth: usize,
+) -> Result<CallHierarchyResult> {
+    debug!(symbol = %symbol, max_depth, "find_call_hierarchy");
+
+    Ok(collect_call_hierarchy(index, symbol, max_depth))
+}
+
+/// Build a caller tree rooted at every definition of `symbol`
+///
+/// Each symbol's callers are expanded once; later occurrences (shared
+/// callers and recursion) are marked `see_above` with no children.
+fn collect_call_hierarchy(
+    index: &SemanticIndex,
+    symbol: &str,
+    max_depth: usize,
+) -> CallHierarchyResult {
+    let symbol_ids = index.resolve_symbols(symbol);
+    let mut expanded: HashSet<u32> = symbol_ids.iter().copied().collect();
+
+    let roots: Vec<CallHierarchyNode> = symbol_ids
+        .iter()
+        .filter_map(|&id| {
+            let mut node = call_hierarchy_node(index, id, None)?;
+            node.callers = call_hierarchy_callers(index, id, 1, max_depth, &mut expanded);
+            Some(node)
+        })
+        .collect();
+
+    CallHierarchyResult {
+        symbol: symbol.to_string(),
+        total_nodes: CallHierarchyNode::walk(&roots).len(),
+        roots,
+        max_depth,
+    }
+}
+
+/// Callers of `sym_id` at `depth`, expanding each symbol at most once
+fn call_hierarchy_callers(
+    index: &SemanticIndex,
+    sym_id: u32,
+    depth: usize,
+    max_depth: usize,
+    expanded: &mut HashSet<u32>,
+) -> Vec<CallHierarchyNode> {
+    let mut nodes = Vec::new();
+    for &caller_id in index.callers(sym_id) {
+        let call_line = index
+            .edges
+            .iter()
+            .find(|e| e.from_symbol == caller_id && e.to_symbol == sym_id)
+            .map(|e| e.line);
+        let Some(mut node) = call_hierarchy_node(index, caller_id, call_line) else {
+            continue;
+        };
+
+        // Leaves at the depth limit stay unexpanded so they can still be expanded elsewhere
+        if depth < max_depth {
+            if expanded.insert(caller_id) {
+                node.callers =
+                    call_hierarchy_callers(index, caller_id, depth + 1, max_depth, expanded);
+            } else {
+                node.see_above = true;
+            }
+        }
+        nodes.push(node);
+    }
+    nodes
+}
+
+/// Node for a symbol at its call site, or at its definition when there is none
+fn call_hierarchy_node(
+    index: &SemanticIndex,
+    sym_id: u32,
+    line: Option<u32>,
+) -> Option<CallHierarchyNode> {
+    let sym = index.symbol(sym_id)?;
+    Some(CallHierarchyNode {
+        symbol: index.symbol_name(sym).unwrap_or("<unknown>").to_string(),
+        kind: symbol_kind_str(sym.symbol_kind()).to_string(),
+        file: index
+            .file_path(sym.file_id)
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        line: line.unwrap_or(sym.start_line),
+        see_above: false,
+        callers: Vec::new(),
+    })
+}
+
+/// Find what a symbol calls (callees/outgoing)
+async fn find_callees_cmd(
+    index: &SemanticIndex,
+    symbol: &str,
+    max_depth: usize,
+    dedupe: bool,
+    merge: bool,
+    filter: &TraceFilter,
+) -> Result<TraceResult> {
+    debug!(symbol = %symbol, merge, "find_callees");
+
+    let symbol_ids = index.resolve_symbols(symbol);
+
+    Ok(if merge {
+        collect_callees(index, symbol, &symbol_ids, max_depth, dedupe, filter)
+    } else {
+        definition_sections(index, symbol, &symbol_ids, |ids| {
+            collect_callees(index, symbol, ids, max_depth, dedupe, filter)
+        })
+    })
+}
+
+/// Callees of the given definitions of `symbol`, merged into one result
+///
+/// Callees failing `filter` are neither reported nor traversed further.
+fn collect_callees(
+    index: &SemanticIndex,
+    symbol: &str,
+    symbol_ids: &[u32],
+    max_depth: usize,
+    dedupe: bool,
+    filter: &TraceFilter,
+) -> TraceResult {
+    if symbol_ids.is_empty() {
+        return TraceResult {
+            symbol: symbol.to_string(),
+            defined_at: None,
+            kind: "unknown".to_string(),
+            invocation_paths: Vec::new(),
+            total_paths: 0,
+            entry_points: 0,
+            definitions: Vec::new(),
+            symbol_id: None,
+            stable_id: None,
+        };
+    }
+
+    let mut paths = Vec::new();
+
+    for &sym_id in symbol_ids {
+        let mut visited = HashSet::new();
+        collect_callees_recursive(
+            index,
+            sym_id,
+            &mut paths,
+            &mut visited,
+            Vec::new(),
+            max_depth,
+            filter,
+        );
+    }
+    if dedupe {
+        paths = collapse_shared_tails(paths);
+    }
+
+    let defined_at = symbol_ids.first().and_then(|&id| {
+        let sym = index.symbol(id)?;
+        let file = index.file_path(sym.file_id)?;
+        Some(format!("{}:{}", file.display(), sym.start_line))
+    });
+
+    let kind = symbol_ids
+        .first()
+        .and_then(|&id| index.symbol(id))
+        .map(|s| symbol_kind_str(s.symbol_kind()).to_string())
+        .unwrap_or_else(|| "function".to_string());
+
+    TraceResult {
+        symbol: symbol.to_string(),
+        defined_at,
+        kind,
+        invocation_paths: paths.clone(),
+        total_paths: paths.len(),
+        entry_points: 1,
+        definitions: Vec::new(),
+        symbol_id: symbol_ids.first().copied(),
+        stable_id: symbol_ids
+            .first()
+            .and_then(|&id| index.stable_symbol_id(id)),
+    }
+}
+
+/// Shorten paths that run into a tail an earlier path already shows
+///
+/// A path is cut just after the first step from which the rest of it repeats
+/// the end of an earlier path (where two definitions call into the same
+/// helpers), and dropped when it repeats one entirely. Steps compare by
+/// symbol and location.
+fn collapse_shared_tails(paths: Vec<InvocationPath>) -> Vec<InvocationPath> {
+    let mut tails: HashSet<Vec<(String, String, u32)>> = HashSet::new();
+    let mut kept = Vec::new();
+
+    for mut path in paths {
+        let keys: Vec<_> = path
+            .chain
+            .iter()
+            .map(|s| (s.symbol.clone(), s.file.clone(), s.line))
+            .collect();
+        match (0..keys.len()).find(|&k| tails.contains(&keys[k..])) {
+            Some(0) => continue,
+            Some(k) => path.chain.truncate(k + 1),
+            None => {}
+        }
+        for k in 0..keys.len() {
+            tails.insert(keys[k..].to_vec());
+        }
+        kept.push(path);
+    }
+
+    kept
+}
+
+fn collect_callees_recursive(
+    index: &SemanticIndex,
+    sym_id: u32,
+    paths: &mut Vec<InvocationPath>,
+    visited: &mut HashSet<u32>,
+    current_chain: Vec<ChainStep>,
+    max_depth: usize,
+    filter: &TraceFilter,
+) {
+    if current_chain.len() >= max_depth {
+        return;
+    }
+
+    visited.insert(sym_id);
+
+    let callees: Vec<u32> = index
+        .callees(sym_id)
+        .iter()
+        .copied()
+        .filter(|&id| filter.matches_symbol_id(index, id))
+        .collect();
+
+    // Add current symbol to chain
+    let mut chain = current_chain.clone();
+    if let Some(sym) = index.symbol(sym_id) {
+        let name = index.symbol_name(sym).unwrap_or("<unknown>");
+        let file = index
+            .file_path(sym.file_id)
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        chain.push(ChainStep {
+            symbol: name.to_string(),
+            file: file.clone(),
+            line: sym.start_line,
+            column: None,
+            context: None,
+        });
+
+        if callees.is_empty() && !chain.is_empty() {
+            // End of chain
+            paths.push(InvocationPath {
+                entry_point: chain.first().map(|c| c.symbol.clone()).unwrap_or_default(),
+                entry_kind: symbol_kind_str(sym.symbol_kind()).to_string(),
+                chain: chain.clone(),
+            });
+            return;
+        }
+    }
+
+    for callee_id in callees {
+        if visited.contains(&callee_id) {
+            continue;
+        }
+        collect_callees_recursive(
+            index,
+            callee_id,
+            paths,
+            visited,
+            chain.clone(),
+            max_depth,
+            filter,
+        );
+    }
+}
+
+/// Find the shortest call path between two symbols
+async fn find_call_path_cmd(index: &SemanticIndex, from: &str, to: &str) -> Result<TraceResult> {
+    debug!(from = %from, to = %to, "find_call_path");
+
+    Ok(collect_call_path(index, from, to))
+}
+
+/// Build a trace result holding the shortest `from` -> `to` path, if any
+fn collect_call_path(index: &SemanticIndex, from: &str, to: &str) -> TraceResult {
+    let sources = index.resolve_symbols(from);
+    let targets: HashSet<u32> = index.resolve_symbols(to).into_iter().collect();
+
+    let first = sources.first().and_then(|&id| index.symbol(id));
+    let defined_at = first.and_then(|sym| {
+        let file = index.file_path(sym.file_id)?;
+        Some(format!("{}:{}", file.display(), sym.start_line))
+    });
+    let kind = first
+        .map(|s| symbol_kind_str(s.symbol_kind()).to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let invocation_paths: Vec<InvocationPath> = shortest_call_path(index, &sources, &targets)
+        .map(|ids| {
+            let chain: Vec<ChainStep> = ids
+                .iter()
+                .filter_map(|&id| {
+                    let sym = index.symbol(id)?;
+                    Some(ChainStep {
+                        symbol: index.symbol_name(sym).unwrap_or("<unknown>").to_string(),
+                        file: index
+                            .file_path(sym.file_id)
+                            .map(|p| p.to_string_lossy().to_string())
+                            .unwrap_or_default(),
+                        line: sym.start_line,
+                        column: None,
+                        context: None,
+                    })
+                })
+                .collect();
+            InvocationPath {
+                entry_point: from.to_string(),
+                entry_kind: kind.clone(),
+                chain,
+            }
+        })
+        .into_iter()
+        .collect();
+
+    TraceResult {
+        symbol: format!("{} -> {}", from, to),
+        defined_at,
+        kind,
+        total_paths: invocation_paths.len(),
+        entry_points: invocation_paths.len(),
+        invocation_paths,
+        definitions: Vec::new(),
+        symbol_id: None,
+        stable_id: None,
+    }
+}
+
+/// Breadth-first search over call edges from any source to any target
+fn shortest_call_path(
+    index: &SemanticIndex,
+    sources: &[u32],
+    targets: &HashSet<u32>,
+) -> Option<Vec<u32>> {
+    let mut parent: HashMap<u32, Option<u32>> = HashMap::new();
+    let mut queue = std::collections::VecDeque::new();
+    for &id in sources {
+        if parent.insert(id, None).is_none() {
+            queue.push_back(id);
+        }
+    }
+
+    while let Some(current) = queue.pop_front() {
+        if targets.contains(&current) {
+            let mut path = vec![current];
+            let mut node = current;
+            while let Some(&Some(prev)) = parent.get(&node) {
+                path.push(prev);
+                node = prev;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for &callee in index.callees(current) {
+            if let std::collections::hash_map::Entry::Vacant(e) = parent.entry(callee) {
+                e.insert(Some(current));
+                queue.push_back(callee);
+            }
+        }
+    }
+
+    None
+}
+
+/// Find the direct callers and callees of a symbol (depth 1)
+async fn find_neighborhood_cmd(
+    index: &SemanticIndex,
+    symbol: &str,
+    filter: &TraceFilter,
+) -> Result<NeighborhoodResult> {
+    debug!(symbol = %symbol, "find_neighborhood");
+
+    Ok(collect_neighborhood(index, symbol, filter))
+}
+
+/// Collect the depth-1 call neighborhood of a symbol from a loaded index
+fn collect_neighborhood(
+    index: &SemanticIndex,
+    symbol: &str,
+    filter: &TraceFilter,
+) -> NeighborhoodResult {
+    let symbol_ids = index.resolve_symbols(symbol);
+    let targets: HashSet<u32> = symbol_ids.iter().copied().collect();
+
+    let neighbor = |id: u32, call_line: u32| -> Option<NeighborSymbol> {
+        let sym = index.symbol(id)?;
+        let name = index.symbol_name(sym).unwrap_or("<unknown>").to_string();
+        let kind = symbol_kind_str(sym.symbol_kind()).to_string();
+        let file = index
+            .file_path(sym.file_id)
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if !filter.matches_symbol(&name, &kind, &file) || !filter.matches_visibility(sym) {
+            return None;
+        }
+        Some(NeighborSymbol {
+            name,
+            kind,
+            file,
+            line: sym.start_line,
+            call_line,
+        })
+    };
+
+    let mut seen_callers = HashSet::new();
+    let mut seen_callees = HashSet::new();
+    let mut callers = Vec::new();
+    let mut callees = Vec::new();
+
+    for edge in &index.edges {
+        if targets.contains(&edge.to_symbol) && seen_callers.insert(edge.from_symbol) {
+            callers.extend(neighbor(edge.from_symbol, edge.line));
+        }
+        if targets.contains(&edge.from_symbol) && seen_callees.insert(edge.to_symbol) {
+            callees.extend(neighbor(edge.to_symbol, edge.line));
+        }
+    }
+
+    callers.sort_by(|a, b| (&a.file, a.call_line).cmp(&(&b.file, b.call_line)));
+    callees.sort_by_key(|c| c.call_line);
+
+    let first = symbol_ids.first().and_then(|&id| index.symbol(id));
+    let defined_at = first.and_then(|sym| {
+        let file = index.file_path(sym.file_id)?;
+        Some(format!("{}:{}", file.display(), sym.start_line))
+    });
+    let kind = first
+        .map(|s| symbol_kind_str(s.symbol_kind()).to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    NeighborhoodResult {
+        symbol: symbol.to_string(),
+        defined_at,
+        kind,
+        callers,
+        callees,
+    }
+}
+
+// =============================================================================
+// PHASE 4: IMPACT ANALYSIS (REAL DATA)
+// =============================================================================
+
+/// Analyze impact of changing a symbol
+async fn analyze_impact_cmd(
+    index: &SemanticIndex,
+    symbol: &str,
+    max_depth: usize,
+    filter: &TraceFilter,
+) -> Result<ImpactResult> {
+    let _ = filter; // TODO: apply filter to results
+    debug!(symbol = %symbol, "analyze_impact");
+
+    let config = Config::load()?.impact;
+
+    Ok(analyze_impact(index, symbol, max_depth, &config))
+}
+
+/// Analyze the impact of changing a symbol in a loaded index
+fn analyze_impact(
+    index: &SemanticIndex,
+    symbol: &str,
+    max_depth: usize,
+    config: &ImpactConfig,
+) -> ImpactResult {
+    // Parse file:symbol format if present
+    let sym_name = if symbol.contains(':') {
+        symbol.splitn(2, ':').nth(1).unwrap_or(symbol)
+    } else {
+        symbol
+    };
+
+    let symbol_ids = index.resolve_symbols(sym_name);
+
+    if symbol_ids.is_empty() {
+        return ImpactResult {
+            symbol: symbol.to_string(),
+            file: String::new(),
+            defined_at: None,
+            direct_callers: Vec::new(),
+            direct_caller_count: 0,
+            transitive_callers: Vec::new(),
+            transitive_caller_count: 0,
+            affected_entry_points: Vec::new(),
+            files_affected: Vec::new(),
+            paths_to_entry: Vec::new(),
+            risk_level: RiskLevel::Low,
+            risk_score: 0,
+            risk_factors: Vec::new(),
+        };
+    }
+
+    let first_id = symbol_ids[0];
+    let defined_at = index.symbol(first_id).and_then(|sym| {
+        let file = index.file_path(sym.file_id)?;
+        Some(format!("{}:{}", file.display(), sym.start_line))
+    });
+
+    let file = index
+        .symbol(first_id)
+        .and_then(|s| index.file_path(s.file_id))
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    // Collect direct callers (deduplicated)
+    let mut direct_callers_set = HashSet::new();
+    let mut direct_caller_files = HashSet::new();
+
+    for &sym_id in &symbol_ids {
+        for &caller_id in index.callers(sym_id) {
+            if let Some(caller) = index.symbol(caller_id) {
+                let name = index.symbol_name(caller).unwrap_or("<unknown>");
+                let caller_file = index
+                    .file_path(caller.file_id)
+                    .map(|p| p.to_string_lossy().to_string())
+                    .unwrap_or_default();
+
+                let caller_str = format!("{} ({}:{})", name, caller_file, caller.start_line);
+                direct_callers_set.insert(caller_str);
+                direct_caller_files.insert(caller_file);
+            }
+        }
+    }
+    let direct_callers: Vec<_> = direct_callers_set.into_iter().collect();
+
+    // Collect transitive callers via BFS (deduplicated)
+    let mut transitive_callers_set = HashSet::new();
+    let mut visited = HashSet::new();
+    let mut queue: Vec<(u32, usize)> = symbol_ids.iter().map(|&id| (id, 0)).collect();
+    let mut affected_entry_points_set = HashSet::new();
+    let mut all_files = HashSet::new();
+    let mut in_cycle = false;
+
+    while let Some((current, depth)) = queue.pop() {
+        if depth > max_depth || visited.contains(&current) {
+            continue;
+        }
+        visited.insert(current);
+
+        if let Some(sym) = index.symbol(current) {
+            let sym_file = index
+                .file_path(sym.file_id)
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_default();
+            all_files.insert(sym_file.clone());
+
+            if sym.is_entry_point() {
+                let name = index.symbol_name(sym).unwrap_or("<unknown>");
+                affected_entry_points_set.insert(format!("{} ({})", name, sym_file));
+            }
+
+            if depth > 1 {
+                let name = index.symbol_name(sym).unwrap_or("<unknown>");
+                transitive_callers_set.insert(format!("{} (depth {})", name, depth));
+            }
+        }
+
+        for &caller_id in index.callers(current) {
+            // Reaching the target again means it sits on a call cycle
+            if symbol_ids.contains(&caller_id) {
+                in_cycle = true;
+            }
+            if !visited.contains(&caller_id) {
+                queue.push((caller_id, depth + 1));
+            }
+        }
+    }
+    let transitive_callers: Vec<_> = transitive_callers_set.into_iter().collect();
+    let affected_entry_points: Vec<_> = affected_entry_points_set.into_iter().collect();
+
+    let (risk_score, risk_level, risk_factors) = score_risk(
+        &RiskInputs {
+            entry_points: affected_entry_points.len(),
+            files: all_files.len(),
+            direct_callers: direct_callers.len(),
+            transitive_callers: transitive_callers.len(),
+            in_cycle,
+        },
+        config,
+    );
+
+    ImpactResult {
+        symbol: symbol.to_string(),
+        file,
+        defined_at,
+        direct_callers: direct_callers.clone(),
+        direct_caller_count: direct_callers.len(),
+        transitive_callers: transitive_callers.clone(),
+        transitive_caller_count: transitive_callers.len(),
+        affected_entry_points,
+        files_affected: all_files.into_iter().collect(),
+        paths_to_entry: paths_to_entry(index, &symbol_ids, max_depth),
+        risk_level,
+        risk_score,
+        risk_factors,
+    }
+}
+
+/// Entry-point chains reported by `--impact`, as many as the web UI shows
+const MAX_ENTRY_PATHS: usize = 5;
+
+/// Shortest caller chains from a symbol up to entry points
+///
+/// Mirrors the web impact endpoint: each chain starts at a direct caller and
+/// ends at an entry point, found breadth-first so shorter chains come first.
+fn paths_to_entry(index: &SemanticIndex, symbol_ids: &[u32], max_depth: usize) -> Vec<Vec<String>> {
+    let mut paths = Vec::new();
+    let mut visited: HashSet<u32> = symbol_ids.iter().copied().collect();
+    let mut queue = std::collections::VecDeque::new();
+    for &id in symbol_ids {
+        for &caller_id in index.callers(id) {
+            if visited.insert(caller_id) {
+                queue.push_back(vec![caller_id]);
+            }
+        }
+    }
+
+    while let Some(path) = queue.pop_front() {
+        if paths.len() >= MAX_ENTRY_PATHS {
+            break;
+        }
+        let Some(&current) = path.last() else {
+            continue;
+        };
+        if index.symbol(current).is_some_and(|s| s.is_entry_point()) {
+            paths.push(
+                path.iter()
+                    .filter_map(|&id| index.symbol(id).and_then(|s| index.symbol_name(s)))
+                    .map(str::to_string)
+                    .collect(),
+            );
+        }
+        if path.len() < max_depth {
+            for &caller_id in index.callers(current) {
+                if visited.insert(caller_id) {
+                    let mut longer = path.clone();
+                    longer.push(caller_id);
+                    queue.push_back(longer);
+                }
+            }
+        }
+    }
+    paths
+}
+
+/// Counts that contribute to an impact risk score
+struct RiskInputs {
+    entry_points: usize,
+    files: usize,
+    direct_callers: usize,
+    transitive_callers: usize,
+    in_cycle: bool,
+}
+
+/// Compute a weighted risk score, its level, and the factors explaining it
+fn score_risk(inputs: &RiskInputs, config: &ImpactConfig) -> (u32, RiskLevel, Vec<String>) {
+    let mut score: u32 = 0;
+    let mut factors = Vec::new();
+
+    let mut add = |count: usize, weight: u32, factor: String| {
+        if count > 0 && weight > 0 {
+            score = score.saturating_add((count as u32).saturating_mul(weight));
+            factors.push(factor);
+        }
+    };
+
+    add(
+        inputs.entry_points,
+        config.entry_point_weight,
+        format!("{} entry points affected", inputs.entry_points),
+    );
+    add(
+        inputs.files,
+        config.file_weight,
+        format!("used across {} files", inputs.files),
+    );
+    add(
+        inputs.direct_callers,
+        config.direct_caller_weight,
+        format!("{} direct callers", inputs.direct_callers),
+    );
+    add(
+        inputs.transitive_callers,
+        config.transitive_caller_weight,
+        format!("{} transitive callers", inputs.transitive_callers),
+    );
+    add(
+        inputs.in_cycle as usize,
+        config.cycle_weight,
+        "in a cycle".to_string(),
+    );
+
+    let level = if score >= config.critical_threshold {
+        RiskLevel::Critical
+    } else if score >= config.high_threshold {
+        RiskLevel::High
+    } else if score >= config.medium_threshold {
+        RiskLevel::Medium
+    } else {
+        RiskLevel::Low
+    };
+
+    (score, level, factors)
+}
+
+// =============================================================================
+// PHASE 5: TYPE TRACING (handled by refs with TypeAnnotation filter)
+// =============================================================================
+
+// Type tracing is implemented via find_refs_cmd with ReferenceKind::TypeAnnotation
+
+// =============================================================================
+// PHASE 6: MODULE TRACING
+// =============================================================================
+
+/// Trace module imports/exports
+async fn trace_module_cmd(
+    index: &SemanticIndex,
+    module: &str,
+    filter: &TraceFilter,
+) -> Result<ModuleResult> {
+    let _ = filter; // TODO: apply filter to results
+    debug!(module = %module, "trace_module");
+
+    // Find files that match the module pattern
+    let module_files: Vec<_> = index
+        .files
+        .iter()
+        .enumerate()
+        .filter(|(_, path)| path.to_string_lossy().contains(module))
+        .collect();
+
+    let mut exports = Vec::new();
+    let mut imported_by = Vec::new();
+    let mut dependencies = Vec::new();
+
+    for (file_id, _file_path) in &module_files {
+        // Find exported symbols from this file
+        for symbol in index.symbols_in_file(*file_id as u16) {
+            if symbol.is_exported() {
+                let name = index.symbol_name(symbol).unwrap_or("<unknown>");
+                exports.push(format!(
+                    "{} ({})",
+                    name,
+                    symbol_kind_str(symbol.symbol_kind())
+                ));
+            }
+        }
+
+        // Find imports of this module (tokens with Import kind referencing this file)
+        // This is approximate - we look for symbols from this file being referenced elsewhere
+        for symbol in index.symbols_in_file(*file_id as u16) {
+            for reference in index.references_to(symbol.id) {
+                if reference.ref_kind() == RefKind::Import {
+                    if let Some(token) = index.token(reference.token_id) {
+                        if token.file_id != *file_id as u16 {
+                            let importer_file = index
+                                .file_path(token.file_id)
+                                .map(|p| p.to_string_lossy().to_string())
+                                .unwrap_or_default();
+                            let name = index.symbol_name(symbol).unwrap_or("<unknown>");
+                            imported_by.push(format!("{} imports {}", importer_file, name));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Find what this module depends on (imports from other modules)
+    for (file_id, _) in &module_files {
+        for token in index.tokens_in_file(*file_id as u16) {
+            if token.token_kind() == crate::trace::TokenKind::Import {
+                let name = index.token_name(token).unwrap_or("<unknown>");
+                if !dependencies.contains(&name.to_string()) {
+                    dependencies.push(name.to_string());
+                }
+            }
+        }
+    }
+
+    let file_path = module_files
+        .first()
+        .map(|(_, p)| p.to_string_lossy().to_string())
+        .unwrap_or_else(|| module.to_string());
+
+    Ok(ModuleResult {
+        module: module.to_string(),
+        file_path,
+        exports,
+        imported_by,
+        dependencies,
+        circular_deps: Vec::new(), // Populated by cycles command
+    })
+}
+
+/// Find circular dependencies
+async fn find_cycles_cmd(
+    index: &SemanticIndex,
+    algorithm: &str,
+    imports: bool,
+    filter: &TraceFilter,
+) -> Result<ModuleResult> {
+    debug!(algorithm = %algorithm, imports, "find_cycles filter={:?}", filter);
+
+    Ok(collect_cycles(index, algorithm, imports, filter))
+}
+
+/// File dependency graph from call edges, or from Import references in `imports` mode
+fn file_dependency_graph(
+    index: &SemanticIndex,
+    imports: bool,
+    filter: &TraceFilter,
+) -> HashMap<u16, HashSet<u16>> {
+    // Helper to check if file passes filter
+    let file_passes = |file_id: u16| -> bool {
+        if let Some(path) = index.file_path(file_id) {
+            let path = path.to_string_lossy();
+            filter.matches_path(&path) && !filter.excludes_vendored(&path)
+        } else {
+            false
+        }
+    };
+
+    // (from file, to file) pairs: caller -> callee, or importer -> imported
+    let links: Vec<(u16, u16)> = if imports {
+        index
+            .references
+            .iter()
+            .filter(|r| r.ref_kind() == RefKind::Import)
+            .filter_map(|r| {
+                let token = index.token(r.token_id)?;
+                let symbol = index.symbol(r.symbol_id)?;
+                Some((token.file_id, symbol.file_id))
+            })
+            .collect()
+    } else {
+        index
+            .edges
+            .iter()
+            .filter_map(|edge| {
+                let from_sym = index.symbol(edge.from_symbol)?;
+                let to_sym = index.symbol(edge.to_symbol)?;
+                Some((from_sym.file_id, to_sym.file_id))
+            })
+            .collect()
+    };
+
+    // Build file dependency graph (filtered)
+    let mut file_deps: HashMap<u16, HashSet<u16>> = HashMap::new();
+    for (from_file, to_file) in links {
+        if from_file != to_file {
+            // Only include if at least one file passes the filter (or no filter)
+            let from_passes = file_passes(from_file);
+            let to_passes = file_passes(to_file);
+
+            if from_passes || to_passes || filter.paths.is_empty() {
+                file_deps.entry(from_file).or_default().insert(to_file);
+            }
+        }
+    }
+    file_deps
+}
+
+/// Detect file-level cycles in a loaded index
+fn collect_cycles(
+    index: &SemanticIndex,
+    algorithm: &str,
+    imports: bool,
+    filter: &TraceFilter,
+) -> ModuleResult {
+    let file_deps = file_dependency_graph(index, imports, filter);
+
+    let mut cycles = Vec::new();
+    if algorithm == "dfs" {
+        // Individual back-edge paths; may repeat files across cycles
+        let mut visited = HashSet::new();
+        let mut rec_stack = HashSet::new();
+        let mut path = Vec::new();
+
+        for &file_id in file_deps.keys() {
+            find_cycles_dfs(
+                file_id,
+                &file_deps,
+                &mut visited,
+                &mut rec_stack,
+                &mut path,
+                &mut cycles,
+                index,
+                filter,
+            );
+        }
+    } else {
+        // One entry per strongly connected component, listing its members
+        for component in strongly_connected_components(&file_deps) {
+            let members: Vec<_> = component
+                .iter()
+                .filter_map(|&fid| {
+                    index
+                        .file_path(fid)
+                        .map(|p| p.to_string_lossy().to_string())
+                })
+                .collect();
+
+            let passes = filter.paths.is_empty() || members.iter().any(|p| filter.matches_path(p));
+            if !members.is_empty() && passes {
+                cycles.push(members.join(" <-> "));
+            }
+        }
+    }
+
+    ModuleResult {
+        module: if imports {
+            "Circular Imports".to_string()
+        } else {
+            "Circular Dependencies".to_string()
+        },
+        file_path: String::new(),
+        exports: Vec::new(),
+        imported_by: Vec::new(),
+        dependencies: Vec::new(),
+        circular_deps: cycles,
+    }
+}
+
+fn find_cycles_dfs(
+    node: u16,
+    graph: &HashMap<u16, HashSet<u16>>,
+    visited: &mut HashSet<u16>,
+    rec_stack: &mut HashSet<u16>,
+    path: &mut Vec<u16>,
+    cycles: &mut Vec<String>,
+    index: &SemanticIndex,
+    filter: &TraceFilter,
+) {
+    visited.insert(node);
+    rec_stack.insert(node);
+    path.push(node);
+
+    if let Some(neighbors) = graph.get(&node) {
+        for &neighbor in neighbors {
+            if !visited.contains(&neighbor) {
+                find_cycles_dfs(
+                    neighbor, graph, visited, rec_stack, path, cycles, index, filter,
+                );
+            } else if rec_stack.contains(&neighbor) {
+                // Found a cycle
+                let cycle_start = path.iter().position(|&n| n == neighbor).unwrap_or(0);
+                let cycle_path: Vec<_> = path[cycle_start..]
+                    .iter()
+                    .filter_map(|&fid| {
+                        index
+                            .file_path(fid)
+                            .map(|p| p.to_string_lossy().to_string())
+                    })
+                    .collect();
+
+                if !cycle_path.is_empty() {
+                    // Only include cycle if at least one file in the cycle passes the filter
+                    let cycle_passes = filter.paths.is_empty()
+                        || cycle_path.iter().any(|p| filter.matches_path(p));
+
+                    if cycle_passes {
+                        cycles.push(cycle_path.join(" -> ") + " -> " + &cycle_path[0]);
+                    }
+                }
+            }
+        }
+    }
+
+    path.pop();
+    rec_stack.remove(&node);
+}
+
+// =============================================================================
+// PHASE 7: DATA FLOW TRACING
+// =============================================================================
+
+/// Trace data flow for a variable
+async fn trace_flow_cmd(
+    project: &Project,
+    index: &SemanticIndex,
+    symbol: &str,
+    options: &OperationOptions,
+    filter: &TraceFilter,
+) -> Result<FlowResult> {
+    debug!(symbol = %symbol, "trace_flow filter={:?}", filter);
+
+    let mut cache = FileCache::new(&project.root);
+
+    let mut flow_paths = Vec::new();
+    let mut current_path = Vec::new();
+
+    // Find all tokens with this name
+    if let Some(token_ids) = index.tokens_by_name(symbol) {
+        // Group by file and sort by line
+        let mut by_file: HashMap<u16, Vec<&crate::trace::Token>> = HashMap::new();
+        for &token_id in token_ids {
+            if let Some(token) = index.token(token_id) {
+                // Apply path filter
+                if let Some(path) = index.file_path(token.file_id) {
+                    if !filter.matches_path(&path.to_string_lossy()) {
+                        continue;
+                    }
+                }
+                by_file.entry(token.file_id).or_default().push(token);
+            }
+        }
+
+        for (file_id, mut tokens) in by_file {
+            tokens.sort_by_key(|t| (t.line, t.column));
+
+            let file_path = index
+                .file_path(file_id)
+                .map(|p| p.to_path_buf())
+                .unwrap_or_default();
+            let file = file_path.to_string_lossy().to_string();
+
+            for token in tokens {
+                // Map token kinds to flow actions
+                // Note: TokenKind doesn't have Assignment/Return, so we infer from context
+                let action = match token.token_kind() {
+                    crate::trace::TokenKind::Call => FlowAction::PassToFunction,
+                    crate::trace::TokenKind::Property => FlowAction::Read,
+                    crate::trace::TokenKind::Identifier => FlowAction::Read, // Could be assign or read
+                    crate::trace::TokenKind::Variable => FlowAction::Define,
+                    _ => FlowAction::Read,
+                };
+
+                // First occurrence is considered a definition
+                let actual_action = if current_path.is_empty() {
+                    FlowAction::Define
+                } else {
+                    action
+                };
+
+                let expression = get_code_context(&mut cache, &file_path, token.line, 0);
+
+                current_path.push(FlowStep {
+                    variable: symbol.to_string(),
+                    action: actual_action,
+                    file: file.clone(),
+                    line: token.line,
+                    expression,
+                });
+            }
+        }
+    }
+
+    if !current_path.is_empty() {
+        flow_paths.push(current_path);
+    }
+
+    // Apply limit
+    if let Some(limit) = options.limit {
+        for path in &mut flow_paths {
+            path.truncate(limit);
+        }
+    }
+
+    Ok(FlowResult {
+        symbol: symbol.to_string(),
+        flow_paths,
+    })
+}
+
+// =============================================================================
+// PHASE 8: PATTERN SEARCH
+// =============================================================================
+
+/// Search for regex pattern in codebase
+async fn trace_pattern_cmd(
+    project: &Project,
+    index: &SemanticIndex,
+    pattern: &str,
+    options: &OperationOptions,
+    filter: &TraceFilter,
+) -> Result<PatternResult> {
+    debug!(pattern = %pattern, "trace_pattern filter={:?}", filter);
+
+    let regex = pattern_regex(pattern)?;
+    if options.first_match_only {
+        let files = pattern_files(index, filter);
+        return match first_pattern_match(index, &project.root, &regex, options, &files) {
+            Some(first) => Ok(PatternResult {
+                pattern: pattern.to_string(),
+                total_matches: 1,
+                by_file: HashMap::from([(first.file.clone(), 1)]),
+                matches: vec![first],
+                truncated: false,
+                warnings: Vec::new(),
+            }),
+            None => Err(Error::NoMatch {
+                pattern: pattern.to_string(),
+            }),
+        };
+    }
+    let mut cache = FileCache::new(&project.root);
+
+    Ok(collect_pattern(
+        index, &mut cache, &regex, pattern, options, filter,
+    ))
+}
+
+/// The first match in file order (`--first-match-only`)
+///
+/// Files after the one holding the match are not scanned; unreadable files
+/// are skipped.
+fn first_pattern_match(
+    index: &SemanticIndex,
+    root: &Path,
+    regex: &Regex,
+    options: &OperationOptions,
+    files: &[(u16, &PathBuf)],
+) -> Option<PatternMatch> {
+    files
+        .par_iter()
+        .map_init(
+            || FileCache::new(root),
+            |cache, &(file_id, file_path)| {
+                scan_pattern_file(index, cache, regex, options, file_id, file_path, 1).ok()
+            },
+        )
+        .find_map_first(|matches| matches.and_then(|m| m.into_iter().next()))
+}
+
+/// Compile a `--pattern` regex
+pub(crate) fn pattern_regex(pattern: &str) -> Result<Regex> {
+    Regex::new(pattern).map_err(|e| Error::SearchError {
+        message: format!("Invalid regex pattern: {}", e),
+    })
+}
+
+/// Scan indexed files for a regex, noting files that can no longer be read
+///
+/// Files are scanned in parallel unless `--workers 1` is given; either way
+/// matches come back ordered by file then line.
+fn collect_pattern(
+    index: &SemanticIndex,
+    cache: &mut FileCache,
+    regex: &Regex,
+    pattern: &str,
+    options: &OperationOptions,
+    filter: &TraceFilter,
+) -> PatternResult {
+    let (cap, truncates) = pattern_cap(options);
+    let files = pattern_files(index, filter);
+
+    let root = cache.project_root().to_path_buf();
+    let scan = || scan_pattern_parallel(index, &root, regex, options, &files, cap);
+    let scanned = match options.workers {
+        Some(1) => scan_pattern_sequential(index, cache, regex, options, &files, cap),
+        Some(n) => match rayon::ThreadPoolBuilder::new().num_threads(n).build() {
+            Ok(pool) => pool.install(scan),
+            Err(e) => {
+                warn!("Could not start {} pattern workers: {}", n, e);
+                scan_pattern_sequential(index, cache, regex, options, &files, cap)
+            }
+        },
+        None => scan(),
+    };
+
+    // Merge per-file results in file order, then apply the cap
+    let mut matches = Vec::new();
+    let mut warnings = Vec::new();
+    for result in scanned {
+        match result {
+            Ok(file_matches) => matches.extend(file_matches),
+            Err(warning) => warnings.push(warning),
+        }
+        if matches.len() >= cap {
+            break;
+        }
+    }
+    matches.truncate(cap);
+    let truncated = truncates && matches.len() >= cap;
+
+    // Count by file
+    let mut by_file: HashMap<String, usize> = HashMap::new();
+    for m in &matches {
+        *by_file.entry(m.file.clone()).or_insert(0) += 1;
+    }
+
+    PatternResult {
+        pattern: pattern.to_string(),
+        total_matches: matches.len(),
+        matches,
+        by_file,
+        truncated,
+        warnings,
+    }
+}
+
+/// Files handed to each worker per parallel pattern batch
+const PATTERN_FILES_PER_WORKER: usize = 4;
+
+/// Maximum number of pattern matches, and whether reaching it is truncation
+///
+/// An explicit --limit below the safety cap is a normal stop, not truncation.
+fn pattern_cap(options: &OperationOptions) -> (usize, bool) {
+    let cap = options
+        .limit
+        .map_or(options.max_results, |l| l.min(options.max_results));
+    let truncates = !matches!(options.limit, Some(l) if l <= options.max_results);
+    (cap, truncates)
+}
+
+/// Indexed files a pattern search covers, in file order
+fn pattern_files<'a>(index: &'a SemanticIndex, filter: &TraceFilter) -> Vec<(u16, &'a PathBuf)> {
+    index
+        .files
+        .iter()
+        .enumerate()
+        .filter(|(_, path)| filter.matches_path(&path.to_string_lossy()))
+        .map(|(file_id, path)| (file_id as u16, path))
+        .collect()
+}
+
+/// Write `--pattern` JSON file by file as matches are found (`--stream`)
+///
+/// Only one file's matches are held at a time; files are scanned in order.
+pub(crate) fn stream_pattern<W: Write>(
+    index: &SemanticIndex,
+    cache: &mut FileCache,
+    regex: &Regex,
+    pattern: &str,
+    options: &OperationOptions,
+    filter: &TraceFilter,
+    out: W,
+) -> Result<W> {
+    let (cap, truncates) = pattern_cap(options);
+    let mut writer = JsonArrayWriter::begin(out, "matches")?;
+    let mut by_file: HashMap<String, usize> = HashMap::new();
+    let mut warnings = Vec::new();
+
+    for (file_id, file_path) in pattern_files(index, filter) {
+        if writer.len() >= cap {
+            break;
+        }
+        let remaining = cap - writer.len();
+        match scan_pattern_file(index, cache, regex, options, file_id, file_path, remaining) {
+            Ok(file_matches) => {
+                for m in &file_matches {
+                    *by_file.entry(m.file.clone()).or_insert(0) += 1;
+                    writer.push(m)?;
+                }
+            }
+            Err(warning) => warnings.push(warning),
+        }
+    }
+
+    let summary = PatternResult {
+        pattern: pattern.to_string(),
+        total_matches: writer.len(),
+        matches: Vec::new(),
+        by_file,
+        truncated: truncates && writer.len() >= cap,
+        warnings,
+    };
+    Ok(writer.finish(&summary)?)
+}
+
+/// Scan files one at a time through a shared cache, stopping once `cap` is hit
+fn scan_pattern_sequential(
+    index: &SemanticIndex,
+    cache: &mut FileCache,
+    regex: &Regex,
+    options: &OperationOptions,
+    files: &[(u16, &PathBuf)],
+    cap: usize,
+) -> Vec<std::result::Result<Vec<PatternMatch>, String>> {
+    let mut results = Vec::new();
+    let mut found = 0;
+
+    for &(file_id, file_path) in files {
+        if found >= cap {
+            break;
+        }
+        let result = scan_pattern_file(
+            index,
+            cache,
+            regex,
+            options,
+            file_id,
+            file_path,
+            cap - found,
+        );
+        if let Ok(file_matches) = &result {
+            found += file_matches.len();
+        }
+        results.push(result);
+    }
+
+    results
+}
+
+/// Scan files across the current rayon pool, each worker with its own cache
+///
+/// Files go out in ordered batches of a few per worker. Once the batches
+/// scanned so far hold `cap` matches no further files are read; the caller
+/// truncates the last batch's surplus after merging.
+fn scan_pattern_parallel(
+    index: &SemanticIndex,
+    root: &Path,
+    regex: &Regex,
+    options: &OperationOptions,
+    files: &[(u16, &PathBuf)],
+    cap: usize,
+) -> Vec<std::result::Result<Vec<PatternMatch>, String>> {
+    let batch_size = rayon::current_num_threads() * PATTERN_FILES_PER_WORKER;
+    let mut results = Vec::new();
+    let mut found = 0;
+
+    for batch in files.chunks(batch_size.max(1)) {
+        if found >= cap {
+            break;
+        }
+        let remaining = cap - found;
+        let scanned: Vec<_> = batch
+            .par_iter()
+            .map_init(
+                || FileCache::new(root),
+                |cache, &(file_id, file_path)| {
+                    scan_pattern_file(index, cache, regex, options, file_id, file_path, remaining)
+                },
+            )
+            .collect();
+        found += scanned
+            .iter()
+            .filter_map(|r| r.as_ref().ok())
+            .map(Vec::len)
+            .sum::<usize>();
+        results.extend(scanned);
+    }
+
+    results
+}
+
+/// Collect up to `cap` matches from one file, or a warning if it is unreadable
+fn scan_pattern_file(
+    index: &SemanticIndex,
+    cache: &mut FileCache,
+    regex: &Regex,
+    options: &OperationOptions,
+    file_id: u16,
+    file_path: &Path,
+    cap: usize,
+) -> std::result::Result<Vec<PatternMatch>, String> {
+    let Some(line_count) = cache.line_count(file_path) else {
+        return Err(format!("could not read {}", file_path.display()));
+    };
+
+    let mut matches = Vec::new();
+    for line_num in 1..=line_count as u32 {
+        if matches.len() >= cap {
+            break;
+        }
+        let Some(line_content) = cache.get_line(file_path, line_num) else {
+            continue;
+        };
+        let Some(mat) = regex.find(&line_content) else {
+            continue;
+        };
+
+        let context = if options.context > 0 {
+            cache
+                .get_context(file_path, line_num, options.context, options.context)
+                .map(|ctx| ctx.format(false))
+                .unwrap_or_else(|| line_content.clone())
+        } else {
+            line_content.trim().to_string()
+        };
+
+        let enclosing = find_enclosing_symbol(index, file_id, line_num);
+        let nearest = enclosing
+            .clone()
+            .or_else(|| find_preceding_symbol(index, file_id, line_num));
+
+        matches.push(PatternMatch {
+            file: file_path.to_string_lossy().to_string(),
+            line: line_num,
+            column: mat.start() as u16,
+            matched_text: mat.as_str().to_string(),
+            context,
+            enclosing_symbol: enclosing,
+            nearest_symbol: nearest,
+        });
+    }
+
+    Ok(matches)
+}
+
+// =============================================================================
+// PHASE 9: SCOPE ANALYSIS
+// =============================================================================
+
+/// Analyze scope at a specific location
+async fn analyze_scope_cmd(
+    project: &Project,
+    index: &SemanticIndex,
+    location: &str,
+    filter: &TraceFilter,
+) -> Result<ScopeResult> {
+    debug!(location = %location, "analyze_scope");
+    let _ = filter; // TODO: Apply filter to scope analysis
+
+    // Parse file:line format
+    let parts: Vec<&str> = location.rsplitn(2, ':').collect();
+    if parts.len() != 2 {
+        return Err(Error::SearchError {
+            message: "Location must be in format file:line".to_string(),
+        });
+    }
+
+    let line: u32 = parts[0].parse().map_err(|_| Error::SearchError {
+        message: "Invalid line number".to_string(),
+    })?;
+    let file_pattern = parts[1];
+
+    // Find the file
+    let file_id = index
+        .files
+        .iter()
+        .enumerate()
+        .find(|(_, p)| p.to_string_lossy().contains(file_pattern))
+        .map(|(id, _)| id as u16);
+
+    let file_id = match file_id {
+        Some(id) => id,
+        None => {
+            return Err(Error::SearchError {
+                message: format!("File not found: {}", file_pattern),
+            });
+        }
+    };
+
+    let file_path = index
+        .file_path(file_id)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    // Find enclosing scope
+    let enclosing_scope = find_enclosing_symbol(index, file_id, line);
+
+    // Declared types are read from the declarations in the source
+    let mut cache = FileCache::new(&project.root);
+    let mut locators = StatementLocators::new();
+    let mut declared_type = |name: &str, defined_at: u32| {
+        let token = index.tokens_in_file(file_id).find(|t| {
+            t.token_kind() == TokenKind::Variable
+                && t.line == defined_at
+                && index.token_name(t) == Some(name)
+        })?;
+        let path = index.file_path(file_id)?;
+        statement_locator(&mut cache, &mut locators, path)?.declared_type(token.line, token.column)
+    };
+
+    // Find local variables (symbols in the same scope that are defined before this line)
+    let mut local_variables = Vec::new();
+    let mut parameters = Vec::new();
+    let mut imports = Vec::new();
+
+    for symbol in index.symbols_in_file(file_id) {
+        let name = index.symbol_name(symbol).unwrap_or("<unknown>");
+        let kind = symbol_kind_str(symbol.symbol_kind());
+
+        // Check if this symbol is in scope at the given line
+        if symbol.start_line <= line && symbol.end_line >= line {
+            // This is the enclosing function/class
+            continue;
+        }
+
+        if symbol.start_line < line && symbol.end_line < line {
+            // Symbol defined before the line
+            match symbol.symbol_kind() {
+                SymbolKind::Variable | SymbolKind::Constant => {
+                    local_variables.push(ScopeVariable {
+                        name: name.to_string(),
+                        kind: kind.to_string(),
+                        defined_at: symbol.start_line,
+                        declared_type: declared_type(name, symbol.start_line),
+                    });
+                }
+                SymbolKind::Function | SymbolKind::Method => {
+                    // Could be a parameter if inside a function
+                    if let Some(ref scope) = enclosing_scope {
+                        if name != scope {
+                            parameters.push(ScopeVariable {
+                                name: name.to_string(),
+                                kind: kind.to_string(),
+                                defined_at: symbol.start_line,
+                                declared_type: None,
+                            });
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Block-scoped locals declared earlier in a scope that is still open
+    let scopes = ScopesByFile::new(index, Some(file_id));
+    let locals: Vec<(&str, u32)> = index
+        .tokens_in_file(file_id)
+        .filter(|t| t.token_kind() == TokenKind::Variable && t.line < line)
+        .filter(|t| {
+            scopes
+                .innermost(file_id, t.line)
+                .is_some_and(|s| s.end_line >= line)
+        })
+        .filter_map(|t| Some((index.token_name(t)?, t.line)))
+        .collect();
+    for (name, defined_at) in locals {
+        if local_variables
+            .iter()
+            .any(|v| v.name == name && v.defined_at == defined_at)
+        {
+            continue;
+        }
+        local_variables.push(ScopeVariable {
+            name: name.to_string(),
+            kind: "variable".to_string(),
+            defined_at,
+            declared_type: declared_type(name, defined_at),
+        });
+    }
+
+    // Find imports (tokens with Import kind in this file)
+    for token in index.tokens_in_file(file_id) {
+        if token.token_kind() == crate::trace::TokenKind::Import {
+            let name = index.token_name(token).unwrap_or("<unknown>");
+            imports.push(name.to_string());
+        }
+    }
+
+    Ok(ScopeResult {
+        file: file_path,
+        line,
+        enclosing_scope,
+        local_variables,
+        parameters,
+        imports,
+    })
+}
+
+// =============================================================================
+// DEPRECATED SYMBOLS
+// =============================================================================
+
+/// Find deprecated symbols and count their remaining usages
+async fn find_deprecated_cmd(
+    index: &SemanticIndex,
+    limit: Option<usize>,
+    filter: &TraceFilter,
+) -> Result<DeprecatedResult> {
+    debug!("find_deprecated filter={:?}", filter);
+
+    Ok(collect_deprecated(index, limit, filter))
+}
+
+/// Collect deprecated symbols from a loaded index, most used first
+fn collect_deprecated(
+    index: &SemanticIndex,
+    limit: Option<usize>,
+    filter: &TraceFilter,
+) -> DeprecatedResult {
+    let mut symbols = Vec::new();
+
+    for sym in index.symbols.iter().filter(|s| s.is_deprecated()) {
+        let file = index
+            .file_path(sym.file_id)
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|| "<unknown>".to_string());
+
+        let name = index.symbol_name(sym).unwrap_or("<unknown>").to_string();
+        let kind = symbol_kind_str(sym.symbol_kind()).to_string();
+
+        // Apply universal filter
+        if !filter.matches_symbol(&name, &kind, &file) || !filter.matches_visibility(sym) {
+            continue;
+        }
+
+        symbols.push(DeprecatedSymbol {
+            usages: index.references_to(sym.id).count(),
+            name,
+            kind,
+            file,
+            line: sym.start_line,
+        });
+    }
+
+    // Most used first - those are the biggest migration targets
+    symbols.sort_by(|a, b| {
+        b.usages
+            .cmp(&a.usages)
+            .then_with(|| (&a.file, a.line).cmp(&(&b.file, b.line)))
+    });
+
+    let total_deprecated = symbols.len();
+    let total_usages = symbols.iter().map(|s| s.usages).sum();
+
+    if let Some(limit) = limit {
+        symbols.truncate(limit);
+    }
+
+    DeprecatedResult {
+        symbols,
+        total_deprecated,
+        total_usages,
+    }
+}
+
+// =============================================================================
+// UNUSED PARAMETERS
+// =============================================================================
+
+/// Find function parameters that are never read in their function body
+async fn find_unused_params_cmd(
+    index: &SemanticIndex,
+    limit: Option<usize>,
+    filter: &TraceFilter,
+) -> Result<UnusedParamsResult> {
+    debug!("find_unused_params filter={:?}", filter);
+
+    let mut result = collect_unused_params(index, filter);
+    if let Some(limit) = limit {
+        result.params.truncate(limit);
+    }
+    Ok(result)
+}
+
+/// Collect parameters with zero reads inside their enclosing function scope
+///
+/// Underscore-prefixed names (and `self`/`cls` receivers) are skipped, since
+/// they are unused by convention.
+fn collect_unused_params(index: &SemanticIndex, filter: &TraceFilter) -> UnusedParamsResult {
+    // Group once: function scopes by file, and reads (non-binding tokens) by file and name
+    let mut function_scopes: HashMap<u16, Vec<&Scope>> = HashMap::new();
+    for scope in index.scopes.iter().filter(|s| s.is_function()) {
+        function_scopes
+            .entry(scope.file_id)
+            .or_default()
+            .push(scope);
+    }
+    let mut reads: HashMap<(u16, &str), Vec<u32>> = HashMap::new();
+    let mut parameters = Vec::new();
+    for token in &index.tokens {
+        match token.token_kind() {
+            TokenKind::Parameter => parameters.push(token),
+            TokenKind::Variable => {}
+            _ => {
+                if let Some(name) = index.token_name(token) {
+                    reads
+                        .entry((token.file_id, name))
+                        .or_default()
+                        .push(token.line);
+                }
+            }
+        }
+    }
+
+    let mut params = Vec::new();
+    for token in parameters {
+        let Some(name) = index.token_name(token) else {
+            continue;
+        };
+        if name.starts_with('_') || name == "self" || name == "cls" {
+            continue;
+        }
+
+        // Innermost function scope that contains the parameter
+        let Some(scope) = function_scopes
+            .get(&token.file_id)
+            .into_iter()
+            .flatten()
+            .filter(|s| s.start_line <= token.line && s.end_line >= token.line)
+            .min_by_key(|s| s.end_line - s.start_line)
+        else {
+            continue;
+        };
+
+        let read = reads.get(&(token.file_id, name)).is_some_and(|lines| {
+            lines
+                .iter()
+                .any(|&line| line >= scope.start_line && line <= scope.end_line)
+        });
+        if read {
+            continue;
+        }
+
+        let file = index
+            .file_path(token.file_id)
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|| "<unknown>".to_string());
+        let function = find_enclosing_symbol(index, token.file_id, token.line)
+            .unwrap_or_else(|| "<anonymous>".to_string());
+
+        if !filter.matches_symbol(&function, "function", &file) {
+            continue;
+        }
+
+        params.push(UnusedParam {
+            function,
+            name: name.to_string(),
+            file,
+            line: token.line,
+            column: token.column,
+        });
+    }
+
+    params.sort_by(|a, b| (&a.file, a.line, a.column).cmp(&(&b.file, b.line, b.column)));
+    let total = params.len();
+
+    UnusedParamsResult { params, total }
+}
+
+// =============================================================================
+// SHADOWED VARIABLES
+// =============================================================================
+
+/// Find variable declarations that shadow a binding of an enclosing scope
+async fn find_shadows_cmd(
+    index: &SemanticIndex,
+    limit: Option<usize>,
+    filter: &TraceFilter,
+) -> Result<ShadowsResult> {
+    debug!("find_shadows filter={:?}", filter);
+
+    let mut result = collect_shadows(index, filter);
+    if let Some(limit) = limit {
+        result.shadows.truncate(limit);
+    }
+    Ok(result)
+}
+
+/// Scopes grouped by file, narrowest first, for innermost-scope lookups
+struct ScopesByFile<'a> {
+    by_file: HashMap<u16, Vec<&'a Scope>>,
+}
+
+impl<'a> ScopesByFile<'a> {
+    /// Group the scopes of every file, or only of `file_id` when given
+    fn new(index: &'a SemanticIndex, file_id: Option<u16>) -> Self {
+        let mut by_file: HashMap<u16, Vec<&Scope>> = HashMap::new();
+        for scope in &index.scopes {
+            if file_id.map_or(true, |id| id == scope.file_id) {
+                by_file.entry(scope.file_id).or_default().push(scope);
+            }
+        }
+        // Scopes starting on the same line (a function and its body block) tie
+        // on span; the later one is nested deeper
+        for scopes in by_file.values_mut() {
+            scopes.sort_by_key(|s| (s.end_line - s.start_line, std::cmp::Reverse(s.id)));
+        }
+        Self { by_file }
+    }
+
+    /// Innermost scope of `file_id` containing `line`
+    fn innermost(&self, file_id: u16, line: u32) -> Option<&'a Scope> {
+        self.by_file
+            .get(&file_id)?
+            .iter()
+            .find(|s| s.start_line <= line && s.end_line >= line)
+            .copied()
+    }
+}
+
+/// Collect variable declarations whose name is already bound in an enclosing scope
+///
+/// Parameters and block-scoped variables are bindings. Each declaration is
+/// placed in its innermost scope, then the scope tree is walked outwards for
+/// an earlier binding of the same name; redeclarations within one scope are
+/// not reported. Underscore-prefixed names are skipped.
+fn collect_shadows(index: &SemanticIndex, filter: &TraceFilter) -> ShadowsResult {
+    let is_binding =
+        |t: &&Token| matches!(t.token_kind(), TokenKind::Parameter | TokenKind::Variable);
+
+    let scopes = ScopesByFile::new(index, None);
+
+    // Bindings of each scope, keyed by name
+    let mut bindings: HashMap<(u32, &str), Vec<&Token>> = HashMap::new();
+    let mut declarations = Vec::new();
+    for token in index.tokens.iter().filter(is_binding) {
+        let Some(name) = index.token_name(token) else {
+            continue;
+        };
+        if name.starts_with('_') {
+            continue;
+        }
+        let Some(scope) = scopes.innermost(token.file_id, token.line) else {
+            continue;
+        };
+        bindings.entry((scope.id, name)).or_default().push(token);
+        if token.token_kind() == TokenKind::Variable {
+            declarations.push((token, name, scope));
+        }
+    }
+
+    let mut shadows = Vec::new();
+    for (token, name, scope) in declarations {
+        let before = |outer: &&&Token| (outer.line, outer.column) < (token.line, token.column);
+        let mut parent = index.scope(scope.parent_id);
+        let shadowed = loop {
+            let Some(outer) = parent.filter(|p| p.file_id == token.file_id) else {
+                break None;
+            };
+            let earlier = bindings
+                .get(&(outer.id, name))
+                .and_then(|tokens| tokens.iter().rfind(before));
+            if let Some(earlier) = earlier {
+                break Some(*earlier);
+            }
+            parent = index.scope(outer.parent_id);
+        };
+        let Some(shadowed) = shadowed else {
+            continue;
+        };
+
+        let file = index
+            .file_path(token.file_id)
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|| "<unknown>".to_string());
+        let function = find_enclosing_symbol(index, token.file_id, token.line)
+            .unwrap_or_else(|| "<anonymous>".to_string());
+
+        if !filter.matches_symbol(&function, "function", &file) {
+            continue;
+        }
+
+        shadows.push(ShadowedVariable {
+            function,
+            name: name.to_string(),
+            file,
+            line: token.line,
+            column: token.column,
+            shadowed_line: shadowed.line,
+            shadowed_column: shadowed.column,
+        });
+    }
+
+    shadows.sort_by(|a, b| (&a.file, a.line, a.column).cmp(&(&b.file, b.line, b.column)));
+    let total = shadows.len();
+
+    ShadowsResult { shadows, total }
+}
+
+// =============================================================================
+// STUB FUNCTIONS
+// =============================================================================
+
+/// Find functions whose body is empty or only a placeholder
+async fn find_stubs_cmd(
+    project: &Project,
+    index: &SemanticIndex,
+    limit: Option<usize>,
+    filter: &TraceFilter,
+) -> Result<StubsResult> {
+    debug!("find_stubs filter={:?}", filter);
+
+    let mut result = collect_stubs(&project.root, index, filter);
+    if let Some(limit) = limit {
+        result.stubs.truncate(limit);
+    }
+    Ok(result)
+}
+
+/// Parse each indexed file and collect its stub functions
+///
+/// Stubs are matched to indexed symbols by name and definition line for
+/// their kind; files in languages without tree-sitter support are skipped.
+fn collect_stubs(root: &Path, index: &SemanticIndex, filter: &TraceFilter) -> StubsResult {
+    let mut stubs: Vec<StubSymbol> = index
+        .files
+        .par_iter()
+        .enumerate()
+        .flat_map_iter(|(file_id, path)| {
+            let file = path.to_string_lossy().to_string();
+            let language = detect_language(path);
+            let found = if filter.matches_path(&file) && is_treesitter_supported(language) {
+                std::fs::read_to_string(root.join(path))
+                    .ok()
+                    .and_then(|content| stub_functions(&content, language))
+                    .unwrap_or_default()
+            } else {
+                Vec::new()
+            };
+
+            found.into_iter().filter_map(move |stub| {
+                let symbol = index.symbols_in_file(file_id as u16).find(|s| {
+                    s.start_line == stub.line && index.symbol_name(s) == Some(stub.name.as_str())
+                });
+                if symbol.is_some_and(|s| !filter.matches_visibility(s)) {
+                    return None;
+                }
+                let kind = symbol.map_or("function", |s| symbol_kind_str(s.symbol_kind()));
+                if !filter.matches_symbol(&stub.name, kind, &file) {
+                    return None;
+                }
+                Some(StubSymbol {
+                    name: stub.name,
+                    kind: kind.to_string(),
+                    file: file.clone(),
+                    line: stub.line,
+                    placeholder: stub.placeholder,
+                })
+            })
+        })
+        .collect();
+
+    stubs.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+    let total = stubs.len();
+
+    StubsResult { stubs, total }
+}
+
+// =============================================================================
+// HOTSPOTS
+// =============================================================================
+
+/// Rank files by references to their symbols times git churn
+async fn find_hotspots_cmd(
+    project: &Project,
+    index: &SemanticIndex,
+    limit: Option<usize>,
+    filter: &TraceFilter,
+) -> Result<HotspotResult> {
+    debug!(?filter, "find_hotspots");
+
+    let refs = file_reference_counts(index, filter);
+    let churn = git_churn(&project.root);
+
+    Ok(rank_hotspots(refs, churn.as_ref(), limit))
+}
+
+/// Count references to symbols defined in each file
+fn file_reference_counts(index: &SemanticIndex, filter: &TraceFilter) -> HashMap<String, usize> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+
+    for reference in &index.references {
+        let Some(symbol) = index.symbol(reference.symbol_id) else {
+            continue;
+        };
+        let Some(path) = index.file_path(symbol.file_id) else {
+            continue;
+        };
+        let file = path.to_string_lossy().to_string();
+        if filter.matches_path(&file) {
+            *counts.entry(file).or_insert(0) += 1;
+        }
+    }
+
+    counts
+}
+
+/// Commits touching each file, or None outside a git repository
+///
+/// Paths are relative to `root`, which may be a subdirectory of the
+/// repository; files outside it are left out.
+fn git_churn(root: &Path) -> Option<HashMap<String, usize>> {
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(["log", "--format=", "--name-only", "--relative"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let mut churn: HashMap<String, usize> = HashMap::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if !line.is_empty() {
+            *churn.entry(line.to_string()).or_insert(0) += 1;
+        }
+    }
+    Some(churn)
+}
+
+/// Score files by refs * churn (refs alone without churn) and rank them
+fn rank_hotspots(
+    refs: HashMap<String, usize>,
+    churn: Option<&HashMap<String, usize>>,
+    limit: Option<usize>,
+) -> HotspotResult {
+    let mut files: Vec<HotspotFile> = refs
+        .into_iter()
+        .map(|(file, refs)| {
+            let file_churn = churn.map(|c| c.get(&file).copied().unwrap_or(0));
+            HotspotFile {
+                score: file_churn.map_or(refs, |c| refs * c),
+                churn: file_churn,
+                refs,
+                file,
+            }
+        })
+        .collect();
+
+    files.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| b.refs.cmp(&a.refs))
+            .then_with(|| a.file.cmp(&b.file))
+    });
+    files.truncate(limit.unwrap_or(20));
+
+    HotspotResult {
+        files,
+        churn_available: churn.is_some(),
+    }
+}
+
+// =============================================================================
+// PHASE 10: STATISTICS
+// =============================================================================
+
+/// Compute codebase statistics
+async fn compute_stats_cmd(
+    project: &Project,
+    index: &SemanticIndex,
+    filter: &TraceFilter,
+    with_dates: bool,
+) -> Result<StatsResult> {
+    debug!(with_dates, "compute_stats");
+
+    let mut result = compute_stats(index, filter);
+    if with_dates {
+        result.last_modified = collect_symbol_dates(&project.root, index, filter);
+    }
+    Ok(result)
+}
+
+/// Last-modified date of every symbol passing the filter, oldest first
+fn collect_symbol_dates(
+    root: &Path,
+    index: &SemanticIndex,
+    filter: &TraceFilter,
+) -> Vec<SymbolDate> {
+    let ids = index
+        .symbols
+        .iter()
+        .filter(|s| stats_symbol_passes(index, filter, s))
+        .map(|s| s.id);
+    let dates = blame::symbol_dates(root, index, ids);
+
+    let mut dated: Vec<SymbolDate> = dates
+        .into_iter()
+        .filter_map(|(id, last_modified)| {
+            let symbol = index.symbol(id)?;
+            Some(SymbolDate {
+                name: index.symbol_name(symbol)?.to_string(),
+                kind: symbol_kind_str(symbol.symbol_kind()).to_string(),
+                file: index
+                    .file_path(symbol.file_id)?
+                    .to_string_lossy()
+                    .to_string(),
+                line: symbol.start_line,
+                last_modified,
+            })
+        })
+        .collect();
+    dated.sort_by(|a, b| {
+        (&a.last_modified, &a.file, a.line).cmp(&(&b.last_modified, &b.file, b.line))
+    });
+    dated
+}
+
+/// Whether a symbol counts towards statistics under the filter
+fn stats_symbol_passes(index: &SemanticIndex, filter: &TraceFilter, symbol: &Symbol) -> bool {
+    if let Some(path) = index.file_path(symbol.file_id) {
+        let name = index.symbol_name(symbol).unwrap_or("");
+        let kind = symbol_kind_str(symbol.symbol_kind());
+        let path = path.to_string_lossy();
+        filter.matches_symbol(name, kind, &path)
+            && filter.matches_visibility(symbol)
+            && !filter.excludes_vendored(&path)
+    } else {
+        false
+    }
+}
+
+/// Compute statistics from a loaded index, applying the universal filter
+pub fn compute_stats(index: &SemanticIndex, filter: &TraceFilter) -> StatsResult {
+    let stats = index.stats();
+
+    // Helper to check if a file passes the filter
+    let file_passes = |file_id: u16| -> bool {
+        if let Some(path) = index.file_path(file_id) {
+            filter.matches_path(&path.to_string_lossy())
+        } else {
+            false
+        }
+    };
+
+    // Helper to check if a symbol passes the filter
+    let symbol_passes = |symbol: &Symbol| stats_symbol_passes(index, filter, symbol);
+
+    // Count symbols by kind (filtered)
+    let mut symbols_by_kind: HashMap<String, usize> = HashMap::new();
+    let mut symbols_by_visibility: HashMap<String, usize> = HashMap::new();
+    let mut filtered_symbol_count = 0;
+    for symbol in &index.symbols {
+        if symbol_passes(symbol) {
+            filtered_symbol_count += 1;
+            *symbols_by_kind
+                .entry(symbol_kind_str(symbol.symbol_kind()).to_string())
+                .or_insert(0) += 1;
+            *symbols_by_visibility
+                .entry(symbol.visibility().as_str().to_string())
+                .or_insert(0) += 1;
+        }
+    }
+
+    // Count files by extension (filtered)
+    let mut files_by_extension: HashMap<String, usize> = HashMap::new();
+    let mut filtered_file_count = 0;
+    for (idx, file) in index.files.iter().enumerate() {
+        if file_passes(idx as u16) {
+            filtered_file_count += 1;
+            let ext = file
+                .extension()
+                .map(|e| e.to_string_lossy().to_string())
+                .unwrap_or_else(|| "unknown".to_string());
+            *files_by_extension.entry(ext).or_insert(0) += 1;
+        }
+    }
+
+    // Find most referenced symbols (aggregated by name, filtered)
+    let mut symbol_ref_counts: HashMap<String, usize> = HashMap::new();
+    for s in &index.symbols {
+        if symbol_passes(s) {
+            if let Some(name) = index.symbol_name(s) {
+                let ref_count = index.references_to(s.id).count();
+                if ref_count > 0 {
+                    *symbol_ref_counts.entry(name.to_string()).or_insert(0) += ref_count;
+                }
+            }
+        }
+    }
+    let mut sorted_refs: Vec<_> = symbol_ref_counts.into_iter().collect();
+    sorted_refs.sort_by(|a, b| b.1.cmp(&a.1));
+    let most_referenced: Vec<_> = sorted_refs.into_iter().take(10).collect();
+
+    // Find largest files (by symbol count, filtered)
+    let mut file_symbol_counts: HashMap<u16, usize> = HashMap::new();
+    for symbol in &index.symbols {
+        if file_passes(symbol.file_id) {
+            *file_symbol_counts.entry(symbol.file_id).or_insert(0) += 1;
+        }
+    }
+    let mut largest_files: Vec<_> = file_symbol_counts
+        .into_iter()
+        .filter_map(|(file_id, count)| {
+            let path = index.file_path(file_id)?;
+            Some((path.to_string_lossy().to_string(), count))
+        })
+        .collect();
+    largest_files.sort_by(|a, b| b.1.cmp(&a.1));
+    largest_files.truncate(10);
+
+    // Use filtered counts if filter is active, otherwise use global stats
+    let (total_files, total_symbols) = if !filter.paths.is_empty()
+        || filter.symbol_type.is_some()
+        || filter.name_pattern.is_some()
+        || !filter.exclude_kinds.is_empty()
+        || filter.exclude_vendor
+        || filter.visibility.is_some()
+    {
+        (filtered_file_count, filtered_symbol_count)
+    } else {
+        (stats.files, stats.symbols)
+    };
+
+    // Calculate call graph stats over the entry points that pass the filter,
+    // so e.g. `--in src` leaves test entry points out of the averages
+    let depth_entries: Vec<u32> = index
+        .entry_points
+        .iter()
+        .copied()
+        .filter(|&id| index.symbol(id).is_some_and(symbol_passes))
+        .collect();
+    let max_call_depth = calculate_max_call_depth(index, &depth_entries);
+    let avg_call_depth = calculate_avg_call_depth(index, &depth_entries);
+
+    StatsResult {
+        total_files,
+        total_symbols,
+        total_tokens: stats.tokens, // Not filtered (token-level filtering is expensive)
+        total_references: stats.references, // Not filtered
+        total_edges: stats.edges,   // Not filtered
+        total_entry_points: stats.entry_points,
+        symbols_by_kind,
+        symbols_by_visibility,
+        files_by_extension,
+        most_referenced,
+        largest_files,
+        max_call_depth,
+        avg_call_depth,
+        depth_entry_points: depth_entries.len(),
+        last_modified: Vec::new(),
+    }
+}
+
+fn calculate_max_call_depth(index: &SemanticIndex, entry_points: &[u32]) -> usize {
+    let mut max_depth = 0;
+
+    for &entry_id in entry_points {
+        let depth = calculate_depth_from(index, entry_id, &mut HashSet::new());
+        max_depth = max_depth.max(depth);
+    }
+
+    max_depth
+}
+
+fn calculate_depth_from(index: &SemanticIndex, sym_id: u32, visited: &mut HashSet<u32>) -> usize {
+    if visited.contains(&sym_id) {
+        return 0;
+    }
+    visited.insert(sym_id);
+
+    let callees = index.callees(sym_id);
+    if callees.is_empty() {
+        return 0;
+    }
+
+    let max_child_depth = callees
+        .iter()
+        .map(|&callee| calculate_depth_from(index, callee, visited))
+        .max()
+        .unwrap_or(0);
+
+    max_child_depth + 1
+}
+
+fn calculate_avg_call_depth(index: &SemanticIndex, entry_points: &[u32]) -> f32 {
+    if entry_points.is_empty() {
+        return 0.0;
+    }
+
+    let total_depth: usize = entry_points
+        .iter()
+        .map(|&id| calculate_depth_from(index, id, &mut HashSet::new()))
+        .sum();
+
+    total_depth as f32 / entry_points.len() as f32
+}
+
+// =============================================================================
+// TRACE OPERATIONS
+// =============================================================================
+
+/// A definition offered when a symbol name is ambiguous
+#[derive(Debug, Clone)]
+struct DefinitionCandidate {
+    /// Symbols at this location (extractors may record one definition twice)
+    symbol_ids: Vec<u32>,
+    /// `file:line (kind)`, as listed in the picker
+    label: String,
+}
+
+/// Definitions of `name`, one per location, ordered by file and line
+fn definition_candidates(index: &SemanticIndex, name: &str) -> Vec<DefinitionCandidate> {
+    group_by_location(index, index.resolve_symbols(name))
+}
+
+/// Group symbols by definition location, ordered by file and line
+fn group_by_location(
+    index: &SemanticIndex,
+    symbol_ids: impl IntoIterator<Item = u32>,
+) -> Vec<DefinitionCandidate> {
+    let mut by_location: BTreeMap<(String, u32), DefinitionCandidate> = BTreeMap::new();
+    for id in symbol_ids {
+        let Some(sym) = index.symbol(id) else {
+            continue;
+        };
+        let file = index
+            .file_path(sym.file_id)
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default();
+        by_location
+            .entry((file.clone(), sym.start_line))
+            .or_insert_with(|| DefinitionCandidate {
+                symbol_ids: Vec::new(),
+                label: format!(
+                    "{}:{} ({})",
+                    file,
+                    sym.start_line,
+                    symbol_kind_str(sym.symbol_kind())
+                ),
+            })
+            .symbol_ids
+            .push(id);
+    }
+    by_location.into_values().collect()
+}
+
+/// One labeled section per definition location of `symbol`
+///
+/// `collect` builds the result for one definition's symbols. The sections
+/// are wrapped in a summary whose totals add up theirs; a single definition
+/// is returned as is.
+fn definition_sections(
+    index: &SemanticIndex,
+    symbol: &str,
+    symbol_ids: &[u32],
+    mut collect: impl FnMut(&[u32]) -> TraceResult,
+) -> TraceResult {
+    let locations = group_by_location(index, symbol_ids.iter().copied());
+    if locations.len() <= 1 {
+        return collect(symbol_ids);
+    }
+
+    let count = locations.len();
+    let definitions: Vec<TraceResult> = locations
+        .iter()
+        .enumerate()
+        .map(|(i, location)| {
+            let mut section = collect(&location.symbol_ids);
+            section.symbol = format!("{} (definition {} of {})", section.symbol, i + 1, count);
+            section
+        })
+        .collect();
+
+    TraceResult {
+        symbol: symbol.to_string(),
+        defined_at: None,
+        kind: definitions[0].kind.clone(),
+        invocation_paths: Vec::new(),
+        total_paths: definitions.iter().map(|d| d.total_paths).sum(),
+        entry_points: definitions.iter().map(|d| d.entry_points).sum(),
+        definitions,
+        symbol_id: None,
+        stable_id: None,
+    }
+}
+
+/// Symbol ids to trace among `candidates`
+///
+/// `pick` is only consulted in interactive mode when there is more than one
+/// candidate; it receives the labels and returns the chosen index, or `None`
+/// if the user cancelled.
+fn resolve_definitions(
+    candidates: &[DefinitionCandidate],
+    choice: DefinitionChoice,
+    pick: impl FnOnce(&[String]) -> Result<Option<usize>>,
+) -> Result<Vec<u32>> {
+    if candidates.len() <= 1 || choice == DefinitionChoice::All {
+        return Ok(candidates
+            .iter()
+            .flat_map(|c| c.symbol_ids.iter().copied())
+            .collect());
+    }
+    if choice == DefinitionChoice::First {
+        return Ok(candidates[0].symbol_ids.clone());
+    }
+
+    let labels: Vec<String> = candidates.iter().map(|c| c.label.clone()).collect();
+    match pick(&labels)? {
+        Some(i) if i < candidates.len() => Ok(candidates[i].symbol_ids.clone()),
+        _ => Err(Error::SearchError {
+            message: "No definition selected".to_string(),
+        }),
+    }
+}
+
+/// Prompt for one of several definitions of `symbol` on stderr
+fn pick_definition(symbol: &str, labels: &[String]) -> Result<Option<usize>> {
+    Select::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("{} definitions of {}", labels.len(), symbol))
+        .items(labels)
+        .default(0)
+        .interact_opt()
+        .map_err(|e| Error::SearchError {
+            message: format!("Selection failed: {}", e),
+        })
+}
+
+/// Trace symbol invocation paths
+async fn trace_symbol_cmd(
+    index: &SemanticIndex,
+    symbol: &str,
+    max_depth: usize,
+    direct: bool,
+    choice: DefinitionChoice,
+    merge: bool,
+    filter: &TraceFilter,
+) -> Result<TraceResult> {
+    debug!(symbol = %symbol, max_depth, direct, ?choice, merge, ?filter, "trace_symbol");
+
+    // A pattern means every symbol it matches: nothing to pick, nothing to expand
+    let pattern = is_glob_pattern(symbol);
+    let choice = if pattern {
+        DefinitionChoice::All
+    } else {
+        choice
+    };
+
+    // Narrow the queried name itself; alias and AI expansions stay merged
+    let selected = resolve_definitions(&definition_candidates(index, symbol), choice, |labels| {
+        pick_definition(symbol, labels)
+    })?;
+
+    // Determine symbols to search for: configured aliases first (offline),
+    // then AI expansion unless in direct mode
+    let mut symbols_to_search = expand_aliases(symbol, &Config::load()?.aliases);
+    if !direct && !pattern {
+        for expanded in expand_query_with_ai(symbol).await {
+            if !symbols_to_search.contains(&expanded) {
+                symbols_to_search.push(expanded);
+            }
+        }
+    }
+
+    debug!(symbols = ?symbols_to_search, "Searching for symbols");
+
+    // Find and trace all matching symbols
+    let mut all_trace_results = Vec::new();
+    for sym_name in &symbols_to_search {
+        if sym_name == symbol {
+            all_trace_results.extend(
+                selected
+                    .iter()
+                    .map(|&id| trace_symbol(index, id, Some(max_depth))),
+            );
+        } else {
+            all_trace_results.extend(trace_symbol_by_name(index, sym_name, Some(max_depth)));
+        }
+    }
+
+    if all_trace_results.is_empty() {
+        return Ok(TraceResult {
+            symbol: symbol.to_string(),
+            defined_at: None,
+            kind: "unknown".to_string(),
+            invocation_paths: Vec::new(),
+            total_paths: 0,
+            entry_points: 0,
+            definitions: Vec::new(),
+            symbol_id: None,
+            stable_id: None,
+        });
+    }
+
+    let mut result = if merge {
+        collect_trace(index, symbol, &all_trace_results)
+    } else {
+        let targets: Vec<u32> = all_trace_results.iter().map(|r| r.target).collect();
+        definition_sections(index, symbol, &targets, |ids| {
+            let results: Vec<TraverseTraceResult> = all_trace_results
+                .iter()
+                .filter(|r| ids.contains(&r.target))
+                .cloned()
+                .collect();
+            // Sections may come from alias expansions: label them by their own name
+            let name = index
+                .symbol(ids[0])
+                .and_then(|s| index.symbol_name(s))
+                .unwrap_or(symbol);
+            collect_trace(index, name, &results)
+        })
+    };
+
+    // AI reranking when not in direct mode
+    if !direct {
+        for section in std::iter::once(&mut result.invocation_paths).chain(
+            result
+                .definitions
+                .iter_mut()
+                .map(|d| &mut d.invocation_paths),
+        ) {
+            *section = rerank_paths_with_ai(symbol, std::mem::take(section)).await;
+        }
+    }
+
+    Ok(result)
+}
+
+/// Convert traverse results for `symbol` into one output result
+fn collect_trace(
+    index: &SemanticIndex,
+    symbol: &str,
+    trace_results: &[TraverseTraceResult],
+) -> TraceResult {
+    // Convert traverse results to output format
+    let mut invocation_paths = Vec::new();
+    let mut entry_points_set = std::collections::HashSet::new();
+
+    for trace_result in trace_results {
+        for path in &trace_result.paths {
+            let entry_symbol = index.symbol(path.entry_point);
+            let entry_name = entry_symbol
+                .and_then(|s| index.symbol_name(s))
+                .unwrap_or("<unknown>");
+            let entry_file = entry_symbol
+                .and_then(|s| index.file_path(s.file_id))
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let entry_kind = entry_symbol
+                .map(|s| symbol_kind_str(s.symbol_kind()))
+                .unwrap_or("function");
+
+            entry_points_set.insert(path.entry_point);
+
+            let chain: Vec<ChainStep> = path
+                .chain
+                .iter()
+                .enumerate()
+                .filter_map(|(i, &sym_id)| {
+                    let sym = index.symbol(sym_id)?;
+                    let name = index.symbol_name(sym)?;
+                    let file = index
+                        .file_path(sym.file_id)
+                        .map(|p| p.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    let line = if i > 0 {
+                        path.call_lines
+                            .get(i - 1)
+                            .copied()
+                            .unwrap_or(sym.start_line)
+                    } else {
+                        sym.start_line
+                    };
+
+                    Some(ChainStep {
+                        symbol: name.to_string(),
+                        file,
+                        line,
+                        column: None,
+                        context: None,
+                    })
+                })
+                .collect();
+
+            if !chain.is_empty() {
+                invocation_paths.push(InvocationPath {
+                    entry_point: format!("{} ({})", entry_name, entry_file),
+                    entry_kind: entry_kind.to_string(),
+                    chain,
+                });
+            }
+        }
+    }
+
+    let first_target = trace_results.first().map(|r| r.target);
+    let defined_at = first_target.and_then(|id| {
+        let sym = index.symbol(id)?;
+        let file = index.file_path(sym.file_id)?;
+        Some(format!("{}:{}", file.display(), sym.start_line))
+    });
+    let kind = first_target
+        .and_then(|id| index.symbol(id))
+        .map(|s| symbol_kind_str(s.symbol_kind()).to_string())
+        .unwrap_or_else(|| "function".to_string());
+
+    TraceResult {
+        symbol: symbol.to_string(),
+        defined_at,
+        kind,
+        invocation_paths: invocation_paths.clone(),
+        total_paths: invocation_paths.len(),
+        entry_points: entry_points_set.len(),
+        definitions: Vec::new(),
+        symbol_id: first_target,
+        stable_id: first_target.and_then(|id| index.stable_symbol_id(id)),
+    }
+}
+
+// =============================================================================
+// AI ENHANCEMENT
+// =============================================================================
+
+/// Expand a query into related symbol names using AI
+async fn expand_query_with_ai(query: &str) -> Vec<String> {
+    let providers = auth::get_authenticated_providers();
+
+    if providers.is_empty() {
+        debug!("No AI provider authenticated, skipping query expansion");
+        return vec![query.to_string()];
+    }
+
+    if !is_natural_language_query(query) && query.len() > 15 {
+        return vec![query.to_string()];
+    }
+
+    let expanded = if providers.contains(&Provider::Anthropic) {
+        match auth::get_anthropic_token() {
+            Ok(token) => {
+                let client = ClaudeClient::new(token);
+                match client.expand_query(query).await {
+                    Ok(symbols) => {
+                        debug!(count = symbols.len(), "AI expanded query to symbols");
+                        symbols
+                    }
+                    Err(e) => {
+                        warn!("AI query expansion failed: {}", e);
+                        vec![query.to_string()]
+                    }
+                }
+            }
+            Err(e) => {
+                warn!("Failed to get Anthropic token: {}", e);
+                vec![query.to_string()]
+            }
+        }
+    } else if providers.contains(&Provider::Google) {
+        match auth::get_google_token() {
+            Ok(token) => {
+                let client = GeminiClient::new(token);
+                match client.expand_query(query).await {
+                    Ok(symbols) => {
+                        debug!(count = symbols.len(), "AI expanded query to symbols");
+                        symbols
+                    }
+                    Err(e) => {
+                        warn!("AI query expansion failed: {}", e);
+                        vec![query.to_string()]
+                    }
+                }
+            }
+            Err(e) => {
+                warn!("Failed to get Google token: {}", e);
+                vec![query.to_string()]
+            }
+        }
+    } else {
+        vec![query.to_string()]
+    };
+
+    let mut result = expanded;
+    if !result.iter().any(|s| s.eq_ignore_ascii_case(query)) {
+        result.insert(0, query.to_string());
+    }
+    result.truncate(10);
+    result
+}
+
+/// Describe paths for the AI reranker, keeping the first `rerank_max_items`
+fn rerank_path_descriptions(paths: &[InvocationPath], config: &AiConfig) -> Vec<String> {
+    paths
+        .iter()
+        .take(config.rerank_max_items.unwrap_or(usize::MAX))
+        .map(|p| {
+            let chain_str: Vec<String> = p.chain.iter().map(|c| c.symbol.clone()).collect();
+            format!(
+                "Entry: {} ({})\nChain: {}",
+                p.entry_point,
+                p.entry_kind,
+                chain_str.join(" -> ")
+            )
+        })
+        .collect()
+}
+
+/// Rerank invocation paths by relevance using AI
+async fn rerank_paths_with_ai(query: &str, mut paths: Vec<InvocationPath>) -> Vec<InvocationPath> {
+    let providers = auth::get_authenticated_providers();
+
+    if providers.is_empty() {
+        debug!("No AI provider authenticated, skipping reranking");
+        return paths;
+    }
+
+    if paths.len() <= 3 {
+        return paths;
+    }
+
+    let ai_config = Config::load().map(|c| c.ai).unwrap_or_default();
+    let path_descriptions = rerank_path_descriptions(&paths, &ai_config);
+
+    let indices = if providers.contains(&Provider::Anthropic) {
+        match auth::get_anthropic_token() {
+            Ok(token) => {
+                let client = ClaudeClient::new(token);
+                match client.rerank_trace(query, &path_descriptions).await {
+                    Ok(idx) => {
+                        debug!(order = ?idx, "AI reranked trace paths");
+                        idx
+                    }
+                    Err(e) => {
+                        warn!("AI reranking failed: {}", e);
+                        (0..paths.len()).collect()
+                    }
+                }
+            }
+            Err(e) => {
+                warn!("Failed to get Anthropic token: {}", e);
+                (0..paths.len()).collect()
+            }
+        }
+    } else if providers.contains(&Provider::Google) {
+        match auth::get_google_token() {
+            Ok(token) => {
+                let client = GeminiClient::new(token);
+                match client.rerank_trace(query, &path_descriptions).await {
+                    Ok(idx) => {
+                        debug!(order = ?idx, "AI reranked trace paths");
+                        idx
+                    }
+                    Err(e) => {
+                        warn!("AI reranking failed: {}", e);
+                        (0..paths.len()).collect()
+                    }
+                }
+            }
+            Err(e) => {
+                warn!("Failed to get Google token: {}", e);
+                (0..paths.len()).collect()
+            }
+        }
+    } else {
+        (0..paths.len()).collect()
+    };
+
+    let original_paths = std::mem::take(&mut paths);
+    let mut reranked = Vec::with_capacity(original_paths.len());
+
+    for &idx in &indices {
+        if idx < original_paths.len() {
+            reranked.push(original_paths[idx].clone());
+        }
+    }
+
+    for (i, path) in original_paths.into_iter().enumerate() {
+        if !indices.contains(&i) {
+            reranked.push(path);
+        }
+    }
+
+    reranked
+}
+
+// =============================================================================
+// DEAD CODE
+// =============================================================================
+
+/// Find dead/unused code
+async fn find_dead_code_cmd(
+    root: &Path,
+    index: &SemanticIndex,
+    limit: Option<usize>,
+    filter: &TraceFilter,
+    xref: bool,
+) -> Result<DeadCodeResult> {
+    debug!("find_dead_code filter={:?} xref={}", filter, xref);
+
+    let options = DeadCodeOptions {
+        annotation_root: Some(root.to_path_buf()),
+        ..DeadCodeOptions::from_config(&Config::load()?.dead_code)
+    };
+
+    Ok(collect_dead_code(index, limit, filter, xref, &options))
+}
+
+/// Collect dead symbols from a loaded index, applying the universal filter
+fn collect_dead_code(
+    index: &SemanticIndex,
+    limit: Option<usize>,
+    filter: &TraceFilter,
+    xref: bool,
+    options: &DeadCodeOptions,
+) -> DeadCodeResult {
+    let dead_symbols = find_dead_symbols_with(index, options);
+    let dead_ids: HashSet<u32> = dead_symbols.iter().map(|s| s.id).collect();
+
+    let mut symbols = Vec::new();
+    let mut by_kind: HashMap<String, usize> = HashMap::new();
+    let mut by_file: HashMap<String, usize> = HashMap::new();
+
+    for sym in dead_symbols {
+        let file = index
+            .file_path(sym.file_id)
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|| "<unknown>".to_string());
+
+        let name = index.symbol_name(sym).unwrap_or("<unknown>").to_string();
+        let kind = symbol_kind_str(sym.symbol_kind()).to_string();
+
+        // Apply universal filter
+        if !filter.matches_symbol(&name, &kind, &file)
+            || !filter.matches_visibility(sym)
+            || filter.excludes_vendored(&file)
+        {
+            continue;
+        }
+
+        *by_kind.entry(kind.clone()).or_insert(0) += 1;
+        *by_file.entry(file.clone()).or_insert(0) += 1;
+
+        // Cross-reference: find potential callers if enabled
+        let (potential_callers, likely_replacement) = if xref {
+            (
+                find_potential_callers(index, sym, &name),
+                find_likely_replacement(index, sym, &name, &dead_ids),
+            )
+        } else {
+            (Vec::new(), None)
+        };
+
+        symbols.push(DeadSymbol {
+            name,
+            kind,
+            file,
+            line: sym.start_line,
+            reason: "No references or calls found".to_string(),
+            potential_callers,
+            likely_replacement,
+        });
+    }
+
+    // Sort by file and line
+    symbols.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+
+    // Apply limit
+    if let Some(limit) = limit {
+        symbols.truncate(limit);
+    }
+
+    DeadCodeResult {
+        total_dead: symbols.len(),
+        symbols,
+        by_kind,
+        by_file,
+    }
+}
+
+/// Find potential callers for a dead symbol (for cross-referencing)
+fn find_potential_callers(
+    index: &SemanticIndex,
+    dead_sym: &crate::trace::Symbol,
+    dead_name: &str,
+) -> Vec<PotentialCaller> {
+    let mut callers = Vec::new();
+
+    // Strategy 1: Find functions in the same file that could call this
+    let same_file_symbols: Vec<_> = index
+        .symbols
+        .iter()
+        .filter(|s| {
+            s.file_id == dead_sym.file_id
+                && s.id != dead_sym.id
+                && matches!(s.symbol_kind(), SymbolKind::Function | SymbolKind::Method)
+        })
+        .collect();
+
+    for caller in same_file_symbols.iter().take(3) {
+        if let Some(caller_name) = index.symbol_name(caller) {
+            if let Some(path) = index.file_path(caller.file_id) {
+                callers.push(PotentialCaller {
+                    name: caller_name.to_string(),
+                    file: path.to_string_lossy().to_string(),
+                    line: caller.start_line,
+                    reason: "Same file - could call this".to_string(),
+                });
+            }
+        }
+    }
+
+    // Strategy 2: Find entry points that could use this
+    for &entry_id in index.entry_points.iter().take(2) {
+        if let Some(entry_sym) = index.symbols.iter().find(|s| s.id == entry_id) {
+            if entry_sym.id != dead_sym.id {
+                if let Some(entry_name) = index.symbol_name(entry_sym) {
+                    if let Some(path) = index.file_path(entry_sym.file_id) {
+                        callers.push(PotentialCaller {
+                            name: entry_name.to_string(),
+                            file: path.to_string_lossy().to_string(),
+                            line: entry_sym.start_line,
+                            reason: "Entry point - could reach this".to_string(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    // Strategy 3: Find token matches (name appears but not as actual reference)
+    if let Some(token_ids) = index.tokens_by_name(dead_name) {
+        for &token_id in token_ids.iter().take(2) {
+            if let Some(token) = index.token(token_id) {
+                // Skip if it's at the definition location
+                if token.file_id != dead_sym.file_id || token.line != dead_sym.start_line {
+                    if let Some(path) = index.file_path(token.file_id) {
+                        callers.push(PotentialCaller {
+                            name: dead_name.to_string(),
+                            file: path.to_string_lossy().to_string(),
+                            line: token.line,
+                            reason: "Token match - name appears here".to_string(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    callers
+}
+
+/// Closest-named live symbol in the same file or directory as a dead one
+///
+/// A small edit distance (`getUserOld` vs `getUser`) hints the dead symbol
+/// was superseded by a renamed version. Same-file candidates win ties.
+fn find_likely_replacement(
+    index: &SemanticIndex,
+    dead_sym: &Symbol,
+    dead_name: &str,
+    dead_ids: &HashSet<u32>,
+) -> Option<String> {
+    let dir = |file_id: u16| index.file_path(file_id).and_then(|p| p.parent());
+    let dead_dir = dir(dead_sym.file_id);
+    let dead_lower = dead_name.to_lowercase();
+
+    index
+        .symbols
+        .iter()
+        .filter(|s| !dead_ids.contains(&s.id) && s.id != dead_sym.id)
+        .filter(|s| s.file_id == dead_sym.file_id || dir(s.file_id) == dead_dir)
+        .filter_map(|s| {
+            let name = index.symbol_name(s)?;
+            if name == dead_name {
+                return None;
+            }
+            let distance = levenshtein(&dead_lower, &name.to_lowercase());
+            // Close enough to be a rename: at most half the longer name changed
+            let longest = dead_name.chars().count().max(name.chars().count());
+            (distance * 2 <= longest).then_some((distance, s.file_id != dead_sym.file_id, name))
+        })
+        .min()
+        .map(|(_, _, name)| name.to_string())
+}
+
+/// Edit distance between two strings, in characters
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace::output::TraceFormatter;
+
+    /// Build a small index with one dead symbol of each given kind under `src/`
+    /// and one dead function under `lib/`
+    fn dead_code_fixture(kinds: &[(&str, SymbolKind)]) -> SemanticIndex {
+        use crate::trace::{Symbol, SymbolFlags};
+
+        let mut index = SemanticIndex::new();
+        let src = index.add_file("src/app.rs".into());
+        let lib = index.add_file("lib/util.rs".into());
+
+        let mut next_id = 0;
+        for (name, kind) in kinds {
+            let offset = index.strings.intern(name);
+            index.add_symbol(
+                Symbol::new(
+                    next_id,
+                    offset,
+                    src,
+                    *kind,
+                    SymbolFlags::empty(),
+                    next_id * 10 + 1,
+                    next_id * 10 + 5,
+                ),
+                name,
+            );
+            next_id += 1;
+        }
+
+        let offset = index.strings.intern("lib_helper");
+        index.add_symbol(
+            Symbol::new(
+                next_id,
+                offset,
+                lib,
+                SymbolKind::Function,
+                SymbolFlags::empty(),
+                1,
+                5,
+            ),
+            "lib_helper",
+        );
+
+        index
+    }
+
+    #[test]
+    fn test_dead_code_exclude_kind() {
+        let index = dead_code_fixture(&[
+            ("unused_fn", SymbolKind::Function),
+            ("UNUSED_CONST", SymbolKind::Constant),
+            ("UnusedAlias", SymbolKind::TypeAlias),
+            ("unused_method", SymbolKind::Method),
+        ]);
+
+        let filter = TraceFilter {
+            exclude_kinds: vec!["constant".to_string(), "type_alias".to_string()],
+            ..Default::default()
+        };
+        let result = collect_dead_code(&index, None, &filter, false, &DeadCodeOptions::default());
+
+        let kinds: HashSet<_> = result.symbols.iter().map(|s| s.kind.as_str()).collect();
+        assert!(!kinds.contains("constant"));
+        assert!(!kinds.contains("type_alias"));
+        assert!(kinds.contains("function"));
+        assert!(kinds.contains("method"));
+        assert_eq!(result.total_dead, 3);
+
+        // Composes with --in
+        let filter = TraceFilter {
+            paths: vec!["src".to_string()],
+            exclude_kinds: vec!["constant".to_string(), "type_alias".to_string()],
+            ..Default::default()
+        };
+        let result = collect_dead_code(&index, None, &filter, false, &DeadCodeOptions::default());
+        let names: Vec<_> = result.symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["unused_fn", "unused_method"]);
+    }
+
+    #[test]
+    fn test_deprecated_symbols_with_usages() {
+        use crate::trace::SemanticIndexBuilder;
+        use tempfile::tempdir;
+
+        let code = r#"
+#[deprecated(note = "use new_api")]
+pub fn old_api() -> u32 {
+    1
+}
+
+pub fn new_api() -> u32 {
+    2
+}
+
+pub fn first() -> u32 {
+    old_api()
+}
+
+pub fn second() -> u32 {
+    old_api() + new_api()
+}
+"#;
+
+        let dir = tempdir().unwrap();
+        let mut builder = SemanticIndexBuilder::new(dir.path());
+        builder.add_file(&dir.path().join("lib.rs"), code);
+        let index = builder.build();
+
+        let result = collect_deprecated(&index, None, &TraceFilter::default());
+        assert_eq!(result.total_deprecated, 1);
+        assert_eq!(result.symbols[0].name, "old_api");
+        assert_eq!(result.symbols[0].usages, 2);
+        assert_eq!(result.total_usages, 2);
+    }
+
+    #[test]
+    fn test_unused_params_reports_only_unread() {
+        use crate::trace::SemanticIndexBuilder;
+        use tempfile::tempdir;
+
+        let code = r#"
+pub fn scale(value: u32, factor: u32, _ignored: u32) -> u32 {
+    value * 2
+}
+"#;
+
+        let dir = tempdir().unwrap();
+        let mut builder = SemanticIndexBuilder::new(dir.path());
+        builder.add_file(&dir.path().join("lib.rs"), code);
+        let index = builder.build();
+
+        let result = collect_unused_params(&index, &TraceFilter::default());
+        assert_eq!(result.total, 1);
+        assert_eq!(result.params[0].name, "factor");
+        assert_eq!(result.params[0].function, "scale");
+        assert_eq!(result.params[0].line, 2);
+    }
+
+    #[test]
+    fn test_impact_risk_score_many_entry_points() {
+        use crate::trace::{Edge, Symbol, SymbolFlags};
+
+        // 8 entry points in separate files all call `core`
+        let mut index = SemanticIndex::new();
+        let core_file = index.add_file("src/core.rs".into());
+        let core = index.strings.intern("core");
+        index.add_symbol(
+            Symbol::new(
+                0,
+                core,
+                core_file,
+                SymbolKind::Function,
+                SymbolFlags::empty(),
+                1,
+                5,
+            ),
+            "core",
+        );
+        for i in 1..=8u32 {
+            let file = index.add_file(format!("src/cmd_{}.rs", i).into());
+            let name = format!("cmd_{}", i);
+            let offset = index.strings.intern(&name);
+            index.add_symbol(
+                Symbol::new(
+                    i,
+                    offset,
+                    file,
+                    SymbolKind::Function,
+                    SymbolFlags::IS_ENTRY_POINT,
+                    1,
+                    5,
+                ),
+                &name,
+            );
+            index.add_edge(Edge::new(i, 0, 2));
+        }
+
+        let config = ImpactConfig::default();
+        let result = analyze_impact(&index, "core", 10, &config);
+
+        assert_eq!(result.risk_level, RiskLevel::Critical);
+        assert!(result.risk_score >= config.critical_threshold);
+        assert!(result
+            .risk_factors
+            .contains(&"8 entry points affected".to_string()));
+        assert!(result
+            .risk_factors
+            .contains(&"used across 9 files".to_string()));
+        assert!(!result.risk_factors.iter().any(|f| f == "in a cycle"));
+
+        // An entry point nobody calls only counts itself: low risk
+        let result = analyze_impact(&index, "cmd_1", 10, &config);
+        assert_eq!(result.risk_level, RiskLevel::Low);
+        assert!(result.risk_score < config.medium_threshold);
+    }
+
+    #[test]
+    fn test_impact_reports_path_to_entry_through_intermediary() {
+        use crate::trace::{Edge, JsonFormatter, PlainFormatter, Symbol, SymbolFlags};
+
+        // main (entry point) -> handle -> save
+        let mut index = SemanticIndex::new();
+        let file = index.add_file("src/app.rs".into());
+        for (id, (name, flags)) in [
+            ("save", SymbolFlags::empty()),
+            ("handle", SymbolFlags::empty()),
+            ("main", SymbolFlags::IS_ENTRY_POINT),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let offset = index.strings.intern(name);
+            let start = id as u32 * 10 + 1;
+            index.add_symbol(
+                Symbol::new(
+                    id as u32,
+                    offset,
+                    file,
+                    SymbolKind::Function,
+                    flags,
+                    start,
+                    start + 5,
+                ),
+                name,
+            );
+        }
+        index.add_edge(Edge::new(1, 0, 12));
+        index.add_edge(Edge::new(2, 1, 22));
+
+        let result = analyze_impact(&index, "save", 10, &ImpactConfig::default());
+        assert_eq!(result.paths_to_entry, vec![vec!["handle", "main"]]);
+
+        let json = JsonFormatter::new().format_impact(&result);
+        assert!(json.contains("\"paths_to_entry\""));
+        let plain = PlainFormatter::new().format_impact(&result);
+        assert!(plain.contains("save -> handle -> main"));
+
+        // Chains longer than --max-depth are not followed
+        let shallow = analyze_impact(&index, "save", 1, &ImpactConfig::default());
+        assert!(shallow.paths_to_entry.is_empty());
+    }
+
+    #[test]
+    fn test_call_hierarchy_json_nesting() {
+        use crate::trace::{Edge, Symbol, SymbolFlags};
+
+        // main -> a -> target, main -> b -> target, target -> target
+        let mut index = SemanticIndex::new();
+        let file = index.add_file("src/lib.rs".into());
+        for (id, name) in ["target", "a", "b", "main"].iter().enumerate() {
+            let offset = index.strings.intern(name);
+            let start = id as u32 * 10 + 1;
+            index.add_symbol(
+                Symbol::new(
+                    id as u32,
+                    offset,
+                    file,
+                    SymbolKind::Function,
+                    SymbolFlags::empty(),
+                    start,
+                    start + 5,
+                ),
+                name,
+            );
+        }
+        index.add_edge(Edge::new(1, 0, 12));
+        index.add_edge(Edge::new(2, 0, 22));
+        index.add_edge(Edge::new(3, 1, 32));
+        index.add_edge(Edge::new(3, 2, 33));
+        index.add_edge(Edge::new(0, 0, 3));
+
+        let result = collect_call_hierarchy(&index, "target", 10);
+        let tree = serde_json::to_value(&result).unwrap();
+        let root = &tree["roots"][0];
+        assert_eq!(root["symbol"], "target");
+
+        let callers: Vec<&str> = root["callers"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| c["symbol"].as_str().unwrap())
+            .collect();
+        assert_eq!(callers, ["a", "b", "target"]);
+
+        // main is expanded under a, then referenced from b
+        let a = &root["callers"][0];
+        assert_eq!(a["line"], 12);
+        assert_eq!(a["callers"][0]["symbol"], "main");
+        assert_eq!(a["callers"][0]["line"], 32);
+        assert_eq!(a["callers"][0]["see_above"], false);
+        let b = &root["callers"][1];
+        assert_eq!(b["callers"][0]["symbol"], "main");
+        assert_eq!(b["callers"][0]["see_above"], true);
+        assert!(b["callers"][0]["callers"].as_array().unwrap().is_empty());
+
+        // Recursion back into the root is not expanded again
+        assert_eq!(root["callers"][2]["see_above"], true);
+        assert_eq!(result.total_nodes, 6);
+
+        // Depth 1 keeps only direct callers
+        let shallow = collect_call_hierarchy(&index, "target", 1);
+        assert_eq!(shallow.total_nodes, 4);
+        assert!(shallow.roots[0].callers[0].callers.is_empty());
+    }
+
+    #[test]
+    fn test_stats_call_depth_respects_path_filter() {
+        use crate::trace::{Edge, Symbol, SymbolFlags};
+
+        // src: main -> load -> parse (depth 2); tests: three leaf test functions
+        let mut index = SemanticIndex::new();
+        let src = index.add_file("src/main.rs".into());
+        let tests = index.add_file("tests/cli.rs".into());
+        let symbols = [
+            ("main", src, SymbolFlags::IS_ENTRY_POINT),
+            ("load", src, SymbolFlags::empty()),
+            ("parse", src, SymbolFlags::empty()),
+            ("test_a", tests, SymbolFlags::IS_ENTRY_POINT),
+            ("test_b", tests, SymbolFlags::IS_ENTRY_POINT),
+            ("test_c", tests, SymbolFlags::IS_ENTRY_POINT),
+        ];
+        for (id, (name, file, flags)) in symbols.into_iter().enumerate() {
+            let offset = index.strings.intern(name);
+            let line = id as u32 * 5 + 1;
+            index.add_symbol(
+                Symbol::new(
+                    id as u32,
+                    offset,
+                    file,
+                    SymbolKind::Function,
+                    flags,
+                    line,
+                    line + 3,
+                ),
+                name,
+            );
+        }
+        index.add_edge(Edge::new(0, 1, 2));
+        index.add_edge(Edge::new(1, 2, 7));
+
+        let all = compute_stats(&index, &TraceFilter::default());
+        assert_eq!(all.depth_entry_points, 4);
+        assert!((all.avg_call_depth - 0.5).abs() < f32::EPSILON);
+
+        let filter = TraceFilter {
+            paths: vec!["src".to_string()],
+            ..Default::default()
+        };
+        let src_only = compute_stats(&index, &filter);
+        assert_eq!(src_only.depth_entry_points, 1);
+        assert!((src_only.avg_call_depth - 2.0).abs() < f32::EPSILON);
+        assert_eq!(src_only.max_call_depth, 2);
+    }
+
+    #[test]
+    fn test_dedupe_collapses_shared_callee_tails() {
+        use crate::trace::SemanticIndexBuilder;
+
+        // Two definitions of handle meet at validate -> check
+        let dir = tempfile::tempdir().unwrap();
+        let mut builder = SemanticIndexBuilder::new(dir.path());
+        for (name, code) in [
+            (
+                "a.rs",
+                "fn handle() {\n    validate();\n}\n\nfn validate() {\n    check();\n}\n\nfn check() {}\n",
+            ),
+            ("b.rs", "fn handle() {\n    validate();\n}\n"),
+        ] {
+            let path = dir.path().join(name);
+            std::fs::write(&path, code).unwrap();
+            builder.add_file(&path, code);
+        }
+        let index = builder.build();
+        let ids = index.resolve_symbols("handle");
+        assert_eq!(ids.len(), 2);
+
+        let chains = |dedupe: bool| -> Vec<Vec<(String, String)>> {
+            collect_callees(&index, "handle", &ids, 10, dedupe, &TraceFilter::default())
+                .invocation_paths
+                .iter()
+                .map(|p| {
+                    p.chain
+                        .iter()
+                        .map(|s| (s.symbol.clone(), s.file.clone()))
+                        .collect()
+                })
+                .collect()
+        };
+        let step = |symbol: &str, file: &str| (symbol.to_string(), file.to_string());
+
+        let full = chains(false);
+        assert_eq!(full.len(), 2);
+        assert_eq!(full[0][1..], full[1][1..]);
+
+        // The second path stops where it joins the first
+        assert_eq!(
+            chains(true),
+            vec![
+                vec![
+                    step("handle", "a.rs"),
+                    step("validate", "a.rs"),
+                    step("check", "a.rs"),
+                ],
+                vec![step("handle", "b.rs"), step("validate", "a.rs")],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_neighborhood_direct_callers_and_callees() {
+        use crate::trace::{Edge, Symbol, SymbolFlags};
+
+        // main -> handle -> {validate, save}; save -> write (depth 2, excluded)
+        let mut index = SemanticIndex::new();
+        let file = index.add_file("src/app.rs".into());
+        for (id, name) in ["main", "handle", "validate", "save", "write", "cron"]
+            .iter()
+            .enumerate()
+        {
+            let offset = index.strings.intern(name);
+            let id = id as u32;
+            index.add_symbol(
+                Symbol::new(
+                    id,
+                    offset,
+                    file,
+                    SymbolKind::Function,
+                    SymbolFlags::empty(),
+                    id * 10 + 1,
+                    id * 10 + 9,
+                ),
+                name,
+            );
+        }
+        index.add_edge(Edge::new(0, 1, 3));
+        index.add_edge(Edge::new(5, 1, 53));
+        index.add_edge(Edge::new(1, 2, 12));
+        index.add_edge(Edge::new(1, 3, 13));
+        index.add_edge(Edge::new(3, 4, 33));
+
+        let result = collect_neighborhood(&index, "handle", &TraceFilter::default());
+
+        let callers: Vec<_> = result.callers.iter().map(|c| c.name.as_str()).collect();
+        let callees: Vec<_> = result.callees.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(callers, vec!["main", "cron"]);
+        assert_eq!(callees, vec!["validate", "save"]);
+        assert_eq!(result.defined_at.as_deref(), Some("src/app.rs:11"));
+
+        // JSON nests both sides
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["callers"].as_array().unwrap().len(), 2);
+        assert_eq!(json["callees"][1]["name"], "save");
+    }
+
+    #[test]
+    fn test_rerank_descriptions_respect_max_items() {
+        let paths: Vec<InvocationPath> = (0..8)
+            .map(|i| InvocationPath {
+                entry_point: format!("entry{}", i),
+                entry_kind: "function".to_string(),
+                chain: Vec::new(),
+            })
+            .collect();
+
+        let all = rerank_path_descriptions(&paths, &AiConfig::default());
+        assert_eq!(all.len(), 8);
+
+        let config = AiConfig {
+            rerank_max_items: Some(3),
+            ..AiConfig::default()
+        };
+        let capped = rerank_path_descriptions(&paths, &config);
+        assert_eq!(capped.len(), 3);
+        assert!(capped[2].starts_with("Entry: entry2"));
+    }
+
+    #[test]
+    fn test_hotspot_ranking() {
+        let refs = HashMap::from([
+            ("src/core.rs".to_string(), 40),
+            ("src/hot.rs".to_string(), 10),
+            ("src/stable.rs".to_string(), 100),
+        ]);
+        let churn = HashMap::from([
+            ("src/core.rs".to_string(), 2),
+            ("src/hot.rs".to_string(), 30),
+        ]);
+
+        let result = rank_hotspots(refs.clone(), Some(&churn), None);
+        assert!(result.churn_available);
+        let ranked: Vec<_> = result
+            .files
+            .iter()
+            .map(|f| (f.file.as_str(), f.score))
+            .collect();
+        assert_eq!(
+            ranked,
+            vec![
+                ("src/hot.rs", 300),
+                ("src/core.rs", 80),
+                ("src/stable.rs", 0)
+            ]
+        );
+
+        // Without git history, references alone decide the order
+        let result = rank_hotspots(refs, None, Some(2));
+        assert!(!result.churn_available);
+        let ranked: Vec<_> = result.files.iter().map(|f| f.file.as_str()).collect();
+        assert_eq!(ranked, vec!["src/stable.rs", "src/core.rs"]);
+        assert!(result.files[0].churn.is_none());
+    }
+
+    #[test]
+    fn test_git_churn_in_repository_subdirectory() {
+        let dir = tempfile::tempdir().unwrap();
+        let git = |options: &[&str]| {
+            let status = std::process::Command::new("git")
+                .arg("-C")
+                .arg(dir.path())
+                .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+                .args(options)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?} failed", options);
+        };
+        git(&["init", "-q"]);
+        let project = dir.path().join("app");
+        std::fs::create_dir_all(project.join("src")).unwrap();
+        for (round, file) in ["app/src/lib.rs", "app/src/lib.rs", "README.md"]
+            .iter()
+            .enumerate()
+        {
+            std::fs::write(dir.path().join(file), format!("// {}\n", round)).unwrap();
+            git(&["add", "-A"]);
+            git(&["commit", "-q", "-m", "change"]);
+        }
+
+        // Keys match the project-relative paths the index uses
+        let churn = git_churn(&project).unwrap();
+        assert_eq!(churn, HashMap::from([("src/lib.rs".to_string(), 2)]));
+    }
+
+    #[test]
+    fn test_shortest_call_path() {
+        use crate::trace::{Edge, Symbol, SymbolFlags};
+
+        // a -> x -> b, plus a longer a -> y -> z -> b; c is unreachable from a
+        let mut index = SemanticIndex::new();
+        let file = index.add_file("src/app.rs".into());
+        for (id, name) in ["a", "x", "b", "y", "z", "c"].iter().enumerate() {
+            let offset = index.strings.intern(name);
+            let id = id as u32;
+            index.add_symbol(
+                Symbol::new(
+                    id,
+                    offset,
+                    file,
+                    SymbolKind::Function,
+                    SymbolFlags::empty(),
+                    id * 10 + 1,
+                    id * 10 + 9,
+                ),
+                name,
+            );
+        }
+        index.add_edge(Edge::new(0, 3, 2));
+        index.add_edge(Edge::new(3, 4, 32));
+        index.add_edge(Edge::new(4, 2, 42));
+        index.add_edge(Edge::new(0, 1, 3));
+        index.add_edge(Edge::new(1, 2, 12));
+
+        let result = collect_call_path(&index, "a", "b");
+        assert_eq!(result.total_paths, 1);
+        let steps: Vec<_> = result.invocation_paths[0]
+            .chain
+            .iter()
+            .map(|s| s.symbol.as_str())
+            .collect();
+        assert_eq!(steps, vec!["a", "x", "b"]);
+
+        let none = collect_call_path(&index, "a", "c");
+        assert_eq!(none.total_paths, 0);
+        assert!(none.invocation_paths.is_empty());
+    }
+
+    #[test]
+    fn test_pattern_nearest_symbol_between_functions() {
+        use crate::trace::{Symbol, SymbolFlags};
+
+        let mut index = SemanticIndex::new();
+        let file = index.add_file("src/config.rs".into());
+        for (id, name, start, end) in [(0, "load", 3, 10), (1, "save", 20, 30)] {
+            let offset = index.strings.intern(name);
+            index.add_symbol(
+                Symbol::new(
+                    id,
+                    offset,
+                    file,
+                    SymbolKind::Function,
+                    SymbolFlags::empty(),
+                    start,
+                    end,
+                ),
+                name,
+            );
+        }
+
+        // Line 15 sits between `load` and `save`
+        assert_eq!(find_enclosing_symbol(&index, file, 15), None);
+        assert_eq!(
+            find_preceding_symbol(&index, file, 15).as_deref(),
+            Some("load")
+        );
+        // Before any symbol there is nothing to report
+        assert_eq!(find_preceding_symbol(&index, file, 1), None);
+    }
+
+    #[test]
+    fn test_refs_limit_per_file() {
+        use crate::trace::{Token, TokenKind};
+        let mut index = SemanticIndex::new();
+        let generated = index.add_file("gen/bindings.rs".into());
+        let app = index.add_file("src/app.rs".into());
+        let name = index.strings.intern("handle");
+        let locations = (1..=5).map(|line| (generated, line * 10)).chain([(app, 3)]);
+        for (id, (file_id, line)) in locations.enumerate() {
+            index.add_token(
+                Token::new(id as u32, name, file_id, line, 4, TokenKind::Call, 0),
+                "handle",
+            );
+        }
+
+        let options = OperationOptions {
+            limit_per_file: Some(2),
+            ..Default::default()
+        };
+        let mut cache = FileCache::new(Path::new("/nonexistent"));
+        let result = collect_refs(
+            &index,
+            &mut cache,
+            "handle",
+            None,
+            &options,
+            &TraceFilter::default(),
+        );
+
+        let shown: Vec<_> = result
+            .references
+            .iter()
+            .filter(|r| r.file == "gen/bindings.rs")
+            .map(|r| r.line)
+            .collect();
+        assert_eq!(shown, vec![10, 20]);
+        assert_eq!(result.by_file["gen/bindings.rs"], 5);
+        assert_eq!(result.by_file["src/app.rs"], 1);
+        assert_eq!(result.references.len(), 3);
+    }
+
+    #[test]
+    fn test_pattern_warns_on_unreadable_file() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(
+            dir.path().join("src/live.rs"),
+            "fn main() {\n    todo!()\n}\n",
+        )
+        .unwrap();
+
+        let mut index = SemanticIndex::new();
+        index.add_file("src/deleted.rs".into());
+        index.add_file("src/live.rs".into());
+
+        let mut cache = FileCache::new(dir.path());
+        let regex = Regex::new("todo!").unwrap();
+        let result = collect_pattern(
+            &index,
+            &mut cache,
+            &regex,
+            "todo!",
+            &OperationOptions::default(),
+            &TraceFilter::default(),
+        );
+
+        assert_eq!(result.total_matches, 1);
+        assert_eq!(result.matches[0].file, "src/live.rs");
+        assert_eq!(result.warnings, vec!["could not read src/deleted.rs"]);
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["warnings"][0], "could not read src/deleted.rs");
+    }
+
+    #[test]
+    fn test_pattern_max_results_truncates() {
+        let dir = tempfile::tempdir().unwrap();
+        let body: String = (1..=50).map(|i| format!("line {}\n", i)).collect();
+        std::fs::write(dir.path().join("a.txt"), &body).unwrap();
+        std::fs::write(dir.path().join("b.txt"), &body).unwrap();
+
+        let mut index = SemanticIndex::new();
+        index.add_file("a.txt".into());
+        index.add_file("b.txt".into());
+        let regex = Regex::new(".").unwrap();
+
+        let run = |options: OperationOptions| {
+            let mut cache = FileCache::new(dir.path());
+            collect_pattern(
+                &index,
+                &mut cache,
+                &regex,
+                ".",
+                &options,
+                &TraceFilter::default(),
+            )
+        };
+
+        let capped = run(OperationOptions {
+            max_results: 60,
+            ..Default::default()
+        });
+        assert_eq!(capped.total_matches, 60);
+        assert!(capped.truncated);
+        assert_eq!(capped.by_file["b.txt"], 10);
+
+        // An explicit --limit under the cap is not truncation
+        let limited = run(OperationOptions {
+            max_results: 60,
+            limit: Some(5),
+            ..Default::default()
+        });
+        assert_eq!(limited.total_matches, 5);
+        assert!(!limited.truncated);
+
+        let all = run(OperationOptions::default());
+        assert_eq!(all.total_matches, 100);
+        assert!(!all.truncated);
+    }
+
+    #[test]
+    fn test_pattern_parallel_stops_reading_files_past_the_cap() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut index = SemanticIndex::new();
+        // One batch of two workers holds 8 files; every later file is missing,
+        // so reading any of them would leave a warning
+        for i in 0..40 {
+            let name = format!("f{:02}.rs", i);
+            if i < 2 * PATTERN_FILES_PER_WORKER {
+                std::fs::write(dir.path().join(&name), "// TODO one\n// TODO two\n").unwrap();
+            }
+            index.add_file(name.into());
+        }
+        let regex = Regex::new("TODO").unwrap();
+
+        for (limit, max_results, expected) in [(Some(3), 10_000, 3), (None, 5, 5)] {
+            let options = OperationOptions {
+                workers: Some(2),
+                limit,
+                max_results,
+                ..Default::default()
+            };
+            let mut cache = FileCache::new(dir.path());
+            let result = collect_pattern(
+                &index,
+                &mut cache,
+                &regex,
+                "TODO",
+                &options,
+                &TraceFilter::default(),
+            );
+            assert_eq!(result.total_matches, expected);
+            assert_eq!(result.matches[0].file, "f00.rs");
+            assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+        }
+    }
+
+    #[test]
+    fn test_pattern_parallel_matches_sequential() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut index = SemanticIndex::new();
+        for i in 0..12 {
+            let body: String = (1..=40)
+                .map(|l| {
+                    if l % (i + 2) == 0 {
+                        "// TODO fix\n"
+                    } else {
+                        "code\n"
+                    }
+                })
+                .collect();
+            let name = format!("f{:02}.rs", i);
+            std::fs::write(dir.path().join(&name), body).unwrap();
+            index.add_file(name.into());
+        }
+        index.add_file("missing.rs".into());
+        let regex = Regex::new("TODO").unwrap();
+
+        let run = |workers: usize, limit: Option<usize>| {
+            let options = OperationOptions {
+                workers: Some(workers),
+                limit,
+                ..Default::default()
+            };
+            let mut cache = FileCache::new(dir.path());
+            collect_pattern(
+                &index,
+                &mut cache,
+                &regex,
+                "TODO",
+                &options,
+                &TraceFilter::default(),
+            )
+        };
+        let located = |r: &PatternResult| -> Vec<(String, u32)> {
+            r.matches.iter().map(|m| (m.file.clone(), m.line)).collect()
+        };
+
+        let sequential = run(1, None);
+        let parallel = run(4, None);
+        assert!(sequential.total_matches > 50);
+        assert_eq!(located(&parallel), located(&sequential));
+        assert_eq!(parallel.warnings, sequential.warnings);
+        assert_eq!(parallel.warnings.len(), 1);
+
+        // The limit keeps the first matches in file order, not whichever worker finished first
+        let sequential = run(1, Some(25));
+        let parallel = run(4, Some(25));
+        assert_eq!(parallel.total_matches, 25);
+        assert_eq!(located(&parallel), located(&sequential));
+    }
+
+    #[test]
+    fn test_refs_after_line() {
+        use crate::trace::{Token, TokenKind};
+        let mut index = SemanticIndex::new();
+        let edited = index.add_file("src/app.ts".into());
+        let other = index.add_file("src/other.ts".into());
+        let name = index.strings.intern("render");
+        let locations = [(edited, 12), (edited, 200), (edited, 340), (other, 5)];
+        for (id, (file_id, line)) in locations.into_iter().enumerate() {
+            index.add_token(
+                Token::new(id as u32, name, file_id, line, 2, TokenKind::Call, 0),
+                "render",
+            );
+        }
+
+        let options = OperationOptions {
+            after_line: Some(200),
+            ..Default::default()
+        };
+        let filter = TraceFilter {
+            paths: vec!["src/app.ts".to_string()],
+            ..Default::default()
+        };
+        let mut cache = FileCache::new(Path::new("/nonexistent"));
+        let result = collect_refs(&index, &mut cache, "render", None, &options, &filter);
+
+        let lines: Vec<_> = result
+            .references
+            .iter()
+            .map(|r| (r.file.as_str(), r.line))
+            .collect();
+        assert_eq!(lines, vec![("src/app.ts", 200), ("src/app.ts", 340)]);
+    }
+
+    #[test]
+    fn test_refs_context_lang_aware_spans_call() {
+        use crate::trace::SemanticIndexBuilder;
+        let code = "fn target() -> u32 {
+    1
+}
+
+fn caller() -> u32 {
+    let total = combine(
+        1,
+        target(),
+        3,
+    );
+    total
+}
+";
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        std::fs::write(&path, code).unwrap();
+        let mut builder = SemanticIndexBuilder::new(dir.path());
+        builder.add_file(&path, code);
+        let index = builder.build();
+
+        let run = |context_lang_aware: bool| {
+            let options = OperationOptions {
+                context_lang_aware,
+                ..Default::default()
+            };
+            let mut cache = FileCache::new(dir.path());
+            collect_refs(
+                &index,
+                &mut cache,
+                "target",
+                None,
+                &options,
+                &TraceFilter::default(),
+            )
+        };
+
+        let result = run(true);
+        let call = result.references.iter().find(|r| r.line == 8).unwrap();
+        assert!(call.context.contains("let total = combine("));
+        assert!(call.context.contains(");"));
+        assert!(!call.context.contains("    total\n"));
+
+        // Without the flag the context stays on the reference line
+        let result = run(false);
+        let call = result.references.iter().find(|r| r.line == 8).unwrap();
+        assert_eq!(call.context, "target(),");
+    }
+
+    #[test]
+    fn test_import_cycle_detected_only_in_import_mode() {
+        use crate::trace::SemanticIndexBuilder;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut builder = SemanticIndexBuilder::new(dir.path());
+        let files = [
+            (
+                "a.ts",
+                "import { b } from './b';\nexport function a() {\n  return 1;\n}\n",
+            ),
+            (
+                "b.ts",
+                "import { a } from './a';\nexport function b() {\n  return 2;\n}\n",
+            ),
+        ];
+        for (name, code) in files {
+            let path = dir.path().join(name);
+            std::fs::write(&path, code).unwrap();
+            builder.add_file(&path, code);
+        }
+        let index = builder.build();
+        let filter = TraceFilter::default();
+
+        let calls = collect_cycles(&index, "tarjan", false, &filter);
+        assert!(calls.circular_deps.is_empty());
+
+        let imports = collect_cycles(&index, "tarjan", true, &filter);
+        assert_eq!(imports.circular_deps.len(), 1);
+        assert!(imports.circular_deps[0].contains("a.ts"));
+        assert!(imports.circular_deps[0].contains("b.ts"));
+    }
+
+    #[test]
+    fn test_refs_group_by_directory() {
+        use crate::trace::SemanticIndexBuilder;
+        let dir = tempfile::tempdir().unwrap();
+        let mut builder = SemanticIndexBuilder::new(dir.path());
+        let files = [
+            ("src/lib.ts", "export function target() {\n  return 1;\n}\n"),
+            (
+                "src/a/x.ts",
+                "function x() {\n  target();\n  target();\n}\n",
+            ),
+            ("src/a/y.ts", "function y() {\n  target();\n}\n"),
+            ("src/b/z.ts", "function z() {\n  target();\n}\n"),
+        ];
+        for (name, code) in files {
+            let path = dir.path().join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, code).unwrap();
+            builder.add_file(&path, code);
+        }
+        let index = builder.build();
+
+        let options = OperationOptions {
+            group_by: Some("directory".to_string()),
+            ..Default::default()
+        };
+        let mut cache = FileCache::new(dir.path());
+        let result = collect_refs(
+            &index,
+            &mut cache,
+            "target",
+            None,
+            &options,
+            &TraceFilter::default(),
+        );
+
+        let group = &result.by_directory[0];
+        assert_eq!(group.directory, "src/a");
+        assert_eq!(
+            group.count,
+            result.by_file["src/a/x.ts"] + result.by_file["src/a/y.ts"]
+        );
+        let files: Vec<_> = group.files.iter().map(|f| f.file.as_str()).collect();
+        assert_eq!(files, ["src/a/x.ts", "src/a/y.ts"]);
+        assert!(result.by_directory.iter().any(|g| g.directory == "src/b"));
+    }
+
+    #[test]
+    fn test_refs_confidence_ast_vs_token() {
+        use crate::trace::{Reference, Symbol, SymbolFlags, Token, TokenKind};
+        let mut index = SemanticIndex::new();
+        let file = index.add_file("src/app.rs".into());
+        let name = index.strings.intern("count");
+        index.add_symbol(
+            Symbol::new(
+                0,
+                name,
+                file,
+                SymbolKind::Function,
+                SymbolFlags::empty(),
+                1,
+                3,
+            ),
+            "count",
+        );
+        // Token 0 resolves to the symbol; token 1 is an unrelated local
+        for (id, line) in [(0, 10), (1, 20)] {
+            index.add_token(
+                Token::new(id, name, file, line, 4, TokenKind::Identifier, 0),
+                "count",
+            );
+        }
+        index.add_reference(Reference::new(0, 0, RefKind::Call));
+
+        let mut cache = FileCache::new(Path::new("/nonexistent"));
+        let filter = TraceFilter::default();
+        let options = OperationOptions::default();
+        let result = collect_refs(&index, &mut cache, "count", None, &options, &filter);
+        let by_line: HashMap<u32, f32> = result
+            .references
+            .iter()
+            .map(|r| (r.line, r.confidence))
+            .collect();
+        assert_eq!(by_line[&10], 1.0);
+        assert!(by_line[&20] < 1.0);
+
+        let options = OperationOptions {
+            min_confidence: 1.0,
+            ..Default::default()
+        };
+        let result = collect_refs(&index, &mut cache, "count", None, &options, &filter);
+        let lines: Vec<_> = result.references.iter().map(|r| r.line).collect();
+        assert_eq!(lines, vec![10]);
+    }
+
+    #[test]
+    fn test_multiple_in_paths() {
+        use crate::trace::{Symbol, SymbolFlags};
+        let filter = TraceFilter {
+            paths: vec!["src".to_string(), "lib".to_string()],
+            exclude_vendor: true,
+            ..Default::default()
+        };
+
+        let mut index = dead_code_fixture(&[("unused_fn", SymbolKind::Function)]);
+        let vendor = index.add_file("vendor/dep.rs".into());
+        let offset = index.strings.intern("vendored_fn");
+        index.add_symbol(
+            Symbol::new(
+                2,
+                offset,
+                vendor,
+                SymbolKind::Function,
+                SymbolFlags::empty(),
+                1,
+                5,
+            ),
+            "vendored_fn",
+        );
+
+        let result = collect_dead_code(&index, None, &filter, false, &DeadCodeOptions::default());
+        let names: HashSet<_> = result.symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, HashSet::from(["unused_fn", "lib_helper"]));
+
+        // Single value keeps working
+        let filter = TraceFilter {
+            paths: vec!["lib".to_string()],
+            exclude_vendor: true,
+            ..Default::default()
+        };
+        let result = collect_dead_code(&index, None, &filter, false, &DeadCodeOptions::default());
+        let names: Vec<_> = result.symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["lib_helper"]);
+    }
+
+    #[test]
+    fn test_resolve_definitions_with_injected_choice() {
+        use crate::trace::SemanticIndexBuilder;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut builder = SemanticIndexBuilder::new(dir.path());
+        for name in ["src/b.ts", "src/a.ts"] {
+            let path = dir.path().join(name);
+            builder.add_file(&path, "\nexport function handle() {\n  return 1;\n}\n");
+        }
+        let index = builder.build();
+
+        let candidates = definition_candidates(&index, "handle");
+        assert_eq!(candidates.len(), 2);
+        assert!(candidates[0].label.ends_with("a.ts:2 (function)"));
+        assert!(candidates[1].label.ends_with("b.ts:2 (function)"));
+        let a = candidates[0].symbol_ids.clone();
+        let b = candidates[1].symbol_ids.clone();
+
+        let never = |_: &[String]| -> Result<Option<usize>> { panic!("picker not expected") };
+        let all = resolve_definitions(&candidates, DefinitionChoice::All, never).unwrap();
+        assert_eq!(all, [a.clone(), b.clone()].concat());
+        let first = resolve_definitions(&candidates, DefinitionChoice::First, never).unwrap();
+        assert_eq!(first, a);
+
+        let picked = resolve_definitions(&candidates, DefinitionChoice::Interactive, |labels| {
+            assert_eq!(labels.len(), 2);
+            Ok(Some(1))
+        })
+        .unwrap();
+        assert_eq!(picked, b);
+
+        let cancelled =
+            resolve_definitions(&candidates, DefinitionChoice::Interactive, |_| Ok(None));
+        assert!(cancelled.is_err());
+
+        // A single definition never prompts
+        let single = resolve_definitions(&candidates[..1], DefinitionChoice::Interactive, never);
+        assert_eq!(single.unwrap(), a);
+    }
+
+    #[test]
+    fn test_refs_byte_offsets_match_source() {
+        use crate::trace::SemanticIndexBuilder;
+        let code = "fn target() -> u32 {\r\n    1\r\n}\r\n\r\nfn caller() -> u32 {\r\n    target() + 1\r\n}\r\n";
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        std::fs::write(&path, code).unwrap();
+        let mut builder = SemanticIndexBuilder::new(dir.path());
+        builder.add_file(&path, code);
+        let index = builder.build();
+
+        let options = OperationOptions {
+            byte_offsets: true,
+            ..Default::default()
+        };
+        let filter = TraceFilter::default();
+        let mut cache = FileCache::new(dir.path());
+        let result = collect_refs(&index, &mut cache, "target", None, &options, &filter);
+
+        let call = result
+            .references
+            .iter()
+            .find(|r| r.line == 6)
+            .expect("call reference");
+        let expected = code.find("target() + 1").unwrap() as u32;
+        assert_eq!(call.byte_offset, Some(expected));
+        let json = serde_json::to_value(call).unwrap();
+        assert_eq!(json["byte_offset"], expected);
+
+        // Offsets are opt-in
+        let options = OperationOptions::default();
+        let result = collect_refs(&index, &mut cache, "target", None, &options, &filter);
+        assert!(result.references.iter().all(|r| r.byte_offset.is_none()));
+    }
+
+    #[test]
+    fn test_refs_arg_positions_report_callee_and_index() {
+        use crate::trace::SemanticIndexBuilder;
+        let code =
+            "function run(db) {\n  const token = load();\n  save(db, token);\n  return token;\n}\n";
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.ts");
+        std::fs::write(&path, code).unwrap();
+        let mut builder = SemanticIndexBuilder::new(dir.path());
+        builder.add_file(&path, code);
+        let index = builder.build();
+
+        let options = OperationOptions {
+            arg_positions: true,
+            ..Default::default()
+        };
+        let mut cache = FileCache::new(dir.path());
+        let result = collect_refs(
+            &index,
+            &mut cache,
+            "token",
+            None,
+            &options,
+            &TraceFilter::default(),
+        );
+
+        let passed = result
+            .references
+            .iter()
+            .find(|r| r.line == 3)
+            .expect("reference in the call");
+        assert_eq!(passed.arg_index, Some(1));
+        assert_eq!(passed.callee.as_deref(), Some("save"));
+
+        // Uses outside an argument list carry no argument position
+        let returned = result.references.iter().find(|r| r.line == 4).unwrap();
+        assert_eq!(returned.arg_index, None);
+        assert_eq!(returned.callee, None);
+    }
+
+    #[test]
+    fn test_dead_code_excludes_vendor() {
+        use crate::trace::{Symbol, SymbolFlags};
+        let mut index = dead_code_fixture(&[("unused_fn", SymbolKind::Function)]);
+        let vendor = index.add_file("vendor/dep/lib.rs".into());
+        let offset = index.strings.intern("vendored_fn");
+        index.add_symbol(
+            Symbol::new(
+                2,
+                offset,
+                vendor,
+                SymbolKind::Function,
+                SymbolFlags::empty(),
+                1,
+                5,
+            ),
+            "vendored_fn",
+        );
+        let dead_names = |filter: TraceFilter| -> HashSet<String> {
+            collect_dead_code(&index, None, &filter, false, &DeadCodeOptions::default())
+                .symbols
+                .into_iter()
+                .map(|s| s.name)
+                .collect()
+        };
+
+        let names = dead_names(TraceFilter {
+            exclude_vendor: true,
+            ..Default::default()
+        });
+        assert!(names.contains("unused_fn"));
+        assert!(!names.contains("vendored_fn"));
+
+        // The exclusion applies even when the path filter names the vendor directory
+        assert!(dead_names(TraceFilter {
+            paths: vec!["vendor".to_string()],
+            exclude_vendor: true,
+            ..Default::default()
+        })
+        .is_empty());
+
+        let names = dead_names(TraceFilter::default());
+        assert!(names.contains("vendored_fn"));
+    }
+
+    #[test]
+    fn test_no_merge_definitions_reports_labeled_sections() {
+        use crate::trace::SemanticIndexBuilder;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut builder = SemanticIndexBuilder::new(dir.path());
+        for (file, caller) in [("a.rs", "run_a"), ("b.rs", "run_b")] {
+            let content = format!(
+                "fn {}() {{\n    helper();\n}}\n\nfn helper() {{}}\n",
+                caller
+            );
+            builder.add_file(&dir.path().join(file), &content);
+        }
+        let index = builder.build();
+        let ids = index.resolve_symbols("helper");
+
+        let merged = collect_callers(&index, "helper", &ids, 5, &TraceFilter::default());
+        assert!(merged.definitions.is_empty());
+
+        let split = definition_sections(&index, "helper", &ids, |ids| {
+            collect_callers(&index, "helper", ids, 5, &TraceFilter::default())
+        });
+        assert_eq!(split.symbol, "helper");
+        assert_eq!(split.definitions.len(), 2);
+        assert_eq!(split.definitions[0].symbol, "helper (definition 1 of 2)");
+        assert_eq!(split.definitions[1].symbol, "helper (definition 2 of 2)");
+        assert!(split.definitions[0]
+            .defined_at
+            .as_deref()
+            .is_some_and(|at| at.ends_with("a.rs:5")));
+        assert!(split.definitions[1]
+            .defined_at
+            .as_deref()
+            .is_some_and(|at| at.ends_with("b.rs:5")));
+        assert_eq!(
+            split.total_paths,
+            split
+                .definitions
+                .iter()
+                .map(|d| d.total_paths)
+                .sum::<usize>()
+        );
+    }
+
+    #[test]
+    fn test_refs_flag_deprecated_targets() {
+        use crate::trace::SemanticIndexBuilder;
+        let code = "#[deprecated]\npub fn old_api() {}\n\npub fn new_api() {}\n\npub fn run() {\n    old_api();\n    new_api();\n}\n";
+        let dir = tempfile::tempdir().unwrap();
+        let mut builder = SemanticIndexBuilder::new(dir.path());
+        builder.add_file(&dir.path().join("lib.rs"), code);
+        let index = builder.build();
+        let mut cache = FileCache::new(dir.path());
+        let mut refs = |symbol: &str, options: &OperationOptions| {
+            collect_refs(
+                &index,
+                &mut cache,
+                symbol,
+                None,
+                options,
+                &TraceFilter::default(),
+            )
+        };
+        let annotate = OperationOptions {
+            annotate_deprecated: true,
+            ..Default::default()
+        };
+        let deprecated_only = OperationOptions {
+            deprecated_only: true,
+            ..Default::default()
+        };
+
+        let result = refs("old_api", &annotate);
+        assert!(!result.references.is_empty());
+        assert!(result.references.iter().all(|r| r.target_deprecated));
+        let result = refs("new_api", &annotate);
+        assert!(result.references.iter().all(|r| !r.target_deprecated));
+
+        // Not annotated unless asked
+        let result = refs("old_api", &OperationOptions::default());
+        assert!(result.references.iter().all(|r| !r.target_deprecated));
+
+        let result = refs("old_api", &deprecated_only);
+        assert!(result.references.iter().any(|r| r.line == 7));
+        assert!(result.references.iter().all(|r| r.target_deprecated));
+        assert!(refs("new_api", &deprecated_only).references.is_empty());
+    }
+
+    #[test]
+    fn test_shadows_reports_inner_redeclaration() {
+        use crate::trace::SemanticIndexBuilder;
+        use tempfile::tempdir;
+
+        let code = r#"
+pub fn total_steps(limit: u32) -> u32 {
+    let total = 0;
+    for step in 0..limit {
+        let total = step * 2;
+        println!("{}", total);
+    }
+    total
+}
+
+pub fn distinct(limit: u32) -> u32 {
+    let count = limit;
+    {
+        let doubled = count * 2;
+        println!("{}", doubled);
+    }
+    count
+}
+"#;
+
+        let dir = tempdir().unwrap();
+        let mut builder = SemanticIndexBuilder::new(dir.path());
+        builder.add_file(&dir.path().join("lib.rs"), code);
+        let index = builder.build();
+
+        let result = collect_shadows(&index, &TraceFilter::default());
+        assert_eq!(result.total, 1);
+        let shadow = &result.shadows[0];
+        assert_eq!(shadow.name, "total");
+        assert_eq!(shadow.function, "total_steps");
+        assert_eq!(shadow.line, 5);
+        assert_eq!(shadow.shadowed_line, 3);
+    }
+
+    #[test]
+    fn test_callers_apply_path_filter() {
+        use crate::trace::SemanticIndexBuilder;
+        let dir = tempfile::tempdir().unwrap();
+        let mut builder = SemanticIndexBuilder::new(dir.path());
+        builder.add_file(
+            &dir.path().join("src/lib.rs"),
+            "pub fn helper() {}\n\npub fn from_src() {\n    helper();\n}\n",
+        );
+        builder.add_file(
+            &dir.path().join("tests/it.rs"),
+            "fn from_tests() {\n    helper();\n}\n",
+        );
+        let index = builder.build();
+        let ids = index.resolve_symbols("helper");
+
+        let callers = |filter: TraceFilter| {
+            let result = collect_callers(&index, "helper", &ids, 5, &filter);
+            result
+                .invocation_paths
+                .iter()
+                .flat_map(|p| p.chain.iter().map(|s| s.symbol.clone()))
+                .collect::<HashSet<_>>()
+        };
+
+        let all = callers(TraceFilter::default());
+        assert!(all.contains("from_src") && all.contains("from_tests"));
+
+        let filtered = callers(TraceFilter {
+            paths: vec!["src".to_string()],
+            ..Default::default()
+        });
+        assert!(filtered.contains("from_src"));
+        assert!(!filtered.contains("from_tests"));
+    }
+
+    #[test]
+    fn test_refs_by_confidence_splits_exact_and_heuristic() {
+        use crate::trace::{
+            JsonFormatter, PlainFormatter, Reference, Symbol, SymbolFlags, Token, TokenKind,
+        };
+        let mut index = SemanticIndex::new();
+        let file = index.add_file("src/app.rs".into());
+        let name = index.strings.intern("count");
+        index.add_symbol(
+            Symbol::new(
+                0,
+                name,
+                file,
+                SymbolKind::Function,
+                SymbolFlags::empty(),
+                1,
+                3,
+            ),
+            "count",
+        );
+        // Tokens 0 and 1 resolve to the symbol; 2, 3 and 4 only match by name
+        for id in 0..5u32 {
+            index.add_token(
+                Token::new(id, name, file, 10 + id, 4, TokenKind::Identifier, 0),
+                "count",
+            );
+        }
+        index.add_reference(Reference::new(0, 0, RefKind::Call));
+        index.add_reference(Reference::new(1, 0, RefKind::Read));
+
+        let mut cache = FileCache::new(Path::new("/nonexistent"));
+        let result = collect_refs(
+            &index,
+            &mut cache,
+            "count",
+            None,
+            &OperationOptions::default(),
+            &TraceFilter::default(),
+        );
+
+        assert_eq!(result.total_refs, 5);
+        assert_eq!(result.by_confidence["exact"], 2);
+        assert_eq!(result.by_confidence["heuristic"], 3);
+        assert_eq!(
+            result.by_confidence.values().sum::<usize>(),
+            result.total_refs
+        );
+
+        let json = JsonFormatter::new().format_refs(&result);
+        assert!(json.contains("\"by_confidence\""));
+        let plain = PlainFormatter::new().format_refs(&result);
+        assert!(plain.contains("Found: 5 references (2 exact, 3 heuristic)"));
+    }
+
+    #[test]
+    fn test_follow_rename_reports_refs_under_old_and_new_names() {
+        use crate::trace::{Reference, Symbol, SymbolFlags, Token, TokenKind};
+        // `git log -M -p -U0 --format=` output for two successive renames
+        let diff = "\
+diff --git a/src/user.rs b/src/user.rs
+index 1111111..2222222 100644
+--- a/src/user.rs
++++ b/src/user.rs
+@@ -1 +1 @@
+-pub fn load_user(id: u32) -> User {
++pub fn fetch_user(id: u32) -> User {
+@@ -9,2 +9,2 @@
+-    let user = load_user(id);
+-    log(user, id);
++    let user = fetch_user(id);
++    audit(user, id);
+diff --git a/src/user.rs b/src/user.rs
+index 0000000..1111111 100644
+--- a/src/user.rs
++++ b/src/user.rs
+@@ -1 +1 @@
+-pub fn get_user(id: u32) -> User {
++pub fn load_user(id: u32) -> User {
+";
+        let pairs = rename_pairs(diff);
+        assert_eq!(
+            pairs,
+            [
+                ("load_user".to_string(), "fetch_user".to_string()),
+                ("log".to_string(), "audit".to_string()),
+                ("get_user".to_string(), "load_user".to_string()),
+            ]
+        );
+        let old_names = previous_names(&pairs, "fetch_user");
+        assert_eq!(old_names, ["load_user", "get_user"]);
+
+        let mut index = SemanticIndex::new();
+        let file = index.add_file("src/user.rs".into());
+        let fetch = index.strings.intern("fetch_user");
+        index.add_symbol(
+            Symbol::new(
+                0,
+                fetch,
+                file,
+                SymbolKind::Function,
+                SymbolFlags::empty(),
+                1,
+                3,
+            ),
+            "fetch_user",
+        );
+        index.add_token(
+            Token::new(0, fetch, file, 10, 15, TokenKind::Call, 0),
+            "fetch_user",
+        );
+        index.add_reference(Reference::new(0, 0, RefKind::Call));
+        let stale = index.strings.intern("get_user");
+        index.add_token(
+            Token::new(1, stale, file, 20, 4, TokenKind::Call, 0),
+            "get_user",
+        );
+
+        let mut cache = FileCache::new(Path::new("/nonexistent"));
+        let result = collect_renamed_refs(
+            &index,
+            &mut cache,
+            "fetch_user",
+            &old_names,
+            None,
+            &OperationOptions::default(),
+            &TraceFilter::default(),
+        );
+
+        assert_eq!(result.total_refs, 2);
+        assert_eq!(result.by_file["src/user.rs"], 2);
+        let used: Vec<_> = result
+            .references
+            .iter()
+            .map(|r| (r.line, r.referenced_as.as_deref()))
+            .collect();
+        assert_eq!(used, [(10, Some("fetch_user")), (20, Some("get_user"))]);
+        assert_eq!(
+            result.references[1].renamed_from("fetch_user"),
+            Some("get_user")
+        );
+    }
+
+    #[test]
+    fn test_streamed_refs_json_matches_buffered() {
+        use crate::trace::{Reference, Symbol, SymbolFlags, Token, TokenKind};
+        let mut index = SemanticIndex::new();
+        let files: Vec<u16> = (0..20)
+            .map(|i| index.add_file(format!("src/m{:02}.rs", i).into()))
+            .collect();
+        let name = index.strings.intern("handle");
+        index.add_symbol(
+            Symbol::new(
+                0,
+                name,
+                files[0],
+                SymbolKind::Function,
+                SymbolFlags::empty(),
+                1,
+                3,
+            ),
+            "handle",
+        );
+        // 10,000 uses; the first few resolve to the symbol, the rest match by name
+        let mut id = 0;
+        for &file in &files {
+            for line in 1..=500 {
+                index.add_token(
+                    Token::new(id, name, file, line, 8, TokenKind::Call, 0),
+                    "handle",
+                );
+                if id < 5 {
+                    index.add_reference(Reference::new(id, 0, RefKind::Call));
+                }
+                id += 1;
+            }
+        }
+
+        let mut cache = FileCache::new(Path::new("/nonexistent"));
+        let filter = TraceFilter::default();
+        let limited = OperationOptions {
+            limit_per_file: Some(30),
+            limit: Some(250),
+            ..Default::default()
+        };
+        for options in [OperationOptions::default(), limited] {
+            let buffered = collect_refs(&index, &mut cache, "handle", None, &options, &filter);
+            let streamed = stream_refs(
+                &index,
+                &mut cache,
+                "handle",
+                None,
+                &options,
+                &filter,
+                Vec::new(),
+            )
+            .unwrap();
+
+            let streamed: serde_json::Value = serde_json::from_slice(&streamed).unwrap();
+            assert_eq!(streamed, serde_json::to_value(&buffered).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_dead_code_xref_suggests_renamed_replacement() {
+        use crate::trace::{JsonFormatter, Reference, SymbolFlags, Token};
+
+        let mut index = SemanticIndex::new();
+        let file = index.add_file("src/users.ts".into());
+        let other = index.add_file("lib/users.ts".into());
+        for (id, name, file_id) in [
+            (0, "getUserOld", file),
+            (1, "getUser", file),
+            (2, "getAccount", file),
+            (3, "getUsers", other),
+        ] {
+            let offset = index.strings.intern(name);
+            index.add_symbol(
+                Symbol::new(
+                    id,
+                    offset,
+                    file_id,
+                    SymbolKind::Function,
+                    SymbolFlags::empty(),
+                    id * 10 + 1,
+                    id * 10 + 5,
+                ),
+                name,
+            );
+            // Everything but getUserOld is called
+            if id > 0 {
+                index.add_token(
+                    Token::new(id, offset, file_id, 100 + id, 2, TokenKind::Call, 0),
+                    name,
+                );
+                index.add_reference(Reference::new(id, id, RefKind::Call));
+            }
+        }
+
+        let filter = TraceFilter::default();
+        let result = collect_dead_code(&index, None, &filter, true, &DeadCodeOptions::default());
+        assert_eq!(result.total_dead, 1);
+        let dead = &result.symbols[0];
+        assert_eq!(dead.name, "getUserOld");
+        assert_eq!(dead.likely_replacement.as_deref(), Some("getUser"));
+
+        let json = JsonFormatter::new().format_dead_code(&result);
+        assert!(json.contains("\"likely_replacement\": \"getUser\""));
+
+        // Without --xref the hint is left out
+        let result = collect_dead_code(&index, None, &filter, false, &DeadCodeOptions::default());
+        assert_eq!(result.symbols[0].likely_replacement, None);
+        assert_eq!(levenshtein("getUserOld", "getUser"), 3);
+    }
+
+    #[test]
+    fn test_exclude_comments_drops_refs_in_comments_and_strings() {
+        use crate::trace::{Reference, Symbol, SymbolFlags, Token, TokenKind};
+        // Ruby is scanned lexically, TypeScript parsed with tree-sitter
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(
+            dir.path().join("src/billing.rb"),
+            "def charge(amount)\n  amount * 2\nend\n\n# charge is retried by the worker\ntotal = charge(10)\nputs \"charge failed\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("src/ui.ts"),
+            "// charge the card\nconst msg = `sent ${charge(1)}`;\nconst label = \"charge\";\n",
+        )
+        .unwrap();
+
+        let mut index = SemanticIndex::new();
+        let rb = index.add_file("src/billing.rb".into());
+        let ts = index.add_file("src/ui.ts".into());
+        let name = index.strings.intern("charge");
+        index.add_symbol(
+            Symbol::new(
+                0,
+                name,
+                rb,
+                SymbolKind::Function,
+                SymbolFlags::empty(),
+                1,
+                3,
+            ),
+            "charge",
+        );
+        let positions = [
+            (rb, 5, 2),
+            (rb, 6, 8),
+            (rb, 7, 6),
+            (ts, 1, 3),
+            (ts, 2, 20),
+            (ts, 3, 15),
+        ];
+        for (id, &(file, line, column)) in positions.iter().enumerate() {
+            index.add_token(
+                Token::new(
+                    id as u32,
+                    name,
+                    file,
+                    line,
+                    column,
+                    TokenKind::Identifier,
+                    0,
+                ),
+                "charge",
+            );
+        }
+        // The comment match is also resolved, so both phases are covered
+        index.add_reference(Reference::new(0, 0, RefKind::Read));
+        index.add_reference(Reference::new(1, 0, RefKind::Call));
+
+        let refs = |exclude_comments: bool| {
+            let options = OperationOptions {
+                exclude_comments,
+                ..Default::default()
+            };
+            let mut cache = FileCache::new(dir.path());
+            let filter = TraceFilter::default();
+            let result = collect_refs(&index, &mut cache, "charge", None, &options, &filter);
+            let mut found: Vec<(String, u32)> = result
+                .references
+                .into_iter()
+                .map(|r| (r.file, r.line))
+                .collect();
+            found.sort();
+            found
+        };
+
+        assert_eq!(refs(false).len(), 6);
+        assert_eq!(
+            refs(true),
+            vec![
+                ("src/billing.rb".to_string(), 6),
+                ("src/ui.ts".to_string(), 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dead_code_skips_ignored_names_and_annotated_symbols() {
+        use crate::core::config::DeadCodeConfig;
+        // Symbols start on lines 1, 11 and 21 of src/app.rs
+        let index = dead_code_fixture(&[
+            ("_placeholder", SymbolKind::Function),
+            ("legacy_hook", SymbolKind::Function),
+            ("really_dead", SymbolKind::Function),
+        ]);
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        let mut source = vec![String::new(); 25];
+        source[9] = "// greppy:ignore-dead".to_string();
+        std::fs::write(dir.path().join("src/app.rs"), source.join("\n")).unwrap();
+
+        let options = DeadCodeOptions {
+            annotation_root: Some(dir.path().to_path_buf()),
+            ..DeadCodeOptions::from_config(&DeadCodeConfig {
+                ignore_names: vec!["^_".to_string()],
+                ..Default::default()
+            })
+        };
+        let filter = TraceFilter::default();
+        let result = collect_dead_code(&index, None, &filter, false, &options);
+        let names: Vec<_> = result.symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["lib_helper", "really_dead"]);
+    }
+
+    #[test]
+    fn test_refs_with_hashes_match_file_content() {
+        use crate::trace::SemanticIndexBuilder;
+        let dir = tempfile::tempdir().unwrap();
+        let lib = "pub fn target() -> u32 {\n    1\n}\n";
+        let app = "fn main() {\n    let n = target();\n    println!(\"{}\", n);\n}\n";
+        std::fs::create_dir_all(dir.path().join("src/bin")).unwrap();
+        let mut builder = SemanticIndexBuilder::new(dir.path());
+        for (file, code) in [("src/lib.rs", lib), ("src/bin/app.rs", app)] {
+            let path = dir.path().join(file);
+            std::fs::write(&path, code).unwrap();
+            builder.add_file(&path, code);
+        }
+        let index = builder.build();
+
+        let refs = |with_hashes: bool| {
+            let options = OperationOptions {
+                with_hashes,
+                group_by: Some("directory".to_string()),
+                ..Default::default()
+            };
+            let mut cache = FileCache::new(dir.path());
+            let filter = TraceFilter::default();
+            collect_refs(&index, &mut cache, "target", None, &options, &filter)
+        };
+
+        let result = refs(false);
+        assert!(result.source_hashes.is_empty());
+
+        let result = refs(true);
+        let app_file = result
+            .by_file
+            .keys()
+            .find(|f| f.ends_with("app.rs"))
+            .unwrap();
+        let expected = format!("{:016x}", xxhash_rust::xxh3::xxh3_64(app.as_bytes()));
+        assert_eq!(result.source_hashes[app_file], expected);
+        assert_eq!(result.source_hashes.len(), result.by_file.len());
+
+        let grouped = result
+            .by_directory
+            .iter()
+            .flat_map(|g| &g.files)
+            .find(|f| &f.file == app_file)
+            .unwrap();
+        assert_eq!(grouped.source_hash.as_deref(), Some(expected.as_str()));
+    }
+
+    #[test]
+    fn test_transitive_refs_reach_callers_of_callers() {
+        use crate::trace::SemanticIndexBuilder;
+        let code = "fn old_api() {}
+
+fn wrapper() {
+    old_api();
+}
+
+fn handler() {
+    wrapper();
+}
+
+fn main() {
+    handler();
+}
+";
+        // An unrelated local that shares the wrapper's name
+        let other = "fn unrelated() {
+    let wrapper = 1;
+    println!(\"{}\", wrapper + 1);
+}
+";
+        let dir = tempfile::tempdir().unwrap();
+        let mut builder = SemanticIndexBuilder::new(dir.path());
+        for (name, code) in [("lib.rs", code), ("other.rs", other)] {
+            let path = dir.path().join(name);
+            std::fs::write(&path, code).unwrap();
+            builder.add_file(&path, code);
+        }
+        let index = builder.build();
+
+        let refs = |max_depth: usize| {
+            let options = OperationOptions {
+                transitive: true,
+                max_depth,
+                ..Default::default()
+            };
+            let mut cache = FileCache::new(dir.path());
+            let filter = TraceFilter::default();
+            collect_transitive_refs(&index, &mut cache, "old_api", None, &options, &filter)
+                .references
+        };
+        // Calls only: the definitions' own name tokens are reported too
+        let calls = |max_depth: usize| {
+            refs(max_depth)
+                .into_iter()
+                .filter(|r| r.kind == ReferenceKind::Call)
+                .map(|r| (r.file, r.line, r.referenced_as, r.depth))
+                .collect::<Vec<_>>()
+        };
+        let lib = "lib.rs".to_string();
+
+        // Two hops by default
+        let expected = vec![
+            (lib.clone(), 4, Some("old_api".to_string()), Some(1)),
+            (lib.clone(), 8, Some("wrapper".to_string()), Some(2)),
+        ];
+        assert_eq!(calls(TRANSITIVE_DEFAULT_DEPTH), expected);
+
+        // Hops follow the wrapper definition, not every token spelled `wrapper`
+        let all = refs(TRANSITIVE_DEFAULT_DEPTH);
+        assert!(all.iter().all(|r| r.file == lib), "{:?}", all);
+
+        // One more hop reaches main's call of handler
+        let found = calls(3);
+        assert_eq!(found.len(), 3);
+        assert_eq!(found[2], (lib, 12, Some("handler".to_string()), Some(3)));
+    }
+
+    #[tokio::test]
+    async fn test_run_operation_returns_refs_result() {
+        use crate::trace::SemanticIndexBuilder;
+
+        let code = "fn target() {}
+
+fn caller() {
+    target();
+}
+";
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        std::fs::write(&path, code).unwrap();
+        let mut builder = SemanticIndexBuilder::new(dir.path());
+        builder.add_file(&path, code);
+        let index = builder.build();
+
+        let operation = TraceOperation::Refs {
+            symbol: "target".to_string(),
+            kind: Some(ReferenceKind::Call),
+        };
+        let project = Project::from_path(dir.path()).unwrap();
+        let result = run_operation(
+            &project,
+            &index,
+            &operation,
+            &OperationOptions::default(),
+            &TraceFilter::default(),
+        )
+        .await
+        .unwrap();
+        let OperationResult::Refs(refs) = result else {
+            panic!("expected refs result, got {:?}", result);
+        };
+        assert_eq!(refs.symbol, "target");
+        assert_eq!(refs.total_refs, 1);
+        assert_eq!(refs.references[0].line, 4);
+    }
+
+    #[test]
+    fn test_stubs_reports_only_placeholder_bodies() {
+        use crate::trace::SemanticIndexBuilder;
+
+        let code = "fn real(x: u32) -> u32 {
+    x + 1
+}
+
+fn pending(x: u32) -> u32 {
+    // TODO: wire up
+    todo!()
+}
+";
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        std::fs::write(&path, code).unwrap();
+        let mut builder = SemanticIndexBuilder::new(dir.path());
+        builder.add_file(&path, code);
+        let index = builder.build();
+
+        let result = collect_stubs(dir.path(), &index, &TraceFilter::default());
+        assert_eq!(result.total, 1);
+        let stub = &result.stubs[0];
+        assert_eq!(stub.name, "pending");
+        assert_eq!(stub.kind, "function");
+        assert_eq!(stub.line, 5);
+        assert_eq!(stub.placeholder.as_deref(), Some("todo!()"));
+    }
+
+    #[tokio::test]
+    async fn test_scope_reports_declared_type_of_typed_local() {
+        use crate::trace::SemanticIndexBuilder;
+
+        let code = "struct User {
+    id: u32,
+}
+
+fn handle() {
+    let user: User = User { id: 1 };
+    let count = user.id;
+    println!(\"{}\", count);
+}
+";
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        std::fs::write(&path, code).unwrap();
+        let mut builder = SemanticIndexBuilder::new(dir.path());
+        builder.add_file(&path, code);
+        let index = builder.build();
+        let project = Project::from_path(dir.path()).unwrap();
+
+        let result = analyze_scope_cmd(&project, &index, "lib.rs:8", &TraceFilter::default())
+            .await
+            .unwrap();
+        let variable = |name: &str| {
+            result
+                .local_variables
+                .iter()
+                .find(|v| v.name == name)
+                .unwrap_or_else(|| panic!("{} not in scope: {:?}", name, result.local_variables))
+        };
+        assert_eq!(variable("user").declared_type.as_deref(), Some("User"));
+        assert_eq!(variable("user").defined_at, 6);
+        assert_eq!(variable("count").declared_type, None);
+    }
+
+    #[tokio::test]
+    async fn test_first_match_only_exit_codes() {
+        use crate::core::error::exit_code;
+        use crate::trace::SemanticIndexBuilder;
+        let dir = tempfile::tempdir().unwrap();
+        let mut builder = SemanticIndexBuilder::new(dir.path());
+        for (name, code) in [
+            ("a.rs", "fn a() {\n    dbg!(1);\n    dbg!(2);\n}\n"),
+            ("b.rs", "fn b() {\n    dbg!(3);\n}\n"),
+        ] {
+            let path = dir.path().join(name);
+            std::fs::write(&path, code).unwrap();
+            builder.add_file(&path, code);
+        }
+        let index = builder.build();
+        let project = Project::from_path(dir.path()).unwrap();
+
+        let check = |pattern: &'static str| {
+            let options = OperationOptions {
+                first_match_only: true,
+                ..Default::default()
+            };
+            let (index, project) = (&index, &project);
+            async move {
+                trace_pattern_cmd(project, index, pattern, &options, &TraceFilter::default()).await
+            }
+        };
+
+        // Present: the scan stops at the first of three matches
+        let found = check("dbg!").await.unwrap();
+        assert_eq!(found.total_matches, 1);
+        assert_eq!(found.matches[0].line, 2);
+        assert!(found.matches[0].file.ends_with("a.rs"));
+
+        // Absent: an error that exits with 1
+        let err = check("println!").await.unwrap_err();
+        assert!(matches!(err, Error::NoMatch { .. }));
+        assert_eq!(err.exit_code(), exit_code::FAILURE);
+    }
+
+    #[test]
+    fn test_stable_id_survives_rebuild_of_unchanged_fixture() {
+        use crate::trace::SemanticIndexBuilder;
+
+        let code = "fn target() {}
+
+fn caller() {
+    target();
+}
+";
+        let extra = "fn helper() {}\nfn other() {}\n";
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        let extra_path = dir.path().join("extra.rs");
+        std::fs::write(&path, code).unwrap();
+        std::fs::write(&extra_path, extra).unwrap();
+
+        let refs = |files: &[(&PathBuf, &str)]| {
+            let mut builder = SemanticIndexBuilder::new(dir.path());
+            for (path, code) in files {
+                builder.add_file(path, code);
+            }
+            let index = builder.build();
+            let mut cache = FileCache::new(dir.path());
+            collect_refs(
+                &index,
+                &mut cache,
+                "target",
+                None,
+                &OperationOptions::default(),
+                &TraceFilter::default(),
+            )
+        };
+
+        // Indexing another file first shifts the positional id
+        let first = refs(&[(&path, code)]);
+        let second = refs(&[(&extra_path, extra), (&path, code)]);
+        assert_ne!(first.symbol_id, second.symbol_id);
+        assert!(first.stable_id.is_some());
+        assert_eq!(first.stable_id, second.stable_id);
+
+        let json = serde_json::to_value(&second).unwrap();
+        assert_eq!(json["stable_id"], first.stable_id.unwrap().as_str());
+        assert_eq!(json["symbol_id"], second.symbol_id.unwrap());
+    }
+}
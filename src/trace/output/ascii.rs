@@ -8,9 +8,12 @@
 //! @module trace/output/ascii
 
 use super::{
-    DeadCodeResult, FlowResult, ImpactResult, ModuleResult, PatternResult, ReferenceKind,
-    RefsResult, RiskLevel, ScopeResult, StatsResult, TraceFormatter, TraceResult,
+    CallHierarchyNode, CallHierarchyResult, DeadCodeResult, DeprecatedResult, FlowResult,
+    HotspotResult, ImpactResult, ModuleResult, NeighborhoodResult, PatternResult, ReferenceKind,
+    RefsResult, RiskLevel, ScopeResult, ShadowsResult, StatsResult, StubsResult, TraceFormatter,
+    TraceResult, UnusedParamsResult,
 };
+use crate::output::color::strip_ansi;
 
 // =============================================================================
 // CONSTANTS
@@ -35,34 +38,93 @@ mod colors {
     pub const BG_YELLOW: &str = "\x1b[43m";
 }
 
-/// Box-drawing characters
-mod box_chars {
-    pub const TOP_LEFT: char = '╔';
-    pub const TOP_RIGHT: char = '╗';
-    pub const BOTTOM_LEFT: char = '╚';
-    pub const BOTTOM_RIGHT: char = '╝';
-    pub const HORIZONTAL: char = '═';
-    pub const VERTICAL: char = '║';
-    pub const THIN_HORIZONTAL: char = '━';
-    pub const ARROW_DOWN: &str = "│";
-    pub const ARROW_RIGHT: &str = "→";
-    pub const TARGET: &str = "←";
+/// Box-drawing glyph set
+#[derive(Debug)]
+struct BoxChars {
+    top_left: char,
+    top_right: char,
+    bottom_left: char,
+    bottom_right: char,
+    horizontal: char,
+    vertical: char,
+    thin_horizontal: char,
+    arrow_down: &'static str,
+    arrow_right: &'static str,
+    target: &'static str,
+    warning: &'static str,
 }
 
+/// Unicode box-drawing characters (default)
+const UNICODE_BOX: BoxChars = BoxChars {
+    top_left: '╔',
+    top_right: '╗',
+    bottom_left: '╚',
+    bottom_right: '╝',
+    horizontal: '═',
+    vertical: '║',
+    thin_horizontal: '━',
+    arrow_down: "│",
+    arrow_right: "→",
+    target: "←",
+    warning: "⚠",
+};
+
+/// Pure-ASCII connectors with the same widths, for terminals and log
+/// viewers that mangle Unicode
+const ASCII_BOX: BoxChars = BoxChars {
+    top_left: '+',
+    top_right: '+',
+    bottom_left: '+',
+    bottom_right: '+',
+    horizontal: '-',
+    vertical: '|',
+    thin_horizontal: '-',
+    arrow_down: "|",
+    arrow_right: ">",
+    target: "<",
+    warning: "!",
+};
+
 // =============================================================================
 // FORMATTER IMPLEMENTATION
 // =============================================================================
 
+/// Configuration for the ASCII formatter
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AsciiConfig {
+    /// Use pure-ASCII connectors (`+`, `-`, `|`) instead of Unicode box-drawing
+    pub ascii_safe: bool,
+    /// Leave out ANSI colors (`--color never`, or auto when piped)
+    pub no_color: bool,
+}
+
 /// ASCII formatter with rich terminal output
 pub struct AsciiFormatter {
     width: usize,
+    chars: &'static BoxChars,
+    color: bool,
 }
 
 impl AsciiFormatter {
     /// Create a new ASCII formatter
-    pub fn new() -> Self {
+    pub fn new(config: AsciiConfig) -> Self {
         Self {
             width: Self::detect_terminal_width(),
+            chars: if config.ascii_safe {
+                &ASCII_BOX
+            } else {
+                &UNICODE_BOX
+            },
+            color: !config.no_color,
+        }
+    }
+
+    /// Strip the colors from finished output when they are disabled
+    fn finish(&self, output: String) -> String {
+        if self.color {
+            output
+        } else {
+            strip_ansi(&output)
         }
     }
 
@@ -88,28 +150,28 @@ impl AsciiFormatter {
         let inner_width = self.width - 4;
         let mut output = String::new();
 
-        output.push(box_chars::TOP_LEFT);
+        output.push(self.chars.top_left);
         for _ in 0..inner_width + 2 {
-            output.push(box_chars::HORIZONTAL);
+            output.push(self.chars.horizontal);
         }
-        output.push(box_chars::TOP_RIGHT);
+        output.push(self.chars.top_right);
         output.push('\n');
 
         for line in lines {
-            output.push(box_chars::VERTICAL);
+            output.push(self.chars.vertical);
             output.push_str("  ");
             let display_line = self.truncate_or_pad(line.as_ref(), inner_width);
             output.push_str(&display_line);
             output.push_str("  ");
-            output.push(box_chars::VERTICAL);
+            output.push(self.chars.vertical);
             output.push('\n');
         }
 
-        output.push(box_chars::BOTTOM_LEFT);
+        output.push(self.chars.bottom_left);
         for _ in 0..inner_width + 2 {
-            output.push(box_chars::HORIZONTAL);
+            output.push(self.chars.horizontal);
         }
-        output.push(box_chars::BOTTOM_RIGHT);
+        output.push(self.chars.bottom_right);
         output.push('\n');
 
         output
@@ -124,7 +186,7 @@ impl AsciiFormatter {
 
         let mut output = String::new();
         for _ in 0..inner_width {
-            output.push(box_chars::THIN_HORIZONTAL);
+            output.push(self.chars.thin_horizontal);
         }
         output.push('\n');
         output.push_str(left_text);
@@ -134,7 +196,7 @@ impl AsciiFormatter {
         output.push_str(right_text);
         output.push('\n');
         for _ in 0..inner_width {
-            output.push(box_chars::THIN_HORIZONTAL);
+            output.push(self.chars.thin_horizontal);
         }
         output.push('\n');
 
@@ -207,6 +269,20 @@ impl AsciiFormatter {
         }
     }
 
+    /// Footer listing files that could not be read (empty when none)
+    fn format_warnings(&self, warnings: &[String]) -> String {
+        let mut output = String::new();
+        for warning in warnings {
+            output.push_str(&format!(
+                "{}warning:{} {}\n",
+                colors::YELLOW,
+                colors::RESET,
+                warning
+            ));
+        }
+        output
+    }
+
     /// Color a string based on risk level
     fn color_risk(&self, risk: RiskLevel) -> &'static str {
         match risk {
@@ -220,7 +296,7 @@ impl AsciiFormatter {
 
 impl Default for AsciiFormatter {
     fn default() -> Self {
-        Self::new()
+        Self::new(AsciiConfig::default())
     }
 }
 
@@ -280,12 +356,12 @@ impl TraceFormatter for AsciiFormatter {
                         colors::DIM,
                         location,
                         colors::RESET,
-                        box_chars::ARROW_RIGHT,
+                        self.chars.arrow_right,
                         colors::BOLD,
                         colors::GREEN,
                         step.symbol,
                         colors::YELLOW,
-                        box_chars::TARGET,
+                        self.chars.target,
                         colors::RESET,
                         width = max_file_width + padding
                     ));
@@ -295,7 +371,7 @@ impl TraceFormatter for AsciiFormatter {
                         colors::DIM,
                         location,
                         colors::RESET,
-                        box_chars::ARROW_RIGHT,
+                        self.chars.arrow_right,
                         colors::CYAN,
                         step.symbol,
                         colors::RESET,
@@ -322,7 +398,7 @@ impl TraceFormatter for AsciiFormatter {
                         colors::DIM,
                         "",
                         colors::RESET,
-                        box_chars::ARROW_DOWN,
+                        self.chars.arrow_down,
                         width = max_file_width + padding
                     ));
                     output.push('\n');
@@ -331,7 +407,7 @@ impl TraceFormatter for AsciiFormatter {
             output.push('\n');
         }
 
-        output
+        self.finish(output)
     }
 
     fn format_refs(&self, result: &RefsResult) -> String {
@@ -414,11 +490,93 @@ impl TraceFormatter for AsciiFormatter {
                     ));
                     output.push('\n');
                 }
+                if let (Some(callee), Some(index)) = (&r.callee, r.arg_index) {
+                    output.push_str(&format!(
+                        "      {}(argument {} of {}){}",
+                        colors::DIM,
+                        index + 1,
+                        callee,
+                        colors::RESET
+                    ));
+                    output.push('\n');
+                }
+                if r.target_deprecated {
+                    output.push_str(&format!(
+                        "      {}(deprecated){}\n",
+                        colors::YELLOW,
+                        colors::RESET
+                    ));
+                }
+                if let Some(old_name) = r.renamed_from(&result.symbol) {
+                    output.push_str(&format!(
+                        "      {}(as {}){}\n",
+                        colors::DIM,
+                        old_name,
+                        colors::RESET
+                    ));
+                }
+                if let Some(depth) = r.depth {
+                    output.push_str(&format!(
+                        "      {}(depth {}){}\n",
+                        colors::DIM,
+                        depth,
+                        colors::RESET
+                    ));
+                }
             }
             output.push('\n');
         }
 
-        output
+        output.push_str(&self.format_warnings(&result.warnings));
+        self.finish(output)
+    }
+
+    fn format_refs_by_directory(&self, result: &RefsResult) -> String {
+        let mut output = String::new();
+
+        let header_lines = [
+            &format!(
+                "{}{}REFS BY DIRECTORY:{} {}",
+                colors::BOLD,
+                colors::CYAN,
+                colors::RESET,
+                result.symbol
+            ),
+            &format!(
+                "{}Found:{} {} references in {} directories",
+                colors::DIM,
+                colors::RESET,
+                result.by_file.values().sum::<usize>(),
+                result.by_directory.len()
+            ),
+        ];
+        output.push_str(&self.draw_header_box(&header_lines));
+        output.push('\n');
+
+        for group in &result.by_directory {
+            output.push_str(&format!(
+                "{}{}{}/{}  {}{} refs{}\n",
+                colors::BOLD,
+                colors::WHITE,
+                group.directory,
+                colors::RESET,
+                colors::DIM,
+                group.count,
+                colors::RESET
+            ));
+            for file in &group.files {
+                output.push_str(&format!(
+                    "  {:<5} {}{}{}\n",
+                    file.count,
+                    colors::CYAN,
+                    file.file,
+                    colors::RESET
+                ));
+            }
+            output.push('\n');
+        }
+
+        self.finish(output)
     }
 
     fn format_dead_code(&self, result: &DeadCodeResult) -> String {
@@ -474,8 +632,9 @@ impl TraceFormatter for AsciiFormatter {
                 ));
                 for caller in &sym.potential_callers {
                     output.push_str(&format!(
-                        "        {}→{} {}  {}{}:{}{}  {}{}{}\n",
+                        "        {}{}{} {}  {}{}:{}{}  {}{}{}\n",
                         colors::GREEN,
+                        self.chars.arrow_right,
                         colors::RESET,
                         caller.name,
                         colors::DIM,
@@ -488,9 +647,342 @@ impl TraceFormatter for AsciiFormatter {
                     ));
                 }
             }
+            if let Some(replacement) = &sym.likely_replacement {
+                output.push_str(&format!(
+                    "      {}Likely replacement:{} {}{}{}\n",
+                    colors::DIM,
+                    colors::RESET,
+                    colors::GREEN,
+                    replacement,
+                    colors::RESET
+                ));
+            }
         }
 
-        output
+        self.finish(output)
+    }
+
+    fn format_deprecated(&self, result: &DeprecatedResult) -> String {
+        let mut output = String::new();
+
+        let header_lines = [
+            &format!(
+                "{}{}DEPRECATED SYMBOLS{}",
+                colors::BOLD,
+                colors::YELLOW,
+                colors::RESET
+            ),
+            &format!(
+                "{}Found:{} {} deprecated symbols, {} usages to migrate",
+                colors::DIM,
+                colors::RESET,
+                result.total_deprecated,
+                result.total_usages
+            ),
+        ];
+        output.push_str(&self.draw_header_box(&header_lines));
+        output.push('\n');
+
+        for sym in &result.symbols {
+            let usage_color = if sym.usages > 0 {
+                colors::RED
+            } else {
+                colors::GREEN
+            };
+            output.push_str(&format!(
+                "  {}{}{}  {}{}:{}{}  {}{} usages{}\n",
+                colors::YELLOW,
+                sym.name,
+                colors::RESET,
+                colors::DIM,
+                sym.file,
+                sym.line,
+                colors::RESET,
+                usage_color,
+                sym.usages,
+                colors::RESET
+            ));
+        }
+
+        self.finish(output)
+    }
+
+    fn format_neighborhood(&self, result: &NeighborhoodResult) -> String {
+        let mut output = String::new();
+
+        let header_lines = [
+            &format!(
+                "{}{}NEIGHBORHOOD:{} {}",
+                colors::BOLD,
+                colors::CYAN,
+                colors::RESET,
+                result.symbol
+            ),
+            &format!(
+                "{}Callers:{} {}  {}Callees:{} {}",
+                colors::DIM,
+                colors::RESET,
+                result.callers.len(),
+                colors::DIM,
+                colors::RESET,
+                result.callees.len()
+            ),
+        ];
+        output.push_str(&self.draw_header_box(&header_lines));
+        output.push('\n');
+
+        // Callers above
+        for caller in &result.callers {
+            output.push_str(&format!(
+                "  {}{}{}  {}{}:{}{}\n",
+                colors::CYAN,
+                caller.name,
+                colors::RESET,
+                colors::DIM,
+                caller.file,
+                caller.call_line,
+                colors::RESET
+            ));
+        }
+        if !result.callers.is_empty() {
+            output.push_str(&format!("      {}\n", self.chars.arrow_down));
+        }
+
+        // Symbol center
+        output.push_str(&format!(
+            "  {}{}{}{} {}{}{}{}\n",
+            colors::BOLD,
+            colors::GREEN,
+            result.symbol,
+            colors::RESET,
+            colors::DIM,
+            result.defined_at.as_deref().unwrap_or(""),
+            colors::RESET,
+            if result.kind.is_empty() {
+                String::new()
+            } else {
+                format!("  ({})", result.kind)
+            }
+        ));
+
+        // Callees below
+        if !result.callees.is_empty() {
+            output.push_str(&format!("      {}\n", self.chars.arrow_down));
+        }
+        for callee in &result.callees {
+            output.push_str(&format!(
+                "  {} {}{}{}  {}{}:{}{}\n",
+                self.chars.arrow_right,
+                colors::YELLOW,
+                callee.name,
+                colors::RESET,
+                colors::DIM,
+                callee.file,
+                callee.line,
+                colors::RESET
+            ));
+        }
+
+        self.finish(output)
+    }
+
+    fn format_hotspots(&self, result: &HotspotResult) -> String {
+        let mut output = String::new();
+
+        let ranking = if result.churn_available {
+            "refs x churn"
+        } else {
+            "refs only (no git history)"
+        };
+        let header_lines = [
+            &format!("{}{}HOTSPOTS{}", colors::BOLD, colors::CYAN, colors::RESET),
+            &format!("{}Ranked by:{} {}", colors::DIM, colors::RESET, ranking),
+        ];
+        output.push_str(&self.draw_header_box(&header_lines));
+        output.push('\n');
+
+        for file in &result.files {
+            let churn = file.churn.map_or("-".to_string(), |c| c.to_string());
+            output.push_str(&format!(
+                "  {}{:>8}{}  {}refs{} {:<6} {}churn{} {:<6} {}\n",
+                colors::YELLOW,
+                file.score,
+                colors::RESET,
+                colors::DIM,
+                colors::RESET,
+                file.refs,
+                colors::DIM,
+                colors::RESET,
+                churn,
+                file.file
+            ));
+        }
+
+        self.finish(output)
+    }
+
+    fn format_unused_params(&self, result: &UnusedParamsResult) -> String {
+        let mut output = String::new();
+
+        let header_lines = [
+            &format!(
+                "{}{}UNUSED PARAMETERS{}",
+                colors::BOLD,
+                colors::YELLOW,
+                colors::RESET
+            ),
+            &format!(
+                "{}Found:{} {} parameters never read",
+                colors::DIM,
+                colors::RESET,
+                result.total
+            ),
+        ];
+        output.push_str(&self.draw_header_box(&header_lines));
+        output.push('\n');
+
+        for param in &result.params {
+            output.push_str(&format!(
+                "  {}{}{} in {}  {}{}:{}{}\n",
+                colors::YELLOW,
+                param.name,
+                colors::RESET,
+                param.function,
+                colors::DIM,
+                param.file,
+                param.line,
+                colors::RESET
+            ));
+        }
+
+        self.finish(output)
+    }
+
+    fn format_shadows(&self, result: &ShadowsResult) -> String {
+        let mut output = String::new();
+
+        let header_lines = [
+            &format!(
+                "{}{}SHADOWED VARIABLES{}",
+                colors::BOLD,
+                colors::YELLOW,
+                colors::RESET
+            ),
+            &format!(
+                "{}Found:{} {} declarations shadowing an outer binding",
+                colors::DIM,
+                colors::RESET,
+                result.total
+            ),
+        ];
+        output.push_str(&self.draw_header_box(&header_lines));
+        output.push('\n');
+
+        for shadow in &result.shadows {
+            output.push_str(&format!(
+                "  {}{}{} in {}  {}{}:{} shadows line {}{}\n",
+                colors::YELLOW,
+                shadow.name,
+                colors::RESET,
+                shadow.function,
+                colors::DIM,
+                shadow.file,
+                shadow.line,
+                shadow.shadowed_line,
+                colors::RESET
+            ));
+        }
+
+        self.finish(output)
+    }
+
+    fn format_stubs(&self, result: &StubsResult) -> String {
+        let mut output = String::new();
+
+        let header_lines = [
+            &format!(
+                "{}{}STUB FUNCTIONS{}",
+                colors::BOLD,
+                colors::YELLOW,
+                colors::RESET
+            ),
+            &format!(
+                "{}Found:{} {} functions with empty or placeholder bodies",
+                colors::DIM,
+                colors::RESET,
+                result.total
+            ),
+        ];
+        output.push_str(&self.draw_header_box(&header_lines));
+        output.push('\n');
+
+        for stub in &result.stubs {
+            output.push_str(&format!(
+                "  {}{}{} ({})  {}{}:{}{}  {}\n",
+                colors::YELLOW,
+                stub.name,
+                colors::RESET,
+                stub.kind,
+                colors::DIM,
+                stub.file,
+                stub.line,
+                colors::RESET,
+                stub.placeholder.as_deref().unwrap_or("empty")
+            ));
+        }
+
+        self.finish(output)
+    }
+
+    fn format_call_hierarchy(&self, result: &CallHierarchyResult) -> String {
+        let mut output = String::new();
+
+        let header_lines = [
+            &format!(
+                "{}{}CALL HIERARCHY:{} {}",
+                colors::BOLD,
+                colors::CYAN,
+                colors::RESET,
+                result.symbol
+            ),
+            &format!(
+                "{}Found:{} {} callers (max depth {})",
+                colors::DIM,
+                colors::RESET,
+                result.total_nodes.saturating_sub(result.roots.len()),
+                result.max_depth
+            ),
+        ];
+        output.push_str(&self.draw_header_box(&header_lines));
+        output.push('\n');
+
+        for (depth, node) in CallHierarchyNode::walk(&result.roots) {
+            let indent = format!("{}  ", self.chars.arrow_down).repeat(depth);
+            let (color, marker) = if depth == 0 {
+                (colors::GREEN, String::new())
+            } else {
+                (colors::CYAN, format!("{} ", self.chars.target))
+            };
+            output.push_str(&format!(
+                "  {}{}{}{}{}{}  {}{}:{}{}",
+                colors::DIM,
+                indent,
+                colors::RESET,
+                marker,
+                color,
+                node.symbol,
+                colors::DIM,
+                node.file,
+                node.line,
+                colors::RESET
+            ));
+            if node.see_above {
+                output.push_str(&format!("  {}(see above){}", colors::DIM, colors::RESET));
+            }
+            output.push('\n');
+        }
+
+        self.finish(output)
     }
 
     fn format_flow(&self, result: &FlowResult) -> String {
@@ -545,7 +1037,7 @@ impl TraceFormatter for AsciiFormatter {
             output.push('\n');
         }
 
-        output
+        self.finish(output)
     }
 
     fn format_impact(&self, result: &ImpactResult) -> String {
@@ -570,10 +1062,24 @@ impl TraceFormatter for AsciiFormatter {
                 result.risk_level,
                 colors::RESET
             ),
+            &format!(
+                "{}Risk Score:{} {}",
+                colors::DIM,
+                colors::RESET,
+                result.risk_score
+            ),
         ];
         output.push_str(&self.draw_header_box(&header_lines));
         output.push('\n');
 
+        if !result.risk_factors.is_empty() {
+            output.push_str(&format!("{}Risk factors:{}\n", colors::BOLD, colors::RESET));
+            for factor in &result.risk_factors {
+                output.push_str(&format!("  - {}\n", factor));
+            }
+            output.push('\n');
+        }
+
         output.push_str(&format!(
             "{}Direct callers ({}):{}\n",
             colors::BOLD,
@@ -581,7 +1087,7 @@ impl TraceFormatter for AsciiFormatter {
             colors::RESET
         ));
         for caller in &result.direct_callers {
-            output.push_str(&format!("  {} {}\n", box_chars::ARROW_RIGHT, caller));
+            output.push_str(&format!("  {} {}\n", self.chars.arrow_right, caller));
         }
         output.push('\n');
 
@@ -595,7 +1101,7 @@ impl TraceFormatter for AsciiFormatter {
             for caller in result.transitive_callers.iter().take(10) {
                 output.push_str(&format!(
                     "  {} {}{}{}\n",
-                    box_chars::ARROW_RIGHT,
+                    self.chars.arrow_right,
                     colors::DIM,
                     caller,
                     colors::RESET
@@ -621,13 +1127,30 @@ impl TraceFormatter for AsciiFormatter {
         for ep in &result.affected_entry_points {
             output.push_str(&format!(
                 "  {} {}{}{}\n",
-                box_chars::ARROW_RIGHT,
+                self.chars.arrow_right,
                 colors::GREEN,
                 ep,
                 colors::RESET
             ));
         }
 
+        if !result.paths_to_entry.is_empty() {
+            output.push_str(&format!(
+                "\n{}Paths to entry points:{}\n",
+                colors::BOLD,
+                colors::RESET
+            ));
+            let separator = format!(" {} ", self.chars.arrow_right);
+            for path in &result.paths_to_entry {
+                output.push_str(&format!(
+                    "  {}{}{}\n",
+                    result.symbol,
+                    separator,
+                    path.join(&separator)
+                ));
+            }
+        }
+
         output.push_str(&format!(
             "\n{}Files affected:{} {}\n",
             colors::DIM,
@@ -635,7 +1158,7 @@ impl TraceFormatter for AsciiFormatter {
             result.files_affected.len()
         ));
 
-        output
+        self.finish(output)
     }
 
     fn format_module(&self, result: &ModuleResult) -> String {
@@ -664,7 +1187,7 @@ impl TraceFormatter for AsciiFormatter {
             for export in &result.exports {
                 output.push_str(&format!(
                     "  {} {}{}{}\n",
-                    box_chars::ARROW_RIGHT,
+                    self.chars.arrow_right,
                     colors::GREEN,
                     export,
                     colors::RESET
@@ -681,7 +1204,7 @@ impl TraceFormatter for AsciiFormatter {
                 colors::RESET
             ));
             for importer in &result.imported_by {
-                output.push_str(&format!("  {} {}\n", box_chars::ARROW_RIGHT, importer));
+                output.push_str(&format!("  {} {}\n", self.chars.arrow_right, importer));
             }
             output.push('\n');
         }
@@ -696,7 +1219,7 @@ impl TraceFormatter for AsciiFormatter {
             for dep in &result.dependencies {
                 output.push_str(&format!(
                     "  {} {}{}{}\n",
-                    box_chars::ARROW_RIGHT,
+                    self.chars.arrow_right,
                     colors::CYAN,
                     dep,
                     colors::RESET
@@ -715,15 +1238,16 @@ impl TraceFormatter for AsciiFormatter {
             ));
             for cycle in &result.circular_deps {
                 output.push_str(&format!(
-                    "  {}⚠ {}{}\n",
+                    "  {}{} {}{}\n",
                     colors::YELLOW,
+                    self.chars.warning,
                     cycle,
                     colors::RESET
                 ));
             }
         }
 
-        output
+        self.finish(output)
     }
 
     fn format_pattern(&self, result: &PatternResult) -> String {
@@ -786,12 +1310,29 @@ impl TraceFormatter for AsciiFormatter {
                         colors::RESET
                     ));
                     output.push('\n');
+                } else if let Some(ref nearest) = m.nearest_symbol {
+                    output.push_str(&format!(
+                        "      {}(after {}){}",
+                        colors::DIM,
+                        nearest,
+                        colors::RESET
+                    ));
+                    output.push('\n');
                 }
             }
             output.push('\n');
         }
 
-        output
+        if result.truncated {
+            output.push_str(&format!(
+                "{}Results truncated at {} matches (raise --max-results to see more){}\n",
+                colors::YELLOW,
+                result.total_matches,
+                colors::RESET
+            ));
+        }
+        output.push_str(&self.format_warnings(&result.warnings));
+        self.finish(output)
     }
 
     fn format_scope(&self, result: &ScopeResult) -> String {
@@ -825,7 +1366,7 @@ impl TraceFormatter for AsciiFormatter {
                     colors::CYAN,
                     var.name,
                     colors::RESET,
-                    var.kind,
+                    var.declared_type.as_deref().unwrap_or(&var.kind),
                     colors::DIM,
                     var.defined_at,
                     colors::RESET
@@ -866,7 +1407,7 @@ impl TraceFormatter for AsciiFormatter {
             }
         }
 
-        output
+        self.finish(output)
     }
 
     fn format_stats(&self, result: &StatsResult) -> String {
@@ -917,10 +1458,29 @@ impl TraceFormatter for AsciiFormatter {
         }
         output.push('\n');
 
+        // Symbols by visibility
+        if !result.symbols_by_visibility.is_empty() {
+            output.push_str(&format!(
+                "{}Symbols by visibility:{}\n",
+                colors::BOLD,
+                colors::RESET
+            ));
+            for (visibility, count) in result.visibility_counts() {
+                output.push_str(&format!("  {}: {}\n", visibility, count));
+            }
+            output.push('\n');
+        }
+
         // Call graph
         output.push_str(&format!("{}Call Graph:{}\n", colors::BOLD, colors::RESET));
         output.push_str(&format!("  Max Call Depth: {}\n", result.max_call_depth));
         output.push_str(&format!("  Avg Call Depth: {:.1}\n", result.avg_call_depth));
+        output.push_str(&format!(
+            "  {}over {} entry points{}\n",
+            colors::DIM,
+            result.depth_entry_points,
+            colors::RESET
+        ));
         output.push('\n');
 
         // Most referenced
@@ -954,7 +1514,29 @@ impl TraceFormatter for AsciiFormatter {
             }
         }
 
-        output
+        // Stalest symbols (--with-dates)
+        if !result.last_modified.is_empty() {
+            output.push_str(&format!(
+                "\n{}Least Recently Modified:{}\n",
+                colors::BOLD,
+                colors::RESET
+            ));
+            for s in result.last_modified.iter().take(10) {
+                output.push_str(&format!(
+                    "  {}{}{} {}{}{} {}:{}\n",
+                    colors::DIM,
+                    s.last_modified,
+                    colors::RESET,
+                    colors::CYAN,
+                    s.name,
+                    colors::RESET,
+                    s.file,
+                    s.line
+                ));
+            }
+        }
+
+        self.finish(output)
     }
 }
 
@@ -964,7 +1546,7 @@ mod tests {
 
     #[test]
     fn test_visible_len() {
-        let formatter = AsciiFormatter::new();
+        let formatter = AsciiFormatter::default();
         assert_eq!(formatter.visible_len("hello"), 5);
         assert_eq!(formatter.visible_len("\x1b[32mhello\x1b[0m"), 5);
         assert_eq!(formatter.visible_len("\x1b[1m\x1b[32mtest\x1b[0m"), 4);
@@ -972,7 +1554,7 @@ mod tests {
 
     #[test]
     fn test_format_trace_basic() {
-        let formatter = AsciiFormatter::new();
+        let formatter = AsciiFormatter::default();
         let result = TraceResult {
             symbol: "validateUser".to_string(),
             defined_at: Some("utils/validation.ts:8".to_string()),
@@ -980,9 +1562,85 @@ mod tests {
             invocation_paths: vec![],
             total_paths: 0,
             entry_points: 0,
+            definitions: Vec::new(),
+            symbol_id: None,
+            stable_id: None,
         };
         let output = formatter.format_trace(&result);
         assert!(output.contains("validateUser"));
         assert!(output.contains("TRACE"));
     }
+
+    #[test]
+    fn test_ascii_safe_output_is_pure_ascii() {
+        use super::super::{ChainStep, DeadSymbol, InvocationPath, PotentialCaller};
+
+        let formatter = AsciiFormatter::new(AsciiConfig {
+            ascii_safe: true,
+            no_color: false,
+        });
+        let step = |symbol: &str, line: u32| ChainStep {
+            symbol: symbol.to_string(),
+            file: "src/api.ts".to_string(),
+            line,
+            column: None,
+            context: None,
+        };
+        let trace = TraceResult {
+            symbol: "validateUser".to_string(),
+            defined_at: Some("utils/validation.ts:8".to_string()),
+            kind: "function".to_string(),
+            invocation_paths: vec![InvocationPath {
+                entry_point: "main".to_string(),
+                entry_kind: "function".to_string(),
+                chain: vec![step("main", 1), step("login", 10), step("validateUser", 20)],
+            }],
+            total_paths: 1,
+            entry_points: 1,
+            definitions: Vec::new(),
+            symbol_id: None,
+            stable_id: None,
+        };
+        let dead = DeadCodeResult {
+            symbols: vec![DeadSymbol {
+                name: "unused".to_string(),
+                kind: "function".to_string(),
+                file: "src/lib.ts".to_string(),
+                line: 3,
+                reason: "No references or calls found".to_string(),
+                potential_callers: vec![PotentialCaller {
+                    name: "helper".to_string(),
+                    file: "src/lib.ts".to_string(),
+                    line: 9,
+                    reason: "same file".to_string(),
+                }],
+                likely_replacement: None,
+            }],
+            total_dead: 1,
+            by_kind: Default::default(),
+            by_file: Default::default(),
+        };
+
+        for output in [
+            formatter.format_trace(&trace),
+            formatter.format_dead_code(&dead),
+        ] {
+            assert!(output.is_ascii(), "non-ASCII output: {}", output);
+            assert!(output.contains("+--"));
+        }
+
+        // Same layout as the Unicode formatter
+        let unicode = AsciiFormatter::default().format_trace(&trace);
+        assert_eq!(
+            unicode
+                .lines()
+                .map(|l| l.chars().count())
+                .collect::<Vec<_>>(),
+            formatter
+                .format_trace(&trace)
+                .lines()
+                .map(|l| l.chars().count())
+                .collect::<Vec<_>>()
+        );
+    }
 }
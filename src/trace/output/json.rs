@@ -8,9 +8,11 @@
 //! @module trace/output/json
 
 use super::{
-    DeadCodeResult, FlowResult, ImpactResult, ModuleResult, PatternResult, RefsResult, ScopeResult,
-    StatsResult, TraceFormatter, TraceResult,
+    CallHierarchyResult, DeadCodeResult, DeprecatedResult, FlowResult, HotspotResult, ImpactResult,
+    ModuleResult, NeighborhoodResult, PatternResult, RefsResult, ScopeResult, ShadowsResult,
+    StatsResult, StubsResult, TraceFormatter, TraceResult, UnusedParamsResult,
 };
+use std::io::{self, Write};
 
 // =============================================================================
 // FORMATTER IMPLEMENTATION
@@ -59,10 +61,42 @@ impl TraceFormatter for JsonFormatter {
         self.to_json(result)
     }
 
+    fn format_refs_by_directory(&self, result: &RefsResult) -> String {
+        self.to_json(result)
+    }
+
     fn format_dead_code(&self, result: &DeadCodeResult) -> String {
         self.to_json(result)
     }
 
+    fn format_deprecated(&self, result: &DeprecatedResult) -> String {
+        self.to_json(result)
+    }
+
+    fn format_neighborhood(&self, result: &NeighborhoodResult) -> String {
+        self.to_json(result)
+    }
+
+    fn format_hotspots(&self, result: &HotspotResult) -> String {
+        self.to_json(result)
+    }
+
+    fn format_unused_params(&self, result: &UnusedParamsResult) -> String {
+        self.to_json(result)
+    }
+
+    fn format_shadows(&self, result: &ShadowsResult) -> String {
+        self.to_json(result)
+    }
+
+    fn format_stubs(&self, result: &StubsResult) -> String {
+        self.to_json(result)
+    }
+
+    fn format_call_hierarchy(&self, result: &CallHierarchyResult) -> String {
+        self.to_json(result)
+    }
+
     fn format_flow(&self, result: &FlowResult) -> String {
         self.to_json(result)
     }
@@ -88,6 +122,72 @@ impl TraceFormatter for JsonFormatter {
     }
 }
 
+// =============================================================================
+// STREAMING
+// =============================================================================
+
+/// Writes one JSON object whose array member is streamed element by element
+///
+/// Large results (`--refs`, `--pattern` with `--stream`) are written as they
+/// are found instead of being collected and serialized at the end. The array
+/// comes first; the remaining members are taken from a summary value whose
+/// own copy of the array is skipped.
+pub struct JsonArrayWriter<W: Write> {
+    out: W,
+    field: String,
+    len: usize,
+}
+
+impl<W: Write> JsonArrayWriter<W> {
+    /// Open the object and its `field` array
+    pub fn begin(mut out: W, field: &str) -> io::Result<Self> {
+        out.write_all(b"{")?;
+        serde_json::to_writer(&mut out, field)?;
+        out.write_all(b":[")?;
+        Ok(Self {
+            out,
+            field: field.to_string(),
+            len: 0,
+        })
+    }
+
+    /// Write one array element
+    pub fn push<T: serde::Serialize>(&mut self, item: &T) -> io::Result<()> {
+        if self.len > 0 {
+            self.out.write_all(b",")?;
+        }
+        serde_json::to_writer(&mut self.out, item)?;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Number of elements written so far
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether no elements have been written
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Close the array and write the other members of `summary`
+    pub fn finish<T: serde::Serialize>(mut self, summary: &T) -> io::Result<W> {
+        self.out.write_all(b"]")?;
+        if let serde_json::Value::Object(members) = serde_json::to_value(summary)? {
+            for (key, value) in members.iter().filter(|(key, _)| **key != self.field) {
+                self.out.write_all(b",")?;
+                serde_json::to_writer(&mut self.out, key)?;
+                self.out.write_all(b":")?;
+                serde_json::to_writer(&mut self.out, value)?;
+            }
+        }
+        self.out.write_all(b"}")?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -129,6 +229,9 @@ mod tests {
             }],
             total_paths: 47,
             entry_points: 12,
+            definitions: Vec::new(),
+            symbol_id: None,
+            stable_id: None,
         };
 
         let output = formatter.format_trace(&result);
@@ -169,10 +272,23 @@ mod tests {
                 kind: ReferenceKind::Read,
                 context: "const id = userId;".to_string(),
                 enclosing_symbol: Some("handleRequest".to_string()),
+                confidence: 1.0,
+                byte_offset: None,
+                callee: None,
+                arg_index: None,
+                target_deprecated: false,
+                referenced_as: None,
+                depth: None,
             }],
             total_refs: 7,
             by_kind,
+            by_confidence: std::collections::HashMap::new(),
             by_file: std::collections::HashMap::new(),
+            by_directory: Vec::new(),
+            source_hashes: std::collections::HashMap::new(),
+            warnings: vec![],
+            symbol_id: None,
+            stable_id: None,
         };
 
         let output = formatter.format_refs(&result);
@@ -197,6 +313,9 @@ mod tests {
             invocation_paths: vec![],
             total_paths: 0,
             entry_points: 0,
+            definitions: Vec::new(),
+            symbol_id: None,
+            stable_id: None,
         };
 
         let output = formatter.format_trace(&result);
@@ -215,6 +334,9 @@ mod tests {
             invocation_paths: vec![],
             total_paths: 0,
             entry_points: 0,
+            definitions: Vec::new(),
+            symbol_id: None,
+            stable_id: None,
         };
 
         let output = formatter.format_trace(&result);
@@ -0,0 +1,138 @@
+//! LSP location output
+//!
+//! Renders references as a Language Server Protocol `Location[]`, so editor
+//! extensions can use `--format lsp` output without translating it:
+//! `file://` URIs and zero-based lines, where greppy's own lines are 1-based.
+//!
+//! @module trace/output/lsp
+
+use serde::Serialize;
+use std::path::Path;
+
+use super::RefsResult;
+
+/// Zero-based line and character offset
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LspPosition {
+    pub line: u32,
+    pub character: u32,
+}
+
+/// Half-open range between two positions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LspRange {
+    pub start: LspPosition,
+    pub end: LspPosition,
+}
+
+/// A range inside a document
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LspLocation {
+    pub uri: String,
+    pub range: LspRange,
+}
+
+/// The references of `result` as LSP locations
+///
+/// Relative paths are resolved against `root`. Each range covers the name
+/// used at the reference; columns are passed through as character offsets,
+/// which matches LSP's UTF-16 offsets on ASCII lines.
+pub fn lsp_locations(result: &RefsResult, root: &Path) -> Vec<LspLocation> {
+    result
+        .references
+        .iter()
+        .filter_map(|r| {
+            let uri = url::Url::from_file_path(root.join(&r.file)).ok()?;
+            let name = r.referenced_as.as_deref().unwrap_or(&result.symbol);
+            // `Type::method` and `obj.method` are referenced by their last segment
+            let name = name.rsplit([':', '.']).next().unwrap_or(name);
+            let line = r.line.saturating_sub(1);
+            let character = u32::from(r.column);
+            Some(LspLocation {
+                uri: uri.to_string(),
+                range: LspRange {
+                    start: LspPosition { line, character },
+                    end: LspPosition {
+                        line,
+                        character: character + name.encode_utf16().count() as u32,
+                    },
+                },
+            })
+        })
+        .collect()
+}
+
+/// `Location[]` JSON for the references of `result`
+pub fn format_lsp_locations(result: &RefsResult, root: &Path) -> String {
+    serde_json::to_string_pretty(&lsp_locations(result, root))
+        .unwrap_or_else(|e| format!(r#"{{"error": "JSON serialization failed: {}"}}"#, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace::output::{ReferenceInfo, ReferenceKind};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_lsp_locations_are_zero_based_file_uris() {
+        let reference = |file: &str, line: u32, column: u16| ReferenceInfo {
+            file: file.to_string(),
+            line,
+            column,
+            kind: ReferenceKind::Call,
+            context: String::new(),
+            enclosing_symbol: None,
+            confidence: 1.0,
+            byte_offset: None,
+            callee: None,
+            arg_index: None,
+            target_deprecated: false,
+            referenced_as: None,
+            depth: None,
+        };
+        let root = std::env::temp_dir().join("project");
+        let result = RefsResult {
+            symbol: "Cache::fetch".to_string(),
+            defined_at: None,
+            symbol_kind: None,
+            references: vec![
+                reference("src/lib.rs", 1, 4),
+                reference(&root.join("src/main.rs").to_string_lossy(), 12, 8),
+            ],
+            total_refs: 2,
+            by_kind: HashMap::new(),
+            by_confidence: HashMap::new(),
+            by_file: HashMap::new(),
+            by_directory: Vec::new(),
+            source_hashes: HashMap::new(),
+            warnings: Vec::new(),
+            symbol_id: None,
+            stable_id: None,
+        };
+
+        let output = format_lsp_locations(&result, &root);
+        let parsed: serde_json::Value = serde_json::from_str(&output).unwrap();
+        let locations = parsed.as_array().expect("Location[]");
+        assert_eq!(locations.len(), 2);
+
+        let first = &locations[0];
+        let uri = url::Url::parse(first["uri"].as_str().unwrap()).unwrap();
+        assert_eq!(uri.scheme(), "file");
+        assert_eq!(uri.to_file_path().unwrap(), root.join("src/lib.rs"));
+        assert_eq!(
+            first["range"],
+            serde_json::json!({
+                "start": { "line": 0, "character": 4 },
+                "end": { "line": 0, "character": 9 },
+            })
+        );
+
+        // Absolute paths are kept as they are
+        let second = &locations[1];
+        let uri = url::Url::parse(second["uri"].as_str().unwrap()).unwrap();
+        assert_eq!(uri.to_file_path().unwrap(), root.join("src/main.rs"));
+        assert_eq!(second["range"]["start"]["line"], 11);
+        assert_eq!(second["range"]["end"]["character"], 13);
+    }
+}
@@ -7,11 +7,13 @@
 //! - CSV: Spreadsheet-compatible format
 //! - DOT: Graph visualization format
 //! - Markdown: Documentation format
+//! - LSP: `Location[]` for editor extensions (references only)
 //!
 //! @module trace/output
 
 pub mod ascii;
 pub mod json;
+pub mod lsp;
 pub mod plain;
 
 // =============================================================================
@@ -66,6 +68,35 @@ pub struct ReferenceInfo {
     pub context: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub enclosing_symbol: Option<String>,
+    /// 1.0 for AST-resolved references, lower for name-only token matches
+    pub confidence: f32,
+    /// Byte offset of the reference in its file (with `--byte-offsets`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub byte_offset: Option<u32>,
+    /// Function the symbol is passed to (with `--arg-positions`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub callee: Option<String>,
+    /// 0-based argument position in the call to `callee`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arg_index: Option<usize>,
+    /// The referenced symbol is deprecated (with `--annotate-deprecated`)
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub target_deprecated: bool,
+    /// Name the reference used, old or current (with `--follow-rename`),
+    /// or the symbol it reaches the target through (with `--transitive`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub referenced_as: Option<String>,
+    /// Hops from the queried symbol (with `--transitive`): 1 for direct
+    /// references, 2 for references to their enclosing symbols, ...
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depth: Option<u32>,
+}
+
+impl ReferenceInfo {
+    /// Old name the reference used, when it differs from `symbol`
+    pub fn renamed_from(&self, symbol: &str) -> Option<&str> {
+        self.referenced_as.as_deref().filter(|name| *name != symbol)
+    }
 }
 
 /// Kind of reference
@@ -104,6 +135,10 @@ pub struct DeadSymbol {
     /// Cross-reference: potential callers that could use this symbol
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub potential_callers: Vec<PotentialCaller>,
+    /// Cross-reference: the closest-named live symbol nearby, which may have
+    /// superseded this one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub likely_replacement: Option<String>,
 }
 
 /// A potential caller/reference for dead code cross-referencing
@@ -163,7 +198,13 @@ pub struct ImpactResult {
     pub transitive_caller_count: usize,
     pub affected_entry_points: Vec<String>,
     pub files_affected: Vec<String>,
+    /// Caller chains from a direct caller up to an entry point (a few, shortest first)
+    pub paths_to_entry: Vec<Vec<String>>,
     pub risk_level: RiskLevel,
+    /// Weighted risk score the level was derived from
+    pub risk_score: u32,
+    /// Human-readable contributors to the risk score
+    pub risk_factors: Vec<String>,
 }
 
 /// Risk level for impact analysis
@@ -197,6 +238,9 @@ pub struct PatternMatch {
     pub context: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub enclosing_symbol: Option<String>,
+    /// Enclosing symbol, or the closest symbol defined before the match
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nearest_symbol: Option<String>,
 }
 
 /// Variable in scope
@@ -205,6 +249,9 @@ pub struct ScopeVariable {
     pub name: String,
     pub kind: String,
     pub defined_at: u32,
+    /// Type written in the declaration (`let x: Foo`), when there is one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub declared_type: Option<String>,
 }
 
 // =============================================================================
@@ -218,9 +265,18 @@ pub struct TraceResult {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub defined_at: Option<String>,
     pub kind: String,
+    /// Index id of the traced symbol; changes when the index is rebuilt
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol_id: Option<u32>,
+    /// Id derived from the symbol's name, file and kind, stable across rebuilds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stable_id: Option<String>,
     pub invocation_paths: Vec<InvocationPath>,
     pub total_paths: usize,
     pub entry_points: usize,
+    /// One result per definition when definitions are not merged
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub definitions: Vec<TraceResult>,
 }
 
 /// Result of a reference trace operation
@@ -231,10 +287,46 @@ pub struct RefsResult {
     pub defined_at: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub symbol_kind: Option<String>,
+    /// Index id of the resolved symbol; changes when the index is rebuilt
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol_id: Option<u32>,
+    /// Id derived from the symbol's name, file and kind, stable across rebuilds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stable_id: Option<String>,
     pub references: Vec<ReferenceInfo>,
     pub total_refs: usize,
     pub by_kind: std::collections::HashMap<String, usize>,
+    /// Reported references split into `exact` (AST-resolved) and `heuristic` (name-only)
+    pub by_confidence: std::collections::HashMap<String, usize>,
     pub by_file: std::collections::HashMap<String, usize>,
+    /// Reference counts rolled up by directory (`--group-by directory`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub by_directory: Vec<DirectoryGroup>,
+    /// Content hash of every file with references, keyed like `by_file`
+    /// (`--with-hashes`)
+    #[serde(skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub source_hashes: std::collections::HashMap<String, String>,
+    /// Indexed files that could not be read at query time
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// References under one directory, with per-file counts
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DirectoryGroup {
+    pub directory: String,
+    pub count: usize,
+    pub files: Vec<FileCount>,
+}
+
+/// Number of references in one file
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FileCount {
+    pub file: String,
+    pub count: usize,
+    /// Content hash of the file (`--with-hashes`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_hash: Option<String>,
 }
 
 /// Result of dead code analysis
@@ -246,6 +338,159 @@ pub struct DeadCodeResult {
     pub by_file: std::collections::HashMap<String, usize>,
 }
 
+/// A deprecated symbol and how often it is still used
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DeprecatedSymbol {
+    pub name: String,
+    pub kind: String,
+    pub file: String,
+    pub line: u32,
+    /// Number of references that should be migrated
+    pub usages: usize,
+}
+
+/// Result of deprecated symbol tracking
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DeprecatedResult {
+    pub symbols: Vec<DeprecatedSymbol>,
+    pub total_deprecated: usize,
+    pub total_usages: usize,
+}
+
+/// A symbol adjacent to the center of a neighborhood view
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct NeighborSymbol {
+    pub name: String,
+    pub kind: String,
+    pub file: String,
+    pub line: u32,
+    /// Line of the call site connecting it to the center symbol
+    pub call_line: u32,
+}
+
+/// Direct callers and callees of a symbol (depth 1)
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct NeighborhoodResult {
+    pub symbol: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub defined_at: Option<String>,
+    pub kind: String,
+    pub callers: Vec<NeighborSymbol>,
+    pub callees: Vec<NeighborSymbol>,
+}
+
+/// A file ranked by how often it is referenced and changed
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct HotspotFile {
+    pub file: String,
+    /// References to symbols defined in this file
+    pub refs: usize,
+    /// Commits touching this file (None without git history)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub churn: Option<usize>,
+    /// refs * churn, or refs alone without git history
+    pub score: usize,
+}
+
+/// Files ranked by combined reference count and git churn
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct HotspotResult {
+    pub files: Vec<HotspotFile>,
+    pub churn_available: bool,
+}
+
+/// A function parameter that is never read in the function body
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct UnusedParam {
+    pub function: String,
+    pub name: String,
+    pub file: String,
+    pub line: u32,
+    pub column: u16,
+}
+
+/// Parameters with zero reads inside their function
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct UnusedParamsResult {
+    pub params: Vec<UnusedParam>,
+    pub total: usize,
+}
+
+/// A variable declaration that shadows a binding of an enclosing scope
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ShadowedVariable {
+    pub function: String,
+    pub name: String,
+    pub file: String,
+    pub line: u32,
+    pub column: u16,
+    /// Location of the outer binding being shadowed
+    pub shadowed_line: u32,
+    pub shadowed_column: u16,
+}
+
+/// Inner declarations that shadow an outer binding of the same name
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ShadowsResult {
+    pub shadows: Vec<ShadowedVariable>,
+    pub total: usize,
+}
+
+/// A function whose body is empty or only a placeholder
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct StubSymbol {
+    pub name: String,
+    pub kind: String,
+    pub file: String,
+    pub line: u32,
+    /// The placeholder statement (`todo!()`, `pass`, ...); absent for an empty body
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
+}
+
+/// Functions that are stubs: empty or placeholder bodies
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct StubsResult {
+    pub stubs: Vec<StubSymbol>,
+    pub total: usize,
+}
+
+/// One symbol in a call hierarchy, with the symbols that call it
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CallHierarchyNode {
+    pub symbol: String,
+    pub kind: String,
+    pub file: String,
+    /// Call site for callers, definition line for the root
+    pub line: u32,
+    /// Callers were already expanded earlier in the tree
+    pub see_above: bool,
+    pub callers: Vec<CallHierarchyNode>,
+}
+
+impl CallHierarchyNode {
+    /// Depth-first walk yielding each node with its depth (roots at 0)
+    pub fn walk(roots: &[CallHierarchyNode]) -> Vec<(usize, &CallHierarchyNode)> {
+        let mut out = Vec::new();
+        let mut stack: Vec<(usize, &CallHierarchyNode)> =
+            roots.iter().rev().map(|n| (0, n)).collect();
+        while let Some((depth, node)) = stack.pop() {
+            out.push((depth, node));
+            stack.extend(node.callers.iter().rev().map(|c| (depth + 1, c)));
+        }
+        out
+    }
+}
+
+/// Incoming call tree for a symbol
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CallHierarchyResult {
+    pub symbol: String,
+    pub roots: Vec<CallHierarchyNode>,
+    pub total_nodes: usize,
+    pub max_depth: usize,
+}
+
 /// Result of data flow analysis
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct FlowResult {
@@ -271,6 +516,11 @@ pub struct PatternResult {
     pub total_matches: usize,
     pub matches: Vec<PatternMatch>,
     pub by_file: std::collections::HashMap<String, usize>,
+    /// Scanning stopped at the `--max-results` safety cap
+    pub truncated: bool,
+    /// Indexed files that could not be read at query time
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
 }
 
 /// Result of scope analysis
@@ -295,11 +545,38 @@ pub struct StatsResult {
     pub total_edges: usize,
     pub total_entry_points: usize,
     pub symbols_by_kind: std::collections::HashMap<String, usize>,
+    pub symbols_by_visibility: std::collections::HashMap<String, usize>,
     pub files_by_extension: std::collections::HashMap<String, usize>,
     pub most_referenced: Vec<(String, usize)>,
     pub largest_files: Vec<(String, usize)>,
     pub max_call_depth: usize,
     pub avg_call_depth: f32,
+    /// Entry points passing the filter, which the call depths are computed over
+    pub depth_entry_points: usize,
+    /// Symbols by last-modified date, oldest first (with `--with-dates`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub last_modified: Vec<SymbolDate>,
+}
+
+impl StatsResult {
+    /// Symbol counts by visibility, most visible first
+    pub fn visibility_counts(&self) -> Vec<(&str, usize)> {
+        ["public", "internal", "protected", "private"]
+            .into_iter()
+            .filter_map(|v| Some((v, *self.symbols_by_visibility.get(v)?)))
+            .collect()
+    }
+}
+
+/// When a symbol's definition line last changed, per git blame
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SymbolDate {
+    pub name: String,
+    pub kind: String,
+    pub file: String,
+    pub line: u32,
+    /// Commit date, `YYYY-MM-DD`
+    pub last_modified: String,
 }
 
 // =============================================================================
@@ -314,9 +591,33 @@ pub trait TraceFormatter {
     /// Format references to a symbol
     fn format_refs(&self, result: &RefsResult) -> String;
 
+    /// Format references rolled up by directory
+    fn format_refs_by_directory(&self, result: &RefsResult) -> String;
+
     /// Format dead code analysis results
     fn format_dead_code(&self, result: &DeadCodeResult) -> String;
 
+    /// Format deprecated symbol usage
+    fn format_deprecated(&self, result: &DeprecatedResult) -> String;
+
+    /// Format the caller/callee neighborhood of a symbol
+    fn format_neighborhood(&self, result: &NeighborhoodResult) -> String;
+
+    /// Format file hotspots (references x churn)
+    fn format_hotspots(&self, result: &HotspotResult) -> String;
+
+    /// Format unused function parameters
+    fn format_unused_params(&self, result: &UnusedParamsResult) -> String;
+
+    /// Format shadowed variable declarations
+    fn format_shadows(&self, result: &ShadowsResult) -> String;
+
+    /// Format stub functions
+    fn format_stubs(&self, result: &StubsResult) -> String;
+
+    /// Format an incoming call hierarchy tree
+    fn format_call_hierarchy(&self, result: &CallHierarchyResult) -> String;
+
     /// Format data flow analysis results
     fn format_flow(&self, result: &FlowResult) -> String;
 
@@ -341,14 +642,19 @@ pub trait TraceFormatter {
 // =============================================================================
 
 /// Create a formatter for the given output format
-pub fn create_formatter(format: OutputFormat) -> Box<dyn TraceFormatter> {
+pub fn create_formatter(
+    format: OutputFormat,
+    ascii: AsciiConfig,
+    markdown: MarkdownConfig,
+    csv: CsvConfig,
+) -> Box<dyn TraceFormatter> {
     match format {
-        OutputFormat::Ascii => Box::new(ascii::AsciiFormatter::new()),
+        OutputFormat::Ascii => Box::new(ascii::AsciiFormatter::new(ascii)),
         OutputFormat::Plain => Box::new(plain::PlainFormatter::new()),
         OutputFormat::Json => Box::new(json::JsonFormatter::new()),
-        OutputFormat::Csv => Box::new(plain::CsvFormatter::new()),
+        OutputFormat::Csv => Box::new(plain::CsvFormatter::with_config(csv)),
         OutputFormat::Dot => Box::new(plain::DotFormatter::new()),
-        OutputFormat::Markdown => Box::new(plain::MarkdownFormatter::new()),
+        OutputFormat::Markdown => Box::new(plain::MarkdownFormatter::with_config(markdown)),
     }
 }
 
@@ -356,6 +662,9 @@ pub fn create_formatter(format: OutputFormat) -> Box<dyn TraceFormatter> {
 // RE-EXPORTS
 // =============================================================================
 
-pub use ascii::AsciiFormatter;
-pub use json::JsonFormatter;
-pub use plain::{CsvFormatter, DotFormatter, MarkdownFormatter, PlainFormatter};
+pub use ascii::{AsciiConfig, AsciiFormatter};
+pub use json::{JsonArrayWriter, JsonFormatter};
+pub use lsp::{format_lsp_locations, lsp_locations, LspLocation, LspPosition, LspRange};
+pub use plain::{
+    CsvConfig, CsvFormatter, DotFormatter, MarkdownConfig, MarkdownFormatter, PlainFormatter,
+};
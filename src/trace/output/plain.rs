@@ -10,9 +10,13 @@
 //! @module trace/output/plain
 
 use super::{
-    DeadCodeResult, FlowResult, ImpactResult, ModuleResult, PatternResult, RefsResult, ScopeResult,
-    StatsResult, TraceFormatter, TraceResult,
+    CallHierarchyNode, CallHierarchyResult, DeadCodeResult, DeprecatedResult, FlowResult,
+    HotspotResult, ImpactResult, ModuleResult, NeighborhoodResult, PatternResult, RefsResult,
+    ScopeResult, ShadowsResult, StatsResult, StubsResult, TraceFormatter, TraceResult,
+    UnusedParamsResult,
 };
+use std::collections::BTreeMap;
+use std::path::Path;
 
 // =============================================================================
 // PLAIN TEXT FORMATTER
@@ -26,6 +30,15 @@ impl PlainFormatter {
     pub fn new() -> Self {
         Self
     }
+
+    /// Footer listing files that could not be read (empty when none)
+    fn format_warnings(&self, warnings: &[String]) -> String {
+        let mut output = String::new();
+        for warning in warnings {
+            output.push_str(&format!("warning: {}\n", warning));
+        }
+        output
+    }
 }
 
 impl Default for PlainFormatter {
@@ -87,7 +100,16 @@ impl TraceFormatter for PlainFormatter {
         if let Some(ref defined_at) = result.defined_at {
             output.push_str(&format!("Defined: {}\n", defined_at));
         }
-        output.push_str(&format!("Found: {} references\n", result.total_refs));
+        output.push_str(&format!("Found: {} references", result.total_refs));
+        if !result.by_confidence.is_empty() {
+            let count = |label: &str| result.by_confidence.get(label).copied().unwrap_or(0);
+            output.push_str(&format!(
+                " ({} exact, {} heuristic)",
+                count("exact"),
+                count("heuristic")
+            ));
+        }
+        output.push('\n');
 
         if !result.by_kind.is_empty() {
             output.push_str("By kind: ");
@@ -128,11 +150,46 @@ impl TraceFormatter for PlainFormatter {
                     if let Some(ref enclosing) = r.enclosing_symbol {
                         output.push_str(&format!(" (in {})", enclosing));
                     }
+                    if let (Some(callee), Some(index)) = (&r.callee, r.arg_index) {
+                        output.push_str(&format!(" (argument {} of {})", index + 1, callee));
+                    }
+                    if r.target_deprecated {
+                        output.push_str(" (deprecated)");
+                    }
+                    if let Some(old_name) = r.renamed_from(&result.symbol) {
+                        output.push_str(&format!(" (as {})", old_name));
+                    }
+                    if let Some(depth) = r.depth {
+                        output.push_str(&format!(" (depth {})", depth));
+                    }
                     output.push('\n');
                 }
             }
         }
 
+        output.push_str(&self.format_warnings(&result.warnings));
+        output
+    }
+
+    fn format_refs_by_directory(&self, result: &RefsResult) -> String {
+        let mut output = String::new();
+
+        output.push_str(&format!("REFS BY DIRECTORY: {}\n", result.symbol));
+        output.push_str(&format!(
+            "Found: {} references in {} directories\n",
+            result.by_file.values().sum::<usize>(),
+            result.by_directory.len()
+        ));
+        output.push_str(&"-".repeat(60));
+        output.push('\n');
+
+        for group in &result.by_directory {
+            output.push_str(&format!("\n{}/ ({} refs)\n", group.directory, group.count));
+            for file in &group.files {
+                output.push_str(&format!("  {:<5} {}\n", file.count, file.file));
+            }
+        }
+
         output
     }
 
@@ -165,6 +222,167 @@ impl TraceFormatter for PlainFormatter {
         output
     }
 
+    fn format_deprecated(&self, result: &DeprecatedResult) -> String {
+        let mut output = String::new();
+
+        output.push_str("DEPRECATED SYMBOLS\n");
+        output.push_str(&format!(
+            "Found: {} deprecated symbols, {} usages to migrate\n",
+            result.total_deprecated, result.total_usages
+        ));
+        output.push_str(&"-".repeat(60));
+        output.push('\n');
+
+        for sym in &result.symbols {
+            output.push_str(&format!(
+                "{}  {}:{}  {} - {} usages\n",
+                sym.kind, sym.file, sym.line, sym.name, sym.usages
+            ));
+        }
+
+        output
+    }
+
+    fn format_neighborhood(&self, result: &NeighborhoodResult) -> String {
+        let mut output = String::new();
+
+        output.push_str(&format!("NEIGHBORHOOD: {}\n", result.symbol));
+        if let Some(ref defined_at) = result.defined_at {
+            output.push_str(&format!("Defined at: {}\n", defined_at));
+        }
+        output.push_str(&"-".repeat(60));
+        output.push('\n');
+
+        output.push_str(&format!("\nCallers ({}):\n", result.callers.len()));
+        for caller in &result.callers {
+            output.push_str(&format!(
+                "  {}  {}:{}\n",
+                caller.name, caller.file, caller.call_line
+            ));
+        }
+
+        output.push_str(&format!("\nCallees ({}):\n", result.callees.len()));
+        for callee in &result.callees {
+            output.push_str(&format!(
+                "  {}  {}:{}\n",
+                callee.name, callee.file, callee.line
+            ));
+        }
+
+        output
+    }
+
+    fn format_hotspots(&self, result: &HotspotResult) -> String {
+        let mut output = String::new();
+
+        output.push_str("HOTSPOTS\n");
+        if !result.churn_available {
+            output.push_str("No git history: ranked by references only\n");
+        }
+        output.push_str(&"-".repeat(60));
+        output.push('\n');
+
+        for file in &result.files {
+            let churn = file.churn.map_or("-".to_string(), |c| c.to_string());
+            output.push_str(&format!(
+                "  {:>8}  refs={:<6} churn={:<6} {}\n",
+                file.score, file.refs, churn, file.file
+            ));
+        }
+
+        output
+    }
+
+    fn format_unused_params(&self, result: &UnusedParamsResult) -> String {
+        let mut output = String::new();
+
+        output.push_str("UNUSED PARAMETERS\n");
+        output.push_str(&format!("Found: {} parameters never read\n", result.total));
+        output.push_str(&"-".repeat(60));
+        output.push('\n');
+
+        for param in &result.params {
+            output.push_str(&format!(
+                "  {} in {}  {}:{}\n",
+                param.name, param.function, param.file, param.line
+            ));
+        }
+
+        output
+    }
+
+    fn format_shadows(&self, result: &ShadowsResult) -> String {
+        let mut output = String::new();
+
+        output.push_str("SHADOWED VARIABLES\n");
+        output.push_str(&format!(
+            "Found: {} declarations shadowing an outer binding\n",
+            result.total
+        ));
+        output.push_str(&"-".repeat(60));
+        output.push('\n');
+
+        for shadow in &result.shadows {
+            output.push_str(&format!(
+                "  {} in {}  {}:{} shadows line {}\n",
+                shadow.name, shadow.function, shadow.file, shadow.line, shadow.shadowed_line
+            ));
+        }
+
+        output
+    }
+
+    fn format_stubs(&self, result: &StubsResult) -> String {
+        let mut output = String::new();
+
+        output.push_str("STUB FUNCTIONS\n");
+        output.push_str(&format!(
+            "Found: {} functions with empty or placeholder bodies\n",
+            result.total
+        ));
+        output.push_str(&"-".repeat(60));
+        output.push('\n');
+
+        for stub in &result.stubs {
+            output.push_str(&format!(
+                "  {} ({})  {}:{}  {}\n",
+                stub.name,
+                stub.kind,
+                stub.file,
+                stub.line,
+                stub.placeholder.as_deref().unwrap_or("empty")
+            ));
+        }
+
+        output
+    }
+
+    fn format_call_hierarchy(&self, result: &CallHierarchyResult) -> String {
+        let mut output = String::new();
+
+        output.push_str(&format!("CALL HIERARCHY: {}\n", result.symbol));
+        output.push_str(&format!(
+            "Found: {} callers (max depth {})\n",
+            result.total_nodes.saturating_sub(result.roots.len()),
+            result.max_depth
+        ));
+        output.push_str(&"-".repeat(60));
+        output.push('\n');
+
+        for (depth, node) in CallHierarchyNode::walk(&result.roots) {
+            output.push_str(&format!(
+                "{}{}  {}:{}{}\n",
+                "  ".repeat(depth + 1),
+                node.symbol,
+                node.file,
+                node.line,
+                if node.see_above { "  (see above)" } else { "" }
+            ));
+        }
+
+        output
+    }
+
     fn format_flow(&self, result: &FlowResult) -> String {
         let mut output = String::new();
 
@@ -196,6 +414,10 @@ impl TraceFormatter for PlainFormatter {
         output.push_str(&format!("IMPACT ANALYSIS: {}\n", result.symbol));
         output.push_str(&format!("File: {}\n", result.file));
         output.push_str(&format!("Risk Level: {}\n", result.risk_level));
+        output.push_str(&format!("Risk Score: {}\n", result.risk_score));
+        for factor in &result.risk_factors {
+            output.push_str(&format!("  - {}\n", factor));
+        }
         output.push_str(&"-".repeat(60));
         output.push('\n');
 
@@ -225,6 +447,13 @@ impl TraceFormatter for PlainFormatter {
             output.push_str(&format!("  {}\n", ep));
         }
 
+        if !result.paths_to_entry.is_empty() {
+            output.push_str("\nPaths to entry points:\n");
+            for path in &result.paths_to_entry {
+                output.push_str(&format!("  {} -> {}\n", result.symbol, path.join(" -> ")));
+            }
+        }
+
         output.push_str(&format!(
             "\nFiles affected: {}\n",
             result.files_affected.len()
@@ -316,6 +545,13 @@ impl TraceFormatter for PlainFormatter {
             }
         }
 
+        if result.truncated {
+            output.push_str(&format!(
+                "Results truncated at {} matches (raise --max-results to see more)\n",
+                result.total_matches
+            ));
+        }
+        output.push_str(&self.format_warnings(&result.warnings));
         output
     }
 
@@ -337,7 +573,9 @@ impl TraceFormatter for PlainFormatter {
             for var in &result.local_variables {
                 output.push_str(&format!(
                     "  {}: {} (line {})\n",
-                    var.name, var.kind, var.defined_at
+                    var.name,
+                    var.declared_type.as_deref().unwrap_or(&var.kind),
+                    var.defined_at
                 ));
             }
         }
@@ -380,9 +618,30 @@ impl TraceFormatter for PlainFormatter {
             output.push_str(&format!("  {}: {}\n", kind, count));
         }
 
+        if !result.symbols_by_visibility.is_empty() {
+            output.push_str("\nSymbols by visibility:\n");
+            for (visibility, count) in result.visibility_counts() {
+                output.push_str(&format!("  {}: {}\n", visibility, count));
+            }
+        }
+
         output.push_str("\nCall Graph:\n");
         output.push_str(&format!("  Max Depth: {}\n", result.max_call_depth));
         output.push_str(&format!("  Avg Depth: {:.1}\n", result.avg_call_depth));
+        output.push_str(&format!(
+            "  Entry Points Used: {}\n",
+            result.depth_entry_points
+        ));
+
+        if !result.last_modified.is_empty() {
+            output.push_str("\nLeast recently modified:\n");
+            for s in result.last_modified.iter().take(10) {
+                output.push_str(&format!(
+                    "  {} {} {}:{}\n",
+                    s.last_modified, s.name, s.file, s.line
+                ));
+            }
+        }
 
         output
     }
@@ -392,15 +651,38 @@ impl TraceFormatter for PlainFormatter {
 // CSV FORMATTER
 // =============================================================================
 
+/// Configuration for the CSV formatter
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CsvConfig {
+    /// Omit the header row, so outputs of several runs can be concatenated
+    pub no_header: bool,
+}
+
 /// CSV formatter for spreadsheet export
-pub struct CsvFormatter;
+pub struct CsvFormatter {
+    config: CsvConfig,
+}
 
 impl CsvFormatter {
     pub fn new() -> Self {
-        Self
+        Self::with_config(CsvConfig::default())
+    }
+
+    pub fn with_config(config: CsvConfig) -> Self {
+        Self { config }
+    }
+
+    /// Start the output with the header row, unless disabled
+    fn header(&self, columns: &str) -> String {
+        if self.config.no_header {
+            String::new()
+        } else {
+            format!("{}\n", columns)
+        }
     }
 
-    fn escape_csv(s: &str) -> String {
+    /// Quote a CSV field when it contains a comma, quote or newline
+    pub fn escape_csv(s: &str) -> String {
         if s.contains(',') || s.contains('"') || s.contains('\n') {
             format!("\"{}\"", s.replace('"', "\"\""))
         } else {
@@ -417,7 +699,7 @@ impl Default for CsvFormatter {
 
 impl TraceFormatter for CsvFormatter {
     fn format_trace(&self, result: &TraceResult) -> String {
-        let mut output = String::from("path_num,entry_point,entry_kind,step,symbol,file,line\n");
+        let mut output = self.header("path_num,entry_point,entry_kind,step,symbol,file,line");
 
         for (i, path) in result.invocation_paths.iter().enumerate() {
             for (j, step) in path.chain.iter().enumerate() {
@@ -438,7 +720,7 @@ impl TraceFormatter for CsvFormatter {
     }
 
     fn format_refs(&self, result: &RefsResult) -> String {
-        let mut output = String::from("file,line,column,kind,context,enclosing_symbol\n");
+        let mut output = self.header("file,line,column,kind,context,enclosing_symbol");
 
         for r in &result.references {
             let context_single = r.context.lines().next().unwrap_or("").trim();
@@ -456,8 +738,25 @@ impl TraceFormatter for CsvFormatter {
         output
     }
 
+    fn format_refs_by_directory(&self, result: &RefsResult) -> String {
+        let mut output = self.header("directory,file,count");
+
+        for group in &result.by_directory {
+            for file in &group.files {
+                output.push_str(&format!(
+                    "{},{},{}\n",
+                    Self::escape_csv(&group.directory),
+                    Self::escape_csv(&file.file),
+                    file.count
+                ));
+            }
+        }
+
+        output
+    }
+
     fn format_dead_code(&self, result: &DeadCodeResult) -> String {
-        let mut output = String::from("name,kind,file,line,reason\n");
+        let mut output = self.header("name,kind,file,line,reason");
 
         for sym in &result.symbols {
             output.push_str(&format!(
@@ -473,8 +772,133 @@ impl TraceFormatter for CsvFormatter {
         output
     }
 
+    fn format_deprecated(&self, result: &DeprecatedResult) -> String {
+        let mut output = self.header("name,kind,file,line,usages");
+
+        for sym in &result.symbols {
+            output.push_str(&format!(
+                "{},{},{},{},{}\n",
+                Self::escape_csv(&sym.name),
+                Self::escape_csv(&sym.kind),
+                Self::escape_csv(&sym.file),
+                sym.line,
+                sym.usages
+            ));
+        }
+
+        output
+    }
+
+    fn format_neighborhood(&self, result: &NeighborhoodResult) -> String {
+        let mut output = self.header("direction,name,kind,file,line,call_line");
+
+        for (direction, symbols) in [("caller", &result.callers), ("callee", &result.callees)] {
+            for sym in symbols {
+                output.push_str(&format!(
+                    "{},{},{},{},{},{}\n",
+                    direction,
+                    Self::escape_csv(&sym.name),
+                    Self::escape_csv(&sym.kind),
+                    Self::escape_csv(&sym.file),
+                    sym.line,
+                    sym.call_line
+                ));
+            }
+        }
+
+        output
+    }
+
+    fn format_hotspots(&self, result: &HotspotResult) -> String {
+        let mut output = self.header("file,refs,churn,score");
+
+        for file in &result.files {
+            output.push_str(&format!(
+                "{},{},{},{}\n",
+                Self::escape_csv(&file.file),
+                file.refs,
+                file.churn.map_or(String::new(), |c| c.to_string()),
+                file.score
+            ));
+        }
+
+        output
+    }
+
+    fn format_unused_params(&self, result: &UnusedParamsResult) -> String {
+        let mut output = self.header("function,parameter,file,line,column");
+
+        for param in &result.params {
+            output.push_str(&format!(
+                "{},{},{},{},{}\n",
+                Self::escape_csv(&param.function),
+                Self::escape_csv(&param.name),
+                Self::escape_csv(&param.file),
+                param.line,
+                param.column
+            ));
+        }
+
+        output
+    }
+
+    fn format_shadows(&self, result: &ShadowsResult) -> String {
+        let mut output =
+            self.header("function,variable,file,line,column,shadowed_line,shadowed_column");
+
+        for shadow in &result.shadows {
+            output.push_str(&format!(
+                "{},{},{},{},{},{},{}\n",
+                Self::escape_csv(&shadow.function),
+                Self::escape_csv(&shadow.name),
+                Self::escape_csv(&shadow.file),
+                shadow.line,
+                shadow.column,
+                shadow.shadowed_line,
+                shadow.shadowed_column
+            ));
+        }
+
+        output
+    }
+
+    fn format_stubs(&self, result: &StubsResult) -> String {
+        let mut output = self.header("name,kind,file,line,placeholder");
+
+        for stub in &result.stubs {
+            output.push_str(&format!(
+                "{},{},{},{},{}\n",
+                Self::escape_csv(&stub.name),
+                Self::escape_csv(&stub.kind),
+                Self::escape_csv(&stub.file),
+                stub.line,
+                Self::escape_csv(stub.placeholder.as_deref().unwrap_or(""))
+            ));
+        }
+
+        output
+    }
+
+    fn format_call_hierarchy(&self, result: &CallHierarchyResult) -> String {
+        let mut output = self.header("depth,symbol,kind,file,line,see_above");
+
+        for (depth, node) in CallHierarchyNode::walk(&result.roots) {
+            output.push_str(&format!(
+                "{},{},{},{},{},{}\n",
+                depth,
+                Self::escape_csv(&node.symbol),
+                Self::escape_csv(&node.kind),
+                Self::escape_csv(&node.file),
+                node.line,
+                node.see_above
+            ));
+        }
+
+        output
+    }
+
     fn format_flow(&self, result: &FlowResult) -> String {
-        let mut output = String::from("path,step,variable,action,file,line,expression\n");
+        let mut output = self.header("path,step,variable,action,file,line,expression");
 
         for (i, path) in result.flow_paths.iter().enumerate() {
             for (j, step) in path.iter().enumerate() {
@@ -495,11 +919,15 @@ impl TraceFormatter for CsvFormatter {
     }
 
     fn format_impact(&self, result: &ImpactResult) -> String {
-        let mut output = String::from("type,value\n");
+        let mut output = self.header("type,value");
 
         output.push_str(&format!("symbol,{}\n", Self::escape_csv(&result.symbol)));
         output.push_str(&format!("file,{}\n", Self::escape_csv(&result.file)));
         output.push_str(&format!("risk_level,{}\n", result.risk_level));
+        output.push_str(&format!("risk_score,{}\n", result.risk_score));
+        for factor in &result.risk_factors {
+            output.push_str(&format!("risk_factor,{}\n", Self::escape_csv(factor)));
+        }
         output.push_str(&format!(
             "direct_caller_count,{}\n",
             result.direct_caller_count
@@ -518,7 +946,7 @@ impl TraceFormatter for CsvFormatter {
     }
 
     fn format_module(&self, result: &ModuleResult) -> String {
-        let mut output = String::from("type,value\n");
+        let mut output = self.header("type,value");
 
         output.push_str(&format!("module,{}\n", Self::escape_csv(&result.module)));
         output.push_str(&format!("path,{}\n", Self::escape_csv(&result.file_path)));
@@ -531,7 +959,7 @@ impl TraceFormatter for CsvFormatter {
     }
 
     fn format_pattern(&self, result: &PatternResult) -> String {
-        let mut output = String::from("file,line,column,matched_text,context\n");
+        let mut output = self.header("file,line,column,matched_text,context");
 
         for m in &result.matches {
             let context_single = m.context.lines().next().unwrap_or("").trim();
@@ -549,7 +977,7 @@ impl TraceFormatter for CsvFormatter {
     }
 
     fn format_scope(&self, result: &ScopeResult) -> String {
-        let mut output = String::from("type,name,kind,defined_at\n");
+        let mut output = self.header("type,name,kind,defined_at");
 
         for var in &result.local_variables {
             output.push_str(&format!(
@@ -576,7 +1004,7 @@ impl TraceFormatter for CsvFormatter {
     }
 
     fn format_stats(&self, result: &StatsResult) -> String {
-        let mut output = String::from("metric,value\n");
+        let mut output = self.header("metric,value");
 
         output.push_str(&format!("total_files,{}\n", result.total_files));
         output.push_str(&format!("total_symbols,{}\n", result.total_symbols));
@@ -589,6 +1017,10 @@ impl TraceFormatter for CsvFormatter {
         ));
         output.push_str(&format!("max_call_depth,{}\n", result.max_call_depth));
         output.push_str(&format!("avg_call_depth,{:.2}\n", result.avg_call_depth));
+        output.push_str(&format!(
+            "depth_entry_points,{}\n",
+            result.depth_entry_points
+        ));
 
         output
     }
@@ -606,9 +1038,41 @@ impl DotFormatter {
         Self
     }
 
-    fn escape_dot(s: &str) -> String {
+    /// Escape a string for a quoted DOT identifier or label
+    pub fn escape_dot(s: &str) -> String {
         s.replace('"', "\\\"").replace('\n', "\\n")
     }
+
+    /// Node statements grouped into one `subgraph cluster_N` per directory
+    ///
+    /// Each statement is paired with the file its node lives in. Files at
+    /// the project root share the `.` cluster; clusters are ordered by
+    /// directory so the output is stable.
+    pub fn cluster_by_directory(nodes: &[(String, String)]) -> String {
+        let mut clusters: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+        for (file, statement) in nodes {
+            let directory = Path::new(file)
+                .parent()
+                .map(|p| p.display().to_string())
+                .filter(|d| !d.is_empty())
+                .unwrap_or_else(|| ".".to_string());
+            clusters.entry(directory).or_default().push(statement);
+        }
+
+        let mut output = String::new();
+        for (i, (directory, statements)) in clusters.iter().enumerate() {
+            output.push_str(&format!(
+                "  subgraph cluster_{} {{\n    label=\"{}\";\n",
+                i,
+                Self::escape_dot(directory)
+            ));
+            for statement in statements {
+                output.push_str(&format!("    {}\n", statement));
+            }
+            output.push_str("  }\n");
+        }
+        output
+    }
 }
 
 impl Default for DotFormatter {
@@ -623,35 +1087,45 @@ impl TraceFormatter for DotFormatter {
         output.push_str("  rankdir=LR;\n");
         output.push_str("  node [shape=box];\n\n");
 
-        let mut nodes = std::collections::HashSet::new();
+        let mut seen = std::collections::HashSet::new();
+        let mut nodes = Vec::new();
         let mut edges = Vec::new();
 
         for path in &result.invocation_paths {
             for (i, step) in path.chain.iter().enumerate() {
                 let node_id = format!("{}_{}", step.symbol.replace(['.', '/'], "_"), step.line);
-                if !nodes.contains(&node_id) {
-                    nodes.insert(node_id.clone());
-                    output.push_str(&format!(
-                        "  {} [label=\"{}\\n{}:{}\"];\n",
-                        node_id,
-                        Self::escape_dot(&step.symbol),
-                        Self::escape_dot(&step.file),
-                        step.line
+                if seen.insert(node_id.clone()) {
+                    nodes.push((
+                        step.file.clone(),
+                        format!(
+                            "{} [label=\"{}\\n{}:{}\"];",
+                            node_id,
+                            Self::escape_dot(&step.symbol),
+                            Self::escape_dot(&step.file),
+                            step.line
+                        ),
                     ));
                 }
 
                 if i > 0 {
                     let prev = &path.chain[i - 1];
                     let prev_id = format!("{}_{}", prev.symbol.replace(['.', '/'], "_"), prev.line);
-                    let edge = (prev_id.clone(), node_id.clone());
+                    let edge = (prev_id, node_id.clone());
                     if !edges.contains(&edge) {
-                        edges.push(edge.clone());
-                        output.push_str(&format!("  {} -> {};\n", edge.0, edge.1));
+                        edges.push(edge);
                     }
                 }
             }
         }
 
+        output.push_str(&Self::cluster_by_directory(&nodes));
+        if !edges.is_empty() {
+            output.push('\n');
+        }
+        for (from, to) in &edges {
+            output.push_str(&format!("  {} -> {};\n", from, to));
+        }
+
         output.push_str("}\n");
         output
     }
@@ -682,14 +1156,36 @@ impl TraceFormatter for DotFormatter {
         output
     }
 
-    fn format_dead_code(&self, result: &DeadCodeResult) -> String {
-        let mut output = String::from("digraph dead_code {\n");
-        output.push_str("  node [shape=box style=filled fillcolor=lightgray];\n");
-
-        for (i, sym) in result.symbols.iter().enumerate() {
-            output.push_str(&format!(
-                "  dead_{} [label=\"{}\\n{}:{}\"];\n",
-                i,
+    fn format_refs_by_directory(&self, result: &RefsResult) -> String {
+        let mut output = String::from("digraph refs {\n");
+        output.push_str("  rankdir=LR;\n");
+        output.push_str(&format!(
+            "  center [label=\"{}\" shape=ellipse style=filled fillcolor=yellow];\n",
+            Self::escape_dot(&result.symbol)
+        ));
+
+        for (i, group) in result.by_directory.iter().enumerate() {
+            output.push_str(&format!(
+                "  dir_{} [label=\"{}/\\n{} refs\" shape=folder];\n",
+                i,
+                Self::escape_dot(&group.directory),
+                group.count
+            ));
+            output.push_str(&format!("  dir_{} -> center;\n", i));
+        }
+
+        output.push_str("}\n");
+        output
+    }
+
+    fn format_dead_code(&self, result: &DeadCodeResult) -> String {
+        let mut output = String::from("digraph dead_code {\n");
+        output.push_str("  node [shape=box style=filled fillcolor=lightgray];\n");
+
+        for (i, sym) in result.symbols.iter().enumerate() {
+            output.push_str(&format!(
+                "  dead_{} [label=\"{}\\n{}:{}\"];\n",
+                i,
                 Self::escape_dot(&sym.name),
                 Self::escape_dot(&sym.file),
                 sym.line
@@ -700,6 +1196,160 @@ impl TraceFormatter for DotFormatter {
         output
     }
 
+    fn format_deprecated(&self, result: &DeprecatedResult) -> String {
+        let mut output = String::from("digraph deprecated {\n");
+        output.push_str("  node [shape=box style=filled fillcolor=khaki];\n");
+
+        for (i, sym) in result.symbols.iter().enumerate() {
+            output.push_str(&format!(
+                "  deprecated_{} [label=\"{}\\n{}:{}\\n{} usages\"];\n",
+                i,
+                Self::escape_dot(&sym.name),
+                Self::escape_dot(&sym.file),
+                sym.line,
+                sym.usages
+            ));
+        }
+
+        output.push_str("}\n");
+        output
+    }
+
+    fn format_neighborhood(&self, result: &NeighborhoodResult) -> String {
+        let mut output = String::from("digraph neighborhood {\n");
+        output.push_str("  rankdir=TB;\n");
+        output.push_str(&format!(
+            "  center [label=\"{}\" style=filled fillcolor=lightgreen];\n",
+            Self::escape_dot(&result.symbol)
+        ));
+
+        for (i, caller) in result.callers.iter().enumerate() {
+            output.push_str(&format!(
+                "  caller_{} [label=\"{}\"];\n  caller_{} -> center;\n",
+                i,
+                Self::escape_dot(&caller.name),
+                i
+            ));
+        }
+        for (i, callee) in result.callees.iter().enumerate() {
+            output.push_str(&format!(
+                "  callee_{} [label=\"{}\"];\n  center -> callee_{};\n",
+                i,
+                Self::escape_dot(&callee.name),
+                i
+            ));
+        }
+
+        output.push_str("}\n");
+        output
+    }
+
+    fn format_hotspots(&self, result: &HotspotResult) -> String {
+        let mut output = String::from("digraph hotspots {\n");
+        output.push_str("  node [shape=box];\n");
+
+        for (i, file) in result.files.iter().enumerate() {
+            output.push_str(&format!(
+                "  hotspot_{} [label=\"{}\\nscore {}\"];\n",
+                i,
+                Self::escape_dot(&file.file),
+                file.score
+            ));
+        }
+
+        output.push_str("}\n");
+        output
+    }
+
+    fn format_unused_params(&self, result: &UnusedParamsResult) -> String {
+        let mut output = String::from("digraph unused_params {\n");
+        output.push_str("  node [shape=box];\n");
+
+        for (i, param) in result.params.iter().enumerate() {
+            output.push_str(&format!(
+                "  param_{} [label=\"{}({})\", color=orange];\n",
+                i,
+                Self::escape_dot(&param.function),
+                Self::escape_dot(&param.name)
+            ));
+        }
+
+        output.push_str("}\n");
+        output
+    }
+
+    fn format_shadows(&self, result: &ShadowsResult) -> String {
+        let mut output = String::from("digraph shadows {\n");
+        output.push_str("  node [shape=box];\n");
+
+        for (i, shadow) in result.shadows.iter().enumerate() {
+            output.push_str(&format!(
+                "  outer_{} [label=\"{}:{}\"];\n",
+                i,
+                Self::escape_dot(&shadow.name),
+                shadow.shadowed_line
+            ));
+            output.push_str(&format!(
+                "  inner_{} [label=\"{}:{}\", color=orange];\n",
+                i,
+                Self::escape_dot(&shadow.name),
+                shadow.line
+            ));
+            output.push_str(&format!(
+                "  inner_{} -> outer_{} [label=\"shadows\"];\n",
+                i, i
+            ));
+        }
+
+        output.push_str("}\n");
+        output
+    }
+
+    fn format_stubs(&self, result: &StubsResult) -> String {
+        let mut output = String::from("digraph stubs {\n");
+        output.push_str("  node [shape=box];\n");
+
+        for (i, stub) in result.stubs.iter().enumerate() {
+            output.push_str(&format!(
+                "  stub_{} [label=\"{}\", color=orange];\n",
+                i,
+                Self::escape_dot(&stub.name)
+            ));
+        }
+
+        output.push_str("}\n");
+        output
+    }
+
+    fn format_call_hierarchy(&self, result: &CallHierarchyResult) -> String {
+        let mut output = String::from("digraph call_hierarchy {\n");
+        output.push_str("  rankdir=LR;\n");
+        output.push_str("  node [shape=box];\n\n");
+
+        for root in &result.roots {
+            output.push_str(&format!(
+                "  \"{}\" [style=filled fillcolor=yellow];\n",
+                Self::escape_dot(&root.symbol)
+            ));
+        }
+
+        let mut edges = std::collections::HashSet::new();
+        for (_, node) in CallHierarchyNode::walk(&result.roots) {
+            for caller in &node.callers {
+                if edges.insert((&caller.symbol, &node.symbol)) {
+                    output.push_str(&format!(
+                        "  \"{}\" -> \"{}\";\n",
+                        Self::escape_dot(&caller.symbol),
+                        Self::escape_dot(&node.symbol)
+                    ));
+                }
+            }
+        }
+
+        output.push_str("}\n");
+        output
+    }
+
     fn format_flow(&self, result: &FlowResult) -> String {
         let mut output = String::from("digraph flow {\n");
         output.push_str("  rankdir=TB;\n");
@@ -795,12 +1445,25 @@ impl TraceFormatter for DotFormatter {
 // MARKDOWN FORMATTER
 // =============================================================================
 
+/// Configuration for the Markdown formatter
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MarkdownConfig {
+    /// Emit only the references table, without the title and summary lines
+    pub table_only: bool,
+}
+
 /// Markdown formatter for documentation
-pub struct MarkdownFormatter;
+pub struct MarkdownFormatter {
+    config: MarkdownConfig,
+}
 
 impl MarkdownFormatter {
     pub fn new() -> Self {
-        Self
+        Self::with_config(MarkdownConfig::default())
+    }
+
+    pub fn with_config(config: MarkdownConfig) -> Self {
+        Self { config }
     }
 }
 
@@ -853,38 +1516,76 @@ impl TraceFormatter for MarkdownFormatter {
     fn format_refs(&self, result: &RefsResult) -> String {
         let mut output = String::new();
 
-        output.push_str(&format!("# References: {}\n\n", result.symbol));
+        if !self.config.table_only {
+            output.push_str(&format!("# References: {}\n\n", result.symbol));
 
-        if let Some(ref defined_at) = result.defined_at {
-            output.push_str(&format!("**Defined at:** `{}`\n\n", defined_at));
-        }
+            if let Some(ref defined_at) = result.defined_at {
+                output.push_str(&format!("**Defined at:** `{}`\n\n", defined_at));
+            }
 
-        output.push_str(&format!("**Total:** {} references\n\n", result.total_refs));
+            output.push_str(&format!("**Total:** {} references\n\n", result.total_refs));
 
-        if !result.by_kind.is_empty() {
-            output.push_str("### By Kind\n\n");
-            for (kind, count) in &result.by_kind {
-                output.push_str(&format!("- **{}:** {}\n", kind, count));
+            if !result.by_kind.is_empty() {
+                output.push_str("### By Kind\n\n");
+                for (kind, count) in &result.by_kind {
+                    output.push_str(&format!("- **{}:** {}\n", kind, count));
+                }
+                output.push('\n');
             }
-            output.push('\n');
-        }
 
-        output.push_str("### References\n\n");
+            output.push_str("### References\n\n");
+        }
         output.push_str("| File | Line | Kind | Context |\n");
         output.push_str("|------|------|------|----------|\n");
 
         for r in &result.references {
             let context_short = r.context.lines().next().unwrap_or("").trim();
             let context_escaped = context_short.replace('|', "\\|");
+            let deprecated = if r.target_deprecated {
+                " (deprecated)"
+            } else {
+                ""
+            };
+            let renamed = r
+                .renamed_from(&result.symbol)
+                .map(|name| format!(" (as `{}`)", name))
+                .unwrap_or_default();
             output.push_str(&format!(
-                "| `{}` | {} | {} | `{}` |\n",
-                r.file, r.line, r.kind, context_escaped
+                "| `{}` | {} | {}{}{} | `{}` |\n",
+                r.file, r.line, r.kind, deprecated, renamed, context_escaped
             ));
         }
 
         output
     }
 
+    fn format_refs_by_directory(&self, result: &RefsResult) -> String {
+        let mut output = String::new();
+
+        if !self.config.table_only {
+            output.push_str(&format!("# References by Directory: {}\n\n", result.symbol));
+            output.push_str(&format!(
+                "**Total:** {} references in {} directories\n\n",
+                result.by_file.values().sum::<usize>(),
+                result.by_directory.len()
+            ));
+        }
+        output.push_str("| Directory | File | Refs |\n");
+        output.push_str("|-----------|------|------|\n");
+
+        for group in &result.by_directory {
+            output.push_str(&format!(
+                "| `{}/` | | **{}** |\n",
+                group.directory, group.count
+            ));
+            for file in &group.files {
+                output.push_str(&format!("| | `{}` | {} |\n", file.file, file.count));
+            }
+        }
+
+        output
+    }
+
     fn format_dead_code(&self, result: &DeadCodeResult) -> String {
         let mut output = String::new();
 
@@ -916,6 +1617,152 @@ impl TraceFormatter for MarkdownFormatter {
         output
     }
 
+    fn format_deprecated(&self, result: &DeprecatedResult) -> String {
+        let mut output = String::new();
+
+        output.push_str("# Deprecated Symbols\n\n");
+        output.push_str(&format!(
+            "**Found:** {} deprecated symbols, {} usages to migrate\n\n",
+            result.total_deprecated, result.total_usages
+        ));
+
+        output.push_str("| Name | Kind | File | Line | Usages |\n");
+        output.push_str("|------|------|------|------|--------|\n");
+
+        for sym in &result.symbols {
+            output.push_str(&format!(
+                "| `{}` | {} | `{}` | {} | {} |\n",
+                sym.name, sym.kind, sym.file, sym.line, sym.usages
+            ));
+        }
+
+        output
+    }
+
+    fn format_neighborhood(&self, result: &NeighborhoodResult) -> String {
+        let mut output = String::new();
+
+        output.push_str(&format!("# Neighborhood: `{}`\n\n", result.symbol));
+        if let Some(ref defined_at) = result.defined_at {
+            output.push_str(&format!("**Defined at:** `{}`\n\n", defined_at));
+        }
+
+        output.push_str(&format!("## Callers ({})\n\n", result.callers.len()));
+        for caller in &result.callers {
+            output.push_str(&format!(
+                "- `{}` at `{}:{}`\n",
+                caller.name, caller.file, caller.call_line
+            ));
+        }
+
+        output.push_str(&format!("\n## Callees ({})\n\n", result.callees.len()));
+        for callee in &result.callees {
+            output.push_str(&format!(
+                "- `{}` at `{}:{}`\n",
+                callee.name, callee.file, callee.line
+            ));
+        }
+
+        output
+    }
+
+    fn format_hotspots(&self, result: &HotspotResult) -> String {
+        let mut output = String::from("# Hotspots\n\n");
+
+        if !result.churn_available {
+            output.push_str("_No git history: ranked by references only._\n\n");
+        }
+
+        output.push_str("| File | Refs | Churn | Score |\n");
+        output.push_str("|------|------|-------|-------|\n");
+        for file in &result.files {
+            output.push_str(&format!(
+                "| `{}` | {} | {} | {} |\n",
+                file.file,
+                file.refs,
+                file.churn.map_or("-".to_string(), |c| c.to_string()),
+                file.score
+            ));
+        }
+
+        output
+    }
+
+    fn format_unused_params(&self, result: &UnusedParamsResult) -> String {
+        let mut output = String::from("# Unused Parameters\n\n");
+        output.push_str(&format!("**Found:** {} parameters\n\n", result.total));
+
+        output.push_str("| Function | Parameter | Location |\n");
+        output.push_str("|----------|-----------|----------|\n");
+        for param in &result.params {
+            output.push_str(&format!(
+                "| `{}` | `{}` | `{}:{}` |\n",
+                param.function, param.name, param.file, param.line
+            ));
+        }
+
+        output
+    }
+
+    fn format_shadows(&self, result: &ShadowsResult) -> String {
+        let mut output = String::from("# Shadowed Variables\n\n");
+        output.push_str(&format!("**Found:** {} declarations\n\n", result.total));
+
+        output.push_str("| Function | Variable | Location | Shadows |\n");
+        output.push_str("|----------|----------|----------|---------|\n");
+        for shadow in &result.shadows {
+            output.push_str(&format!(
+                "| `{}` | `{}` | `{}:{}` | line {} |\n",
+                shadow.function, shadow.name, shadow.file, shadow.line, shadow.shadowed_line
+            ));
+        }
+
+        output
+    }
+
+    fn format_stubs(&self, result: &StubsResult) -> String {
+        let mut output = String::from("# Stub Functions\n\n");
+        output.push_str(&format!("**Found:** {} functions\n\n", result.total));
+
+        output.push_str("| Function | Location | Body |\n");
+        output.push_str("|----------|----------|------|\n");
+        for stub in &result.stubs {
+            output.push_str(&format!(
+                "| `{}` | `{}:{}` | {} |\n",
+                stub.name,
+                stub.file,
+                stub.line,
+                stub.placeholder
+                    .as_deref()
+                    .map_or("empty".to_string(), |p| format!("`{}`", p))
+            ));
+        }
+
+        output
+    }
+
+    fn format_call_hierarchy(&self, result: &CallHierarchyResult) -> String {
+        let mut output = format!("# Call Hierarchy: `{}`\n\n", result.symbol);
+        output.push_str(&format!(
+            "**Callers:** {} (max depth {})\n\n",
+            result.total_nodes.saturating_sub(result.roots.len()),
+            result.max_depth
+        ));
+
+        for (depth, node) in CallHierarchyNode::walk(&result.roots) {
+            output.push_str(&format!(
+                "{}- `{}` ({}:{}){}\n",
+                "  ".repeat(depth),
+                node.symbol,
+                node.file,
+                node.line,
+                if node.see_above { " *(see above)*" } else { "" }
+            ));
+        }
+
+        output
+    }
+
     fn format_flow(&self, result: &FlowResult) -> String {
         let mut output = String::new();
 
@@ -944,7 +1791,16 @@ impl TraceFormatter for MarkdownFormatter {
 
         output.push_str(&format!("# Impact Analysis: {}\n\n", result.symbol));
         output.push_str(&format!("**File:** `{}`\n\n", result.file));
-        output.push_str(&format!("**Risk Level:** {}\n\n", result.risk_level));
+        output.push_str(&format!(
+            "**Risk Level:** {} (score {})\n\n",
+            result.risk_level, result.risk_score
+        ));
+        if !result.risk_factors.is_empty() {
+            for factor in &result.risk_factors {
+                output.push_str(&format!("- {}\n", factor));
+            }
+            output.push('\n');
+        }
 
         output.push_str(&format!(
             "## Direct Callers ({})\n\n",
@@ -974,6 +1830,13 @@ impl TraceFormatter for MarkdownFormatter {
             output.push_str(&format!("- `{}`\n", ep));
         }
 
+        if !result.paths_to_entry.is_empty() {
+            output.push_str("\n## Paths to Entry Points\n\n");
+            for path in &result.paths_to_entry {
+                output.push_str(&format!("- `{} -> {}`\n", result.symbol, path.join(" -> ")));
+            }
+        }
+
         output
     }
 
@@ -1048,6 +1911,13 @@ impl TraceFormatter for MarkdownFormatter {
             ));
         }
 
+        if result.truncated {
+            output.push_str(&format!(
+                "\n> Results truncated at {} matches (raise `--max-results` to see more)\n",
+                result.total_matches
+            ));
+        }
+
         output
     }
 
@@ -1070,7 +1940,9 @@ impl TraceFormatter for MarkdownFormatter {
             for var in &result.local_variables {
                 output.push_str(&format!(
                     "| `{}` | {} | line {} |\n",
-                    var.name, var.kind, var.defined_at
+                    var.name,
+                    var.declared_type.as_deref().unwrap_or(&var.kind),
+                    var.defined_at
                 ));
             }
             output.push('\n');
@@ -1115,9 +1987,35 @@ impl TraceFormatter for MarkdownFormatter {
         }
         output.push('\n');
 
+        if !result.symbols_by_visibility.is_empty() {
+            output.push_str("## Symbols by Visibility\n\n");
+            output.push_str("| Visibility | Count |\n");
+            output.push_str("|------------|-------|\n");
+            for (visibility, count) in result.visibility_counts() {
+                output.push_str(&format!("| {} | {} |\n", visibility, count));
+            }
+            output.push('\n');
+        }
+
         output.push_str("## Call Graph\n\n");
         output.push_str(&format!("- **Max Depth:** {}\n", result.max_call_depth));
         output.push_str(&format!("- **Avg Depth:** {:.1}\n", result.avg_call_depth));
+        output.push_str(&format!(
+            "- **Entry Points Used:** {}\n",
+            result.depth_entry_points
+        ));
+
+        if !result.last_modified.is_empty() {
+            output.push_str("\n## Least Recently Modified\n\n");
+            output.push_str("| Symbol | Kind | Location | Last Modified |\n");
+            output.push_str("|--------|------|----------|---------------|\n");
+            for s in result.last_modified.iter().take(10) {
+                output.push_str(&format!(
+                    "| `{}` | {} | `{}:{}` | {} |\n",
+                    s.name, s.kind, s.file, s.line, s.last_modified
+                ));
+            }
+        }
 
         output
     }
@@ -1157,6 +2055,9 @@ mod tests {
             }],
             total_paths: 1,
             entry_points: 1,
+            definitions: Vec::new(),
+            symbol_id: None,
+            stable_id: None,
         };
 
         let output = formatter.format_trace(&result);
@@ -1167,6 +2068,53 @@ mod tests {
         assert!(!output.contains("\x1b["));
     }
 
+    #[test]
+    fn test_format_trace_dot_clusters_by_directory() {
+        let step = |symbol: &str, file: &str, line| ChainStep {
+            symbol: symbol.to_string(),
+            file: file.to_string(),
+            line,
+            column: None,
+            context: None,
+        };
+        let result = TraceResult {
+            symbol: "hash".to_string(),
+            defined_at: None,
+            kind: "function".to_string(),
+            invocation_paths: vec![InvocationPath {
+                entry_point: "main".to_string(),
+                entry_kind: "main".to_string(),
+                chain: vec![
+                    step("main", "main.rs", 1),
+                    step("login", "src/auth/login.rs", 4),
+                    step("verify", "src/auth/verify.rs", 9),
+                    step("hash", "src/crypto/hash.rs", 2),
+                ],
+            }],
+            total_paths: 1,
+            entry_points: 1,
+            definitions: Vec::new(),
+            symbol_id: None,
+            stable_id: None,
+        };
+
+        let output = DotFormatter::new().format_trace(&result);
+        let clusters: Vec<&str> = output
+            .lines()
+            .filter(|l| l.trim_start().starts_with("subgraph cluster_"))
+            .collect();
+        assert_eq!(clusters.len(), 3);
+        for label in [".", "src/auth", "src/crypto"] {
+            assert!(output.contains(&format!("label=\"{}\";", label)));
+        }
+        // Both auth files share one cluster
+        let auth = output.find("label=\"src/auth\";").unwrap();
+        let auth_end = auth + output[auth..].find("  }").unwrap();
+        assert!(output[auth..auth_end].contains("login_4"));
+        assert!(output[auth..auth_end].contains("verify_9"));
+        assert!(output.contains("  login_4 -> verify_9;"));
+    }
+
     #[test]
     fn test_format_refs_plain() {
         let formatter = PlainFormatter::new();
@@ -1177,11 +2125,61 @@ mod tests {
             references: vec![],
             total_refs: 0,
             by_kind: std::collections::HashMap::new(),
+            by_confidence: std::collections::HashMap::new(),
             by_file: std::collections::HashMap::new(),
+            by_directory: Vec::new(),
+            source_hashes: std::collections::HashMap::new(),
+            warnings: vec![],
+            symbol_id: None,
+            stable_id: None,
         };
 
         let output = formatter.format_refs(&result);
         assert!(output.contains("REFS: userId"));
         assert!(!output.contains("\x1b["));
     }
+
+    #[test]
+    fn test_format_refs_markdown_table_only() {
+        use crate::trace::output::{ReferenceInfo, ReferenceKind};
+
+        let result = RefsResult {
+            symbol: "userId".to_string(),
+            defined_at: Some("types.ts:5".to_string()),
+            symbol_kind: None,
+            references: vec![ReferenceInfo {
+                file: "api.ts".to_string(),
+                line: 12,
+                column: 4,
+                kind: ReferenceKind::Read,
+                context: "const id = userId;".to_string(),
+                enclosing_symbol: None,
+                confidence: 1.0,
+                byte_offset: None,
+                callee: None,
+                arg_index: None,
+                target_deprecated: false,
+                referenced_as: None,
+                depth: None,
+            }],
+            total_refs: 1,
+            by_kind: [("read".to_string(), 1)].into_iter().collect(),
+            by_confidence: std::collections::HashMap::new(),
+            by_file: std::collections::HashMap::new(),
+            by_directory: Vec::new(),
+            source_hashes: std::collections::HashMap::new(),
+            warnings: vec![],
+            symbol_id: None,
+            stable_id: None,
+        };
+
+        let full = MarkdownFormatter::new().format_refs(&result);
+        assert!(full.starts_with("# References: userId"));
+
+        let formatter = MarkdownFormatter::with_config(MarkdownConfig { table_only: true });
+        let output = formatter.format_refs(&result);
+        assert!(output.starts_with("| File | Line | Kind | Context |\n"));
+        assert!(!output.lines().any(|l| l.starts_with('#')));
+        assert!(output.contains("| `api.ts` | 12 |"));
+    }
 }
@@ -17,7 +17,7 @@
 
 use std::fs::File;
 use std::io::{self, BufReader, BufWriter, Read, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use memmap2::Mmap;
 
@@ -32,8 +32,9 @@ use crate::core::error::{Error, Result};
 /// Magic bytes to identify greppy trace index files
 const MAGIC: [u8; 8] = *b"GRPTRACE";
 
-/// Current file format version
-const VERSION: u32 = 1;
+/// Current file format version (2: symbols record their parent type,
+/// 3: symbols record their visibility)
+const VERSION: u32 = 3;
 
 /// Header size in bytes
 const HEADER_SIZE: usize = 64;
@@ -111,7 +112,8 @@ impl Header {
         if version != VERSION {
             return Err(Error::IndexError {
                 message: format!(
-                    "Unsupported trace index version {} (expected {})",
+                    "Trace index format version {} is incompatible with this greppy (expected {}). \
+                     Run 'greppy index --force' to rebuild it.",
                     version, VERSION
                 ),
             });
@@ -152,9 +154,50 @@ const _: () = {
 // SAVE INDEX
 // =============================================================================
 
+/// Size in bytes of the file `save_index` writes for `index`
+pub fn index_file_size(index: &SemanticIndex) -> u64 {
+    fn slice_bytes<T>(slice: &[T]) -> u64 {
+        std::mem::size_of_val(slice) as u64
+    }
+
+    let paths: u64 = index
+        .files
+        .iter()
+        .map(|p| 4 + p.to_string_lossy().len() as u64)
+        .sum();
+    HEADER_SIZE as u64
+        + slice_bytes(&index.symbols)
+        + slice_bytes(&index.tokens)
+        + slice_bytes(&index.references)
+        + slice_bytes(&index.scopes)
+        + slice_bytes(&index.edges)
+        + paths
+        + index.strings.byte_size() as u64
+}
+
 /// Save a SemanticIndex to a binary file
+///
+/// The index is written to a temporary file beside `path` and renamed over
+/// it, so a reader sees either the previous index or the complete new one.
 pub fn save_index(index: &SemanticIndex, path: impl AsRef<Path>) -> Result<()> {
     let path = path.as_ref();
+    let tmp_path = temp_path(path);
+    let saved = write_index(index, &tmp_path).and_then(|()| Ok(std::fs::rename(&tmp_path, path)?));
+    if saved.is_err() {
+        let _ = std::fs::remove_file(&tmp_path);
+    }
+    saved
+}
+
+/// Sibling of `path` that `save_index` writes before renaming it into place
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}.tmp", std::process::id()));
+    path.with_file_name(name)
+}
+
+/// Write the binary form of `index` to a new file at `path`
+fn write_index(index: &SemanticIndex, path: &Path) -> Result<()> {
     let file = File::create(path)?;
     let mut writer = BufWriter::with_capacity(64 * 1024, file);
 
@@ -189,6 +232,7 @@ pub fn save_index(index: &SemanticIndex, path: impl AsRef<Path>) -> Result<()> {
     writer.write_all(index.strings.as_bytes())?;
 
     writer.flush()?;
+    writer.get_ref().sync_all()?;
     Ok(())
 }
 
@@ -269,6 +313,7 @@ pub fn load_index(path: impl AsRef<Path>) -> Result<SemanticIndex> {
     } else {
         Vec::new()
     };
+    check_string_table(&header, &string_bytes)?;
     let strings = StringTable::from_bytes(string_bytes);
 
     // Build the index
@@ -279,6 +324,7 @@ pub fn load_index(path: impl AsRef<Path>) -> Result<SemanticIndex> {
         scopes,
         edges,
         symbol_by_name: Default::default(),
+        symbol_by_normalized_name: Default::default(),
         token_by_name: Default::default(),
         incoming_edges: Default::default(),
         outgoing_edges: Default::default(),
@@ -286,6 +332,7 @@ pub fn load_index(path: impl AsRef<Path>) -> Result<SemanticIndex> {
         files,
         strings,
         entry_points: Default::default(),
+        normalize_names: false,
     };
 
     // Rebuild lookup structures
@@ -294,6 +341,21 @@ pub fn load_index(path: impl AsRef<Path>) -> Result<SemanticIndex> {
     Ok(index)
 }
 
+/// Reject a string table whose size differs from the one the header records
+fn check_string_table(header: &Header, bytes: &[u8]) -> Result<()> {
+    let expected = header.string_size as usize;
+    if bytes.len() != expected {
+        return Err(Error::IndexError {
+            message: format!(
+                "Truncated trace index file (string table is {} bytes, expected {})",
+                bytes.len(),
+                expected
+            ),
+        });
+    }
+    Ok(())
+}
+
 /// Read a vector of repr(C) types from memory
 fn read_vec<T: Clone>(mmap: &Mmap, offset: &mut usize, count: usize) -> Result<Vec<T>> {
     let size = count * std::mem::size_of::<T>();
@@ -371,6 +433,7 @@ pub fn load_index_streaming(path: impl AsRef<Path>) -> Result<SemanticIndex> {
     // Read string table
     let mut string_bytes = Vec::new();
     reader.read_to_end(&mut string_bytes)?;
+    check_string_table(&header, &string_bytes)?;
     let strings = StringTable::from_bytes(string_bytes);
 
     // Build the index
@@ -381,6 +444,7 @@ pub fn load_index_streaming(path: impl AsRef<Path>) -> Result<SemanticIndex> {
         scopes,
         edges,
         symbol_by_name: Default::default(),
+        symbol_by_normalized_name: Default::default(),
         token_by_name: Default::default(),
         incoming_edges: Default::default(),
         outgoing_edges: Default::default(),
@@ -388,6 +452,7 @@ pub fn load_index_streaming(path: impl AsRef<Path>) -> Result<SemanticIndex> {
         files,
         strings,
         entry_points: Default::default(),
+        normalize_names: false,
     };
 
     // Rebuild lookup structures
@@ -521,6 +586,26 @@ mod tests {
         assert_eq!(loaded.callees(0), &[1]);
     }
 
+    #[test]
+    fn test_truncated_index_is_rejected() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("trace.idx");
+        save_index(&create_test_index(), &path).unwrap();
+
+        // Only the index itself is left behind
+        let names: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(names, vec!["trace.idx"]);
+
+        // Cut inside the string table, where no section count would notice
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &bytes[..bytes.len() - 3]).unwrap();
+        assert!(load_index(&path).is_err());
+        assert!(load_index_streaming(&path).is_err());
+    }
+
     #[test]
     fn test_streaming_load() {
         let dir = tempdir().unwrap();
@@ -535,6 +620,31 @@ mod tests {
         assert_eq!(loaded.tokens.len(), original.tokens.len());
     }
 
+    #[test]
+    fn test_load_old_version_is_rejected() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("trace.idx");
+
+        save_index(&create_test_index(), &path).unwrap();
+
+        // Re-tag the file as if written by an older format version
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[8..12].copy_from_slice(&(VERSION - 1).to_le_bytes());
+        std::fs::write(&path, &bytes).unwrap();
+
+        for result in [load_index(&path), load_index_streaming(&path)] {
+            match result {
+                Err(Error::IndexError { message }) => {
+                    assert!(message.contains("greppy index --force"), "{}", message);
+                }
+                other => panic!(
+                    "Expected version mismatch error, got {:?}",
+                    other.map(|_| ())
+                ),
+            }
+        }
+    }
+
     #[test]
     fn test_header_validation() {
         // Test invalid magic
@@ -4,13 +4,20 @@
 //! - BFS backward traversal from target to entry points
 //! - Reference finding
 //! - Path reconstruction
+//! - Cycle detection (Tarjan strongly connected components)
 //!
 //! @module trace/traverse
 
-use std::collections::{HashSet, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
 
+use regex::Regex;
+use tracing::warn;
+
+use super::context::FileCache;
 use super::index::SemanticIndex;
-use super::types::{RefKind, Reference, Symbol};
+use super::types::{RefKind, Reference, Symbol, TokenKind};
+use crate::core::config::DeadCodeConfig;
 
 // =============================================================================
 // INVOCATION PATH
@@ -193,18 +200,15 @@ pub fn trace_symbol(
 
 /// Trace a symbol by name
 ///
-/// Finds all symbols matching the name and traces each one.
+/// Finds all symbols matching the name (`Type::method` narrows to one
+/// type's method) and traces each one.
 pub fn trace_symbol_by_name(
     index: &SemanticIndex,
     name: &str,
     max_depth: Option<usize>,
 ) -> Vec<TraceResult> {
-    let symbol_ids = match index.symbols_by_name(name) {
-        Some(ids) => ids.clone(),
-        None => return Vec::new(),
-    };
-
-    symbol_ids
+    index
+        .resolve_symbols(name)
         .iter()
         .map(|&id| trace_symbol(index, id, max_depth))
         .collect()
@@ -290,6 +294,59 @@ pub fn find_write_refs(index: &SemanticIndex, symbol_id: u32) -> Vec<ReferenceCo
 // DEAD CODE DETECTION
 // =============================================================================
 
+/// Maximum number of lines between a decorator and the symbol it decorates
+const DECORATOR_LOOKAHEAD: u32 = 5;
+
+/// Options for keeping symbols alive during dead code detection
+///
+/// Frameworks that dispatch via reflection or decorators (DI containers,
+/// route registration) call symbols the call graph cannot see.
+#[derive(Debug, Clone, Default)]
+pub struct DeadCodeOptions {
+    /// Symbols whose name matches any of these patterns are never dead
+    pub keep_alive_patterns: Vec<Regex>,
+    /// Symbols carrying a decorator/attribute are never dead
+    pub keep_decorated: bool,
+    /// Symbols whose name matches any of these patterns are unused on
+    /// purpose and left out of the report
+    pub ignore_names: Vec<Regex>,
+    /// Project root to read `greppy:ignore-dead` annotations from; None
+    /// skips the annotation check
+    pub annotation_root: Option<PathBuf>,
+}
+
+impl DeadCodeOptions {
+    /// Build options from the `[dead_code]` config section
+    ///
+    /// Invalid patterns are skipped with a warning.
+    pub fn from_config(config: &DeadCodeConfig) -> Self {
+        Self {
+            keep_alive_patterns: compile_patterns(&config.keep_alive_patterns, "keep_alive"),
+            keep_decorated: config.keep_decorated,
+            ignore_names: compile_patterns(&config.ignore_names, "ignore_names"),
+            annotation_root: None,
+        }
+    }
+}
+
+/// Comment marker that exempts the definition on the next line from dead
+/// code reports
+pub const IGNORE_DEAD_ANNOTATION: &str = "greppy:ignore-dead";
+
+/// Compile config regexes, skipping invalid ones with a warning
+fn compile_patterns(patterns: &[String], what: &str) -> Vec<Regex> {
+    patterns
+        .iter()
+        .filter_map(|p| match Regex::new(p) {
+            Ok(re) => Some(re),
+            Err(e) => {
+                warn!("Ignoring invalid {} pattern '{}': {}", what, p, e);
+                None
+            }
+        })
+        .collect()
+}
+
 /// Find potentially dead symbols (no incoming references or calls)
 ///
 /// Returns symbols that:
@@ -297,7 +354,21 @@ pub fn find_write_refs(index: &SemanticIndex, symbol_id: u32) -> Vec<ReferenceCo
 /// - Have no incoming edges (no one calls them)
 /// - Have no references
 pub fn find_dead_symbols(index: &SemanticIndex) -> Vec<&Symbol> {
-    index
+    find_dead_symbols_with(index, &DeadCodeOptions::default())
+}
+
+/// Find potentially dead symbols, honoring keep-alive options
+pub fn find_dead_symbols_with<'a>(
+    index: &'a SemanticIndex,
+    options: &DeadCodeOptions,
+) -> Vec<&'a Symbol> {
+    let decorated = if options.keep_decorated {
+        decorated_symbols(index)
+    } else {
+        HashSet::new()
+    };
+
+    let mut dead: Vec<&Symbol> = index
         .symbols
         .iter()
         .filter(|s| {
@@ -314,9 +385,183 @@ pub fn find_dead_symbols(index: &SemanticIndex) -> Vec<&Symbol> {
 
             // Check for references
             let has_refs = index.references_to(s.id).next().is_some();
-            !has_refs
+            if has_refs {
+                return false;
+            }
+
+            // Keep-alive: decorated symbols and configured name patterns
+            if decorated.contains(&s.id) {
+                return false;
+            }
+            let name = index.symbol_name(s).unwrap_or("");
+            if options.keep_alive_patterns.iter().any(|p| p.is_match(name)) {
+                return false;
+            }
+
+            // Deliberately unused: ignored names
+            !options.ignore_names.iter().any(|p| p.is_match(name))
         })
-        .collect()
+        .collect();
+
+    match &options.annotation_root {
+        Some(root) => {
+            let mut cache = FileCache::new(root);
+            dead.retain(|s| !has_ignore_annotation(index, &mut cache, s));
+            dead
+        }
+        None => dead,
+    }
+}
+
+/// Whether the line above a symbol's definition carries `greppy:ignore-dead`
+fn has_ignore_annotation(index: &SemanticIndex, cache: &mut FileCache, symbol: &Symbol) -> bool {
+    if symbol.start_line <= 1 {
+        return false;
+    }
+    index
+        .file_path(symbol.file_id)
+        .and_then(|file| cache.get_line(file, symbol.start_line - 1))
+        .is_some_and(|line| line.contains(IGNORE_DEAD_ANNOTATION))
+}
+
+/// Find symbols that carry a decorator/attribute
+///
+/// A decorator token (or the token of a `RefKind::Decorator` reference)
+/// decorates the first symbol in the same file starting on or shortly
+/// after its line.
+fn decorated_symbols(index: &SemanticIndex) -> HashSet<u32> {
+    let mut decorator_sites: HashSet<(u16, u32)> = index
+        .tokens
+        .iter()
+        .filter(|t| t.token_kind() == TokenKind::Decorator)
+        .map(|t| (t.file_id, t.line))
+        .collect();
+    for reference in &index.references {
+        if reference.ref_kind() == RefKind::Decorator {
+            if let Some(token) = index.token(reference.token_id) {
+                decorator_sites.insert((token.file_id, token.line));
+            }
+        }
+    }
+
+    if decorator_sites.is_empty() {
+        return HashSet::new();
+    }
+
+    let mut by_file: HashMap<u16, Vec<&Symbol>> = HashMap::new();
+    for symbol in &index.symbols {
+        by_file.entry(symbol.file_id).or_default().push(symbol);
+    }
+    for symbols in by_file.values_mut() {
+        symbols.sort_by_key(|s| s.start_line);
+    }
+
+    let mut decorated = HashSet::new();
+    for (file_id, line) in decorator_sites {
+        let Some(symbols) = by_file.get(&file_id) else {
+            continue;
+        };
+        let first = symbols.partition_point(|s| s.start_line < line);
+        if let Some(symbol) = symbols.get(first) {
+            if symbol.start_line <= line + DECORATOR_LOOKAHEAD {
+                decorated.insert(symbol.id);
+            }
+        }
+    }
+
+    decorated
+}
+
+// =============================================================================
+// CYCLE DETECTION
+// =============================================================================
+
+/// Find every non-trivial strongly connected component of a graph
+///
+/// Uses Tarjan's algorithm, so each cycle cluster is reported exactly once
+/// regardless of where traversal starts. Components of a single node are
+/// only included when the node has a self-loop. Members are sorted, and
+/// components are ordered by their smallest member.
+pub fn strongly_connected_components<N>(graph: &HashMap<N, HashSet<N>>) -> Vec<Vec<N>>
+where
+    N: Copy + Eq + Ord + std::hash::Hash,
+{
+    struct Tarjan<'a, N> {
+        graph: &'a HashMap<N, HashSet<N>>,
+        next_index: usize,
+        index: HashMap<N, usize>,
+        lowlink: HashMap<N, usize>,
+        stack: Vec<N>,
+        on_stack: HashSet<N>,
+        components: Vec<Vec<N>>,
+    }
+
+    impl<N: Copy + Eq + Ord + std::hash::Hash> Tarjan<'_, N> {
+        fn visit(&mut self, node: N) {
+            self.index.insert(node, self.next_index);
+            self.lowlink.insert(node, self.next_index);
+            self.next_index += 1;
+            self.stack.push(node);
+            self.on_stack.insert(node);
+
+            let mut neighbors: Vec<N> = self
+                .graph
+                .get(&node)
+                .map(|n| n.iter().copied().collect())
+                .unwrap_or_default();
+            neighbors.sort();
+
+            for neighbor in neighbors {
+                if !self.index.contains_key(&neighbor) {
+                    self.visit(neighbor);
+                    let low = self.lowlink[&node].min(self.lowlink[&neighbor]);
+                    self.lowlink.insert(node, low);
+                } else if self.on_stack.contains(&neighbor) {
+                    let low = self.lowlink[&node].min(self.index[&neighbor]);
+                    self.lowlink.insert(node, low);
+                }
+            }
+
+            // Root of a component: pop its members off the stack
+            if self.lowlink[&node] == self.index[&node] {
+                let mut component = Vec::new();
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack.remove(&member);
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+
+                let self_loop = self.graph.get(&node).is_some_and(|n| n.contains(&node));
+                if component.len() > 1 || self_loop {
+                    component.sort();
+                    self.components.push(component);
+                }
+            }
+        }
+    }
+
+    let mut tarjan = Tarjan {
+        graph,
+        next_index: 0,
+        index: HashMap::new(),
+        lowlink: HashMap::new(),
+        stack: Vec::new(),
+        on_stack: HashSet::new(),
+        components: Vec::new(),
+    };
+
+    let mut nodes: Vec<N> = graph.keys().copied().collect();
+    nodes.sort();
+    for node in nodes {
+        if !tarjan.index.contains_key(&node) {
+            tarjan.visit(node);
+        }
+    }
+
+    tarjan.components.sort();
+    tarjan.components
 }
 
 // =============================================================================
@@ -527,4 +772,100 @@ mod tests {
         assert_eq!(dead.len(), 1);
         assert_eq!(dead[0].id, 2);
     }
+
+    #[test]
+    fn test_dead_symbols_keep_alive() {
+        use crate::trace::types::Token;
+
+        let mut index = SemanticIndex::new();
+        let file_id = index.add_file("app.py".into());
+
+        // @route("/users")
+        // def list_users(): ...
+        let route = index.strings.intern("route");
+        let handler = index.strings.intern("list_users");
+        let plugin = index.strings.intern("plugin_init");
+        index.add_symbol(
+            Symbol::new(
+                0,
+                route,
+                file_id,
+                SymbolKind::Function,
+                SymbolFlags::empty(),
+                1,
+                3,
+            ),
+            "route",
+        );
+        index.add_symbol(
+            Symbol::new(
+                1,
+                handler,
+                file_id,
+                SymbolKind::Function,
+                SymbolFlags::empty(),
+                6,
+                8,
+            ),
+            "list_users",
+        );
+        index.add_symbol(
+            Symbol::new(
+                2,
+                plugin,
+                file_id,
+                SymbolKind::Function,
+                SymbolFlags::empty(),
+                10,
+                12,
+            ),
+            "plugin_init",
+        );
+        index.add_token(
+            Token::new(0, route, file_id, 5, 1, TokenKind::Decorator, 0),
+            "route",
+        );
+        index.add_reference(Reference::new(0, 0, RefKind::Decorator));
+
+        // Without keep-alive the decorated handler is reported dead
+        let dead: Vec<u32> = find_dead_symbols(&index).iter().map(|s| s.id).collect();
+        assert_eq!(dead, vec![1, 2]);
+
+        let options = DeadCodeOptions {
+            keep_decorated: true,
+            ..Default::default()
+        };
+        let dead: Vec<u32> = find_dead_symbols_with(&index, &options)
+            .iter()
+            .map(|s| s.id)
+            .collect();
+        assert_eq!(dead, vec![2]);
+
+        let options = DeadCodeOptions::from_config(&DeadCodeConfig {
+            keep_alive_patterns: vec!["^plugin_".to_string(), "(".to_string()],
+            keep_decorated: true,
+            ..Default::default()
+        });
+        assert_eq!(options.keep_alive_patterns.len(), 1);
+        assert!(find_dead_symbols_with(&index, &options).is_empty());
+    }
+
+    #[test]
+    fn test_strongly_connected_components_two_cycles() {
+        // 1 <-> 2 and 3 -> 4 -> 5 -> 3, joined by a one-way edge 2 -> 3,
+        // plus an acyclic tail 5 -> 6
+        let mut graph: HashMap<u16, HashSet<u16>> = HashMap::new();
+        for (from, to) in [(1, 2), (2, 1), (2, 3), (3, 4), (4, 5), (5, 3), (5, 6)] {
+            graph.entry(from).or_default().insert(to);
+        }
+
+        let components = strongly_connected_components(&graph);
+        assert_eq!(components, vec![vec![1, 2], vec![3, 4, 5]]);
+
+        // A self-loop is a cycle; an isolated node is not
+        graph.entry(6).or_default().insert(6);
+        graph.entry(7).or_default();
+        let components = strongly_connected_components(&graph);
+        assert_eq!(components, vec![vec![1, 2], vec![3, 4, 5], vec![6]]);
+    }
 }
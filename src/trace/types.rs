@@ -92,13 +92,65 @@ impl Default for SymbolFlags {
     }
 }
 
+// =============================================================================
+// VISIBILITY
+// =============================================================================
+
+/// How far outside its definition a symbol can be used
+///
+/// Ordered from least to most visible. `Internal` covers the language's
+/// middle ground: `pub(crate)`/`pub(super)` in Rust, `internal` in C#, and
+/// package scope in Go and Java.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum Visibility {
+    /// Only within its module, file or class
+    Private = 0,
+    /// Within its class and subclasses
+    Protected = 1,
+    /// Within its crate, package or assembly
+    Internal = 2,
+    /// Anywhere (part of the public API)
+    Public = 3,
+}
+
+impl From<u8> for Visibility {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => Self::Protected,
+            2 => Self::Internal,
+            3 => Self::Public,
+            _ => Self::Private,
+        }
+    }
+}
+
+impl Visibility {
+    /// Lowercase name, as accepted by `--visibility`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Private => "private",
+            Self::Protected => "protected",
+            Self::Internal => "internal",
+            Self::Public => "public",
+        }
+    }
+
+    /// Parse a lowercase name (see `as_str`)
+    pub fn parse(name: &str) -> Option<Self> {
+        [Self::Private, Self::Protected, Self::Internal, Self::Public]
+            .into_iter()
+            .find(|v| v.as_str().eq_ignore_ascii_case(name))
+    }
+}
+
 // =============================================================================
 // SYMBOL
 // =============================================================================
 
 /// A code symbol definition (function, class, method, etc.)
 ///
-/// Layout (24 bytes):
+/// Layout (28 bytes):
 /// - id: u32 (4)
 /// - name_offset: u32 (4)
 /// - file_id: u16 (2)
@@ -106,7 +158,9 @@ impl Default for SymbolFlags {
 /// - flags: u8 (1)
 /// - start_line: u32 (4)
 /// - end_line: u32 (4)
-/// - _padding: u32 (4) - for alignment
+/// - parent_offset: u32 (4) - enclosing type name, or `NO_PARENT`
+/// - visibility: u8 (1)
+/// - _padding: [u8; 3] (3)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(C)]
 pub struct Symbol {
@@ -124,12 +178,22 @@ pub struct Symbol {
     pub start_line: u32,
     /// Ending line number (1-indexed)
     pub end_line: u32,
-    /// Padding for alignment
-    _padding: u32,
+    /// Offset into string table for the enclosing type's name (`NO_PARENT` if none)
+    parent_offset: u32,
+    /// Visibility (public, internal, protected, private)
+    visibility: u8,
+    /// Explicit padding so every byte written to disk is initialized
+    _padding: [u8; 3],
 }
 
+/// Sentinel `parent_offset` for symbols without an enclosing type
+pub const NO_PARENT: u32 = u32::MAX;
+
 impl Symbol {
     /// Create a new symbol
+    ///
+    /// Visibility starts as public for exported symbols and private otherwise;
+    /// use `with_visibility` when the source says more.
     #[inline]
     pub const fn new(
         id: u32,
@@ -148,10 +212,42 @@ impl Symbol {
             flags: flags.bits(),
             start_line,
             end_line,
-            _padding: 0,
+            parent_offset: NO_PARENT,
+            visibility: if flags.contains(SymbolFlags::IS_EXPORTED) {
+                Visibility::Public as u8
+            } else {
+                Visibility::Private as u8
+            },
+            _padding: [0; 3],
         }
     }
 
+    /// Set the symbol's visibility
+    #[inline]
+    pub const fn with_visibility(mut self, visibility: Visibility) -> Self {
+        self.visibility = visibility as u8;
+        self
+    }
+
+    /// Get the symbol's visibility
+    #[inline]
+    pub fn visibility(&self) -> Visibility {
+        Visibility::from(self.visibility)
+    }
+
+    /// Set the string-table offset of the enclosing type's name
+    #[inline]
+    pub const fn with_parent(mut self, parent_offset: u32) -> Self {
+        self.parent_offset = parent_offset;
+        self
+    }
+
+    /// String-table offset of the enclosing type's name, if any
+    #[inline]
+    pub fn parent_offset(&self) -> Option<u32> {
+        (self.parent_offset != NO_PARENT).then_some(self.parent_offset)
+    }
+
     /// Get the symbol kind
     #[inline]
     pub fn symbol_kind(&self) -> SymbolKind {
@@ -187,6 +283,12 @@ impl Symbol {
     pub fn is_test(&self) -> bool {
         self.symbol_flags().contains(SymbolFlags::IS_TEST)
     }
+
+    /// Check if this symbol is deprecated
+    #[inline]
+    pub fn is_deprecated(&self) -> bool {
+        self.symbol_flags().contains(SymbolFlags::IS_DEPRECATED)
+    }
 }
 
 // =============================================================================
@@ -211,6 +313,10 @@ pub enum TokenKind {
     Decorator = 5,
     /// Label (for goto/break/continue)
     Label = 6,
+    /// Function parameter declaration
+    Parameter = 7,
+    /// Local variable declaration
+    Variable = 8,
     /// Unknown token type
     Unknown = 255,
 }
@@ -225,6 +331,8 @@ impl From<u8> for TokenKind {
             4 => Self::Property,
             5 => Self::Decorator,
             6 => Self::Label,
+            7 => Self::Parameter,
+            8 => Self::Variable,
             _ => Self::Unknown,
         }
     }
@@ -587,7 +695,7 @@ impl Edge {
 
 // Compile-time size checks for mmap compatibility
 const _: () = {
-    assert!(std::mem::size_of::<Symbol>() == 24);
+    assert!(std::mem::size_of::<Symbol>() == 28);
     assert!(std::mem::size_of::<Token>() == 24);
     assert!(std::mem::size_of::<Reference>() == 12);
     assert!(std::mem::size_of::<Scope>() == 24);
@@ -648,6 +756,7 @@ mod tests {
         assert!(!sym.is_async());
         assert_eq!(sym.start_line, 10);
         assert_eq!(sym.end_line, 50);
+        assert_eq!(sym.visibility(), Visibility::Public);
     }
 
     #[test]
@@ -0,0 +1,152 @@
+//! Token authentication for the web UI
+//!
+//! With `[web] auth_token` set, every route requires the token, either as an
+//! `Authorization: Bearer` header or a `?token=` query parameter. A valid
+//! query token also sets a cookie, so the browser UI (scripts, API calls,
+//! event stream) keeps working after opening `/?token=...` once.
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    Router,
+};
+use std::sync::Arc;
+
+/// Cookie carrying the token for the browser UI
+const TOKEN_COOKIE: &str = "greppy_token";
+
+/// Require `token` on every route of `router`; an unset or empty token
+/// leaves it open
+pub fn protect(router: Router, token: Option<&str>) -> Router {
+    match token.filter(|t| !t.is_empty()) {
+        Some(token) => router.layer(middleware::from_fn_with_state(
+            Arc::<str>::from(token),
+            require_token,
+        )),
+        None => router,
+    }
+}
+
+/// Reject requests that don't carry the configured token with 401
+async fn require_token(State(token): State<Arc<str>>, request: Request, next: Next) -> Response {
+    let from_query = query_token(&request).is_some_and(|t| tokens_match(&t, &token));
+    let authorized = from_query
+        || bearer_token(&request).is_some_and(|t| tokens_match(t, &token))
+        || cookie_token(&request).is_some_and(|t| tokens_match(&t, &token));
+    if !authorized {
+        return (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+            "Unauthorized",
+        )
+            .into_response();
+    }
+
+    let mut response = next.run(request).await;
+    if from_query {
+        let cookie = format!(
+            "{}={}; Path=/; HttpOnly; SameSite=Strict",
+            TOKEN_COOKIE,
+            urlencoding::encode(&token)
+        );
+        if let Ok(cookie) = HeaderValue::from_str(&cookie) {
+            response.headers_mut().append(header::SET_COOKIE, cookie);
+        }
+    }
+    response
+}
+
+/// Token from `Authorization: Bearer <token>`
+fn bearer_token(request: &Request) -> Option<&str> {
+    request
+        .headers()
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
+
+/// Token from the `?token=` query parameter
+fn query_token(request: &Request) -> Option<String> {
+    let query = request.uri().query()?;
+    url::form_urlencoded::parse(query.as_bytes())
+        .find(|(key, _)| key == "token")
+        .map(|(_, value)| value.into_owned())
+}
+
+/// Token from the cookie set after a `?token=` visit
+fn cookie_token(request: &Request) -> Option<String> {
+    request
+        .headers()
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .find_map(|pair| {
+            let value = pair.trim().strip_prefix(TOKEN_COOKIE)?.strip_prefix('=')?;
+            urlencoding::decode(value).ok().map(|v| v.into_owned())
+        })
+}
+
+/// Compare tokens without stopping at the first differing byte
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+
+    #[tokio::test]
+    async fn test_configured_token_is_required_on_every_route() {
+        let router = Router::new()
+            .route("/", get(|| async { "page" }))
+            .route("/api/stats", get(|| async { "stats" }));
+        let app = protect(router, Some("s3cret"));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let client = reqwest::Client::new();
+        let status = |request: reqwest::RequestBuilder| async move {
+            request.send().await.unwrap().status().as_u16()
+        };
+
+        for path in ["/", "/api/stats"] {
+            let url = format!("{}{}", base, path);
+            assert_eq!(status(client.get(&url)).await, 401);
+            assert_eq!(status(client.get(&url).bearer_auth("wrong")).await, 401);
+            assert_eq!(status(client.get(&url).bearer_auth("s3cret")).await, 200);
+            assert_eq!(
+                status(client.get(format!("{}?token=s3cret", url))).await,
+                200
+            );
+        }
+
+        // The query token leaves a cookie for the UI's later requests
+        let response = client
+            .get(format!("{}/?token=s3cret", base))
+            .send()
+            .await
+            .unwrap();
+        let cookie = response.headers()[reqwest::header::SET_COOKIE]
+            .to_str()
+            .unwrap()
+            .split(';')
+            .next()
+            .unwrap()
+            .to_string();
+        let with_cookie = client
+            .get(format!("{}/api/stats", base))
+            .header(reqwest::header::COOKIE, cookie);
+        assert_eq!(status(with_cookie).await, 200);
+    }
+}
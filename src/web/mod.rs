@@ -2,6 +2,7 @@
 //!
 //! Single-page web UI for exploring codebase structure and dependencies.
 
+pub mod auth;
 pub mod events;
 pub mod projects;
 pub mod server;
@@ -1,7 +1,8 @@
 //! Axum web server for greppy web UI
 
 use axum::{
-    extract::{Path, Query, State},
+    body::{Body, Bytes},
+    extract::{FromRef, Path, Query, State},
     http::{header, StatusCode},
     response::{Html, IntoResponse, Response},
     routing::{get, post},
@@ -9,17 +10,28 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{IsTerminal, Write};
 use std::net::SocketAddr;
 use std::path::PathBuf;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::warn;
 
+use crate::core::config::Config;
 use crate::core::error::Result;
 use crate::core::project::Project;
+use crate::output::color::{paint, should_color};
+use crate::trace::context::FileCache;
+use crate::trace::output::{CsvFormatter, DotFormatter};
 use crate::trace::{
-    compare_snapshots, create_snapshot, find_dead_symbols, list_snapshots, load_index,
-    load_snapshot, trace_index_exists, trace_index_path, SemanticIndex, SymbolKind,
+    blame, compare_snapshots, create_snapshot, export_index_with_paths, find_dead_symbols,
+    list_snapshots, load_index, load_snapshot, strongly_connected_components, trace_index_exists,
+    trace_index_path, RefKind, SemanticIndex, SymbolKind,
 };
-use crate::web::events::{api_events, start_daemon_event_forwarder, EventsState};
+use crate::web::auth;
+use crate::web::events::{api_events, start_daemon_event_forwarder, EventsState, SseEvent};
 use crate::web::projects::{api_projects, api_switch_project, ProjectsState};
 use crate::web::settings::{
     api_get_settings, api_put_settings, redact_path, SettingsState, WebSettings,
@@ -29,6 +41,12 @@ use crate::web::settings::{
 // STATIC FILES (EMBEDDED)
 // =============================================================================
 
+/// Upper bound on `?context=N` for the symbol refs endpoint
+const MAX_REF_CONTEXT: u32 = 10;
+
+/// How often the trace index file is checked for a rebuild
+const INDEX_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
 const INDEX_HTML: &str = include_str!("static/index.html");
 const STYLE_CSS: &str = include_str!("static/style.css");
 const APP_JS: &str = include_str!("static/app.js");
@@ -72,6 +90,97 @@ pub struct AppState {
     pub index: Arc<SemanticIndex>,
     pub dead_symbols: Arc<HashSet<u32>>,
     pub settings: Arc<RwLock<WebSettings>>,
+    /// Source files preloaded by `[web] warm_context`, shared by all requests;
+    /// without warming each request reads sources afresh
+    pub context_cache: Option<Arc<Mutex<FileCache>>>,
+}
+
+/// State of the data routes: the current `AppState`, replaced as a whole
+/// when the trace index is rebuilt
+///
+/// Each request takes a snapshot (see `FromRef`), so a reload never changes
+/// the index under a request that is already running.
+#[derive(Clone)]
+pub struct LiveState {
+    current: Arc<RwLock<AppState>>,
+    index_path: PathBuf,
+    /// Stamp of the index file when `new` was called
+    initial_stamp: Option<(SystemTime, u64)>,
+}
+
+impl LiveState {
+    /// Serve `state`, whose index was loaded from `index_path`
+    pub fn new(state: AppState, index_path: PathBuf) -> Self {
+        Self {
+            current: Arc::new(RwLock::new(state)),
+            initial_stamp: file_stamp(&index_path),
+            index_path,
+        }
+    }
+
+    /// Load the index from disk again and swap it in with its dead symbols
+    ///
+    /// On error the current index stays in place.
+    pub fn reload(&self) -> Result<AppState> {
+        let index = load_index(&self.index_path)?;
+        let dead_symbols = dead_symbol_ids(&index);
+
+        let mut current = self.current.write().unwrap();
+        current.index = Arc::new(index);
+        current.dead_symbols = Arc::new(dead_symbols);
+        // Sources were likely edited along with the rebuild
+        if let Some(cache) = &current.context_cache {
+            cache.lock().unwrap().clear();
+        }
+        Ok(current.clone())
+    }
+}
+
+impl FromRef<LiveState> for AppState {
+    fn from_ref(live: &LiveState) -> Self {
+        live.current.read().unwrap().clone()
+    }
+}
+
+/// Ids of the symbols `find_dead_symbols` reports
+fn dead_symbol_ids(index: &SemanticIndex) -> HashSet<u32> {
+    find_dead_symbols(index).iter().map(|s| s.id).collect()
+}
+
+/// Modification time and size of a file, to notice it being rewritten
+fn file_stamp(path: &std::path::Path) -> Option<(SystemTime, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Reload `live` whenever its index file changes, checking every `interval`
+///
+/// `on_reload` receives the new state and how long loading it took. A failed
+/// load (e.g. an index still being written) is logged and retried on the next
+/// change.
+pub async fn watch_index(
+    live: LiveState,
+    interval: Duration,
+    on_reload: impl Fn(&AppState, Duration),
+) {
+    let mut last = live.initial_stamp;
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        let stamp = file_stamp(&live.index_path);
+        if stamp.is_none() || stamp == last {
+            continue;
+        }
+        last = stamp;
+
+        let reloading = live.clone();
+        let start = Instant::now();
+        match tokio::task::spawn_blocking(move || reloading.reload()).await {
+            Ok(Ok(state)) => on_reload(&state, start.elapsed()),
+            Ok(Err(e)) => warn!("Failed to reload trace index: {}", e),
+            Err(e) => warn!("Trace index reload panicked: {}", e),
+        }
+    }
 }
 
 impl AppState {
@@ -99,6 +208,8 @@ pub struct StatsResponse {
     pub cycles: usize,
     pub last_indexed: String,
     pub breakdown: SymbolBreakdown,
+    pub references: usize,
+    pub references_by_kind: HashMap<&'static str, usize>,
 }
 
 #[derive(Serialize)]
@@ -118,6 +229,10 @@ pub struct ListQuery {
     pub state: Option<String>,
     pub search: Option<String>,
     pub limit: Option<usize>,
+    /// Add each symbol's last-modified date from git blame (slow)
+    pub with_dates: Option<bool>,
+    /// Only symbols with this visibility (public, internal, protected, private)
+    pub visibility: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -138,6 +253,10 @@ pub struct ListItem {
     pub callers: usize,
     pub callees: usize,
     pub state: String,
+    pub visibility: &'static str,
+    /// Commit date of the definition line, `YYYY-MM-DD` (with `with_dates`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -149,12 +268,24 @@ pub struct GraphQuery {
     pub hierarchical: Option<bool>,
     /// Path to zoom into (for drill-down navigation)
     pub path: Option<String>,
+    /// Maximum nodes to return (default 100, capped at 1000)
+    pub limit: Option<usize>,
 }
 
+/// Default node count for the force-directed graph
+const DEFAULT_GRAPH_LIMIT: usize = 100;
+
+/// Upper bound on `limit` so a request cannot ask for the whole repo
+const MAX_GRAPH_LIMIT: usize = 1000;
+
 #[derive(Serialize)]
 pub struct GraphResponse {
     pub nodes: Vec<GraphNode>,
     pub edges: Vec<GraphEdge>,
+    /// Nodes were dropped to honor the limit
+    pub truncated: bool,
+    /// Nodes matching the query before the limit was applied
+    pub total_nodes: usize,
 }
 
 #[derive(Serialize)]
@@ -334,6 +465,20 @@ pub struct RefInfo {
     pub context: String,
 }
 
+/// Query parameters for the symbol refs endpoint
+#[derive(Deserialize)]
+pub struct RefsQuery {
+    /// Lines of real source before and after each reference
+    pub context: Option<u32>,
+}
+
+/// Query parameters for the cycles endpoint
+#[derive(Deserialize)]
+pub struct CyclesQuery {
+    /// "tarjan" (default, one entry per strongly connected component) or "dfs"
+    pub algorithm: Option<String>,
+}
+
 /// Response for symbol refs endpoint
 #[derive(Serialize)]
 pub struct RefsResponse {
@@ -432,6 +577,17 @@ pub struct SnapshotDiffResponse {
     pub cycles: i32,
 }
 
+// =============================================================================
+// EXPORT API TYPES
+// =============================================================================
+
+/// Query for the bulk export endpoint
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    /// json (default, the JSON Lines export document), csv or dot
+    pub format: Option<String>,
+}
+
 // =============================================================================
 // HELPERS
 // =============================================================================
@@ -452,6 +608,21 @@ fn symbol_kind_str(kind: SymbolKind) -> &'static str {
     }
 }
 
+fn ref_kind_str(kind: RefKind) -> &'static str {
+    match kind {
+        RefKind::Read => "read",
+        RefKind::Write => "write",
+        RefKind::Call => "call",
+        RefKind::TypeAnnotation => "type",
+        RefKind::Import => "import",
+        RefKind::Export => "export",
+        RefKind::Inheritance => "inheritance",
+        RefKind::Decorator => "decorator",
+        RefKind::Construction => "construction",
+        RefKind::Unknown => "unknown",
+    }
+}
+
 /// Count cycles using DFS (simplified version)
 fn count_cycles(index: &SemanticIndex) -> usize {
     let mut graph: HashMap<u16, HashSet<u16>> = HashMap::new();
@@ -994,6 +1165,11 @@ async fn lib_persistence_js() -> Response {
 }
 
 async fn api_stats(State(state): State<AppState>) -> Json<StatsResponse> {
+    Json(build_stats(&state))
+}
+
+/// Project totals with symbol and reference kind breakdowns
+fn build_stats(state: &AppState) -> StatsResponse {
     let index = &state.index;
     let stats = index.stats();
 
@@ -1003,10 +1179,17 @@ async fn api_stats(State(state): State<AppState>) -> Json<StatsResponse> {
         *breakdown.entry(kind).or_insert(0usize) += 1;
     }
 
+    let mut references_by_kind = HashMap::new();
+    for reference in &index.references {
+        *references_by_kind
+            .entry(ref_kind_str(reference.ref_kind()))
+            .or_insert(0usize) += 1;
+    }
+
     let dead = state.dead_symbols.len();
     let cycles = count_cycles(index);
 
-    Json(StatsResponse {
+    StatsResponse {
         project: state.project_name.clone(),
         files: stats.files,
         symbols: stats.symbols,
@@ -1021,7 +1204,9 @@ async fn api_stats(State(state): State<AppState>) -> Json<StatsResponse> {
             interfaces: *breakdown.get("interface").unwrap_or(&0),
             methods: *breakdown.get("method").unwrap_or(&0),
         },
-    })
+        references: index.references.len(),
+        references_by_kind,
+    }
 }
 
 async fn api_list(
@@ -1048,6 +1233,13 @@ async fn api_list(
             }
         }
 
+        let visibility = symbol.visibility().as_str();
+        if let Some(ref visibility_filter) = query.visibility {
+            if visibility_filter != "all" && visibility != visibility_filter {
+                continue;
+            }
+        }
+
         let is_dead = state.dead_symbols.contains(&symbol.id);
 
         if let Some(ref state_filter) = query.state {
@@ -1082,6 +1274,8 @@ async fn api_list(
             } else {
                 "used".to_string()
             },
+            visibility,
+            last_modified: None,
         });
 
         if items.len() >= limit {
@@ -1089,6 +1283,14 @@ async fn api_list(
         }
     }
 
+    if query.with_dates.unwrap_or(false) {
+        let mut dates =
+            blame::symbol_dates(&state.project_path, index, items.iter().map(|item| item.id));
+        for item in &mut items {
+            item.last_modified = dates.remove(&item.id);
+        }
+    }
+
     let total = items.len();
     Json(ListResponse { items, total })
 }
@@ -1101,7 +1303,11 @@ async fn api_graph(State(state): State<AppState>, Query(query): Query<GraphQuery
             .into_response();
     }
 
-    // Original force-directed graph logic
+    Json(build_file_graph(&state, &query)).into_response()
+}
+
+/// Build the force-directed file graph, keeping the files with the most symbols
+fn build_file_graph(state: &AppState, query: &GraphQuery) -> GraphResponse {
     let index = &state.index;
 
     // Build file-level graph
@@ -1184,7 +1390,16 @@ async fn api_graph(State(state): State<AppState>, Query(query): Query<GraphQuery
         });
     }
 
-    // Build edges (only between included nodes)
+    // Limit for performance: keep the files with the most symbols
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_GRAPH_LIMIT)
+        .min(MAX_GRAPH_LIMIT);
+    let total_nodes = nodes.len();
+    nodes.sort_by(|a, b| b.symbols.cmp(&a.symbols).then_with(|| a.id.cmp(&b.id)));
+    nodes.truncate(limit);
+
+    // Build edges (only between retained nodes)
     let node_ids: HashSet<_> = nodes.iter().map(|n| n.id.clone()).collect();
     let mut edges = Vec::new();
 
@@ -1207,18 +1422,12 @@ async fn api_graph(State(state): State<AppState>, Query(query): Query<GraphQuery
         }
     }
 
-    // Limit for performance
-    if nodes.len() > 100 {
-        // Sort by symbol count and take top 100
-        nodes.sort_by(|a, b| b.symbols.cmp(&a.symbols));
-        nodes.truncate(100);
-
-        // Filter edges to only include remaining nodes
-        let remaining_ids: HashSet<_> = nodes.iter().map(|n| n.id.clone()).collect();
-        edges.retain(|e| remaining_ids.contains(&e.source) && remaining_ids.contains(&e.target));
+    GraphResponse {
+        truncated: total_nodes > nodes.len(),
+        total_nodes,
+        nodes,
+        edges,
     }
-
-    Json(GraphResponse { nodes, edges }).into_response()
 }
 
 /// Build hierarchical treemap data for scalable visualization
@@ -1646,14 +1855,41 @@ async fn api_symbol_callees(
 async fn api_symbol_refs(
     State(state): State<AppState>,
     Path(symbol_id): Path<u32>,
+    Query(query): Query<RefsQuery>,
 ) -> std::result::Result<Json<RefsResponse>, StatusCode> {
-    let index = &state.index;
-
     // Verify symbol exists
-    if index.symbol(symbol_id).is_none() {
+    if state.index.symbol(symbol_id).is_none() {
         return Err(StatusCode::NOT_FOUND);
     }
 
+    let refs = collect_symbol_refs(&state, symbol_id, query.context);
+
+    let total = refs.len();
+    Ok(Json(RefsResponse {
+        symbol_id,
+        refs,
+        total,
+    }))
+}
+
+/// Build reference info, with `context` lines of source around each when given
+fn collect_symbol_refs(state: &AppState, symbol_id: u32, context: Option<u32>) -> Vec<RefInfo> {
+    let index = &state.index;
+    let context = context.map(|n| n.min(MAX_REF_CONTEXT));
+    // The shared cache is locked per lookup, so other requests are not held up
+    let mut local_cache = None;
+    let mut read_context = |path: &std::path::Path, line: u32, n: u32| {
+        let read = |cache: &mut FileCache| {
+            cache
+                .get_context(path, line, n, n)
+                .map(|ctx| ctx.format(false))
+        };
+        match &state.context_cache {
+            Some(shared) => read(&mut shared.lock().unwrap()),
+            None => read(local_cache.get_or_insert_with(|| FileCache::new(&state.project_path))),
+        }
+    };
+
     let mut refs: Vec<RefInfo> = Vec::new();
 
     for reference in index.references_to(symbol_id) {
@@ -1677,14 +1913,20 @@ async fn api_symbol_refs(
             }
             .to_string();
 
-            // Build context string (we don't have source content, so use token name)
-            let token_name = index.token_name(token).unwrap_or("");
-            let context = format!(
-                "{}:{} - {}",
-                file.split('/').last().unwrap_or(&file),
-                token.line,
-                token_name
-            );
+            // Real source when requested, otherwise a one-line token summary
+            let source = match (context, index.file_path(token.file_id)) {
+                (Some(n), Some(path)) => read_context(path, token.line, n),
+                _ => None,
+            };
+            let context = source.unwrap_or_else(|| {
+                let token_name = index.token_name(token).unwrap_or("");
+                format!(
+                    "{}:{} - {}",
+                    file.split('/').last().unwrap_or(&file),
+                    token.line,
+                    token_name
+                )
+            });
 
             refs.push(RefInfo {
                 file,
@@ -1698,12 +1940,29 @@ async fn api_symbol_refs(
     // Sort by file, then by line
     refs.sort_by(|a, b| a.file.cmp(&b.file).then_with(|| a.line.cmp(&b.line)));
 
-    let total = refs.len();
-    Ok(Json(RefsResponse {
-        symbol_id,
-        refs,
-        total,
-    }))
+    refs
+}
+
+/// Read the most-referenced files of `index` into `cache`
+///
+/// Files are taken in order of how many references point into them; any
+/// that would push the cache over its limit are skipped. Returns the
+/// number of files cached.
+fn warm_context_cache(index: &SemanticIndex, cache: &mut FileCache) -> usize {
+    let mut refs_per_file: HashMap<u16, usize> = HashMap::new();
+    for reference in &index.references {
+        if let Some(token) = index.token(reference.token_id) {
+            *refs_per_file.entry(token.file_id).or_insert(0) += 1;
+        }
+    }
+    let mut files: Vec<(u16, usize)> = refs_per_file.into_iter().collect();
+    files.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    files
+        .into_iter()
+        .filter_map(|(file_id, _)| index.file_path(file_id))
+        .filter(|path| cache.preload(path))
+        .count()
 }
 
 /// GET /api/symbol/:id/impact - Impact analysis for a symbol
@@ -1796,7 +2055,10 @@ async fn api_symbol_impact(
 }
 
 /// GET /api/cycles - All circular dependencies
-async fn api_cycles(State(state): State<AppState>) -> Json<CyclesResponse> {
+async fn api_cycles(
+    State(state): State<AppState>,
+    Query(query): Query<CyclesQuery>,
+) -> Json<CyclesResponse> {
     let index = &state.index;
 
     // Build file-level graph and find cycles
@@ -1815,24 +2077,29 @@ async fn api_cycles(State(state): State<AppState>) -> Json<CyclesResponse> {
         }
     }
 
-    // Find all cycles using DFS with path tracking
-    let mut all_cycles: Vec<Vec<u16>> = Vec::new();
-    let mut visited: HashSet<u16> = HashSet::new();
-    let mut rec_stack: HashSet<u16> = HashSet::new();
-    let mut path: Vec<u16> = Vec::new();
-
-    for &node in graph.keys() {
-        if !visited.contains(&node) {
-            find_all_cycles(
-                node,
-                &graph,
-                &mut visited,
-                &mut rec_stack,
-                &mut path,
-                &mut all_cycles,
-            );
+    let all_cycles: Vec<Vec<u16>> = if query.algorithm.as_deref() == Some("dfs") {
+        // Find all cycles using DFS with path tracking
+        let mut all_cycles = Vec::new();
+        let mut visited: HashSet<u16> = HashSet::new();
+        let mut rec_stack: HashSet<u16> = HashSet::new();
+        let mut path: Vec<u16> = Vec::new();
+
+        for &node in graph.keys() {
+            if !visited.contains(&node) {
+                find_all_cycles(
+                    node,
+                    &graph,
+                    &mut visited,
+                    &mut rec_stack,
+                    &mut path,
+                    &mut all_cycles,
+                );
+            }
         }
-    }
+        all_cycles
+    } else {
+        strongly_connected_components(&graph)
+    };
 
     // Convert cycles to response format
     let mut cycles: Vec<CycleInfo> = Vec::new();
@@ -2089,40 +2356,256 @@ async fn api_compare_snapshots(
     }
 }
 
+// =============================================================================
+// EXPORT HANDLERS
+// =============================================================================
+
+/// Buffered bytes sent to the response body at a time
+const EXPORT_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Writer feeding a streamed response body in chunks
+///
+/// Runs on a blocking thread; a closed channel (client gone) fails the write.
+struct BodyWriter {
+    buffer: Vec<u8>,
+    sender: mpsc::Sender<std::io::Result<Bytes>>,
+}
+
+impl BodyWriter {
+    fn new(sender: mpsc::Sender<std::io::Result<Bytes>>) -> Self {
+        Self {
+            buffer: Vec::with_capacity(EXPORT_CHUNK_BYTES),
+            sender,
+        }
+    }
+
+    fn send(&mut self, chunk: std::io::Result<Bytes>) -> std::io::Result<()> {
+        self.sender.blocking_send(chunk).map_err(|_| {
+            std::io::Error::new(std::io::ErrorKind::BrokenPipe, "export client went away")
+        })
+    }
+}
+
+impl Write for BodyWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= EXPORT_CHUNK_BYTES {
+            self.flush()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let chunk = Bytes::from(std::mem::take(&mut self.buffer));
+        self.send(Ok(chunk))
+    }
+}
+
+/// Write the full symbol/edge dataset in `format`, with paths redacted in
+/// streamer mode
+fn write_export(state: &AppState, format: &str, out: &mut impl Write) -> std::io::Result<()> {
+    let settings = state.settings.read().unwrap().clone();
+    let path_text = |path: &std::path::Path| {
+        let path = path.to_string_lossy();
+        if settings.streamer_mode {
+            redact_path(&path, &settings)
+        } else {
+            path.to_string()
+        }
+    };
+
+    match format {
+        "json" => export_index_with_paths(&state.index, out, path_text)
+            .map(|_| ())
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string())),
+        "csv" => write_export_csv(state, &path_text, out),
+        _ => write_export_dot(state, &path_text, out),
+    }
+}
+
+/// CSV document: one `symbol` row per symbol, then one `edge` row per call
+fn write_export_csv(
+    state: &AppState,
+    path_text: &dyn Fn(&std::path::Path) -> String,
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    let index = &state.index;
+    writeln!(
+        out,
+        "record,id,name,kind,file,start_line,end_line,dead,from,to,line"
+    )?;
+    for symbol in &index.symbols {
+        let file = index.file_path(symbol.file_id).map(|p| path_text(p));
+        writeln!(
+            out,
+            "symbol,{},{},{},{},{},{},{},,,",
+            symbol.id,
+            CsvFormatter::escape_csv(index.symbol_name(symbol).unwrap_or("")),
+            symbol_kind_str(symbol.symbol_kind()),
+            CsvFormatter::escape_csv(file.as_deref().unwrap_or("")),
+            symbol.start_line,
+            symbol.end_line,
+            state.dead_symbols.contains(&symbol.id)
+        )?;
+    }
+    for edge in &index.edges {
+        writeln!(
+            out,
+            "edge,,,,,,,,{},{},{}",
+            edge.from_symbol, edge.to_symbol, edge.line
+        )?;
+    }
+    Ok(())
+}
+
+/// DOT document with a node per symbol, clustered by directory, and an
+/// edge per call
+fn write_export_dot(
+    state: &AppState,
+    path_text: &dyn Fn(&std::path::Path) -> String,
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    let index = &state.index;
+    writeln!(
+        out,
+        "digraph \"{}\" {{",
+        DotFormatter::escape_dot(&state.project_name)
+    )?;
+    writeln!(out, "  rankdir=LR;")?;
+    writeln!(out, "  node [shape=box];\n")?;
+
+    let nodes: Vec<(String, String)> = index
+        .symbols
+        .iter()
+        .map(|symbol| {
+            let file = index
+                .file_path(symbol.file_id)
+                .map(|p| path_text(p))
+                .unwrap_or_default();
+            let node = format!(
+                "s{} [label=\"{}\\n{}:{}\"{}];",
+                symbol.id,
+                DotFormatter::escape_dot(index.symbol_name(symbol).unwrap_or("")),
+                DotFormatter::escape_dot(&file),
+                symbol.start_line,
+                if state.dead_symbols.contains(&symbol.id) {
+                    ", style=dashed"
+                } else {
+                    ""
+                }
+            );
+            (file, node)
+        })
+        .collect();
+    out.write_all(DotFormatter::cluster_by_directory(&nodes).as_bytes())?;
+
+    if !index.edges.is_empty() {
+        writeln!(out)?;
+    }
+    for edge in &index.edges {
+        writeln!(out, "  s{} -> s{};", edge.from_symbol, edge.to_symbol)?;
+    }
+    writeln!(out, "}}")
+}
+
+/// Bulk export of the full symbol/edge dataset as a downloadable file
+///
+/// The document is written on a blocking thread and streamed in chunks, so
+/// large indexes are never held in memory as a whole.
+async fn api_export(State(state): State<AppState>, Query(query): Query<ExportQuery>) -> Response {
+    let format = query.format.unwrap_or_else(|| "json".to_string());
+    let (content_type, extension) = match format.as_str() {
+        "json" => ("application/x-ndjson", "jsonl"),
+        "csv" => ("text/csv", "csv"),
+        "dot" => ("text/vnd.graphviz", "dot"),
+        other => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({
+                    "error": format!("unknown export format '{}' (expected json, csv or dot)", other)
+                })),
+            )
+                .into_response();
+        }
+    };
+    let disposition = format!("attachment; filename=\"greppy-export.{}\"", extension);
+
+    let (sender, receiver) = mpsc::channel(4);
+    tokio::task::spawn_blocking(move || {
+        let mut writer = BodyWriter::new(sender);
+        if let Err(e) = write_export(&state, &format, &mut writer).and_then(|()| writer.flush()) {
+            warn!("Export failed: {}", e);
+            // End the body with an error rather than as a silently short file
+            let _ = writer.send(Err(e));
+        }
+    });
+
+    (
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        Body::from_stream(ReceiverStream::new(receiver)),
+    )
+        .into_response()
+}
+
 // =============================================================================
 // SERVER
 // =============================================================================
 
-pub async fn run(project_path: PathBuf, port: u16, open_browser: bool) -> Result<()> {
-    let project = Project::detect(&project_path)?;
+pub async fn run(project: Project, port: u16, open_browser: bool) -> Result<()> {
     let project_name = project
         .root
         .file_name()
         .map(|s| s.to_string_lossy().to_string())
         .unwrap_or_else(|| "unknown".to_string());
 
+    let color = should_color(std::io::stderr().is_terminal());
+    let web_config = Config::load()?.web;
+    let auth_token = web_config.auth_token;
+
     if !trace_index_exists(&project.root) {
-        eprintln!("\x1b[31m>\x1b[0m Trace index not found. Run 'greppy index' first.");
-        return Err(crate::core::error::Error::IndexError {
-            message: "Index not found".to_string(),
+        eprintln!(
+            "{} Trace index not found. Run 'greppy index' first.",
+            paint(">", "31", color)
+        );
+        return Err(crate::core::error::Error::IndexNotFound {
+            path: project.root.clone(),
         });
     }
 
-    eprintln!("\x1b[36m>\x1b[0m Loading index...");
+    eprintln!("{} Loading index...", paint(">", "36", color));
     let index_path = trace_index_path(&project.root);
     let index = load_index(&index_path)?;
 
     // Pre-compute dead symbols
-    let dead_symbols: HashSet<u32> = find_dead_symbols(&index).iter().map(|s| s.id).collect();
+    let dead_symbols = dead_symbol_ids(&index);
 
     let stats = index.stats();
     eprintln!(
-        "\x1b[36m>\x1b[0m Loaded {} files, {} symbols ({} dead)",
+        "{} Loaded {} files, {} symbols ({} dead)",
+        paint(">", "36", color),
         stats.files,
         stats.symbols,
         dead_symbols.len()
     );
 
+    let context_cache = web_config.warm_context.then(|| {
+        let mut cache = FileCache::new(&project.root);
+        let warmed = warm_context_cache(&index, &mut cache);
+        eprintln!(
+            "{} Warmed context for {} files ({} KB)",
+            paint(">", "36", color),
+            warmed,
+            cache.stats().bytes_cached / 1024
+        );
+        Arc::new(Mutex::new(cache))
+    });
+
     // Create settings state (shared between AppState and settings routes)
     let settings_state = SettingsState::new();
 
@@ -2132,7 +2615,9 @@ pub async fn run(project_path: PathBuf, port: u16, open_browser: bool) -> Result
         index: Arc::new(index),
         dead_symbols: Arc::new(dead_symbols),
         settings: settings_state.settings.clone(),
+        context_cache,
     };
+    let live_state = LiveState::new(state, index_path);
 
     // Create project selector state
     let projects_state = ProjectsState {
@@ -2148,6 +2633,23 @@ pub async fn run(project_path: PathBuf, port: u16, open_browser: bool) -> Result
         start_daemon_event_forwarder(events_state_clone).await;
     });
 
+    // Pick up rebuilt indexes without a restart; clients refresh on the event
+    let events_state_clone = events_state.clone();
+    tokio::spawn(watch_index(
+        live_state.clone(),
+        INDEX_POLL_INTERVAL,
+        move |state, elapsed| {
+            let stats = state.index.stats();
+            events_state_clone.update_indexed_at();
+            events_state_clone.broadcast(SseEvent::ReindexComplete {
+                files: stats.files,
+                symbols: stats.symbols,
+                dead: state.dead_symbols.len(),
+                duration_ms: elapsed.as_secs_f64() * 1000.0,
+            });
+        },
+    ));
+
     // Build sub-routers with their respective states
     let data_routes = Router::new()
         .route("/stats", get(api_stats))
@@ -2170,7 +2672,9 @@ pub async fn run(project_path: PathBuf, port: u16, open_browser: bool) -> Result
         )
         .route("/snapshots/compare", get(api_compare_snapshots))
         .route("/snapshots/:id", get(api_get_snapshot))
-        .with_state(state);
+        // Bulk export endpoint
+        .route("/export", get(api_export))
+        .with_state(live_state);
 
     let projects_routes = Router::new()
         .route("/", get(api_projects))
@@ -2219,19 +2723,30 @@ pub async fn run(project_path: PathBuf, port: u16, open_browser: bool) -> Result
         .nest("/api/projects", projects_routes)
         .nest("/api/settings", settings_routes)
         .nest("/api/events", events_routes);
+    let app = auth::protect(app, auth_token.as_deref());
 
     let addr = SocketAddr::from(([127, 0, 0, 1], port));
 
     eprintln!();
     eprintln!(
-        "\x1b[36m>\x1b[0m greppy web running at \x1b[36mhttp://{}\x1b[0m",
-        addr
+        "{} greppy web running at {}",
+        paint(">", "36", color),
+        paint(&format!("http://{}", addr), "36", color)
     );
-    eprintln!("\x1b[90m  Press Ctrl+C to stop\x1b[0m");
+    if auth_token.as_deref().is_some_and(|t| !t.is_empty()) {
+        eprintln!(
+            "{}",
+            paint(
+                "  Token required: open the UI once with ?token=<auth_token>",
+                "90",
+                color
+            )
+        );
+    }
+    eprintln!("{}", paint("  Press Ctrl+C to stop", "90", color));
 
     if open_browser {
-        let url = format!("http://{}", addr);
-        let _ = open::that(&url);
+        let _ = open::that(browser_url(addr, auth_token.as_deref()));
     }
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
@@ -2239,3 +2754,337 @@ pub async fn run(project_path: PathBuf, port: u16, open_browser: bool) -> Result
 
     Ok(())
 }
+
+/// URL `--open` launches, carrying the token so the first visit authenticates
+fn browser_url(addr: SocketAddr, auth_token: Option<&str>) -> String {
+    match auth_token {
+        Some(token) if !token.is_empty() => {
+            format!("http://{}/?token={}", addr, urlencoding::encode(token))
+        }
+        _ => format!("http://{}", addr),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace::{RefKind, Reference, Symbol, SymbolFlags, Token, TokenKind};
+    use tempfile::TempDir;
+
+    fn refs_state(root: &std::path::Path) -> AppState {
+        let mut index = SemanticIndex::new();
+        let file = index.add_file("src/app.rs".into());
+        let name = index.strings.intern("load");
+        index.add_symbol(
+            Symbol::new(
+                0,
+                name,
+                file,
+                SymbolKind::Function,
+                SymbolFlags::empty(),
+                1,
+                3,
+            ),
+            "load",
+        );
+        index.add_token(Token::new(0, name, file, 6, 4, TokenKind::Call, 0), "load");
+        index.add_reference(Reference::new(0, 0, RefKind::Call));
+
+        AppState {
+            project_name: "demo".to_string(),
+            project_path: root.to_path_buf(),
+            index: Arc::new(index),
+            dead_symbols: Arc::new(HashSet::new()),
+            settings: Arc::new(RwLock::new(WebSettings::default())),
+            context_cache: None,
+        }
+    }
+
+    #[test]
+    fn test_symbol_refs_context_param() {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir(temp.path().join("src")).unwrap();
+        let source = (1..=10)
+            .map(|i| format!("line {}", i))
+            .collect::<Vec<_>>()
+            .join("\n");
+        std::fs::write(temp.path().join("src/app.rs"), source).unwrap();
+        let state = refs_state(temp.path());
+
+        let refs = collect_symbol_refs(&state, 0, Some(2));
+        let context = &refs[0].context;
+        assert_eq!(context.lines().count(), 5);
+        assert!(context.contains("line 4") && context.contains("line 8"));
+
+        // Without the param the single-line summary is unchanged
+        let refs = collect_symbol_refs(&state, 0, None);
+        assert_eq!(refs[0].context, "app.rs:6 - load");
+    }
+
+    #[test]
+    fn test_warmed_context_is_served_from_memory() {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir(temp.path().join("src")).unwrap();
+        let source = (1..=10)
+            .map(|i| format!("line {}", i))
+            .collect::<Vec<_>>()
+            .join("\n");
+        std::fs::write(temp.path().join("src/app.rs"), source).unwrap();
+        let mut state = refs_state(temp.path());
+
+        let mut cache = FileCache::new(temp.path());
+        assert_eq!(warm_context_cache(&state.index, &mut cache), 1);
+        assert_eq!(cache.stats().disk_reads, 1);
+        let shared = Arc::new(Mutex::new(cache));
+        state.context_cache = Some(shared.clone());
+
+        let refs = collect_symbol_refs(&state, 0, Some(1));
+        assert!(refs[0].context.contains("line 6"));
+        assert_eq!(shared.lock().unwrap().stats().disk_reads, 1);
+
+        // Files beyond the cache limit are left for lazy reads
+        let mut small = FileCache::with_capacity(temp.path(), 8);
+        assert_eq!(warm_context_cache(&state.index, &mut small), 0);
+        assert_eq!(small.stats().disk_reads, 0);
+    }
+
+    #[tokio::test]
+    async fn test_rebuilt_index_on_disk_updates_stats() {
+        use crate::trace::save_index;
+
+        let temp = TempDir::new().unwrap();
+        let index_path = temp.path().join("trace.idx");
+        let index_of = |names: &[&str]| {
+            let mut index = SemanticIndex::new();
+            let file = index.add_file("src/app.rs".into());
+            for (id, name) in names.iter().enumerate() {
+                let offset = index.strings.intern(name);
+                let line = id as u32 * 5 + 1;
+                index.add_symbol(
+                    Symbol::new(
+                        id as u32,
+                        offset,
+                        file,
+                        SymbolKind::Function,
+                        SymbolFlags::empty(),
+                        line,
+                        line + 3,
+                    ),
+                    name,
+                );
+            }
+            index
+        };
+
+        save_index(&index_of(&["load"]), &index_path).unwrap();
+        let state = AppState {
+            index: Arc::new(load_index(&index_path).unwrap()),
+            ..refs_state(temp.path())
+        };
+        let live = LiveState::new(state, index_path.clone());
+        let stats = |live: &LiveState| build_stats(&AppState::from_ref(live));
+        assert_eq!(stats(&live).symbols, 1);
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(watch_index(
+            live.clone(),
+            Duration::from_millis(20),
+            move |state, _| {
+                let _ = tx.send(state.index.stats().symbols);
+            },
+        ));
+        save_index(&index_of(&["load", "save", "parse"]), &index_path).unwrap();
+
+        let reloaded = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .expect("index reload");
+        assert_eq!(reloaded, Some(3));
+        let Json(stats) = api_stats(State(AppState::from_ref(&live))).await;
+        assert_eq!(stats.symbols, 3);
+        assert_eq!(stats.dead, 3);
+    }
+
+    #[test]
+    fn test_stats_reference_kinds_sum_to_total() {
+        use crate::trace::{RefKind, Reference, Token, TokenKind};
+
+        let mut index = SemanticIndex::new();
+        let file = index.add_file("src/app.rs".into());
+        let name = index.strings.intern("load");
+        index.add_symbol(
+            Symbol::new(
+                0,
+                name,
+                file,
+                SymbolKind::Function,
+                SymbolFlags::empty(),
+                1,
+                3,
+            ),
+            "load",
+        );
+        let kinds = [RefKind::Call, RefKind::Call, RefKind::Read, RefKind::Write];
+        for (i, kind) in kinds.into_iter().enumerate() {
+            let id = i as u32;
+            index.add_token(
+                Token::new(id, name, file, 5 + id, 4, TokenKind::Identifier, 0),
+                "load",
+            );
+            index.add_reference(Reference::new(id, 0, kind));
+        }
+        let state = AppState {
+            index: Arc::new(index),
+            ..refs_state(std::path::Path::new("."))
+        };
+
+        let stats = build_stats(&state);
+        assert_eq!(stats.references, 4);
+        assert_eq!(stats.references_by_kind.values().sum::<usize>(), 4);
+        assert_eq!(stats.references_by_kind["call"], 2);
+        assert_eq!(stats.references_by_kind["read"], 1);
+        assert_eq!(stats.references_by_kind["write"], 1);
+    }
+
+    #[test]
+    fn test_graph_limit_truncates_nodes_and_edges() {
+        use crate::trace::Edge;
+
+        // 30 files in a chain: file i calls into file i + 1
+        let mut index = SemanticIndex::new();
+        for i in 0..30u32 {
+            let file = index.add_file(format!("src/f{:02}.rs", i).into());
+            let name = format!("f{}", i);
+            let offset = index.strings.intern(&name);
+            index.add_symbol(
+                Symbol::new(
+                    i,
+                    offset,
+                    file,
+                    SymbolKind::Function,
+                    SymbolFlags::empty(),
+                    1,
+                    3,
+                ),
+                &name,
+            );
+            if i > 0 {
+                index.add_edge(Edge::new(i - 1, i, 2));
+            }
+        }
+        let state = AppState {
+            index: Arc::new(index),
+            ..refs_state(std::path::Path::new("."))
+        };
+        let query = |limit: Option<usize>| GraphQuery {
+            symbol_type: None,
+            state: None,
+            hierarchical: None,
+            path: None,
+            limit,
+        };
+
+        let graph = build_file_graph(&state, &query(Some(10)));
+        assert!(graph.nodes.len() <= 10);
+        assert!(graph.truncated);
+        assert_eq!(graph.total_nodes, 30);
+        let ids: HashSet<_> = graph.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert!(graph
+            .edges
+            .iter()
+            .all(|e| ids.contains(e.source.as_str()) && ids.contains(e.target.as_str())));
+
+        let graph = build_file_graph(&state, &query(None));
+        assert_eq!(graph.nodes.len(), 30);
+        assert!(!graph.truncated);
+        assert_eq!(graph.edges.len(), 29);
+    }
+
+    #[test]
+    fn test_browser_url_carries_auth_token() {
+        let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
+        assert_eq!(browser_url(addr, None), "http://127.0.0.1:3000");
+        assert_eq!(browser_url(addr, Some("")), "http://127.0.0.1:3000");
+        assert_eq!(
+            browser_url(addr, Some("a b&c")),
+            "http://127.0.0.1:3000/?token=a%20b%26c"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_lists_fixture_symbols() {
+        use crate::trace::Edge;
+
+        let mut index = SemanticIndex::new();
+        for (id, (path, name)) in [("src/app.rs", "load"), ("src/secret.rs", "save")]
+            .into_iter()
+            .enumerate()
+        {
+            let id = id as u32;
+            let file = index.add_file(path.into());
+            let offset = index.strings.intern(name);
+            index.add_symbol(
+                Symbol::new(
+                    id,
+                    offset,
+                    file,
+                    SymbolKind::Function,
+                    SymbolFlags::empty(),
+                    1 + id,
+                    3 + id,
+                ),
+                name,
+            );
+        }
+        index.add_edge(Edge::new(0, 1, 2));
+        let state = AppState {
+            index: Arc::new(index),
+            ..refs_state(std::path::Path::new("."))
+        };
+        {
+            let mut settings = state.settings.write().unwrap();
+            settings.streamer_mode = true;
+            settings.hidden_patterns = vec!["*secret*".to_string()];
+        }
+
+        let query = ExportQuery {
+            format: Some("dot".to_string()),
+        };
+        let response = api_export(State(state.clone()), Query(query)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/vnd.graphviz"
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let dot = String::from_utf8(body.to_vec()).unwrap();
+
+        assert!(dot.starts_with("digraph \"demo\" {"));
+        assert!(dot.trim_end().ends_with('}'));
+        assert!(dot.contains("s0 [label=\"load\\nsrc/app.rs:1\"];"));
+        assert!(dot.contains("s1 [label=\"save\\nsrc/[HIDDEN].rs:2\"];"));
+        assert!(dot.contains("s0 -> s1;"));
+        assert!(!dot.contains("secret"));
+
+        // JSON is the JSON Lines export document, with the same redaction
+        let query = ExportQuery { format: None };
+        let response = api_export(State(state), Query(query)).await;
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let records: Vec<crate::trace::ExportRecord> = String::from_utf8(body.to_vec())
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let paths: Vec<&str> = records
+            .iter()
+            .filter_map(|r| match r {
+                crate::trace::ExportRecord::File { path, .. } => Some(path.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(paths, ["src/app.rs", "src/[HIDDEN].rs"]);
+    }
+}
@@ -2,7 +2,8 @@
  * Export Component
  *
  * Data export functionality for symbols, files, cycles, and graphs.
- * Supports JSON, CSV, and PNG screenshot exports.
+ * Supports JSON, CSV, DOT, and PNG screenshot exports. Full-index exports
+ * are generated by the server (`/api/export`).
  *
  * @module components/export
  */
@@ -20,7 +21,9 @@ const EXPORT_FORMATS = [
   { id: 'files-csv', label: 'Files (CSV)', handler: exportFilesCSV },
   { id: 'cycles-json', label: 'Cycles (JSON)', handler: exportCyclesJSON },
   { id: 'graph-json', label: 'Graph (JSON)', handler: exportGraphJSON },
-  { id: 'full-json', label: 'Full Index (JSON)', handler: exportFullIndexJSON },
+  { id: 'full-json', label: 'Full Index (JSON)', handler: () => exportFullIndex('json') },
+  { id: 'full-csv', label: 'Full Index (CSV)', handler: () => exportFullIndex('csv') },
+  { id: 'full-dot', label: 'Full Index (DOT)', handler: () => exportFullIndex('dot') },
   { id: 'screenshot', label: 'Current View (PNG)', handler: exportScreenshot }
 ];
 
@@ -270,26 +273,18 @@ async function exportGraphJSON() {
 }
 
 /**
- * Export full index as JSON.
+ * Export the full symbol/edge dataset, generated by the server.
+ * @param {string} format - json, csv or dot
  */
-async function exportFullIndexJSON() {
+async function exportFullIndex(format) {
   setExportLoading(true);
   try {
-    const [stats, list, tree, graph] = await Promise.all([
-      fetchStats(),
-      fetchList({}),
-      fetchTree(),
-      fetchGraph({})
-    ]);
-    
-    exportJSON({
-      exported_at: new Date().toISOString(),
-      project: getProjectName(),
-      stats: stats,
-      symbols: list.items ?? [],
-      files: tree,
-      graph: graph
-    }, 'full-index');
+    const res = await fetch(`/api/export?format=${format}`);
+    if (!res.ok) throw new Error(`HTTP ${res.status}`);
+    const blob = await res.blob();
+    // The JSON export is the JSON Lines index document
+    const extension = format === 'json' ? 'jsonl' : format;
+    downloadBlob(blob, `full-index_${getProjectName()}_${getTimestamp()}.${extension}`);
   } catch (err) {
     console.error('Export failed:', err);
     alert('Failed to export full index');